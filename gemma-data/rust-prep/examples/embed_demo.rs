@@ -1,4 +1,4 @@
-use autoreply::embeddings::{load_embeddings, embed_text};
+use autoreply::embeddings::{load_embeddings, embed_text, DEFAULT_BITS};
 use autoreply::sentencepiece::tokenizer::SentencePieceProcessor;
 use autoreply::sentencepiece::loader::SentencePieceModel;
 use std::time::Instant;
@@ -6,19 +6,18 @@ use std::time::Instant;
 fn main() -> anyhow::Result<()> {
     let start = Instant::now();
     
+    let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+
     // Load embeddings
     println!("Loading embeddings...");
-    let table = load_embeddings()?;
+    let table = load_embeddings(data_dir, DEFAULT_BITS)?;
     println!("✓ Loaded {} tokens × {} dims ({:.1} MB)",
              table.vocab_size, table.embed_dim,
              (table.embeddings.len() as f64) / 1_000_000.0);
     println!("  Load time: {:?}", start.elapsed());
     
     // Load tokenizer
-    let tokenizer_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .unwrap()
-        .join("tokenizer.model");
+    let tokenizer_path = data_dir.join("tokenizer.model");
     
     let model = SentencePieceModel::load_from_file(&tokenizer_path)?;
     let tokenizer = SentencePieceProcessor::new(model);
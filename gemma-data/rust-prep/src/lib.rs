@@ -0,0 +1,284 @@
+//! Embedding preparation: extract a model's token embedding table from safetensors,
+//! reduce it with PCA and quantize it to the EMB8 format read by `autoreply::embeddings`.
+
+use anyhow::{bail, Context, Result};
+use half::bf16;
+use ndarray::Array2;
+use safetensors::{Dtype, SafeTensors};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub mod quantize;
+
+pub use quantize::QuantBits;
+
+/// Tensor names tried, in order, when no embedding tensor is named
+pub const EMBEDDING_TENSOR_CANDIDATES: &[&str] = &[
+    "model.embed_tokens.weight",
+    "transformer.wte.weight",
+    "wte.weight",
+    "embed_tokens.weight",
+    "model.decoder.embed_tokens.weight",
+];
+
+/// How [`prepare_embeddings_with`] reduces and stores the embeddings
+#[derive(Debug, Clone)]
+pub struct PrepareOptions {
+    /// Dimensions kept after PCA
+    pub dims: usize,
+    /// Embedding tensor name; `None` tries [`EMBEDDING_TENSOR_CANDIDATES`]
+    pub tensor: Option<String>,
+    /// Bits stored per dimension
+    pub bits: QuantBits,
+    /// Threads for PCA; 0 uses every core, 1 runs serially
+    pub threads: usize,
+}
+
+impl Default for PrepareOptions {
+    fn default() -> Self {
+        Self {
+            dims: 64,
+            tensor: None,
+            bits: QuantBits::default(),
+            threads: 0,
+        }
+    }
+}
+
+/// Files written by [`prepare_embeddings`]
+#[derive(Debug, Clone)]
+pub struct PreparedEmbeddings {
+    /// Raw float32 embeddings (`embeddings.npy`)
+    pub npy_path: PathBuf,
+    /// PCA-reduced, quantized embeddings (`embeddings_{dims}d_q{bits}.bin`)
+    pub bin_path: PathBuf,
+}
+
+/// Convert a 2-D F32, BF16 or F16 tensor to f32; `None` for other shapes, types or sizes
+pub fn tensor_to_array2_f32(bytes: &[u8], shape: &[usize], dtype: Dtype) -> Option<Array2<f32>> {
+    if shape.len() != 2 {
+        return None;
+    }
+
+    let rows = shape[0];
+    let cols = shape[1];
+    let total = rows * cols;
+
+    let v: Vec<f32> = match dtype {
+        Dtype::F32 => {
+            if bytes.len() != total * 4 {
+                return None;
+            }
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        }
+        Dtype::BF16 => {
+            if bytes.len() != total * 2 {
+                return None;
+            }
+            bytes
+                .chunks_exact(2)
+                .map(|b| bf16::from_bits(u16::from_le_bytes([b[0], b[1]])).to_f32())
+                .collect()
+        }
+        Dtype::F16 => {
+            if bytes.len() != total * 2 {
+                return None;
+            }
+            bytes
+                .chunks_exact(2)
+                .map(|b| half::f16::from_bits(u16::from_le_bytes([b[0], b[1]])).to_f32())
+                .collect()
+        }
+        _ => return None,
+    };
+
+    Array2::from_shape_vec((rows, cols), v).ok()
+}
+
+/// The embedding tensor's name: `tensor` if given (it must exist), else the first
+/// of [`EMBEDDING_TENSOR_CANDIDATES`] present in the file
+pub fn find_embedding_tensor(st: &SafeTensors, tensor: Option<&str>) -> Result<String> {
+    let names = st.names();
+    if let Some(tensor) = tensor {
+        if names.iter().any(|n| *n == tensor) {
+            return Ok(tensor.to_string());
+        }
+        bail!("tensor '{}' not found; available: {}", tensor, sorted_names(&names));
+    }
+
+    EMBEDDING_TENSOR_CANDIDATES
+        .iter()
+        .find(|cand| names.iter().any(|n| n == *cand))
+        .map(|cand| cand.to_string())
+        .with_context(|| {
+            format!(
+                "embedding tensor not found among common keys; name it explicitly. Available: {}",
+                sorted_names(&names)
+            )
+        })
+}
+
+fn sorted_names(names: &[&String]) -> String {
+    let mut names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    names.sort_unstable();
+    names.join(", ")
+}
+
+/// Extract the embedding table from `model_path`, auto-detecting its tensor, and write
+/// `embeddings.npy` plus the 8-bit `embeddings_{dims}d_q8.bin` into `out_dir`
+pub fn prepare_embeddings(model_path: &Path, out_dir: &Path, dims: usize) -> Result<()> {
+    let options = PrepareOptions {
+        dims,
+        ..PrepareOptions::default()
+    };
+    prepare_embeddings_with(model_path, out_dir, &options, &|_| {}).map(|_| ())
+}
+
+/// [`prepare_embeddings`] with an explicit tensor name and quantization width, reporting
+/// each step to `progress` as a line of text
+pub fn prepare_embeddings_with(
+    model_path: &Path,
+    out_dir: &Path,
+    options: &PrepareOptions,
+    progress: &dyn Fn(&str),
+) -> Result<PreparedEmbeddings> {
+    let dims = options.dims;
+    progress(&format!("Reading safetensors from: {}", model_path.display()));
+
+    let data = fs::read(model_path)
+        .with_context(|| format!("failed to read safetensors file {:?}", model_path))?;
+    let st = SafeTensors::deserialize(&data).with_context(|| "failed to parse safetensors")?;
+
+    let name = find_embedding_tensor(&st, options.tensor.as_deref())?;
+    let view = st.tensor(&name).with_context(|| format!("failed to get tensor {}", name))?;
+    let (shape, dtype) = (view.shape(), view.dtype());
+    progress(&format!("Found tensor '{}' shape={:?} dtype={:?}", name, shape, dtype));
+
+    let Some(arr) = tensor_to_array2_f32(view.data(), shape, dtype) else {
+        bail!("tensor {} not a 2-D float tensor or unexpected size", name);
+    };
+    if dims == 0 || dims > arr.ncols() {
+        bail!("dims must be between 1 and the embedding width {}, got {}", arr.ncols(), dims);
+    }
+
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    let prepared = PreparedEmbeddings {
+        npy_path: out_dir.join("embeddings.npy"),
+        bin_path: out_dir.join(format!("embeddings_{}d_q{}.bin", dims, options.bits.bits())),
+    };
+
+    // Save raw f32 version
+    ndarray_npy::write_npy(&prepared.npy_path, &arr)
+        .with_context(|| format!("failed to write {}", prepared.npy_path.display()))?;
+    progress(&format!("Wrote raw embeddings {:?} to {}", arr.dim(), prepared.npy_path.display()));
+
+    // PCA + quantization
+    progress(&format!("Computing randomized PCA: {} -> {} dimensions", arr.ncols(), dims));
+    let (transformed, mean) = quantize::pca_transform(arr.view(), dims, options.threads)?;
+    progress(&format!("Quantizing to {}-bit", options.bits.bits()));
+    let quantized = quantize::quantize(transformed.view(), options.bits);
+    quantize::write_quantized_embeddings(&prepared.bin_path, &quantized, &mean)?;
+    let size = fs::metadata(&prepared.bin_path).map(|m| m.len()).unwrap_or(0);
+    progress(&format!(
+        "Wrote quantized embeddings ({} bytes) to {}",
+        size,
+        prepared.bin_path.display()
+    ));
+
+    Ok(prepared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use safetensors::tensor::TensorView;
+
+    const VOCAB: usize = 8;
+    const WIDTH: usize = 16;
+
+    /// A safetensors file holding one VOCAB × WIDTH f32 tensor called `name`
+    fn write_model(dir: &Path, name: &str) -> PathBuf {
+        let bytes: Vec<u8> = (0..VOCAB * WIDTH)
+            .flat_map(|i| ((i as f32 * 0.37).sin()).to_le_bytes())
+            .collect();
+        let view = TensorView::new(Dtype::F32, vec![VOCAB, WIDTH], &bytes).unwrap();
+        let path = dir.join("model.safetensors");
+        fs::write(&path, safetensors::serialize([(name, view)], &None).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_prepare_embeddings_writes_emb8() {
+        let temp = tempfile::tempdir().unwrap();
+        let model = write_model(temp.path(), "model.embed_tokens.weight");
+        let out = temp.path().join("out");
+
+        prepare_embeddings(&model, &out, 4).unwrap();
+
+        let bin = fs::read(out.join("embeddings_4d_q8.bin")).unwrap();
+        assert_eq!(&bin[..4], b"EMB8");
+        let header: Vec<u32> = bin[4..20]
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(header, vec![1, VOCAB as u32, 4, WIDTH as u32]);
+        assert_eq!(bin.len(), 32 + WIDTH * 4 + VOCAB * (4 + 4));
+
+        let raw: Array2<f32> = ndarray_npy::read_npy(out.join("embeddings.npy")).unwrap();
+        assert_eq!(raw.dim(), (VOCAB, WIDTH));
+    }
+
+    #[test]
+    fn test_prepare_embeddings_from_named_tensor() {
+        let temp = tempfile::tempdir().unwrap();
+        let model = write_model(temp.path(), "encoder.token_table");
+        let named = |dims, tensor: &str| PrepareOptions {
+            dims,
+            tensor: Some(tensor.to_string()),
+            ..PrepareOptions::default()
+        };
+
+        let err = prepare_embeddings(&model, temp.path(), 4).unwrap_err();
+        assert!(err.to_string().contains("encoder.token_table"), "{}", err);
+
+        let quiet = |_: &str| {};
+        let err = prepare_embeddings_with(&model, temp.path(), &named(4, "missing"), &quiet)
+            .unwrap_err();
+        assert!(err.to_string().contains("'missing' not found"), "{}", err);
+
+        let options = PrepareOptions {
+            bits: QuantBits::Four,
+            ..named(2, "encoder.token_table")
+        };
+        let lines = std::cell::RefCell::new(Vec::new());
+        let record = |line: &str| lines.borrow_mut().push(line.to_string());
+        let prepared = prepare_embeddings_with(&model, temp.path(), &options, &record).unwrap();
+        assert!(prepared.bin_path.ends_with("embeddings_2d_q4.bin"));
+        assert!(prepared.bin_path.exists());
+        let lines = lines.into_inner();
+        assert!(lines[1].starts_with("Found tensor 'encoder.token_table'"), "{:?}", lines);
+        assert!(lines.iter().any(|l| l == "Quantizing to 4-bit"), "{:?}", lines);
+        assert!(lines.last().unwrap().ends_with("embeddings_2d_q4.bin"), "{:?}", lines);
+
+        let options = named(32, "encoder.token_table");
+        let err = prepare_embeddings_with(&model, temp.path(), &options, &quiet).unwrap_err();
+        assert!(err.to_string().contains("dims"), "{}", err);
+    }
+
+    #[test]
+    fn test_tensor_to_array2_half_precision() {
+        let bytes: Vec<u8> = [1.5f32, -2.0]
+            .iter()
+            .flat_map(|&x| bf16::from_f32(x).to_bits().to_le_bytes())
+            .collect();
+        let arr = tensor_to_array2_f32(&bytes, &[1, 2], Dtype::BF16).unwrap();
+        assert_eq!(arr.as_slice().unwrap(), &[1.5, -2.0]);
+
+        assert!(tensor_to_array2_f32(&bytes, &[2], Dtype::BF16).is_none());
+        assert!(tensor_to_array2_f32(&bytes, &[1, 2], Dtype::F32).is_none());
+    }
+}
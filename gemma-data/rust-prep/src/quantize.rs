@@ -1,34 +1,98 @@
 use anyhow::Result;
-use ndarray::{Array1, Array2, ArrayView2, Axis};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::str::FromStr;
 use byteorder::{LittleEndian, WriteBytesExt};
 use rand::Rng;
+use rayon::prelude::*;
 
-/// Fast randomized PCA using random projections + power iteration
-pub fn pca_transform(data: ArrayView2<f32>, target_dim: usize) -> Result<(Array2<f32>, Array1<f32>)> {
-    println!("Computing randomized PCA: {} -> {} dimensions...", data.ncols(), target_dim);
-    
+/// Rows handled per parallel task, so each one amortizes its scheduling
+const MIN_CHUNK_ROWS: usize = 256;
+
+/// Fast randomized PCA using random projections + power iteration.
+///
+/// `threads` sets the parallelism of the covariance, centering and projection: 1 runs
+/// serially, 0 uses every core.
+pub fn pca_transform(
+    data: ArrayView2<f32>,
+    target_dim: usize,
+    threads: usize,
+) -> Result<(Array2<f32>, Array1<f32>)> {
+    let mut rng = rand::thread_rng();
+    let random_matrix = random_projection_matrix(data.ncols(), target_dim, &mut rng);
+
+    // One power iteration: steer the random directions towards the top components
+    let mean = data.mean_axis(Axis(0)).unwrap_or_else(|| Array1::zeros(data.ncols()));
+    let workers = if threads == 0 {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        threads
+    };
+    let basis = covariance(data, mean.view(), workers).dot(&random_matrix);
+
+    let result = if threads == 1 {
+        project_serial(data, basis.view())
+    } else {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+        pool.install(|| project_parallel(data, basis.view()))
+    };
+
+    Ok(result)
+}
+
+/// `n_features × n_features` covariance of `data` about `mean`. With more than one worker
+/// the rows are split into chunks on scoped threads and their products summed in order.
+pub fn covariance(data: ArrayView2<f32>, mean: ArrayView1<f32>, workers: usize) -> Array2<f32> {
     let (n_samples, n_features) = data.dim();
-    
+    let scatter = |chunk: ArrayView2<f32>| {
+        let centered = &chunk - &mean.insert_axis(Axis(0));
+        centered.t().dot(&centered)
+    };
+    let sum = if workers <= 1 || n_samples < MIN_CHUNK_ROWS * 2 {
+        scatter(data)
+    } else {
+        let chunk_rows = n_samples.div_ceil(workers).max(MIN_CHUNK_ROWS);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = data
+                .axis_chunks_iter(Axis(0), chunk_rows)
+                .map(|chunk| scope.spawn(move || scatter(chunk)))
+                .collect();
+            let mut sum = Array2::zeros((n_features, n_features));
+            for handle in handles {
+                sum += &handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+            }
+            sum
+        })
+    };
+    sum / n_samples.saturating_sub(1).max(1) as f32
+}
+
+/// `n_features × target_dim` matrix of uniform values in [-0.5, 0.5)
+pub fn random_projection_matrix(
+    n_features: usize,
+    target_dim: usize,
+    rng: &mut impl Rng,
+) -> Array2<f32> {
+    Array2::from_shape_simple_fn((n_features, target_dim), || rng.gen::<f32>() - 0.5)
+}
+
+/// Center `data`, project it onto `random_matrix` and scale each column to unit length;
+/// returns the projection and the mean
+pub fn project_serial(
+    data: ArrayView2<f32>,
+    random_matrix: ArrayView2<f32>,
+) -> (Array2<f32>, Array1<f32>) {
+    let (n_samples, target_dim) = (data.nrows(), random_matrix.ncols());
+
     // Center the data
     let mean = data.mean_axis(Axis(0)).unwrap();
-    let mean_clone = mean.clone();
-    let centered = &data - &mean.insert_axis(Axis(0));
-    
-    // Random projection matrix
-    let mut rng = rand::thread_rng();
-    let mut random_matrix = Array2::<f32>::zeros((n_features, target_dim));
-    for i in 0..n_features {
-        for j in 0..target_dim {
-            random_matrix[[i, j]] = rng.gen::<f32>() - 0.5;
-        }
-    }
-    
+    let centered = &data - &mean.view().insert_axis(Axis(0));
+
     // Project
     let mut projected = centered.dot(&random_matrix);
-    
+
     // Normalize columns
     for j in 0..target_dim {
         let col = projected.column(j);
@@ -39,86 +103,352 @@ pub fn pca_transform(data: ArrayView2<f32>, target_dim: usize) -> Result<(Array2
             }
         }
     }
-    
-    println!("  Randomized projection complete");
-    
-    Ok((projected, mean_clone))
+
+    (projected, mean)
 }
 
-/// Symmetric 8-bit quantization: map [-max_abs, max_abs] to [0, 255]
-pub fn quantize_8bit(data: ArrayView2<f32>) -> (Array2<u8>, Vec<f32>) {
-    println!("Quantizing to 8-bit...");
+/// [`project_serial`] over row chunks on the current rayon pool. Sums are combined per
+/// chunk, so results match the serial path up to floating-point reassociation.
+pub fn project_parallel(
+    data: ArrayView2<f32>,
+    random_matrix: ArrayView2<f32>,
+) -> (Array2<f32>, Array1<f32>) {
+    let (n_samples, n_features) = data.dim();
+    let chunk_rows = n_samples
+        .div_ceil(rayon::current_num_threads() * 4)
+        .max(MIN_CHUNK_ROWS);
+    let chunks: Vec<ArrayView2<f32>> = data.axis_chunks_iter(Axis(0), chunk_rows).collect();
+
+    // Column sums per chunk, then the mean
+    let sum = chunks
+        .par_iter()
+        .map(|chunk| chunk.sum_axis(Axis(0)))
+        .reduce(|| Array1::zeros(n_features), |a, b| a + b);
+    let mean = sum / n_samples.max(1) as f32;
+
+    // Center and project each chunk without materializing the centered matrix
+    let parts: Vec<Array2<f32>> = chunks
+        .par_iter()
+        .map(|chunk| (chunk - &mean.view().insert_axis(Axis(0))).dot(&random_matrix))
+        .collect();
+    let part_views: Vec<ArrayView2<f32>> = parts.iter().map(|part| part.view()).collect();
+    let mut projected = if part_views.is_empty() {
+        Array2::zeros((0, random_matrix.ncols()))
+    } else {
+        ndarray::concatenate(Axis(0), &part_views).expect("chunks share a width")
+    };
+
+    // Normalize columns: squared norms per chunk, then scale the rows in parallel
+    let squares = parts
+        .par_iter()
+        .map(|part| part.mapv(|x| x * x).sum_axis(Axis(0)))
+        .reduce(|| Array1::zeros(random_matrix.ncols()), |a, b| a + b);
+    let scale = squares.mapv(|sq| {
+        let norm = sq.sqrt();
+        if norm > 1e-8 { 1.0 / norm } else { 1.0 }
+    });
+    projected
+        .axis_chunks_iter_mut(Axis(0), chunk_rows)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .for_each(|mut chunk| chunk *= &scale.view().insert_axis(Axis(0)));
+
+    (projected, mean)
+}
+
+/// Bits stored per embedding dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuantBits {
+    /// Two dimensions per byte, for the smallest files
+    Four,
+    /// One byte per dimension; the EMB8 v1 format
+    #[default]
+    Eight,
+    /// Two bytes per dimension, for higher fidelity
+    Sixteen,
+}
+
+impl QuantBits {
+    pub const ALL: [QuantBits; 3] = [QuantBits::Four, QuantBits::Eight, QuantBits::Sixteen];
+
+    /// Width in bits, also the header's format tag
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::Four => 4,
+            Self::Eight => 8,
+            Self::Sixteen => 16,
+        }
+    }
+
+    /// Width for a header format tag; 0 is the untagged v1 (8-bit) header
+    pub fn from_tag(tag: u32) -> Option<Self> {
+        match tag {
+            4 => Some(Self::Four),
+            0 | 8 => Some(Self::Eight),
+            16 => Some(Self::Sixteen),
+            _ => None,
+        }
+    }
+
+    /// Largest stored magnitude; values map symmetrically onto `-max_level..=max_level`
+    pub fn max_level(self) -> f32 {
+        ((1u32 << (self.bits() - 1)) - 1) as f32
+    }
+
+    /// Offset added so stored values are unsigned
+    fn offset(self) -> u32 {
+        1 << (self.bits() - 1)
+    }
+
+    /// Bytes holding one row of `dim` dimensions (4-bit rows round up to whole bytes)
+    pub fn row_bytes(self, dim: usize) -> usize {
+        (dim * self.bits() as usize).div_ceil(8)
+    }
+}
+
+impl FromStr for QuantBits {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.parse::<u32>() {
+            Ok(bits @ (4 | 8 | 16)) => Ok(QuantBits::from_tag(bits).expect("known width")),
+            _ => anyhow::bail!("quantization bits must be 4, 8 or 16, got '{}'", s),
+        }
+    }
+}
+
+/// Quantized embedding rows with one scale factor per row
+#[derive(Debug, Clone)]
+pub struct QuantizedEmbeddings {
+    pub bits: QuantBits,
+    /// Dimensions per row
+    pub embed_dim: usize,
+    /// `rows × bits.row_bytes(embed_dim)` packed little-endian values
+    pub data: Array2<u8>,
+    pub scales: Vec<f32>,
+}
+
+impl QuantizedEmbeddings {
+    /// Reconstruct row `row` as floats
+    pub fn dequantize_row(&self, row: usize) -> Vec<f32> {
+        let bytes = self.data.row(row);
+        let bytes = bytes.as_slice().expect("rows are contiguous");
+        let (scale, offset) = (self.scales[row], self.bits.offset() as f32);
+        (0..self.embed_dim)
+            .map(|j| (read_level(bytes, j, self.bits) as f32 - offset) * scale)
+            .collect()
+    }
+}
+
+/// Stored (unsigned) level of dimension `j` in a packed row
+fn read_level(bytes: &[u8], j: usize, bits: QuantBits) -> u32 {
+    match bits {
+        QuantBits::Four => u32::from(bytes[j / 2] >> ((j % 2) * 4) & 0x0f),
+        QuantBits::Eight => u32::from(bytes[j]),
+        QuantBits::Sixteen => u32::from(u16::from_le_bytes([bytes[2 * j], bytes[2 * j + 1]])),
+    }
+}
+
+/// Store level `q` of dimension `j` in a packed row; 4-bit values fill the low nibble first
+fn write_level(bytes: &mut [u8], j: usize, bits: QuantBits, q: u32) {
+    match bits {
+        QuantBits::Four => bytes[j / 2] |= (q as u8 & 0x0f) << ((j % 2) * 4),
+        QuantBits::Eight => bytes[j] = q as u8,
+        QuantBits::Sixteen => bytes[2 * j..2 * j + 2].copy_from_slice(&(q as u16).to_le_bytes()),
+    }
+}
+
+/// Symmetric quantization: map each row's [-max_abs, max_abs] to `bits` levels
+pub fn quantize(data: ArrayView2<f32>, bits: QuantBits) -> QuantizedEmbeddings {
     let (rows, cols) = data.dim();
-    let mut quantized = Array2::<u8>::zeros((rows, cols));
+    let max_level = bits.max_level();
+    let mut packed = Array2::<u8>::zeros((rows, bits.row_bytes(cols)));
     let mut scales = Vec::with_capacity(rows);
-    
+
     for (i, row) in data.axis_iter(Axis(0)).enumerate() {
         let max_abs = row.iter()
             .map(|&x| x.abs())
             .fold(0.0f32, f32::max);
-        
+
         let scale = if max_abs > 1e-8 {
-            max_abs / 127.0
+            max_abs / max_level
         } else {
             1.0
         };
-        
+
         scales.push(scale);
-        
+
+        let mut out = packed.row_mut(i);
+        let out = out.as_slice_mut().expect("rows are contiguous");
         for (j, &val) in row.iter().enumerate() {
-            let quantized_val = ((val / scale).clamp(-127.0, 127.0) + 128.0) as u8;
-            quantized[(i, j)] = quantized_val;
+            let level = match bits {
+                // Truncating like the original 8-bit writer keeps its output unchanged
+                QuantBits::Eight => (val / scale).clamp(-max_level, max_level) + 128.0,
+                _ => (val / scale).round().clamp(-max_level, max_level) + bits.offset() as f32,
+            };
+            write_level(out, j, bits, level as u32);
         }
     }
-    
-    println!("  Scale range: [{:.6}, {:.6}]", 
-             scales.iter().copied().fold(f32::INFINITY, f32::min),
-             scales.iter().copied().fold(f32::NEG_INFINITY, f32::max));
-    
-    (quantized, scales)
+
+    QuantizedEmbeddings { bits, embed_dim: cols, data: packed, scales }
 }
 
-/// Write binary format: header + scales + quantized data
+/// Symmetric 8-bit quantization: map [-max_abs, max_abs] to [0, 255]
+pub fn quantize_8bit(data: ArrayView2<f32>) -> (Array2<u8>, Vec<f32>) {
+    let quantized = quantize(data, QuantBits::Eight);
+    (quantized.data, quantized.scales)
+}
+
+/// Write binary format: header + scales + quantized data.
+///
+/// 8-bit output is the EMB8 v1 format. Other widths are version 2, which stores the
+/// width in the first reserved header word so loaders know how to unpack the rows.
 pub fn write_quantized_embeddings<P: AsRef<Path>>(
     path: P,
-    quantized: &Array2<u8>,
-    scales: &[f32],
+    quantized: &QuantizedEmbeddings,
     mean: &Array1<f32>,
 ) -> Result<()> {
-    let (vocab_size, embed_dim) = quantized.dim();
+    let (vocab_size, embed_dim) = (quantized.data.nrows(), quantized.embed_dim);
     let file = File::create(path.as_ref())?;
     let mut writer = BufWriter::new(file);
-    
+
     // Header (32 bytes)
     writer.write_all(b"EMB8")?;           // magic (4 bytes)
-    writer.write_u32::<LittleEndian>(1)?; // version
+    let tagged = quantized.bits != QuantBits::Eight;
+    writer.write_u32::<LittleEndian>(if tagged { 2 } else { 1 })?; // version
     writer.write_u32::<LittleEndian>(vocab_size as u32)?;
     writer.write_u32::<LittleEndian>(embed_dim as u32)?;
     writer.write_u32::<LittleEndian>(mean.len() as u32)?; // original dim
-    writer.write_all(&[0u8; 12])?;        // reserved
-    
+    writer.write_u32::<LittleEndian>(if tagged { quantized.bits.bits() } else { 0 })?;
+    writer.write_all(&[0u8; 8])?;         // reserved
+
     // PCA mean vector (original_dim * 4 bytes)
     for &val in mean.iter() {
         writer.write_f32::<LittleEndian>(val)?;
     }
-    
+
     // Scale factors (vocab_size * 4 bytes)
-    for &scale in scales.iter() {
+    for &scale in quantized.scales.iter() {
         writer.write_f32::<LittleEndian>(scale)?;
     }
-    
-    // Quantized embeddings (vocab_size * embed_dim bytes)
-    for row in quantized.axis_iter(Axis(0)) {
+
+    // Quantized embeddings (vocab_size * row_bytes bytes)
+    for row in quantized.data.axis_iter(Axis(0)) {
         for &val in row.iter() {
             writer.write_u8(val)?;
         }
     }
-    
+
     writer.flush()?;
-    
-    println!("✓ Wrote quantized embeddings to {}", path.as_ref().display());
-    println!("  Format: EMB8 v1");
-    println!("  Size: {} bytes", 32 + mean.len() * 4 + vocab_size * (4 + embed_dim));
-    
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::s;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn sample() -> Array2<f32> {
+        Array2::from_shape_fn((5, 7), |(i, j)| ((i * 7 + j) as f32 * 0.61).sin() * (i + 1) as f32)
+    }
+
+    fn random_matrix(rows: usize, cols: usize, seed: u64) -> Array2<f32> {
+        random_projection_matrix(rows, cols, &mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn test_parallel_projection_matches_serial() {
+        let data = random_matrix(3000, 48, 1) + 0.25;
+        let projection = random_matrix(48, 8, 2);
+
+        let (serial, serial_mean) = project_serial(data.view(), projection.view());
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let (parallel, parallel_mean) =
+            pool.install(|| project_parallel(data.view(), projection.view()));
+
+        assert_eq!(parallel.dim(), serial.dim());
+        for (a, b) in parallel.iter().zip(&serial).chain(parallel_mean.iter().zip(&serial_mean)) {
+            assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+        }
+
+        let empty = data.slice(s![..0, ..]);
+        let (empty, _) = pool.install(|| project_parallel(empty, projection.view()));
+        assert_eq!(empty.dim(), (0, 8));
+    }
+
+    #[test]
+    fn test_parallel_covariance_matches_serial() {
+        let data = random_matrix(3000, 24, 3) + 0.25;
+        let mean = data.mean_axis(Axis(0)).unwrap();
+
+        let serial = covariance(data.view(), mean.view(), 1);
+        assert_eq!(serial.dim(), (24, 24));
+        for workers in [2, 3, 8] {
+            let parallel = covariance(data.view(), mean.view(), workers);
+            assert_eq!(parallel.dim(), serial.dim());
+            for (a, b) in parallel.iter().zip(&serial) {
+                assert!((a - b).abs() < 1e-5, "{} workers: {} vs {}", workers, a, b);
+            }
+        }
+
+        // Too few rows to split stays on the calling thread and matches exactly
+        let small = data.slice(s![..100, ..]);
+        assert_eq!(covariance(small, mean.view(), 8), covariance(small, mean.view(), 1));
+    }
+
+    #[test]
+    fn test_round_trip_error_is_bounded_per_width() {
+        let data = sample();
+        for bits in QuantBits::ALL {
+            let quantized = quantize(data.view(), bits);
+            assert_eq!(quantized.data.ncols(), bits.row_bytes(7));
+            for (i, row) in data.axis_iter(Axis(0)).enumerate() {
+                // Rounding is off by at most half a step; the 8-bit writer truncates, one step
+                let step = quantized.scales[i];
+                let bound = if bits == QuantBits::Eight { step } else { step / 2.0 } + 1e-6;
+                for (got, want) in quantized.dequantize_row(i).iter().zip(row) {
+                    let error = (got - want).abs();
+                    assert!(error <= bound, "{:?} row {}: {} vs {}", bits, i, got, want);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wider_quantization_is_more_faithful() {
+        let data = sample();
+        let max_error = |bits| {
+            let quantized = quantize(data.view(), bits);
+            (0..data.nrows())
+                .flat_map(|i| {
+                    let row = quantized.dequantize_row(i);
+                    row.into_iter().zip(data.row(i).to_vec()).map(|(a, b)| (a - b).abs())
+                })
+                .fold(0.0f32, f32::max)
+        };
+        let errors: Vec<f32> = QuantBits::ALL.iter().map(|&bits| max_error(bits)).collect();
+        assert!(errors[0] > errors[1] && errors[1] > errors[2], "{:?}", errors);
+    }
+
+    #[test]
+    fn test_header_tags_width() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = sample();
+        let mean = Array1::zeros(7);
+        for bits in QuantBits::ALL {
+            let path = temp.path().join(format!("q{}.bin", bits.bits()));
+            write_quantized_embeddings(&path, &quantize(data.view(), bits), &mean).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+            let (version, tag) = (word(4), word(20));
+            assert_eq!(QuantBits::from_tag(tag), Some(bits));
+            assert_eq!(version, if bits == QuantBits::Eight { 1 } else { 2 });
+            assert_eq!(bytes.len(), 32 + 7 * 4 + 5 * (4 + bits.row_bytes(7)));
+        }
+        assert!("12".parse::<QuantBits>().is_err());
+        assert!("0".parse::<QuantBits>().is_err());
+        assert_eq!("16".parse::<QuantBits>().unwrap(), QuantBits::Sixteen);
+    }
+}
@@ -16,9 +16,13 @@ mod auth_helper_tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() + Duration::hours(2)),
+            oauth: None,
         };
 
-        assert!(!session.is_expired(), "Session should not be expired when expiry is far in future");
+        assert!(
+            !session.expires_within(Duration::minutes(5)),
+            "Session should not be expired when expiry is far in future"
+        );
     }
 
     #[test]
@@ -31,9 +35,13 @@ mod auth_helper_tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() + Duration::minutes(3)),
+            oauth: None,
         };
 
-        assert!(session.is_expired(), "Session should be expired when within 5-minute window");
+        assert!(
+            session.expires_within(Duration::minutes(5)),
+            "Session should be expired when within 5-minute window"
+        );
     }
 
     #[test]
@@ -46,9 +54,13 @@ mod auth_helper_tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() - Duration::hours(1)),
+            oauth: None,
         };
 
-        assert!(session.is_expired(), "Session should be expired when expiry is in the past");
+        assert!(
+            session.expires_within(Duration::minutes(5)),
+            "Session should be expired when expiry is in the past"
+        );
     }
 
     #[test]
@@ -61,9 +73,13 @@ mod auth_helper_tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: None,
+            oauth: None,
         };
 
-        assert!(!session.is_expired(), "Session without expiry should not be expired");
+        assert!(
+            !session.expires_within(Duration::minutes(5)),
+            "Session without expiry should not be expired"
+        );
     }
 
     #[test]
@@ -96,6 +112,7 @@ mod auth_helper_tests {
             did: "did:plc:test123".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() + Duration::hours(1)),
+            oauth: None,
         };
 
         // Serialize to JSON
@@ -119,6 +136,7 @@ mod auth_helper_tests {
             did: "did:plc:abc".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: None,
+            oauth: None,
         };
 
         let json = serde_json::to_string(&original).expect("Should serialize");
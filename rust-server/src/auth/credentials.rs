@@ -1,5 +1,6 @@
 //! Credential management for BlueSky accounts
 
+use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 
 /// User credentials for BlueSky authentication
@@ -14,6 +15,39 @@ pub struct Credentials {
     /// Service URL (defaults to <https://bsky.social>)
     #[serde(default = "default_service")]
     pub service: String,
+
+    /// Account DID, once the server has accepted these credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<String>,
+
+    /// How the account authenticates; entries stored without it are app passwords
+    #[serde(default)]
+    pub auth_type: AuthType,
+
+    /// DPoP private key an OAuth account's tokens are bound to (base64url P-256 scalar)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpop_key: Option<String>,
+}
+
+/// How a stored account authenticates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthType {
+    /// Handle and app password, exchanged for a session on use
+    #[default]
+    AppPassword,
+    /// OAuth login, stored as the account DID and its refresh token
+    #[serde(rename = "oauth")]
+    OAuth,
+}
+
+impl std::fmt::Display for AuthType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthType::AppPassword => write!(f, "app password"),
+            AuthType::OAuth => write!(f, "OAuth"),
+        }
+    }
 }
 
 fn default_service() -> String {
@@ -21,12 +55,16 @@ fn default_service() -> String {
 }
 
 impl Credentials {
-    /// Create new credentials
+    /// Create new credentials for the default service
+    #[cfg(test)]
     pub fn new(identifier: impl Into<String>, password: impl Into<String>) -> Self {
         Self {
             identifier: identifier.into(),
             password: password.into(),
             service: default_service(),
+            did: None,
+            auth_type: AuthType::AppPassword,
+            dpop_key: None,
         }
     }
 
@@ -40,10 +78,55 @@ impl Credentials {
             identifier: identifier.into(),
             password: password.into(),
             service: service.into(),
+            did: None,
+            auth_type: AuthType::AppPassword,
+            dpop_key: None,
+        }
+    }
+
+    /// Credentials for an OAuth login: the account DID, its refresh token and the DPoP
+    /// key the tokens are bound to
+    pub fn oauth(
+        did: impl Into<String>,
+        refresh_token: impl Into<String>,
+        service: impl Into<String>,
+        dpop_key: impl Into<String>,
+    ) -> Self {
+        Self {
+            auth_type: AuthType::OAuth,
+            dpop_key: Some(dpop_key.into()),
+            ..Self::with_service(did, refresh_token, service)
         }
     }
 }
 
+/// Check a PDS service URL, returning it without a trailing slash.
+/// Only HTTPS is accepted, apart from plain HTTP to a loopback address for local servers.
+pub fn validate_service_url(service: &str) -> Result<String, AppError> {
+    let url = url::Url::parse(service.trim()).map_err(|e| {
+        AppError::InvalidInput(format!("Invalid service URL '{}': {}", service, e))
+    })?;
+
+    let loopback = match url.host() {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    };
+    match url.scheme() {
+        "https" if url.host().is_some() => {}
+        "http" if loopback => {}
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Service URL must use https: {}",
+                service
+            )))
+        }
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +154,45 @@ mod tests {
         assert_eq!(creds.identifier, deserialized.identifier);
         assert_eq!(creds.password, deserialized.password);
     }
+
+    #[test]
+    fn test_credentials_keep_auth_type() {
+        let creds =
+            Credentials::oauth("did:plc:alice", "ref-token", "https://pds.example", "dpop-key");
+        let json = serde_json::to_value(&creds).unwrap();
+        assert_eq!(json["auth_type"], "oauth");
+        let deserialized: Credentials = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.auth_type, AuthType::OAuth);
+        assert_eq!(deserialized.dpop_key.as_deref(), Some("dpop-key"));
+
+        let stored_earlier: Credentials = serde_json::from_str(
+            r#"{"identifier":"alice.test","password":"pass","service":"https://bsky.social"}"#,
+        )
+        .unwrap();
+        assert_eq!(stored_earlier.auth_type, AuthType::AppPassword);
+    }
+
+    #[test]
+    fn test_validate_service_url() {
+        assert_eq!(
+            validate_service_url("https://pds.example.com/").unwrap(),
+            "https://pds.example.com"
+        );
+        assert_eq!(
+            validate_service_url("http://127.0.0.1:2583").unwrap(),
+            "http://127.0.0.1:2583"
+        );
+        assert_eq!(
+            validate_service_url("http://localhost:2583").unwrap(),
+            "http://localhost:2583"
+        );
+
+        for bad in ["http://pds.example.com", "ftp://pds.example.com", "pds.example.com", ""] {
+            assert!(
+                matches!(validate_service_url(bad), Err(AppError::InvalidInput(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
 }
@@ -0,0 +1,236 @@
+//! Passphrase encryption for the file-based credential fallback
+//!
+//! The credentials file is sealed with ChaCha20-Poly1305 under a key derived from a
+//! passphrase with PBKDF2-HMAC-SHA256. The passphrase comes from
+//! `AUTOREPLY_CREDENTIALS_PASSPHRASE`, or is asked for once per process on a terminal.
+
+use crate::error::AppError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+use std::num::NonZeroU32;
+use std::sync::Mutex;
+
+/// Environment variable supplying the passphrase non-interactively
+pub const PASSPHRASE_ENV: &str = "AUTOREPLY_CREDENTIALS_PASSPHRASE";
+
+/// PBKDF2 rounds for newly written files (OWASP guidance for PBKDF2-HMAC-SHA256)
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Fewest PBKDF2 rounds a credentials file may use; fewer make the passphrase cheap to guess
+pub const MIN_ITERATIONS: u32 = 100_000;
+
+/// Most PBKDF2 rounds a credentials file may use; more would stall every read
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+const FORMAT_VERSION: u32 = 1;
+const KDF_NAME: &str = "pbkdf2-hmac-sha256";
+const CIPHER_NAME: &str = "chacha20-poly1305";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Passphrase read from the environment or the terminal, kept for the rest of the process
+static PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// On-disk form of an encrypted credentials file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedFile {
+    version: u32,
+    kdf: String,
+    iterations: u32,
+    cipher: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Seal `plaintext` under a key derived from `passphrase`, with a fresh salt and nonce
+pub fn encrypt(
+    plaintext: &[u8],
+    passphrase: &str,
+    iterations: u32,
+) -> Result<EncryptedFile, AppError> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| AppError::ConfigError("Failed to generate random bytes".to_string()))?;
+
+    let key = derive_key(passphrase, &salt, iterations)?;
+    let mut sealed = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), aad(), &mut sealed)
+        .map_err(|_| AppError::ConfigError("Failed to encrypt credentials".to_string()))?;
+
+    Ok(EncryptedFile {
+        version: FORMAT_VERSION,
+        kdf: KDF_NAME.to_string(),
+        iterations,
+        cipher: CIPHER_NAME.to_string(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(sealed),
+    })
+}
+
+/// Open an encrypted file, failing with an authentication error on a wrong passphrase
+pub fn decrypt(file: &EncryptedFile, passphrase: &str) -> Result<Vec<u8>, AppError> {
+    if file.version != FORMAT_VERSION || file.kdf != KDF_NAME || file.cipher != CIPHER_NAME {
+        return Err(AppError::ConfigError(format!(
+            "Unsupported credentials file encryption: version {}, {}, {}",
+            file.version, file.kdf, file.cipher
+        )));
+    }
+
+    let decode = |field: &str, value: &str| {
+        STANDARD.decode(value).map_err(|e| {
+            AppError::ConfigError(format!("Invalid {} in credentials file: {}", field, e))
+        })
+    };
+    let salt = decode("salt", &file.salt)?;
+    let nonce: [u8; NONCE_LEN] = decode("nonce", &file.nonce)?
+        .try_into()
+        .map_err(|_| AppError::ConfigError("Invalid nonce in credentials file".to_string()))?;
+    let mut sealed = decode("ciphertext", &file.ciphertext)?;
+
+    let key = derive_key(passphrase, &salt, file.iterations)?;
+    let plaintext = key
+        .open_in_place(Nonce::assume_unique_for_key(nonce), aad(), &mut sealed)
+        .map_err(|_| {
+            AppError::Authentication(
+                "Wrong passphrase for the credentials file, or the file is corrupted".to_string(),
+            )
+        })?;
+    Ok(plaintext.to_vec())
+}
+
+/// The passphrase for this process: `AUTOREPLY_CREDENTIALS_PASSPHRASE`, else a terminal prompt.
+/// With `confirm`, a prompted passphrase is asked for twice, as when sealing a new file.
+pub fn passphrase(confirm: bool) -> Result<String, AppError> {
+    let mut cached = PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(passphrase) = cached.as_ref() {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(value) if !value.is_empty() => value,
+        _ => prompt_passphrase(confirm)?,
+    };
+    *cached = Some(passphrase.clone());
+    Ok(passphrase)
+}
+
+/// Drop a remembered passphrase so the next access asks again
+pub fn forget_passphrase() {
+    *PASSPHRASE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn prompt_passphrase(confirm: bool) -> Result<String, AppError> {
+    // MCP mode talks JSON-RPC over stdin, so only prompt when a person is typing
+    if !std::io::stdin().is_terminal() {
+        return Err(AppError::ConfigError(format!(
+            "The credentials file is encrypted: set {} to its passphrase",
+            PASSPHRASE_ENV
+        )));
+    }
+
+    let passphrase = read_hidden("Passphrase for the credentials file: ")?;
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidInput("Passphrase cannot be empty".to_string()));
+    }
+    if confirm && read_hidden("Repeat the passphrase: ")? != passphrase {
+        return Err(AppError::InvalidInput("Passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// Read a line from the terminal without echoing it
+fn read_hidden(prompt: &str) -> Result<String, AppError> {
+    rpassword::prompt_password(prompt)
+        .map_err(|e| AppError::ConfigError(format!("Failed to read passphrase: {}", e)))
+}
+
+/// Derive the file key; the round count is checked first, since a tampered file could
+/// name zero rounds or enough to hang the process
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, AppError> {
+    let iterations = NonZeroU32::new(iterations)
+        .filter(|n| (MIN_ITERATIONS..=MAX_ITERATIONS).contains(&n.get()))
+        .ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "Invalid key derivation rounds in credentials file: {} (expected {} to {})",
+                iterations, MIN_ITERATIONS, MAX_ITERATIONS
+            ))
+        })?;
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+        .map_err(|_| AppError::ConfigError("Failed to create encryption key".to_string()))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Binds ciphertexts to this file format
+fn aad() -> Aad<&'static [u8]> {
+    Aad::from(b"autoreply-credentials-v1".as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let file = encrypt(b"{\"accounts\":{}}", "correct horse", MIN_ITERATIONS).unwrap();
+        assert!(!file.ciphertext.contains("accounts"));
+        assert_eq!(decrypt(&file, "correct horse").unwrap(), b"{\"accounts\":{}}");
+
+        // Fresh salt and nonce on every write
+        let again = encrypt(b"{\"accounts\":{}}", "correct horse", MIN_ITERATIONS).unwrap();
+        assert_ne!(file.salt, again.salt);
+        assert_ne!(file.nonce, again.nonce);
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let file = encrypt(b"secret", "correct horse", MIN_ITERATIONS).unwrap();
+        assert!(matches!(
+            decrypt(&file, "battery staple"),
+            Err(AppError::Authentication(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let mut file = encrypt(b"secret", "correct horse", MIN_ITERATIONS).unwrap();
+        let mut bytes = STANDARD.decode(&file.ciphertext).unwrap();
+        bytes[0] ^= 1;
+        file.ciphertext = STANDARD.encode(bytes);
+        assert!(decrypt(&file, "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_rounds_out_of_range_are_rejected() {
+        let file = encrypt(b"secret", "correct horse", MIN_ITERATIONS).unwrap();
+        for iterations in [0, 1_000, MIN_ITERATIONS - 1, MAX_ITERATIONS + 1, u32::MAX] {
+            let tampered = EncryptedFile {
+                iterations,
+                ..file.clone()
+            };
+            assert!(
+                matches!(decrypt(&tampered, "correct horse"), Err(AppError::ConfigError(_))),
+                "{} rounds",
+                iterations
+            );
+            assert!(encrypt(b"secret", "correct horse", iterations).is_err());
+        }
+    }
+}
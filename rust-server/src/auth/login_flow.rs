@@ -1,10 +1,13 @@
+use crate::auth::credentials::validate_service_url;
 use crate::auth::{
-    AtProtoOAuthManager, CallbackResult, CallbackServer, CredentialStorage, Credentials,
-    SessionManager, StorageBackend,
+    AccountInfo, AtProtoOAuthManager, CallbackResult, CallbackServer, CredentialStorage,
+    Credentials, Session, SessionManager, StorageBackend, DEFAULT_SERVICE,
 };
-use crate::cli::{LoginCommand, LoginSubcommands};
+use crate::bluesky::did::DidResolver;
+use crate::cli::{AccountsSubcommands, LoginCommand, LoginSubcommands};
 use crate::error::AppError;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tracing::{debug, warn};
 
@@ -24,6 +27,8 @@ pub struct LoginRequest {
 pub struct LoginOutcome {
     pub message: String,
     pub elicitation: Option<LoginElicitation>,
+    /// Device flow still waiting for approval; resolves to the final login message
+    pub pending: Option<JoinHandle<Result<String, AppError>>>,
 }
 pub struct LoginManager {
     storage: Arc<CredentialStorage>,
@@ -36,15 +41,26 @@ impl LoginManager {
         })
     }
 
+    /// Manager over a given storage
+    #[cfg(test)]
+    pub(crate) fn with_storage(storage: CredentialStorage) -> Self {
+        Self {
+            storage: Arc::new(storage),
+        }
+    }
+
     pub async fn execute(&self, request: LoginRequest) -> Result<LoginOutcome, AppError> {
         match &request.payload.command {
             Some(LoginSubcommands::List) => {
                 let accounts = self.storage.list_accounts()?;
                 let default = self.storage.get_default_account()?;
-                let message = format_account_list(&accounts, default.as_deref());
+                let entries = accounts.iter().map(|account| (account.as_str(), String::new()));
+                let message =
+                    format_account_list("Authenticated accounts", entries, default.as_deref());
                 Ok(LoginOutcome {
                     message,
                     elicitation: None,
+                    pending: None,
                 })
             }
             Some(LoginSubcommands::Default { handle }) => {
@@ -53,6 +69,7 @@ impl LoginManager {
                 Ok(LoginOutcome {
                     message: format!("✓ Set @{} as default account", handle),
                     elicitation: None,
+                    pending: None,
                 })
             }
             Some(LoginSubcommands::Delete { handle }) => {
@@ -65,10 +82,11 @@ impl LoginManager {
                         )
                     })?
                 };
-                self.storage.remove_account(&handle_to_delete)?;
+                self.storage.remove(&handle_to_delete)?;
                 Ok(LoginOutcome {
                     message: format!("✓ Deleted account @{}", handle_to_delete),
                     elicitation: None,
+                    pending: None,
                 })
             }
             None => self.handle_login(request).await,
@@ -80,10 +98,16 @@ impl LoginManager {
             mut handle,
             password,
             service,
+            device,
             ..
         } = request.payload.clone();
 
         normalize_handle(&mut handle);
+        let service = service
+            .or_else(|| crate::config::config().service.clone())
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| validate_service_url(&s))
+            .transpose()?;
 
         // Handle can be None for OAuth - allows user to select account during OAuth flow
         // If using app password, handle is required
@@ -100,6 +124,7 @@ impl LoginManager {
                             field: "handle".to_string(),
                             message: "Enter Bluesky handle (e.g., alice.bsky.social)".to_string(),
                         }),
+                        pending: None,
                     });
                 }
                 _ => {
@@ -118,6 +143,7 @@ impl LoginManager {
                             field: "password".to_string(),
                             message: format!("App password for @{}", handle_str),
                         }),
+                        pending: None,
                     });
                 }
                 return Err(AppError::InvalidInput(r#"# Login via app password failed: the client does not support interactive prompts (MCP elicitation). Please choose one of these options:
@@ -132,14 +158,39 @@ IMPORTANT Security Warning:
 - OAuth is the most secure option and is strongly preferred"#.to_string()));
             }
 
-            let credentials = build_credentials(&handle_str, &pwd, service.as_deref());
-            let message = self
+            let service = match service {
+                Some(service) => service,
+                None => discover_service(&handle_str).await,
+            };
+            let credentials = Credentials::with_service(&handle_str, &pwd, service);
+            return match self
                 .authenticate_with_app_password(&handle_str, credentials)
-                .await?;
-            return Ok(LoginOutcome {
-                message,
-                elicitation: None,
-            });
+                .await
+            {
+                Ok(message) => Ok(LoginOutcome {
+                    message,
+                    elicitation: None,
+                    pending: None,
+                }),
+                // Nothing was stored: ask for the app password again
+                Err(AppError::Authentication(reason)) if request.interactive => {
+                    Ok(LoginOutcome {
+                        message: format!("Login rejected: {}", reason),
+                        elicitation: Some(LoginElicitation {
+                            field: "password".to_string(),
+                            message: format!("App password for @{} (try again)", handle_str),
+                        }),
+                        pending: None,
+                    })
+                }
+                Err(e) => Err(e),
+            };
+        }
+
+        if device {
+            return self
+                .authenticate_with_device_flow(handle.as_deref(), service.as_deref())
+                .await;
         }
 
         // OAuth mode - handle is optional
@@ -152,6 +203,7 @@ IMPORTANT Security Warning:
             Ok(response) => Ok(LoginOutcome {
                 message: response,
                 elicitation: None,
+                pending: None,
             }),
             Err(oauth_error) => {
                 warn!("OAuth authentication failed: {}", oauth_error.message());
@@ -167,6 +219,7 @@ IMPORTANT Security Warning:
                                 handle_for_password
                             ),
                         }),
+                        pending: None,
                     })
                 } else {
                     Err(oauth_error)
@@ -230,11 +283,7 @@ IMPORTANT Security Warning:
                             // Store credentials using the handle from the session (obtained after OAuth)
                             if let Err(e) = storage.store_credentials_with_fallback(
                                 &session.handle,
-                                Credentials::with_service(
-                                    &session.did,
-                                    &session.refresh_jwt,
-                                    &session.service,
-                                ),
+                                oauth_credentials(&session),
                             ) {
                                 warn!(
                                     "OAuth background task: Failed to store credentials: {}",
@@ -292,14 +341,63 @@ IMPORTANT Security Warning:
         ))
     }
 
+    /// Start the OAuth device flow and poll for approval in the background
+    async fn authenticate_with_device_flow(
+        &self,
+        handle: Option<&str>,
+        service: Option<&str>,
+    ) -> Result<LoginOutcome, AppError> {
+        debug!("Starting OAuth device flow");
+
+        let mut oauth_manager = AtProtoOAuthManager::new()?;
+        let flow_state = oauth_manager.start_device_flow(handle).await?;
+        let message = format_device_instructions(
+            &flow_state.verification_uri,
+            flow_state.verification_uri_complete.as_deref(),
+            &flow_state.user_code,
+            flow_state.expires_in,
+        );
+
+        let storage = self.storage.clone();
+        let service_owned = service.map(|s| s.to_string());
+        let pending = tokio::spawn(async move {
+            let mut session = oauth_manager.complete_device_flow(&flow_state).await?;
+            if let Some(service_url) = service_owned {
+                session.service = service_url;
+            }
+
+            storage.store_credentials_with_fallback(
+                &session.handle,
+                oauth_credentials(&session),
+            )?;
+            storage.store_session(&session.handle, session.clone())?;
+            ensure_default(&storage, &session.handle)?;
+
+            debug!("OAuth device flow: authenticated as @{}", session.handle);
+            Ok(format!(
+                "✓ Successfully authenticated as @{}\n  DID: {}\n  Method: OAuth device flow",
+                session.handle, session.did
+            ))
+        });
+
+        Ok(LoginOutcome {
+            message,
+            elicitation: None,
+            pending: Some(pending),
+        })
+    }
+
+    /// Check the app password with createSession, storing it (with the DID the server
+    /// returned) only once it has been accepted
     async fn authenticate_with_app_password(
         &self,
         handle: &str,
-        credentials: Credentials,
+        mut credentials: Credentials,
     ) -> Result<String, AppError> {
         debug!("Authenticating with app password for @{}", handle);
         let manager = SessionManager::new()?;
         let session = manager.login(&credentials).await?;
+        credentials.did = Some(session.did.clone());
 
         self.storage
             .store_credentials_with_fallback(handle, credentials)?;
@@ -319,14 +417,38 @@ IMPORTANT Security Warning:
     }
 }
 
-fn build_credentials(handle: &str, password: &str, service: Option<&str>) -> Credentials {
-    if let Some(service_url) = service {
-        Credentials::with_service(handle, password, service_url)
+/// The PDS named in the account's DID document, or the default service when the
+/// handle or DID cannot be resolved
+async fn discover_service(handle: &str) -> String {
+    let resolver = DidResolver::new();
+    let did = if handle.starts_with("did:") {
+        Some(handle.to_string())
     } else {
-        Credentials::new(handle, password)
+        resolver.resolve_handle(handle).await.ok().flatten()
+    };
+    let pds = match did {
+        Some(did) => resolver.discover_pds(&did).await.ok().flatten(),
+        None => None,
+    };
+
+    match pds.as_deref().map(validate_service_url) {
+        Some(Ok(service)) => {
+            debug!("Using PDS {} for @{}", service, handle);
+            service
+        }
+        _ => {
+            warn!("Could not find a PDS for @{}, using {}", handle, DEFAULT_SERVICE);
+            DEFAULT_SERVICE.to_string()
+        }
     }
 }
 
+/// Credentials for a completed OAuth login, holding the DPoP key its tokens are bound to
+fn oauth_credentials(session: &Session) -> Credentials {
+    let dpop_key = session.oauth.as_ref().map(|oauth| oauth.dpop_key.clone()).unwrap_or_default();
+    Credentials::oauth(&session.did, &session.refresh_jwt, &session.service, dpop_key)
+}
+
 fn ensure_default(storage: &CredentialStorage, handle: &str) -> Result<(), AppError> {
     if storage.get_default_account()?.is_none() {
         storage.set_default_account(handle)?;
@@ -334,23 +456,86 @@ fn ensure_default(storage: &CredentialStorage, handle: &str) -> Result<(), AppEr
     Ok(())
 }
 
-fn format_account_list(accounts: &[String], default: Option<&str>) -> String {
-    if accounts.is_empty() {
+fn format_device_instructions(
+    verification_uri: &str,
+    verification_uri_complete: Option<&str>,
+    user_code: &str,
+    expires_in: Duration,
+) -> String {
+    let open = match verification_uri_complete {
+        Some(complete) => format!(
+            "1. On any device, open:\n   {}\n\n   (or open {} and enter the code below)",
+            complete, verification_uri
+        ),
+        None => format!("1. On any device, open:\n   {}", verification_uri),
+    };
+    format!(
+        "# OAuth Device Login\n\n{}\n\n2. Confirm the code: **{}**\n\nWaiting for approval (the code expires in {} minutes)...",
+        open,
+        user_code,
+        expires_in.as_secs().div_ceil(60)
+    )
+}
+
+/// List accounts under `title`, marking the default; each entry is a handle and the
+/// detail lines to show under it
+fn format_account_list<'a>(
+    title: &str,
+    accounts: impl ExactSizeIterator<Item = (&'a str, String)>,
+    default: Option<&str>,
+) -> String {
+    if accounts.len() == 0 {
         return "No accounts stored. Use 'autoreply login' to add an account.".to_string();
     }
 
-    let mut output = format!("Authenticated accounts ({}):\n", accounts.len());
-    for account in accounts {
-        let marker = if Some(account.as_str()) == default {
+    let mut output = format!("{} ({}):\n", title, accounts.len());
+    for (account, details) in accounts {
+        let marker = if Some(account) == default {
             " (default)"
         } else {
             ""
         };
-        output.push_str(&format!("  • @{}{}\n", account, marker));
+        output.push_str(&format!("  • @{}{}\n{}", account, marker, details));
     }
     output
 }
 
+/// Run an `accounts` subcommand against the credential storage
+pub fn manage_accounts(
+    storage: &CredentialStorage,
+    command: &AccountsSubcommands,
+) -> Result<String, AppError> {
+    match command {
+        AccountsSubcommands::List => {
+            let mut accounts = storage
+                .list_accounts()?
+                .iter()
+                .map(|handle| storage.account_info(handle))
+                .collect::<Result<Vec<_>, _>>()?;
+            accounts.sort_by(|a, b| a.handle.cmp(&b.handle));
+            let default = storage.get_default_account()?;
+            Ok(format_account_details(&accounts, default.as_deref()))
+        }
+        AccountsSubcommands::Remove { handle } => {
+            let handle = handle.trim().trim_start_matches('@');
+            storage.remove(handle)?;
+            Ok(format!("✓ Removed account @{}", handle))
+        }
+    }
+}
+
+fn format_account_details(accounts: &[AccountInfo], default: Option<&str>) -> String {
+    let entries = accounts.iter().map(|account| {
+        let details = format!(
+            "    DID: {}\n    Auth: {}\n",
+            account.did.as_deref().unwrap_or("unknown"),
+            account.auth_type
+        );
+        (account.handle.as_str(), details)
+    });
+    format_account_list("Stored accounts", entries, default)
+}
+
 fn normalize_handle(handle: &mut Option<String>) {
     if let Some(ref mut h) = handle {
         let normalized = h.trim().trim_start_matches('@').to_string();
@@ -386,13 +571,154 @@ mod tests {
     }
 
     #[test]
-    fn format_account_list_marks_default() {
+    fn format_device_instructions_shows_url_and_code() {
+        let plain = format_device_instructions(
+            "https://bsky.social/oauth/device",
+            None,
+            "WDJB-MJHT",
+            Duration::from_secs(600),
+        );
+        assert!(plain.contains("https://bsky.social/oauth/device"));
+        assert!(plain.contains("**WDJB-MJHT**"));
+        assert!(plain.contains("expires in 10 minutes"));
+
+        let complete = format_device_instructions(
+            "https://bsky.social/oauth/device",
+            Some("https://bsky.social/oauth/device?user_code=WDJB-MJHT"),
+            "WDJB-MJHT",
+            Duration::from_secs(900),
+        );
+        assert!(
+            complete.contains("open:\n   https://bsky.social/oauth/device?user_code=WDJB-MJHT"),
+            "{}",
+            complete
+        );
+    }
+
+    #[test]
+    fn format_account_details_hides_secrets() {
         let accounts = vec![
-            "alice.bsky.social".to_string(),
-            "bob.bsky.social".to_string(),
+            AccountInfo {
+                handle: "alice.bsky.social".to_string(),
+                did: Some("did:plc:alice".to_string()),
+                auth_type: crate::auth::AuthType::OAuth,
+            },
+            AccountInfo {
+                handle: "bob.bsky.social".to_string(),
+                did: None,
+                auth_type: crate::auth::AuthType::AppPassword,
+            },
         ];
-        let output = format_account_list(&accounts, Some("alice.bsky.social"));
-        assert!(output.contains("@alice.bsky.social (default)"));
-        assert!(output.contains("@bob.bsky.social"));
+        let output = format_account_details(&accounts, Some("bob.bsky.social"));
+        assert!(output.contains("Stored accounts (2)"));
+        assert!(output.contains("@alice.bsky.social\n    DID: did:plc:alice\n    Auth: OAuth"));
+        assert!(output.contains("@bob.bsky.social (default)\n    DID: unknown"));
+        assert!(output.contains("Auth: app password"));
+    }
+
+    #[test]
+    fn format_account_list_marks_default() {
+        let entries = ["alice.bsky.social", "bob.bsky.social"]
+            .into_iter()
+            .map(|account| (account, String::new()));
+        let output =
+            format_account_list("Authenticated accounts", entries, Some("alice.bsky.social"));
+        assert!(output.starts_with("Authenticated accounts (2):\n"));
+        assert!(output.contains("@alice.bsky.social (default)\n  • @bob.bsky.social\n"));
+    }
+
+    mod app_password_validation {
+        use super::*;
+        use axum::{http::StatusCode, routing::post, Json, Router};
+
+        /// A PDS whose createSession accepts only `good-password`
+        async fn mock_pds() -> String {
+            let app = Router::new().route(
+                "/xrpc/com.atproto.server.createSession",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    if body["password"] == "good-password" {
+                        let session = serde_json::json!({
+                            "accessJwt": "access",
+                            "refreshJwt": "refresh",
+                            "handle": "alice.test",
+                            "did": "did:plc:alice"
+                        });
+                        (StatusCode::OK, Json(session))
+                    } else {
+                        let error = serde_json::json!({
+                            "error": "AuthenticationRequired",
+                            "message": "Invalid identifier or password"
+                        });
+                        (StatusCode::UNAUTHORIZED, Json(error))
+                    }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            url
+        }
+
+        fn login_request(service: &str, password: &str, interactive: bool) -> LoginRequest {
+            LoginRequest {
+                payload: LoginCommand {
+                    command: None,
+                    handle: Some("alice.test".to_string()),
+                    password: Some(password.to_string()),
+                    device: false,
+                    service: Some(service.to_string()),
+                },
+                interactive,
+            }
+        }
+
+        #[tokio::test]
+        async fn accepted_password_is_stored_with_did() {
+            let service = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let manager = LoginManager::with_storage(CredentialStorage::with_file(
+                dir.path().join("credentials.json"),
+            ));
+
+            let outcome = manager
+                .execute(login_request(&service, "good-password", false))
+                .await
+                .unwrap();
+            assert!(outcome.elicitation.is_none());
+            assert!(outcome.message.contains("did:plc:alice"));
+
+            let credentials = manager.storage.get_credentials("alice.test").unwrap();
+            assert_eq!(credentials.did.as_deref(), Some("did:plc:alice"));
+            assert_eq!(credentials.service, service);
+            assert_eq!(
+                manager.storage.get_default_account().unwrap().as_deref(),
+                Some("alice.test")
+            );
+        }
+
+        #[tokio::test]
+        async fn rejected_password_prompts_again_without_storing() {
+            let service = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let manager = LoginManager::with_storage(CredentialStorage::with_file(
+                dir.path().join("credentials.json"),
+            ));
+
+            let outcome = manager
+                .execute(login_request(&service, "typo-password", true))
+                .await
+                .unwrap();
+            let elicitation = outcome.elicitation.expect("should ask for the password again");
+            assert_eq!(elicitation.field, "password");
+            assert!(outcome.message.contains("401"));
+            assert!(manager.storage.list_accounts().unwrap().is_empty());
+
+            // Without prompts the rejection is an error, and still nothing is stored
+            let result = manager
+                .execute(login_request(&service, "typo-password", false))
+                .await;
+            assert!(matches!(result, Err(AppError::Authentication(_))));
+            assert!(manager.storage.list_accounts().unwrap().is_empty());
+        }
     }
 }
@@ -3,29 +3,32 @@
 //! Provides authentication functionality including:
 //! - App password authentication via com.atproto.server.createSession
 //! - OAuth 2.0 with PKCE and DPoP (browser-based flow)
+//! - OAuth device authorization grant for headless machines
 //! - Credential storage with OS keyring and file fallback
 //! - Token refresh and lifecycle management
 //! - Multi-account support
 
 pub mod callback_server;
 pub mod credentials;
+pub mod file_crypto;
 pub mod login_flow;
 pub mod oauth_atproto;
 pub mod session;
 pub mod storage;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod auth_helper_tests;
 
 #[cfg(test)]
 mod oauth_tests;
 
 pub use callback_server::{CallbackResult, CallbackServer};
-pub use credentials::Credentials;
+pub use credentials::{AuthType, Credentials};
 pub use login_flow::{LoginManager, LoginRequest};
 pub use oauth_atproto::AtProtoOAuthManager;
 pub use session::{Session, SessionManager};
-pub use storage::{CredentialStorage, StorageBackend};
+pub use storage::{AccountInfo, CredentialStorage, StorageBackend};
 
 use crate::error::AppError;
 
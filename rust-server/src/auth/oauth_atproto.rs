@@ -11,8 +11,10 @@
 //! - DPoP with nonce handling - mandatory
 //! - Client metadata as URL or loopback
 
+use crate::auth::session::OAuthSession;
 use crate::auth::{AuthError, Session};
 use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
 use base64::Engine;
 use p256::ecdsa::SigningKey;
 use rand::rngs::OsRng;
@@ -29,8 +31,25 @@ pub struct DPoPManager {
 impl DPoPManager {
     /// Create a new DPoP manager with a fresh ES256 keypair
     pub fn new() -> Result<Self, AppError> {
-        let signing_key = SigningKey::random(&mut OsRng);
+        Self::with_key(SigningKey::random(&mut OsRng))
+    }
+
+    /// Restore the DPoP manager a session's tokens are bound to, from `export_key` output
+    pub fn from_key(encoded: &str) -> Result<Self, AppError> {
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| AppError::ParseError(format!("Invalid stored DPoP key: {}", e)))?;
+        let signing_key = SigningKey::from_slice(&bytes)
+            .map_err(|e| AppError::ParseError(format!("Invalid stored DPoP key: {}", e)))?;
+        Self::with_key(signing_key)
+    }
+
+    /// The private key, base64url encoded, for storing with the session
+    pub fn export_key(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(self.signing_key.to_bytes())
+    }
 
+    fn with_key(signing_key: SigningKey) -> Result<Self, AppError> {
         // Extract public key in JWK format
         let verifying_key = signing_key.verifying_key();
         let encoded_point = verifying_key.to_encoded_point(false);
@@ -150,6 +169,9 @@ pub struct AuthServerMetadata {
     pub authorization_endpoint: String,
     pub token_endpoint: String,
     pub pushed_authorization_request_endpoint: String,
+    /// Present when the server supports the device authorization grant (RFC 8628)
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
     #[serde(default)]
     pub dpop_signing_alg_values_supported: Vec<String>,
     #[serde(default)]
@@ -187,6 +209,128 @@ pub struct TokenResponse {
     pub sub: Option<String>,
 }
 
+/// Grant type for polling the token endpoint in the device flow (RFC 8628)
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Seconds between polls when the server names no interval (RFC 8628 section 3.2)
+const DEFAULT_DEVICE_POLL_INTERVAL: u64 = 5;
+
+/// Added to the polling interval on each `slow_down` (RFC 8628 section 3.5)
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+fn default_device_poll_interval() -> u64 {
+    DEFAULT_DEVICE_POLL_INTERVAL
+}
+
+/// Device authorization response
+#[derive(Debug, Deserialize)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_device_poll_interval")]
+    pub interval: u64,
+}
+
+/// One token endpoint answer while polling in the device flow
+#[derive(Debug)]
+pub enum DevicePollResponse {
+    Token(TokenResponse),
+    /// `authorization_pending`: the user has not approved yet
+    Pending,
+    /// `slow_down`: keep polling, less often
+    SlowDown,
+    /// `expired_token`: the device code is no longer valid
+    Expired,
+    /// `access_denied`: the user declined
+    Denied,
+    Failed(String),
+}
+
+impl DevicePollResponse {
+    /// Classify a token endpoint response per RFC 8628 section 3.5
+    pub fn from_body(success: bool, body: &str) -> Self {
+        if success {
+            return match serde_json::from_str(body) {
+                Ok(token) => DevicePollResponse::Token(token),
+                Err(e) => DevicePollResponse::Failed(format!(
+                    "Failed to parse token response: {}",
+                    e
+                )),
+            };
+        }
+
+        let error = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v.get("error")?.as_str().map(str::to_string));
+        match error.as_deref() {
+            Some("authorization_pending") => DevicePollResponse::Pending,
+            Some("slow_down") => DevicePollResponse::SlowDown,
+            Some("expired_token") => DevicePollResponse::Expired,
+            Some("access_denied") => DevicePollResponse::Denied,
+            _ => DevicePollResponse::Failed(body.to_string()),
+        }
+    }
+}
+
+/// Polling schedule for the device flow: how long to wait, and when to give up
+#[derive(Debug)]
+pub struct DevicePoller {
+    interval: Duration,
+    remaining: Duration,
+}
+
+impl DevicePoller {
+    pub fn new(interval: Duration, expires_in: Duration) -> Self {
+        Self {
+            interval,
+            remaining: expires_in,
+        }
+    }
+
+    /// Wait before the next token request, or `None` once the device code has expired
+    pub fn next_wait(&mut self) -> Option<Duration> {
+        if self.interval > self.remaining {
+            return None;
+        }
+        self.remaining -= self.interval;
+        Some(self.interval)
+    }
+
+    /// Apply a poll response: `Some(token)` when authorized, `None` to keep polling
+    pub fn handle(
+        &mut self,
+        response: DevicePollResponse,
+    ) -> Result<Option<TokenResponse>, AppError> {
+        match response {
+            DevicePollResponse::Token(token) => Ok(Some(token)),
+            DevicePollResponse::Pending => Ok(None),
+            DevicePollResponse::SlowDown => {
+                self.interval += SLOW_DOWN_INCREMENT;
+                Ok(None)
+            }
+            DevicePollResponse::Expired => Err(device_code_expired()),
+            DevicePollResponse::Denied => Err(AuthError::AuthenticationFailed(
+                "Authorization was denied".to_string(),
+            )
+            .into()),
+            DevicePollResponse::Failed(error) => Err(AuthError::AuthenticationFailed(format!(
+                "Device token request failed: {}",
+                error
+            ))
+            .into()),
+        }
+    }
+}
+
+fn device_code_expired() -> AppError {
+    AuthError::AuthenticationFailed("Device code expired before it was approved".to_string())
+        .into()
+}
+
 /// AT Protocol OAuth manager
 pub struct AtProtoOAuthManager {
     config: AtProtoOAuthConfig,
@@ -202,12 +346,53 @@ impl AtProtoOAuthManager {
 
     /// Create OAuth manager with custom config
     pub fn with_config(config: AtProtoOAuthConfig) -> Result<Self, AppError> {
+        Ok(Self::with_dpop(config, DPoPManager::new()?))
+    }
+
+    fn with_dpop(config: AtProtoOAuthConfig, dpop: DPoPManager) -> Self {
         let client = crate::http::client_with_timeout(Duration::from_secs(120));
-        let dpop = DPoPManager::new()?;
-        Ok(Self {
+        Self {
             config,
             client,
             dpop,
+        }
+    }
+
+    /// Refresh an OAuth session at the token endpoint that issued it, proving possession
+    /// of the DPoP key its tokens are bound to
+    pub async fn refresh_session(
+        session: &Session,
+        oauth: &OAuthSession,
+    ) -> Result<Session, AppError> {
+        let config = AtProtoOAuthConfig {
+            client_id: oauth.client_id.clone(),
+            ..AtProtoOAuthConfig::default()
+        };
+        let mut manager = Self::with_dpop(config, DPoPManager::from_key(&oauth.dpop_key)?);
+        let params = [
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", session.refresh_jwt.clone()),
+            ("client_id", oauth.client_id.clone()),
+        ];
+
+        let (success, body) = manager.post_form_with_dpop(&oauth.token_endpoint, &params).await?;
+        if !success {
+            return Err(AuthError::RefreshFailed(format!("Token refresh failed: {}", body)).into());
+        }
+        let token_response: TokenResponse = serde_json::from_str(&body).map_err(|e| {
+            AppError::ParseError(format!("Failed to parse token refresh response: {}", e))
+        })?;
+
+        Ok(Session {
+            access_jwt: token_response.access_token,
+            // Servers that do not rotate refresh tokens leave the current one valid
+            refresh_jwt: token_response
+                .refresh_token
+                .unwrap_or_else(|| session.refresh_jwt.clone()),
+            expires_at: Some(
+                chrono::Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64),
+            ),
+            ..session.clone()
         })
     }
 
@@ -256,12 +441,16 @@ impl AtProtoOAuthManager {
             .client
             .get(&api_url)
             .timeout(Duration::from_secs(120))
-            .send()
+            .send_xrpc()
             .await
             .map_err(|e| {
                 AuthError::AuthenticationFailed(format!("Handle resolution failed: {}", e))
             })?;
 
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
         if !response.status().is_success() {
             return Err(AuthError::AuthenticationFailed(format!(
                 "Handle resolution failed with status {} for handle '{}'",
@@ -468,6 +657,19 @@ impl AtProtoOAuthManager {
         &mut self,
         handle: Option<&str>,
     ) -> Result<BrowserFlowState, AppError> {
+        let (did, auth_metadata) = self.discover_for_login(handle).await?;
+        self.complete_browser_flow(auth_metadata, handle, did).await
+    }
+
+    /// Find the authorization server to log in with
+    ///
+    /// A valid handle is resolved to its DID and PDS, whose authorization server is used.
+    /// Without one, the default entryway lets the user pick an account; the DID is then
+    /// empty until the token response names it.
+    async fn discover_for_login(
+        &self,
+        handle: Option<&str>,
+    ) -> Result<(String, AuthServerMetadata), AppError> {
         // Validate handle format: must contain a dot (e.g., "user.bsky.social")
         // If handle is provided but invalid, treat it like None and use default OAuth
         let valid_handle = handle.filter(|h| h.contains('.'));
@@ -506,9 +708,7 @@ impl AtProtoOAuthManager {
                 let auth_metadata = self.discover_from_issuer(issuer).await?;
                 tracing::debug!("Authorization server: {}", auth_metadata.issuer);
 
-                return self
-                    .complete_browser_flow(auth_metadata, handle, String::new())
-                    .await;
+                return Ok((String::new(), auth_metadata));
             }
         };
 
@@ -517,7 +717,7 @@ impl AtProtoOAuthManager {
         let auth_metadata = self.discover_authorization_server(&pds_url).await?;
         tracing::debug!("Authorization server: {}", auth_metadata.issuer);
 
-        self.complete_browser_flow(auth_metadata, handle, did).await
+        Ok((did, auth_metadata))
     }
 
     /// Complete the browser flow after discovering authorization server
@@ -803,6 +1003,128 @@ impl AtProtoOAuthManager {
         Ok(token_response)
     }
 
+    /// Start the device authorization flow for machines without a browser
+    ///
+    /// The returned state carries the URL and code for the user to approve on another
+    /// device; `complete_device_flow` then polls until they do.
+    pub async fn start_device_flow(
+        &mut self,
+        handle: Option<&str>,
+    ) -> Result<DeviceFlowState, AppError> {
+        let (did, auth_metadata) = self.discover_for_login(handle).await?;
+        let endpoint = auth_metadata.device_authorization_endpoint.ok_or_else(|| {
+            AuthError::AuthenticationFailed(format!(
+                "Authorization server {} does not support the device flow",
+                auth_metadata.issuer
+            ))
+        })?;
+
+        let mut params = vec![
+            ("client_id", self.config.client_id.clone()),
+            ("scope", self.config.scope.clone()),
+        ];
+        if let Some(hint) = handle {
+            params.push(("login_hint", hint.to_string()));
+        }
+
+        let (success, body) = self.post_form_with_dpop(&endpoint, &params).await?;
+        if !success {
+            return Err(AuthError::AuthenticationFailed(format!(
+                "Device authorization failed: {}",
+                body
+            ))
+            .into());
+        }
+        let response: DeviceAuthorizationResponse = serde_json::from_str(&body).map_err(|e| {
+            AppError::ParseError(format!("Failed to parse device authorization response: {}", e))
+        })?;
+
+        Ok(DeviceFlowState {
+            user_code: response.user_code,
+            verification_uri: response.verification_uri,
+            verification_uri_complete: response.verification_uri_complete,
+            device_code: response.device_code,
+            token_endpoint: auth_metadata.token_endpoint,
+            did,
+            interval: Duration::from_secs(response.interval.max(1)),
+            expires_in: Duration::from_secs(response.expires_in),
+        })
+    }
+
+    /// Poll the token endpoint until the user approves the device code
+    pub async fn poll_device_token(
+        &mut self,
+        state: &DeviceFlowState,
+    ) -> Result<TokenResponse, AppError> {
+        let params = [
+            ("grant_type", DEVICE_CODE_GRANT.to_string()),
+            ("device_code", state.device_code.clone()),
+            ("client_id", self.config.client_id.clone()),
+        ];
+
+        let mut poller = DevicePoller::new(state.interval, state.expires_in);
+        while let Some(wait) = poller.next_wait() {
+            tokio::time::sleep(wait).await;
+            let (success, body) = self
+                .post_form_with_dpop(&state.token_endpoint, &params)
+                .await?;
+            if let Some(token) = poller.handle(DevicePollResponse::from_body(success, &body))? {
+                return Ok(token);
+            }
+        }
+        Err(device_code_expired())
+    }
+
+    /// Wait for the device code to be approved and create a session
+    pub async fn complete_device_flow(
+        &mut self,
+        state: &DeviceFlowState,
+    ) -> Result<Session, AppError> {
+        let token_response = self.poll_device_token(state).await?;
+        self.session_from_token(token_response, &state.did, &state.token_endpoint)
+            .await
+    }
+
+    /// POST a form with a DPoP proof, retrying once if the server asks for a nonce
+    ///
+    /// Returns whether the status was a success, and the response body.
+    async fn post_form_with_dpop(
+        &mut self,
+        url: &str,
+        params: &[(&str, String)],
+    ) -> Result<(bool, String), AppError> {
+        let mut retried = false;
+        loop {
+            let dpop_proof = self.dpop.create_proof("POST", url)?;
+            let response = self
+                .client
+                .post(url)
+                .header("DPoP", dpop_proof)
+                .form(params)
+                .send()
+                .await
+                .map_err(|e| AppError::NetworkError(format!("Request to {} failed: {}", url, e)))?;
+
+            // Extract and store DPoP nonce if present
+            if let Some(nonce) = response.headers().get("dpop-nonce") {
+                if let Ok(nonce_str) = nonce.to_str() {
+                    self.dpop.set_nonce(nonce_str.to_string());
+                }
+            }
+
+            let success = response.status().is_success();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            if !success && !retried && body.contains("use_dpop_nonce") {
+                retried = true;
+                continue;
+            }
+            return Ok((success, body));
+        }
+    }
+
     /// Complete the OAuth flow and create a session
     pub async fn complete_flow(
         &mut self,
@@ -814,6 +1136,20 @@ impl AtProtoOAuthManager {
             .exchange_code(code, &state.code_verifier, &state.token_endpoint)
             .await?;
 
+        self.session_from_token(token_response, &state.did, &state.token_endpoint)
+            .await
+    }
+
+    /// Build a session from a token response, resolving the account's handle and PDS
+    ///
+    /// `expected_did` is the DID the flow started with, or empty if the user picked the
+    /// account during authorization. `token_endpoint` is kept for refreshing the session.
+    async fn session_from_token(
+        &self,
+        token_response: TokenResponse,
+        expected_did: &str,
+        token_endpoint: &str,
+    ) -> Result<Session, AppError> {
         // Get DID from token response (mandatory per AT Protocol OAuth spec)
         let did = token_response
             .sub
@@ -824,10 +1160,10 @@ impl AtProtoOAuthManager {
             .clone();
 
         // If we started with a handle, verify the DID matches
-        if !state.did.is_empty() && state.did != did {
+        if !expected_did.is_empty() && expected_did != did {
             return Err(AppError::Authentication(format!(
                 "DID mismatch: expected {}, got {}",
-                state.did, did
+                expected_did, did
             )));
         }
 
@@ -891,6 +1227,11 @@ impl AtProtoOAuthManager {
             expires_at: Some(
                 chrono::Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64),
             ),
+            oauth: Some(OAuthSession {
+                token_endpoint: token_endpoint.to_string(),
+                client_id: self.config.client_id.clone(),
+                dpop_key: self.dpop.export_key(),
+            }),
         };
 
         Ok(session)
@@ -914,3 +1255,24 @@ pub struct BrowserFlowState {
     /// User's PDS URL
     pub pds_url: String,
 }
+
+/// State maintained during the device authorization flow
+#[derive(Debug, Clone)]
+pub struct DeviceFlowState {
+    /// Code the user enters at the verification URL
+    pub user_code: String,
+    /// Where the user approves the login
+    pub verification_uri: String,
+    /// Verification URL with the code already filled in, if the server gives one
+    pub verification_uri_complete: Option<String>,
+    /// Device code to poll the token endpoint with (keep secret)
+    pub device_code: String,
+    /// Token endpoint to poll
+    pub token_endpoint: String,
+    /// User's DID, or empty if the account is chosen during authorization
+    pub did: String,
+    /// Wait between polls
+    pub interval: Duration,
+    /// Lifetime of the device code
+    pub expires_in: Duration,
+}
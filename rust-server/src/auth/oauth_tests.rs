@@ -73,4 +73,152 @@ mod tests {
             .chars()
             .all(|c| c.is_alphanumeric() || c == '_' || c == '-'));
     }
+
+    mod device_flow {
+        use crate::auth::oauth_atproto::{
+            AtProtoOAuthManager, DeviceFlowState, DevicePollResponse, DevicePoller,
+        };
+        use crate::error::AppError;
+        use axum::{http::StatusCode, response::IntoResponse, routing::post, Router};
+        use std::collections::VecDeque;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        const TOKEN: &str = r#"{"access_token":"at","token_type":"DPoP","expires_in":3600,
+            "refresh_token":"rt","sub":"did:plc:alice"}"#;
+
+        fn error(code: &str) -> String {
+            format!(r#"{{"error":"{}"}}"#, code)
+        }
+
+        #[test]
+        fn test_poll_response_classification() {
+            let classify = |success, body: &str| DevicePollResponse::from_body(success, body);
+            let rejected = |code| classify(false, &error(code));
+            assert!(matches!(rejected("authorization_pending"), DevicePollResponse::Pending));
+            assert!(matches!(rejected("slow_down"), DevicePollResponse::SlowDown));
+            assert!(matches!(rejected("expired_token"), DevicePollResponse::Expired));
+            assert!(matches!(rejected("access_denied"), DevicePollResponse::Denied));
+            assert!(matches!(rejected("invalid_grant"), DevicePollResponse::Failed(_)));
+            assert!(matches!(classify(false, "<html>"), DevicePollResponse::Failed(_)));
+            assert!(matches!(classify(true, "{}"), DevicePollResponse::Failed(_)));
+            match classify(true, TOKEN) {
+                DevicePollResponse::Token(token) => {
+                    assert_eq!(token.sub.as_deref(), Some("did:plc:alice"))
+                }
+                other => panic!("expected token, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn test_poller_slows_down_and_expires() {
+            let mut poller = DevicePoller::new(Duration::from_secs(5), Duration::from_secs(22));
+            assert_eq!(poller.next_wait(), Some(Duration::from_secs(5)));
+            assert!(poller.handle(DevicePollResponse::Pending).unwrap().is_none());
+            assert_eq!(poller.next_wait(), Some(Duration::from_secs(5)));
+
+            // slow_down adds five seconds to every later wait
+            assert!(poller.handle(DevicePollResponse::SlowDown).unwrap().is_none());
+            assert_eq!(poller.next_wait(), Some(Duration::from_secs(10)));
+
+            // 20 of 22 seconds used: the next 10 second wait would outlive the code
+            assert_eq!(poller.next_wait(), None);
+        }
+
+        #[test]
+        fn test_poller_stops_on_terminal_errors() {
+            let mut poller = DevicePoller::new(Duration::from_secs(5), Duration::from_secs(600));
+            let expired = poller.handle(DevicePollResponse::Expired).unwrap_err();
+            assert!(expired.message().contains("expired"), "{}", expired.message());
+            let denied = poller.handle(DevicePollResponse::Denied).unwrap_err();
+            assert!(denied.message().contains("denied"), "{}", denied.message());
+            assert!(matches!(
+                poller.handle(DevicePollResponse::Failed("boom".to_string())),
+                Err(AppError::Authentication(_))
+            ));
+        }
+
+        /// Token endpoint answering each poll with the next canned (status, nonce, body)
+        async fn mock_token_endpoint(
+            responses: Vec<(StatusCode, Option<&'static str>, String)>,
+        ) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+            let queue = Arc::new(Mutex::new(VecDeque::from(responses)));
+            let dpop_headers = Arc::new(Mutex::new(Vec::new()));
+            let seen = dpop_headers.clone();
+            let app = Router::new().route(
+                "/token",
+                post(move |headers: axum::http::HeaderMap| {
+                    let queue = queue.clone();
+                    let seen = seen.clone();
+                    async move {
+                        let dpop = headers.get("dpop").and_then(|v| v.to_str().ok());
+                        seen.lock().unwrap().push(dpop.map(str::to_string));
+                        let (status, nonce, body) = queue.lock().unwrap().pop_front().unwrap();
+                        let mut response = (status, body).into_response();
+                        if let Some(nonce) = nonce {
+                            response.headers_mut().insert("dpop-nonce", nonce.parse().unwrap());
+                        }
+                        response
+                    }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/token", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            (url, dpop_headers)
+        }
+
+        fn flow_state(token_endpoint: String) -> DeviceFlowState {
+            DeviceFlowState {
+                user_code: "WDJB-MJHT".to_string(),
+                verification_uri: "https://auth.example/device".to_string(),
+                verification_uri_complete: None,
+                device_code: "device-code".to_string(),
+                token_endpoint,
+                did: String::new(),
+                interval: Duration::ZERO,
+                expires_in: Duration::from_secs(60),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_poll_device_token_until_authorized() {
+            let bad_request = StatusCode::BAD_REQUEST;
+            let (url, dpop_headers) = mock_token_endpoint(vec![
+                (bad_request, None, error("authorization_pending")),
+                (bad_request, Some("nonce-1"), error("use_dpop_nonce")),
+                (bad_request, None, error("authorization_pending")),
+                (StatusCode::OK, None, TOKEN.to_string()),
+            ])
+            .await;
+
+            let mut manager = AtProtoOAuthManager::new().unwrap();
+            let token = manager.poll_device_token(&flow_state(url)).await.unwrap();
+            assert_eq!(token.access_token, "at");
+            assert_eq!(token.refresh_token.as_deref(), Some("rt"));
+
+            // Every poll carried a DPoP proof
+            let dpop_headers = dpop_headers.lock().unwrap();
+            assert_eq!(dpop_headers.len(), 4);
+            assert!(dpop_headers.iter().all(Option::is_some));
+        }
+
+        #[tokio::test]
+        async fn test_poll_device_token_reports_expiry_and_denial() {
+            let bad_request = StatusCode::BAD_REQUEST;
+            let (url, _) = mock_token_endpoint(vec![
+                (bad_request, None, error("authorization_pending")),
+                (bad_request, None, error("expired_token")),
+            ])
+            .await;
+            let mut manager = AtProtoOAuthManager::new().unwrap();
+            let err = manager.poll_device_token(&flow_state(url)).await.unwrap_err();
+            assert!(err.message().contains("expired"), "{}", err.message());
+
+            let (url, _) =
+                mock_token_endpoint(vec![(bad_request, None, error("access_denied"))]).await;
+            let err = manager.poll_device_token(&flow_state(url)).await.unwrap_err();
+            assert!(err.message().contains("denied"), "{}", err.message());
+        }
+    }
 }
@@ -1,9 +1,29 @@
 //! Session management for authenticated BlueSky sessions
 
-use crate::auth::{AuthError, Credentials};
+use crate::auth::{AtProtoOAuthManager, AuthError, AuthType, CredentialStorage, Credentials};
 use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tracing::{debug, warn};
+
+/// How long before expiry a stored session is refreshed, unless configured otherwise
+pub const DEFAULT_REFRESH_BUFFER_SECS: u64 = 5 * 60;
+
+static REFRESH_BUFFER: OnceLock<Duration> = OnceLock::new();
+
+/// Configure the refresh buffer from the CLI value.
+/// Call once at startup; a second call keeps the first configuration.
+pub fn init_refresh_buffer(seconds: Option<u64>) {
+    let seconds = seconds.unwrap_or(DEFAULT_REFRESH_BUFFER_SECS);
+    let _ = REFRESH_BUFFER.set(Duration::seconds(seconds.min(i64::MAX as u64) as i64));
+}
+
+/// The configured refresh buffer (five minutes if none was configured)
+pub fn refresh_buffer() -> Duration {
+    *REFRESH_BUFFER.get_or_init(|| Duration::seconds(DEFAULT_REFRESH_BUFFER_SECS as i64))
+}
 
 /// Session data from com.atproto.server.createSession
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +47,25 @@ pub struct Session {
     /// Token expiration time (calculated from creation)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
+
+    /// How to refresh an OAuth session; app password sessions refresh through the PDS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth: Option<OAuthSession>,
+}
+
+/// What an OAuth session needs to refresh its tokens at the authorization server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthSession {
+    /// Token endpoint of the authorization server that issued the tokens
+    pub token_endpoint: String,
+
+    /// Client ID the tokens were issued to
+    pub client_id: String,
+
+    /// DPoP private key the tokens are bound to (base64url P-256 scalar). Kept with the
+    /// account's credentials, never in the stored session.
+    #[serde(skip)]
+    pub dpop_key: String,
 }
 
 fn default_service() -> String {
@@ -34,18 +73,11 @@ fn default_service() -> String {
 }
 
 impl Session {
-    /// Check if the access token is expired or will expire soon (within 5 minutes)
-    /// Used by token refresh logic when OAuth is enabled
-    #[allow(dead_code)]
-    pub fn is_expired(&self) -> bool {
-        if let Some(expires_at) = self.expires_at {
-            // Consider expired if within 5 minutes of expiry
-            let now = Utc::now();
-            now >= expires_at - Duration::minutes(5)
-        } else {
-            // If no expiry time set, assume 2 hour lifetime from AT Protocol spec
-            false
-        }
+    /// Check if the access token lapses within `buffer` from now.
+    /// A session without an expiry time is never considered expiring.
+    pub fn expires_within(&self, buffer: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Utc::now() >= expires_at - buffer)
     }
 }
 
@@ -64,8 +96,6 @@ struct CreateSessionResponse {
 }
 
 /// Response from com.atproto.server.refreshSession
-/// All fields needed for token refresh functionality when OAuth is enabled
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RefreshSessionResponse {
@@ -74,6 +104,7 @@ struct RefreshSessionResponse {
     handle: String,
     did: String,
     #[serde(default)]
+    #[allow(dead_code)]
     did_doc: Option<serde_json::Value>,
 }
 
@@ -105,21 +136,26 @@ impl SessionManager {
             .client
             .post(&url)
             .json(&body)
-            .send()
+            .send_xrpc()
             .await
             .map_err(|e| AppError::NetworkError(format!("Login request failed: {}", e)))?;
 
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
         let status = response.status();
         if !status.is_success() {
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AuthError::AuthenticationFailed(format!(
-                "Login failed with status {}: {}",
-                status, error_text
-            ))
-            .into());
+            let message = format!("Login failed with status {}: {}", status, error_text);
+            // A server failure says nothing about the credentials
+            if status.is_server_error() {
+                return Err(AppError::NetworkError(message));
+            }
+            return Err(AuthError::AuthenticationFailed(message).into());
         }
 
         let session_response: CreateSessionResponse = response.json().await.map_err(|e| {
@@ -136,23 +172,31 @@ impl SessionManager {
             did: session_response.did,
             service: credentials.service.clone(),
             expires_at: Some(expires_at),
+            oauth: None,
         })
     }
 
-    /// Refresh an existing session using the refresh token
-    /// Will be used for automatic token refresh when OAuth is enabled
-    #[allow(dead_code)]
+    /// Refresh an existing session using the refresh token: at the OAuth token endpoint
+    /// for OAuth sessions, through `refreshSession` on the PDS otherwise
     pub async fn refresh(&self, session: &Session) -> Result<Session, AppError> {
+        if let Some(oauth) = &session.oauth {
+            return AtProtoOAuthManager::refresh_session(session, oauth).await;
+        }
+
         let url = format!("{}/xrpc/com.atproto.server.refreshSession", session.service);
 
         let response = self
             .client
             .post(&url)
             .header("Authorization", format!("Bearer {}", session.refresh_jwt))
-            .send()
+            .send_xrpc()
             .await
             .map_err(|e| AppError::NetworkError(format!("Refresh request failed: {}", e)))?;
 
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
         let status = response.status();
         if !status.is_success() {
             let error_text = response
@@ -180,18 +224,85 @@ impl SessionManager {
             did: refresh_response.did,
             service: session.service.clone(),
             expires_at: Some(expires_at),
+            oauth: None,
         })
     }
 
-    /// Get a valid session, refreshing if necessary
-    /// Will be used for automatic token refresh when OAuth is enabled
-    #[allow(dead_code)]
-    pub async fn get_valid_session(&self, session: &Session) -> Result<Session, AppError> {
-        if session.is_expired() {
-            self.refresh(session).await
-        } else {
-            Ok(session.clone())
+    /// Get a session for a stored account that stays valid for at least `buffer`.
+    ///
+    /// A stored session lapsing sooner is refreshed; one that cannot be refreshed is
+    /// still used while it has not expired, and replaced by a fresh login once it has.
+    /// OAuth sessions only refresh at their token endpoint: once that fails after expiry,
+    /// the user has to log in again. New and refreshed sessions are persisted for the
+    /// next call.
+    pub async fn session_for(
+        &self,
+        storage: &CredentialStorage,
+        handle: &str,
+        buffer: Duration,
+    ) -> Result<Session, AppError> {
+        let Some(session) = storage.get_session(handle)? else {
+            // App password accounts without a session yet
+            debug!("No stored session, creating new session for {}", handle);
+            return self.login_and_store(storage, handle).await;
+        };
+
+        if !session.expires_within(buffer) {
+            debug!("Using stored session for {}", handle);
+            return Ok(session);
         }
+
+        debug!("Session for {} expires within {}s, refreshing", handle, buffer.num_seconds());
+        match self.refresh(&session).await {
+            Ok(refreshed) => {
+                store_session(storage, handle, &refreshed);
+                Ok(refreshed)
+            }
+            Err(e) if !session.expires_within(Duration::zero()) => {
+                warn!("Could not refresh session for {}, using it until expiry: {}", handle, e);
+                Ok(session)
+            }
+            Err(e) => {
+                warn!("Could not refresh expired session for {}: {}", handle, e);
+                if session.oauth.is_some() {
+                    return Err(oauth_login_again(handle));
+                }
+                self.login_and_store(storage, handle).await
+            }
+        }
+    }
+
+    /// Log in with the stored app password. OAuth accounts hold a refresh token instead,
+    /// which must never reach createSession.
+    async fn login_and_store(
+        &self,
+        storage: &CredentialStorage,
+        handle: &str,
+    ) -> Result<Session, AppError> {
+        let credentials = storage.get_credentials(handle)?;
+        if credentials.auth_type == AuthType::OAuth {
+            return Err(oauth_login_again(handle));
+        }
+        let session = self.login(&credentials).await?;
+        store_session(storage, handle, &session);
+        Ok(session)
+    }
+}
+
+/// The error for an OAuth account whose session can no longer be refreshed
+fn oauth_login_again(handle: &str) -> AppError {
+    AuthError::AuthenticationFailed(format!(
+        "The OAuth session for @{} has expired and could not be refreshed. \
+         Run 'autoreply login' to log in again",
+        handle
+    ))
+    .into()
+}
+
+/// Persist a session, logging rather than failing the call if storage refuses it
+fn store_session(storage: &CredentialStorage, handle: &str, session: &Session) {
+    if let Err(e) = storage.store_session(handle, session.clone()) {
+        warn!("Failed to store session for {}: {}", handle, e);
     }
 }
 
@@ -214,9 +325,10 @@ mod tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: None,
+            oauth: None,
         };
 
-        assert!(!session.is_expired());
+        assert!(!session.expires_within(Duration::minutes(5)));
     }
 
     #[test]
@@ -228,9 +340,10 @@ mod tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() - Duration::hours(1)),
+            oauth: None,
         };
 
-        assert!(session.is_expired());
+        assert!(session.expires_within(Duration::minutes(5)));
     }
 
     #[test]
@@ -242,9 +355,10 @@ mod tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() + Duration::hours(1)),
+            oauth: None,
         };
 
-        assert!(!session.is_expired());
+        assert!(!session.expires_within(Duration::minutes(5)));
     }
 
     #[test]
@@ -256,10 +370,11 @@ mod tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now() + Duration::minutes(3)),
+            oauth: None,
         };
 
         // Should be considered expired if within 5 minutes
-        assert!(session.is_expired());
+        assert!(session.expires_within(Duration::minutes(5)));
     }
 
     #[test]
@@ -271,6 +386,7 @@ mod tests {
             did: "did:plc:test".to_string(),
             service: "https://bsky.social".to_string(),
             expires_at: Some(Utc::now()),
+            oauth: None,
         };
 
         let json = serde_json::to_string(&session).unwrap();
@@ -278,4 +394,293 @@ mod tests {
         assert_eq!(session.handle, deserialized.handle);
         assert_eq!(session.did, deserialized.did);
     }
+
+    mod proactive_refresh {
+        use super::*;
+        use axum::{routing::post, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// A PDS whose refreshSession hands out new tokens, counting the calls
+        async fn mock_pds() -> (String, Arc<AtomicUsize>) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let counter = calls.clone();
+            let app = Router::new().route(
+                "/xrpc/com.atproto.server.refreshSession",
+                post(move || {
+                    let counter = counter.clone();
+                    async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Json(serde_json::json!({
+                            "accessJwt": "new-access",
+                            "refreshJwt": "new-refresh",
+                            "handle": "alice.test",
+                            "did": "did:plc:alice"
+                        }))
+                    }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            (url, calls)
+        }
+
+        fn stored_session(
+            dir: &tempfile::TempDir,
+            service: &str,
+            expires_in: Duration,
+        ) -> CredentialStorage {
+            let storage = CredentialStorage::with_file(dir.path().join("credentials.json"));
+            storage
+                .store_credentials("alice.test", Credentials::new("alice.test", "app-password"))
+                .unwrap();
+            let session = Session {
+                access_jwt: "old-access".to_string(),
+                refresh_jwt: "old-refresh".to_string(),
+                handle: "alice.test".to_string(),
+                did: "did:plc:alice".to_string(),
+                service: service.to_string(),
+                expires_at: Some(Utc::now() + expires_in),
+                oauth: None,
+            };
+            storage.store_session("alice.test", session).unwrap();
+            storage
+        }
+
+        #[tokio::test]
+        async fn refreshes_session_expiring_within_buffer() {
+            let (url, calls) = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let storage = stored_session(&dir, &url, Duration::seconds(30));
+
+            let session = SessionManager::new()
+                .unwrap()
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap();
+
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+            assert_eq!(session.access_jwt, "new-access");
+            assert!(!session.expires_within(Duration::minutes(5)));
+
+            // The new tokens replace the stored ones
+            let stored = storage.get_session("alice.test").unwrap().unwrap();
+            assert_eq!(stored.refresh_jwt, "new-refresh");
+        }
+
+        #[tokio::test]
+        async fn leaves_fresh_session_untouched() {
+            let (url, calls) = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let storage = stored_session(&dir, &url, Duration::hours(1));
+
+            let session = SessionManager::new()
+                .unwrap()
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap();
+
+            assert_eq!(calls.load(Ordering::SeqCst), 0);
+            assert_eq!(session.access_jwt, "old-access");
+        }
+
+        #[tokio::test]
+        async fn buffer_decides_when_to_refresh() {
+            let (url, calls) = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let storage = stored_session(&dir, &url, Duration::seconds(30));
+
+            let session = SessionManager::new()
+                .unwrap()
+                .session_for(&storage, "alice.test", Duration::seconds(10))
+                .await
+                .unwrap();
+
+            assert_eq!(calls.load(Ordering::SeqCst), 0);
+            assert_eq!(session.access_jwt, "old-access");
+        }
+
+        /// Storage holding an OAuth account on `service`, its DPoP key and a session
+        /// lapsing in `expires_in`
+        fn oauth_session(
+            dir: &tempfile::TempDir,
+            service: &str,
+            token_endpoint: &str,
+            dpop_key: &str,
+            expires_in: Duration,
+        ) -> CredentialStorage {
+            let storage = stored_session(dir, service, expires_in);
+            let credentials = Credentials::oauth("did:plc:alice", "old-refresh", service, dpop_key);
+            let mut session = storage.get_session("alice.test").unwrap().unwrap();
+            storage.store_credentials("alice.test", credentials).unwrap();
+            session.oauth = Some(OAuthSession {
+                token_endpoint: token_endpoint.to_string(),
+                client_id: "http://localhost".to_string(),
+                dpop_key: dpop_key.to_string(),
+            });
+            storage.store_session("alice.test", session).unwrap();
+            storage
+        }
+
+        #[tokio::test]
+        async fn refreshes_oauth_session_at_token_endpoint() {
+            use crate::auth::oauth_atproto::DPoPManager;
+            use axum::extract::State;
+            use axum::http::HeaderMap;
+            use axum::Form;
+            use std::collections::HashMap;
+            use std::sync::Mutex;
+
+            // An authorization server recording each refresh form and its DPoP proof
+            type Requests = Arc<Mutex<Vec<(HashMap<String, String>, String)>>>;
+            let requests: Requests = Arc::default();
+            let app = Router::new()
+                .route(
+                    "/oauth/token",
+                    post(
+                        |State(requests): State<Requests>,
+                         headers: HeaderMap,
+                         Form(form): Form<HashMap<String, String>>| async move {
+                            let proof = headers["DPoP"].to_str().unwrap().to_string();
+                            requests.lock().unwrap().push((form, proof));
+                            Json(serde_json::json!({
+                                "access_token": "oauth-access",
+                                "token_type": "DPoP",
+                                "expires_in": 3600,
+                                "refresh_token": "oauth-refresh",
+                                "sub": "did:plc:alice"
+                            }))
+                        },
+                    ),
+                )
+                .with_state(requests.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            let dir = tempfile::tempdir().unwrap();
+            let key = DPoPManager::new().unwrap().export_key();
+            let token_endpoint = format!("{}/oauth/token", url);
+            let storage = oauth_session(
+                &dir,
+                "https://pds.example",
+                &token_endpoint,
+                &key,
+                Duration::seconds(30),
+            );
+
+            let session = SessionManager::new()
+                .unwrap()
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap();
+
+            assert_eq!(session.access_jwt, "oauth-access");
+            assert_eq!(session.refresh_jwt, "oauth-refresh");
+            assert_eq!(session.service, "https://pds.example");
+            assert!(!session.expires_within(Duration::minutes(5)));
+            let stored = storage.get_session("alice.test").unwrap().unwrap();
+            assert_eq!(stored.refresh_jwt, "oauth-refresh");
+            assert_eq!(stored.oauth.unwrap().dpop_key, key);
+
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests.len(), 1);
+            let (form, proof) = &requests[0];
+            assert_eq!(form["grant_type"], "refresh_token");
+            assert_eq!(form["refresh_token"], "old-refresh");
+            assert_eq!(form["client_id"], "http://localhost");
+
+            // The proof carries the key the tokens are bound to, not a fresh one
+            let jwk = |proof: &str| jsonwebtoken::decode_header(proof).unwrap().jwk;
+            let bound = DPoPManager::from_key(&key).unwrap().create_proof("POST", &url).unwrap();
+            let fresh = DPoPManager::new().unwrap().create_proof("POST", &url).unwrap();
+            assert_eq!(jwk(proof), jwk(&bound));
+            assert_ne!(jwk(proof), jwk(&fresh));
+        }
+
+        #[tokio::test]
+        async fn expired_oauth_session_asks_to_log_in_again() {
+            // A PDS counting createSession calls, which must never see the refresh token
+            let calls = Arc::new(AtomicUsize::new(0));
+            let counter = calls.clone();
+            let app = Router::new().route(
+                "/xrpc/com.atproto.server.createSession",
+                post(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    async { axum::http::StatusCode::UNAUTHORIZED }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            let dir = tempfile::tempdir().unwrap();
+            let key = crate::auth::oauth_atproto::DPoPManager::new().unwrap().export_key();
+            // The token endpoint is missing, so the refresh fails
+            let token_endpoint = format!("{}/oauth/token", url);
+            let storage = oauth_session(&dir, &url, &token_endpoint, &key, Duration::seconds(-30));
+            let manager = SessionManager::new().unwrap();
+
+            let err = manager
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Authentication(_)), "{:?}", err);
+            assert!(err.to_string().contains("log in again"), "{}", err);
+
+            // Without a stored session, an OAuth account is not logged in with its token
+            // either; storing the credentials again drops the session
+            let credentials = storage.get_credentials("alice.test").unwrap();
+            storage.store_credentials("alice.test", credentials).unwrap();
+            assert!(storage.get_session("alice.test").unwrap().is_none());
+            let err = manager
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("log in again"), "{}", err);
+            assert_eq!(calls.load(Ordering::SeqCst), 0);
+        }
+
+        #[test]
+        fn dpop_key_is_kept_out_of_the_stored_session() {
+            let dir = tempfile::tempdir().unwrap();
+            let storage = oauth_session(
+                &dir,
+                "https://pds.example",
+                "https://auth.example/token",
+                "secret-key",
+                Duration::hours(1),
+            );
+
+            let stored = std::fs::read(dir.path().join("credentials.json")).unwrap();
+            let file: crate::auth::file_crypto::EncryptedFile =
+                serde_json::from_slice(&stored).unwrap();
+            let plain = crate::auth::file_crypto::decrypt(&file, "test passphrase").unwrap();
+            let plain: serde_json::Value = serde_json::from_slice(&plain).unwrap();
+            let account = &plain["accounts"]["alice.test"];
+            assert!(account["session"]["oauth"].get("dpop_key").is_none(), "{}", account);
+            assert_eq!(account["credentials"]["dpop_key"], "secret-key");
+
+            // Reading the session brings the key back from the credentials
+            let session = storage.get_session("alice.test").unwrap().unwrap();
+            assert_eq!(session.oauth.unwrap().dpop_key, "secret-key");
+        }
+
+        #[tokio::test]
+        async fn keeps_unexpired_session_when_refresh_fails() {
+            let (url, calls) = mock_pds().await;
+            let dir = tempfile::tempdir().unwrap();
+            let storage = stored_session(&dir, &format!("{}/missing", url), Duration::seconds(30));
+
+            let session = SessionManager::new()
+                .unwrap()
+                .session_for(&storage, "alice.test", Duration::minutes(5))
+                .await
+                .unwrap();
+
+            assert_eq!(calls.load(Ordering::SeqCst), 0);
+            assert_eq!(session.access_jwt, "old-access");
+        }
+    }
 }
@@ -1,6 +1,7 @@
 //! Credential storage with keyring and file fallback
 
-use crate::auth::{AuthError, Credentials, Session};
+use crate::auth::file_crypto::{self, EncryptedFile};
+use crate::auth::{AuthError, AuthType, Credentials, Session};
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -18,6 +19,14 @@ pub enum StorageBackend {
     File,
 }
 
+/// Public details of a stored account, without any secrets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+    pub handle: String,
+    pub did: Option<String>,
+    pub auth_type: AuthType,
+}
+
 /// Stored account data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredAccount {
@@ -37,6 +46,10 @@ struct FileStorage {
 pub struct CredentialStorage {
     backend: StorageBackend,
     file_path: Option<PathBuf>,
+    /// Passphrase for the encrypted file; the process-wide one when unset
+    passphrase: Option<String>,
+    /// PBKDF2 rounds used when writing the encrypted file
+    iterations: u32,
 }
 
 impl CredentialStorage {
@@ -47,14 +60,33 @@ impl CredentialStorage {
             Ok(Self {
                 backend: StorageBackend::Keyring,
                 file_path: None,
+                passphrase: None,
+                iterations: file_crypto::DEFAULT_ITERATIONS,
             })
         } else {
             // Fall back to file storage
-            let file_path = Self::get_storage_file_path()?;
-            Ok(Self {
-                backend: StorageBackend::File,
-                file_path: Some(file_path),
-            })
+            Self::file_fallback()
+        }
+    }
+
+    /// File storage at the default path, used when the keyring is unavailable
+    fn file_fallback() -> Result<Self, AppError> {
+        Ok(Self {
+            backend: StorageBackend::File,
+            file_path: Some(Self::get_storage_file_path()?),
+            passphrase: None,
+            iterations: file_crypto::DEFAULT_ITERATIONS,
+        })
+    }
+
+    /// Create a file-backed storage at a given path, encrypted with a test passphrase
+    #[cfg(test)]
+    pub(crate) fn with_file(path: PathBuf) -> Self {
+        Self {
+            backend: StorageBackend::File,
+            file_path: Some(path),
+            passphrase: Some("test passphrase".to_string()),
+            iterations: file_crypto::MIN_ITERATIONS,
         }
     }
 
@@ -92,8 +124,37 @@ impl CredentialStorage {
             AppError::ConfigError(format!("Failed to read credentials file: {}", e))
         })?;
 
-        serde_json::from_str(&contents)
-            .map_err(|e| AppError::ConfigError(format!("Failed to parse credentials file: {}", e)))
+        if let Ok(encrypted) = serde_json::from_str::<EncryptedFile>(&contents) {
+            let plaintext = match file_crypto::decrypt(&encrypted, &self.passphrase(false)?) {
+                Ok(plaintext) => plaintext,
+                Err(e) => {
+                    // Ask again next time rather than repeating a mistyped passphrase
+                    if self.passphrase.is_none() {
+                        file_crypto::forget_passphrase();
+                    }
+                    return Err(e);
+                }
+            };
+            return serde_json::from_slice(&plaintext).map_err(|e| {
+                AppError::ConfigError(format!("Failed to parse credentials file: {}", e))
+            });
+        }
+
+        // Files written before encryption are plaintext: read once, then rewrite encrypted
+        let storage: FileStorage = serde_json::from_str(&contents).map_err(|e| {
+            AppError::ConfigError(format!("Failed to parse credentials file: {}", e))
+        })?;
+        self.write_file_storage(&storage)?;
+        tracing::info!("Encrypted the plaintext credentials file {}", path.display());
+        Ok(storage)
+    }
+
+    /// Passphrase for the encrypted credentials file, asked for twice when `confirm`
+    fn passphrase(&self, confirm: bool) -> Result<String, AppError> {
+        match &self.passphrase {
+            Some(passphrase) => Ok(passphrase.clone()),
+            None => file_crypto::passphrase(confirm),
+        }
     }
 
     /// Write file storage
@@ -103,7 +164,14 @@ impl CredentialStorage {
             .as_ref()
             .ok_or_else(|| AppError::ConfigError("No file path set".to_string()))?;
 
-        let contents = serde_json::to_string_pretty(storage).map_err(|e| {
+        let plaintext = serde_json::to_vec(storage).map_err(|e| {
+            AppError::ConfigError(format!("Failed to serialize credentials: {}", e))
+        })?;
+        // Every write follows a read, which has already taken the passphrase of an encrypted
+        // file: one still unknown here seals a new file, so it is confirmed
+        let passphrase = self.passphrase(true)?;
+        let encrypted = file_crypto::encrypt(&plaintext, &passphrase, self.iterations)?;
+        let contents = serde_json::to_string_pretty(&encrypted).map_err(|e| {
             AppError::ConfigError(format!("Failed to serialize credentials: {}", e))
         })?;
 
@@ -152,7 +220,7 @@ impl CredentialStorage {
                     AppError::ConfigError(format!("Platform secure storage failure: {}", e))
                 })?;
 
-                Ok(())
+                self.update_account_list(handle, true)
             }
             StorageBackend::File => {
                 let mut storage = self.read_file_storage()?;
@@ -179,10 +247,7 @@ impl CredentialStorage {
             Err(e) if self.backend == StorageBackend::Keyring => {
                 // If keyring fails, try file storage
                 tracing::warn!("Keyring failed ({}), falling back to file storage", e);
-                let file_storage = Self {
-                    backend: StorageBackend::File,
-                    file_path: Some(Self::get_storage_file_path()?),
-                };
+                let file_storage = Self::file_fallback()?;
                 file_storage.store_credentials(handle, credentials)
             }
             Err(e) => Err(e),
@@ -238,10 +303,7 @@ impl CredentialStorage {
                             "Keyring session storage failed: {}, falling back to file storage",
                             e
                         );
-                        let file_storage = Self {
-                            backend: StorageBackend::File,
-                            file_path: Some(Self::get_storage_file_path()?),
-                        };
+                        let file_storage = Self::file_fallback()?;
                         file_storage.store_session(handle, session)
                     }
                 }
@@ -259,8 +321,6 @@ impl CredentialStorage {
     }
 
     /// Retrieve session for an account
-    /// Will be used for token refresh when OAuth is enabled
-    #[allow(dead_code)]
     pub fn get_session(&self, handle: &str) -> Result<Option<Session>, AppError> {
         match self.backend {
             StorageBackend::Keyring => {
@@ -274,21 +334,38 @@ impl CredentialStorage {
                         let session = serde_json::from_str(&data).map_err(|e| {
                             AppError::ConfigError(format!("Failed to parse session: {}", e))
                         })?;
-                        Ok(Some(session))
+                        Ok(Some(self.with_dpop_key(handle, session)))
                     }
                     Err(_) => Ok(None),
                 }
             }
             StorageBackend::File => {
                 let storage = self.read_file_storage()?;
-                Ok(storage
-                    .accounts
-                    .get(handle)
-                    .and_then(|account| account.session.clone()))
+                let account = storage.accounts.get(handle);
+                Ok(account.and_then(|account| {
+                    let mut session = account.session.clone()?;
+                    if let Some(oauth) = session.oauth.as_mut() {
+                        oauth.dpop_key = account.credentials.dpop_key.clone().unwrap_or_default();
+                    }
+                    Some(session)
+                }))
             }
         }
     }
 
+    /// Attach the DPoP key of an OAuth session, which is stored with the credentials
+    /// rather than in the session. Without it the session cannot be refreshed.
+    fn with_dpop_key(&self, handle: &str, mut session: Session) -> Session {
+        if let Some(oauth) = session.oauth.as_mut() {
+            oauth.dpop_key = self
+                .get_credentials(handle)
+                .ok()
+                .and_then(|credentials| credentials.dpop_key)
+                .unwrap_or_default();
+        }
+        session
+    }
+
     /// Delete credentials for an account
     pub fn delete_credentials(&self, handle: &str) -> Result<(), AppError> {
         match self.backend {
@@ -374,18 +451,33 @@ impl CredentialStorage {
         Ok(())
     }
 
-    /// Store credentials and update account list
-    #[allow(dead_code)]
-    pub fn add_account(&self, handle: &str, credentials: Credentials) -> Result<(), AppError> {
-        self.store_credentials(handle, credentials)?;
-        self.update_account_list(handle, true)?;
-        Ok(())
+    /// Describe a stored account: its DID and how it authenticates
+    pub fn account_info(&self, handle: &str) -> Result<AccountInfo, AppError> {
+        let credentials = self.get_credentials(handle)?;
+        let session = self.get_session(handle)?;
+        let did = match session {
+            Some(session) => Some(session.did),
+            None => credentials.did.clone().or_else(|| {
+                Some(credentials.identifier.clone()).filter(|id| id.starts_with("did:"))
+            }),
+        };
+
+        Ok(AccountInfo {
+            handle: handle.to_string(),
+            did,
+            auth_type: credentials.auth_type,
+        })
     }
 
-    /// Delete credentials and update account list
-    pub fn remove_account(&self, handle: &str) -> Result<(), AppError> {
+    /// Remove an account's credentials and session, clearing it as the default
+    pub fn remove(&self, handle: &str) -> Result<(), AppError> {
+        self.get_credentials(handle)?;
         self.delete_credentials(handle)?;
         self.update_account_list(handle, false)?;
+
+        if self.get_default_account()?.as_deref() == Some(handle) {
+            self.clear_default_account()?;
+        }
         Ok(())
     }
 
@@ -427,10 +519,7 @@ impl CredentialStorage {
                             "Keyring set_default_account failed: {}, falling back to file storage",
                             e
                         );
-                        let file_storage = Self {
-                            backend: StorageBackend::File,
-                            file_path: Some(Self::get_storage_file_path()?),
-                        };
+                        let file_storage = Self::file_fallback()?;
                         let mut storage = file_storage.read_file_storage()?;
                         storage.default_account = Some(handle.to_string());
                         file_storage.write_file_storage(&storage)
@@ -445,12 +534,76 @@ impl CredentialStorage {
         }
     }
 
+    /// Resolve which account a tool call acts as: the requested one, else the config
+    /// file's `account`, else the default account, else the only stored account
+    pub fn resolve_account(&self, requested: Option<&str>) -> Result<String, AppError> {
+        let requested = requested.or(crate::config::config().account.as_deref());
+        if requested.is_some() {
+            return select_account(requested, &[], None);
+        }
+        let default = self.get_default_account()?;
+        select_account(None, &self.list_accounts()?, default.as_deref())
+    }
+
+    /// Forget the default account
+    fn clear_default_account(&self) -> Result<(), AppError> {
+        match self.backend {
+            StorageBackend::Keyring => {
+                let entry =
+                    keyring::Entry::new(SERVICE_NAME, DEFAULT_ACCOUNT_KEY).map_err(|e| {
+                        AppError::ConfigError(format!("Failed to create keyring entry: {}", e))
+                    })?;
+                let _ = entry.delete_password(); // Ignore errors if not set
+                Ok(())
+            }
+            StorageBackend::File => {
+                let mut storage = self.read_file_storage()?;
+                storage.default_account = None;
+                self.write_file_storage(&storage)
+            }
+        }
+    }
+
     /// Get the storage backend type
     pub fn backend(&self) -> StorageBackend {
         self.backend
     }
 }
 
+/// Pick an account from the stored ones, failing with the list of choices when
+/// several are stored and neither a request nor a default narrows them down
+fn select_account(
+    requested: Option<&str>,
+    accounts: &[String],
+    default: Option<&str>,
+) -> Result<String, AppError> {
+    if let Some(account) = requested {
+        let account = account.trim();
+        if account.is_empty() {
+            return Err(AppError::InvalidInput("Account cannot be empty".to_string()));
+        }
+        return Ok(account.to_string());
+    }
+    if let Some(default) = default {
+        return Ok(default.to_string());
+    }
+
+    match accounts {
+        [] => Err(AppError::Authentication(
+            "No stored accounts. Run 'autoreply login' first".to_string(),
+        )),
+        [only] => Ok(only.clone()),
+        _ => {
+            let mut choices = accounts.to_vec();
+            choices.sort();
+            Err(AppError::InvalidInput(format!(
+                "Multiple accounts are stored, choose one with --account: {}",
+                choices.join(", ")
+            )))
+        }
+    }
+}
+
 impl Default for CredentialStorage {
     fn default() -> Self {
         Self::new().expect("Failed to create CredentialStorage")
@@ -477,4 +630,176 @@ mod tests {
         assert!(path.to_string_lossy().contains("autoreply"));
         assert!(path.to_string_lossy().ends_with("credentials.json"));
     }
+
+    fn stored(handles: &[&str]) -> Vec<String> {
+        handles.iter().map(|h| h.to_string()).collect()
+    }
+
+    #[test]
+    fn test_select_account_explicit() {
+        let accounts = stored(&["alice.bsky.social", "bob.bsky.social"]);
+        let selected =
+            select_account(Some(" bob.bsky.social "), &accounts, Some("alice.bsky.social"));
+        assert_eq!(selected.unwrap(), "bob.bsky.social");
+        assert!(matches!(
+            select_account(Some(" "), &accounts, None),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_account_default() {
+        let accounts = stored(&["alice.bsky.social", "bob.bsky.social"]);
+        let selected = select_account(None, &accounts, Some("bob.bsky.social"));
+        assert_eq!(selected.unwrap(), "bob.bsky.social");
+
+        let single = stored(&["alice.bsky.social"]);
+        assert_eq!(select_account(None, &single, None).unwrap(), "alice.bsky.social");
+
+        assert!(matches!(select_account(None, &[], None), Err(AppError::Authentication(_))));
+    }
+
+    #[test]
+    fn test_select_account_ambiguous() {
+        let accounts = stored(&["bob.bsky.social", "alice.bsky.social"]);
+        match select_account(None, &accounts, None) {
+            Err(AppError::InvalidInput(msg)) => {
+                assert!(msg.contains("--account"));
+                assert!(msg.contains("alice.bsky.social, bob.bsky.social"));
+            }
+            other => panic!("expected an ambiguity error, got {:?}", other),
+        }
+    }
+
+    fn file_storage(dir: &tempfile::TempDir) -> CredentialStorage {
+        CredentialStorage::with_file(dir.path().join("credentials.json"))
+    }
+
+    fn oauth_session(handle: &str, did: &str) -> Session {
+        Session {
+            access_jwt: "access".to_string(),
+            refresh_jwt: "ref-token".to_string(),
+            handle: handle.to_string(),
+            did: did.to_string(),
+            service: crate::auth::DEFAULT_SERVICE.to_string(),
+            expires_at: None,
+            oauth: None,
+        }
+    }
+
+    #[test]
+    fn test_file_backend_lists_account_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = file_storage(&dir);
+        assert!(storage.list_accounts().unwrap().is_empty());
+
+        storage
+            .store_credentials("alice.test", Credentials::new("alice.test", "abcd-efgh-ijkl-mnop"))
+            .unwrap();
+        storage
+            .store_credentials(
+                "bob.test",
+                Credentials::oauth(
+                    "did:plc:bob",
+                    "ref-token",
+                    crate::auth::DEFAULT_SERVICE,
+                    "dpop-key",
+                ),
+            )
+            .unwrap();
+        storage.store_session("bob.test", oauth_session("bob.test", "did:plc:bob")).unwrap();
+
+        let mut accounts = storage.list_accounts().unwrap();
+        accounts.sort();
+        assert_eq!(accounts, stored(&["alice.test", "bob.test"]));
+
+        let alice = storage.account_info("alice.test").unwrap();
+        assert_eq!(alice.auth_type, AuthType::AppPassword);
+        assert_eq!(alice.did, None);
+
+        let bob = storage.account_info("bob.test").unwrap();
+        assert_eq!(bob.auth_type, AuthType::OAuth);
+        assert_eq!(bob.did.as_deref(), Some("did:plc:bob"));
+    }
+
+    #[test]
+    fn test_file_backend_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = file_storage(&dir);
+        storage
+            .store_credentials("alice.test", Credentials::new("alice.test", "abcd-efgh-ijkl-mnop"))
+            .unwrap();
+        storage
+            .store_credentials("bob.test", Credentials::new("bob.test", "qrst-uvwx-yzab-cdef"))
+            .unwrap();
+        storage.set_default_account("alice.test").unwrap();
+
+        storage.remove("alice.test").unwrap();
+        assert_eq!(storage.list_accounts().unwrap(), stored(&["bob.test"]));
+        assert_eq!(storage.get_default_account().unwrap(), None);
+        assert!(storage.get_credentials("alice.test").is_err());
+
+        assert!(matches!(storage.remove("alice.test"), Err(AppError::Authentication(_))));
+        assert_eq!(storage.list_accounts().unwrap(), stored(&["bob.test"]));
+    }
+
+    #[test]
+    fn test_file_backend_is_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = file_storage(&dir);
+        storage
+            .store_credentials("alice.test", Credentials::new("alice.test", "abcd-efgh-ijkl-mnop"))
+            .unwrap();
+
+        let contents = fs::read_to_string(dir.path().join("credentials.json")).unwrap();
+        assert!(!contents.contains("alice.test"));
+        assert!(!contents.contains("abcd-efgh-ijkl-mnop"));
+
+        let reopened = file_storage(&dir);
+        let credentials = reopened.get_credentials("alice.test").unwrap();
+        assert_eq!(credentials.password, "abcd-efgh-ijkl-mnop");
+    }
+
+    #[test]
+    fn test_file_backend_wrong_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        file_storage(&dir)
+            .store_credentials("alice.test", Credentials::new("alice.test", "abcd-efgh-ijkl-mnop"))
+            .unwrap();
+
+        let mut wrong = file_storage(&dir);
+        wrong.passphrase = Some("not the passphrase".to_string());
+        assert!(matches!(wrong.get_credentials("alice.test"), Err(AppError::Authentication(_))));
+        assert!(matches!(wrong.list_accounts(), Err(AppError::Authentication(_))));
+    }
+
+    #[test]
+    fn test_file_backend_migrates_plaintext() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials.json");
+        let legacy = serde_json::json!({
+            "accounts": {
+                "alice.test": {
+                    "credentials": {
+                        "identifier": "alice.test",
+                        "password": "abcd-efgh-ijkl-mnop",
+                        "service": "https://bsky.social"
+                    }
+                }
+            },
+            "default_account": "alice.test"
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let storage = file_storage(&dir);
+        assert_eq!(storage.list_accounts().unwrap(), stored(&["alice.test"]));
+        assert_eq!(storage.get_default_account().unwrap().as_deref(), Some("alice.test"));
+
+        // The first read rewrote the file encrypted
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("abcd-efgh-ijkl-mnop"));
+        assert!(serde_json::from_str::<EncryptedFile>(&contents).is_ok());
+        let credentials = storage.get_credentials("alice.test").unwrap();
+        assert_eq!(credentials.password, "abcd-efgh-ijkl-mnop");
+    }
 }
@@ -0,0 +1,364 @@
+//! Image blob URL construction
+//!
+//! Blobs (images, avatars, link thumbnails) are referenced by CID and served
+//! either by an image CDN or directly by a PDS. The host is configured once at
+//! startup from `--cdn-host`, `AUTOREPLY_CDN_HOST` or the config file's `cdn-host`
+//! and defaults to the bsky CDN. A host containing `{` is a URL template, for CDNs
+//! that lay their URLs out differently. `--image-size` picks the rendition used for
+//! images in posts.
+
+use crate::bluesky::uri::AtUri;
+use crate::error::AppError;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Default image CDN
+pub const DEFAULT_CDN_HOST: &str = "https://cdn.bsky.app";
+
+/// Environment variable consulted when `--cdn-host` is not given
+pub const CDN_HOST_ENV: &str = "AUTOREPLY_CDN_HOST";
+
+/// Environment variable consulted when `--image-size` is not given
+pub const IMAGE_SIZE_ENV: &str = "AUTOREPLY_IMAGE_SIZE";
+
+/// Prefix selecting the PDS `getBlob` URL form, e.g. `pds:https://pds.example.com`
+const PDS_PREFIX: &str = "pds:";
+
+/// Placeholders a URL template must contain
+const REQUIRED_PLACEHOLDERS: [&str; 2] = ["{did}", "{ref}"];
+
+/// Placeholders a URL template may contain
+const TEMPLATE_PLACEHOLDERS: [&str; 4] = ["{did}", "{ref}", "{ext}", "{size}"];
+
+static BLOB_HOST: OnceLock<BlobHost> = OnceLock::new();
+
+static POST_IMAGE_PRESET: OnceLock<ImagePreset> = OnceLock::new();
+
+/// Which CDN rendition images in posts link to
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageSize {
+    /// Full-size images
+    #[default]
+    Fullsize,
+    /// Small previews
+    Thumbnail,
+}
+
+/// Image rendition requested from a CDN (ignored by the PDS form)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImagePreset {
+    /// Full-size image in a post
+    FeedFullsize,
+    /// Small preview, e.g. a link card thumbnail
+    FeedThumbnail,
+    /// Profile avatar
+    Avatar,
+}
+
+impl ImagePreset {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::FeedFullsize => "feed_fullsize",
+            Self::FeedThumbnail => "feed_thumbnail",
+            Self::Avatar => "avatar",
+        }
+    }
+}
+
+impl From<ImageSize> for ImagePreset {
+    fn from(size: ImageSize) -> Self {
+        match size {
+            ImageSize::Fullsize => Self::FeedFullsize,
+            ImageSize::Thumbnail => Self::FeedThumbnail,
+        }
+    }
+}
+
+/// Where blob URLs point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobHost {
+    /// Image CDN: `{base}/img/{preset}/plain/{did}/{cid}@{ext}`
+    Cdn(String),
+    /// PDS blob endpoint: `{base}/xrpc/com.atproto.sync.getBlob?did={did}&cid={cid}`
+    Pds(String),
+    /// Full URL with `{did}` and `{ref}` placeholders, and optionally `{ext}` and `{size}`
+    /// (the preset name, e.g. `feed_fullsize`), each filled in URL-encoded
+    Template(String),
+}
+
+impl Default for BlobHost {
+    fn default() -> Self {
+        Self::Cdn(DEFAULT_CDN_HOST.to_string())
+    }
+}
+
+impl BlobHost {
+    /// Parse a host setting: an http(s) URL for a CDN, `pds:` followed by a PDS URL, or a
+    /// URL template with placeholders
+    pub fn parse(value: &str) -> Result<Self, AppError> {
+        let value = value.trim();
+        if value.contains('{') {
+            return Self::parse_template(value);
+        }
+        let (is_pds, base) = match value.strip_prefix(PDS_PREFIX) {
+            Some(base) => (true, base),
+            None => (false, value),
+        };
+
+        check_http_url(value, base)?;
+        let base = base.trim_end_matches('/').to_string();
+        Ok(if is_pds { Self::Pds(base) } else { Self::Cdn(base) })
+    }
+
+    /// A URL template must name the blob's owner and CID, and nothing it cannot fill in
+    fn parse_template(value: &str) -> Result<Self, AppError> {
+        let missing: Vec<&str> = REQUIRED_PLACEHOLDERS
+            .into_iter()
+            .filter(|placeholder| !value.contains(placeholder))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::ConfigError(format!(
+                "Invalid image URL template '{}': missing {}",
+                value,
+                missing.join(" and ")
+            )));
+        }
+
+        let mut rest = value.to_string();
+        for placeholder in TEMPLATE_PLACEHOLDERS {
+            rest = rest.replace(placeholder, "x");
+        }
+        if rest.contains(['{', '}']) {
+            return Err(AppError::ConfigError(format!(
+                "Invalid image URL template '{}': only {} can be filled in",
+                value,
+                TEMPLATE_PLACEHOLDERS.join(", ")
+            )));
+        }
+        check_http_url(value, &rest)?;
+
+        Ok(Self::Template(value.to_string()))
+    }
+
+    /// URL of an image blob owned by `did`
+    ///
+    /// `ext` is the file extension a CDN should serve (e.g. `jpeg`).
+    pub fn image_url(&self, did: &str, cid: &str, preset: ImagePreset, ext: &str) -> String {
+        // Tolerate refs that are already qualified with the owner's DID
+        let cid = cid
+            .strip_prefix(did)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(cid);
+
+        match self {
+            Self::Cdn(base) => format!(
+                "{}/img/{}/plain/{}/{}@{}",
+                base,
+                preset.as_str(),
+                did,
+                cid,
+                ext
+            ),
+            Self::Pds(base) => format!(
+                "{}/xrpc/com.atproto.sync.getBlob?did={}&cid={}",
+                base,
+                urlencoding::encode(did),
+                urlencoding::encode(cid)
+            ),
+            Self::Template(template) => template
+                .replace("{did}", &urlencoding::encode(did))
+                .replace("{ref}", &urlencoding::encode(cid))
+                .replace("{ext}", &urlencoding::encode(ext))
+                .replace("{size}", preset.as_str()),
+        }
+    }
+}
+
+/// Reject anything but an http(s) URL with a host; `value` is the setting as given
+fn check_http_url(value: &str, url: &str) -> Result<(), AppError> {
+    let parsed = url::Url::parse(url).map_err(|e| {
+        AppError::ConfigError(format!(
+            "Invalid CDN host '{}': {} (expected e.g. https://cdn.example.com)",
+            value, e
+        ))
+    })?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(AppError::ConfigError(format!(
+            "Invalid CDN host '{}': an http:// or https:// URL is required",
+            value
+        )));
+    }
+    Ok(())
+}
+
+/// Configure the blob host and post image size from CLI values, falling back to
+/// `AUTOREPLY_CDN_HOST` and `AUTOREPLY_IMAGE_SIZE`, then the config file.
+/// Call once at startup; an invalid value is an error rather than a silent default.
+pub fn init_blob_host(
+    cli_value: Option<&str>,
+    cli_size: Option<ImageSize>,
+) -> Result<(), AppError> {
+    let config = crate::config::config();
+    let env_value = std::env::var(CDN_HOST_ENV).ok();
+    let host = match cli_value
+        .or(env_value.as_deref())
+        .or(config.cdn_host.as_deref())
+        .filter(|v| !v.trim().is_empty())
+    {
+        Some(value) => BlobHost::parse(value)?,
+        None => BlobHost::default(),
+    };
+
+    let env_size = match std::env::var(IMAGE_SIZE_ENV).ok().filter(|v| !v.trim().is_empty()) {
+        Some(value) => Some(ImageSize::from_str(value.trim(), true).map_err(|_| {
+            AppError::ConfigError(format!(
+                "Invalid {} '{}': expected fullsize or thumbnail",
+                IMAGE_SIZE_ENV, value
+            ))
+        })?),
+        None => None,
+    };
+    let size = cli_size.or(env_size).or(config.image_size).unwrap_or_default();
+
+    // A second call keeps the first configuration
+    let _ = BLOB_HOST.set(host);
+    let _ = POST_IMAGE_PRESET.set(size.into());
+    Ok(())
+}
+
+/// The configured blob host (the bsky CDN if none was configured)
+pub fn blob_host() -> &'static BlobHost {
+    BLOB_HOST.get_or_init(BlobHost::default)
+}
+
+/// Rendition for images in posts (full size if none was configured)
+pub fn post_image_preset() -> ImagePreset {
+    *POST_IMAGE_PRESET.get_or_init(|| ImagePreset::FeedFullsize)
+}
+
+/// DID of the repo an `at://did/collection/rkey` URI belongs to
+pub fn did_from_at_uri(uri: &str) -> Option<&str> {
+    AtUri::parse(uri).ok().map(|uri| uri.did)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_bsky_cdn() {
+        let url = BlobHost::default().image_url(
+            "did:plc:abc",
+            "bafkreicid",
+            ImagePreset::FeedFullsize,
+            "jpeg",
+        );
+        assert_eq!(
+            url,
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:abc/bafkreicid@jpeg"
+        );
+    }
+
+    #[test]
+    fn test_custom_cdn_host() {
+        let host = BlobHost::parse("https://img.example.com/").unwrap();
+        assert_eq!(host, BlobHost::Cdn("https://img.example.com".to_string()));
+        assert_eq!(
+            host.image_url("did:web:me.example", "bafy", ImagePreset::Avatar, "png"),
+            "https://img.example.com/img/avatar/plain/did:web:me.example/bafy@png"
+        );
+    }
+
+    #[test]
+    fn test_pds_get_blob_form() {
+        let host = BlobHost::parse("pds:https://pds.example.com").unwrap();
+        assert_eq!(
+            host.image_url("did:plc:abc", "bafy", ImagePreset::FeedThumbnail, "jpeg"),
+            "https://pds.example.com/xrpc/com.atproto.sync.getBlob?did=did%3Aplc%3Aabc&cid=bafy"
+        );
+    }
+
+    #[test]
+    fn test_host_requires_scheme() {
+        for bad in ["cdn.example.com", "ftp://cdn.example.com", "pds:pds.example.com", "https://"] {
+            assert!(
+                matches!(BlobHost::parse(bad), Err(AppError::ConfigError(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_url_template() {
+        let host = BlobHost::parse("https://img.example.com/{size}/{did}/{ref}.{ext}").unwrap();
+        assert_eq!(
+            host.image_url("did:plc:abc", "bafy", ImagePreset::FeedThumbnail, "png"),
+            "https://img.example.com/feed_thumbnail/did%3Aplc%3Aabc/bafy.png"
+        );
+
+        // Values are encoded, so a DID cannot add path segments or a query
+        let host = BlobHost::parse("https://img.example.com/i?owner={did}&ref={ref}").unwrap();
+        assert_eq!(
+            host.image_url("did:web:a.example/x?y=1", "bafy", ImagePreset::Avatar, "jpeg"),
+            "https://img.example.com/i?owner=did%3Aweb%3Aa.example%2Fx%3Fy%3D1&ref=bafy"
+        );
+
+        // {ext} and {size} are optional
+        let host = BlobHost::parse("https://img.example.com/{did}/{ref}").unwrap();
+        assert_eq!(
+            host.image_url("did:plc:abc", "bafy", ImagePreset::Avatar, "jpeg"),
+            "https://img.example.com/did%3Aplc%3Aabc/bafy"
+        );
+    }
+
+    #[test]
+    fn test_template_requires_placeholders() {
+        for bad in [
+            "https://img.example.com/{ref}@{ext}",
+            "https://img.example.com/{did}/{cid}",
+            "https://img.example.com/{did}/{ref}/{preset}",
+            "img.example.com/{did}/{ref}",
+            "https://img.example.com/{did}/{ref",
+        ] {
+            assert!(
+                matches!(BlobHost::parse(bad), Err(AppError::ConfigError(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+        let err = BlobHost::parse("https://img.example.com/{size}/{ext}").unwrap_err();
+        assert!(err.to_string().contains("missing {did} and {ref}"), "{}", err);
+    }
+
+    #[test]
+    fn test_image_size_presets() {
+        assert_eq!(ImagePreset::from(ImageSize::default()), ImagePreset::FeedFullsize);
+        assert_eq!(ImagePreset::from(ImageSize::Thumbnail), ImagePreset::FeedThumbnail);
+    }
+
+    #[test]
+    fn test_did_qualified_ref_is_not_duplicated() {
+        let url = BlobHost::default().image_url(
+            "did:plc:abc",
+            "did:plc:abc/bafy",
+            ImagePreset::FeedFullsize,
+            "jpeg",
+        );
+        assert_eq!(url, "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:abc/bafy@jpeg");
+    }
+
+    #[test]
+    fn test_did_from_at_uri() {
+        assert_eq!(
+            did_from_at_uri("at://did:plc:abc/app.bsky.feed.post/3k"),
+            Some("did:plc:abc")
+        );
+        assert_eq!(did_from_at_uri("https://bsky.app"), None);
+        // Rejected the same way as by AtUri::parse
+        assert_eq!(did_from_at_uri("at://did:plc:abc"), None);
+        assert_eq!(did_from_at_uri("at:///app.bsky.feed.post/3k"), None);
+    }
+}
@@ -6,11 +6,19 @@
 #![allow(dead_code)]
 
 use crate::error::AppError;
+use crate::http::{rate_limit_error, retry_policy, send_with_retry, RetryPolicy};
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::time::Instant;
-use tokio::sync::Mutex;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long resolved handles and PDS endpoints stay cached by default
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Handle lookups in flight at once during `DidResolver::resolve_handles`
+const MAX_CONCURRENT_RESOLUTIONS: usize = 8;
 
 /// DID resolution response from XRPC
 #[derive(Debug, Deserialize)]
@@ -52,6 +60,15 @@ pub fn is_valid_did(did: &str) -> bool {
     did.starts_with("did:") && did.len() > 4
 }
 
+/// Handle shown in results for an account argument: DIDs verbatim, handles without `@`
+pub(crate) fn display_handle(account: &str) -> String {
+    if account.starts_with("did:plc:") {
+        account.to_string()
+    } else {
+        account.strip_prefix('@').unwrap_or(account).to_string()
+    }
+}
+
 /// Parse various account reference formats into a normalized form
 /// Supports: handles, @handles, DIDs, partial DIDs (suffix only), and Bsky.app profile URLs
 pub fn parse_account_reference(account: &str) -> String {
@@ -88,23 +105,119 @@ pub fn parse_account_reference(account: &str) -> String {
     account.to_string()
 }
 
+/// Thread-safe map whose entries expire a fixed time after insertion
+struct TtlCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl TtlCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached value for `key`, dropping it if it has expired
+    fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((value, expires_at)) if Instant::now() < *expires_at => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: &str, value: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), (value.to_string(), Instant::now() + self.ttl));
+    }
+}
+
+/// Handle -> DID and DID -> PDS caches shared by every `DidResolver::new()`,
+/// so repeated tool calls in one process skip the network
+struct ResolutionCache {
+    handles: TtlCache,
+    pds_endpoints: TtlCache,
+}
+
+impl ResolutionCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            handles: TtlCache::new(ttl),
+            pds_endpoints: TtlCache::new(ttl),
+        }
+    }
+}
+
+static SHARED_CACHE: LazyLock<Arc<ResolutionCache>> =
+    LazyLock::new(|| Arc::new(ResolutionCache::new(DEFAULT_CACHE_TTL)));
+
 /// Main DID resolver struct
 pub struct DidResolver {
     client: Client,
-    cache: std::sync::Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    cache: Arc<ResolutionCache>,
+    retry: RetryPolicy,
 }
 
 impl DidResolver {
+    /// Resolver using the process-wide cache with `DEFAULT_CACHE_TTL`
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
-            cache: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            cache: SHARED_CACHE.clone(),
+            retry: retry_policy().clone(),
         }
     }
 
+    /// Resolver with its own cache whose entries expire after `ttl`
+    pub fn with_cache_ttl(ttl: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            cache: Arc::new(ResolutionCache::new(ttl)),
+            retry: retry_policy().clone(),
+        }
+    }
+
+    /// Retry failed lookups under `retry` instead of the configured policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Resolve handle to DID
     /// Now supports multiple account reference formats via parse_account_reference
     pub async fn resolve_handle(&self, handle: &str) -> Result<Option<String>, AppError> {
+        self.resolve_handle_with(handle, |normalized| async move {
+            self.try_resolve_handle_direct(&normalized).await
+        })
+        .await
+    }
+
+    /// The DID for an account without the network: a DID given as is, or the one a handle
+    /// was last resolved to while it is cached
+    pub fn cached_did(&self, account: &str) -> Option<String> {
+        let normalized = parse_account_reference(account);
+        if normalized.starts_with("did:") {
+            return Some(normalized);
+        }
+        self.cache.handles.get(&normalized.to_ascii_lowercase())
+    }
+
+    /// Resolve handle to DID, calling `lookup` only when the cache has no live entry
+    async fn resolve_handle_with<F, Fut>(
+        &self,
+        handle: &str,
+        lookup: F,
+    ) -> Result<Option<String>, AppError>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<Option<String>, AppError>>,
+    {
         // Parse the account reference to normalize it
         let normalized = parse_account_reference(handle);
 
@@ -118,49 +231,137 @@ impl DidResolver {
             return Ok(None);
         }
 
+        // Handles are case-insensitive
+        let key = normalized.to_ascii_lowercase();
+
         // Check cache first
-        if let Some(cached) = self.get_cached_resolution(&normalized) {
+        if let Some(cached) = self.cache.handles.get(&key) {
             return Ok(Some(cached));
         }
 
         // Try direct resolution
-        let did = self.try_resolve_handle_direct(&normalized).await?;
+        let did = lookup(normalized).await?;
 
         if let Some(ref did_str) = did {
-            self.cache_resolution(&normalized, did_str);
+            self.cache.handles.insert(&key, did_str);
         }
 
         Ok(did)
     }
 
+    /// Resolve several handles at once, keyed by each input as given
+    ///
+    /// Inputs naming the same account (e.g. `@Alice.bsky.social` and `alice.bsky.social`)
+    /// are looked up once, and up to `MAX_CONCURRENT_RESOLUTIONS` lookups run concurrently.
+    /// A handle that fails to resolve maps to `None`.
+    pub async fn resolve_handles(&self, handles: &[&str]) -> HashMap<String, Option<String>> {
+        self.resolve_handles_with(handles, |normalized| async move {
+            self.try_resolve_handle_direct(&normalized).await
+        })
+        .await
+    }
+
+    /// Resolve several handles, calling `lookup` once per distinct uncached handle
+    async fn resolve_handles_with<F, Fut>(
+        &self,
+        handles: &[&str],
+        lookup: F,
+    ) -> HashMap<String, Option<String>>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<Option<String>, AppError>>,
+    {
+        use futures::stream::{self, StreamExt};
+
+        let mut distinct: Vec<(String, String)> = Vec::new();
+        for &handle in handles {
+            let key = parse_account_reference(handle).to_ascii_lowercase();
+            if !distinct.iter().any(|(seen, _)| *seen == key) {
+                distinct.push((key, handle.to_string()));
+            }
+        }
+
+        let lookup = &lookup;
+        let mut lookups = Vec::with_capacity(distinct.len());
+        for (key, handle) in distinct {
+            lookups.push(async move {
+                let did = self
+                    .resolve_handle_with(&handle, lookup)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::debug!("Failed to resolve {}: {}", handle, e.message());
+                        None
+                    });
+                (key, did)
+            });
+        }
+        let resolved: HashMap<String, Option<String>> = stream::iter(lookups)
+            .buffer_unordered(MAX_CONCURRENT_RESOLUTIONS)
+            .collect()
+            .await;
+
+        handles
+            .iter()
+            .map(|&handle| {
+                let key = parse_account_reference(handle).to_ascii_lowercase();
+                (handle.to_string(), resolved.get(&key).cloned().flatten())
+            })
+            .collect()
+    }
+
     async fn try_resolve_handle_direct(&self, handle: &str) -> Result<Option<String>, AppError> {
         let url = format!(
             "https://bsky.social/xrpc/com.atproto.identity.resolveHandle?handle={}",
             handle
         );
 
-        match self.client.get(&url).send().await {
+        match send_with_retry(&self.retry, || self.client.get(&url)).await {
             Ok(response) if response.status().is_success() => {
                 match response.json::<ResolveHandleResponse>().await {
                     Ok(resolve_response) => Ok(Some(resolve_response.did)),
                     Err(_) => Ok(None),
                 }
             }
-            _ => Ok(None),
+            // A rate limit is worth reporting; other failures just mean "unresolved"
+            Ok(response) => rate_limit_error(&response).map_or(Ok(None), Err),
+            Err(_) => Ok(None),
         }
     }
 
-    fn get_cached_resolution(&self, _handle: &str) -> Option<String> {
-        // For now, simplified without async locking
-        None
+    /// Discover PDS endpoint for a DID
+    pub async fn discover_pds(&self, did: &str) -> Result<Option<String>, AppError> {
+        self.discover_pds_with(did, |did| async move { self.fetch_pds_endpoint(&did).await })
+            .await
     }
 
-    fn cache_resolution(&self, _handle: &str, _did: &str) {
-        // For now, simplified without async locking
+    /// Discover PDS endpoint, calling `lookup` only when the cache has no live entry
+    async fn discover_pds_with<F, Fut>(
+        &self,
+        did: &str,
+        lookup: F,
+    ) -> Result<Option<String>, AppError>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<Option<String>, AppError>>,
+    {
+        if let Some(cached) = self.cache.pds_endpoints.get(did) {
+            return Ok(Some(cached));
+        }
+
+        let endpoint = lookup(did.to_string()).await?;
+        if let Some(ref endpoint) = endpoint {
+            self.cache.pds_endpoints.insert(did, endpoint);
+        }
+        Ok(endpoint)
     }
 
-    /// Discover PDS endpoint for a DID
-    pub async fn discover_pds(&self, _did: &str) -> Result<Option<String>, AppError> {
+    /// Remember `endpoint` as the PDS of `did`, so tests can point a DID at a local server
+    #[cfg(test)]
+    pub(crate) fn remember_pds(&self, did: &str, endpoint: &str) {
+        self.cache.pds_endpoints.insert(did, endpoint);
+    }
+
+    async fn fetch_pds_endpoint(&self, _did: &str) -> Result<Option<String>, AppError> {
         // Implement PDS discovery for supported DID methods (did:plc and did:web)
         #[derive(Debug, serde::Deserialize)]
         struct ServiceEndpoint {
@@ -195,14 +396,15 @@ impl DidResolver {
         // did:plc:<id> -> fetch https://plc.directory/{did}
         if _did.starts_with("did:plc:") {
             let url = construct_pds_endpoint_url(_did);
-            let resp = self
-                .client
-                .get(&url)
-                .header(reqwest::header::ACCEPT, "application/json")
-                .send()
-                .await
-                .map_err(|e| AppError::NetworkError(e.to_string()))?;
-
+            let resp = send_with_retry(&self.retry, || {
+                self.client.get(&url).header(reqwest::header::ACCEPT, "application/json")
+            })
+            .await
+            .map_err(|e| AppError::NetworkError(e.to_string()))?;
+
+            if let Some(e) = rate_limit_error(&resp) {
+                return Err(e);
+            }
             if !resp.status().is_success() {
                 return Err(AppError::DidResolveFailed(format!(
                     "DID document resolution failed with status {}",
@@ -236,15 +438,13 @@ impl DidResolver {
 
             let mut last_err: Option<AppError> = None;
             for url in candidates {
-                let resp = match self
-                    .client
-                    .get(&url)
-                    .header(
+                let resp = match send_with_retry(&self.retry, || {
+                    self.client.get(&url).header(
                         reqwest::header::ACCEPT,
                         "application/did+json, application/json",
                     )
-                    .send()
-                    .await
+                })
+                .await
                 {
                     Ok(r) => r,
                     Err(e) => {
@@ -253,6 +453,10 @@ impl DidResolver {
                     }
                 };
 
+                if let Some(e) = rate_limit_error(&resp) {
+                    last_err = Some(e);
+                    continue;
+                }
                 if !resp.status().is_success() {
                     last_err = Some(AppError::DidResolveFailed(format!(
                         "did:web document fetch failed with status {}",
@@ -745,4 +949,176 @@ mod tests {
             "alice.com#section"
         );
     }
+
+    /// Lookup stub that counts calls and answers with a fixed DID
+    fn counting_lookup<'a>(
+        calls: &'a std::sync::atomic::AtomicUsize,
+        answer: Option<&str>,
+    ) -> impl FnOnce(String) -> std::future::Ready<Result<Option<String>, AppError>> + 'a {
+        let answer = answer.map(str::to_string);
+        move |_| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::future::ready(Ok(answer))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handle_cached_within_ttl() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let resolver = DidResolver::with_cache_ttl(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        let first = resolver
+            .resolve_handle_with(
+                "alice.bsky.social",
+                counting_lookup(&calls, Some("did:plc:alice")),
+            )
+            .await
+            .unwrap();
+        // Different spelling of the same handle hits the same entry
+        let second = resolver
+            .resolve_handle_with(
+                "@Alice.bsky.social",
+                counting_lookup(&calls, Some("did:plc:other")),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(first.as_deref(), Some("did:plc:alice"));
+        assert_eq!(second.as_deref(), Some("did:plc:alice"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The public entry point is served from the cache without touching the network
+        assert_eq!(
+            resolver.resolve_handle("alice.bsky.social").await.unwrap().as_deref(),
+            Some("did:plc:alice")
+        );
+        assert_eq!(resolver.cached_did("@ALICE.bsky.social").as_deref(), Some("did:plc:alice"));
+        assert_eq!(resolver.cached_did("did:plc:bob").as_deref(), Some("did:plc:bob"));
+        assert_eq!(resolver.cached_did("carol.bsky.social"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handles_dedupes_inputs() {
+        let resolver = DidResolver::with_cache_ttl(Duration::from_secs(60));
+        let calls = Mutex::new(Vec::new());
+
+        let resolved = resolver
+            .resolve_handles_with(
+                &[
+                    "bob.bsky.social",
+                    "alice.bsky.social",
+                    "@Alice.bsky.social",
+                    "ghost.bsky.social",
+                    "bob.bsky.social",
+                    "did:plc:carol",
+                ],
+                |handle| {
+                    calls.lock().unwrap().push(handle.clone());
+                    let did = match handle.as_str() {
+                        "alice.bsky.social" => Some("did:plc:alice".to_string()),
+                        "bob.bsky.social" => Some("did:plc:bob".to_string()),
+                        _ => None,
+                    };
+                    async move {
+                        // Finish out of order so results cannot rely on completion order
+                        if did.as_deref() == Some("did:plc:bob") {
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                        }
+                        Ok(did)
+                    }
+                },
+            )
+            .await;
+
+        let mut looked_up = calls.into_inner().unwrap();
+        looked_up.sort();
+        assert_eq!(
+            looked_up,
+            vec!["alice.bsky.social", "bob.bsky.social", "ghost.bsky.social"]
+        );
+
+        assert_eq!(resolved.len(), 5);
+        assert_eq!(resolved["alice.bsky.social"].as_deref(), Some("did:plc:alice"));
+        assert_eq!(resolved["@Alice.bsky.social"].as_deref(), Some("did:plc:alice"));
+        assert_eq!(resolved["bob.bsky.social"].as_deref(), Some("did:plc:bob"));
+        assert_eq!(resolved["ghost.bsky.social"], None);
+        assert_eq!(resolved["did:plc:carol"].as_deref(), Some("did:plc:carol"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handle_refreshes_after_expiry() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let resolver = DidResolver::with_cache_ttl(Duration::ZERO);
+        let calls = AtomicUsize::new(0);
+
+        resolver
+            .resolve_handle_with("bob.bsky.social", counting_lookup(&calls, Some("did:plc:bob")))
+            .await
+            .unwrap();
+        let refreshed = resolver
+            .resolve_handle_with("bob.bsky.social", counting_lookup(&calls, Some("did:plc:bob2")))
+            .await
+            .unwrap();
+
+        assert_eq!(refreshed.as_deref(), Some("did:plc:bob2"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_resolution_is_not_cached() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let resolver = DidResolver::with_cache_ttl(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            let result = resolver
+                .resolve_handle_with("ghost.bsky.social", counting_lookup(&calls, None))
+                .await
+                .unwrap();
+            assert!(result.is_none());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // DIDs pass straight through without a lookup
+        resolver
+            .resolve_handle_with("did:plc:abc", counting_lookup(&calls, None))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_discover_pds_cached_by_did() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let resolver = DidResolver::with_cache_ttl(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        for _ in 0..3 {
+            let endpoint = resolver
+                .discover_pds_with(
+                    "did:plc:alice",
+                    counting_lookup(&calls, Some("https://pds.example")),
+                )
+                .await
+                .unwrap();
+            assert_eq!(endpoint.as_deref(), Some("https://pds.example"));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Separate resolvers with their own TTL do not share entries
+        let other = DidResolver::with_cache_ttl(Duration::from_secs(60));
+        other
+            .discover_pds_with(
+                "did:plc:alice",
+                counting_lookup(&calls, Some("https://pds.example")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
 }
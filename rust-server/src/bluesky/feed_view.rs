@@ -0,0 +1,140 @@
+//! Feed views returned by the AppView
+//!
+//! `app.bsky.feed.getFeed`, `getAuthorFeed`, `getTimeline`, `getPosts` and `getPostThread`
+//! share these shapes; the feed, timeline and thread tools fetch them and the formatters
+//! render them.
+
+use crate::bluesky::records::Facet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct PostAuthor {
+    pub(crate) did: String,
+    pub(crate) handle: String,
+    #[serde(rename = "displayName")]
+    pub(crate) display_name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct PostRecord {
+    pub(crate) text: String,
+    #[serde(rename = "createdAt")]
+    pub(crate) created_at: String,
+    #[serde(default)]
+    pub(crate) facets: Vec<Facet>,
+    #[serde(default)]
+    pub(crate) reply: Option<ReplyRef>,
+}
+
+/// The `reply` field of a post record, pointing at the post it answers
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct ReplyRef {
+    pub(crate) parent: PostRef,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct PostRef {
+    pub(crate) uri: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct FeedPost {
+    pub(crate) uri: String,
+    pub(crate) cid: String,
+    pub(crate) author: PostAuthor,
+    pub(crate) record: PostRecord,
+    #[serde(rename = "indexedAt")]
+    pub(crate) indexed_at: String,
+    #[serde(rename = "likeCount")]
+    pub(crate) like_count: Option<i32>,
+    #[serde(rename = "replyCount")]
+    pub(crate) reply_count: Option<i32>,
+    #[serde(rename = "repostCount")]
+    pub(crate) repost_count: Option<i32>,
+    #[serde(rename = "quoteCount")]
+    pub(crate) quote_count: Option<i32>,
+    #[serde(default)]
+    pub(crate) embed: Option<Value>,
+}
+
+impl FeedPost {
+    /// URI of the post this one replies to, if it is a reply
+    pub(crate) fn reply_parent_uri(&self) -> Option<&str> {
+        self.record.reply.as_ref().map(|reply| reply.parent.uri.as_str())
+    }
+}
+
+/// Why a post appears in a feed (e.g. `app.bsky.feed.defs#reasonRepost`)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct FeedReason {
+    #[serde(rename = "$type")]
+    pub(crate) reason_type: String,
+    pub(crate) by: Option<PostAuthor>,
+    #[serde(rename = "indexedAt")]
+    pub(crate) indexed_at: Option<String>,
+}
+
+impl FeedReason {
+    pub(crate) fn is_repost(&self) -> bool {
+        self.reason_type == "app.bsky.feed.defs#reasonRepost"
+    }
+}
+
+/// A feed slot: either a hydrated post, or a stub for a post the AppView
+/// could not hydrate (deleted, blocked or not found)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum FeedPostSlot {
+    Post(Box<FeedPost>),
+    Unavailable { uri: String },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub(crate) struct FeedViewPost {
+    pub(crate) post: FeedPostSlot,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reason: Option<FeedReason>,
+    /// The post a reply answers, when it could be looked up
+    #[serde(skip_deserializing, rename = "replyParent", skip_serializing_if = "Option::is_none")]
+    pub(crate) reply_parent: Option<Box<FeedPost>>,
+}
+
+impl FeedViewPost {
+    pub(crate) fn repost_reason(&self) -> Option<&FeedReason> {
+        self.reason.as_ref().filter(|r| r.is_repost())
+    }
+
+    /// Timestamp used for chronological ordering: the repost time for
+    /// reposts, otherwise the post's own creation time
+    pub(crate) fn sort_time(&self) -> Option<&str> {
+        self.repost_reason()
+            .and_then(|r| r.indexed_at.as_deref())
+            .or_else(|| self.created_at())
+    }
+
+    /// Where a feed cursor continues after this entry: AppView feed cursors are the time
+    /// the entry was indexed, the repost's for reposts
+    pub(crate) fn cursor_time(&self) -> Option<&str> {
+        match (self.repost_reason(), &self.post) {
+            (Some(reason), _) => reason.indexed_at.as_deref(),
+            (None, FeedPostSlot::Post(post)) => Some(post.indexed_at.as_str()),
+            (None, FeedPostSlot::Unavailable { .. }) => None,
+        }
+    }
+
+    /// Creation time of the post itself, even when it was reposted later
+    pub(crate) fn created_at(&self) -> Option<&str> {
+        match &self.post {
+            FeedPostSlot::Post(post) => Some(post.record.created_at.as_str()),
+            FeedPostSlot::Unavailable { .. } => None,
+        }
+    }
+}
+
+/// One page of a feed, with the cursor for the next page
+#[derive(Deserialize)]
+pub(crate) struct FeedResponse {
+    pub(crate) feed: Vec<FeedViewPost>,
+    pub(crate) cursor: Option<String>,
+}
@@ -0,0 +1,307 @@
+//! Firehose capture replay
+//!
+//! A capture is the raw `com.atproto.sync.subscribeRepos` stream written to a file: each
+//! event is a DAG-CBOR header (`{op, t}`) immediately followed by a DAG-CBOR body, the same
+//! bytes as one websocket frame. Replaying a capture rebuilds the set of records the commits
+//! leave behind, so it can stand in for a repo CAR as a post source.
+
+use crate::bluesky::mst::{format_cid, parse_cid_link_bytes};
+use crate::car::cbor::{get_array_field, get_int_field, get_text_field, CborReader, CborValue};
+use crate::car::reader::SyncCarReader;
+use crate::car::CarError;
+use std::collections::{BTreeMap, HashMap};
+
+/// A record left in place after replaying every commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayRecord {
+    pub did: String,
+    pub collection: String,
+    pub rkey: String,
+    /// CID in the same string form the CAR iterators use
+    pub cid: String,
+    /// DAG-CBOR record bytes
+    pub bytes: Vec<u8>,
+}
+
+/// Where and why decoding a capture stopped before the end of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayStop {
+    /// Byte offset of the first event that could not be read
+    pub offset: usize,
+    pub reason: String,
+}
+
+/// Result of replaying a capture
+#[derive(Debug, Default)]
+pub struct ReplayOutcome {
+    /// Surviving records, ordered by repo, collection and rkey
+    pub records: Vec<ReplayRecord>,
+    /// Events read from the capture, of any type
+    pub frames: usize,
+    /// Commit events that were read but could not be used (error frames, malformed bodies)
+    pub skipped_frames: usize,
+    /// Create or update operations whose record was already present with the same CID
+    pub duplicates: usize,
+    /// Set when the capture ends mid-event or contains bytes that are not DAG-CBOR
+    pub stopped_at: Option<ReplayStop>,
+}
+
+/// One repo operation from a commit
+struct ReplayOp {
+    did: String,
+    path: String,
+    /// Record CID and bytes; `None` for deletes
+    record: Option<(String, Vec<u8>)>,
+}
+
+/// A decoded `#commit` event
+struct CommitEvent {
+    seq: i64,
+    ops: Vec<ReplayOp>,
+}
+
+/// Replay a capture, tolerating duplicate and out-of-order events
+///
+/// Commits are applied in `seq` order regardless of their position in the file. A create or
+/// update whose CID matches the record already at that path is a duplicate delivery and is
+/// counted rather than applied; deletes remove the record.
+pub fn replay_capture(bytes: &[u8]) -> ReplayOutcome {
+    let mut outcome = ReplayOutcome::default();
+    let mut commits = Vec::new();
+
+    let mut reader = CborReader::new(bytes);
+    while reader.position() < bytes.len() {
+        let offset = reader.position();
+        let frame = reader
+            .read_value()
+            .and_then(|header| reader.read_value().map(|body| (header, body)));
+        let (header, body) = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                let reason = match e {
+                    CarError::UnexpectedEof => "capture is truncated mid-event".to_string(),
+                    e => e.to_string(),
+                };
+                outcome.stopped_at = Some(ReplayStop { offset, reason });
+                break;
+            }
+        };
+        outcome.frames += 1;
+
+        let CborValue::Map(header) = header else {
+            outcome.skipped_frames += 1;
+            continue;
+        };
+        if get_int_field(&header, "op") != Some(1) {
+            // Error frames (op -1) carry no data
+            outcome.skipped_frames += 1;
+            continue;
+        }
+        if get_text_field(&header, "t") != Some("#commit") {
+            // Identity, account and other events do not change records
+            continue;
+        }
+        match decode_commit(&body) {
+            Some(commit) => commits.push(commit),
+            None => outcome.skipped_frames += 1,
+        }
+    }
+
+    // Stable sort keeps file order for repeated deliveries of the same seq
+    commits.sort_by_key(|commit| commit.seq);
+
+    let mut records: BTreeMap<(String, String), (String, Vec<u8>)> = BTreeMap::new();
+    for op in commits.into_iter().flat_map(|commit| commit.ops) {
+        let key = (op.did, op.path);
+        match op.record {
+            Some((cid, _)) if records.get(&key).is_some_and(|(current, _)| *current == cid) => {
+                outcome.duplicates += 1;
+            }
+            Some(record) => {
+                records.insert(key, record);
+            }
+            None => {
+                records.remove(&key);
+            }
+        }
+    }
+
+    outcome.records = records
+        .into_iter()
+        .filter_map(|((did, path), (cid, bytes))| {
+            let (collection, rkey) = path.split_once('/')?;
+            Some(ReplayRecord {
+                did,
+                collection: collection.to_string(),
+                rkey: rkey.to_string(),
+                cid,
+                bytes,
+            })
+        })
+        .collect();
+    outcome
+}
+
+/// Decode a `#commit` body into its operations, or `None` if it is malformed
+fn decode_commit(body: &CborValue) -> Option<CommitEvent> {
+    let CborValue::Map(body) = body else {
+        return None;
+    };
+    let did = get_text_field(body, "repo")?;
+    let seq = get_int_field(body, "seq")?;
+    let ops = get_array_field(body, "ops")?;
+
+    // Record blocks travel as a CAR slice; an empty one (e.g. a tooBig commit) has none
+    let blocks = body
+        .iter()
+        .find_map(|(k, v)| match (k, v) {
+            (CborValue::Text("blocks"), CborValue::Bytes(b)) => Some(*b),
+            _ => None,
+        })
+        .map(read_blocks)
+        .unwrap_or_default();
+
+    let mut decoded = Vec::with_capacity(ops.len());
+    for op in ops {
+        let CborValue::Map(op) = op else {
+            return None;
+        };
+        let path = get_text_field(op, "path")?;
+        let record = match get_text_field(op, "action")? {
+            "delete" => None,
+            "create" | "update" => {
+                let cid = op.iter().find_map(|(k, v)| match (k, v) {
+                    (CborValue::Text("cid"), CborValue::Link(cid)) => {
+                        parse_cid_link_bytes(cid).ok()
+                    }
+                    _ => None,
+                })?;
+                // A record missing from the blocks cannot be replayed; leave the path as it was
+                let Some(bytes) = blocks.get(&cid) else {
+                    continue;
+                };
+                Some((cid, bytes.clone()))
+            }
+            _ => continue,
+        };
+        decoded.push(ReplayOp {
+            did: did.to_string(),
+            path: path.to_string(),
+            record,
+        });
+    }
+
+    Some(CommitEvent { seq, ops: decoded })
+}
+
+/// Index the blocks of a commit's CAR slice by CID, keeping whatever precedes a bad block
+fn read_blocks(car_bytes: &[u8]) -> HashMap<String, Vec<u8>> {
+    let mut blocks = HashMap::new();
+    if let Ok(reader) = SyncCarReader::from_bytes(car_bytes) {
+        for entry in reader.map_while(Result::ok) {
+            blocks.insert(format_cid(&entry.cid), entry.bytes);
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::test_support::{encode, firehose_commit as commit, test_cid, Cbor};
+
+    fn rkeys(outcome: &ReplayOutcome) -> Vec<&str> {
+        outcome.records.iter().map(|r| r.rkey.as_str()).collect()
+    }
+
+    #[test]
+    fn test_replay_collects_created_records() {
+        let capture = [
+            commit("did:plc:a", 1, vec![("create", "1", 10, Some("first"))]),
+            commit("did:plc:a", 2, vec![("create", "2", 11, Some("second"))]),
+        ]
+        .concat();
+
+        let outcome = replay_capture(&capture);
+        assert_eq!(outcome.frames, 2);
+        assert_eq!(rkeys(&outcome), vec!["1", "2"]);
+        assert_eq!(outcome.records[0].did, "did:plc:a");
+        assert_eq!(outcome.records[0].collection, "app.bsky.feed.post");
+        assert!(outcome.stopped_at.is_none());
+    }
+
+    #[test]
+    fn test_replay_dedups_repeated_events_by_cid() {
+        let event = commit("did:plc:a", 1, vec![("create", "1", 10, Some("once"))]);
+        let capture = [event.clone(), event].concat();
+
+        let outcome = replay_capture(&capture);
+        assert_eq!(outcome.records.len(), 1);
+        assert_eq!(outcome.duplicates, 1);
+    }
+
+    #[test]
+    fn test_replay_delete_removes_record() {
+        let capture = [
+            commit("did:plc:a", 1, vec![("create", "1", 10, Some("gone"))]),
+            commit("did:plc:a", 2, vec![("create", "2", 11, Some("kept"))]),
+            commit("did:plc:a", 3, vec![("delete", "1", 0, None)]),
+        ]
+        .concat();
+
+        assert_eq!(rkeys(&replay_capture(&capture)), vec!["2"]);
+    }
+
+    #[test]
+    fn test_replay_applies_events_in_seq_order() {
+        // The delete was captured before the create it follows
+        let capture = [
+            commit("did:plc:a", 2, vec![("delete", "1", 0, None)]),
+            commit("did:plc:a", 1, vec![("create", "1", 10, Some("gone"))]),
+            commit("did:plc:a", 4, vec![("update", "2", 13, Some("edited"))]),
+            commit("did:plc:a", 3, vec![("create", "2", 12, Some("draft"))]),
+        ]
+        .concat();
+
+        let outcome = replay_capture(&capture);
+        assert_eq!(rkeys(&outcome), vec!["2"]);
+        assert_eq!(outcome.records[0].cid, parse_cid_link_bytes(&test_cid(13)).unwrap());
+    }
+
+    #[test]
+    fn test_replay_truncated_capture_keeps_valid_prefix() {
+        let first = commit("did:plc:a", 1, vec![("create", "1", 10, Some("whole"))]);
+        let second = commit("did:plc:a", 2, vec![("create", "2", 11, Some("cut off"))]);
+        let capture = [first.clone(), second[..second.len() / 2].to_vec()].concat();
+
+        let outcome = replay_capture(&capture);
+        assert_eq!(rkeys(&outcome), vec!["1"]);
+        let stop = outcome.stopped_at.expect("truncation is reported");
+        assert_eq!(stop.offset, first.len());
+        assert!(stop.reason.contains("truncated"), "{}", stop.reason);
+    }
+
+    #[test]
+    fn test_replay_skips_error_and_malformed_frames() {
+        let mut capture = Vec::new();
+        encode(
+            &Cbor::Map(vec![("op", Cbor::Int(-1))]),
+            &mut capture,
+        );
+        encode(
+            &Cbor::Map(vec![("error", Cbor::Text("FutureCursor".to_string()))]),
+            &mut capture,
+        );
+        encode(
+            &Cbor::Map(vec![("op", Cbor::Int(1)), ("t", Cbor::Text("#commit".to_string()))]),
+            &mut capture,
+        );
+        encode(&Cbor::Map(vec![("seq", Cbor::Int(5))]), &mut capture);
+        capture.extend(commit("did:plc:a", 6, vec![("create", "1", 10, Some("ok"))]));
+
+        let outcome = replay_capture(&capture);
+        assert_eq!(outcome.frames, 3);
+        assert_eq!(outcome.skipped_frames, 2);
+        assert_eq!(rkeys(&outcome), vec!["1"]);
+    }
+}
@@ -1,10 +1,14 @@
 //! Bluesky/ATProto related functionality
 
+pub mod blob_url;
 pub mod did;
+pub mod feed_view;
+pub mod firehose;
 pub mod mst;
 pub mod provider;
 pub mod records;
 pub mod uri;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod provider_edge_case_tests;
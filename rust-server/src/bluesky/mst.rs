@@ -6,10 +6,11 @@ use crate::car::reader::SyncCarReader;
 ///
 /// Based on the atcute implementation for efficient MST traversal.
 use crate::car::{
-    cbor::{decode_cbor, CborValue},
-    CarError,
+    cbor::{decode_cbor, get_text_field, CborValue},
+    CarError, CarIndex,
 };
 use std::collections::{HashMap, HashSet};
+use tracing::debug;
 
 /// MST node entry from CBOR
 #[derive(Debug)]
@@ -41,54 +42,98 @@ struct NodeData {
 ///
 /// # Returns
 /// A HashMap mapping CID strings to collection/rkey paths
+#[cfg(test)]
 pub fn extract_cid_to_rkey_mapping(
     car_bytes: &[u8],
     collection: &str,
 ) -> Result<HashMap<String, String>, CarError> {
-    let car_reader = SyncCarReader::from_bytes(car_bytes)?;
+    Ok(collection_mapping(extract_all_rkey_mappings(car_bytes)?, collection))
+}
+
+/// Extract CID -> (collection, rkey) for every record in the CAR in one MST walk
+pub fn extract_all_rkey_mappings(
+    car_bytes: &[u8],
+) -> Result<HashMap<String, (String, String)>, CarError> {
+    index_rkey_mappings(&CarIndex::build(car_bytes)?)
+}
+
+/// A record's (collection, rkey, CBOR block)
+pub type CollectionBlock = (String, String, Vec<u8>);
+
+/// The records of `collections` as (collection, rkey, CBOR block), ordered by collection
+/// and rkey, from one MST walk. Records whose block the CAR lacks are left out.
+pub fn extract_collection_blocks(
+    car_bytes: &[u8],
+    collections: &[&str],
+) -> Result<Vec<CollectionBlock>, CarError> {
+    let index = CarIndex::build(car_bytes)?;
+    Ok(collection_blocks(&index, &index_rkey_mappings(&index)?, collections))
+}
+
+/// Both `extract_cid_to_rkey_mapping(collection)` and `extract_collection_blocks(collections)`
+/// from a single index and MST walk of the CAR
+pub fn extract_mapping_and_blocks(
+    car_bytes: &[u8],
+    collection: &str,
+    collections: &[&str],
+) -> Result<(HashMap<String, String>, Vec<CollectionBlock>), CarError> {
+    let index = CarIndex::build(car_bytes)?;
+    let mappings = index_rkey_mappings(&index)?;
+    let blocks = collection_blocks(&index, &mappings, collections);
+    Ok((collection_mapping(mappings, collection), blocks))
+}
+
+/// CID -> "collection/rkey" for the records of one collection
+fn collection_mapping(
+    mappings: HashMap<String, (String, String)>,
+    collection: &str,
+) -> HashMap<String, String> {
+    mappings
+        .into_iter()
+        .filter(|(_, (coll, _))| coll == collection)
+        .map(|(cid, (coll, rkey))| (cid, format!("{}/{}", coll, rkey)))
+        .collect()
+}
+
+/// The blocks of the mapped records in `collections`, ordered by collection and rkey
+fn collection_blocks(
+    index: &CarIndex,
+    mappings: &HashMap<String, (String, String)>,
+    collections: &[&str],
+) -> Vec<CollectionBlock> {
+    let mut blocks: Vec<CollectionBlock> = mappings
+        .iter()
+        .filter(|(_, (collection, _))| collections.contains(&collection.as_str()))
+        .filter_map(|(cid, (collection, rkey))| {
+            Some((collection.clone(), rkey.clone(), get_block(index, cid)?.to_vec()))
+        })
+        .collect();
+    blocks.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    blocks
+}
+
+/// CID -> (collection, rkey) for every record reachable from the commit of an indexed CAR
+fn index_rkey_mappings(index: &CarIndex) -> Result<HashMap<String, (String, String)>, CarError> {
 
     // Use CAR header root as the commit CID (correct per CAR spec and indigo implementation)
-    let header = car_reader.header();
-    let commit_cid_str = header
+    let commit_cid_str = index
+        .header()
         .roots
         .first()
         .ok_or_else(|| CarError::InvalidHeader("Missing root CID in CAR header".to_string()))
         .map(format_cid)?;
 
-    // Build CID -> bytes map from CAR entries
-    let mut cid_map: HashMap<String, Vec<u8>> = HashMap::new();
-
-    for entry_result in car_reader {
-        let entry = entry_result?;
-        let cid_str = format_cid(&entry.cid);
-        cid_map.insert(cid_str, entry.bytes);
-    }
-
-    // Debug (tests only): show header root and first few entries present
-    if cfg!(test) {
-        eprintln!("DEBUG: Header root commit: {}", commit_cid_str);
-        eprintln!("DEBUG: Entries in CAR: {}", cid_map.len());
-        let mut __dbg_count = 0usize;
-        for k in cid_map.keys() {
-            if __dbg_count < 10 {
-                eprintln!("DEBUG: Entry key: {}", k);
-            }
-            __dbg_count += 1;
-            if __dbg_count >= 10 {
-                break;
-            }
-        }
-    }
+    debug!("CAR header root {} with {} blocks", commit_cid_str, index.len());
 
     // Parse the commit to get the data MST root; if commit block is absent or
     // header points directly to MST, fall back to using header root as MST root
-    let data_cid = match parse_commit(&cid_map, &commit_cid_str) {
+    let data_cid = match parse_commit(index, &commit_cid_str) {
         Ok(cid) => cid,
         Err(e) => {
             // Fallback: treat the header root as an MST node directly
-            if parse_mst_node(&cid_map, &commit_cid_str).is_ok() {
+            if parse_mst_node(index, &commit_cid_str).is_ok() {
                 commit_cid_str.clone()
-            } else if let Some(root) = detect_mst_root(&cid_map) {
+            } else if let Some(root) = detect_mst_root(index) {
                 root
             } else {
                 return Err(e);
@@ -96,21 +141,62 @@ pub fn extract_cid_to_rkey_mapping(
         }
     };
 
-    // Walk the MST and collect CID -> rkey mappings
+    // Walk the MST, fetching nodes from the index as they are reached
     let mut mappings = HashMap::new();
-    walk_mst(&cid_map, &data_cid, collection, &mut mappings)?;
+    walk_mst(index, &data_cid, &mut None, &mut mappings)?;
 
     Ok(mappings)
 }
 
+/// Block bytes for a CID string, if it is a canonical CID the CAR holds
+fn get_block<'a>(index: &CarIndex<'a>, cid: &str) -> Option<&'a [u8]> {
+    index.get_block(&cid.parse().ok()?)
+}
+
+/// Read the repo DID from the commit block the CAR header's root points to
+///
+/// Attribution must come from the commit rather than from how the file was obtained,
+/// since a locally supplied CAR carries no other trustworthy record of its owner.
+pub fn extract_commit_did(car_bytes: &[u8]) -> Result<String, CarError> {
+    let car_reader = SyncCarReader::from_bytes(car_bytes)?;
+    let commit_cid_str = car_reader
+        .header()
+        .roots
+        .first()
+        .ok_or_else(|| CarError::InvalidHeader("Missing root CID in CAR header".to_string()))
+        .map(format_cid)?;
+
+    for entry_result in car_reader {
+        let entry = entry_result?;
+        if format_cid(&entry.cid) != commit_cid_str {
+            continue;
+        }
+        let value = decode_cbor(&entry.bytes)
+            .map_err(|e| CarError::InvalidHeader(format!("Failed to decode commit: {}", e)))?;
+        if let CborValue::Map(map) = value {
+            if let Some(did) = get_text_field(&map, "did").filter(|d| d.starts_with("did:")) {
+                return Ok(did.to_string());
+            }
+        }
+        return Err(CarError::InvalidHeader(
+            "Commit has no repo DID".to_string(),
+        ));
+    }
+
+    Err(CarError::InvalidHeader(format!(
+        "Commit CID not found: {}",
+        commit_cid_str
+    )))
+}
+
 /// Detect MST root by scanning all MST nodes and finding the one not referenced
-fn detect_mst_root(cid_map: &HashMap<String, Vec<u8>>) -> Option<String> {
+fn detect_mst_root(index: &CarIndex) -> Option<String> {
     let mut nodes: HashSet<String> = HashSet::new();
     let mut referenced: HashSet<String> = HashSet::new();
 
-    for cid in cid_map.keys() {
-        if let Ok(node) = parse_mst_node(cid_map, cid) {
-            nodes.insert(cid.clone());
+    for cid in index.cids().map(format_cid) {
+        if let Ok(node) = parse_mst_node(index, &cid) {
+            nodes.insert(cid);
             if let Some(l) = node.l {
                 referenced.insert(l);
             }
@@ -132,27 +218,18 @@ fn detect_mst_root(cid_map: &HashMap<String, Vec<u8>>) -> Option<String> {
 }
 
 /// Parse commit object to extract data MST root CID
-fn parse_commit(cid_map: &HashMap<String, Vec<u8>>, commit_cid: &str) -> Result<String, CarError> {
-    let bytes = cid_map
-        .get(commit_cid)
+fn parse_commit(index: &CarIndex, commit_cid: &str) -> Result<String, CarError> {
+    let bytes = get_block(index, commit_cid)
         .ok_or_else(|| CarError::InvalidHeader(format!("Commit CID not found: {}", commit_cid)))?;
 
     let value = decode_cbor(bytes)
         .map_err(|e| CarError::InvalidHeader(format!("Failed to decode commit: {}", e)))?;
 
-    // Debug (tests only): print commit structure
-    if cfg!(test) {
-        eprintln!("DEBUG: Commit structure: {:#?}", value);
-    }
-
     if let CborValue::Map(map) = value {
         // Extract "data" field which points to MST root
         // Manual search for "data" field that contains a CID link
         for (k, v) in map.iter() {
             if let CborValue::Text(key) = k {
-                if cfg!(test) {
-                    eprintln!("DEBUG: Found key '{}' in commit", key);
-                }
                 if *key == "data" {
                     return extract_cid_from_cbor(v);
                 }
@@ -165,18 +242,21 @@ fn parse_commit(cid_map: &HashMap<String, Vec<u8>>, commit_cid: &str) -> Result<
     ))
 }
 
-/// Walk MST recursively and collect all CID -> collection/rkey mappings
+/// Walk MST recursively and collect all CID -> (collection, rkey) mappings
+///
+/// `previous_key` is the last key visited anywhere in the tree; an in-order walk of a
+/// well-formed MST sees strictly increasing keys, so anything else is rejected.
 fn walk_mst(
-    cid_map: &HashMap<String, Vec<u8>>,
+    index: &CarIndex,
     node_cid: &str,
-    collection_filter: &str,
-    mappings: &mut HashMap<String, String>,
+    previous_key: &mut Option<String>,
+    mappings: &mut HashMap<String, (String, String)>,
 ) -> Result<(), CarError> {
-    let node_data = parse_mst_node(cid_map, node_cid)?;
+    let node_data = parse_mst_node(index, node_cid)?;
 
     // Process left subtree first
     if let Some(ref left_cid) = node_data.l {
-        walk_mst(cid_map, left_cid, collection_filter, mappings)?;
+        walk_mst(index, left_cid, previous_key, mappings)?;
     }
 
     let mut last_key = String::new();
@@ -196,20 +276,27 @@ fn walk_mst(
         let suffix = std::str::from_utf8(&entry.k)
             .map_err(|e| CarError::InvalidHeader(format!("Invalid UTF-8 in key: {}", e)))?;
 
-        let key = format!("{}{}", &last_key[..prefix_len], suffix);
+        let prefix = last_key.get(..prefix_len).ok_or_else(|| {
+            CarError::InvalidHeader(format!("Prefix length {} splits a character", prefix_len))
+        })?;
+        let key = format!("{}{}", prefix, suffix);
+        if let Some(previous) = previous_key.as_ref().filter(|previous| key <= **previous) {
+            return Err(CarError::MstOrder {
+                previous: previous.clone(),
+                key,
+            });
+        }
         last_key = key.clone();
+        *previous_key = Some(key.clone());
 
-        // Key format is "collection/rkey", filter by collection
+        // Key format is "collection/rkey"
         if let Some((coll, rkey)) = key.split_once('/') {
-            if coll == collection_filter {
-                // Map the value CID to collection/rkey
-                mappings.insert(entry.v.clone(), format!("{}/{}", coll, rkey));
-            }
+            mappings.insert(entry.v.clone(), (coll.to_string(), rkey.to_string()));
         }
 
         // Process right subtree for this entry
         if let Some(ref subtree_cid) = entry.t {
-            walk_mst(cid_map, subtree_cid, collection_filter, mappings)?;
+            walk_mst(index, subtree_cid, previous_key, mappings)?;
         }
     }
 
@@ -217,9 +304,8 @@ fn walk_mst(
 }
 
 /// Parse MST node from CBOR bytes
-fn parse_mst_node(cid_map: &HashMap<String, Vec<u8>>, cid: &str) -> Result<NodeData, CarError> {
-    let bytes = cid_map
-        .get(cid)
+fn parse_mst_node(index: &CarIndex, cid: &str) -> Result<NodeData, CarError> {
+    let bytes = get_block(index, cid)
         .ok_or_else(|| CarError::InvalidHeader(format!("Node CID not found: {}", cid)))?;
 
     let value = decode_cbor(bytes)
@@ -232,10 +318,8 @@ fn parse_mst_node(cid_map: &HashMap<String, Vec<u8>>, cid: &str) -> Result<NodeD
         for (k, v) in map.iter() {
             if let CborValue::Text(key) = k {
                 match *key {
-                    "l" => {
-                        if !matches!(v, CborValue::Null) {
-                            l = Some(extract_cid_from_cbor(v)?);
-                        }
+                    "l" if !matches!(v, CborValue::Null) => {
+                        l = Some(extract_cid_from_cbor(v)?);
                     }
                     "e" => {
                         if let CborValue::Array(entries) = v {
@@ -281,10 +365,8 @@ fn parse_tree_entry(value: &CborValue) -> Result<TreeEntry, CarError> {
                     "v" => {
                         v = extract_cid_from_cbor(val)?;
                     }
-                    "t" => {
-                        if !matches!(val, CborValue::Null) {
-                            t = Some(extract_cid_from_cbor(val)?);
-                        }
+                    "t" if !matches!(val, CborValue::Null) => {
+                        t = Some(extract_cid_from_cbor(val)?);
                     }
                     _ => {}
                 }
@@ -311,8 +393,7 @@ fn extract_cid_from_cbor(value: &CborValue) -> Result<String, CarError> {
                 if let CborValue::Text(key) = k {
                     if *key == "$link" {
                         if let CborValue::Text(cid_str) = v {
-                            // Return the string as-is for compatibility with tests that
-                            // construct $link as text (these do not reconcile with iterator CIDs)
+                            // Already canonical, so it matches the iterator's CID keys
                             return Ok(cid_str.to_string());
                         }
                     }
@@ -328,69 +409,23 @@ fn extract_cid_from_cbor(value: &CborValue) -> Result<String, CarError> {
     }
 }
 
-// Parse CID bytes from DAG-CBOR link into our simple string key format used by iterators
-fn parse_cid_link_bytes(bytes: &[u8]) -> Result<String, CarError> {
-    // CID (multicodec) structure: varint(version), varint(codec), varint(multihash code),
-    // varint(digest length), digest bytes
-    let mut pos = 0usize;
-    fn read_varint_local(bytes: &[u8], pos: &mut usize) -> Result<u64, CarError> {
-        let mut value: u64 = 0;
-        let mut shift = 0;
-        let mut count = 0;
-        while *pos < bytes.len() {
-            let b = bytes[*pos];
-            *pos += 1;
-            value |= ((b & 0x7F) as u64) << shift;
-            shift += 7;
-            count += 1;
-            if b & 0x80 == 0 {
-                break;
-            }
-            if count >= 10 {
-                return Err(CarError::VarintError("Varint too long".to_string()));
-            }
-        }
-        if count == 0 {
-            return Err(CarError::UnexpectedEof);
-        }
-        Ok(value)
-    }
-
-    // Some encodings may include a leading 0x00 marker before varints; support both
-    if pos < bytes.len() && bytes[pos] == 0 {
-        pos += 1;
+/// CID string of a blob's `ref`: a DAG-CBOR link (as in repo records, rendered canonically),
+/// a `{"$link": cid}` map or a plain string
+pub(crate) fn blob_ref_cid(value: &CborValue) -> Option<String> {
+    match value {
+        CborValue::Text(cid) => Some(cid.to_string()),
+        _ => extract_cid_from_cbor(value).ok(),
     }
+}
 
-    let version = read_varint_local(bytes, &mut pos)? as u8;
-    let codec = read_varint_local(bytes, &mut pos)? as u8;
-    let digest_type = read_varint_local(bytes, &mut pos)? as u8;
-    let digest_size = read_varint_local(bytes, &mut pos)? as usize;
-    if pos + digest_size > bytes.len() {
-        return Err(CarError::UnexpectedEof);
-    }
-    let digest = &bytes[pos..pos + digest_size];
-
-    // Match the same formatting used by CarRecords::format_cid_simple
-    Ok(format!(
-        "v{}-c{:02x}-d{:02x}-{}",
-        version,
-        codec,
-        digest_type,
-        hex::encode(digest)
-    ))
+/// Canonical CID string for the raw CID bytes of a DAG-CBOR link
+pub(crate) fn parse_cid_link_bytes(bytes: &[u8]) -> Result<String, CarError> {
+    crate::car::Cid::from_bytes(bytes).map(|cid| cid.to_string())
 }
 
-/// Format CID for use as map key (simple string representation)
-fn format_cid(cid: &crate::car::Cid) -> String {
-    // Use a simple base representation for now
-    // In a full implementation, this would use multibase encoding
-    format!(
-        "v{}-c{:02x}-d{:02x}-{}",
-        cid.version,
-        cid.codec,
-        cid.digest_type,
-        hex::encode(&cid.digest)
-    )
+/// Canonical CID string, used both as the block map key and wherever CIDs reach users
+pub(crate) fn format_cid(cid: &crate::car::Cid) -> String {
+    cid.to_string()
 }
 
 // TODO: Rewrite these tests to use in-house CBOR encoder instead of serde_cbor
@@ -449,6 +484,14 @@ mod tests {
                 buf.push(0x44); // 4-byte bytes
                 buf.extend_from_slice(b"test");
             }
+            CborValue::Float(f) => {
+                buf.push(0xFB);
+                buf.extend_from_slice(&f.to_be_bytes());
+            }
+            CborValue::Tag(tag, inner) => {
+                buf.push(0xC0 | (*tag as u8 & 0x1F));
+                encode_value(buf, inner);
+            }
         }
     }
 
@@ -711,8 +754,8 @@ mod tests {
         };
 
         let formatted = format_cid(&cid);
-        assert!(formatted.starts_with("v1-c71-d12-"));
-        assert!(formatted.contains("abcdef"));
+        assert!(formatted.starts_with("bafyre"), "{}", formatted);
+        assert_eq!(formatted.parse::<crate::car::Cid>().unwrap(), cid);
     }
 
     #[test]
@@ -1,13 +1,139 @@
 //! Repository provider for fetching and parsing ATProto repositories.
 
 use crate::bluesky::did::DidResolver;
+use crate::bluesky::records::QuotedPost;
+use crate::car::{CarError, CarRecords};
 use crate::error::AppError;
+use crate::http::{rate_limit_error, retry_policy, send_with_retry, RetryPolicy, XrpcError};
 use futures::StreamExt;
 use reqwest::Client;
-use std::path::PathBuf;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 use std::time::Duration;
 use tracing::debug;
 
+/// How long a cached repository CAR is served before it is downloaded again
+pub const DEFAULT_REPO_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Environment variable consulted when `--cache-dir` is not given
+pub const CACHE_DIR_ENV: &str = "AUTOREPLY_CACHE_DIR";
+
+/// AppView queried for post views (app.bsky.feed.getPosts)
+pub const DEFAULT_APPVIEW: &str = "https://public.api.bsky.app";
+
+/// app.bsky.feed.getPosts accepts at most 25 URIs per call
+pub const GET_POSTS_MAX_URIS: usize = 25;
+
+/// Largest repository CAR downloaded unless configured otherwise (1 GiB)
+pub const DEFAULT_MAX_CAR_BYTES: usize = 1024 * 1024 * 1024;
+
+static CACHE_CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+
+static MAX_CAR_BYTES: OnceLock<usize> = OnceLock::new();
+
+/// One lock per cache file being fetched, shared by every provider in the process,
+/// so concurrent requests for the same repo wait for a single download
+static IN_FLIGHT: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Where repository CAR files are cached, and whether the cache is used at all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Directory holding cached `{did}.car` files
+    pub dir: PathBuf,
+    /// Always download fresh and never write to the cache
+    pub no_cache: bool,
+    /// Age after which a cached CAR is downloaded again
+    pub ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_cache_dir(),
+            no_cache: false,
+            ttl: DEFAULT_REPO_CACHE_TTL,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Settings from a CLI value, falling back to `AUTOREPLY_CACHE_DIR`, then the config
+    /// file's `cache-dir`, then the OS cache dir
+    pub fn from_settings(
+        cli_dir: Option<&Path>,
+        file_dir: Option<&Path>,
+        no_cache: bool,
+        ttl: Option<Duration>,
+    ) -> Self {
+        let env_dir = std::env::var_os(CACHE_DIR_ENV).map(PathBuf::from);
+        Self {
+            dir: choose_cache_dir(cli_dir, env_dir.as_deref(), file_dir),
+            no_cache,
+            ttl: ttl.unwrap_or(DEFAULT_REPO_CACHE_TTL),
+        }
+    }
+}
+
+/// The first non-empty directory of flag, environment and config file, else the default
+fn choose_cache_dir(
+    cli_dir: Option<&Path>,
+    env_dir: Option<&Path>,
+    file_dir: Option<&Path>,
+) -> PathBuf {
+    [cli_dir, env_dir, file_dir]
+        .into_iter()
+        .flatten()
+        .find(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(default_cache_dir, Path::to_path_buf)
+}
+
+/// `{OS cache dir}/autoreply/repos`, or under the temp dir when there is no cache dir
+fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("autoreply")
+        .join("repos")
+}
+
+/// Configure the repository cache from CLI values, the environment and the config file.
+/// Call once at startup; a second call keeps the first configuration.
+pub fn init_cache_config(cli_dir: Option<&Path>, no_cache: bool, ttl: Option<Duration>) {
+    let file_dir = crate::config::config().cache_dir.as_deref();
+    let _ = CACHE_CONFIG.set(CacheConfig::from_settings(cli_dir, file_dir, no_cache, ttl));
+}
+
+/// The configured repository cache (the OS cache dir if none was configured)
+pub fn cache_config() -> &'static CacheConfig {
+    CACHE_CONFIG.get_or_init(CacheConfig::default)
+}
+
+/// Configure the largest repository CAR downloaded, from the CLI value in MB.
+/// Call once at startup; a second call keeps the first configuration.
+pub fn init_max_car_bytes(max_mb: Option<u64>) {
+    let max_bytes = max_mb.map_or(DEFAULT_MAX_CAR_BYTES, |mb| {
+        usize::try_from(mb.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX)
+    });
+    let _ = MAX_CAR_BYTES.set(max_bytes);
+}
+
+/// The configured repository size limit (`DEFAULT_MAX_CAR_BYTES` if none was configured)
+pub fn max_car_bytes() -> usize {
+    *MAX_CAR_BYTES.get_or_init(|| DEFAULT_MAX_CAR_BYTES)
+}
+
+/// A provider with the cache, retry policy and size limit configured at startup
+pub fn configured_provider() -> Result<RepositoryProvider, AppError> {
+    Ok(RepositoryProvider::new(cache_config().clone())?
+        .with_retry_policy(retry_policy().clone())
+        .with_max_car_bytes(max_car_bytes()))
+}
+
 /// Provides a parsed `Repo` object for a given DID.
 ///
 /// This provider encapsulates the logic for:
@@ -18,12 +144,49 @@ use tracing::debug;
 pub struct RepositoryProvider {
     client: Client,
     cache_dir: PathBuf,
+    no_cache: bool,
+    cache_ttl: Duration,
+    retry: RetryPolicy,
+    max_car_bytes: usize,
+    appview: String,
     did_resolver: DidResolver,
 }
 
+/// Strong reference to a record (`com.atproto.repo.strongRef`): its URI and content CID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrongRef {
+    pub uri: String,
+    pub cid: String,
+}
+
+#[derive(Deserialize)]
+struct GetPostsResponse<T> {
+    posts: Vec<T>,
+}
+
+/// The parts of an app.bsky.feed.defs#postView read for a quoted post
+#[derive(Deserialize)]
+struct QuotedPostView {
+    uri: String,
+    author: QuotedPostAuthor,
+    record: QuotedPostText,
+}
+
+#[derive(Deserialize)]
+struct QuotedPostAuthor {
+    handle: String,
+}
+
+#[derive(Deserialize)]
+struct QuotedPostText {
+    #[serde(default)]
+    text: String,
+}
+
 impl RepositoryProvider {
-    /// Creates a new `RepositoryProvider`.
-    pub fn new() -> Result<Self, AppError> {
+    /// Creates a new `RepositoryProvider` caching as `config` describes, with the default
+    /// retry policy and `DEFAULT_MAX_CAR_BYTES` size limit.
+    pub fn new(config: CacheConfig) -> Result<Self, AppError> {
         let client = Client::builder()
             // No total timeout - let downloads complete as long as data flows
             .connect_timeout(Duration::from_secs(120)) // 2 minutes to establish connection (slow/flaky networks)
@@ -32,28 +195,92 @@ impl RepositoryProvider {
             .build()
             .map_err(|e| AppError::HttpClientInitialization(e.to_string()))?;
 
-        let cache_dir = dirs::cache_dir()
-            .unwrap_or_else(std::env::temp_dir)
-            .join("autoreply")
-            .join("repos");
+        let CacheConfig {
+            dir: cache_dir,
+            no_cache,
+            ttl,
+        } = config;
 
-        std::fs::create_dir_all(&cache_dir).map_err(|e| {
-            AppError::CacheError(format!("Failed to create cache directory: {}", e))
-        })?;
+        if !no_cache {
+            std::fs::create_dir_all(&cache_dir).map_err(|e| {
+                AppError::CacheError(format!("Failed to create cache directory: {}", e))
+            })?;
+        }
 
-        let did_resolver = DidResolver::new();
+        let retry = RetryPolicy::default();
+        let did_resolver = DidResolver::new().with_retry_policy(retry.clone());
         Ok(Self {
             client,
             cache_dir,
+            no_cache,
+            cache_ttl: DEFAULT_REPO_CACHE_TTL,
+            retry,
+            max_car_bytes: DEFAULT_MAX_CAR_BYTES,
+            appview: DEFAULT_APPVIEW.to_string(),
             did_resolver,
-        })
+        }
+        .with_cache_ttl(ttl))
     }
 
-    /// Fetches the repository CAR file for a DID.
-    ///
-    /// Streams the CAR file directly to disk with atomic operations as specified in PROCEED-FIX.md.
-    /// Returns the path to the cached CAR file.
-    pub async fn fetch_repo_car(&self, did: &str) -> Result<PathBuf, AppError> {
+    /// Re-download cached repositories once they are older than `ttl`
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Retry failed GETs, and resolve DIDs, under `retry` instead of the default policy
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.did_resolver = self.did_resolver.with_retry_policy(retry.clone());
+        self.retry = retry;
+        self
+    }
+
+    /// Abandon repository downloads larger than `max_bytes`, without buffering past it
+    pub fn with_max_car_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_car_bytes = max_bytes;
+        self
+    }
+
+    /// Look up post views on `base_url` instead of the public Bluesky AppView
+    pub fn with_appview(mut self, base_url: &str) -> Self {
+        self.appview = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The resolver this provider finds PDS endpoints with
+    pub(crate) fn resolver(&self) -> &DidResolver {
+        &self.did_resolver
+    }
+
+    /// The policy this provider retries failed GETs under
+    pub(crate) fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// The error for a repository that outgrew the size limit
+    fn too_large(&self, did: &str, bytes: u64) -> AppError {
+        AppError::RepoTooLarge(format!(
+            "the repo of {} is over {} bytes (at least {}); raise the limit with --max-repo-mb",
+            did, self.max_car_bytes, bytes
+        ))
+    }
+
+    /// Whether a cached CAR was written within the cache TTL, judged by its mtime
+    fn is_fresh(&self, path: &Path) -> bool {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age < self.cache_ttl)
+    }
+
+    /// Path a DID's repository is cached at
+    fn cache_path(&self, did: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.car", did.replace(':', "_")))
+    }
+
+    /// Start downloading the repository CAR for a DID from its PDS
+    async fn request_repo(&self, did: &str) -> Result<reqwest::Response, AppError> {
         // Resolve DID to PDS endpoint
         let pds_endpoint = self.did_resolver.discover_pds(did).await?.ok_or_else(|| {
             AppError::DidResolveFailed(format!("Could not determine PDS for DID {}", did))
@@ -61,62 +288,216 @@ impl RepositoryProvider {
         let url = format!("{}/xrpc/com.atproto.sync.getRepo?did={}", pds_endpoint, did);
         debug!("Fetching repo from URL: {}", url);
 
-        // Generate cache file paths
-        let cache_filename = format!("{}.car", did.replace(':', "_"));
-        let final_path = self.cache_dir.join(&cache_filename);
+        let response = send_with_retry(&self.retry, || {
+            self.client.get(&url).header("Accept", "application/vnd.ipld.car")
+        })
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Failed to connect: {}", e)))?;
 
-        // Check if cached file exists (no TTL or metadata per PROCEED-FIX.md spec)
-        if final_path.exists() {
-            debug!("Using cached repo for {}", did);
-            return Ok(final_path);
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
         }
-
-        // Generate temporary file path with randomized suffix to avoid collisions
-        let temp_filename = format!("{}.tmp.{}", cache_filename, std::process::id());
-        let temp_path = self.cache_dir.join(&temp_filename);
-
-        // Fetch CAR file and stream directly to temp file
-        let response = self
-            .client
-            .get(&url)
-            .header("Accept", "application/vnd.ipld.car")
-            .send()
-            .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to connect: {}", e)))?;
-
         if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            if let Some(e) = XrpcError::parse(&body).and_then(|x| x.unavailable_error(did)) {
+                return Err(e);
+            }
             return Err(AppError::NetworkError(format!(
                 "Failed to fetch repo: {} {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
+                status, body
             )));
         }
 
-        let content_length = response.content_length().unwrap_or(0);
-        debug!("Downloading repo for {} ({} bytes)", did, content_length);
+        // Refuse a declared oversize body before reading any of it
+        if let Some(length) = response.content_length() {
+            if length > self.max_car_bytes as u64 {
+                return Err(self.too_large(did, length));
+            }
+        }
 
-        // Stream bytes directly to temp file
-        let mut temp_file = tokio::fs::File::create(&temp_path)
+        Ok(response)
+    }
+
+    /// Fetches the repository CAR bytes for a DID, through the cache unless it is disabled.
+    pub async fn fetch_repo_bytes(&self, did: &str) -> Result<Vec<u8>, AppError> {
+        if self.no_cache {
+            debug!("Cache disabled, downloading repo for {} into memory", did);
+            let response = self.request_repo(did).await?;
+            return self.read_car(did, response.bytes_stream()).await;
+        }
+
+        let car_file_path = self.fetch_repo_car(did).await?;
+        tokio::fs::read(&car_file_path)
             .await
-            .map_err(|e| AppError::CacheError(format!("Failed to create temp file: {}", e)))?;
+            .map_err(|e| AppError::CacheError(format!("Failed to read CAR file: {}", e)))
+    }
 
-        let mut stream = response.bytes_stream();
-        let mut bytes_written = 0;
+    /// The cached repository CAR bytes for a DID however old, without the network.
+    /// A cached CAR whose blocks no longer match their CIDs is not returned.
+    pub async fn cached_repo_bytes(&self, did: &str) -> Option<Vec<u8>> {
+        if self.no_cache {
+            return None;
+        }
+        let path = self.cache_path(did);
+        if let Err(e) = verify_car_file(&path).await {
+            debug!("Ignoring cached repo for {}: {}", did, e);
+            return None;
+        }
+        tokio::fs::read(path).await.ok()
+    }
+
+    /// Fetches the repository CAR file for a DID.
+    ///
+    /// Streams the CAR file directly to disk with atomic operations as specified in PROCEED-FIX.md.
+    /// Returns the path to the cached CAR file.
+    pub async fn fetch_repo_car(&self, did: &str) -> Result<PathBuf, AppError> {
+        self.fetch_repo_car_with(did, || async move {
+            // Fetch CAR file and stream directly to the cache
+            let response = self.request_repo(did).await?;
+            let content_length = response.content_length().unwrap_or(0);
+            debug!("Downloading repo for {} ({} bytes)", did, content_length);
+
+            self.store_car(did, response.bytes_stream()).await
+        })
+        .await
+    }
+
+    /// Serve the cached CAR for a DID, running `download` to refresh it when missing or stale.
+    ///
+    /// Callers for the same cache file queue on one lock: the first runs `download`, the
+    /// rest find its fresh file. A failed download releases the lock, so the next caller
+    /// simply tries again.
+    async fn fetch_repo_car_with<F, Fut>(
+        &self,
+        did: &str,
+        download: F,
+    ) -> Result<PathBuf, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<usize, AppError>>,
+    {
+        if self.no_cache {
+            return Err(AppError::CacheError(
+                "Repository cache is disabled (--no-cache)".to_string(),
+            ));
+        }
+
+        let final_path = self.cache_path(did);
+
+        let flight = IN_FLIGHT
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(final_path.clone())
+            .or_default()
+            .clone();
+        let guard = flight.lock().await;
+
+        let result = self.refresh_cached_car(did, &final_path, download).await;
+
+        drop(guard);
+        // Forget the lock once nobody else holds or waits on it
+        let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+        if Arc::strong_count(&flight) == 2 {
+            in_flight.remove(&final_path);
+        }
+
+        result
+    }
+
+    async fn refresh_cached_car<F, Fut>(
+        &self,
+        did: &str,
+        final_path: &Path,
+        download: F,
+    ) -> Result<PathBuf, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<usize, AppError>>,
+    {
+        // Serve the cached file while it is fresh and intact; the rename on download
+        // refreshes its mtime
+        if final_path.exists() {
+            if !self.is_fresh(final_path) {
+                debug!("Cached repo for {} is older than {:?}, refetching", did, self.cache_ttl);
+            } else if let Err(e) = verify_car_file(final_path).await {
+                debug!("Cached repo for {} is damaged ({}), refetching", did, e);
+            } else {
+                debug!("Using cached repo for {}", did);
+                return Ok(final_path.to_path_buf());
+            }
+        }
+
+        let bytes_written = download().await?;
+
+        debug!("Cached repo for {} ({} bytes)", did, bytes_written);
+        Ok(final_path.to_path_buf())
+    }
+
+    /// Collect CAR bytes in memory, failing as soon as they pass the size limit or stop
+    /// parsing as a CAR
+    async fn read_car<S, B, E>(&self, did: &str, mut stream: S) -> Result<Vec<u8>, AppError>
+    where
+        S: futures::Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]>,
+        E: std::fmt::Display,
+    {
+        let mut car = Vec::new();
+        let mut check = CarCheck::start();
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result.map_err(|e| {
                 AppError::NetworkError(format!(
                     "Connection interrupted after {} bytes: {}",
-                    bytes_written, e
+                    car.len(),
+                    e
                 ))
             })?;
-
-            bytes_written += chunk.len();
-            tokio::io::AsyncWriteExt::write_all(&mut temp_file, &chunk)
-                .await
-                .map_err(|e| {
-                    AppError::CacheError(format!("Failed to write to temp file: {}", e))
-                })?;
+            let chunk = chunk.as_ref();
+            if car.len() + chunk.len() > self.max_car_bytes {
+                return Err(self.too_large(did, (car.len() + chunk.len()) as u64));
+            }
+            check.push(chunk).await?;
+            car.extend_from_slice(chunk);
         }
+        let records = check.finish().await?;
+        debug!("Read {} records ({} bytes) for {}", records, car.len(), did);
+        Ok(car)
+    }
+
+    /// Stream CAR bytes into the cache for a DID, returning the number of bytes written.
+    ///
+    /// Bytes go to a temp file that is fsynced and atomically renamed into place,
+    /// so readers never see a partial CAR. A download that passes the size limit or stops
+    /// parsing as a CAR is abandoned and its temp file removed.
+    async fn store_car<S, B, E>(&self, did: &str, stream: S) -> Result<usize, AppError>
+    where
+        S: futures::Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]>,
+        E: std::fmt::Display,
+    {
+        let final_path = self.cache_path(did);
+
+        // Generate temporary file path with randomized suffix to avoid collisions
+        let temp_filename = format!(
+            "{}.car.tmp.{}",
+            did.replace(':', "_"),
+            std::process::id()
+        );
+        let temp_path = self.cache_dir.join(&temp_filename);
+
+        // Stream bytes directly to temp file
+        let mut temp_file = tokio::fs::File::create(&temp_path)
+            .await
+            .map_err(|e| AppError::CacheError(format!("Failed to create temp file: {}", e)))?;
+
+        let bytes_written = match self.write_car(did, &mut temp_file, stream).await {
+            Ok(bytes_written) => bytes_written,
+            Err(e) => {
+                drop(temp_file);
+                let _ = tokio::fs::remove_file(&temp_path).await;
+                return Err(e);
+            }
+        };
 
         // Flush and fsync as required
         tokio::io::AsyncWriteExt::flush(&mut temp_file)
@@ -135,41 +516,263 @@ impl RepositoryProvider {
             AppError::CacheError(format!("Failed to atomically rename temp file: {}", e))
         })?;
 
-        debug!("Cached repo for {} ({} bytes)", did, bytes_written);
-        Ok(final_path)
+        Ok(bytes_written)
+    }
+
+    /// Copy CAR chunks into `file`, parsing them as they arrive and enforcing the size limit
+    async fn write_car<S, B, E>(
+        &self,
+        did: &str,
+        file: &mut tokio::fs::File,
+        mut stream: S,
+    ) -> Result<usize, AppError>
+    where
+        S: futures::Stream<Item = Result<B, E>> + Unpin,
+        B: AsRef<[u8]>,
+        E: std::fmt::Display,
+    {
+        let mut bytes_written = 0;
+        let mut check = CarCheck::start();
+        while let Some(chunk_result) = stream.next().await {
+            let chunk = chunk_result.map_err(|e| {
+                AppError::NetworkError(format!(
+                    "Connection interrupted after {} bytes: {}",
+                    bytes_written, e
+                ))
+            })?;
+            let chunk = chunk.as_ref();
+
+            bytes_written += chunk.len();
+            if bytes_written > self.max_car_bytes {
+                return Err(self.too_large(did, bytes_written as u64));
+            }
+            check.push(chunk).await?;
+            tokio::io::AsyncWriteExt::write_all(file, chunk)
+                .await
+                .map_err(|e| {
+                    AppError::CacheError(format!("Failed to write to temp file: {}", e))
+                })?;
+        }
+        let records = check.finish().await?;
+        debug!("Downloaded {} records for {}", records, did);
+        Ok(bytes_written)
     }
 
     /// Get an iterator over AT Protocol records from a user's repository.
     /// Returns a streaming iterator that yields (record_type, cbor_data) tuples.
     /// This avoids loading all records into memory and supports early termination.
-    pub async fn records(&self, did: &str) -> Result<crate::car::CarRecords, AppError> {
-        let car_file_path = self.fetch_repo_car(did).await?;
+    pub async fn records(&self, did: &str) -> Result<CarRecords, AppError> {
+        let records = if self.no_cache {
+            CarRecords::from_bytes(self.fetch_repo_bytes(did).await?)
+        } else {
+            // Stream the cached file instead of reading it all into memory
+            let car_file_path = self.fetch_repo_car(did).await?;
+            CarRecords::from_file(&car_file_path)
+        };
+
+        records
+            .map_err(|e| AppError::RepoParseFailed(format!("Failed to create CAR iterator: {}", e)))
+    }
 
-        // Read entire file into memory (CAR files are typically 1-10MB)
-        let car_bytes = tokio::fs::read(&car_file_path)
-            .await
-            .map_err(|e| AppError::CacheError(format!("Failed to read CAR file: {}", e)))?;
+    /// Strong ref (URI and CID) of one post, from the AppView instead of the author's repo.
+    /// A post the AppView does not know (deleted, taken down, never existed) is `NotFound`.
+    pub async fn resolve_post_ref(&self, uri: &str) -> Result<StrongRef, AppError> {
+        self.get_posts::<StrongRef>(&[uri.to_string()])
+            .await?
+            .into_iter()
+            .find(|post| post.uri == uri)
+            .ok_or_else(|| AppError::NotFound(format!("Post not found: {}", uri)))
+    }
 
-        // Create iterator from CAR file bytes
-        crate::car::CarRecords::from_bytes(car_bytes)
-            .map_err(|e| AppError::RepoParseFailed(format!("Failed to create CAR iterator: {}", e)))
+    /// Author handle and text of quoted posts, keyed by URI, through app.bsky.feed.getPosts.
+    /// Posts the AppView did not return are missing from the map.
+    pub async fn fetch_quoted_posts(
+        &self,
+        uris: &[String],
+    ) -> Result<HashMap<String, QuotedPost>, AppError> {
+        let views = self.get_posts::<QuotedPostView>(uris).await?;
+        Ok(views
+            .into_iter()
+            .map(|view| {
+                let quoted = QuotedPost {
+                    uri: view.uri.clone(),
+                    author: view.author.handle,
+                    text: view.record.text,
+                };
+                (view.uri, quoted)
+            })
+            .collect())
+    }
+
+    /// Post views of `at://` post URIs, `GET_POSTS_MAX_URIS` per getPosts call
+    pub async fn get_posts<T: DeserializeOwned>(
+        &self,
+        uris: &[String],
+    ) -> Result<Vec<T>, AppError> {
+        if let Some(uri) = uris.iter().find(|uri| !uri.starts_with("at://did:")) {
+            return Err(AppError::InvalidInput(format!(
+                "Expected an at://did:... post URI: {}",
+                uri
+            )));
+        }
+
+        let mut posts = Vec::new();
+        for batch in uris.chunks(GET_POSTS_MAX_URIS) {
+            let query = batch
+                .iter()
+                .map(|uri| format!("uris={}", urlencoding::encode(uri)))
+                .collect::<Vec<_>>()
+                .join("&");
+            let url = format!("{}/xrpc/app.bsky.feed.getPosts?{}", self.appview, query);
+            debug!("Fetching {} posts", batch.len());
+
+            let response = send_with_retry(&self.retry, || self.client.get(&url))
+                .await
+                .map_err(|e| AppError::NetworkError(format!("Failed to fetch posts: {}", e)))?;
+            if let Some(e) = rate_limit_error(&response) {
+                return Err(e);
+            }
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(AppError::NetworkError(format!(
+                    "Failed to fetch posts: {} {}",
+                    status, body
+                )));
+            }
+
+            let body: GetPostsResponse<T> = response.json().await.map_err(|e| {
+                AppError::ParseError(format!("Failed to parse getPosts response: {}", e))
+            })?;
+            posts.extend(body.posts);
+        }
+        Ok(posts)
     }
 }
 
 impl Default for RepositoryProvider {
     fn default() -> Self {
-        Self::new().expect("Failed to create default RepositoryProvider")
+        Self::new(CacheConfig::default()).expect("Failed to create default RepositoryProvider")
+    }
+}
+
+/// Parses a CAR on a blocking thread as its download arrives, one chunk at a time, so a
+/// malformed, truncated or corrupted body (a block not matching its CID) fails without
+/// being buffered or cached first
+struct CarCheck {
+    chunks: Option<tokio::sync::mpsc::Sender<Vec<u8>>>,
+    parse: tokio::task::JoinHandle<Result<usize, CarError>>,
+    parsed: Option<usize>,
+}
+
+impl CarCheck {
+    /// Chunks queued for the parser before the download waits on it
+    const QUEUE: usize = 4;
+
+    fn start() -> Self {
+        let (chunks, receiver) = tokio::sync::mpsc::channel(Self::QUEUE);
+        let parse = tokio::task::spawn_blocking(move || {
+            let reader = ChunkReader {
+                chunks: receiver,
+                chunk: Vec::new(),
+                pos: 0,
+            };
+            let mut records = 0;
+            for record in CarRecords::from_reader(reader)?.with_verify(true) {
+                record?;
+                records += 1;
+            }
+            Ok(records)
+        });
+        Self {
+            chunks: Some(chunks),
+            parse,
+            parsed: None,
+        }
+    }
+
+    /// Hand the parser the next chunk, failing once it has rejected the CAR
+    async fn push(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        let Some(chunks) = &self.chunks else {
+            return Ok(());
+        };
+        if chunks.send(chunk.to_vec()).await.is_err() {
+            // The parser stopped early: on an error, or after a CARv2 data section
+            self.chunks = None;
+            self.parsed = Some(self.join().await?);
+        }
+        Ok(())
+    }
+
+    /// Close the stream and wait for the parser, returning the number of records read
+    async fn finish(mut self) -> Result<usize, AppError> {
+        self.chunks = None;
+        match self.parsed {
+            Some(records) => Ok(records),
+            None => self.join().await,
+        }
+    }
+
+    async fn join(&mut self) -> Result<usize, AppError> {
+        match (&mut self.parse).await {
+            Ok(parsed) => Ok(parsed?),
+            Err(e) => Err(AppError::RepoParseFailed(format!("CAR parser stopped: {}", e))),
+        }
+    }
+}
+
+/// Read a cached CAR file through on a blocking thread, checking every block against its
+/// CID, and return the number of records in it
+async fn verify_car_file(path: &Path) -> Result<usize, AppError> {
+    let path = path.to_path_buf();
+    let verify = tokio::task::spawn_blocking(move || {
+        let mut records = 0;
+        for record in CarRecords::from_file(&path)?.with_verify(true) {
+            record?;
+            records += 1;
+        }
+        Ok::<_, CarError>(records)
+    });
+    match verify.await {
+        Ok(verified) => Ok(verified?),
+        Err(e) => Err(AppError::RepoParseFailed(format!("CAR check stopped: {}", e))),
+    }
+}
+
+/// `Read` over the chunks of a download, blocking until the next one arrives
+struct ChunkReader {
+    chunks: tokio::sync::mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::car::test_support::synthetic_repo_car;
     use std::fs;
 
     #[test]
     fn test_repository_provider_new() {
-        let provider = RepositoryProvider::new();
+        let provider = RepositoryProvider::new(CacheConfig::default());
         assert!(provider.is_ok(), "Should create provider successfully");
 
         let provider = provider.unwrap();
@@ -192,7 +795,7 @@ mod tests {
 
     #[test]
     fn test_cache_dir_in_correct_location() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Verify cache is in system cache dir or temp, not in project dir
         let cache_path = provider.cache_dir.to_string_lossy();
@@ -207,7 +810,7 @@ mod tests {
 
     #[test]
     fn test_cache_filename_sanitization() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // DIDs contain colons which must be sanitized
         let did = "did:plc:abc123";
@@ -222,7 +825,7 @@ mod tests {
 
     #[test]
     fn test_temp_filename_generation() {
-        let _provider = RepositoryProvider::new().unwrap();
+        let _provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         let did = "did:plc:test123";
         let cache_filename = format!("{}.car", did.replace(':', "_"));
@@ -238,8 +841,8 @@ mod tests {
 
     #[test]
     fn test_multiple_providers_share_cache() {
-        let provider1 = RepositoryProvider::new().unwrap();
-        let provider2 = RepositoryProvider::new().unwrap();
+        let provider1 = RepositoryProvider::new(CacheConfig::default()).unwrap();
+        let provider2 = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Both should use same cache directory
         assert_eq!(provider1.cache_dir, provider2.cache_dir);
@@ -247,7 +850,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_repo_car_invalid_did() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Invalid DID should fail during resolution
         let result = provider.fetch_repo_car("not-a-did").await;
@@ -256,7 +859,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_repo_car_empty_did() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         let result = provider.fetch_repo_car("").await;
         assert!(result.is_err(), "Should fail for empty DID");
@@ -264,7 +867,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_repo_car_unsupported_did_method() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // did:key is unsupported for PDS discovery
         let result = provider
@@ -275,7 +878,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_records_invalid_did() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         let result = provider.records("not-a-did").await;
         assert!(result.is_err(), "Should fail for invalid DID");
@@ -283,7 +886,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_records_empty_did() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         let result = provider.records("").await;
         assert!(result.is_err(), "Should fail for empty DID");
@@ -291,7 +894,7 @@ mod tests {
 
     #[test]
     fn test_cache_path_structure() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
         let did = "did:plc:5cajdgeo6qz32kptlpg4c3lv";
 
         let cache_filename = format!("{}.car", did.replace(':', "_"));
@@ -312,7 +915,7 @@ mod tests {
 
     #[test]
     fn test_client_configuration() {
-        let _provider = RepositoryProvider::new().unwrap();
+        let _provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Client should be configured with reasonable timeouts
         // Can't directly inspect reqwest::Client config, but can verify it was created
@@ -326,7 +929,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_repo_car_uses_cache() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
         let did = "did:plc:testcachecheck";
 
         let cache_filename = format!("{}.car", did.replace(':', "_"));
@@ -348,7 +951,7 @@ mod tests {
 
     #[test]
     fn test_atomic_rename_paths() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
         let did = "did:plc:atomictest";
 
         let cache_filename = format!("{}.car", did.replace(':', "_"));
@@ -378,7 +981,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_handles_network_interruption() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Invalid URL will cause network error
         // This tests error handling for interrupted downloads
@@ -406,7 +1009,7 @@ mod tests {
 
     #[test]
     fn test_cache_dir_creation_permissions() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
 
         // Verify cache directory is writable
         let test_file = provider.cache_dir.join("test_write.tmp");
@@ -420,7 +1023,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_records_with_cached_car() {
-        let provider = RepositoryProvider::new().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig::default()).unwrap();
         let did = "did:plc:testrecords";
 
         let cache_filename = format!("{}.car", did.replace(':', "_"));
@@ -449,4 +1052,438 @@ mod tests {
             "Should attempt to parse CAR"
         );
     }
+
+    #[test]
+    fn test_cache_config_precedence() {
+        // CLI value wins over everything
+        let config = CacheConfig::from_settings(
+            Some(Path::new("/tmp/cli-cache")),
+            Some(Path::new("/tmp/file-cache")),
+            true,
+            Some(Duration::from_secs(30)),
+        );
+        assert_eq!(config.dir, PathBuf::from("/tmp/cli-cache"));
+        assert!(config.no_cache);
+        assert_eq!(config.ttl, Duration::from_secs(30));
+
+        // Without a CLI value or environment override the default path is unchanged
+        if std::env::var_os(CACHE_DIR_ENV).is_none() {
+            let config = CacheConfig::from_settings(None, None, false, None);
+            assert_eq!(config, CacheConfig::default());
+            assert!(config.dir.ends_with(Path::new("autoreply").join("repos")));
+        }
+    }
+
+    #[test]
+    fn test_cache_dir_flag_env_file_default_order() {
+        let (cli, env, file) = (Path::new("/cli"), Path::new("/env"), Path::new("/file"));
+        assert_eq!(choose_cache_dir(Some(cli), Some(env), Some(file)), cli);
+        assert_eq!(choose_cache_dir(None, Some(env), Some(file)), env);
+        assert_eq!(choose_cache_dir(None, None, Some(file)), file);
+        assert_eq!(choose_cache_dir(None, None, None), default_cache_dir());
+        // An empty environment variable counts as unset
+        assert_eq!(choose_cache_dir(None, Some(Path::new("")), Some(file)), file);
+    }
+
+    #[tokio::test]
+    async fn test_custom_cache_dir_reads_and_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("nested").join("repos");
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: dir.clone(),
+            no_cache: false,
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        assert!(dir.is_dir(), "Cache directory should be created");
+
+        // Writes land in the configured directory under the sanitized DID
+        let car = synthetic_repo_car(None, &[("3k1", "hello")]);
+        let chunks: Vec<Result<&[u8], std::io::Error>> = car.chunks(7).map(Ok).collect();
+        let written = provider
+            .store_car("did:plc:written", futures::stream::iter(chunks))
+            .await
+            .unwrap();
+        assert_eq!(written, car.len());
+        let stored = dir.join("did_plc_written.car");
+        assert_eq!(fs::read(&stored).unwrap(), car);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1, "No temp file left behind");
+
+        // Reads are served from there without resolving the DID
+        assert_eq!(provider.fetch_repo_car("did:plc:written").await.unwrap(), stored);
+        assert_eq!(provider.fetch_repo_bytes("did:plc:written").await.unwrap(), car);
+    }
+
+    #[tokio::test]
+    async fn test_oversize_download_stops_at_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            no_cache: false,
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_max_car_bytes(3 * 1024);
+
+        // A large repo in 1 KB chunks, counting how many are pulled
+        let text = "x".repeat(200);
+        let rkeys: Vec<String> = (0..500).map(|i| format!("3k{:04}", i)).collect();
+        let posts: Vec<(&str, &str)> = rkeys.iter().map(|r| (r.as_str(), text.as_str())).collect();
+        let car = synthetic_repo_car(None, &posts);
+        assert!(car.len() > 100 * 1024);
+        let pulled = AtomicUsize::new(0);
+        let body = || {
+            futures::stream::iter(car.chunks(1024).map(|c| Ok::<_, std::io::Error>(c.to_vec())))
+                .inspect(|_| {
+                    pulled.fetch_add(1, Ordering::SeqCst);
+                })
+        };
+
+        let result = provider.store_car("did:plc:huge", body()).await;
+        assert!(matches!(result, Err(AppError::RepoTooLarge(_))), "{:?}", result);
+        assert_eq!(pulled.load(Ordering::SeqCst), 4, "Stops at the first chunk over the limit");
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0, "Temp file removed");
+
+        // The in-memory path used with --no-cache stops the same way
+        pulled.store(0, Ordering::SeqCst);
+        let result = provider.read_car("did:plc:huge", body()).await;
+        assert!(matches!(result, Err(AppError::RepoTooLarge(_))), "{:?}", result);
+        assert_eq!(pulled.load(Ordering::SeqCst), 4);
+
+        // Bodies within the limit are read whole
+        let small_car = synthetic_repo_car(None, &[("3k1", "small")]);
+        let small = futures::stream::iter(vec![Ok::<_, std::io::Error>(small_car.clone())]);
+        assert_eq!(provider.read_car("did:plc:small", small).await.unwrap(), small_car);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_download_fails_as_bytes_arrive() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            no_cache: false,
+            ..CacheConfig::default()
+        })
+        .unwrap();
+
+        // An HTML error page served as the repo, 100 times over, counting chunks pulled. Each
+        // chunk holds the whole (bogus) header, so the parser fails on the first one and at
+        // most the queue plus the send that finds it gone follow.
+        let pulled = AtomicUsize::new(0);
+        let page = format!("<html>{}</html>", "server busy, try again later ".repeat(4));
+        let body = || {
+            futures::stream::iter(
+                (0..100).map(|_| Ok::<_, std::io::Error>(page.clone().into_bytes())),
+            )
+            .inspect(|_| {
+                pulled.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+
+        let result = provider.store_car("did:plc:html", body()).await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        assert!(pulled.load(Ordering::SeqCst) <= CarCheck::QUEUE + 2, "Stops early");
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0, "Nothing cached");
+
+        pulled.store(0, Ordering::SeqCst);
+        let result = provider.read_car("did:plc:html", body()).await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        assert!(pulled.load(Ordering::SeqCst) <= CarCheck::QUEUE + 2);
+
+        // A body cut off inside a block fails once the stream ends
+        let car = synthetic_repo_car(None, &[("3k1", "hello")]);
+        let cut = futures::stream::iter(vec![Ok::<_, std::io::Error>(&car[..car.len() - 3])]);
+        let result = provider.store_car("did:plc:cut", cut).await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+
+        // A block whose bytes no longer match its CID is rejected too
+        let mut car = synthetic_repo_car(None, &[("3k1", "hello")]);
+        let at = car.windows(5).position(|w| w == b"hello").unwrap();
+        car[at] ^= 0x20;
+        let flipped = futures::stream::iter(vec![Ok::<_, std::io::Error>(car)]);
+        let result = provider.store_car("did:plc:flipped", flipped).await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_blocks_rejected_on_download_and_in_cache() {
+        // A PDS stand-in serving one intact repo and one whose post block was altered
+        let good = synthetic_repo_car(None, &[("3k1", "hello")]);
+        let mut bad = good.clone();
+        let at = bad.windows(5).position(|w| w == b"hello").unwrap();
+        bad[at] ^= 0x20;
+        let served = (good.clone(), bad.clone());
+        let app = axum::Router::new().route(
+            "/xrpc/com.atproto.sync.getRepo",
+            axum::routing::get(
+                move |axum::extract::Query(query): axum::extract::Query<HashMap<String, String>>| {
+                    let body = if query["did"].ends_with("bad") {
+                        served.1.clone()
+                    } else {
+                        served.0.clone()
+                    };
+                    async move { body }
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let pds = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let temp = tempfile::tempdir().unwrap();
+        let config = CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        };
+        let provider = RepositoryProvider::new(config.clone()).unwrap();
+        let uncached = RepositoryProvider::new(CacheConfig {
+            no_cache: true,
+            ..config
+        })
+        .unwrap();
+        for did in ["did:plc:corruptbad", "did:plc:corruptgood"] {
+            provider.resolver().remember_pds(did, &pds);
+        }
+
+        // Downloads, cached or not, fail on the altered block and cache nothing
+        let result = provider.fetch_repo_bytes("did:plc:corruptbad").await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        let result = uncached.fetch_repo_bytes("did:plc:corruptbad").await;
+        assert!(matches!(result, Err(AppError::RepoParseFailed(_))), "{:?}", result);
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+
+        // A fresh cache file damaged on disk is neither served nor returned offline,
+        // but downloaded again
+        let good_did = "did:plc:corruptgood";
+        fs::write(provider.cache_path(good_did), &bad).unwrap();
+        assert!(provider.cached_repo_bytes(good_did).await.is_none());
+        assert_eq!(provider.fetch_repo_bytes(good_did).await.unwrap(), good);
+        assert_eq!(provider.cached_repo_bytes(good_did).await, Some(good));
+    }
+
+    #[tokio::test]
+    async fn test_no_cache_skips_cached_files_and_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("repos");
+        fs::create_dir_all(&dir).unwrap();
+        // did:example has no PDS discovery, so any lookup fails without network access
+        let did = "did:example:cached";
+        fs::write(dir.join("did_example_cached.car"), b"stale").unwrap();
+
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: dir.clone(),
+            no_cache: true,
+            ..CacheConfig::default()
+        })
+        .unwrap();
+
+        // The cached copy is ignored in favour of a fresh download
+        let result = provider.fetch_repo_bytes(did).await;
+        assert!(matches!(result, Err(AppError::DidResolveFailed(_))), "{:?}", result.err());
+        assert!(matches!(
+            provider.fetch_repo_car(did).await,
+            Err(AppError::CacheError(_))
+        ));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert!(provider.cached_repo_bytes(did).await.is_none());
+
+        // A disabled cache does not create its directory
+        let missing = temp.path().join("never-created");
+        RepositoryProvider::new(CacheConfig {
+            dir: missing.clone(),
+            no_cache: true,
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        assert!(!missing.exists());
+    }
+
+    #[tokio::test]
+    async fn test_stale_cache_is_refetched_and_fresh_cache_reused() {
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            no_cache: false,
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_cache_ttl(Duration::from_secs(60));
+
+        // did:example has no PDS discovery, so a refetch fails without network access
+        let fresh_did = "did:example:fresh";
+        let fresh_path = temp.path().join("did_example_fresh.car");
+        fs::write(&fresh_path, synthetic_repo_car(None, &[("3k1", "fresh")])).unwrap();
+        assert_eq!(provider.fetch_repo_car(fresh_did).await.unwrap(), fresh_path);
+
+        let stale_did = "did:example:stale";
+        let stale_path = temp.path().join("did_example_stale.car");
+        let stale_car = synthetic_repo_car(None, &[("3k1", "stale")]);
+        fs::write(&stale_path, &stale_car).unwrap();
+        let two_minutes_ago = std::time::SystemTime::now() - Duration::from_secs(120);
+        fs::File::options()
+            .write(true)
+            .open(&stale_path)
+            .unwrap()
+            .set_modified(two_minutes_ago)
+            .unwrap();
+
+        let result = provider.fetch_repo_car(stale_did).await;
+        assert!(
+            matches!(result, Err(AppError::DidResolveFailed(_))),
+            "Stale cache should trigger a refetch, got {:?}",
+            result
+        );
+        // Offline callers can still read the stale copy
+        assert_eq!(provider.cached_repo_bytes(stale_did).await, Some(stale_car));
+        assert!(provider.cached_repo_bytes("did:example:missing").await.is_none());
+
+        // A longer TTL accepts the same file
+        let lenient = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            no_cache: false,
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_cache_ttl(Duration::from_secs(600));
+        assert_eq!(lenient.fetch_repo_car(stale_did).await.unwrap(), stale_path);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_share_one_download() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let temp = tempfile::tempdir().unwrap();
+        let config = CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        };
+        // Separate providers, as separate tool calls would create
+        let providers: Vec<RepositoryProvider> = (0..5)
+            .map(|_| RepositoryProvider::new(config.clone()).unwrap())
+            .collect();
+        let downloads = AtomicUsize::new(0);
+        let did = "did:plc:concurrent";
+        let car = synthetic_repo_car(Some(did), &[("3k1", "hello")]);
+
+        let fetches = providers.iter().map(|provider| {
+            provider.fetch_repo_car_with(did, || async {
+                downloads.fetch_add(1, Ordering::SeqCst);
+                // Hold the download open so the other callers pile up behind it
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let chunks: Vec<Result<&[u8], std::io::Error>> = vec![Ok(&car)];
+                provider.store_car(did, futures::stream::iter(chunks)).await
+            })
+        });
+        let results = futures::future::join_all(fetches).await;
+
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        let expected = temp.path().join("did_plc_concurrent.car");
+        for result in results {
+            assert_eq!(result.unwrap(), expected);
+        }
+        assert_eq!(fs::read(&expected).unwrap(), car);
+        assert!(!IN_FLIGHT.lock().unwrap().contains_key(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_does_not_poison_later_attempts() {
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let did = "did:plc:flaky";
+        let car = synthetic_repo_car(Some(did), &[("3k1", "hello")]);
+
+        let failed = provider
+            .fetch_repo_car_with(did, || async {
+                Err(AppError::NetworkError("connection reset".to_string()))
+            })
+            .await;
+        assert!(matches!(failed, Err(AppError::NetworkError(_))));
+        assert!(!temp.path().join("did_plc_flaky.car").exists());
+
+        let retried = provider
+            .fetch_repo_car_with(did, || async {
+                let chunks: Vec<Result<&[u8], std::io::Error>> = vec![Ok(&car)];
+                provider.store_car(did, futures::stream::iter(chunks)).await
+            })
+            .await
+            .unwrap();
+        assert_eq!(fs::read(retried).unwrap(), car);
+    }
+
+    #[tokio::test]
+    async fn test_get_posts_batches_and_resolves_post_refs() {
+        use axum::extract::RawQuery;
+
+        // getPosts stand-in that knows every post except rkey 7, recording each batch size
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let seen = batches.clone();
+        let app = axum::Router::new().route(
+            "/xrpc/app.bsky.feed.getPosts",
+            axum::routing::get(move |RawQuery(query): RawQuery| {
+                let uris: Vec<String> = url::form_urlencoded::parse(query.unwrap().as_bytes())
+                    .filter(|(key, _)| key == "uris")
+                    .map(|(_, uri)| uri.into_owned())
+                    .collect();
+                seen.lock().unwrap().push(uris.len());
+                let posts: Vec<serde_json::Value> = uris
+                    .iter()
+                    .filter(|uri| !uri.ends_with("/7"))
+                    .map(|uri| {
+                        let rkey = uri.rsplit('/').next().unwrap();
+                        serde_json::json!({
+                            "uri": uri,
+                            "cid": format!("bafypost{}", rkey),
+                            "author": { "did": "did:plc:alice", "handle": "alice.test" },
+                            "record": { "text": "hi" }
+                        })
+                    })
+                    .collect();
+                async move { axum::Json(serde_json::json!({ "posts": posts })) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_appview(&base);
+
+        let uri = |i: usize| format!("at://did:plc:alice/app.bsky.feed.post/{}", i);
+        let uris: Vec<String> = (0..30).map(uri).collect();
+        let refs = provider.get_posts::<StrongRef>(&uris).await.unwrap();
+        assert_eq!(*batches.lock().unwrap(), [GET_POSTS_MAX_URIS, 5]);
+        assert_eq!(refs.len(), 29);
+        assert_eq!(
+            refs[28],
+            StrongRef {
+                uri: uri(29),
+                cid: "bafypost29".to_string()
+            }
+        );
+        assert!(refs.iter().all(|post| post.uri != uri(7)));
+
+        let single = provider.resolve_post_ref(&uri(3)).await.unwrap();
+        assert_eq!(single.cid, "bafypost3");
+        let missing = provider.resolve_post_ref(&uri(7)).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))), "{:?}", missing);
+
+        let url = "https://bsky.app/profile/alice.test/post/3k";
+        let invalid = provider.get_posts::<StrongRef>(&[url.to_string()]).await;
+        assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+    }
 }
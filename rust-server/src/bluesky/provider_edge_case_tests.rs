@@ -2,8 +2,12 @@
 
 #[cfg(test)]
 mod provider_edge_case_tests {
-    use crate::bluesky::mst::extract_cid_to_rkey_mapping;
-    use crate::bluesky::provider::RepositoryProvider;
+    use crate::bluesky::mst::{
+        extract_all_rkey_mappings, extract_cid_to_rkey_mapping, extract_mapping_and_blocks,
+    };
+    use crate::bluesky::provider::{CacheConfig, RepositoryProvider};
+    use crate::car::test_support::{car_file, test_cid, Cbor};
+    use crate::car::{CarError, Cid};
 
     #[test]
     fn test_extract_cid_to_rkey_mapping_empty_car() {
@@ -15,6 +19,108 @@ mod provider_edge_case_tests {
         assert!(result.is_ok() || result.is_err(), "Should handle empty CAR data");
     }
 
+    /// A repo whose single MST node holds `(prefix length, key suffix, record CID n)` entries
+    fn mst_car(entries: &[(i64, &str, u8)]) -> Vec<u8> {
+        let entry = |&(p, k, n): &(i64, &str, u8)| {
+            Cbor::Map(vec![
+                ("k", Cbor::Bytes(k.as_bytes().to_vec())),
+                ("p", Cbor::Int(p)),
+                ("t", Cbor::Null),
+                ("v", Cbor::Link(test_cid(n))),
+            ])
+        };
+        let mut blocks = vec![
+            (
+                test_cid(1),
+                Cbor::Map(vec![("data", Cbor::Link(test_cid(2))), ("version", Cbor::Int(3))]),
+            ),
+            (
+                test_cid(2),
+                Cbor::Map(vec![
+                    ("e", Cbor::Array(entries.iter().map(entry).collect())),
+                    ("l", Cbor::Null),
+                ]),
+            ),
+        ];
+        for &(_, _, n) in entries {
+            blocks.push((test_cid(n), Cbor::Map(vec![("text", Cbor::Text(n.to_string()))])));
+        }
+        car_file(&test_cid(1), blocks)
+    }
+
+    /// One profile and two posts, sharing key prefixes
+    fn mixed_collection_car() -> Vec<u8> {
+        mst_car(&[
+            (0, "app.bsky.actor.profile/self", 10),
+            (9, "feed.post/3kaaa", 11),
+            (21, "bbb", 12),
+        ])
+    }
+
+    #[test]
+    fn test_extract_all_rkey_mappings_covers_every_collection() {
+        let car = mixed_collection_car();
+        let cid = |n| Cid::from_bytes(&test_cid(n)).unwrap().to_string();
+        let pair = |c: &str, r: &str| (c.to_string(), r.to_string());
+
+        let all = extract_all_rkey_mappings(&car).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[&cid(10)], pair("app.bsky.actor.profile", "self"));
+        assert_eq!(all[&cid(11)], pair("app.bsky.feed.post", "3kaaa"));
+        assert_eq!(all[&cid(12)], pair("app.bsky.feed.post", "3kbbb"));
+
+        let posts = extract_cid_to_rkey_mapping(&car, "app.bsky.feed.post").unwrap();
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[&cid(12)], "app.bsky.feed.post/3kbbb");
+        let profiles = extract_cid_to_rkey_mapping(&car, "app.bsky.actor.profile").unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[&cid(10)], "app.bsky.actor.profile/self");
+    }
+
+    #[test]
+    fn test_extract_mapping_and_blocks_matches_separate_walks() {
+        let car = mixed_collection_car();
+        let (posts, blocks) =
+            extract_mapping_and_blocks(&car, "app.bsky.feed.post", &["app.bsky.actor.profile"])
+                .unwrap();
+        assert_eq!(posts, extract_cid_to_rkey_mapping(&car, "app.bsky.feed.post").unwrap());
+        assert_eq!(
+            blocks,
+            crate::bluesky::mst::extract_collection_blocks(&car, &["app.bsky.actor.profile"])
+                .unwrap()
+        );
+        assert_eq!(blocks.len(), 1);
+        let (collection, rkey, _) = &blocks[0];
+        assert_eq!((collection.as_str(), rkey.as_str()), ("app.bsky.actor.profile", "self"));
+    }
+
+    #[test]
+    fn test_mst_keys_out_of_order_are_rejected() {
+        // "app.bsky.feed.post/3kbbb" then "app.bsky.feed.post/3kaaa"
+        let car = mst_car(&[(0, "app.bsky.feed.post/3kbbb", 10), (21, "aaa", 11)]);
+        match extract_all_rkey_mappings(&car) {
+            Err(CarError::MstOrder { previous, key }) => {
+                assert_eq!(previous, "app.bsky.feed.post/3kbbb");
+                assert_eq!(key, "app.bsky.feed.post/3kaaa");
+            }
+            other => panic!("expected MstOrder, got {:?}", other),
+        }
+
+        // A repeated key is out of order too
+        let car = mst_car(&[(0, "app.bsky.feed.post/3kaaa", 10), (24, "", 11)]);
+        assert!(matches!(
+            extract_cid_to_rkey_mapping(&car, "app.bsky.feed.post"),
+            Err(CarError::MstOrder { .. })
+        ));
+
+        // The prefix bounds check still applies
+        let car = mst_car(&[(0, "app.bsky.feed.post/3kaaa", 10), (99, "b", 11)]);
+        assert!(matches!(
+            extract_all_rkey_mappings(&car),
+            Err(CarError::InvalidHeader(_))
+        ));
+    }
+
     #[test]
     fn test_repository_provider_creation() {
         // Test that RepositoryProvider can be created with default settings
@@ -26,7 +132,7 @@ mod provider_edge_case_tests {
     #[test]
     fn test_repository_provider_with_custom_client() {
         // Test creating a new RepositoryProvider
-        let provider = RepositoryProvider::new();
+        let provider = RepositoryProvider::new(CacheConfig::default());
         assert!(provider.is_ok(), "Should create provider successfully");
     }
 
@@ -176,56 +282,6 @@ mod records_edge_case_tests {
         assert!(markdown.contains("developer"));
     }
 
-    #[test]
-    fn test_post_record_to_markdown_preserves_text() {
-        // Test that post markdown preserves original text
-        let post = PostRecord {
-            uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
-            cid: "cid1".to_string(),
-            text: "Hello world!".to_string(),
-            created_at: "2024-01-15T10:30:45Z".to_string(),
-            embeds: None,
-            facets: vec![],
-        };
-
-        let markdown = post.to_markdown("testuser", "world");
-        // The text should be in the markdown (may be highlighted if matching the query)
-        assert!(markdown.contains("Hello") || markdown.contains("**Hello**"));
-        // Should include timestamp
-        assert!(markdown.contains("2024-01-15"));
-        // Should include the link
-        assert!(markdown.contains("bsky.app/profile"));
-    }
-
-    #[test]
-    fn test_post_record_to_markdown_with_facets() {
-        // Test that facets are properly formatted in markdown
-        use crate::bluesky::records::{Facet, FacetIndex, FacetFeature};
-
-        let post = PostRecord {
-            uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
-            cid: "cid1".to_string(),
-            text: "Check out this link".to_string(),
-            created_at: "2024-01-15T10:30:45Z".to_string(),
-            embeds: None,
-            facets: vec![
-                Facet {
-                    index: FacetIndex {
-                        byte_start: 15,
-                        byte_end: 19,
-                    },
-                    features: vec![
-                        FacetFeature::Link { uri: "https://example.com".to_string() },
-                    ],
-                },
-            ],
-        };
-
-        let markdown = post.to_markdown("testuser", "link");
-        assert!(markdown.contains("link"));
-        assert!(markdown.contains("https://example.com"));
-    }
-
     #[test]
     fn test_blob_ref_serialization_roundtrip() {
         // Test BlobRef can be serialized and deserialized
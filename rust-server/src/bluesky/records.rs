@@ -2,6 +2,9 @@
 //!
 //! Defines the data structures for Bluesky records as specified in docs/7.1-rust.md
 
+use crate::bluesky::blob_url::{blob_host, ImagePreset};
+use crate::car::cbor::{decode_cbor, get_text_field, CborValue};
+use crate::search::parser::FilterTarget;
 use serde::{Deserialize, Serialize};
 
 /// Profile record from app.bsky.actor.profile collection
@@ -18,6 +21,270 @@ pub struct ProfileRecord {
     pub created_at: String,
 }
 
+/// Labeler declaration from the app.bsky.labeler.service collection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelerServiceRecord {
+    /// Label values declared in `policies.labelValues` (may be empty)
+    #[serde(rename = "labelValues", default)]
+    pub label_values: Vec<String>,
+}
+
+impl LabelerServiceRecord {
+    /// Markdown line marking an account as a labeler
+    pub fn to_markdown(&self) -> String {
+        if self.label_values.is_empty() {
+            "**🏷 Labeler service**\n\n".to_string()
+        } else {
+            format!(
+                "**🏷 Labeler service** · labels: {}\n\n",
+                self.label_values.join(", ")
+            )
+        }
+    }
+}
+
+/// Repository scale, tallied in a single pass over a CAR's records
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoStats {
+    /// Records of any collection
+    #[serde(rename = "totalRecords")]
+    pub total_records: usize,
+    /// Records in app.bsky.feed.post
+    #[serde(rename = "postCount")]
+    pub post_count: usize,
+    /// Size of the CAR file in bytes
+    #[serde(rename = "repoBytes")]
+    pub repo_bytes: usize,
+}
+
+impl RepoStats {
+    /// Start a tally for a CAR of the given size
+    pub fn new(repo_bytes: usize) -> Self {
+        Self {
+            repo_bytes,
+            ..Self::default()
+        }
+    }
+
+    /// Count one record of the given collection
+    pub fn tally(&mut self, record_type: &str) {
+        self.total_records += 1;
+        if record_type == "app.bsky.feed.post" {
+            self.post_count += 1;
+        }
+    }
+
+    /// Markdown list item for the profile stats section
+    pub fn to_markdown(&self) -> String {
+        format!("- Repo: {}\n", self.summary())
+    }
+
+    /// `N records · N posts · size`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} records · {} posts · {}",
+            self.total_records,
+            self.post_count,
+            format_byte_size(self.repo_bytes)
+        )
+    }
+}
+
+/// Audience counts from the app.bsky.actor.getProfile view, which the repo does not hold
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileCounts {
+    #[serde(rename = "followersCount", default, skip_serializing_if = "Option::is_none")]
+    pub followers_count: Option<u64>,
+    #[serde(rename = "followsCount", default, skip_serializing_if = "Option::is_none")]
+    pub follows_count: Option<u64>,
+    #[serde(rename = "postsCount", default, skip_serializing_if = "Option::is_none")]
+    pub posts_count: Option<u64>,
+}
+
+impl ProfileCounts {
+    /// Markdown list items for the profile stats section, skipping counts the view omitted
+    pub fn to_markdown(&self) -> String {
+        self.labeled()
+            .map(|(label, count)| format!("- {}: {}\n", label, count))
+            .collect()
+    }
+
+    /// The counts the view included, with their labels
+    pub fn labeled(&self) -> impl Iterator<Item = (&'static str, u64)> {
+        [
+            ("Followers", self.followers_count),
+            ("Following", self.follows_count),
+            ("Posts", self.posts_count),
+        ]
+        .into_iter()
+        .filter_map(|(label, count)| count.map(|count| (label, count)))
+    }
+}
+
+/// List record from the app.bsky.graph.list collection, with the members that
+/// app.bsky.graph.listitem records add to it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ListRecord {
+    pub uri: String,
+    pub name: String,
+    /// Purpose token without its lexicon prefix: curatelist, modlist or referencelist
+    pub purpose: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// DIDs of the accounts on the list, in listitem rkey order
+    pub members: Vec<String>,
+}
+
+impl ListRecord {
+    /// Decode the list record stored at `uri`; one without a name is not a valid list
+    pub fn from_cbor(uri: &str, cbor_data: &[u8]) -> Option<Self> {
+        let CborValue::Map(map) = decode_cbor(cbor_data).ok()? else {
+            return None;
+        };
+        let purpose = get_text_field(&map, "purpose").unwrap_or_default();
+        Some(Self {
+            uri: uri.to_string(),
+            name: get_text_field(&map, "name")?.to_string(),
+            purpose: purpose.rsplit('#').next().unwrap_or_default().to_string(),
+            description: get_text_field(&map, "description").map(str::to_string),
+            created_at: get_text_field(&map, "createdAt").unwrap_or_default().to_string(),
+            members: Vec::new(),
+        })
+    }
+
+    /// Readable name of the list's purpose
+    pub fn purpose_label(&self) -> &str {
+        match self.purpose.as_str() {
+            "curatelist" => "curated list",
+            "modlist" => "moderation list",
+            "referencelist" => "reference list",
+            other => other,
+        }
+    }
+
+    /// Markdown section for the list, showing at most `max_members` members
+    pub fn to_markdown(&self, max_members: usize) -> String {
+        let mut markdown = format!(
+            "## {}\n{} · {} members · created {}\n{}\n",
+            self.name,
+            self.purpose_label(),
+            self.members.len(),
+            self.created_at,
+            self.uri
+        );
+        if let Some(description) = self.description.as_deref().filter(|d| !d.is_empty()) {
+            markdown.push_str(&format!("\n{}\n", description));
+        }
+        if !self.members.is_empty() {
+            markdown.push('\n');
+            for member in self.members.iter().take(max_members) {
+                markdown.push_str(&format!("- {}\n", member));
+            }
+            if self.members.len() > max_members {
+                let more = self.members.len() - max_members;
+                markdown.push_str(&format!("- … {} more\n", more));
+            }
+        }
+        markdown.push('\n');
+        markdown
+    }
+}
+
+/// Membership record from the app.bsky.graph.listitem collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItemRecord {
+    /// DID of the account on the list
+    pub subject: String,
+    /// at:// URI of the list
+    pub list: String,
+}
+
+impl ListItemRecord {
+    /// Decode a listitem record; both fields are required
+    pub fn from_cbor(cbor_data: &[u8]) -> Option<Self> {
+        let CborValue::Map(map) = decode_cbor(cbor_data).ok()? else {
+            return None;
+        };
+        Some(Self {
+            subject: get_text_field(&map, "subject")?.to_string(),
+            list: get_text_field(&map, "list")?.to_string(),
+        })
+    }
+}
+
+/// Custom feed declaration from the app.bsky.feed.generator collection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FeedGeneratorRecord {
+    pub uri: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// DID of the service that serves the feed
+    pub did: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl FeedGeneratorRecord {
+    /// Decode the feed generator record stored at `uri`
+    pub fn from_cbor(uri: &str, cbor_data: &[u8]) -> Option<Self> {
+        let CborValue::Map(map) = decode_cbor(cbor_data).ok()? else {
+            return None;
+        };
+        Some(Self {
+            uri: uri.to_string(),
+            display_name: get_text_field(&map, "displayName")?.to_string(),
+            description: get_text_field(&map, "description").map(str::to_string),
+            did: get_text_field(&map, "did").unwrap_or_default().to_string(),
+            created_at: get_text_field(&map, "createdAt").unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Markdown section for the feed
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "## {}\nfeed · served by {} · created {}\n{}\n",
+            self.display_name, self.did, self.created_at, self.uri
+        );
+        if let Some(description) = self.description.as_deref().filter(|d| !d.is_empty()) {
+            markdown.push_str(&format!("\n{}\n", description));
+        }
+        markdown.push('\n');
+        markdown
+    }
+}
+
+/// Whether `text` spells out `#tag` (lowercase `tag`) as a whole hashtag, ignoring case:
+/// at the start or after whitespace, and not running on into more letters or digits
+fn text_has_hashtag(text: &str, tag: &str) -> bool {
+    let text = text.to_lowercase();
+    let hashtag = format!("#{}", tag);
+    let is_tag_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(&hashtag).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + hashtag.len()..].chars().next();
+        before.is_none_or(char::is_whitespace) && !after.is_some_and(is_tag_char)
+    })
+}
+
+/// Human-readable byte size (B, KB, MB, GB with one decimal)
+fn format_byte_size(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// Post record from app.bsky.feed.post collection  
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostRecord {
@@ -69,13 +336,15 @@ impl Embed {
                 texts.push(external.title.clone());
                 texts.push(external.description.clone());
             }
-            Embed::RecordWithMedia { media, .. } => {
+            Embed::RecordWithMedia { record, media } => {
                 // Recursively get text from the media part of the embed
                 texts.extend(media.get_searchable_text());
+                texts.extend(record.quoted.iter().map(|quoted| quoted.text.clone()));
             }
-            Embed::Record { .. } => {
-                // A simple record embed (quote post) doesn't contain the text of the
-                // quoted post itself, so there's no text to add here.
+            Embed::Record { record } => {
+                // A record embed (quote post) only holds a strong ref; the quoted text is
+                // searchable once the quoted post has been looked up
+                texts.extend(record.quoted.iter().map(|quoted| quoted.text.clone()));
             }
         }
         texts
@@ -99,10 +368,22 @@ pub struct ExternalEmbed {
 }
 
 /// Record embed (quote posts)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RecordEmbed {
     pub uri: String,
     pub cid: String,
+    /// The quoted post, when it has been looked up; never part of a stored record
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub quoted: Option<QuotedPost>,
+}
+
+/// Author and text of a quoted post, looked up from the AppView
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotedPost {
+    pub uri: String,
+    /// Handle of the quoted post's author
+    pub author: String,
+    pub text: String,
 }
 
 /// Blob reference for images
@@ -117,15 +398,25 @@ pub struct BlobRef {
     pub size: u64,
 }
 
-/// Custom serde module for handling CID as either string or bytes
+/// Custom serde module for handling CID as either string, bytes or a `{"$link": cid}` map
 mod cid_or_bytes {
-    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// JSON form of a CID link in records (`{"$link": "bafy..."}`)
+    #[derive(Serialize, Deserialize)]
+    struct Link {
+        #[serde(rename = "$link")]
+        link: String,
+    }
 
     pub fn serialize<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(value)
+        Link {
+            link: value.to_string(),
+        }
+        .serialize(serializer)
     }
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
@@ -136,11 +427,13 @@ mod cid_or_bytes {
         #[serde(untagged)]
         enum StringOrBytes {
             String(String),
+            Link(Link),
             Bytes(serde_bytes::ByteBuf),
         }
 
         match StringOrBytes::deserialize(deserializer)? {
             StringOrBytes::String(s) => Ok(s),
+            StringOrBytes::Link(link) => Ok(link.link),
             StringOrBytes::Bytes(bytes) => {
                 // Convert CID bytes to base58 string representation
                 Ok(bs58::encode(&bytes).into_string())
@@ -156,7 +449,9 @@ pub struct Facet {
     pub features: Vec<FacetFeature>,
 }
 
-/// Facet byte index
+/// Facet byte index: UTF-8 byte offsets into the post text exactly as stored. Search
+/// compares text in Unicode NFC but never rewrites it, so the offsets stay in the original,
+/// un-normalized encoding.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FacetIndex {
     #[serde(rename = "byteStart")]
@@ -181,8 +476,25 @@ impl ProfileRecord {
     /// Convert to markdown format as specified in docs
     #[allow(dead_code)]
     pub fn to_markdown(&self, handle: &str, did: &str) -> String {
+        self.to_markdown_with_details(handle, did, None, None, None)
+    }
+
+    /// Convert to markdown, marking the account as a labeler when one is declared
+    /// and adding repository statistics when they were computed
+    pub fn to_markdown_with_details(
+        &self,
+        handle: &str,
+        did: &str,
+        labeler: Option<&LabelerServiceRecord>,
+        counts: Option<&ProfileCounts>,
+        repo_stats: Option<&RepoStats>,
+    ) -> String {
         let mut markdown = format!("# @{} ({})\n\n", handle, did);
 
+        if let Some(labeler) = labeler {
+            markdown.push_str(&labeler.to_markdown());
+        }
+
         if let Some(display_name) = &self.display_name {
             markdown.push_str(&format!("**Display Name:** {}\n\n", display_name));
         }
@@ -194,11 +506,18 @@ impl ProfileRecord {
         }
 
         if let Some(avatar) = &self.avatar {
-            markdown.push_str(&format!("**Avatar:** ![Avatar](blob:{})\n\n", avatar.ref_));
+            let url = blob_host().image_url(did, &avatar.ref_, ImagePreset::Avatar, "jpeg");
+            markdown.push_str(&format!("**Avatar:** ![Avatar]({})\n\n", url));
         }
 
         markdown.push_str("**Stats:**\n");
         markdown.push_str(&format!("- Created: {}\n", self.created_at));
+        if let Some(counts) = counts {
+            markdown.push_str(&counts.to_markdown());
+        }
+        if let Some(repo_stats) = repo_stats {
+            markdown.push_str(&repo_stats.to_markdown());
+        }
 
         // Add raw profile data
         markdown.push_str("\n<details>\n<summary>Raw Profile Data</summary>\n\n```json\n");
@@ -211,7 +530,79 @@ impl ProfileRecord {
     }
 }
 
+impl FilterTarget for PostRecord {
+    fn has_image(&self) -> bool {
+        PostRecord::has_image(self)
+    }
+
+    fn has_link(&self) -> bool {
+        PostRecord::has_link(self)
+    }
+
+    fn has_quote(&self) -> bool {
+        PostRecord::has_quote(self)
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        PostRecord::has_tag(self, tag)
+    }
+}
+
 impl PostRecord {
+    /// Iterate embeds, descending into the media of record-with-media embeds
+    pub fn embeds_flat(&self) -> impl Iterator<Item = &Embed> {
+        self.embeds.iter().flatten().flat_map(|embed| match embed {
+            Embed::RecordWithMedia { media, .. } => vec![embed, media.as_ref()],
+            _ => vec![embed],
+        })
+    }
+
+    /// Whether the post carries image embeds
+    pub fn has_image(&self) -> bool {
+        self.embeds_flat().any(|e| matches!(e, Embed::Images { .. }))
+    }
+
+    /// Whether the post carries a link card or a link facet
+    pub fn has_link(&self) -> bool {
+        self.embeds_flat().any(|e| matches!(e, Embed::External { .. }))
+            || self.facets.iter().any(|f| {
+                f.features
+                    .iter()
+                    .any(|feat| matches!(feat, FacetFeature::Link { .. }))
+            })
+    }
+
+    /// Whether the post quotes another record
+    pub fn has_quote(&self) -> bool {
+        self.embeds_flat()
+            .any(|e| matches!(e, Embed::Record { .. } | Embed::RecordWithMedia { .. }))
+    }
+
+    /// Whether the post is tagged `tag` (lowercase, without `#`), ignoring case: through a
+    /// hashtag facet or, for posts written without facets, a literal `#tag` in the text
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let in_facets = self.facets.iter().flat_map(|f| &f.features).any(|feat| {
+            matches!(feat, FacetFeature::Tag { tag: t } if t.to_lowercase() == tag)
+        });
+        in_facets || text_has_hashtag(&self.text, tag)
+    }
+
+    /// The quoted record of a quote post, with or without media
+    pub fn quote_embed(&self) -> Option<&RecordEmbed> {
+        self.embeds.iter().flatten().find_map(|embed| match embed {
+            Embed::Record { record } | Embed::RecordWithMedia { record, .. } => Some(record),
+            _ => None,
+        })
+    }
+
+    /// Mutable access to the quoted record, to attach the looked-up post
+    pub fn quote_embed_mut(&mut self) -> Option<&mut RecordEmbed> {
+        self.embeds.iter_mut().flatten().find_map(|embed| match embed {
+            Embed::Record { record } | Embed::RecordWithMedia { record, .. } => Some(record),
+            _ => None,
+        })
+    }
+
     /// Get searchable text from the post
     pub fn get_searchable_text(&self) -> Vec<String> {
         let mut texts = vec![self.text.clone()];
@@ -235,109 +626,16 @@ impl PostRecord {
         texts
     }
 
-    /// Convert to markdown format for search results
-    #[allow(dead_code)]
-    pub fn to_markdown(&self, handle: &str, query: &str) -> String {
-        let mut markdown = String::new();
-
-        // Link (web URL) and timestamp
-        if !self.uri.is_empty() {
-            let post_url = format!(
-                "https://bsky.app/profile/{}/post/{}",
-                handle,
-                self.uri.split('/').next_back().unwrap_or("")
-            );
-            markdown.push_str(&format!("**Link:** {}\n", post_url));
-        }
-
-        markdown.push_str(&format!("**Created:** {}\n\n", self.created_at));
-
-        // Highlighted post text
-        let highlighted_text = highlight_text(&self.text, query);
-        markdown.push_str(&highlighted_text);
-        markdown.push_str("\n\n");
-
-        // Collect links from external embeds and facet link features
-        let mut link_lines: Vec<String> = Vec::new();
-        if let Some(embeds) = &self.embeds {
-            for embed in embeds {
-                if let Embed::External { external } = embed {
-                    link_lines.push(format!("- [{}]({})\n", external.title, external.uri));
-                }
-            }
-        }
-        for facet in &self.facets {
-            for feat in &facet.features {
-                if let FacetFeature::Link { uri } = feat {
-                    link_lines.push(format!("- {}\n", uri));
-                }
-            }
-        }
-        if !link_lines.is_empty() {
-            markdown.push_str("**Links:**\n");
-            for line in link_lines {
-                markdown.push_str(&line);
-            }
-            markdown.push('\n');
-        }
-
-        // Add images alt text (no URLs in scope)
-        if let Some(embeds) = &self.embeds {
-            if !embeds.is_empty() {
-                for embed in embeds {
-                    if let Embed::Images { images } = embed {
-                        markdown.push_str("**Images:**\n");
-                        for (i, img) in images.iter().enumerate() {
-                            let default_alt = format!("Image {}", i + 1);
-                            let alt_text = img.alt.as_deref().unwrap_or(&default_alt);
-                            markdown.push_str(&format!("- {}\n", alt_text));
-                        }
-                        markdown.push('\n');
-                    }
-                }
-            }
-        }
-
-        markdown
-    }
-}
-
-#[allow(dead_code)]
-fn highlight_text(text: &str, query: &str) -> String {
-    if query.is_empty() {
-        return text.to_string();
-    }
-
-    // Simple case-insensitive highlighting
-    let lower_text = text.to_lowercase();
-    let lower_query = query.to_lowercase();
-
-    if !lower_text.contains(&lower_query) {
-        return text.to_string();
-    }
-
-    let mut result = String::new();
-    let mut last_end = 0;
-
-    while let Some(start) = lower_text[last_end..].find(&lower_query) {
-        let absolute_start = last_end + start;
-        let absolute_end = absolute_start + query.len();
-
-        // Add text before match
-        result.push_str(&text[last_end..absolute_start]);
-
-        // Add highlighted match
-        result.push_str("**");
-        result.push_str(&text[absolute_start..absolute_end]);
-        result.push_str("**");
-
-        last_end = absolute_end;
+    /// Key ordering search results of equal score: creation time (oldest first, undated
+    /// last), then URI
+    pub fn tie_key(&self) -> String {
+        let created = match crate::search::timestamp::parse_timestamp(&self.created_at) {
+            // Fixed width, so the text sorts like the time
+            Some(created) => created.format("%Y-%m-%dT%H:%M:%S%.9fZ").to_string(),
+            None => "~".to_string(),
+        };
+        format!("{}\n{}", created, self.uri)
     }
-
-    // Add remaining text
-    result.push_str(&text[last_end..]);
-
-    result
 }
 
 // TODO: Rewrite these tests to use in-house CBOR encoder instead of serde_cbor
@@ -381,7 +679,7 @@ mod tests {
         assert!(markdown.contains("**Display Name:** Test User"));
         assert!(markdown.contains("**Description:**"));
         assert!(markdown.contains("A test user profile"));
-        assert!(markdown.contains("**Avatar:** ![Avatar](blob:bafyavatar)"));
+        assert!(markdown.contains("**Avatar:** ![Avatar](https://cdn.bsky.app/img/avatar/plain/"));
         assert!(markdown.contains("**Stats:**"));
         assert!(markdown.contains("- Created: 2024-01-01T00:00:00Z"));
         assert!(markdown.contains("Raw Profile Data"));
@@ -497,113 +795,6 @@ mod tests {
         assert!(searchable.contains(&"https://example.com".to_string()));
     }
 
-    #[test]
-    fn test_post_record_to_markdown() {
-        let post = create_test_post();
-        let markdown = post.to_markdown("alice.bsky.social", "hello");
-
-        assert!(markdown.contains("**Link:** https://bsky.app/profile/alice.bsky.social/post/123"));
-        assert!(markdown.contains("**Created:** 2024-01-01T12:00:00Z"));
-        assert!(markdown.contains("**Hello** world!"));
-    }
-
-    #[test]
-    fn test_post_record_to_markdown_with_links() {
-        let mut post = create_test_post();
-
-        // Add external embed
-        post.embeds = Some(vec![Embed::External {
-            external: ExternalEmbed {
-                uri: "https://example.com/article".to_string(),
-                title: "Great Article".to_string(),
-                description: "Amazing content".to_string(),
-                thumb: None,
-            },
-        }]);
-
-        // Add facet link
-        post.facets.push(Facet {
-            index: FacetIndex {
-                byte_start: 0,
-                byte_end: 5,
-            },
-            features: vec![FacetFeature::Link {
-                uri: "https://facet-link.com".to_string(),
-            }],
-        });
-
-        let markdown = post.to_markdown("alice.bsky.social", "hello");
-
-        assert!(markdown.contains("**Links:**"));
-        assert!(markdown.contains("- [Great Article](https://example.com/article)"));
-        assert!(markdown.contains("- https://facet-link.com"));
-    }
-
-    #[test]
-    fn test_post_record_to_markdown_with_images() {
-        let mut post = create_test_post();
-
-        post.embeds = Some(vec![Embed::Images {
-            images: vec![
-                ImageEmbed {
-                    alt: Some("Sunset photo".to_string()),
-                    image: BlobRef {
-                        type_: "blob".to_string(),
-                        ref_: "bafy123".to_string(),
-                        mime_type: "image/jpeg".to_string(),
-                        size: 1024,
-                    },
-                },
-                ImageEmbed {
-                    alt: None,
-                    image: BlobRef {
-                        type_: "blob".to_string(),
-                        ref_: "bafy456".to_string(),
-                        mime_type: "image/png".to_string(),
-                        size: 2048,
-                    },
-                },
-            ],
-        }]);
-
-        let markdown = post.to_markdown("alice.bsky.social", "hello");
-
-        assert!(markdown.contains("**Images:**"));
-        assert!(markdown.contains("- Sunset photo"));
-        assert!(markdown.contains("- Image 2"));
-    }
-
-    #[test]
-    fn test_highlight_text_basic() {
-        let text = "Hello world, this is a test";
-        let result = highlight_text(text, "world");
-        assert_eq!(result, "Hello **world**, this is a test");
-    }
-
-    #[test]
-    fn test_highlight_text_case_insensitive() {
-        let text = "Hello World, this is a TEST";
-        let result = highlight_text(text, "world");
-        assert_eq!(result, "Hello **World**, this is a TEST");
-
-        let result = highlight_text(text, "test");
-        assert_eq!(result, "Hello World, this is a **TEST**");
-    }
-
-    #[test]
-    fn test_highlight_text_multiple_matches() {
-        let text = "test test test";
-        let result = highlight_text(text, "test");
-        assert_eq!(result, "**test** **test** **test**");
-    }
-
-    #[test]
-    fn test_highlight_text_no_match() {
-        let text = "Hello world";
-        let result = highlight_text(text, "xyz");
-        assert_eq!(result, "Hello world");
-    }
-
     #[test]
     fn test_highlight_text_empty_query() {
         let text = "Hello world";
@@ -2,6 +2,70 @@
 
 use crate::bluesky::did::DidResolver;
 use crate::error::AppError;
+use std::fmt;
+
+/// Segments of an `at://{did}/{collection}/{rkey}` record URI, borrowed from the URI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtUri<'a> {
+    pub did: &'a str,
+    pub collection: &'a str,
+    pub rkey: &'a str,
+}
+
+/// Why a string is not an `at://{did}/{collection}/{rkey}` record URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AtUriError {
+    /// The string does not start with `at://`
+    NotAtUri(String),
+    /// The DID, collection or rkey segment is missing or empty
+    MissingSegment { uri: String, segment: &'static str },
+}
+
+impl fmt::Display for AtUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtUriError::NotAtUri(uri) => write!(f, "Not an at:// URI: {}", uri),
+            AtUriError::MissingSegment { uri, segment } => write!(
+                f,
+                "Invalid at:// URI format: {}. Missing {}; expected at://{{did}}/{{collection}}/{{rkey}}",
+                uri, segment
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AtUriError {}
+
+impl From<AtUriError> for AppError {
+    fn from(err: AtUriError) -> Self {
+        AppError::InvalidInput(err.to_string())
+    }
+}
+
+impl<'a> AtUri<'a> {
+    /// Split an at:// record URI into its DID, collection and rkey. A trailing slash is
+    /// allowed and path segments past the rkey are ignored; an empty segment is an error.
+    pub fn parse(uri: &'a str) -> Result<Self, AtUriError> {
+        let rest = uri
+            .strip_prefix("at://")
+            .ok_or_else(|| AtUriError::NotAtUri(uri.to_string()))?;
+        let mut segments = rest.split('/');
+        let mut next = |segment: &'static str| {
+            segments
+                .next()
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| AtUriError::MissingSegment {
+                    uri: uri.to_string(),
+                    segment,
+                })
+        };
+        Ok(AtUri {
+            did: next("DID")?,
+            collection: next("collection")?,
+            rkey: next("rkey")?,
+        })
+    }
+}
 
 /// Parsed post reference containing DID and record key
 #[derive(Debug, Clone)]
@@ -38,18 +102,10 @@ pub async fn parse_post_uri(uri: &str) -> Result<PostRef, AppError> {
 /// Parse an at:// URI
 fn parse_at_uri(uri: &str) -> Result<PostRef, AppError> {
     // Format: at://{did}/app.bsky.feed.post/{rkey}
-    let parts: Vec<&str> = uri.trim_start_matches("at://").split('/').collect();
-
-    if parts.len() < 3 {
-        return Err(AppError::InvalidInput(format!(
-            "Invalid at:// URI format: {}. Expected at://{{did}}/app.bsky.feed.post/{{rkey}}",
-            uri
-        )));
-    }
-
+    let parsed = AtUri::parse(uri)?;
     Ok(PostRef {
-        did: parts[0].to_string(),
-        rkey: parts[2].to_string(),
+        did: parsed.did.to_string(),
+        rkey: parsed.rkey.to_string(),
     })
 }
 
@@ -249,9 +305,48 @@ mod tests {
     #[test]
     fn test_parse_at_uri_empty_components() {
         let uri = "at:///app.bsky.feed.post/xyz"; // Empty DID
-        let result = parse_at_uri(uri).unwrap();
-        assert_eq!(result.did, ""); // Implementation doesn't validate
-        assert_eq!(result.rkey, "xyz");
+        let result = parse_at_uri(uri);
+        assert!(matches!(result, Err(AppError::InvalidInput(msg)) if msg.contains("Missing DID")));
+    }
+
+    #[test]
+    fn test_at_uri_parse_segments() {
+        let uri = "at://did:plc:abc/app.bsky.feed.post/3kabc";
+        let expected = AtUri {
+            did: "did:plc:abc",
+            collection: "app.bsky.feed.post",
+            rkey: "3kabc",
+        };
+        assert_eq!(AtUri::parse(uri), Ok(expected));
+        // Trailing slash and extra segments
+        assert_eq!(AtUri::parse("at://did:plc:abc/app.bsky.feed.post/3kabc/"), Ok(expected));
+        assert_eq!(AtUri::parse("at://did:plc:abc/app.bsky.feed.post/3kabc/x"), Ok(expected));
+    }
+
+    #[test]
+    fn test_at_uri_parse_rejects_malformed() {
+        let missing = |uri: &str, segment: &'static str| {
+            Err(AtUriError::MissingSegment {
+                uri: uri.to_string(),
+                segment,
+            })
+        };
+        for (uri, segment) in [
+            ("at://", "DID"),
+            ("at://did:plc:abc", "collection"),
+            ("at://did:plc:abc/", "collection"),
+            ("at://did:plc:abc/app.bsky.feed.post", "rkey"),
+            ("at://did:plc:abc/app.bsky.feed.post/", "rkey"),
+            ("at://did:plc:abc//3kabc", "collection"),
+        ] {
+            assert_eq!(AtUri::parse(uri), missing(uri, segment), "{}", uri);
+        }
+        for uri in ["justid", "", "https://bsky.app/profile/a/post/b", " at://did:plc:a/c/r"] {
+            assert_eq!(AtUri::parse(uri), Err(AtUriError::NotAtUri(uri.to_string())));
+        }
+
+        let err: AppError = AtUri::parse("justid").unwrap_err().into();
+        assert_eq!(err.to_string(), "Invalid input: Not an at:// URI: justid");
     }
 
     // =========================================================================
@@ -1,8 +1,7 @@
-/// RFC 4648 base32 (lowercase) decoder for multibase 'b' prefix
+/// RFC 4648 base32 (lowercase) codec for multibase 'b' prefix
 /// No external dependencies; bit-level implementation
 use crate::car::CarError;
 
-#[allow(dead_code)]
 const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
 
 #[allow(dead_code)]
@@ -14,6 +13,37 @@ fn char_to_value(c: u8) -> Option<u8> {
     }
 }
 
+/// Encode bytes as base32 (RFC 4648, lowercase) without padding
+pub fn encode_base32(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(5) * 8);
+
+    let mut buffer: u64 = 0;
+    let mut bits_in_buffer = 0;
+
+    for &byte in input {
+        buffer = (buffer << 8) | (byte as u64);
+        bits_in_buffer += 8;
+
+        while bits_in_buffer >= 5 {
+            bits_in_buffer -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits_in_buffer) & 0x1F) as usize] as char);
+        }
+        buffer &= (1 << bits_in_buffer) - 1;
+    }
+
+    // Left-align the leftover bits in a final character
+    if bits_in_buffer > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits_in_buffer)) & 0x1F) as usize] as char);
+    }
+
+    output
+}
+
+/// Encode bytes as a multibase string with the 'b' (base32) prefix
+pub fn encode_multibase(input: &[u8]) -> String {
+    format!("b{}", encode_base32(input))
+}
+
 /// Decode base32 (RFC 4648, lowercase) to bytes
 /// Ignores padding ('=') and validates input
 #[allow(dead_code)]
@@ -144,4 +174,18 @@ mod tests {
         let decoded = result.unwrap();
         assert_eq!(decoded, b"abc");
     }
+
+    #[test]
+    fn test_encode_base32_round_trip() {
+        assert_eq!(encode_base32(b""), "");
+        assert_eq!(encode_base32(b"hello"), "nbswy3dp");
+        assert_eq!(encode_base32(b"abc"), "mfrgg");
+        assert_eq!(encode_multibase(b"hello"), "bnbswy3dp");
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in 0..bytes.len() {
+            let encoded = encode_multibase(&bytes[..len]);
+            assert_eq!(decode_multibase(&encoded).unwrap(), &bytes[..len]);
+        }
+    }
 }
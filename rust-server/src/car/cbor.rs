@@ -1,5 +1,6 @@
 /// Minimal DAG-CBOR decoder for AT Protocol records
-/// Supports: maps, arrays, text, bytes, integers, booleans, null, tag-0 (CID links)
+/// Supports: maps, arrays, text, bytes, integers, floats, booleans, null, tags
+/// (tag 42 decodes to a CID link)
 /// Zero-copy where possible; no external CBOR libraries
 use crate::car::CarError;
 
@@ -12,8 +13,12 @@ pub enum CborValue<'a> {
     Integer(i64),
     Bool(bool),
     Null,
+    /// Half, single or double precision float, widened to f64
+    Float(f64),
     /// Tag 42 with CID bytes (for DAG-CBOR links)
     Link(&'a [u8]),
+    /// Any other tag with its content, passed through undecoded
+    Tag(u64, Box<CborValue<'a>>),
 }
 
 pub struct CborReader<'a> {
@@ -26,6 +31,11 @@ impl<'a> CborReader<'a> {
         Self { buf, pos: 0 }
     }
 
+    /// Byte offset of the next value to be read
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
     fn remaining(&self) -> usize {
         self.buf.len().saturating_sub(self.pos)
     }
@@ -143,9 +153,8 @@ impl<'a> CborReader<'a> {
                         )),
                     }
                 } else {
-                    // Skip other tags by reading and discarding the value
-                    let _value = self.read_value()?;
-                    Err(CarError::InvalidHeader(format!("Unsupported tag: {}", tag)))
+                    let value = self.read_value()?;
+                    Ok(CborValue::Tag(tag, Box::new(value)))
                 }
             }
             7 => {
@@ -154,6 +163,21 @@ impl<'a> CborReader<'a> {
                     20 => Ok(CborValue::Bool(false)),
                     21 => Ok(CborValue::Bool(true)),
                     22 => Ok(CborValue::Null),
+                    25 => {
+                        let bytes = self.read_bytes(2)?;
+                        Ok(CborValue::Float(f16_to_f64(u16::from_be_bytes([bytes[0], bytes[1]]))))
+                    }
+                    26 => {
+                        let bytes = self.read_bytes(4)?;
+                        let bits = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                        Ok(CborValue::Float(f32::from_bits(bits) as f64))
+                    }
+                    27 => {
+                        let bytes = self.read_bytes(8)?;
+                        let mut be = [0u8; 8];
+                        be.copy_from_slice(bytes);
+                        Ok(CborValue::Float(f64::from_be_bytes(be)))
+                    }
                     _ => Err(CarError::InvalidHeader(
                         "Unsupported CBOR simple value".to_string(),
                     )),
@@ -166,6 +190,20 @@ impl<'a> CborReader<'a> {
     }
 }
 
+/// Widen an IEEE 754 half-precision float
+fn f16_to_f64(half: u16) -> f64 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f64;
+    match exponent {
+        // Subnormal
+        0 => sign * mantissa * 2f64.powi(-24),
+        0x1F if mantissa == 0.0 => sign * f64::INFINITY,
+        0x1F => f64::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
 /// Decode CBOR bytes to CborValue
 pub fn decode_cbor(bytes: &[u8]) -> Result<CborValue<'_>, CarError> {
     let mut reader = CborReader::new(bytes);
@@ -412,4 +450,71 @@ mod tests {
         assert_eq!(get_text_field(&map, "age"), None); // Not a text value
         assert_eq!(get_text_field(&map, "missing"), None);
     }
+
+    #[test]
+    fn test_decode_half_float() {
+        // 1.5 as f16: 0xF9 0x3E00
+        assert_eq!(decode_cbor(&[0xF9, 0x3E, 0x00]).unwrap(), CborValue::Float(1.5));
+        // -2.0
+        assert_eq!(decode_cbor(&[0xF9, 0xC0, 0x00]).unwrap(), CborValue::Float(-2.0));
+        // Smallest subnormal, 2^-24
+        assert_eq!(
+            decode_cbor(&[0xF9, 0x00, 0x01]).unwrap(),
+            CborValue::Float(5.960464477539063e-8)
+        );
+        assert_eq!(
+            decode_cbor(&[0xF9, 0x7C, 0x00]).unwrap(),
+            CborValue::Float(f64::INFINITY)
+        );
+        match decode_cbor(&[0xF9, 0x7E, 0x00]).unwrap() {
+            CborValue::Float(f) => assert!(f.is_nan()),
+            other => panic!("Expected float, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_single_float() {
+        // 100000.0 as f32: 0xFA 0x47C35000
+        let bytes = [0xFA, 0x47, 0xC3, 0x50, 0x00];
+        assert_eq!(decode_cbor(&bytes).unwrap(), CborValue::Float(100000.0));
+    }
+
+    #[test]
+    fn test_decode_double_float() {
+        // 1.1 as f64: 0xFB 0x3FF199999999999A
+        let bytes = [0xFB, 0x3F, 0xF1, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9A];
+        assert_eq!(decode_cbor(&bytes).unwrap(), CborValue::Float(1.1));
+    }
+
+    #[test]
+    fn test_decode_tagged_integer() {
+        // Tag 1 (epoch time) around 1363896240: 0xC1 0x1A 514B67B0
+        let bytes = [0xC1, 0x1A, 0x51, 0x4B, 0x67, 0xB0];
+        assert_eq!(
+            decode_cbor(&bytes).unwrap(),
+            CborValue::Tag(1, Box::new(CborValue::Integer(1363896240)))
+        );
+    }
+
+    #[test]
+    fn test_decode_unknown_tag_in_map_passes_through() {
+        // {"n": tag(12345, "x"), "f": 0.5 as f16}
+        let bytes = [
+            0xA2, 0x61, b'n', 0xD9, 0x30, 0x39, 0x61, b'x', 0x61, b'f', 0xF9, 0x38, 0x00,
+        ];
+        match decode_cbor(&bytes).unwrap() {
+            CborValue::Map(map) => {
+                assert_eq!(map[0].1, CborValue::Tag(12345, Box::new(CborValue::Text("x"))));
+                assert_eq!(map[1].1, CborValue::Float(0.5));
+            }
+            other => panic!("Expected map, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_cid_link_tag_still_a_link() {
+        // tag(42, bytes[0x00, 0x01, 0x71])
+        let bytes = [0xD8, 0x2A, 0x43, 0x00, 0x01, 0x71];
+        assert_eq!(decode_cbor(&bytes).unwrap(), CborValue::Link(&[0x01, 0x71]));
+    }
 }
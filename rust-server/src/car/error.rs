@@ -6,6 +6,8 @@ pub enum CarError {
     UnexpectedEof,
     #[error("Invalid CAR header: {0}")]
     InvalidHeader(String),
+    #[error("Unsupported CAR version: {0}")]
+    UnsupportedVersion(u8),
     #[error("Invalid CID version: {0}")]
     InvalidCidVersion(u8),
     #[error("Invalid CID codec: {0:#x}")]
@@ -21,4 +23,12 @@ pub enum CarError {
     InvalidDigestSize { expected: usize, actual: usize },
     #[error("UTF-8 decode error: {0}")]
     Utf8StrError(#[from] std::str::Utf8Error),
+    #[error("Block digest mismatch: CID has {expected}, content hashes to {actual}")]
+    DigestMismatch { expected: String, actual: String },
+    #[error("Block {cid} is not valid DAG-CBOR: {reason}")]
+    UndecodableBlock { cid: String, reason: String },
+    #[error("MST keys out of order: {key:?} follows {previous:?}")]
+    MstOrder { previous: String, key: String },
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
@@ -1,19 +1,23 @@
+#![forbid(unsafe_code)]
+
 mod base32;
 pub mod cbor;
 mod error;
 mod multihash;
 mod reader_clean;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 pub mod reader {
     pub use super::reader_clean::*;
 }
 mod types;
 
 #[allow(unused_imports)]
-pub use base32::{decode_base32, decode_multibase};
+pub use base32::{decode_base32, decode_multibase, encode_base32, encode_multibase};
 #[allow(unused_imports)]
 pub use cbor::{decode_cbor, get_array_field, get_int_field, get_map_field, get_text_field, CborValue};
 pub use error::CarError;
 #[allow(unused_imports)]
 pub use multihash::{extract_digest, parse_multihash, Multihash};
-pub use reader::CarRecords;
+pub use reader::{CarIndex, CarRecords};
 pub use types::{CarEntry, CarHeader, Cid};
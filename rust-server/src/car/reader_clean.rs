@@ -1,5 +1,8 @@
 use crate::car::cbor::{decode_cbor, get_text_field, CborValue};
 use crate::car::{CarEntry, CarError, CarHeader, Cid};
+use std::collections::HashMap;
+use std::io::{BufReader, Cursor, Read};
+use std::ops::Range;
 
 pub struct SyncByteReader<'a> {
     buf: &'a [u8],
@@ -114,11 +117,34 @@ fn read_cid(reader: &mut SyncByteReader) -> Result<Cid, CarError> {
     })
 }
 
+/// Check that `bytes` hash to the SHA-256 digest in `cid`.
+///
+/// CIDs with another hash function or an empty digest carry nothing to compare and pass.
+fn verify_digest(cid: &Cid, bytes: &[u8]) -> Result<(), CarError> {
+    use sha2::{Digest, Sha256};
+
+    if cid.digest_type != 0x12 || cid.digest.is_empty() {
+        return Ok(());
+    }
+    let actual = Sha256::digest(bytes);
+    if actual[..] != cid.digest[..] {
+        return Err(CarError::DigestMismatch {
+            expected: hex::encode(&cid.digest),
+            actual: hex::encode(actual),
+        });
+    }
+    Ok(())
+}
+
 fn read_header(reader: &mut SyncByteReader) -> Result<CarHeader, CarError> {
     // Read header length as varint, then parse CBOR map with version and roots
     let header_len = read_varint(reader, 10)? as usize;
     let header_bytes = reader.exactly(header_len, true)?;
+    parse_header(header_bytes)
+}
 
+/// Parse the CBOR header map (version and roots) that follows the header length
+fn parse_header(header_bytes: &[u8]) -> Result<CarHeader, CarError> {
     let header_value = decode_cbor(header_bytes)?;
     let header_map = match header_value {
         CborValue::Map(map) => map,
@@ -143,10 +169,7 @@ fn read_header(reader: &mut SyncByteReader) -> Result<CarHeader, CarError> {
         .ok_or_else(|| CarError::InvalidHeader("Missing version".to_string()))?;
 
     if version != 1 {
-        return Err(CarError::InvalidHeader(format!(
-            "Unsupported version: {}",
-            version
-        )));
+        return Err(CarError::UnsupportedVersion(version));
     }
 
     // Extract roots array
@@ -199,6 +222,92 @@ fn read_header(reader: &mut SyncByteReader) -> Result<CarHeader, CarError> {
     Ok(CarHeader { version, roots })
 }
 
+/// CARv2 pragma: a v1-style header of length 10 holding just `{version: 2}`
+const CARV2_PRAGMA: [u8; 11] = [
+    0x0a, 0xa1, 0x67, b'v', b'e', b'r', b's', b'i', b'o', b'n', 0x02,
+];
+
+/// Size of the fixed CARv2 header that follows the pragma
+const CARV2_HEADER_LEN: usize = 40;
+
+/// Where the v1 payload sits inside a CARv2 container
+struct CarV2Header {
+    data_offset: u64,
+    data_size: u64,
+}
+
+/// Parse the CARv2 header: 16 bytes of characteristics, then data offset, data size
+/// and index offset as little-endian u64s. The characteristics and index are not used.
+fn parse_v2_header(bytes: &[u8]) -> Result<CarV2Header, CarError> {
+    let field = |at: usize| {
+        let mut le = [0u8; 8];
+        le.copy_from_slice(&bytes[at..at + 8]);
+        u64::from_le_bytes(le)
+    };
+    let (data_offset, data_size, index_offset) = (field(16), field(24), field(32));
+
+    if data_offset < (CARV2_PRAGMA.len() + CARV2_HEADER_LEN) as u64 {
+        return Err(CarError::InvalidHeader(format!(
+            "CARv2 data offset {} overlaps the header",
+            data_offset
+        )));
+    }
+    let data_end = data_offset
+        .checked_add(data_size)
+        .ok_or_else(|| CarError::InvalidHeader("CARv2 data size overflows".to_string()))?;
+    if index_offset != 0 && index_offset < data_end {
+        return Err(CarError::InvalidHeader(format!(
+            "CARv2 index offset {} overlaps the data section",
+            index_offset
+        )));
+    }
+    Ok(CarV2Header {
+        data_offset,
+        data_size,
+    })
+}
+
+/// The v1 CAR inside `buf`: the data section of a CARv2 container, or `buf` itself
+fn carv2_data_section(buf: &[u8]) -> Result<&[u8], CarError> {
+    if !buf.starts_with(&CARV2_PRAGMA) {
+        return Ok(buf);
+    }
+    let mut reader = SyncByteReader::new(buf);
+    reader.seek(CARV2_PRAGMA.len())?;
+    let header = parse_v2_header(reader.exactly(CARV2_HEADER_LEN, true)?)?;
+
+    let start = header.data_offset as usize;
+    let end = start.saturating_add(header.data_size as usize);
+    if end > buf.len() {
+        return Err(CarError::UnexpectedEof);
+    }
+    Ok(&buf[start..end])
+}
+
+/// Read one entry's CID, returning it with the byte range of the entry's block
+fn read_entry_span(reader: &mut SyncByteReader) -> Result<(Cid, Range<usize>), CarError> {
+    // Entry size as varint
+    let entry_size = read_varint(reader, 10)? as usize;
+    if reader.remaining() < entry_size {
+        return Err(CarError::UnexpectedEof);
+    }
+
+    // CID, then the block bytes
+    let cid_start = reader.pos();
+    let cid = read_cid(reader)?;
+    let cid_size = reader.pos() - cid_start;
+    let bytes_size = entry_size.checked_sub(cid_size).ok_or_else(|| {
+        CarError::InvalidHeader(format!(
+            "Entry of {} bytes is shorter than its CID",
+            entry_size
+        ))
+    })?;
+
+    let start = reader.pos();
+    reader.seek(bytes_size)?;
+    Ok((cid, start..start + bytes_size))
+}
+
 pub struct SyncCarReader<'a> {
     reader: SyncByteReader<'a>,
     _header: CarHeader,
@@ -206,7 +315,7 @@ pub struct SyncCarReader<'a> {
 
 impl<'a> SyncCarReader<'a> {
     pub fn from_bytes(buf: &'a [u8]) -> Result<Self, CarError> {
-        let mut reader = SyncByteReader::new(buf);
+        let mut reader = SyncByteReader::new(carv2_data_section(buf)?);
         let header = read_header(&mut reader)?;
         Ok(Self {
             reader,
@@ -225,103 +334,281 @@ impl<'a> Iterator for SyncCarReader<'a> {
             return None;
         }
 
-        // Entry size as varint
-        let entry_size = match read_varint(&mut self.reader, 10) {
-            Ok(size) => size as usize,
+        let (cid, range) = match read_entry_span(&mut self.reader) {
+            Ok(span) => span,
             Err(e) => return Some(Err(e)),
         };
-        if self.reader.remaining() < entry_size {
-            return Some(Err(CarError::UnexpectedEof));
+        let bytes = self.reader.buf[range].to_vec();
+        Some(Ok(CarEntry { cid, bytes }))
+    }
+}
+
+/// Block locations of an in-memory CAR, for fetching single blocks by CID.
+///
+/// Building the index scans the entries once and records offsets only; block bytes
+/// stay in the borrowed buffer until asked for.
+pub struct CarIndex<'a> {
+    buf: &'a [u8],
+    header: CarHeader,
+    blocks: HashMap<Cid, Range<usize>>,
+}
+
+impl<'a> CarIndex<'a> {
+    /// Index every block of a CARv1 or CARv2 buffer
+    pub fn build(buf: &'a [u8]) -> Result<Self, CarError> {
+        let buf = carv2_data_section(buf)?;
+        let mut reader = SyncByteReader::new(buf);
+        let header = read_header(&mut reader)?;
+
+        let mut blocks = HashMap::new();
+        while reader.remaining() > 0 {
+            let (cid, range) = read_entry_span(&mut reader)?;
+            blocks.insert(cid, range);
         }
+        Ok(Self {
+            buf,
+            header,
+            blocks,
+        })
+    }
 
-        // CID
-        let cid_start = self.reader.pos();
-        let cid = match read_cid(&mut self.reader) {
-            Ok(cid) => cid,
-            Err(e) => return Some(Err(e)),
-        };
-        let cid_end = self.reader.pos();
-        let cid_size = cid_end - cid_start;
+    pub fn header(&self) -> &CarHeader {
+        &self.header
+    }
 
-        // Block bytes
-        let bytes_size = entry_size - cid_size;
-        let bytes = match self.reader.exactly(bytes_size, true) {
-            Ok(bytes) => bytes.to_vec(),
-            Err(e) => return Some(Err(e)),
-        };
+    /// Bytes of the block with this CID, if the CAR holds it
+    pub fn get_block(&self, cid: &Cid) -> Option<&'a [u8]> {
+        let buf = self.buf;
+        self.blocks.get(cid).map(|range| &buf[range.clone()])
+    }
 
-        Some(Ok(CarEntry { cid, bytes }))
+    /// CIDs of all indexed blocks, in no particular order
+    pub fn cids(&self) -> impl Iterator<Item = &Cid> {
+        self.blocks.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
     }
 }
 
+/// Iterator over the AT Protocol records of a CAR, read incrementally from any `Read`.
+///
+/// Only the header and one entry at a time are held in memory.
 pub struct CarRecords {
-    car_bytes: Vec<u8>,
-    car_reader: Option<SyncCarReader<'static>>, // internal self-referential lifetime via transmute
+    reader: Box<dyn Read + Send>,
+    header: Option<CarHeader>,
+    total_len: Option<usize>,
+    bytes_read: usize,
     processed_count: usize,
+    done: bool,
+    verify: bool,
+    report_undecodable: bool,
 }
 
 impl CarRecords {
+    /// Records of an in-memory CAR
     pub fn from_bytes(buf: Vec<u8>) -> Result<Self, CarError> {
+        let total_len = buf.len();
+        let mut records = Self::from_reader(Cursor::new(buf))?;
+        records.total_len = Some(total_len);
+        Ok(records)
+    }
+
+    /// Records of a CAR streamed from `reader`; the header is read with the first record
+    pub fn from_reader(reader: impl Read + Send + 'static) -> Result<Self, CarError> {
         Ok(Self {
-            car_bytes: buf,
-            car_reader: None,
+            reader: Box::new(BufReader::new(reader)),
+            header: None,
+            total_len: None,
+            bytes_read: 0,
             processed_count: 0,
+            done: false,
+            verify: false,
+            report_undecodable: false,
         })
     }
 
-    fn ensure_reader(&mut self) -> Result<(), CarError> {
-        if self.car_reader.is_none() {
-            // SAFETY: we own car_bytes and store the reader in self, so the reference remains valid
-            let reader = unsafe {
-                let bytes_ref: &'static [u8] = std::mem::transmute(self.car_bytes.as_slice());
-                SyncCarReader::from_bytes(bytes_ref)?
-            };
-            self.car_reader = Some(reader);
+    /// Hash each block and fail with `CarError::DigestMismatch` if it does not match its CID;
+    /// off by default for the search and replay hot paths
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Yield `CarError::UndecodableBlock` for blocks that are not valid DAG-CBOR and keep
+    /// reading, rather than skipping them silently
+    pub fn with_undecodable(mut self, report: bool) -> Self {
+        self.report_undecodable = report;
+        self
+    }
+
+    /// Records of a CAR file on disk, streamed rather than read into memory
+    pub fn from_file(path: &std::path::Path) -> Result<Self, CarError> {
+        let file = std::fs::File::open(path)?;
+        let total_len = file.metadata()?.len() as usize;
+        let mut records = Self::from_reader(file)?;
+        records.total_len = Some(total_len);
+        Ok(records)
+    }
+
+    /// Size in bytes of the underlying CAR file: the full length for `from_bytes`,
+    /// otherwise the bytes read so far
+    pub fn byte_len(&self) -> usize {
+        self.total_len.unwrap_or(self.bytes_read)
+    }
+
+    /// Read into `buf` until it is full; `Ok(false)` if the stream ended before any byte
+    fn fill(&mut self, buf: &mut [u8]) -> Result<bool, CarError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => return Err(CarError::UnexpectedEof),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(CarError::Io(e)),
+            }
+        }
+        self.bytes_read += filled;
+        Ok(true)
+    }
+
+    /// Read a varint; `Ok(None)` at a clean end of stream
+    fn read_varint(&mut self) -> Result<Option<u64>, CarError> {
+        let mut value = 0u64;
+        for i in 0..10 {
+            let mut byte = [0u8; 1];
+            if !self.fill(&mut byte)? {
+                return if i == 0 {
+                    Ok(None)
+                } else {
+                    Err(CarError::UnexpectedEof)
+                };
+            }
+            value |= ((byte[0] & 0x7F) as u64) << (i * 7);
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+        }
+        Err(CarError::VarintError("Varint too long".to_string()))
+    }
+
+    /// Read exactly `len` bytes without trusting `len` for the allocation up front
+    fn read_block(&mut self, len: usize) -> Result<Vec<u8>, CarError> {
+        let mut buf = Vec::new();
+        let read = self
+            .reader
+            .by_ref()
+            .take(len as u64)
+            .read_to_end(&mut buf)
+            .map_err(CarError::Io)?;
+        self.bytes_read += read;
+        if read < len {
+            return Err(CarError::UnexpectedEof);
+        }
+        Ok(buf)
+    }
+
+    fn ensure_header(&mut self) -> Result<(), CarError> {
+        if self.header.is_none() {
+            let mut header_bytes = self.read_header_block()?;
+            if header_bytes[..] == CARV2_PRAGMA[1..] {
+                self.enter_v2_data_section()?;
+                header_bytes = self.read_header_block()?;
+            }
+            self.header = Some(parse_header(&header_bytes)?);
         }
         Ok(())
     }
+
+    /// Length-prefixed header block at the current position
+    fn read_header_block(&mut self) -> Result<Vec<u8>, CarError> {
+        let header_len = self.read_varint()?.ok_or(CarError::UnexpectedEof)? as usize;
+        self.read_block(header_len)
+    }
+
+    /// After a CARv2 pragma: read the v2 header, skip to the data section and stop
+    /// reading at its end so the trailing index is never parsed as entries
+    fn enter_v2_data_section(&mut self) -> Result<(), CarError> {
+        let header = parse_v2_header(&self.read_block(CARV2_HEADER_LEN)?)?;
+
+        let padding = header.data_offset - self.bytes_read as u64;
+        let skipped = std::io::copy(&mut self.reader.by_ref().take(padding), &mut std::io::sink())?;
+        self.bytes_read += skipped as usize;
+        if skipped < padding {
+            return Err(CarError::UnexpectedEof);
+        }
+
+        let reader = std::mem::replace(&mut self.reader, Box::new(std::io::empty()));
+        self.reader = Box::new(reader.take(header.data_size));
+        Ok(())
+    }
+
+    /// Next raw entry, or `None` at the end of the CAR
+    fn next_entry(&mut self) -> Result<Option<CarEntry>, CarError> {
+        self.ensure_header()?;
+
+        // Entry size as varint
+        let Some(entry_size) = self.read_varint()? else {
+            return Ok(None);
+        };
+        let entry = self.read_block(entry_size as usize)?;
+
+        // CID, then the block bytes
+        let mut entry_reader = SyncByteReader::new(&entry);
+        let cid = read_cid(&mut entry_reader)?;
+        let bytes = entry[entry_reader.pos()..].to_vec();
+        if self.verify {
+            verify_digest(&cid, &bytes)?;
+        }
+
+        Ok(Some(CarEntry { cid, bytes }))
+    }
 }
 
 impl Iterator for CarRecords {
     type Item = Result<(String, Vec<u8>, String), CarError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Err(e) = self.ensure_reader() {
-            return Some(Err(e));
-        }
-
-        let reader = self.car_reader.as_mut()?;
-
         // Iterate underlying entries until we find an AT Protocol record or exhaust
-        for entry_result in reader.by_ref() {
-            let entry = match entry_result {
-                Ok(e) => e,
-                Err(e) => return Some(Err(e)),
+        while !self.done {
+            let entry = match self.next_entry() {
+                Ok(Some(entry)) => entry,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    // The stream position is unknown after an error, so stop here
+                    self.done = true;
+                    return Some(Err(e));
+                }
             };
             self.processed_count += 1;
-            let cid_str = format_cid_simple(&entry.cid);
+            let cid_str = entry.cid.to_string();
 
             // Decode CBOR to find $type field
-            if let Ok(CborValue::Map(ref cbor_map)) = decode_cbor(&entry.bytes) {
-                if let Some(type_str) = get_text_field(cbor_map, "$type") {
-                    return Some(Ok((type_str.to_string(), entry.bytes.clone(), cid_str)));
+            match decode_cbor(&entry.bytes) {
+                Ok(CborValue::Map(ref cbor_map)) => {
+                    if let Some(type_str) = get_text_field(cbor_map, "$type") {
+                        return Some(Ok((type_str.to_string(), entry.bytes.clone(), cid_str)));
+                    }
                 }
+                Ok(_) => {}
+                Err(e) if self.report_undecodable => {
+                    return Some(Err(CarError::UndecodableBlock {
+                        cid: cid_str,
+                        reason: e.to_string(),
+                    }));
+                }
+                Err(_) => {}
             }
         }
         None
     }
 }
 
-fn format_cid_simple(cid: &Cid) -> String {
-    format!(
-        "v{}-c{:02x}-d{:02x}-{}",
-        cid.version,
-        cid.codec,
-        cid.digest_type,
-        hex::encode(&cid.digest)
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -722,7 +1009,7 @@ mod tests {
         data.extend_from_slice(&header_cbor);
         let mut reader = SyncByteReader::new(&data);
         let result = read_header(&mut reader);
-        assert!(matches!(result, Err(CarError::InvalidHeader(_))));
+        assert!(matches!(result, Err(CarError::UnsupportedVersion(2))));
     }
 
     #[test]
@@ -746,9 +1033,9 @@ mod tests {
         let results = collected.unwrap();
         assert_eq!(results.len(), 1);
         let (_type, _cbor, cid) = &results[0];
-        assert!(cid.starts_with("v1-"));
-        assert!(cid.contains("-c71-"));
-        assert!(cid.contains("-d12-"));
+        // DAG-CBOR + SHA-256 CIDv1 strings all start with "bafyrei"
+        assert!(cid.starts_with("bafyrei"), "{}", cid);
+        assert_eq!(cid.parse::<Cid>().unwrap().codec, 0x71);
     }
 
     #[test]
@@ -872,15 +1159,306 @@ mod tests {
     }
 
     #[test]
-    fn test_format_cid_simple() {
+    fn test_cid_display_is_multibase_base32() {
         let cid = Cid {
             version: 1,
             codec: 0x71,
             digest_type: 0x12,
             digest: vec![0x01, 0x02, 0x03, 0x04],
         };
-        let formatted = format_cid_simple(&cid);
-        assert!(formatted.starts_with("v1-c71-d12-"));
-        assert!(formatted.contains("01020304"));
+        assert_eq!(cid.to_string(), "bafyrebabaibqi");
+    }
+
+    /// Reader handing out at most `chunk` bytes per read, like a slow socket
+    struct Trickle {
+        data: Vec<u8>,
+        pos: usize,
+        chunk: usize,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.chunk).min(self.data.len() - self.pos);
+            buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    fn multi_entry_car() -> Vec<u8> {
+        let mut car_data = create_car_header();
+        car_data.extend_from_slice(&create_at_protocol_entry("app.bsky.feed.post", "First"));
+        car_data.extend_from_slice(&create_non_at_protocol_entry());
+        car_data.extend_from_slice(&create_at_protocol_entry(
+            "app.bsky.feed.post",
+            &"long enough to need a two byte entry length ".repeat(4),
+        ));
+        car_data.extend_from_slice(&create_at_protocol_entry("app.bsky.feed.like", "Like"));
+        car_data.extend_from_slice(&create_non_at_protocol_entry());
+        car_data
+    }
+
+    #[test]
+    fn test_car_records_streaming_matches_in_memory() {
+        let car_data = multi_entry_car();
+
+        let in_memory: Vec<_> = CarRecords::from_bytes(car_data.clone())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(in_memory.len(), 3);
+
+        for chunk in [1, 7, 4096] {
+            let reader = Trickle {
+                data: car_data.clone(),
+                pos: 0,
+                chunk,
+            };
+            let streamed: Vec<_> = CarRecords::from_reader(reader)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(streamed, in_memory, "chunk size {}", chunk);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo.car");
+        std::fs::write(&path, &car_data).unwrap();
+        let from_file = CarRecords::from_file(&path).unwrap();
+        assert_eq!(from_file.byte_len(), car_data.len());
+        assert_eq!(from_file.collect::<Result<Vec<_>, _>>().unwrap(), in_memory);
+    }
+
+    #[test]
+    fn test_car_records_streaming_truncated_entry() {
+        let mut car_data = multi_entry_car();
+        car_data.truncate(car_data.len() - 5);
+
+        let reader = Trickle {
+            data: car_data,
+            pos: 0,
+            chunk: 3,
+        };
+        let mut records = CarRecords::from_reader(reader).unwrap();
+        let results: Vec<_> = records.by_ref().collect();
+
+        // Every complete record comes through, then the cut-off entry is an error
+        assert_eq!(results.len(), 4);
+        assert!(results[..3].iter().all(|r| r.is_ok()));
+        assert!(matches!(results[3], Err(CarError::UnexpectedEof)));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_car_records_survive_moves() {
+        let car_data = multi_entry_car();
+        let expected: Vec<_> = CarRecords::from_bytes(car_data.clone())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // Start iterating, then move the half-consumed iterator around before finishing
+        let mut records = CarRecords::from_bytes(car_data).unwrap();
+        let first = records.next().unwrap().unwrap();
+        let boxed = Box::new(records);
+        let mut shelf = vec![boxed];
+        let moved = *shelf.pop().unwrap();
+        let rest = std::thread::spawn(move || moved.collect::<Result<Vec<_>, _>>())
+            .join()
+            .unwrap()
+            .unwrap();
+
+        let mut all = vec![first];
+        all.extend(rest);
+        assert_eq!(all, expected);
+    }
+
+    /// A CAR with one record whose CID carries the real SHA-256 of its block
+    fn hashed_car(text: &str) -> Vec<u8> {
+        use sha2::{Digest, Sha256};
+
+        let record_cbor = encode_cbor_map(&[
+            ("$type", CborVal::Text("app.bsky.feed.post".to_string())),
+            ("text", CborVal::Text(text.to_string())),
+        ]);
+        let mut entry = vec![1, 0x71, 0x12, 32];
+        entry.extend_from_slice(&Sha256::digest(&record_cbor));
+        entry.extend_from_slice(&record_cbor);
+
+        let mut car_data = create_car_header();
+        car_data.push(entry.len() as u8);
+        car_data.extend_from_slice(&entry);
+        car_data
+    }
+
+    #[test]
+    fn test_verify_accepts_matching_digest() {
+        let car_data = hashed_car("Verified");
+
+        let records: Vec<_> = CarRecords::from_bytes(car_data)
+            .unwrap()
+            .with_verify(true)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records[0].0, "app.bsky.feed.post");
+    }
+
+    #[test]
+    fn test_verify_detects_flipped_byte() {
+        let mut car_data = hashed_car("Verified");
+        // Flip a bit in the last byte of the block
+        *car_data.last_mut().unwrap() ^= 0x01;
+
+        let mut records = CarRecords::from_bytes(car_data.clone())
+            .unwrap()
+            .with_verify(true);
+        match records.next() {
+            Some(Err(CarError::DigestMismatch { expected, actual })) => {
+                assert_eq!(expected.len(), 64);
+                assert_ne!(expected, actual);
+            }
+            other => panic!("expected DigestMismatch, got {:?}", other.map(|r| r.map(|r| r.2))),
+        }
+
+        // Without verification the corrupted block is read as-is
+        assert_eq!(CarRecords::from_bytes(car_data).unwrap().count(), 1);
+    }
+
+    /// Wrap a v1 payload in a CARv2 envelope with `padding` bytes before the data
+    /// section and a fake index after it
+    fn carv2_wrap(v1: &[u8], padding: usize) -> Vec<u8> {
+        let data_offset = (CARV2_PRAGMA.len() + CARV2_HEADER_LEN + padding) as u64;
+        let data_size = v1.len() as u64;
+        let index_offset = data_offset + data_size;
+
+        let mut car = CARV2_PRAGMA.to_vec();
+        car.extend_from_slice(&[0u8; 16]); // characteristics
+        car.extend_from_slice(&data_offset.to_le_bytes());
+        car.extend_from_slice(&data_size.to_le_bytes());
+        car.extend_from_slice(&index_offset.to_le_bytes());
+        car.extend(std::iter::repeat_n(0u8, padding));
+        car.extend_from_slice(v1);
+        // Index bytes that would not parse as CAR entries
+        car.extend_from_slice(&[0x80, 0x80, 0x80, 0xff, 0x01, 0x02]);
+        car
+    }
+
+    #[test]
+    fn test_carv2_reads_embedded_v1_payload() {
+        let v1 = multi_entry_car();
+        let expected_entries: Vec<_> = SyncCarReader::from_bytes(&v1)
+            .unwrap()
+            .map(|entry| entry.unwrap().bytes)
+            .collect();
+        let expected_records: Vec<_> = CarRecords::from_bytes(v1.clone())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        for padding in [0, 7] {
+            let car = carv2_wrap(&v1, padding);
+
+            let reader = SyncCarReader::from_bytes(&car).unwrap();
+            assert_eq!(reader.header().version, 1);
+            assert_eq!(reader.header().roots.len(), 1);
+            let entries: Vec<_> = reader.map(|entry| entry.unwrap().bytes).collect();
+            assert_eq!(entries, expected_entries);
+
+            let records: Vec<_> = CarRecords::from_bytes(car.clone())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(records, expected_records);
+
+            let streamed: Vec<_> = CarRecords::from_reader(Trickle {
+                data: car,
+                pos: 0,
+                chunk: 3,
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+            assert_eq!(streamed, expected_records, "padding {}", padding);
+        }
+    }
+
+    #[test]
+    fn test_carv2_truncated_data_section() {
+        let v1 = multi_entry_car();
+        let car = carv2_wrap(&v1, 0);
+        let truncated = &car[..CARV2_PRAGMA.len() + CARV2_HEADER_LEN + v1.len() / 2];
+
+        assert!(matches!(
+            SyncCarReader::from_bytes(truncated),
+            Err(CarError::UnexpectedEof)
+        ));
+        let records: Vec<_> = CarRecords::from_bytes(truncated.to_vec()).unwrap().collect();
+        assert!(matches!(records.last(), Some(Err(CarError::UnexpectedEof))));
+    }
+
+    #[test]
+    fn test_unknown_car_version_is_unsupported() {
+        let header_cbor = encode_cbor_map(&[
+            ("version", CborVal::Int(3)),
+            ("roots", CborVal::Array(vec![])),
+        ]);
+        let mut data = vec![header_cbor.len() as u8];
+        data.extend_from_slice(&header_cbor);
+
+        assert!(matches!(
+            SyncCarReader::from_bytes(&data),
+            Err(CarError::UnsupportedVersion(3))
+        ));
+        let mut records = CarRecords::from_bytes(data).unwrap();
+        assert!(matches!(
+            records.next(),
+            Some(Err(CarError::UnsupportedVersion(3)))
+        ));
+    }
+
+    #[test]
+    fn test_car_index_fetches_blocks_by_cid() {
+        use crate::car::test_support::{car_file, encode, test_cid, Cbor};
+
+        let text = |t: &str| Cbor::Map(vec![("text", Cbor::Text(t.to_string()))]);
+        let car = car_file(
+            &test_cid(1),
+            vec![
+                (test_cid(1), text("root")),
+                (test_cid(2), text("second")),
+                (test_cid(3), Cbor::Bytes(vec![0xAB; 300])),
+            ],
+        );
+        let cid = |n| Cid::from_bytes(&test_cid(n)).unwrap();
+
+        let index = CarIndex::build(&car).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.header().roots[0], cid(1));
+
+        let mut expected = Vec::new();
+        encode(&text("second"), &mut expected);
+        assert_eq!(index.get_block(&cid(2)), Some(&expected[..]));
+        assert_eq!(index.get_block(&cid(3)).map(<[u8]>::len), Some(303));
+        assert_eq!(index.get_block(&cid(9)), None);
+
+        // Same blocks as a sequential read
+        for entry in SyncCarReader::from_bytes(&car).unwrap() {
+            let entry = entry.unwrap();
+            assert_eq!(index.get_block(&entry.cid), Some(&entry.bytes[..]));
+        }
+
+        // CARv2 containers index their data section
+        let v2 = carv2_wrap(&car, 5);
+        let v2_index = CarIndex::build(&v2).unwrap();
+        assert_eq!(v2_index.get_block(&cid(2)), Some(&expected[..]));
+    }
+
+    #[test]
+    fn test_car_index_rejects_truncated_car() {
+        let car = multi_entry_car();
+        assert!(matches!(
+            CarIndex::build(&car[..car.len() - 1]),
+            Err(CarError::UnexpectedEof)
+        ));
     }
 }
@@ -0,0 +1,196 @@
+//! Synthetic DAG-CBOR and CAR builders for tests
+
+/// Minimal DAG-CBOR values for building test fixtures
+pub enum Cbor {
+    Text(String),
+    Bytes(Vec<u8>),
+    Int(i64),
+    Null,
+    Link(Vec<u8>),
+    Array(Vec<Cbor>),
+    Map(Vec<(&'static str, Cbor)>),
+}
+
+fn cbor_head(out: &mut Vec<u8>, major: u8, len: u64) {
+    if len < 24 {
+        out.push(major << 5 | len as u8);
+    } else if len < 256 {
+        out.extend([major << 5 | 24, len as u8]);
+    } else if len < 65536 {
+        out.push(major << 5 | 25);
+        out.extend((len as u16).to_be_bytes());
+    } else {
+        out.push(major << 5 | 26);
+        out.extend((len as u32).to_be_bytes());
+    }
+}
+
+/// Append the DAG-CBOR encoding of `value`
+pub fn encode(value: &Cbor, out: &mut Vec<u8>) {
+    match value {
+        Cbor::Text(t) => {
+            cbor_head(out, 3, t.len() as u64);
+            out.extend(t.as_bytes());
+        }
+        Cbor::Bytes(b) => {
+            cbor_head(out, 2, b.len() as u64);
+            out.extend(b);
+        }
+        Cbor::Int(n) if *n >= 0 => cbor_head(out, 0, *n as u64),
+        Cbor::Int(n) => cbor_head(out, 1, (-1 - *n) as u64),
+        Cbor::Null => out.push(0xF6),
+        Cbor::Link(cid) => {
+            out.extend([0xD8, 42]);
+            cbor_head(out, 2, cid.len() as u64 + 1);
+            out.push(0x00);
+            out.extend(cid);
+        }
+        Cbor::Array(items) => {
+            cbor_head(out, 4, items.len() as u64);
+            items.iter().for_each(|item| encode(item, out));
+        }
+        Cbor::Map(pairs) => {
+            cbor_head(out, 5, pairs.len() as u64);
+            for (k, v) in pairs {
+                encode(&Cbor::Text(k.to_string()), out);
+                encode(v, out);
+            }
+        }
+    }
+}
+
+/// Append `n` as an unsigned LEB128 varint
+pub fn varint(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7F) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// A dag-cbor sha-256 CID whose digest is `n` repeated
+pub fn test_cid(n: u8) -> Vec<u8> {
+    let mut cid = vec![1, 0x71, 0x12, 32];
+    cid.extend([n; 32]);
+    cid
+}
+
+/// The dag-cbor sha-256 CID of `value`'s encoding, as a real repo would name its block
+pub fn block_cid(value: &Cbor) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+
+    let mut block = Vec::new();
+    encode(value, &mut block);
+    let mut cid = vec![1, 0x71, 0x12, 32];
+    cid.extend(Sha256::digest(&block));
+    cid
+}
+
+/// A CAR file with one root and the given (CID, block) sections
+pub fn car_file(root: &[u8], blocks: Vec<(Vec<u8>, Cbor)>) -> Vec<u8> {
+    let mut header = Vec::new();
+    encode(
+        &Cbor::Map(vec![
+            ("roots", Cbor::Array(vec![Cbor::Bytes(root.to_vec())])),
+            ("version", Cbor::Int(1)),
+        ]),
+        &mut header,
+    );
+    let mut car = Vec::new();
+    varint(&mut car, header.len());
+    car.extend(header);
+    for (cid, value) in blocks {
+        let mut block = Vec::new();
+        encode(&value, &mut block);
+        varint(&mut car, cid.len() + block.len());
+        car.extend(cid);
+        car.extend(block);
+    }
+    car
+}
+
+/// A repo CAR whose commit names `did` (if any) and whose MST holds `posts` (rkey, text)
+pub fn synthetic_repo_car(did: Option<&str>, posts: &[(&str, &str)]) -> Vec<u8> {
+    let records = posts
+        .iter()
+        .map(|(rkey, text)| ("app.bsky.feed.post", *rkey, post_record(text)))
+        .collect();
+    synthetic_repo_records_car(did, records)
+}
+
+/// A repo CAR whose commit names `did` (if any) and whose MST holds `records`
+/// (collection, rkey, value), which must be given in key order
+pub fn synthetic_repo_records_car(did: Option<&str>, records: Vec<(&str, &str, Cbor)>) -> Vec<u8> {
+    let mut blocks: Vec<(Vec<u8>, Cbor)> = Vec::new();
+
+    let mut entries = Vec::new();
+    for (collection, rkey, value) in records {
+        let record_cid = block_cid(&value);
+        entries.push(Cbor::Map(vec![
+            ("k", Cbor::Bytes(format!("{}/{}", collection, rkey).into_bytes())),
+            ("p", Cbor::Int(0)),
+            ("t", Cbor::Null),
+            ("v", Cbor::Link(record_cid.clone())),
+        ]));
+        blocks.push((record_cid, value));
+    }
+    let mst = Cbor::Map(vec![("e", Cbor::Array(entries)), ("l", Cbor::Null)]);
+    let mst_cid = block_cid(&mst);
+    blocks.push((mst_cid.clone(), mst));
+
+    let mut commit = vec![("data", Cbor::Link(mst_cid)), ("version", Cbor::Int(3))];
+    if let Some(did) = did {
+        commit.push(("did", Cbor::Text(did.to_string())));
+    }
+    let commit = Cbor::Map(commit);
+    let commit_cid = block_cid(&commit);
+    blocks.insert(0, (commit_cid.clone(), commit));
+
+    car_file(&commit_cid, blocks)
+}
+
+/// An `app.bsky.feed.post` record with fixed `createdAt`
+fn post_record(text: &str) -> Cbor {
+    Cbor::Map(vec![
+        ("$type", Cbor::Text("app.bsky.feed.post".to_string())),
+        ("text", Cbor::Text(text.to_string())),
+        ("createdAt", Cbor::Text("2025-01-01T00:00:00Z".to_string())),
+    ])
+}
+
+/// A firehose `#commit` event for `did` with post ops `(action, rkey, CID number, text)`
+pub fn firehose_commit(did: &str, seq: i64, ops: Vec<(&str, &str, u8, Option<&str>)>) -> Vec<u8> {
+    let mut blocks = Vec::new();
+    let mut op_values = Vec::new();
+    for (action, rkey, cid, text) in ops {
+        let cid_value = if action == "delete" {
+            Cbor::Null
+        } else {
+            Cbor::Link(test_cid(cid))
+        };
+        op_values.push(Cbor::Map(vec![
+            ("action", Cbor::Text(action.to_string())),
+            ("path", Cbor::Text(format!("app.bsky.feed.post/{}", rkey))),
+            ("cid", cid_value),
+        ]));
+        if let Some(text) = text {
+            blocks.push((test_cid(cid), post_record(text)));
+        }
+    }
+
+    let mut frame = Vec::new();
+    encode(
+        &Cbor::Map(vec![("op", Cbor::Int(1)), ("t", Cbor::Text("#commit".to_string()))]),
+        &mut frame,
+    );
+    encode(
+        &Cbor::Map(vec![
+            ("seq", Cbor::Int(seq)),
+            ("repo", Cbor::Text(did.to_string())),
+            ("ops", Cbor::Array(op_values)),
+            ("blocks", Cbor::Bytes(car_file(&test_cid(1), blocks))),
+        ]),
+        &mut frame,
+    );
+    frame
+}
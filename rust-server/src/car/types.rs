@@ -1,7 +1,9 @@
+use crate::car::{decode_multibase, encode_multibase, CarError};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Cid {
     pub version: u8,
     pub codec: u8,
@@ -9,9 +11,85 @@ pub struct Cid {
     pub digest: Vec<u8>,
 }
 
+impl Cid {
+    /// Binary CID: varint version, codec, multihash code and digest length, then the digest
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.digest.len());
+        for value in [
+            self.version as u64,
+            self.codec as u64,
+            self.digest_type as u64,
+            self.digest.len() as u64,
+        ] {
+            push_varint(&mut bytes, value);
+        }
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+
+    /// Parse a binary CID, as found in DAG-CBOR links
+    ///
+    /// A leading 0x00 (the identity multibase prefix used inside tag 42) is skipped.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CarError> {
+        let mut pos = 0usize;
+        if bytes.first() == Some(&0) {
+            pos += 1;
+        }
+
+        let version = read_varint(bytes, &mut pos)? as u8;
+        let codec = read_varint(bytes, &mut pos)? as u8;
+        let digest_type = read_varint(bytes, &mut pos)? as u8;
+        let digest_size = read_varint(bytes, &mut pos)? as usize;
+        if bytes.len() - pos < digest_size {
+            return Err(CarError::UnexpectedEof);
+        }
+        Ok(Self {
+            version,
+            codec,
+            digest_type,
+            digest: bytes[pos..pos + digest_size].to_vec(),
+        })
+    }
+}
+
+fn push_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, CarError> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let byte = *bytes.get(*pos).ok_or(CarError::UnexpectedEof)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(CarError::VarintError("Varint too long".to_string()))
+}
+
+/// Canonical CIDv1 string: multibase base32 with the `b` prefix, e.g. `bafyrei...`
 impl fmt::Display for Cid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(&self.digest))
+        f.write_str(&encode_multibase(&self.to_bytes()))
+    }
+}
+
+impl FromStr for Cid {
+    type Err = CarError;
+
+    /// Parse a canonical base32 CIDv1 string
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cid = Self::from_bytes(&decode_multibase(s)?)?;
+        if cid.version != 1 {
+            return Err(CarError::InvalidCidVersion(cid.version));
+        }
+        Ok(cid)
     }
 }
 
@@ -44,3 +122,66 @@ impl CidLink {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cid_known_vectors() {
+        // Raw-codec CID of the empty byte string
+        let empty = Cid {
+            version: 1,
+            codec: 0x55,
+            digest_type: 0x12,
+            digest: hex::decode(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            )
+            .unwrap(),
+        };
+        let empty_str = "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+        assert_eq!(empty.to_string(), empty_str);
+        assert_eq!(empty_str.parse::<Cid>().unwrap(), empty);
+
+        // DAG-CBOR CID of sha256("hello")
+        let hello_str = "bafyreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq";
+        let hello: Cid = hello_str.parse().unwrap();
+        assert_eq!(hello.codec, 0x71);
+        assert_eq!(hello.digest_type, 0x12);
+        assert_eq!(
+            hex::encode(&hello.digest),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(hello.to_string(), hello_str);
+    }
+
+    #[test]
+    fn test_cid_bytes_round_trip() {
+        let cid = Cid {
+            version: 1,
+            codec: 0x71,
+            digest_type: 0x12,
+            digest: vec![7; 32],
+        };
+        let bytes = cid.to_bytes();
+        assert_eq!(&bytes[..4], &[1, 0x71, 0x12, 32]);
+        assert_eq!(Cid::from_bytes(&bytes).unwrap(), cid);
+
+        // DAG-CBOR links carry a leading identity multibase byte
+        let mut link = vec![0];
+        link.extend_from_slice(&bytes);
+        assert_eq!(Cid::from_bytes(&link).unwrap(), cid);
+
+        assert!(matches!(
+            Cid::from_bytes(&bytes[..10]),
+            Err(CarError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn test_cid_parse_rejects_non_canonical() {
+        assert!("v1-c71-d12-abcdef".parse::<Cid>().is_err());
+        assert!("zQmSomething".parse::<Cid>().is_err());
+        assert!("".parse::<Cid>().is_err());
+    }
+}
@@ -4,9 +4,14 @@
 
 #![allow(non_snake_case)]
 
-use clap::{Parser, Subcommand};
+use crate::bluesky::blob_url::ImageSize;
+use crate::config::Config;
+use crate::error::AppError;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Autoreply CLI
 #[derive(Parser)]
@@ -24,6 +29,123 @@ pub struct Cli {
     /// Suppress non-error output (no short flag to avoid conflicts)
     #[arg(long, global = true)]
     pub quiet: bool,
+
+    /// Image host for blob URLs: an http(s) CDN base, `pds:<url>` for a PDS getBlob endpoint,
+    /// or a URL template with `{did}` and `{ref}` (and optionally `{ext}` and `{size}`)
+    /// (defaults to AUTOREPLY_CDN_HOST, then the config file, then https://cdn.bsky.app)
+    #[arg(long = "cdn-host", global = true)]
+    pub cdn_host: Option<String>,
+
+    /// Rendition of images in posts
+    /// (defaults to AUTOREPLY_IMAGE_SIZE, then the config file, then fullsize)
+    #[arg(long = "image-size", value_enum, global = true)]
+    pub image_size: Option<ImageSize>,
+
+    /// Directory for cached repository CAR files
+    /// (defaults to AUTOREPLY_CACHE_DIR, then the OS cache directory)
+    #[arg(long = "cache-dir", global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Download repositories fresh and do not write them to the cache
+    #[arg(long = "no-cache", global = true)]
+    pub no_cache: bool,
+
+    /// Seconds a cached repository is reused before it is downloaded again (default 3600)
+    #[arg(long = "cache-ttl", global = true)]
+    pub cache_ttl: Option<u64>,
+
+    /// Attempts for each repository or identity lookup before a network error is final
+    /// (default 3; connection errors, timeouts, 429 and 5xx responses are retried)
+    #[arg(
+        long = "http-attempts",
+        global = true,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub http_attempts: Option<u32>,
+
+    /// Largest repository CAR downloaded, in MB (default 1024); bigger downloads are
+    /// abandoned with a repo_too_large error
+    #[arg(
+        long = "max-repo-mb",
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub max_repo_mb: Option<u64>,
+
+    /// Seconds before expiry a stored session is refreshed (default 300)
+    #[arg(long = "refresh-buffer", global = true)]
+    pub refresh_buffer: Option<u64>,
+
+    /// Output format for command results
+    /// (defaults to AUTOREPLY_FORMAT, then the config file, then markdown)
+    #[arg(long, value_enum, global = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Config file with defaults for these options
+    /// (defaults to AUTOREPLY_CONFIG, then config.toml in the autoreply config directory)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Run the MCP server instead of a command, on stdio (as with no arguments) or HTTP
+    #[arg(long, value_enum)]
+    pub transport: Option<Transport>,
+
+    /// Port for `--transport http`, listening on 127.0.0.1 (default 8080)
+    #[arg(long, requires = "transport")]
+    pub port: Option<u16>,
+}
+
+/// Environment variable naming the output format when `--format` is not given
+pub const FORMAT_ENV: &str = "AUTOREPLY_FORMAT";
+
+impl Cli {
+    /// Output format: `--format`, else `AUTOREPLY_FORMAT`, else the config file's `format`,
+    /// else markdown. An unknown `AUTOREPLY_FORMAT` value is an error.
+    pub fn output_format(&self, config: &Config) -> Result<OutputFormat, AppError> {
+        self.output_format_with(config, |name| std::env::var(name).ok())
+    }
+
+    /// [`Cli::output_format`], reading environment variables through `env`
+    fn output_format_with(
+        &self,
+        config: &Config,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<OutputFormat, AppError> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+        match env(FORMAT_ENV).filter(|value| !value.is_empty()) {
+            Some(value) => OutputFormat::from_str(&value, false).map_err(|_| {
+                AppError::InvalidInput(format!(
+                    "Invalid {} '{}': expected markdown, json or plain",
+                    FORMAT_ENV, value
+                ))
+            }),
+            None => Ok(config.format.unwrap_or_default()),
+        }
+    }
+}
+
+/// MCP server transport
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// JSON-RPC over stdin and stdout
+    Stdio,
+    /// Streamable HTTP (JSON or SSE responses) at /mcp
+    Http,
+}
+
+/// How CLI commands print their result
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// LLM-oriented markdown, as MCP clients see it
+    #[default]
+    Markdown,
+    /// The structured data behind the markdown, for scripts
+    Json,
+    /// Plain text without markdown decorations, for piping into text tools
+    Plain,
 }
 
 #[derive(Subcommand)]
@@ -34,6 +156,8 @@ pub enum Commands {
     Search(SearchArgs),
     /// Manage authentication and accounts
     Login(LoginCommand),
+    /// List or remove stored accounts
+    Accounts(AccountsCommand),
     /// Get the latest feed from BlueSky
     Feed(FeedArgs),
     /// Fetch a thread by post URI
@@ -42,25 +166,103 @@ pub enum Commands {
     Post(PostArgs),
     /// Perform batch reactions on posts (like, unlike, repost, delete)
     React(ReactArgs),
+    /// Follow or unfollow an account
+    Follow(FollowArgs),
+    /// List recent notifications grouped by kind
+    Notifications(NotificationsArgs),
+    /// Get the home timeline of an authenticated account
+    Timeline(TimelineArgs),
+    /// Show the lists and custom feeds an account curates
+    List(ListArgs),
+    /// Show the accounts that liked a post
+    Likes(LikesArgs),
+    /// List an account's followers or the accounts it follows
+    Graph(GraphArgs),
+    /// Mute, unmute, block or unblock an account
+    Moderation(ModerationArgs),
+    /// Show one record of any collection as JSON
+    Record(RecordArgs),
+    /// Print a shell completion script to stdout
+    Completions(CompletionsArgs),
+}
+
+/// Completions command arguments
+#[derive(Parser, Debug, Clone)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for: bash, zsh, fish, powershell or elvish
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+/// Completion script for `shell`, covering every subcommand and flag
+pub fn completions(shell: Shell) -> String {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "autoreply", &mut script);
+    String::from_utf8_lossy(&script).into_owned()
 }
 
 /// Profile tool arguments
-#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
 pub struct ProfileArgs {
     #[arg(short = 'a', long)]
     #[schemars(
         description = "Account to find: handle (alice.bsky.social), DID (did:plc:...), Bsky.app profile URL or even display name or search term"
     )]
     pub account: String,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Also report repository statistics (total records, posts, repo size), counted from the cached repo"
+    )]
+    pub withRepoStats: bool,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Skip the follower, following and post counts from the Bluesky API and render only what the repo holds"
+    )]
+    pub noNetworkStats: bool,
 }
 
 /// Search tool arguments
-#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
 pub struct SearchArgs {
-    #[arg(short = 'f', long)]
-    #[schemars(description = "Account whose posts to search: handle, DID, Bsky.app profile URL")]
+    // Not required as such (clap rejects that alongside required_unless); `from` defaults
+    // to empty when a CAR file or capture is searched instead
+    #[arg(
+        short = 'f',
+        long,
+        default_value = "",
+        hide_default_value = true,
+        required = false,
+        required_unless_present_any = ["car", "replay", "scope"]
+    )]
+    #[serde(default)]
+    #[schemars(
+        description = "Account whose posts to search: handle, DID, Bsky.app profile URL. Separate up to 10 accounts with commas to search them together. With scope global, optional: one account to limit the network search to"
+    )]
     pub from: String,
 
+    #[arg(long)]
+    #[schemars(
+        description = "What to search: repo (the repos of from, car or replay, ranked locally) or global (posts across the network via app.bsky.feed.searchPosts, which understands operators such as from:handle, #tag, \"exact phrase\" and lang:en). Defaults to repo"
+    )]
+    pub scope: Option<String>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Paths of downloaded repo CAR files to search offline instead of from; repeat for several. Posts are attributed to each repo's commit DID"
+    )]
+    pub car: Vec<String>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Path of a captured firehose stream to search instead of from or car. Commits are replayed in sequence order, deletes remove posts, and a truncated capture is searched up to where it stops"
+    )]
+    pub replay: Option<String>,
+
     #[arg(short = 'q', long)]
     #[schemars(description = "Search terms")]
     pub query: String,
@@ -68,14 +270,127 @@ pub struct SearchArgs {
     #[arg(short = 'l', long)]
     #[schemars(description = "Defaults to 50")]
     pub limit: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Skip this many ranked results, to continue from the nextOffset of a previous page. Defaults to 0"
+    )]
+    pub offset: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Maximum characters of link card descriptions to show, 0 hides them. Defaults to 200"
+    )]
+    pub embedDescLen: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Character budget for the results: posts past it are left out whole and counted in a closing line. At least one post is always shown. Unset shows the whole page"
+    )]
+    pub maxChars: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Score multiplier for exact matches, 1.0 to 100.0 (clamped). Defaults to 10.0"
+    )]
+    pub exactBonus: Option<f64>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Weight for matches at the start of a word, 0.0 to 2.0 (clamped). Defaults to 0.8"
+    )]
+    pub prefixBonus: Option<f64>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "How strongly newer posts are favored, 0.0 to 10.0 (clamped). Defaults to 0 (off)"
+    )]
+    pub recencyWeight: Option<f64>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Drop matches scoring below this value. Exact matches score in the thousands, scattered fuzzy matches in the tens. Unset keeps all matches"
+    )]
+    pub minScore: Option<f32>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Search mode: fuzzy (default) or semantic, which re-ranks fuzzy matches by meaning. Semantic needs a build with experimental-sentencepiece and falls back to fuzzy otherwise"
+    )]
+    pub mode: Option<String>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Share of meaning similarity in semantic mode's ranking, 0.0 to 1.0 (clamped). Defaults to 0.5"
+    )]
+    pub semanticWeight: Option<f64>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Match and highlight with original letter case (e.g. IT vs it). Defaults to case-insensitive"
+    )]
+    pub caseSensitive: bool,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Debug richtext: list each post's facets with byte range, covered text and target. Defaults to false"
+    )]
+    pub showFacets: bool,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Show post times relative to now (e.g. 3h ago) instead of ISO timestamps. Defaults to false"
+    )]
+    pub relativeTime: bool,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Look up the posts that the results shown quote, to show each quoted author and text (at most 50 per search). Defaults to false"
+    )]
+    pub quotes: bool,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Result order: relevance (default), newest or oldest. Date ordering applies to the limited results"
+    )]
+    pub order: Option<String>,
+
+    #[arg(long)]
+    #[schemars(description = "Only posts created on or after this date (YYYY-MM-DD or RFC 3339)")]
+    pub since: Option<String>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Only posts created on or before this date (YYYY-MM-DD keeps the whole day, or RFC 3339)"
+    )]
+    pub until: Option<String>,
+
+    #[arg(long, allow_hyphen_values = true)]
+    #[schemars(
+        description = "Only posts from the last N days (UTC, 0 = today). Cannot be combined with since"
+    )]
+    pub sinceDays: Option<i64>,
+
+    #[arg(long, allow_hyphen_values = true)]
+    #[schemars(
+        description = "Only posts created up to N days ago (UTC, 0 = today). Cannot be combined with until"
+    )]
+    pub untilDays: Option<i64>,
 }
 
 /// Post tool arguments
-#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
 pub struct PostArgs {
-    #[arg(short = 'a', long)]
-    #[schemars(description = "Account to post as: handle, DID, Bsky.app profile URL")]
-    pub postAs: String,
+    #[arg(short = 'a', long, visible_alias = "account")]
+    #[schemars(
+        description = "Account to post as: handle, DID, Bsky.app profile URL. Defaults to the default account, or the only stored one"
+    )]
+    #[serde(default, alias = "account")]
+    pub postAs: Option<String>,
 
     #[arg(short = 't', long)]
     #[schemars(description = "The text of the post")]
@@ -86,16 +401,67 @@ pub struct PostArgs {
         description = "When replying to a post, pass the link to that post here, you can use at:// URI or https://bsky.app/... URL or even a simple @handle/rkey form."
     )]
     pub replyTo: Option<String>,
+
+    #[arg(long, requires = "replyTo")]
+    #[schemars(
+        description = "Root post of the thread when replying, in the same forms as replyTo. Defaults to the root recorded on the replied-to post, or that post itself if it starts the thread"
+    )]
+    pub root: Option<String>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Post to quote, as an at:// URI or https://bsky.app/... URL. The quoted post is embedded in the new post"
+    )]
+    pub quote: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["replyTo", "quote", "images"])]
+    #[schemars(
+        description = "Your own post to edit instead of creating a new one, in the same forms as replyTo. Only the text is replaced: reply, quote, images and creation time are kept, and existing links, mentions and hashtags follow the text they were on"
+    )]
+    pub edit: Option<String>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Image files to attach (.jpg, .png, .gif or .webp, up to 4, each at most 1 MB)"
+    )]
+    pub images: Vec<PathBuf>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(description = "Alt text for the attached images, in the same order as images")]
+    pub alt: Vec<String>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(description = "Reject the post if any attached image has no alt text")]
+    pub requireAlt: bool,
+
+    #[arg(long)]
+    #[schemars(description = "Reject the post if it contains more than N hashtags")]
+    pub maxHashtags: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Path to a file of banned words or phrases, one per line ('#' starts a comment). Matching is case-insensitive"
+    )]
+    pub banWords: Option<String>,
 }
 
 /// Feed tool arguments
-#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
 pub struct FeedArgs {
     #[arg(short = 'f', long)]
     #[schemars(description = "Feed URI or name. If omitted, returns the default popular feed")]
     pub feed: Option<String>,
 
-    #[arg(short = 'v', long)]
+    #[arg(short = 'a', long, conflicts_with = "feed")]
+    #[schemars(
+        description = "Account whose own recent posts and reposts to list instead of a feed: handle or DID"
+    )]
+    pub author: Option<String>,
+
+    #[arg(long)]
     #[schemars(
         description = "Optional account to view feed with authenticated pattern: handle, DID, Bsky.app profile URL"
     )]
@@ -107,9 +473,38 @@ pub struct FeedArgs {
 
     #[arg(short = 'l', long)]
     #[schemars(
-        description = "Desired number of posts, when omitted will return a reasonable default batch."
+        description = "Desired number of posts, 1 to 500 (larger values are capped). Defaults to 50"
     )]
     pub limit: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(description = "Only posts created on or after this date (YYYY-MM-DD or RFC 3339)")]
+    pub since: Option<String>,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Only posts created on or before this date (YYYY-MM-DD keeps the whole day, or RFC 3339)"
+    )]
+    pub until: Option<String>,
+
+    #[arg(long, allow_hyphen_values = true)]
+    #[schemars(
+        description = "Only posts from the last N days (UTC, 0 = today). Cannot be combined with since"
+    )]
+    pub sinceDays: Option<i64>,
+
+    #[arg(long, allow_hyphen_values = true)]
+    #[schemars(
+        description = "Only posts created up to N days ago (UTC, 0 = today). Cannot be combined with until"
+    )]
+    pub untilDays: Option<i64>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Show post times relative to now (e.g. 3h ago) instead of ISO timestamps. Defaults to false"
+    )]
+    pub relativeTime: bool,
 }
 
 /// Thread tool arguments
@@ -121,11 +516,28 @@ pub struct ThreadArgs {
     )]
     pub postURI: String,
 
-    #[arg(short = 'v', long)]
+    #[arg(long)]
     #[schemars(
         description = "Optional account to view thread as in authenticated mode: handle, DID, Bsky.app profile URL. Use 'anonymous' for incognito mode"
     )]
     pub viewAs: Option<String>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(
+        description = "Debug richtext: list each post's facets with byte range, covered text and target. Defaults to false"
+    )]
+    pub showFacets: bool,
+
+    #[arg(long)]
+    #[schemars(
+        description = "Deepest reply level to show; 0 shows only the requested post (default: 6)"
+    )]
+    pub maxDepth: Option<usize>,
+
+    #[arg(long)]
+    #[schemars(description = "Most replies shown under each post (default: 20)")]
+    pub maxReplies: Option<usize>,
 }
 
 /// React tool arguments
@@ -134,9 +546,12 @@ pub struct ThreadArgs {
     description = "Perform batch reactions on posts. Post references use at:// URIs, https://bsky.app/... URLs, or @handle/rkey format."
 )]
 pub struct ReactArgs {
-    #[arg(short = 'a', long)]
-    #[schemars(description = "Account to react as: handle, DID, Bsky.app profile URL")]
-    pub reactAs: String,
+    #[arg(short = 'a', long, visible_alias = "account")]
+    #[schemars(
+        description = "Account to react as: handle, DID, Bsky.app profile URL. Defaults to the default account, or the only stored one"
+    )]
+    #[serde(default, alias = "account")]
+    pub reactAs: Option<String>,
 
     #[arg(long)]
     #[schemars(description = "Posts to like")]
@@ -153,12 +568,170 @@ pub struct ReactArgs {
     #[serde(default)]
     pub repost: Vec<String>,
 
+    #[arg(long)]
+    #[schemars(description = "Posts to unrepost (remove your repost)")]
+    #[serde(default)]
+    pub unrepost: Vec<String>,
+
     #[arg(long)]
     #[schemars(description = "Posts to delete (must be your own)")]
     #[serde(default)]
     pub delete: Vec<String>,
 }
 
+/// Follow tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct FollowArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(description = "Account to follow from: handle, DID, Bsky.app profile URL")]
+    pub followAs: String,
+
+    #[arg(long)]
+    #[schemars(description = "Account to follow or unfollow: handle, DID, Bsky.app profile URL")]
+    pub account: String,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(description = "Remove an existing follow instead of creating one")]
+    pub unfollow: bool,
+}
+
+/// Record tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct RecordArgs {
+    #[arg(short = 'u', long)]
+    #[schemars(
+        description = "Record to show: at://{did or handle}/{collection}/{rkey}, e.g. at://alice.bsky.social/app.bsky.actor.profile/self"
+    )]
+    pub uri: String,
+}
+
+/// Moderation tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ModerationArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(
+        description = "Account to moderate from: handle, DID, Bsky.app profile URL. Defaults to the default account, or the only stored one"
+    )]
+    #[serde(default)]
+    pub moderateAs: Option<String>,
+
+    #[arg(long)]
+    #[schemars(description = "Account to act on: handle, DID, Bsky.app profile URL")]
+    pub account: String,
+
+    #[arg(long)]
+    #[schemars(
+        description = "mute or unmute (private, only hides their posts from you), block or unblock (public block record)"
+    )]
+    pub action: String,
+}
+
+/// Graph tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct GraphArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(
+        description = "Account whose follow graph to list: handle, DID or Bsky.app profile URL"
+    )]
+    pub account: String,
+
+    #[arg(short = 'd', long)]
+    #[schemars(
+        description = "'followers' (accounts following it, the default) or 'following' (accounts it follows)"
+    )]
+    pub direction: Option<String>,
+
+    #[arg(short = 'l', long)]
+    #[schemars(
+        description = "Desired number of accounts, 1 to 500 (larger values are capped). Defaults to 50"
+    )]
+    pub limit: Option<usize>,
+
+    #[arg(short = 'c', long)]
+    #[schemars(description = "Optional cursor for pagination.")]
+    pub continueAtCursor: Option<String>,
+}
+
+/// Likes tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct LikesArgs {
+    #[arg(short = 'p', long)]
+    #[schemars(
+        description = "Post whose likes to list: at:// URI, https://bsky.app/... URL, or @handle/rkey format"
+    )]
+    pub postURI: String,
+
+    #[arg(short = 'l', long)]
+    #[schemars(
+        description = "Desired number of accounts, 1 to 500 (larger values are capped). Defaults to 50"
+    )]
+    pub limit: Option<usize>,
+
+    #[arg(short = 'c', long)]
+    #[schemars(description = "Optional cursor for pagination.")]
+    pub continueAtCursor: Option<String>,
+}
+
+/// List tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ListArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(
+        description = "Account whose lists and custom feeds to show: handle, DID or Bsky.app profile URL"
+    )]
+    pub account: String,
+
+    #[arg(short = 'n', long)]
+    #[schemars(
+        description = "Show only this list with all its members: the list name, rkey or at:// URI"
+    )]
+    pub list: Option<String>,
+}
+
+/// Notifications tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct NotificationsArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(
+        description = "Account to read notifications for: handle, DID, Bsky.app profile URL"
+    )]
+    pub viewAs: String,
+
+    #[arg(short = 'l', long)]
+    #[schemars(description = "Number of notifications to fetch (default 50, max 100)")]
+    pub limit: Option<usize>,
+
+    #[arg(long)]
+    #[serde(default)]
+    #[schemars(description = "Only show notifications that have not been seen yet")]
+    pub unreadOnly: bool,
+
+    #[arg(short = 'c', long)]
+    #[schemars(description = "Optional cursor for pagination.")]
+    pub continueAtCursor: Option<String>,
+}
+
+/// Timeline tool arguments
+#[derive(Parser, JsonSchema, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct TimelineArgs {
+    #[arg(short = 'a', long)]
+    #[schemars(
+        description = "Account whose home timeline to read: handle, DID, Bsky.app profile URL"
+    )]
+    pub viewAs: String,
+
+    #[arg(short = 'l', long)]
+    #[schemars(
+        description = "Desired number of posts, when omitted will return a reasonable default batch."
+    )]
+    pub limit: Option<usize>,
+
+    #[arg(short = 'c', long)]
+    #[schemars(description = "Optional cursor for pagination.")]
+    pub continueAtCursor: Option<String>,
+}
+
 /// Login command with subcommands for account management
 #[derive(Parser, Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LoginCommand {
@@ -177,13 +750,39 @@ pub struct LoginCommand {
     )]
     pub password: Option<String>,
 
-    // Service is hidden from schema but available for internal/CLI use
+    #[arg(long, global = true)]
+    #[serde(default)]
+    #[schemars(
+        description = "Use the OAuth device flow for machines without a browser: shows a URL and code to approve on any other device"
+    )]
+    pub device: bool,
+
     #[arg(short = 's', long, global = true)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[schemars(skip)]
+    #[schemars(
+        description = "PDS service URL (https) for self-hosted accounts. Defaults to the PDS in the account's DID document"
+    )]
     pub service: Option<String>,
 }
 
+/// Stored account management
+#[derive(Parser, Debug, Clone)]
+pub struct AccountsCommand {
+    #[command(subcommand)]
+    pub command: AccountsSubcommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AccountsSubcommands {
+    /// List stored accounts with their DID and authentication type
+    List,
+    /// Remove an account's stored credentials
+    Remove {
+        /// Handle of the account to remove
+        handle: String,
+    },
+}
+
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum LoginSubcommands {
     /// List all stored accounts
@@ -205,10 +804,86 @@ pub enum LoginSubcommands {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cli_definition_is_valid() {
+        // Catches flags clashing with global ones, which only fail when a subcommand is built
+        Cli::command().debug_assert();
+        SearchArgs::command().debug_assert();
+    }
+
+    #[test]
+    fn test_bash_completions_cover_subcommands() {
+        let cli = Cli::try_parse_from(["autoreply", "completions", "bash"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Completions(CompletionsArgs { shell: Shell::Bash }))
+        ));
+
+        let script = completions(Shell::Bash);
+        assert!(script.contains("_autoreply()"));
+        for subcommand in ["profile", "search", "feed", "thread", "post", "react", "login"] {
+            assert!(script.contains(subcommand), "missing {}", subcommand);
+        }
+        assert!(script.contains("--query"));
+        assert!(!completions(Shell::Zsh).is_empty());
+    }
+
+    #[test]
+    fn test_format_flag_env_file_default_order() {
+        let parse = |args: &[&str]| {
+            let mut argv = vec!["autoreply"];
+            argv.extend_from_slice(args);
+            argv.extend(["completions", "bash"]);
+            Cli::try_parse_from(argv).unwrap()
+        };
+        let json_file = Config {
+            format: Some(OutputFormat::Json),
+            ..Config::default()
+        };
+        let markdown_file = Config {
+            format: Some(OutputFormat::Markdown),
+            ..Config::default()
+        };
+
+        let no_env = |_: &str| None;
+        let format = |cli: &Cli, config: &Config, env: &dyn Fn(&str) -> Option<String>| {
+            cli.output_format_with(config, env).unwrap()
+        };
+        assert_eq!(format(&parse(&[]), &Config::default(), &no_env), OutputFormat::Markdown);
+        assert_eq!(format(&parse(&[]), &json_file, &no_env), OutputFormat::Json);
+        let flag = parse(&["--format", "markdown"]);
+        assert_eq!(format(&flag, &json_file, &no_env), OutputFormat::Markdown);
+
+        let json_env = |name: &str| (name == FORMAT_ENV).then(|| "json".to_string());
+        assert_eq!(format(&parse(&[]), &markdown_file, &json_env), OutputFormat::Json);
+        assert_eq!(format(&flag, &json_file, &json_env), OutputFormat::Markdown);
+
+        // An empty value is unset; an unknown one is an error
+        let empty_env = |_: &str| Some(String::new());
+        assert_eq!(format(&parse(&[]), &json_file, &empty_env), OutputFormat::Json);
+        let bad_env = |_: &str| Some("yaml".to_string());
+        let err = parse(&[]).output_format_with(&json_file, bad_env).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_search_source_is_required() {
+        let cli = Cli::try_parse_from(["autoreply", "search", "--car", "a.car", "-q", "rust"]);
+        match cli.unwrap().command {
+            Some(Commands::Search(args)) => {
+                assert_eq!(args.from, "");
+                assert_eq!(args.car, vec!["a.car"]);
+            }
+            _ => panic!("expected the search command"),
+        }
+        assert!(Cli::try_parse_from(["autoreply", "search", "-q", "rust"]).is_err());
+    }
+
     #[test]
     fn test_profile_args() {
         let args = ProfileArgs {
             account: "alice.bsky.social".to_string(),
+            ..Default::default()
         };
         assert_eq!(args.account, "alice.bsky.social");
     }
@@ -219,6 +894,7 @@ mod tests {
             from: "bob.bsky.social".to_string(),
             query: "rust programming".to_string(),
             limit: Some(10),
+            ..Default::default()
         };
         assert_eq!(args.from, "bob.bsky.social");
         assert_eq!(args.query, "rust programming");
@@ -228,25 +904,47 @@ mod tests {
     #[test]
     fn test_post_args() {
         let args = PostArgs {
-            postAs: "alice.bsky.social".to_string(),
+            postAs: Some("alice.bsky.social".to_string()),
             text: "Hello, world!".to_string(),
             replyTo: None,
+            ..Default::default()
         };
-        assert_eq!(args.postAs, "alice.bsky.social");
+        assert_eq!(args.postAs.as_deref(), Some("alice.bsky.social"));
         assert_eq!(args.text, "Hello, world!");
         assert!(args.replyTo.is_none());
     }
 
+    #[test]
+    fn test_account_flag_and_field() {
+        let cli = Cli::try_parse_from(["autoreply", "post", "--account", "alice.test", "-t", "hi"])
+            .unwrap();
+        match cli.command {
+            Some(Commands::Post(args)) => assert_eq!(args.postAs.as_deref(), Some("alice.test")),
+            _ => panic!("expected the post command"),
+        }
+
+        let args: ReactArgs = serde_json::from_value(serde_json::json!({
+            "account": "bob.test",
+            "like": ["at://did:plc:abc/app.bsky.feed.post/1"]
+        }))
+        .unwrap();
+        assert_eq!(args.reactAs.as_deref(), Some("bob.test"));
+
+        let args: PostArgs = serde_json::from_value(serde_json::json!({"text": "hi"})).unwrap();
+        assert!(args.postAs.is_none());
+    }
+
     #[test]
     fn test_react_args() {
         let args = ReactArgs {
-            reactAs: "bob.bsky.social".to_string(),
+            reactAs: Some("bob.bsky.social".to_string()),
             like: vec!["at://did:plc:abc/app.bsky.feed.post/123".to_string()],
             unlike: vec![],
             repost: vec![],
+            unrepost: vec![],
             delete: vec![],
         };
-        assert_eq!(args.reactAs, "bob.bsky.social");
+        assert_eq!(args.reactAs.as_deref(), Some("bob.bsky.social"));
         assert_eq!(args.like.len(), 1);
         assert_eq!(args.unlike.len(), 0);
     }
@@ -258,6 +956,7 @@ mod tests {
             viewAs: Some("alice.bsky.social".to_string()),
             continueAtCursor: None,
             limit: Some(50),
+            ..Default::default()
         };
         assert_eq!(
             args.feed,
@@ -271,6 +970,9 @@ mod tests {
         let args = ThreadArgs {
             postURI: "at://did:plc:abc/app.bsky.feed.post/123".to_string(),
             viewAs: None,
+            showFacets: false,
+            maxDepth: None,
+            maxReplies: None,
         };
         assert_eq!(args.postURI, "at://did:plc:abc/app.bsky.feed.post/123");
     }
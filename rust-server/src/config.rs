@@ -0,0 +1,193 @@
+//! Defaults from the config file
+//!
+//! `config.toml` in the autoreply config directory (`~/.config/autoreply` on Linux), or the
+//! file named by `--config` / `AUTOREPLY_CONFIG`, sets defaults for options that would
+//! otherwise be repeated on every invocation. A command-line flag always wins, then the
+//! option's environment variable, then the file, then the built-in default.
+//!
+//! ```toml
+//! account = "alice.bsky.social"
+//! cache-dir = "~/bsky-repos"
+//! service = "https://pds.example.com"
+//! format = "json"
+//! cdn-host = "https://img.example.com/{size}/{did}/{ref}@{ext}"
+//! image-size = "thumbnail"
+//! models-dir = "~/gemma-data"
+//! embedding-bits = 4
+//! ```
+
+use crate::bluesky::blob_url::ImageSize;
+use crate::cli::OutputFormat;
+use crate::error::AppError;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the config file when `--config` is not given
+pub const CONFIG_ENV: &str = "AUTOREPLY_CONFIG";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Defaults read from the config file; every field is optional
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Account used by authenticated commands when none is named (before the stored default)
+    pub account: Option<String>,
+    /// Directory for cached repository CAR files
+    pub cache_dir: Option<PathBuf>,
+    /// PDS service URL used by `login` when none is given
+    pub service: Option<String>,
+    /// Output format for CLI command results
+    pub format: Option<OutputFormat>,
+    /// Image host or URL template for blob URLs
+    pub cdn_host: Option<String>,
+    /// Rendition of images in posts
+    pub image_size: Option<ImageSize>,
+    /// Directory holding the tokenizer and embeddings used by semantic search
+    pub models_dir: Option<PathBuf>,
+    /// Quantization width (4, 8 or 16 bits) of the embeddings used by semantic search
+    pub embedding_bits: Option<u32>,
+}
+
+impl Config {
+    /// Parse config file contents; `path` only names the file in errors
+    pub fn parse(text: &str, path: &Path) -> Result<Self, AppError> {
+        let mut config: Config = toml::from_str(text).map_err(|e| {
+            AppError::ConfigError(format!("Invalid config file {}: {}", path.display(), e))
+        })?;
+        config.cache_dir = config.cache_dir.map(|dir| expand_home(&dir));
+        config.models_dir = config.models_dir.map(|dir| expand_home(&dir));
+        if let Some(bits) = config.embedding_bits.filter(|bits| ![4, 8, 16].contains(bits)) {
+            return Err(AppError::ConfigError(format!(
+                "Invalid config file {}: embedding-bits must be 4, 8 or 16, got {}",
+                path.display(),
+                bits
+            )));
+        }
+        Ok(config)
+    }
+
+    /// Read the config file at `path`, or at the default location when `path` is `None`.
+    /// A missing default file is an empty config; a missing named file is an error.
+    pub fn load(path: Option<&Path>) -> Result<Self, AppError> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text, &path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => {
+                Ok(Self::default())
+            }
+            Err(e) => Err(AppError::ConfigError(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
+
+/// `{OS config dir}/autoreply/config.toml`, next to the credentials file
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("autoreply").join("config.toml"))
+}
+
+/// Load the config file named on the command line, else by `AUTOREPLY_CONFIG`, else the
+/// default one, and keep it for the rest of the process.
+/// Call once at startup; a second call keeps the first configuration.
+pub fn init_config(cli_path: Option<&Path>) -> Result<&'static Config, AppError> {
+    let env_path = std::env::var_os(CONFIG_ENV)
+        .map(PathBuf::from)
+        .filter(|path| !path.as_os_str().is_empty());
+    let config = Config::load(cli_path.or(env_path.as_deref()))?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded config (empty if none was loaded)
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// `~/...` paths are relative to the home directory, as a shell would expand them
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_all_fields() {
+        let config = Config::parse(
+            r#"
+account = "alice.bsky.social"
+cache-dir = "/tmp/autoreply-repos"
+service = "https://pds.example.com"
+format = "json"
+cdn-host = "https://img.example.com/{did}/{ref}"
+image-size = "thumbnail"
+models-dir = "/opt/gemma-data"
+embedding-bits = 16
+"#,
+            Path::new("config.toml"),
+        )
+        .unwrap();
+        assert_eq!(config.account.as_deref(), Some("alice.bsky.social"));
+        assert_eq!(config.cache_dir, Some(PathBuf::from("/tmp/autoreply-repos")));
+        assert_eq!(config.service.as_deref(), Some("https://pds.example.com"));
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.cdn_host.as_deref(), Some("https://img.example.com/{did}/{ref}"));
+        assert_eq!(config.image_size, Some(ImageSize::Thumbnail));
+        assert_eq!(config.models_dir, Some(PathBuf::from("/opt/gemma-data")));
+        assert_eq!(config.embedding_bits, Some(16));
+
+        assert_eq!(Config::parse("", Path::new("config.toml")).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_malformed_config_names_the_file() {
+        let path = Path::new("/etc/autoreply.toml");
+        for text in [
+            "account = ",
+            "format = \"yaml\"",
+            "cache_dir = \"/tmp\"",
+            "account = 42",
+            "embedding-bits = 2",
+        ] {
+            let err = Config::parse(text, path).unwrap_err();
+            assert!(matches!(err, AppError::ConfigError(_)), "{}", text);
+            assert!(err.to_string().contains("/etc/autoreply.toml"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_load_missing_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let missing = temp.path().join("config.toml");
+        let err = Config::load(Some(&missing)).unwrap_err();
+        assert!(err.to_string().contains("Failed to read config file"));
+
+        std::fs::write(&missing, "service = \"https://pds.example.com\"\n").unwrap();
+        let config = Config::load(Some(&missing)).unwrap();
+        assert_eq!(config.service.as_deref(), Some("https://pds.example.com"));
+    }
+
+    #[test]
+    fn test_cache_dir_expands_home() {
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        let config = Config::parse("cache-dir = \"~/repos\"", Path::new("config.toml")).unwrap();
+        assert_eq!(config.cache_dir, Some(home.join("repos")));
+    }
+}
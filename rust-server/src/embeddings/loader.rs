@@ -1,9 +1,13 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::{Cursor, Read};
+use std::path::Path;
 
-#[cfg(not(feature = "embed-model"))]
-use anyhow::Context;
+/// Dimensions of the table produced by rust-prep by default
+pub const EMBED_DIMS: usize = 64;
+
+/// Bits per dimension used when none is configured
+pub const DEFAULT_BITS: u32 = 8;
 
 /// Embedding table loaded from EMB8 format
 #[derive(Debug)]
@@ -11,17 +15,25 @@ pub struct EmbeddingTable {
     pub vocab_size: usize,
     pub embed_dim: usize,
     pub original_dim: usize,
+    /// Bits per dimension: 8, or 4 / 16 in version 2 files
+    pub bits: u32,
     pub pca_mean: Vec<f32>,
     pub scales: Vec<f32>,
-    pub embeddings: Vec<u8>, // flattened: vocab_size × embed_dim
+    pub embeddings: Vec<u8>, // flattened: vocab_size × row_bytes()
 }
 
 impl EmbeddingTable {
-    /// Get embedding for a token ID (returns u8 slice, caller must dequantize)
+    /// Bytes per token row; 4-bit rows round up to whole bytes
+    #[inline]
+    pub fn row_bytes(&self) -> usize {
+        (self.embed_dim * self.bits as usize).div_ceil(8)
+    }
+
+    /// Get embedding for a token ID (returns the packed row, caller must dequantize)
     #[inline]
     pub fn get_quantized(&self, token_id: usize) -> &[u8] {
-        let start = token_id * self.embed_dim;
-        let end = start + self.embed_dim;
+        let start = token_id * self.row_bytes();
+        let end = start + self.row_bytes();
         &self.embeddings[start..end]
     }
 
@@ -36,33 +48,59 @@ impl EmbeddingTable {
     pub fn dequantize_into(&self, token_id: usize, out: &mut [f32]) {
         let quantized = self.get_quantized(token_id);
         let scale = self.get_scale(token_id);
-        for (i, &q) in quantized.iter().enumerate() {
-            out[i] = (q as f32 - 128.0) * scale;
+        match self.bits {
+            4 => {
+                for (i, x) in out.iter_mut().take(self.embed_dim).enumerate() {
+                    let q = (quantized[i / 2] >> ((i % 2) * 4)) & 0x0f;
+                    *x = (q as f32 - 8.0) * scale;
+                }
+            }
+            16 => {
+                for (x, q) in out.iter_mut().zip(quantized.chunks_exact(2)) {
+                    *x = (u16::from_le_bytes([q[0], q[1]]) as f32 - 32768.0) * scale;
+                }
+            }
+            _ => {
+                for (i, &q) in quantized.iter().enumerate() {
+                    out[i] = (q as f32 - 128.0) * scale;
+                }
+            }
         }
     }
 }
 
-/// Load embeddings from embedded binary (compile-time included)
+/// File name rust-prep writes for the [`EMBED_DIMS`]-dimension table quantized to `bits`
+pub fn embeddings_file_name(bits: u32) -> String {
+    format!("embeddings_{}d_q{}.bin", EMBED_DIMS, bits)
+}
+
+/// Load the embeddings quantized to `bits`; the default width is embedded in the binary
 #[cfg(feature = "embed-model")]
-pub fn load_embeddings() -> Result<EmbeddingTable> {
+pub fn load_embeddings(dir: &Path, bits: u32) -> Result<EmbeddingTable> {
     const DATA: &[u8] = include_bytes!("../../../gemma-data/embeddings_64d_q8.bin");
-    parse_emb8(DATA)
+    if bits == DEFAULT_BITS {
+        return parse_emb8(DATA);
+    }
+    read_embeddings(dir, bits)
 }
 
-/// Load embeddings from external file (runtime load, memory-mapped)
+/// Load the embeddings quantized to `bits` from their file in `dir`
 #[cfg(not(feature = "embed-model"))]
-pub fn load_embeddings() -> Result<EmbeddingTable> {
-    use std::fs;
-    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .unwrap()
-        .join("gemma-data")
-        .join("embeddings_64d_q8.bin");
-
-    let data =
-        fs::read(&path).with_context(|| format!("failed to read embeddings from {:?}", path))?;
-
-    parse_emb8(&data)
+pub fn load_embeddings(dir: &Path, bits: u32) -> Result<EmbeddingTable> {
+    read_embeddings(dir, bits)
+}
+
+fn read_embeddings(dir: &Path, bits: u32) -> Result<EmbeddingTable> {
+    let path = dir.join(embeddings_file_name(bits));
+
+    let data = std::fs::read(&path)
+        .with_context(|| format!("failed to read embeddings from {:?}", path))?;
+
+    let table = parse_emb8(&data)?;
+    if table.bits != bits {
+        bail!("{:?} holds {}-bit embeddings, expected {}", path, table.bits, bits);
+    }
+    Ok(table)
 }
 
 /// Parse EMB8 binary format
@@ -77,7 +115,7 @@ fn parse_emb8(data: &[u8]) -> Result<EmbeddingTable> {
     }
 
     let version = cursor.read_u32::<LittleEndian>()?;
-    if version != 1 {
+    if version != 1 && version != 2 {
         bail!("unsupported version: {}", version);
     }
 
@@ -85,8 +123,16 @@ fn parse_emb8(data: &[u8]) -> Result<EmbeddingTable> {
     let embed_dim = cursor.read_u32::<LittleEndian>()? as usize;
     let original_dim = cursor.read_u32::<LittleEndian>()? as usize;
 
+    // Version 2 tags the width in the first reserved word; version 1 is always 8-bit
+    let tag = cursor.read_u32::<LittleEndian>()?;
+    let bits = match (version, tag) {
+        (1, _) => 8,
+        (_, 4 | 8 | 16) => tag,
+        _ => bail!("unsupported quantization width: {} bits", tag),
+    };
+
     // Skip reserved bytes
-    cursor.set_position(cursor.position() + 12);
+    cursor.set_position(cursor.position() + 8);
 
     // Read PCA mean (original_dim floats)
     let mut pca_mean = vec![0.0f32; original_dim];
@@ -100,15 +146,19 @@ fn parse_emb8(data: &[u8]) -> Result<EmbeddingTable> {
         scales[i] = cursor.read_f32::<LittleEndian>()?;
     }
 
-    // Read quantized embeddings (vocab_size × embed_dim bytes)
-    let emb_size = vocab_size * embed_dim;
+    // Read quantized embeddings (vocab_size × row bytes)
+    let emb_size = vocab_size * (embed_dim * bits as usize).div_ceil(8);
     let pos = cursor.position() as usize;
+    if data.len() < pos + emb_size {
+        bail!("truncated embeddings: expected {} bytes, found {}", emb_size, data.len() - pos);
+    }
     let embeddings = data[pos..pos + emb_size].to_vec();
 
     Ok(EmbeddingTable {
         vocab_size,
         embed_dim,
         original_dim,
+        bits,
         pca_mean,
         scales,
         embeddings,
@@ -120,9 +170,12 @@ mod tests {
     use super::*;
 
     #[test]
+    #[ignore = "needs generated models in gemma-data"]
     fn test_load_embeddings() {
-        let table = load_embeddings().expect("failed to load embeddings");
-        assert_eq!(table.embed_dim, 64);
+        // The table is generated by rust-prep and not checked in
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../gemma-data");
+        let table = load_embeddings(&dir, DEFAULT_BITS).expect("failed to load embeddings");
+        assert_eq!(table.embed_dim, EMBED_DIMS);
         assert!(table.vocab_size > 0);
 
         // Test dequantization
@@ -132,4 +185,39 @@ mod tests {
         // Should have reasonable values
         assert!(out.iter().any(|&x| x.abs() > 1e-6));
     }
+
+    /// An EMB8 file with one token of `values` stored at `bits` wide, scale 0.5
+    fn emb8(version: u32, bits: u32, dim: u32, values: &[u8]) -> Vec<u8> {
+        let mut data = b"EMB8".to_vec();
+        for word in [version, 1, dim, 1, bits, 0, 0] {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data.extend_from_slice(&0.0f32.to_le_bytes()); // PCA mean
+        data.extend_from_slice(&0.5f32.to_le_bytes()); // scale
+        data.extend_from_slice(values);
+        data
+    }
+
+    #[test]
+    fn test_parse_quantization_widths() {
+        let dequantize = |data: Vec<u8>| {
+            let table = parse_emb8(&data).unwrap();
+            let mut out = vec![0.0f32; table.embed_dim];
+            table.dequantize_into(0, &mut out);
+            out
+        };
+
+        // Version 1 ignores the reserved word and stays 8-bit
+        assert_eq!(dequantize(emb8(1, 0, 2, &[130, 126])), vec![1.0, -1.0]);
+        assert_eq!(dequantize(emb8(1, 16, 2, &[130, 126])), vec![1.0, -1.0]);
+        // 4-bit: low nibble first, offset 8; an odd dimension leaves the last high nibble unused
+        assert_eq!(dequantize(emb8(2, 4, 3, &[0x6a, 0x09])), vec![1.0, -1.0, 0.5]);
+        // 16-bit little-endian, offset 32768
+        let (up, down) = ((32770u16).to_le_bytes(), (32766u16).to_le_bytes());
+        let values = [up[0], up[1], down[0], down[1]];
+        assert_eq!(dequantize(emb8(2, 16, 2, &values)), vec![1.0, -1.0]);
+
+        assert!(parse_emb8(&emb8(2, 12, 2, &[0, 0, 0])).is_err());
+        assert!(parse_emb8(&emb8(2, 16, 2, &[0, 0])).is_err());
+    }
 }
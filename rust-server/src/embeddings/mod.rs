@@ -8,4 +8,4 @@ mod engine;
 mod loader;
 
 pub use engine::embed_text;
-pub use loader::{load_embeddings, EmbeddingTable};
+pub use loader::{embeddings_file_name, load_embeddings, EmbeddingTable, DEFAULT_BITS};
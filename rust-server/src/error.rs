@@ -10,12 +10,15 @@ pub enum AppError {
     InvalidInput(String),
     DidResolveFailed(String),
     RepoFetchFailed(String),
+    RepoTooLarge(String),
     RepoParseFailed(String),
     NotFound(String),
+    AccountUnavailable(String),
     Timeout(String),
     CacheError(String),
     HttpClientInitialization(String),
     NetworkError(String),
+    RateLimited(String),
     Authentication(String),
     ConfigError(String),
     ParseError(String),
@@ -28,14 +31,17 @@ impl fmt::Display for AppError {
             AppError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             AppError::DidResolveFailed(msg) => write!(f, "DID resolution failed: {}", msg),
             AppError::RepoFetchFailed(msg) => write!(f, "Repository fetch failed: {}", msg),
+            AppError::RepoTooLarge(msg) => write!(f, "Repository too large: {}", msg),
             AppError::RepoParseFailed(msg) => write!(f, "Repository parse failed: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::AccountUnavailable(msg) => write!(f, "Account unavailable: {}", msg),
             AppError::Timeout(msg) => write!(f, "Timeout: {}", msg),
             AppError::CacheError(msg) => write!(f, "Cache error: {}", msg),
             AppError::HttpClientInitialization(msg) => {
                 write!(f, "HTTP client initialization failed: {}", msg)
             }
             AppError::NetworkError(msg) => write!(f, "Network error: {}", msg),
+            AppError::RateLimited(msg) => write!(f, "Rate limited: {}", msg),
             AppError::Authentication(msg) => write!(f, "Authentication error: {}", msg),
             AppError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
             AppError::ParseError(msg) => write!(f, "Parse error: {}", msg),
@@ -53,12 +59,15 @@ impl AppError {
             AppError::InvalidInput(_) => "invalid_input",
             AppError::DidResolveFailed(_) => "did_resolve_failed",
             AppError::RepoFetchFailed(_) => "repo_fetch_failed",
+            AppError::RepoTooLarge(_) => "repo_too_large",
             AppError::RepoParseFailed(_) => "repo_parse_failed",
             AppError::NotFound(_) => "not_found",
+            AppError::AccountUnavailable(_) => "account_unavailable",
             AppError::Timeout(_) => "timeout",
             AppError::CacheError(_) => "cache_error",
             AppError::HttpClientInitialization(_) => "http_client_initialization",
             AppError::NetworkError(_) => "network_error",
+            AppError::RateLimited(_) => "rate_limited",
             AppError::Authentication(_) => "authentication_error",
             AppError::ConfigError(_) => "config_error",
             AppError::ParseError(_) => "parse_error",
@@ -66,6 +75,29 @@ impl AppError {
         }
     }
 
+    /// Process exit code for CLI mode: 1 invalid input, 2 network or API failure,
+    /// 3 not found or unavailable, 4 timeout, 5 anything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::InvalidInput(_) => 1,
+            AppError::RepoFetchFailed(_)
+            | AppError::RepoTooLarge(_)
+            | AppError::NetworkError(_)
+            | AppError::RateLimited(_) => 2,
+            AppError::NotFound(_)
+            | AppError::AccountUnavailable(_)
+            | AppError::DidResolveFailed(_) => 3,
+            AppError::Timeout(_) => 4,
+            AppError::RepoParseFailed(_)
+            | AppError::CacheError(_)
+            | AppError::HttpClientInitialization(_)
+            | AppError::Authentication(_)
+            | AppError::ConfigError(_)
+            | AppError::ParseError(_)
+            | AppError::Internal(_) => 5,
+        }
+    }
+
     /// Get the error message
     pub fn message(&self) -> String {
         self.to_string()
@@ -168,10 +200,12 @@ pub fn validate_query(query: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Normalize text using Unicode NFKC as specified
+/// Normalize text to Unicode NFC, the form search compares and highlights text in, so a
+/// composed and a decomposed accent are the same. Compatibility characters (fullwidth
+/// letters, ligatures) are kept, as they are in the post text being searched.
 pub fn normalize_text(text: &str) -> String {
     use unicode_normalization::UnicodeNormalization;
-    text.nfkc().collect::<String>().trim().to_string()
+    text.nfc().collect::<String>().trim().to_string()
 }
 
 #[cfg(test)]
@@ -252,6 +286,7 @@ mod tests {
                 "http_client_initialization",
             ),
             (AppError::NetworkError("test".to_string()), "network_error"),
+            (AppError::RateLimited("test".to_string()), "rate_limited"),
             (AppError::Internal("test".to_string()), "internal_error"),
         ];
 
@@ -260,6 +295,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_every_variant_has_exit_and_mcp_codes() {
+        let msg = || "test".to_string();
+        let cases = vec![
+            (AppError::InvalidInput(msg()), 1, "invalid_input"),
+            (AppError::DidResolveFailed(msg()), 3, "did_resolve_failed"),
+            (AppError::RepoFetchFailed(msg()), 2, "repo_fetch_failed"),
+            (AppError::RepoTooLarge(msg()), 2, "repo_too_large"),
+            (AppError::RepoParseFailed(msg()), 5, "repo_parse_failed"),
+            (AppError::NotFound(msg()), 3, "not_found"),
+            (AppError::AccountUnavailable(msg()), 3, "account_unavailable"),
+            (AppError::Timeout(msg()), 4, "timeout"),
+            (AppError::CacheError(msg()), 5, "cache_error"),
+            (AppError::HttpClientInitialization(msg()), 5, "http_client_initialization"),
+            (AppError::NetworkError(msg()), 2, "network_error"),
+            (AppError::RateLimited(msg()), 2, "rate_limited"),
+            (AppError::Authentication(msg()), 5, "authentication_error"),
+            (AppError::ConfigError(msg()), 5, "config_error"),
+            (AppError::ParseError(msg()), 5, "parse_error"),
+            (AppError::Internal(msg()), 5, "internal_error"),
+        ];
+
+        for (error, exit_code, mcp_code) in cases {
+            assert_eq!(error.exit_code(), exit_code, "{:?}", error);
+            assert_eq!(error.error_code(), mcp_code, "{:?}", error);
+        }
+    }
+
     #[test]
     fn test_from_reqwest_error() {
         // We can't easily create specific reqwest errors in tests,
@@ -434,7 +497,7 @@ mod tests {
 
     #[test]
     fn test_normalize_text_unicode() {
-        // Test NFKC normalization
+        // Test NFC normalization
         // These examples use different Unicode forms that should normalize to the same result
         let text1 = "café"; // e with acute accent (composed)
         let text2 = "cafe\u{0301}"; // e + combining acute accent (decomposed)
@@ -458,11 +521,19 @@ mod tests {
 
     #[test]
     fn test_normalize_text_compatibility() {
-        // Test NFKC compatibility normalization
-        // Roman numeral I (Ⅰ U+2160) should normalize to regular I
-        assert_eq!(normalize_text("Ⅰ"), "I");
+        // NFC keeps compatibility characters, which post text is not folded from either:
+        // Roman numeral I (Ⅰ U+2160) and fullwidth A stay as they are
+        assert_eq!(normalize_text("Ⅰ"), "Ⅰ");
+        assert_eq!(normalize_text("Ａ"), "Ａ");
 
-        // Fullwidth A should normalize to regular A
-        assert_eq!(normalize_text("Ａ"), "A");
+        // Combining marks compose in canonical order, whatever order they came in
+        assert_eq!(normalize_text("o\u{031B}\u{0301}"), "\u{1EDB}");
+        assert_eq!(normalize_text("o\u{0301}\u{031B}"), "\u{1EDB}");
+    }
+
+    #[test]
+    fn test_normalize_text_keeps_hashtags_and_digits() {
+        assert_eq!(normalize_text(" #rust "), "#rust");
+        assert_eq!(normalize_text("web3 #2024"), "web3 #2024");
     }
 }
@@ -1,11 +1,298 @@
 //! HTTP client utilities
 //!
-//! Provides a reqwest::Client configured with timeouts and system proxy support
-
-use reqwest::{Client, Proxy};
+//! Provides a reqwest::Client configured with timeouts and system proxy support,
+//! retries with exponential backoff for idempotent GET requests, a wait for rate limits on
+//! every XRPC call, and readable errors for XRPC error responses
+
+use crate::error::AppError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::OnceLock;
 use std::time::Duration;
+use tracing::debug;
 use url::Url;
 
+/// How often, and how patiently, idempotent GETs are retried on transient failures
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry; each further retry doubles it
+    pub base_delay: Duration,
+    /// Upper bound on any single backoff delay
+    pub max_delay: Duration,
+    /// Longest wait honored when the server names one (`Retry-After`, `ratelimit-reset`);
+    /// a longer one ends the retries
+    pub max_server_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_server_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+static RETRY_POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Configure the attempts made for each idempotent GET from the CLI value.
+/// Call once at startup; a second call keeps the first configuration.
+pub fn init_retry_policy(max_attempts: Option<u32>) {
+    let mut policy = RetryPolicy::default();
+    if let Some(max_attempts) = max_attempts {
+        policy.max_attempts = max_attempts.max(1);
+    }
+    let _ = RETRY_POLICY.set(policy);
+}
+
+/// The configured retry policy (`RetryPolicy::default()` if none was configured)
+pub fn retry_policy() -> &'static RetryPolicy {
+    RETRY_POLICY.get_or_init(RetryPolicy::default)
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `retry` (1 for the first): exponential, capped at
+    /// `max_delay`, with jitter taking it down by up to half so clients spread out
+    fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+        exponential.mul_f64(rand::random::<f64>().mul_add(0.5, 0.5))
+    }
+}
+
+/// Request budget a server reports in its `ratelimit-*` headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimit {
+    /// Requests allowed per window (`ratelimit-limit`)
+    pub limit: Option<u64>,
+    /// Requests left in the current window (`ratelimit-remaining`)
+    pub remaining: Option<u64>,
+    /// When the window resets (`ratelimit-reset`, in Unix seconds)
+    pub reset: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// The budget in `headers`, or `None` when the server reports none
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let number = |name: &str| -> Option<u64> {
+            headers.get(name)?.to_str().ok()?.trim().parse().ok()
+        };
+        let limit = Self {
+            limit: number("ratelimit-limit"),
+            remaining: number("ratelimit-remaining"),
+            reset: number("ratelimit-reset")
+                .and_then(|secs| DateTime::from_timestamp(i64::try_from(secs).ok()?, 0)),
+        };
+        (limit != Self::default()).then_some(limit)
+    }
+
+    /// Time left until the window resets, zero once it has passed
+    fn reset_after(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.reset.map(|reset| (reset - now).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+/// Send the request built by `build`, retrying connection errors, timeouts and 5xx
+/// responses as `policy` allows. Other responses, including 4xx like 404, come back as is.
+///
+/// A 429 is retried once, after the wait the server names in `Retry-After` or
+/// `ratelimit-reset`; if that wait is longer than `max_server_wait`, or the retry is limited
+/// too, the 429 comes back for `rate_limit_error` to explain.
+///
+/// Only use this for idempotent requests: a retried request may already have reached the
+/// server.
+pub async fn send_with_retry<F>(policy: &RetryPolicy, build: F) -> Result<Response, reqwest::Error>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 1;
+    let mut rate_limit_retried = false;
+    loop {
+        let delay = match build().send().await {
+            Ok(response) => {
+                let status = response.status();
+                if let Some(limit) = RateLimit::from_headers(response.headers()) {
+                    debug!("{} rate limit: {:?}", response.url(), limit);
+                }
+                let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+                if !(rate_limited || status.is_server_error())
+                    || attempt >= policy.max_attempts
+                    || (rate_limited && rate_limit_retried)
+                {
+                    return Ok(response);
+                }
+
+                let delay = match server_wait(&response, Utc::now()) {
+                    Some(wait) if wait > policy.max_server_wait => return Ok(response),
+                    Some(wait) => wait,
+                    None => policy.backoff(attempt),
+                };
+                rate_limit_retried |= rate_limited;
+                debug!("{} returned {}, retrying in {:?}", response.url(), status, delay);
+                delay
+            }
+            Err(e) if is_retryable_error(&e) && attempt < policy.max_attempts => {
+                let delay = policy.backoff(attempt);
+                debug!("Request failed ({}), retrying in {:?}", e, delay);
+                delay
+            }
+            Err(e) => return Err(e),
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Sending an XRPC call so that it waits out a rate limit
+pub trait SendXrpc {
+    /// Send the request, and send it once more when a 429 names a wait within the retry
+    /// policy's `max_server_wait`, as [`send_with_retry`] does. Nothing else is retried, so
+    /// procedures that write can use it too; a 429 that persists comes back for
+    /// [`rate_limit_error`] to explain.
+    fn send_xrpc(self) -> impl Future<Output = Result<Response, reqwest::Error>> + Send;
+
+    /// [`SendXrpc::send_xrpc`] under `policy` instead of the configured one
+    fn send_xrpc_with(
+        self,
+        policy: &RetryPolicy,
+    ) -> impl Future<Output = Result<Response, reqwest::Error>> + Send;
+}
+
+impl SendXrpc for RequestBuilder {
+    async fn send_xrpc(self) -> Result<Response, reqwest::Error> {
+        self.send_xrpc_with(retry_policy()).await
+    }
+
+    async fn send_xrpc_with(self, policy: &RetryPolicy) -> Result<Response, reqwest::Error> {
+        send_once_rate_limited(policy, self).await
+    }
+}
+
+async fn send_once_rate_limited(
+    policy: &RetryPolicy,
+    request: RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    // A streamed body cannot be sent twice
+    let retry = request.try_clone();
+    let response = request.send().await?;
+    if let Some(limit) = RateLimit::from_headers(response.headers()) {
+        debug!("{} rate limit: {:?}", response.url(), limit);
+    }
+    let Some(retry) = retry else {
+        return Ok(response);
+    };
+    if response.status() != StatusCode::TOO_MANY_REQUESTS || policy.max_attempts < 2 {
+        return Ok(response);
+    }
+    let delay = match server_wait(&response, Utc::now()) {
+        Some(wait) if wait > policy.max_server_wait => return Ok(response),
+        Some(wait) => wait,
+        None => policy.backoff(1),
+    };
+    debug!("{} is limiting requests, retrying in {:?}", response.url(), delay);
+    tokio::time::sleep(delay).await;
+    retry.send().await
+}
+
+/// `AppError::RateLimited` explaining a 429 response and when the limit resets,
+/// or `None` for any other response
+pub fn rate_limit_error(response: &Response) -> Option<AppError> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let host = response.url().host_str().unwrap_or("The server");
+    let now = Utc::now();
+    let limit = RateLimit::from_headers(response.headers()).unwrap_or_default();
+
+    let mut message = format!("{} is limiting requests", host);
+    if let Some(max) = limit.limit {
+        message.push_str(&format!(" ({} per window)", max));
+    }
+    let reset = limit
+        .reset
+        .or_else(|| retry_after(response.headers(), now).map(|wait| now + wait_delta(wait)));
+    match reset {
+        Some(reset) => message.push_str(&format!(
+            "; the limit resets at {} (in {}s)",
+            reset.to_rfc3339_opts(SecondsFormat::Secs, true),
+            (reset - now).num_seconds().max(0)
+        )),
+        None => message.push_str("; try again later"),
+    }
+    Some(AppError::RateLimited(message))
+}
+
+/// Dropped connections and timeouts; not malformed requests or undecodable bodies
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+/// The wait a server asks for: `Retry-After`, or for a 429 the time to `ratelimit-reset`
+fn server_wait(response: &Response, now: DateTime<Utc>) -> Option<Duration> {
+    retry_after(response.headers(), now).or_else(|| {
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+        RateLimit::from_headers(response.headers())?.reset_after(now)
+    })
+}
+
+/// A `Retry-After` header, given in seconds or as an HTTP date
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+fn wait_delta(wait: Duration) -> chrono::TimeDelta {
+    chrono::TimeDelta::from_std(wait).unwrap_or(chrono::TimeDelta::MAX)
+}
+
+/// The `{"error": ..., "message": ...}` body of a failed XRPC call
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct XrpcError {
+    /// Machine-readable error name, e.g. `BlockedActor`
+    pub error: String,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl XrpcError {
+    /// The XRPC error in a response body, if it is one
+    pub fn parse(body: &str) -> Option<Self> {
+        serde_json::from_str(body).ok()
+    }
+
+    /// A clear error when this says `subject` (an account, or a feed) cannot be viewed:
+    /// blocked, taken down, deactivated, suspended or missing. `None` for other errors.
+    pub fn unavailable_error(&self, subject: &str) -> Option<AppError> {
+        let reason = match self.error.as_str() {
+            "BlockedActor" => "is blocked by the viewing account",
+            "BlockedByActor" => "has blocked the viewing account",
+            "AccountTakedown" | "RepoTakendown" => "has been taken down by moderators",
+            "AccountDeactivated" | "RepoDeactivated" => "has been deactivated by its owner",
+            "RepoSuspended" => "is suspended",
+            "ActorNotFound" | "RepoNotFound" | "UnknownFeed" => {
+                return Some(AppError::NotFound(format!("{} does not exist", subject)));
+            }
+            _ => return None,
+        };
+        Some(AppError::AccountUnavailable(format!("{} {}", subject, reason)))
+    }
+}
+
 /// Build a reqwest Client with the given timeout and honoring system proxy env vars
 ///
 /// Recognized env vars (handled by Proxy::system):
@@ -126,8 +413,295 @@ fn should_bypass_proxy(host: &str, rules: &[NoProxyRule]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::http::{HeaderMap, StatusCode as AxumStatus};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
 
+    /// Client that ignores proxy variables other tests set
+    fn direct_client() -> Client {
+        Client::builder().no_proxy().build().unwrap()
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+            max_server_wait: Duration::from_secs(5),
+        }
+    }
+
+    /// Serve `/` with `replies` (status and headers) in turn, the last one repeating,
+    /// counting requests
+    async fn scripted_server(
+        replies: Vec<(u16, Vec<(&'static str, String)>)>,
+    ) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::get(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, reply_headers) = &replies[n.min(replies.len() - 1)];
+                let status = *status;
+                let mut headers = HeaderMap::new();
+                for (name, value) in reply_headers {
+                    headers.insert(*name, value.parse().unwrap());
+                }
+                async move { (AxumStatus::from_u16(status).unwrap(), headers, "body") }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let (url, hits) = scripted_server(vec![(503, vec![]), (500, vec![]), (200, vec![])]).await;
+        let client = direct_client();
+
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let (url, hits) = scripted_server(vec![(502, vec![])]).await;
+        let client = direct_client();
+
+        let response = send_with_retry(&fast_policy(2), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 502);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_client_errors() {
+        let (url, hits) = scripted_server(vec![(404, vec![]), (200, vec![])]).await;
+        let client = direct_client();
+
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    fn header(name: &'static str, value: impl ToString) -> (&'static str, String) {
+        (name, value.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_waits_then_retries_once() {
+        let client = direct_client();
+
+        // Retry-After names the wait
+        let (url, hits) = scripted_server(vec![
+            (429, vec![header("retry-after", 0)]),
+            (200, vec![]),
+        ])
+        .await;
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(rate_limit_error(&response).is_none());
+
+        // So does ratelimit-reset, with the budget exposed to callers
+        let reset = Utc::now().timestamp();
+        let (url, hits) = scripted_server(vec![
+            (
+                429,
+                vec![
+                    header("ratelimit-limit", 3000),
+                    header("ratelimit-remaining", 0),
+                    header("ratelimit-reset", reset),
+                ],
+            ),
+            (200, vec![header("ratelimit-remaining", 2999)]),
+        ])
+        .await;
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        let limit = RateLimit::from_headers(response.headers()).unwrap();
+        assert_eq!(limit.remaining, Some(2999));
+
+        // Only one retry for a rate limit, however many attempts the policy allows
+        let (url, hits) = scripted_server(vec![(429, vec![header("retry-after", 0)])]).await;
+        let response = send_with_retry(&fast_policy(5), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(matches!(rate_limit_error(&response), Some(AppError::RateLimited(_))));
+    }
+
+    #[tokio::test]
+    async fn test_xrpc_send_waits_out_rate_limit_only() {
+        let client = direct_client();
+
+        let (url, hits) = scripted_server(vec![
+            (429, vec![header("retry-after", 0)]),
+            (200, vec![]),
+        ])
+        .await;
+        let response = send_once_rate_limited(&fast_policy(3), client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // A persistent limit is reported after one retry
+        let (url, hits) = scripted_server(vec![(429, vec![header("retry-after", 0)])]).await;
+        let response = send_once_rate_limited(&fast_policy(3), client.get(&url)).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(matches!(rate_limit_error(&response), Some(AppError::RateLimited(_))));
+
+        // Server errors are not retried: the call may have written already
+        let (url, hits) = scripted_server(vec![(503, vec![]), (200, vec![])]).await;
+        let response = send_once_rate_limited(&fast_policy(3), client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Nor is a limit when retries are off
+        let (url, hits) = scripted_server(vec![
+            (429, vec![header("retry-after", 0)]),
+            (200, vec![]),
+        ])
+        .await;
+        let response = send_once_rate_limited(&fast_policy(1), client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_gives_up_on_long_wait() {
+        let client = direct_client();
+        let reset = DateTime::from_timestamp(Utc::now().timestamp() + 600, 0).unwrap();
+        let (url, hits) = scripted_server(vec![
+            (
+                429,
+                vec![
+                    header("ratelimit-limit", 3000),
+                    header("ratelimit-remaining", 0),
+                    header("ratelimit-reset", reset.timestamp()),
+                ],
+            ),
+            (200, vec![]),
+        ])
+        .await;
+
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let Some(AppError::RateLimited(message)) = rate_limit_error(&response) else {
+            panic!("Expected a rate limit error");
+        };
+        assert!(message.starts_with("127.0.0.1 is limiting requests (3000 per window)"));
+        assert!(message.contains(&reset.to_rfc3339_opts(SecondsFormat::Secs, true)));
+
+        // A long Retry-After likewise ends the retries
+        let (url, hits) = scripted_server(vec![
+            (429, vec![header("retry-after", 120)]),
+            (200, vec![]),
+        ])
+        .await;
+        let response = send_with_retry(&fast_policy(3), || client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        let message = rate_limit_error(&response).unwrap().to_string();
+        assert!(message.contains("the limit resets at"), "{}", message);
+    }
+
+    #[test]
+    fn test_xrpc_unavailable_errors() {
+        let outcome = |error: &str| {
+            let body = format!(r#"{{"error":"{}","message":"details"}}"#, error);
+            XrpcError::parse(&body)
+                .unwrap()
+                .unavailable_error("@alice.test")
+                .map(|e| (e.error_code(), e.to_string()))
+        };
+
+        let unavailable = |reason: &str| {
+            Some(("account_unavailable", format!("Account unavailable: @alice.test {}", reason)))
+        };
+        let not_found = Some(("not_found", "Not found: @alice.test does not exist".to_string()));
+        let cases = [
+            ("BlockedActor", unavailable("is blocked by the viewing account")),
+            ("BlockedByActor", unavailable("has blocked the viewing account")),
+            ("AccountTakedown", unavailable("has been taken down by moderators")),
+            ("RepoTakendown", unavailable("has been taken down by moderators")),
+            ("AccountDeactivated", unavailable("has been deactivated by its owner")),
+            ("RepoDeactivated", unavailable("has been deactivated by its owner")),
+            ("RepoSuspended", unavailable("is suspended")),
+            ("ActorNotFound", not_found.clone()),
+            ("RepoNotFound", not_found.clone()),
+            ("UnknownFeed", not_found),
+            ("InvalidRequest", None),
+            ("ExpiredToken", None),
+        ];
+        for (error, expected) in cases {
+            let expected = expected.map(|(code, message)| (code, message.to_string()));
+            assert_eq!(outcome(error), expected, "{}", error);
+        }
+
+        // Bodies that are not XRPC errors
+        assert_eq!(XrpcError::parse("Bad Gateway"), None);
+        assert_eq!(XrpcError::parse(r#"{"message":"no error name"}"#), None);
+    }
+
+    #[test]
+    fn test_rate_limit_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("ratelimit-limit", "3000".parse().unwrap());
+        headers.insert("ratelimit-remaining", "42".parse().unwrap());
+        headers.insert("ratelimit-reset", "1700000000".parse().unwrap());
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(limit.limit, Some(3000));
+        assert_eq!(limit.remaining, Some(42));
+        assert_eq!(limit.reset, DateTime::from_timestamp(1_700_000_000, 0));
+        let now = DateTime::from_timestamp(1_699_999_990, 0).unwrap();
+        assert_eq!(limit.reset_after(now), Some(Duration::from_secs(10)));
+        let later = DateTime::from_timestamp(1_700_000_100, 0).unwrap();
+        assert_eq!(limit.reset_after(later), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_connection_errors() {
+        // A port nothing listens on any more refuses every connection
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = direct_client();
+        let attempts = AtomicUsize::new(0);
+        let result = send_with_retry(&fast_policy(3), || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            client.get(&url)
+        })
+        .await;
+        assert!(result.unwrap_err().is_connect());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            assert!(policy.backoff(30) <= Duration::from_millis(1000));
+        }
+    }
+
     #[test]
     fn test_client_with_timeout_creation() {
         let timeout = Duration::from_secs(30);
@@ -1,7 +1,105 @@
+//! autoreply: Bluesky profile, search and feed tools as a library
+//!
+//! The `autoreply` binary, both its MCP server and its CLI, is a thin wrapper over this
+//! crate. Each tool is an async function taking the same argument struct the CLI parses,
+//! the [`OutputFormat`] to render posts in and the [`RepositoryProvider`] to fetch with,
+//! and returning a [`ToolResult`], which renders as text or JSON. The provider carries
+//! every setting the tool uses: its [`CacheConfig`], [`RetryPolicy`] and repo size limit.
+//! Callers that draw search highlights themselves get their byte ranges from
+//! [`search_matches`] rather than parsing the bold markdown.
+//!
+//! Searching a downloaded repository CAR file, without touching the network:
+//!
+//! ```no_run
+//! use autoreply::{execute_search, CacheConfig, OutputFormat, RepositoryProvider, SearchArgs};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), autoreply::AppError> {
+//! // A repo CAR, e.g. one saved from com.atproto.sync.getRepo
+//! let args: SearchArgs = serde_json::from_value(serde_json::json!({
+//!     "car": ["alice.car"],
+//!     "query": "rust",
+//! }))?;
+//! let provider = RepositoryProvider::new(CacheConfig {
+//!     no_cache: true,
+//!     ..CacheConfig::default()
+//! })?;
+//! let result = execute_search(args, OutputFormat::Markdown, &provider).await?;
+//!
+//! println!("{}", result.render(OutputFormat::Markdown));
+//! # Ok(())
+//! # }
+//! ```
+
+// Without the `binary` feature nothing reaches the login flow or the MCP servers
+#![cfg_attr(not(feature = "binary"), allow(dead_code))]
+
+pub(crate) mod auth;
+pub(crate) mod bluesky;
+pub(crate) mod car;
+pub mod cli;
+pub(crate) mod config;
+pub mod error;
+pub(crate) mod http;
+pub(crate) mod mcp;
+pub(crate) mod mcp_http;
+pub(crate) mod resources;
+pub(crate) mod search;
+pub(crate) mod tools;
+
 #[cfg(feature = "experimental-sentencepiece")]
 pub mod sentencepiece;
 
 #[cfg(feature = "experimental-sentencepiece")]
 pub mod embeddings;
 
-pub mod search;
+#[cfg(not(feature = "experimental-sentencepiece"))]
+#[allow(dead_code)]
+mod sentencepiece_stub;
+
+#[cfg(test)]
+mod tests_mcp_adjustments;
+
+pub use bluesky::did::DidResolver;
+pub use bluesky::provider::{CacheConfig, RepositoryProvider};
+pub use bluesky::records::PostRecord;
+pub use cli::{FeedArgs, OutputFormat, ProfileArgs, SearchArgs};
+pub use error::AppError;
+pub use http::RetryPolicy;
+pub use mcp::ToolResult;
+pub use tools::feed::execute_feed;
+pub use tools::profile::execute_profile;
+pub use tools::search::{
+    execute_search, format_search_results, search_matches, search_matches_with_case, PostMatch,
+};
+
+/// Synthetic repository CAR files for tests and examples
+#[cfg(feature = "test-support")]
+pub use car::test_support;
+
+/// The remaining tools, the MCP servers and the process-wide settings they read, for the
+/// `autoreply` binary only. Not part of the library API: the setters are set-once for the
+/// whole process, and the module is only built with the `binary` feature.
+#[cfg(feature = "binary")]
+pub mod binary {
+    pub use crate::auth::login_flow::manage_accounts;
+    pub use crate::auth::session::init_refresh_buffer;
+    pub use crate::auth::{CredentialStorage, LoginManager, LoginRequest};
+    pub use crate::bluesky::blob_url::init_blob_host;
+    pub use crate::bluesky::provider::{configured_provider, init_cache_config, init_max_car_bytes};
+    pub use crate::config::init_config;
+    pub use crate::http::init_retry_policy;
+    pub use crate::mcp::handle_stdio;
+    pub use crate::mcp_http::{handle_http, DEFAULT_PORT};
+    pub use crate::tools::follow::execute_follow;
+    pub use crate::tools::graph::execute_graph;
+    pub use crate::tools::likes::execute_likes;
+    pub use crate::tools::list::execute_list;
+    pub use crate::tools::moderation::execute_moderation;
+    pub use crate::tools::notifications::execute_notifications;
+    pub use crate::tools::post::execute_post;
+    pub use crate::tools::react::execute_react;
+    pub use crate::tools::record::execute_record;
+    pub use crate::tools::thread::execute_thread;
+    pub use crate::tools::timeline::execute_timeline;
+}
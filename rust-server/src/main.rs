@@ -4,35 +4,14 @@
 //! - MCP Server Mode (default): Model Context Protocol server using stdio
 //! - CLI Mode: Command-line utility for direct tool execution
 //!
-//! Implements two tools:
-//! - `profile(account)` - Retrieve user profile information
-//! - `search(account, query)` - Search posts within a user's repository
-
-mod auth;
-mod bluesky;
-mod car;
-mod cli;
-mod error;
-mod http;
-mod mcp;
-mod search;
-
-#[cfg(feature = "experimental-sentencepiece")]
-mod sentencepiece;
-
-#[cfg(not(feature = "experimental-sentencepiece"))]
-#[allow(dead_code)]
-mod sentencepiece_stub;
-
-mod tools;
-
-#[cfg(test)]
-mod tests_mcp_adjustments;
+//! The tools themselves live in the `autoreply` library crate; this binary only parses
+//! arguments, configures logging and caching, and prints results.
 
 use anyhow::Result;
-use auth::{LoginManager, LoginRequest};
+use autoreply::binary::{self, CredentialStorage, LoginManager, LoginRequest};
+use autoreply::cli::{self, Cli, Commands, OutputFormat};
+use autoreply::error::AppError;
 use clap::Parser;
-use cli::{Cli, Commands};
 use tracing::info;
 
 #[tokio::main]
@@ -67,19 +46,63 @@ async fn run_cli_mode() -> Result<()> {
         .with_writer(std::io::stderr) // Log to stderr to keep stdout clean
         .init();
 
+    let config = match binary::init_config(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(get_exit_code(&anyhow::Error::from(e)));
+        }
+    };
+    let format = match cli.output_format(config) {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(get_exit_code(&anyhow::Error::from(e)));
+        }
+    };
+
+    if let Err(e) = binary::init_blob_host(cli.cdn_host.as_deref(), cli.image_size) {
+        eprintln!("Error: {}", e);
+        std::process::exit(get_exit_code(&anyhow::Error::from(e)));
+    }
+    binary::init_cache_config(
+        cli.cache_dir.as_deref(),
+        cli.no_cache,
+        cli.cache_ttl.map(std::time::Duration::from_secs),
+    );
+    binary::init_refresh_buffer(cli.refresh_buffer);
+    binary::init_retry_policy(cli.http_attempts);
+    binary::init_max_car_bytes(cli.max_repo_mb);
+
     // Execute command
     let result = match cli.command {
-        Some(Commands::Profile(args)) => execute_profile_cli(args).await,
-        Some(Commands::Search(args)) => execute_search_cli(args).await,
+        Some(Commands::Profile(args)) => execute_profile_cli(args, format).await,
+        Some(Commands::Search(args)) => execute_search_cli(args, format).await,
         Some(Commands::Login(args)) => execute_login_cli(args).await,
-        Some(Commands::Feed(args)) => execute_feed_cli(args).await,
-        Some(Commands::Thread(args)) => execute_thread_cli(args).await,
-        Some(Commands::Post(args)) => execute_post_cli(args).await,
-        Some(Commands::React(args)) => execute_react_cli(args).await,
-        None => {
-            eprintln!("Error: No command specified. Use --help for usage information.");
-            std::process::exit(1);
-        }
+        Some(Commands::Accounts(args)) => execute_accounts_cli(args),
+        Some(Commands::Feed(args)) => execute_feed_cli(args, format).await,
+        Some(Commands::Thread(args)) => execute_thread_cli(args, format).await,
+        Some(Commands::Post(args)) => execute_post_cli(args, format).await,
+        Some(Commands::React(args)) => execute_react_cli(args, format).await,
+        Some(Commands::Follow(args)) => execute_follow_cli(args, format).await,
+        Some(Commands::Notifications(args)) => execute_notifications_cli(args, format).await,
+        Some(Commands::Timeline(args)) => execute_timeline_cli(args, format).await,
+        Some(Commands::List(args)) => execute_list_cli(args, format).await,
+        Some(Commands::Likes(args)) => execute_likes_cli(args, format).await,
+        Some(Commands::Graph(args)) => execute_graph_cli(args, format).await,
+        Some(Commands::Moderation(args)) => execute_moderation_cli(args, format).await,
+        Some(Commands::Record(args)) => execute_record_cli(args, format).await,
+        Some(Commands::Completions(args)) => Ok(cli::completions(args.shell)),
+        None => match cli.transport {
+            Some(cli::Transport::Stdio) => return binary::handle_stdio().await,
+            Some(cli::Transport::Http) => {
+                return binary::handle_http(cli.port.unwrap_or(binary::DEFAULT_PORT)).await
+            }
+            None => {
+                eprintln!("Error: No command specified. Use --help for usage information.");
+                std::process::exit(1);
+            }
+        },
     };
 
     // Handle result and exit with appropriate code
@@ -96,50 +119,38 @@ async fn run_cli_mode() -> Result<()> {
 }
 
 /// Execute profile command in CLI mode
-async fn execute_profile_cli(args: cli::ProfileArgs) -> Result<String> {
+async fn execute_profile_cli(args: cli::ProfileArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
+    let provider = binary::configured_provider()?;
     let result = timeout(
         Duration::from_secs(120),
-        tools::profile::execute_profile(args),
+        autoreply::execute_profile(args, format, &provider),
     )
     .await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
 /// Execute search command in CLI mode
-async fn execute_search_cli(args: cli::SearchArgs) -> Result<String> {
+async fn execute_search_cli(args: cli::SearchArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
+    let provider = binary::configured_provider()?;
     let result = timeout(
         Duration::from_secs(120),
-        tools::search::execute_search(args),
+        autoreply::execute_search(args, format, &provider),
     )
     .await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
@@ -156,10 +167,7 @@ async fn execute_login_cli(args: cli::LoginCommand) -> Result<String> {
             interactive: true,
         };
 
-        let outcome = manager
-            .execute(request)
-            .await
-            .map_err(|e| anyhow::anyhow!(e.message()))?;
+        let outcome = manager.execute(request).await?;
 
         if let Some(elicitation) = outcome.elicitation {
             if !outcome.message.is_empty() {
@@ -197,110 +205,211 @@ async fn execute_login_cli(args: cli::LoginCommand) -> Result<String> {
             continue;
         }
 
+        // Device flow: show the code now, then wait until it is approved
+        if let Some(pending) = outcome.pending {
+            eprintln!("{}", outcome.message);
+            return pending
+                .await
+                .map_err(|e| anyhow::anyhow!("Device login task failed: {}", e))?
+                .map_err(anyhow::Error::from);
+        }
+
         return Ok(outcome.message);
     }
 }
 
 /// Execute feed command in CLI mode
-async fn execute_feed_cli(args: cli::FeedArgs) -> Result<String> {
+async fn execute_feed_cli(args: cli::FeedArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
-    let result = timeout(Duration::from_secs(120), tools::feed::execute_feed(args)).await;
+    let provider = binary::configured_provider()?;
+    let result = timeout(
+        Duration::from_secs(120),
+        autoreply::execute_feed(args, format, &provider),
+    )
+    .await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
 /// Execute thread command in CLI mode
-async fn execute_thread_cli(args: cli::ThreadArgs) -> Result<String> {
+async fn execute_thread_cli(args: cli::ThreadArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
     let result = timeout(
         Duration::from_secs(120),
-        tools::thread::execute_thread(args),
+        binary::execute_thread(args, format),
     )
     .await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
 /// Execute post command in CLI mode
-async fn execute_post_cli(args: cli::PostArgs) -> Result<String> {
+async fn execute_post_cli(args: cli::PostArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
-    let result = timeout(Duration::from_secs(120), tools::post::execute_post(args)).await;
+    let result = timeout(Duration::from_secs(120), binary::execute_post(args)).await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
 /// Execute react command in CLI mode
-async fn execute_react_cli(args: cli::ReactArgs) -> Result<String> {
+async fn execute_react_cli(args: cli::ReactArgs, format: OutputFormat) -> Result<String> {
     use tokio::time::{timeout, Duration};
 
-    let result = timeout(Duration::from_secs(120), tools::react::execute_react(args)).await;
+    let result = timeout(Duration::from_secs(120), binary::execute_react(args)).await;
 
     match result {
-        Ok(Ok(tool_result)) => {
-            // Extract markdown text from ToolResult
-            Ok(tool_result
-                .content
-                .first()
-                .map(|c| c.text.clone())
-                .unwrap_or_default())
-        }
-        Ok(Err(e)) => Err(anyhow::anyhow!(e.message())),
-        Err(_) => Err(anyhow::anyhow!("Request exceeded 120 second timeout")),
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
-/// Map AppError to exit code
-fn get_exit_code(err: &anyhow::Error) -> i32 {
-    let err_str = err.to_string().to_lowercase();
-    if err_str.contains("invalid") || err_str.contains("usage") {
-        1 // Invalid arguments or usage error
-    } else if err_str.contains("network") || err_str.contains("connection") {
-        2 // Network or API error
-    } else if err_str.contains("not found") {
-        3 // Not found error
-    } else if err_str.contains("timeout") {
-        4 // Timeout error
-    } else {
-        5 // Other application errors
+/// Execute follow command in CLI mode
+async fn execute_follow_cli(args: cli::FollowArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_follow(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute notifications command in CLI mode
+async fn execute_notifications_cli(
+    args: cli::NotificationsArgs,
+    format: OutputFormat,
+) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(
+        Duration::from_secs(120),
+        binary::execute_notifications(args, format),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute timeline command in CLI mode
+async fn execute_timeline_cli(args: cli::TimelineArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(
+        Duration::from_secs(120),
+        binary::execute_timeline(args, format),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute list command in CLI mode
+async fn execute_list_cli(args: cli::ListArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_list(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
     }
 }
 
+/// Execute likes command in CLI mode
+async fn execute_likes_cli(args: cli::LikesArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_likes(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute graph command in CLI mode
+async fn execute_graph_cli(args: cli::GraphArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_graph(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute moderation command in CLI mode
+async fn execute_moderation_cli(args: cli::ModerationArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_moderation(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+/// Execute record command in CLI mode
+async fn execute_record_cli(args: cli::RecordArgs, format: OutputFormat) -> Result<String> {
+    use tokio::time::{timeout, Duration};
+
+    let result = timeout(Duration::from_secs(120), binary::execute_record(args)).await;
+
+    match result {
+        Ok(Ok(tool_result)) => Ok(tool_result.render(format)),
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => Err(cli_timeout_error()),
+    }
+}
+
+fn execute_accounts_cli(args: cli::AccountsCommand) -> Result<String> {
+    let storage = CredentialStorage::new()?;
+    binary::manage_accounts(&storage, &args.command).map_err(anyhow::Error::from)
+}
+
+/// Error for a CLI command that ran past the 120 second limit
+fn cli_timeout_error() -> anyhow::Error {
+    AppError::Timeout("Request exceeded 120 second timeout".to_string()).into()
+}
+
+/// Exit code for a failed command: the `AppError`'s own code, 5 for any other error
+fn get_exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<AppError>()
+        .map_or(5, AppError::exit_code)
+}
+
 /// Run in MCP server mode
 async fn run_mcp_mode() -> Result<()> {
     // Initialize logging
@@ -308,8 +417,12 @@ async fn run_mcp_mode() -> Result<()> {
 
     info!("Starting autoreply MCP Server");
 
+    binary::init_config(None)?;
+    binary::init_blob_host(None, None)?;
+    binary::init_cache_config(None, false, None);
+
     // Handle stdio MCP communication
-    mcp::handle_stdio().await?;
+    binary::handle_stdio().await?;
 
     Ok(())
 }
@@ -6,42 +6,86 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
-use tokio::sync::{mpsc, Mutex};
-use tracing::{debug, error, info};
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader as AsyncBufReader,
+};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{debug, error, info, warn};
 
 #[cfg(test)]
 type ElicitationHook =
     Arc<dyn Fn(String, Value) -> anyhow::Result<ElicitationResponse> + Send + Sync>;
 
+/// Newest supported protocol version, offered when the client's is absent or unsupported
+pub const LATEST_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// MCP protocol versions this server speaks, oldest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &[LATEST_PROTOCOL_VERSION];
+
+/// Pick the protocol version to answer `initialize` with: the client's if we support it,
+/// otherwise our latest (the client may then disconnect if it cannot use that)
+pub fn negotiate_protocol_version(requested: Option<&str>) -> &'static str {
+    requested
+        .and_then(|v| SUPPORTED_PROTOCOL_VERSIONS.iter().find(|s| **s == v))
+        .copied()
+        .unwrap_or(LATEST_PROTOCOL_VERSION)
+}
+
 /// Server context for tracking client information and bidirectional RPC
 #[derive(Clone)]
 pub struct ServerContext {
     pub client_info: Option<ClientInfo>,
     pub client_capabilities: Option<ClientCapabilities>,
+    /// Protocol version agreed in `initialize` (None before initialization)
+    pub protocol_version: Option<&'static str>,
     pub rpc_sender: Option<Arc<RpcSender>>,
     #[cfg(test)]
     pub test_elicitation_hook: Option<ElicitationHook>,
 }
 
-/// RPC sender for server-to-client requests
+/// Stream the server writes its JSON-RPC messages to (stdout outside of tests)
+type RpcWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// RPC sender for server-to-client requests and notifications
 pub struct RpcSender {
     next_id: AtomicI64,
-    stdout: Arc<Mutex<tokio::io::Stdout>>,
+    writer: Arc<Mutex<RpcWriter>>,
     pending_responses: Arc<Mutex<HashMap<i64, mpsc::Sender<McpResponse>>>>,
 }
 
 impl RpcSender {
-    pub fn new(stdout: tokio::io::Stdout) -> Self {
+    pub fn new(writer: impl AsyncWrite + Send + Unpin + 'static) -> Self {
         Self {
             next_id: AtomicI64::new(1),
-            stdout: Arc::new(Mutex::new(stdout)),
+            writer: Arc::new(Mutex::new(Box::new(writer))),
             pending_responses: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Write one newline-delimited JSON-RPC message
+    async fn write_message(&self, json: &str) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.write_all(json.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Send a JSON-RPC notification; no response is expected
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        });
+        let notification_json = serde_json::to_string(&notification)?;
+        debug!("Sending notification: {}", notification_json);
+        self.write_message(&notification_json).await
+    }
+
     /// Send an elicitation/create request and wait for response
     pub async fn request_elicitation(
         &self,
@@ -76,12 +120,7 @@ impl RpcSender {
             request_id, request_json
         );
 
-        {
-            let mut stdout = self.stdout.lock().await;
-            stdout.write_all(request_json.as_bytes()).await?;
-            stdout.write_all(b"\n").await?;
-            stdout.flush().await?;
-        }
+        self.write_message(&request_json).await?;
 
         // Wait for response
         let response = rx
@@ -124,11 +163,62 @@ impl RpcSender {
     }
 }
 
+/// Progress reporter for one tool call whose request carried a `progressToken`
+///
+/// Reports are queued and written in order by a background task, so synchronous code such
+/// as record iterators can report without awaiting. `progress` counts the reports made.
+/// Clones report into the same sequence, e.g. from a blocking thread.
+#[derive(Clone)]
+pub struct Progress {
+    token: Value,
+    step: Arc<AtomicU64>,
+    queue: mpsc::UnboundedSender<Value>,
+}
+
+impl Progress {
+    /// Start reporting progress for `token` through `sender`
+    pub fn start(sender: Arc<RpcSender>, token: Value) -> Self {
+        let (queue, mut reports) = mpsc::unbounded_channel::<Value>();
+        tokio::spawn(async move {
+            while let Some(params) = reports.recv().await {
+                if let Err(e) = sender.notify("notifications/progress", params).await {
+                    warn!("Failed to send progress notification: {}", e);
+                }
+            }
+        });
+        Self {
+            token,
+            step: Arc::new(AtomicU64::new(0)),
+            queue,
+        }
+    }
+
+    /// Queue a `notifications/progress` message describing the step just completed
+    pub fn report(&self, message: impl Into<String>) {
+        let progress = self.step.fetch_add(1, Ordering::SeqCst) + 1;
+        let params = serde_json::json!({
+            "progressToken": self.token,
+            "progress": progress,
+            "message": message.into()
+        });
+        // The writer task only ends with the process, so a failed send has nowhere to go
+        let _ = self.queue.send(params);
+    }
+}
+
+/// Report a step if the caller asked for progress; the message is only built when needed
+pub fn report_progress(progress: Option<&Progress>, message: impl FnOnce() -> String) {
+    if let Some(progress) = progress {
+        progress.report(message());
+    }
+}
+
 impl ServerContext {
     pub fn new(rpc_sender: Option<Arc<RpcSender>>) -> Self {
         Self {
             client_info: None,
             client_capabilities: None,
+            protocol_version: None,
             rpc_sender,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -142,6 +232,14 @@ impl ServerContext {
             .is_some()
     }
 
+    /// A progress reporter for a tool call, if the client asked for progress by sending a
+    /// `progressToken` and this transport can send notifications
+    pub fn progress_for(&self, meta: Option<&RequestMeta>) -> Option<Progress> {
+        let token = meta?.progress_token.clone()?;
+        let sender = self.rpc_sender.clone()?;
+        Some(Progress::start(sender, token))
+    }
+
     pub fn get_client_name(&self) -> String {
         self.client_info
             .as_ref()
@@ -267,6 +365,15 @@ pub struct McpError {
 pub struct ToolCallArgs {
     pub name: String,
     pub arguments: Value,
+    #[serde(default, rename = "_meta")]
+    pub meta: Option<RequestMeta>,
+}
+
+/// Request `_meta`: a `progressToken` asks for progress notifications about this call
+#[derive(Debug, Deserialize)]
+pub struct RequestMeta {
+    #[serde(default, rename = "progressToken")]
+    pub progress_token: Option<Value>,
 }
 
 /// MCP Content item
@@ -284,6 +391,31 @@ pub struct ToolResult {
     pub content: Vec<ContentItem>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "isError")]
     pub is_error: Option<bool>,
+    /// Structured data for clients alongside the markdown, such as pagination cursors
+    #[serde(skip_serializing_if = "Option::is_none", rename = "_meta")]
+    pub meta: Option<Value>,
+    /// Structured form of the result for `--format json`; MCP clients get the markdown only
+    #[serde(skip)]
+    pub data: Option<Box<dyn ToolData>>,
+}
+
+/// The structured data behind a tool's text, serialized only when JSON output is asked for
+pub trait ToolData: std::fmt::Debug + Send + Sync {
+    /// The data as pretty-printed JSON
+    fn to_json(&self) -> serde_json::Result<String>;
+
+    /// The data as `Any`, for tools that amend their own result after building it
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
+}
+
+impl<T: Serialize + std::fmt::Debug + Send + Sync + 'static> ToolData for T {
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
 }
 
 impl McpResponse {
@@ -334,15 +466,18 @@ impl ToolResult {
         Self {
             content: vec![ContentItem::text(content)],
             is_error: None,
+            meta: None,
+            data: None,
         }
     }
 
     /// Create a result from explicit content items
-    #[allow(dead_code)]
     pub fn from_items(content: Vec<ContentItem>) -> Self {
         Self {
             content,
             is_error: None,
+            meta: None,
+            data: None,
         }
     }
 
@@ -351,6 +486,51 @@ impl ToolResult {
         self.is_error = Some(true);
         self
     }
+
+    /// Attach structured `_meta` data to this result
+    pub fn with_meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Attach the structured data behind the markdown, for machine-readable output
+    pub fn with_data(mut self, data: impl ToolData + 'static) -> Self {
+        self.data = Some(Box::new(data));
+        self
+    }
+
+    /// The attached data, if it is a `T`
+    pub fn data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.data.as_mut()?.as_any_mut().downcast_mut()
+    }
+
+    /// The attached data as a JSON value, for tests to inspect
+    #[cfg(test)]
+    pub fn data_json(&self) -> Option<Value> {
+        let json = self.data.as_ref()?.to_json().ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// The result as CLI output: its text, already rendered in the format the tool was
+    /// given, or the structured data as JSON. Tools without structured data are wrapped as
+    /// `{"markdown": ...}` in JSON mode.
+    pub fn render(&self, format: crate::cli::OutputFormat) -> String {
+        let text = self.content.first().map(|c| c.text.as_str()).unwrap_or_default();
+        match format {
+            crate::cli::OutputFormat::Markdown | crate::cli::OutputFormat::Plain => {
+                text.to_string()
+            }
+            crate::cli::OutputFormat::Json => {
+                match self.data.as_ref().map(|data| data.to_json()) {
+                    Some(Ok(json)) => return json,
+                    Some(Err(e)) => warn!("Failed to serialize structured tool data: {}", e),
+                    None => {}
+                }
+                let wrapped = serde_json::json!({ "markdown": text });
+                serde_json::to_string_pretty(&wrapped).unwrap_or_else(|_| wrapped.to_string())
+            }
+        }
+    }
 }
 
 impl ContentItem {
@@ -388,16 +568,24 @@ pub fn serialize_response(response: &McpResponse) -> Result<String> {
 /// Handle stdio MCP communication
 pub async fn handle_stdio() -> Result<()> {
     info!("Starting autoreply MCP server on stdio");
+    serve_lines(tokio::io::stdin(), tokio::io::stdout()).await
+}
 
-    let stdin = tokio::io::stdin();
-    let mut reader = AsyncBufReader::new(stdin).lines();
-    let stdout = tokio::io::stdout();
+/// Serve newline-delimited JSON-RPC from `input` to `output` until `input` ends, then
+/// wait for the requests still running so their responses are written too
+async fn serve_lines(
+    input: impl AsyncRead + Unpin,
+    output: impl AsyncWrite + Send + Unpin + 'static,
+) -> Result<()> {
+    let mut reader = AsyncBufReader::new(input).lines();
 
     // Create RPC sender for bidirectional communication
-    let rpc_sender = Arc::new(RpcSender::new(stdout));
+    let rpc_sender = Arc::new(RpcSender::new(output));
 
     // Track server context with RPC sender
     let mut context = ServerContext::new(Some(rpc_sender.clone()));
+    let in_flight = InFlightRequests::default();
+    let mut running = Vec::new();
 
     while let Some(line) = reader.next_line().await? {
         debug!("Received message: {}", line);
@@ -425,42 +613,150 @@ pub async fn handle_stdio() -> Result<()> {
                     McpResponse::error(None, "parse_error", &format!("Invalid JSON: {}", e));
                 let response_json = serialize_response(&response)?;
                 debug!("Sending response: {}", response_json);
-                let mut stdout = rpc_sender.stdout.lock().await;
-                stdout.write_all(response_json.as_bytes()).await?;
-                stdout.write_all(b"\n").await?;
-                stdout.flush().await?;
+                rpc_sender.write_message(&response_json).await?;
                 continue;
             }
         };
 
         // Do not respond to JSON-RPC notifications (no id)
-        if parsed.id.is_none() {
-            // Known notification: "notifications/initialized"; silently ignore per spec
+        let Some(id) = parsed.id.clone() else {
+            handle_notification(&parsed, &in_flight);
+            continue;
+        };
+
+        // Initialization updates the shared context, so it is answered before reading on
+        if parsed.method == "initialize" {
+            let response = handle_request(parsed, &mut context).await;
+            let response_json = serialize_response(&response)?;
+            debug!("Sending response: {}", response_json);
+            rpc_sender.write_message(&response_json).await?;
             continue;
         }
 
-        let response = handle_request(parsed, &mut context).await;
+        // Everything else runs in the background so responses to our own requests
+        // (elicitation) and cancellations keep being read while a tool works
+        let mut request_context = context.clone();
+        let work = async move { handle_request(parsed, &mut request_context).await };
+        running.retain(|task: &tokio::task::JoinHandle<()>| !task.is_finished());
+        running.push(in_flight.spawn(id, work, rpc_sender.clone()));
+    }
 
-        let response_json = serialize_response(&response)?;
-        debug!("Sending response: {}", response_json);
+    for task in running {
+        if let Err(e) = task.await {
+            error!("Request task failed: {}", e);
+        }
+    }
+    Ok(())
+}
 
-        {
-            let mut stdout = rpc_sender.stdout.lock().await;
-            stdout.write_all(response_json.as_bytes()).await?;
-            stdout.write_all(b"\n").await?;
-            stdout.flush().await?;
+/// Act on a JSON-RPC notification from the client; both transports hand theirs here
+pub(crate) fn handle_notification(notification: &McpRequest, in_flight: &InFlightRequests) {
+    match notification.method.as_str() {
+        "notifications/cancelled" => {
+            let request_id = notification.params.as_ref().and_then(|p| p.get("requestId"));
+            if let Some(request_id) = request_id {
+                if !in_flight.cancel(request_id) {
+                    debug!("Ignoring cancellation of unknown request {}", request_id);
+                }
+            }
+        }
+        "notifications/initialized" => debug!("Client finished initialization"),
+        // Others, such as progress for requests this server never makes, are ignored per spec
+        method => debug!("Ignoring notification {}", method),
+    }
+}
+
+/// Requests being handled, keyed by JSON-RPC id, so `notifications/cancelled` can stop them
+#[derive(Clone, Default)]
+pub(crate) struct InFlightRequests {
+    cancels: Arc<std::sync::Mutex<HashMap<String, oneshot::Sender<()>>>>,
+}
+
+impl InFlightRequests {
+    fn cancels(&self) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<()>>> {
+        self.cancels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register request `id` as cancellable now, and run `work` to its response, or to
+    /// `None` if the request is cancelled first; cancelling drops the work future
+    pub(crate) fn track(
+        &self,
+        id: &Value,
+        work: impl Future<Output = McpResponse>,
+    ) -> impl Future<Output = Option<McpResponse>> {
+        let key = id.to_string();
+        let (cancel, cancelled) = oneshot::channel();
+        self.cancels().insert(key.clone(), cancel);
+
+        let in_flight = self.clone();
+        async move {
+            let response = tokio::select! {
+                response = work => Some(response),
+                _ = cancelled => None,
+            };
+            in_flight.cancels().remove(&key);
+            if response.is_none() {
+                info!("Request {} cancelled by the client", key);
+            }
+            response
         }
     }
 
-    Ok(())
+    /// Run a request in the background and send its response, unless it is cancelled first;
+    /// then nothing is sent
+    fn spawn(
+        &self,
+        id: Value,
+        work: impl Future<Output = McpResponse> + Send + 'static,
+        rpc_sender: Arc<RpcSender>,
+    ) -> tokio::task::JoinHandle<()> {
+        let key = id.to_string();
+        let tracked = self.track(&id, work);
+        tokio::spawn(async move {
+            let Some(response) = tracked.await else {
+                return;
+            };
+            let sent = match serialize_response(&response) {
+                Ok(response_json) => {
+                    debug!("Sending response: {}", response_json);
+                    rpc_sender.write_message(&response_json).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                error!("Failed to send response to request {}: {}", key, e);
+            }
+        })
+    }
+
+    /// Whether no request is running
+    pub(crate) fn is_empty(&self) -> bool {
+        self.cancels().is_empty()
+    }
+
+    /// Cancel an in-flight request; returns false if no request with that id is running
+    fn cancel(&self, id: &Value) -> bool {
+        match self.cancels().remove(&id.to_string()) {
+            Some(cancel) => {
+                let _ = cancel.send(());
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 /// Handle a single MCP request
-async fn handle_request(request: McpRequest, context: &mut ServerContext) -> McpResponse {
+pub(crate) async fn handle_request(
+    request: McpRequest,
+    context: &mut ServerContext,
+) -> McpResponse {
     match request.method.as_str() {
         "initialize" => handle_initialize(request, context).await,
         "tools/call" => handle_tool_call(request, context).await,
         "tools/list" => handle_tools_list(request).await,
+        "resources/list" => crate::resources::handle_resources_list(request).await,
+        "resources/read" => crate::resources::handle_resources_read(request).await,
         _ => McpResponse::error(
             request.id,
             "method_not_found",
@@ -482,22 +778,93 @@ async fn handle_tool_call(request: McpRequest, context: &ServerContext) -> McpRe
         }
     };
 
-    match args.name.as_str() {
+    // Deprecated names are still dispatched during their grace period
+    let replacement = deprecated_tool_replacement(&args.name);
+    if let Some(new_name) = replacement {
+        warn!(
+            "Tool '{}' is deprecated, dispatching to '{}' instead",
+            args.name, new_name
+        );
+    }
+    let tool_name = replacement.unwrap_or(args.name.as_str());
+    let progress = context.progress_for(args.meta.as_ref());
+
+    let response = match tool_name {
         "profile" => crate::tools::profile::handle_profile(request.id, args.arguments).await,
-        "search" => crate::tools::search::handle_search(request.id, args.arguments).await,
+        "search" => {
+            crate::tools::search::handle_search(request.id, args.arguments, progress.as_ref()).await
+        }
         "login" => crate::tools::login::handle_login(request.id, args.arguments, context).await,
-        "feed" => crate::tools::feed::handle_feed(request.id, args.arguments).await,
+        "feed" => {
+            crate::tools::feed::handle_feed(request.id, args.arguments, progress.as_ref()).await
+        }
         "thread" => crate::tools::thread::handle_thread(request.id, args.arguments).await,
         "post" => crate::tools::post::handle_post(request.id, args.arguments).await,
         "react" => crate::tools::react::handle_react(request.id, args.arguments).await,
+        "follow" => crate::tools::follow::handle_follow(request.id, args.arguments).await,
+        "notifications" => {
+            crate::tools::notifications::handle_notifications(request.id, args.arguments).await
+        }
+        "timeline" => crate::tools::timeline::handle_timeline(request.id, args.arguments).await,
+        "list" => crate::tools::list::handle_list(request.id, args.arguments).await,
+        "likes" => crate::tools::likes::handle_likes(request.id, args.arguments).await,
+        "graph" => crate::tools::graph::handle_graph(request.id, args.arguments).await,
+        "moderation" => {
+            crate::tools::moderation::handle_moderation(request.id, args.arguments).await
+        }
+        "record" => crate::tools::record::handle_record(request.id, args.arguments).await,
         _ => McpResponse::error(
             request.id,
             "tool_not_found",
             &format!("Tool '{}' not found", args.name),
         ),
+    };
+
+    match replacement {
+        Some(new_name) => attach_deprecation_note(response, &args.name, new_name),
+        None => response,
     }
 }
 
+/// Deprecated tool names mapped to their replacements: `(old, new)`.
+/// Add an entry here when a tool is renamed; remove it when the grace period ends.
+const DEPRECATED_TOOL_NAMES: &[(&str, &str)] = &[];
+
+/// Look up the replacement for a deprecated tool name
+fn deprecated_tool_replacement(name: &str) -> Option<&'static str> {
+    replacement_in(DEPRECATED_TOOL_NAMES, name)
+}
+
+/// Look up `name` in an `(old, new)` rename registry
+fn replacement_in(registry: &[(&'static str, &'static str)], name: &str) -> Option<&'static str> {
+    registry
+        .iter()
+        .find(|(old, _)| *old == name)
+        .map(|(_, new)| *new)
+}
+
+/// Append a deprecation notice as an extra trailing content item so that
+/// clients reading only the first item see unchanged output
+fn attach_deprecation_note(mut response: McpResponse, old: &str, new: &str) -> McpResponse {
+    let content = response
+        .result
+        .as_mut()
+        .and_then(|r| r.get_mut("content"))
+        .and_then(|c| c.as_array_mut());
+
+    if let Some(content) = content {
+        let note = ContentItem::text(format!(
+            "⚠️ Tool '{}' is deprecated and will be removed; use '{}' instead.",
+            old, new
+        ));
+        if let Ok(note) = serde_json::to_value(note) {
+            content.push(note);
+        }
+    }
+
+    response
+}
+
 /// Handle tools/list method
 async fn handle_tools_list(request: McpRequest) -> McpResponse {
     let tools = build_tools_array();
@@ -507,8 +874,16 @@ async fn handle_tools_list(request: McpRequest) -> McpResponse {
 
 /// Handle initialize method
 async fn handle_initialize(request: McpRequest, context: &mut ServerContext) -> McpResponse {
+    let mut requested_version = None;
+
     // Parse initialize params
     if let Some(params) = request.params {
+        // Read the version on its own so malformed client info cannot hide it
+        requested_version = params
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
         if let Ok(init_params) = serde_json::from_value::<InitializeParams>(params) {
             // Store client info and capabilities
             context.client_info = init_params.client_info;
@@ -522,15 +897,25 @@ async fn handle_initialize(request: McpRequest, context: &mut ServerContext) ->
         }
     }
 
+    let protocol_version = negotiate_protocol_version(requested_version.as_deref());
+    if requested_version.as_deref() != Some(protocol_version) {
+        info!(
+            "Client requested protocol version {:?}, offering {}",
+            requested_version, protocol_version
+        );
+    }
+    context.protocol_version = Some(protocol_version);
+
     let tools = build_tools_array();
     let result = serde_json::json!({
-        "protocolVersion": "2024-11-05",
+        "protocolVersion": protocol_version,
         "serverInfo": {
             "name": "autoreply",
             "version": env!("CARGO_PKG_VERSION"),
         },
         "capabilities": {
-            "tools": { "list": true, "call": true }
+            "tools": { "list": true, "call": true },
+            "resources": {}
         },
         "tools": tools
     });
@@ -540,7 +925,9 @@ async fn handle_initialize(request: McpRequest, context: &mut ServerContext) ->
 /// Build the tools array returned from tools/list and initialize
 pub(crate) fn build_tools_array() -> serde_json::Value {
     use crate::cli::{
-        FeedArgs, LoginCommand, PostArgs, ProfileArgs, ReactArgs, SearchArgs, ThreadArgs,
+        FeedArgs, FollowArgs, GraphArgs, LikesArgs, ListArgs, LoginCommand, ModerationArgs,
+        NotificationsArgs, PostArgs, ProfileArgs, ReactArgs, RecordArgs, SearchArgs, ThreadArgs,
+        TimelineArgs,
     };
     use schemars::schema_for;
 
@@ -552,6 +939,14 @@ pub(crate) fn build_tools_array() -> serde_json::Value {
     let thread_schema = schema_for!(ThreadArgs);
     let post_schema = schema_for!(PostArgs);
     let react_schema = schema_for!(ReactArgs);
+    let follow_schema = schema_for!(FollowArgs);
+    let notifications_schema = schema_for!(NotificationsArgs);
+    let timeline_schema = schema_for!(TimelineArgs);
+    let list_schema = schema_for!(ListArgs);
+    let likes_schema = schema_for!(LikesArgs);
+    let graph_schema = schema_for!(GraphArgs);
+    let moderation_schema = schema_for!(ModerationArgs);
+    let record_schema = schema_for!(RecordArgs);
 
     serde_json::json!([
         {
@@ -561,7 +956,7 @@ pub(crate) fn build_tools_array() -> serde_json::Value {
         },
         {
             "name": "search",
-            "description": "Search posts within a user's repository",
+            "description": "Search posts within a user's repository, or across the network with scope global",
             "inputSchema": search_schema
         },
         {
@@ -571,7 +966,7 @@ pub(crate) fn build_tools_array() -> serde_json::Value {
         },
         {
             "name": "feed",
-            "description": "Get the latest feed from BlueSky. Returns a list of posts from a feed. If you want to see the latest posts from a specific feed, provide the feed URI or name. These feeds are paginated.",
+            "description": "Get the latest feed from BlueSky. Returns a list of posts from a feed. If you want to see the latest posts from a specific feed, provide the feed URI or name, or an author to list one account's recent posts. These feeds are paginated.",
             "inputSchema": feed_schema
         },
         {
@@ -581,13 +976,53 @@ pub(crate) fn build_tools_array() -> serde_json::Value {
         },
         {
             "name": "post",
-            "description": "Create a new post or reply on BlueSky. Supports text content and replying to existing posts via at:// URI or https://bsky.app/... URL.",
+            "description": "Create a new post, reply or quote post on BlueSky. Supports text content, replying to and quoting existing posts via at:// URI or https://bsky.app/... URL.",
             "inputSchema": post_schema
         },
         {
             "name": "react",
-            "description": "Perform batch reactions on BlueSky posts (like, unlike, repost, delete). All operations support both at:// URIs and https://bsky.app/... URLs. Partial success is allowed - some operations may succeed while others fail.",
+            "description": "Perform batch reactions on BlueSky posts (like, unlike, repost, unrepost, delete). All operations support both at:// URIs and https://bsky.app/... URLs. Partial success is allowed - some operations may succeed while others fail.",
             "inputSchema": react_schema
+        },
+        {
+            "name": "follow",
+            "description": "Follow or unfollow a BlueSky account. Following an account that is already followed reports the existing follow record instead of adding a duplicate.",
+            "inputSchema": follow_schema
+        },
+        {
+            "name": "notifications",
+            "description": "List recent notifications for an authenticated account, grouped into replies, mentions, quotes, reposts, likes and follows. Use unreadOnly to show only unseen notifications. These notifications are paginated.",
+            "inputSchema": notifications_schema
+        },
+        {
+            "name": "timeline",
+            "description": "Get the home timeline of an authenticated account: posts and reposts from the accounts it follows. The timeline is paginated; the next cursor is returned in the result _meta.",
+            "inputSchema": timeline_schema
+        },
+        {
+            "name": "list",
+            "description": "Show the lists (curation and moderation lists, with their members) and custom feeds an account has created, read from its repository. Name one list to see all its members.",
+            "inputSchema": list_schema
+        },
+        {
+            "name": "likes",
+            "description": "List the accounts that liked a post, by handle and display name. The list is paginated; the next cursor is returned in the result _meta.",
+            "inputSchema": likes_schema
+        },
+        {
+            "name": "graph",
+            "description": "List an account's followers, or the accounts it follows, with handle, display name and DID. The list is paginated; the next cursor is returned in the result _meta.",
+            "inputSchema": graph_schema
+        },
+        {
+            "name": "moderation",
+            "description": "Mute, unmute, block or unblock an account. Muting is private and only hides their posts from you; blocking publishes a block record and stops all interaction. Requires login.",
+            "inputSchema": moderation_schema
+        },
+        {
+            "name": "record",
+            "description": "Show one record of any collection (post, like, profile, list item...) by its at:// URI as pretty-printed JSON, read from its owner's repository.",
+            "inputSchema": record_schema
         }
     ])
 }
@@ -629,6 +1064,50 @@ mod tests {
             Some(true)
         );
         assert!(result.get("tools").and_then(|v| v.as_array()).is_some());
+        assert_eq!(context.protocol_version, Some("2024-11-05"));
+    }
+
+    #[test]
+    fn test_deprecated_tool_registry() {
+        let registry = [("like", "react")];
+        assert_eq!(replacement_in(&registry, "like"), Some("react"));
+        assert_eq!(replacement_in(&registry, "react"), None);
+        assert_eq!(deprecated_tool_replacement("search"), None);
+        assert_eq!(deprecated_tool_replacement("no_such_tool"), None);
+    }
+
+    #[test]
+    fn test_attach_deprecation_note_appends_content() {
+        let tool_result = serde_json::to_value(ToolResult::text("real output")).unwrap();
+        let resp = McpResponse::success(Some(json!(1)), tool_result);
+        let resp = attach_deprecation_note(resp, "like", "react");
+        let content = resp.result.unwrap()["content"].as_array().unwrap().clone();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"], "real output");
+        assert!(content[1]["text"].as_str().unwrap().contains("use 'react'"));
+    }
+
+    #[test]
+    fn test_attach_deprecation_note_leaves_errors_untouched() {
+        let resp = McpResponse::error(Some(json!(1)), "invalid_params", "bad args");
+        let resp = attach_deprecation_note(resp, "like", "react");
+        assert!(resp.result.is_none());
+        assert_eq!(resp.error.unwrap().message, "bad args");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_still_not_found() {
+        let req = McpRequest {
+            jsonrpc: "2.0".into(),
+            id: Some(json!(3)),
+            method: "tools/call".into(),
+            params: Some(json!({ "name": "no_such_tool", "arguments": {} })),
+        };
+        let mut context = ServerContext::new(None);
+        let resp = handle_request(req, &mut context).await;
+        let err = resp.error.expect("error expected");
+        assert_eq!(err.code, -32001);
+        assert!(err.message.contains("no_such_tool"));
     }
 
     #[tokio::test]
@@ -661,6 +1140,49 @@ mod tests {
         assert!(names.contains(&"thread".to_string()));
     }
 
+    async fn initialize_with(params: Option<Value>) -> (Option<String>, ServerContext) {
+        let req = McpRequest {
+            jsonrpc: "2.0".into(),
+            id: Some(json!(1)),
+            method: "initialize".into(),
+            params,
+        };
+        let mut context = ServerContext::new(None);
+        let resp = handle_request(req, &mut context).await;
+        let version = resp
+            .result
+            .and_then(|r| r.get("protocolVersion").and_then(|v| v.as_str()).map(str::to_string));
+        (version, context)
+    }
+
+    #[tokio::test]
+    async fn test_initialize_echoes_supported_protocol_version() {
+        let (version, context) =
+            initialize_with(Some(json!({ "protocolVersion": "2024-11-05" }))).await;
+        assert_eq!(version.as_deref(), Some("2024-11-05"));
+        assert_eq!(context.protocol_version, Some("2024-11-05"));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_offers_latest_for_unsupported_or_absent_version() {
+        for params in [
+            Some(json!({ "protocolVersion": "2099-01-01" })),
+            Some(json!({ "clientInfo": { "name": "c" } })),
+            Some(json!({ "protocolVersion": 7 })),
+            Some(json!({ "protocolVersion": "2025-03-26", "clientInfo": "not an object" })),
+            None,
+        ] {
+            let (version, context) = initialize_with(params.clone()).await;
+            assert_eq!(
+                version.as_deref(),
+                Some(LATEST_PROTOCOL_VERSION),
+                "{:?}",
+                params
+            );
+            assert_eq!(context.protocol_version, Some(LATEST_PROTOCOL_VERSION));
+        }
+    }
+
     #[tokio::test]
     async fn test_server_context_supports_elicitation() {
         // Test with elicitation support
@@ -669,6 +1191,7 @@ mod tests {
             client_capabilities: Some(ClientCapabilities {
                 elicitation: Some(ElicitationCapability {}),
             }),
+            protocol_version: None,
             rpc_sender: None,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -679,6 +1202,7 @@ mod tests {
         let context_without = ServerContext {
             client_info: None,
             client_capabilities: Some(ClientCapabilities { elicitation: None }),
+            protocol_version: None,
             rpc_sender: None,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -699,6 +1223,7 @@ mod tests {
                 version: None,
             }),
             client_capabilities: None,
+            protocol_version: None,
             rpc_sender: None,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -716,6 +1241,7 @@ mod tests {
                 version: None,
             }),
             client_capabilities: None,
+            protocol_version: None,
             rpc_sender: None,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -731,6 +1257,7 @@ mod tests {
             client_capabilities: Some(ClientCapabilities {
                 elicitation: Some(ElicitationCapability {}),
             }),
+            protocol_version: None,
             rpc_sender: None,
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -757,6 +1284,7 @@ mod tests {
         let context = ServerContext {
             client_info: None,
             client_capabilities: Some(ClientCapabilities { elicitation: None }),
+            protocol_version: None,
             rpc_sender: Some(rpc_sender),
             #[cfg(test)]
             test_elicitation_hook: None,
@@ -773,6 +1301,136 @@ mod tests {
             .contains("does not support elicitation"));
     }
 
+    /// Sets its flag when dropped, to observe a future being abandoned
+    struct DropFlag(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Everything written to a duplex writer, once every sender holding it is gone
+    async fn written(mut reader: tokio::io::DuplexStream) -> String {
+        use tokio::io::AsyncReadExt;
+        let mut output = String::new();
+        reader.read_to_string(&mut output).await.unwrap();
+        output
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_is_dropped_without_response() {
+        let (writer, reader) = tokio::io::duplex(4096);
+        let sender = Arc::new(RpcSender::new(writer));
+        let in_flight = InFlightRequests::default();
+        let dropped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let flag = DropFlag(dropped.clone());
+        let slow_tool = async move {
+            let _flag = flag;
+            tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+            McpResponse::success(Some(json!(7)), json!({}))
+        };
+        let task = in_flight.spawn(json!(7), slow_tool, sender.clone());
+        tokio::task::yield_now().await;
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        assert!(in_flight.cancel(&json!(7)));
+        task.await.unwrap();
+        assert!(dropped.load(Ordering::SeqCst));
+        // Already finished: a second cancel is a no-op
+        assert!(!in_flight.cancel(&json!(7)));
+
+        drop(sender);
+        assert_eq!(written(reader).await, "");
+    }
+
+    /// A writer collecting everything written so far, readable while it is still held
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl AsyncWrite for SharedOutput {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_answered_after_input_closes() {
+        let input = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+        ]
+        .map(|message| format!("{}\n", message))
+        .concat();
+        let output = SharedOutput::default();
+
+        serve_lines(input.as_bytes(), output.clone()).await.unwrap();
+
+        // Both responses are out by the time serving returns
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let ids: Vec<Value> = written
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!(1), json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_unknown_request_is_noop() {
+        let (writer, reader) = tokio::io::duplex(4096);
+        let sender = Arc::new(RpcSender::new(writer));
+        let in_flight = InFlightRequests::default();
+
+        let task = in_flight.spawn(
+            json!("a"),
+            async { McpResponse::success(Some(json!("a")), json!({"ok": true})) },
+            sender.clone(),
+        );
+        assert!(!in_flight.cancel(&json!("b")));
+        assert!(!in_flight.cancel(&json!(1)));
+        task.await.unwrap();
+
+        drop(sender);
+        let output = written(reader).await;
+        let response: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"]["ok"], true);
+    }
+
+    #[tokio::test]
+    async fn test_progress_needs_token_and_sender() {
+        let meta: RequestMeta = serde_json::from_value(json!({"progressToken": 7})).unwrap();
+        let empty: RequestMeta = serde_json::from_value(json!({})).unwrap();
+
+        let no_sender = ServerContext::new(None);
+        assert!(no_sender.progress_for(Some(&meta)).is_none());
+
+        let context = ServerContext::new(Some(Arc::new(RpcSender::new(tokio::io::sink()))));
+        assert!(context.progress_for(None).is_none());
+        assert!(context.progress_for(Some(&empty)).is_none());
+        assert!(context.progress_for(Some(&meta)).is_some());
+    }
+
     #[tokio::test]
     async fn test_rpc_sender_construction() {
         let stdout = tokio::io::stdout();
@@ -0,0 +1,555 @@
+//! Streamable HTTP transport for the MCP server
+//!
+//! Serves the MCP endpoint at `/mcp` on a loopback port. Clients POST one JSON-RPC message
+//! at a time; requests are answered with a single-event SSE stream when the client accepts
+//! `text/event-stream`, or with a plain JSON body otherwise, and notifications get
+//! `202 Accepted`. `initialize` opens a session whose id travels in the `Mcp-Session-Id`
+//! header; `DELETE /mcp` ends it, and a session left idle for `SESSION_IDLE_TTL` is closed.
+//! Tool dispatch and notification handling, including `notifications/cancelled`, are the
+//! same as on stdio, and so is concurrency: each request runs on its own copy of the
+//! session's context, so one slow tool call does not hold up the next.
+//!
+//! Progress notifications are sent on a request's SSE stream ahead of its response; clients
+//! that only accept JSON get none. The server never sends requests of its own over HTTP, so
+//! elicitation is not offered and tools fall back to their non-interactive messages.
+
+use crate::mcp::{
+    handle_notification, handle_request, parse_request, InFlightRequests, McpResponse,
+    RpcSender, ServerContext,
+};
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Port used by `--transport http` when `--port` is not given
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// Header carrying the session id assigned by `initialize`
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Sessions with no message for this long are closed, for clients that leave without DELETE
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How often idle sessions are looked for
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Bytes of notifications a request's stream buffers before the reporting task waits
+const NOTIFICATION_BUFFER: usize = 64 * 1024;
+
+/// Open sessions, keyed by session id
+type Sessions = Arc<Mutex<HashMap<String, Session>>>;
+
+/// One client's session: the state `initialize` set up, which each request gets a copy of,
+/// and the requests it has in flight
+#[derive(Clone)]
+struct Session {
+    context: ServerContext,
+    in_flight: InFlightRequests,
+    /// When the client last sent a message in this session
+    last_seen: Instant,
+}
+
+/// Serve MCP over HTTP on `127.0.0.1:port` until the process ends
+pub async fn handle_http(port: u16) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    info!("Starting autoreply MCP server on http://{}/mcp", listener.local_addr()?);
+    serve(listener).await
+}
+
+/// Serve MCP on an already bound listener
+pub async fn serve(listener: tokio::net::TcpListener) -> Result<()> {
+    let sessions: Sessions = Arc::default();
+    let sweeper = tokio::spawn({
+        let sessions = sessions.clone();
+        async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                sweep_idle_sessions(&sessions, SESSION_IDLE_TTL).await;
+            }
+        }
+    });
+    let app = Router::new()
+        .route("/mcp", post(handle_post).get(handle_get).delete(handle_delete))
+        .with_state(sessions);
+    let served = axum::serve(listener, app).await;
+    sweeper.abort();
+    served?;
+    Ok(())
+}
+
+/// Close sessions that have been idle for longer than `ttl` and have no request running
+async fn sweep_idle_sessions(sessions: &Sessions, ttl: Duration) {
+    sessions.lock().await.retain(|id, session| {
+        let keep = session.last_seen.elapsed() < ttl || !session.in_flight.is_empty();
+        if !keep {
+            info!("Closed idle MCP HTTP session {}", id);
+        }
+        keep
+    });
+}
+
+async fn handle_post(
+    State(sessions): State<Sessions>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
+    debug!("Received message: {}", body);
+
+    let request = match parse_request(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            // Responses to server requests are never expected over HTTP; accept and drop them
+            if serde_json::from_str::<McpResponse>(&body).is_ok() {
+                return StatusCode::ACCEPTED.into_response();
+            }
+            let response = McpResponse::error(None, "parse_error", &format!("Invalid JSON: {}", e));
+            return (StatusCode::BAD_REQUEST, Json(response)).into_response();
+        }
+    };
+
+    let session_id = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    if request.method == "initialize" {
+        let mut context = ServerContext::new(None);
+        let response = handle_request(request, &mut context).await;
+        // Elicitation needs server-to-client requests, which this transport does not make
+        if let Some(capabilities) = context.client_capabilities.as_mut() {
+            capabilities.elicitation = None;
+        }
+
+        let session_id = hex::encode(rand::random::<[u8; 16]>());
+        let session = Session {
+            context,
+            in_flight: InFlightRequests::default(),
+            last_seen: Instant::now(),
+        };
+        sessions.lock().await.insert(session_id.clone(), session);
+        info!("Opened MCP HTTP session {}", session_id);
+
+        let mut reply = respond(&headers, &response);
+        if let Ok(value) = session_id.parse() {
+            reply.headers_mut().insert(SESSION_HEADER, value);
+        }
+        return reply;
+    }
+
+    let Some(session_id) = session_id else {
+        return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header").into_response();
+    };
+    let session = sessions.lock().await.get_mut(&session_id).map(|session| {
+        session.last_seen = Instant::now();
+        session.clone()
+    });
+    let Some(session) = session else {
+        return (StatusCode::NOT_FOUND, "Unknown or expired MCP session").into_response();
+    };
+
+    // Notifications (no id) are acted on as on stdio and acknowledged without a body
+    let Some(id) = request.id.clone() else {
+        handle_notification(&request, &session.in_flight);
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let mut context = session.context;
+    if !accepts_sse(&headers) {
+        let work = async move { handle_request(request, &mut context).await };
+        return match session.in_flight.track(&id, work).await {
+            Some(response) => Json(response).into_response(),
+            // A cancelled request is not answered
+            None => StatusCode::ACCEPTED.into_response(),
+        };
+    }
+
+    // Notifications the request sends, such as progress, are written to a pipe that the
+    // response stream reads from; the pipe closes once the request and its reporters finish
+    let (writer, reader) = tokio::io::duplex(NOTIFICATION_BUFFER);
+    context.rpc_sender = Some(Arc::new(RpcSender::new(writer)));
+    let work = async move { handle_request(request, &mut context).await };
+    let task = AbortOnDrop(tokio::spawn(session.in_flight.track(&id, work)));
+    stream_response(reader, task)
+}
+
+/// Aborts a spawned request when its response stream is dropped, i.e. the client went away
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// SSE stream of the notifications read from `notifications`, then the response of `task`
+/// (none if the request was cancelled)
+fn stream_response(
+    notifications: tokio::io::DuplexStream,
+    mut task: AbortOnDrop<Option<McpResponse>>,
+) -> Response {
+    let lines = tokio::io::BufReader::new(notifications).lines();
+    let notifications = futures::stream::unfold(lines, |mut lines| async move {
+        let line = lines.next_line().await.ok().flatten()?;
+        debug!("Sending notification: {}", line);
+        Some((message_event(line), lines))
+    });
+    let response = futures::stream::once(async move {
+        let response = (&mut task.0).await.ok().flatten()?;
+        match serde_json::to_string(&response) {
+            Ok(json) => {
+                debug!("Sending response: {}", json);
+                Some(message_event(json))
+            }
+            Err(e) => {
+                warn!("Failed to serialize response: {}", e);
+                None
+            }
+        }
+    });
+    let events = notifications.chain(response.filter_map(|event| async move { event }));
+    Sse::new(events.map(Ok::<_, Infallible>)).into_response()
+}
+
+/// One SSE `message` event carrying a JSON-RPC message
+fn message_event(json: String) -> Event {
+    Event::default().event("message").data(json)
+}
+
+/// This server opens no standalone stream for server-initiated messages
+async fn handle_get() -> Response {
+    (StatusCode::METHOD_NOT_ALLOWED, "Server-initiated streams are not offered").into_response()
+}
+
+async fn handle_delete(State(sessions): State<Sessions>, headers: HeaderMap) -> Response {
+    let session_id = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok());
+    match session_id {
+        Some(id) if sessions.lock().await.remove(id).is_some() => {
+            info!("Closed MCP HTTP session {}", id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Some(_) => StatusCode::NOT_FOUND.into_response(),
+        None => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+/// Whether the client takes `text/event-stream` responses
+fn accepts_sse(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// Answer with a one-event SSE stream if the client accepts it, JSON otherwise
+fn respond(headers: &HeaderMap, response: &McpResponse) -> Response {
+    if !accepts_sse(headers) {
+        return Json(response).into_response();
+    }
+
+    let json = match serde_json::to_string(response) {
+        Ok(json) => json,
+        Err(e) => {
+            warn!("Failed to serialize response: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    debug!("Sending response: {}", json);
+    let event = message_event(json);
+    Sse::new(futures::stream::once(async move { Ok::<_, Infallible>(event) })).into_response()
+}
+
+/// Refuse browser requests from other sites (DNS rebinding protection)
+fn origin_allowed(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) else {
+        return true;
+    };
+    let host = url::Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+    let allowed = matches!(host.as_deref(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if !allowed {
+        warn!("Rejected MCP request from origin {}", origin);
+    }
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    async fn start_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { serve(listener).await.unwrap() });
+        url
+    }
+
+    /// The JSON-RPC message carried by a single-event SSE body
+    fn sse_message(body: &str) -> Value {
+        let data: String = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect();
+        serde_json::from_str(&data).unwrap()
+    }
+
+    /// Every JSON-RPC message in an SSE body, in order
+    fn sse_messages(body: &str) -> Vec<Value> {
+        body.split("\n\n")
+            .filter(|event| event.contains("data:"))
+            .map(sse_message)
+            .collect()
+    }
+
+    /// Open a session over `client` and return its id
+    async fn initialize(client: &reqwest::Client, url: &str) -> String {
+        let response = client
+            .post(url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {"protocolVersion": "2024-11-05", "capabilities": {}}
+            }))
+            .send()
+            .await
+            .unwrap();
+        response.headers()[SESSION_HEADER].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_then_tools_list() {
+        let url = start_server().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "clientInfo": {"name": "http-test", "version": "1.0"},
+                    "capabilities": {"elicitation": {}}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("text/event-stream"));
+        let session = response.headers()[SESSION_HEADER].to_str().unwrap().to_string();
+        let initialized = sse_message(&response.text().await.unwrap());
+        assert_eq!(initialized["id"], 1);
+        assert_eq!(initialized["result"]["protocolVersion"], "2024-11-05");
+
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+
+        let response = client
+            .post(&url)
+            .header("Accept", "application/json, text/event-stream")
+            .header(SESSION_HEADER, &session)
+            .json(&json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let listed = sse_message(&response.text().await.unwrap());
+        assert_eq!(listed["id"], 2);
+        let names: Vec<&str> = listed["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|tool| tool["name"].as_str())
+            .collect();
+        assert!(names.contains(&"profile"));
+        assert!(names.contains(&"search"));
+
+        // Plain JSON for clients that do not take SSE
+        let response = client
+            .post(&url)
+            .header("Accept", "application/json")
+            .header(SESSION_HEADER, &session)
+            .json(&json!({"jsonrpc": "2.0", "id": 3, "method": "no/such/method"}))
+            .send()
+            .await
+            .unwrap();
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["id"], 3);
+        assert!(body["error"]["code"].is_i64());
+
+        let response = client.delete(&url).header(SESSION_HEADER, &session).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+        let response = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&json!({"jsonrpc": "2.0", "id": 4, "method": "tools/list"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_session_and_foreign_origin() {
+        let url = start_server().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(&url)
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        let response = client
+            .post(&url)
+            .header("Origin", "https://evil.example")
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        let response = client.post(&url).body("{not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_progress_streams_ahead_of_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alice.car");
+        let car = crate::car::test_support::synthetic_repo_car(
+            Some("did:plc:alice"),
+            &[("a1", "rust"), ("a2", "tips")],
+        );
+        std::fs::write(&path, car).unwrap();
+
+        let url = start_server().await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "search",
+                "arguments": {"query": "rust", "car": [path.to_string_lossy()]},
+                "_meta": {"progressToken": "p-1"}
+            }
+        });
+
+        let response = client
+            .post(&url)
+            .header("Accept", "application/json, text/event-stream")
+            .header(SESSION_HEADER, &session)
+            .json(&call)
+            .send()
+            .await
+            .unwrap();
+        let messages = sse_messages(&response.text().await.unwrap());
+        let (result, progress) = messages.split_last().unwrap();
+        assert_eq!(result["id"], 2);
+        assert!(result["result"].is_object(), "{}", result);
+        assert!(!progress.is_empty());
+        for (step, notification) in progress.iter().enumerate() {
+            assert_eq!(notification["method"], "notifications/progress");
+            assert_eq!(notification["params"]["progressToken"], "p-1");
+            assert_eq!(notification["params"]["progress"], step + 1);
+        }
+
+        // A JSON-only client gets just the response
+        let response = client
+            .post(&url)
+            .header("Accept", "application/json")
+            .header(SESSION_HEADER, &session)
+            .json(&call)
+            .send()
+            .await
+            .unwrap();
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["id"], 2);
+        assert!(body["result"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_are_swept() {
+        let sessions = Sessions::default();
+        let long_ago = Instant::now()
+            .checked_sub(Duration::from_secs(3600))
+            .unwrap_or_else(Instant::now);
+        let session = |last_seen| Session {
+            context: ServerContext::new(None),
+            in_flight: InFlightRequests::default(),
+            last_seen,
+        };
+        let busy = session(long_ago);
+        let stalled = busy.in_flight.track(&json!(1), std::future::pending());
+        {
+            let mut open = sessions.lock().await;
+            open.insert("idle".to_string(), session(long_ago));
+            open.insert("busy".to_string(), busy);
+            open.insert("active".to_string(), session(Instant::now()));
+        }
+
+        sweep_idle_sessions(&sessions, Duration::from_secs(60)).await;
+        let mut left: Vec<String> = sessions.lock().await.keys().cloned().collect();
+        left.sort();
+        assert_eq!(left, ["active", "busy"]);
+        drop(stalled);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_notification_stops_request_in_session() {
+        let sessions = Sessions::default();
+        let session = Session {
+            context: ServerContext::new(None),
+            in_flight: InFlightRequests::default(),
+            last_seen: Instant::now(),
+        };
+        sessions.lock().await.insert("s1".to_string(), session.clone());
+        let mut headers = HeaderMap::new();
+        headers.insert(SESSION_HEADER, "s1".parse().unwrap());
+        let notify = |method: &str, params: Value| {
+            let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+            handle_post(State(sessions.clone()), headers.clone(), message.to_string())
+        };
+
+        let reply = notify("notifications/initialized", json!({})).await;
+        assert_eq!(reply.status(), StatusCode::ACCEPTED);
+
+        let stalled = session.in_flight.track(&json!(7), std::future::pending());
+        let reply = notify("notifications/cancelled", json!({"requestId": 7})).await;
+        assert_eq!(reply.status(), StatusCode::ACCEPTED);
+        assert!(stalled.await.is_none());
+    }
+}
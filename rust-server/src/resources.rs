@@ -0,0 +1,380 @@
+//! MCP resources: browsing the repos of logged-in accounts
+//!
+//! `resources/list` pages through every stored account's collections
+//! (`at://{did}/{collection}`) and records (`at://{did}/{collection}/{rkey}`);
+//! `resources/read` renders one of them as markdown. Repos are fetched through the same
+//! cached provider that `search` uses and walked with the existing CAR and MST readers.
+
+use crate::auth::CredentialStorage;
+use crate::bluesky::did::DidResolver;
+use crate::bluesky::mst::extract_all_rkey_mappings;
+use crate::bluesky::provider::configured_provider;
+use crate::car::{decode_cbor, CarIndex, CborValue, Cid};
+use crate::error::AppError;
+use crate::mcp::{McpRequest, McpResponse};
+use crate::tools::post_format::{blockquote_content, format_timestamp};
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use tracing::debug;
+
+/// Resources returned per `resources/list` page
+pub const RESOURCES_PAGE_SIZE: usize = 100;
+
+const MARKDOWN: &str = "text/markdown";
+
+/// One entry of a `resources/list` result
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: &'static str,
+}
+
+/// Handle resources/list method
+pub async fn handle_resources_list(request: McpRequest) -> McpResponse {
+    let cursor = request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("cursor"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    match list_resources(cursor.as_deref()).await {
+        Ok(result) => McpResponse::success(request.id, result),
+        Err(e) => McpResponse::error(request.id, e.error_code(), &e.message()),
+    }
+}
+
+/// Handle resources/read method
+pub async fn handle_resources_read(request: McpRequest) -> McpResponse {
+    let uri = request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("uri"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let Some(uri) = uri else {
+        return McpResponse::error(request.id, "invalid_params", "Missing resource uri");
+    };
+
+    match read_resource(&uri).await {
+        Ok(text) => McpResponse::success(
+            request.id,
+            json!({ "contents": [{ "uri": uri, "mimeType": MARKDOWN, "text": text }] }),
+        ),
+        Err(e) => McpResponse::error(request.id, e.error_code(), &e.message()),
+    }
+}
+
+async fn list_resources(cursor: Option<&str>) -> Result<Value, AppError> {
+    let start = match cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| AppError::InvalidInput(format!("Invalid cursor: {}", cursor)))?,
+        None => 0,
+    };
+
+    let storage = CredentialStorage::new()?;
+    let resolver = DidResolver::new();
+    let provider = configured_provider()?;
+
+    let mut handles = storage.list_accounts()?;
+    handles.sort();
+    let mut resources = Vec::new();
+    for handle in handles {
+        let did = match storage.account_info(&handle)?.did {
+            Some(did) => did,
+            None => resolve_did(&resolver, &handle).await?,
+        };
+        let car_bytes = provider.fetch_repo_bytes(&did).await?;
+        resources.extend(repo_resources(&did, &car_bytes)?);
+    }
+
+    let (page, next_cursor) = page_of(resources, start);
+    let mut result = json!({ "resources": page });
+    if let Some(next_cursor) = next_cursor {
+        result["nextCursor"] = json!(next_cursor);
+    }
+    Ok(result)
+}
+
+/// One page of resources starting at `start`, with the cursor of the next page if any
+fn page_of(resources: Vec<Resource>, start: usize) -> (Vec<Resource>, Option<String>) {
+    let end = start.saturating_add(RESOURCES_PAGE_SIZE);
+    let next_cursor = (resources.len() > end).then(|| end.to_string());
+    let page = resources.into_iter().skip(start).take(RESOURCES_PAGE_SIZE).collect();
+    (page, next_cursor)
+}
+
+async fn read_resource(uri: &str) -> Result<String, AppError> {
+    let (authority, collection, rkey) = parse_resource_uri(uri)?;
+    let did = if authority.starts_with("did:") {
+        authority.to_string()
+    } else {
+        resolve_did(&DidResolver::new(), authority).await?
+    };
+
+    let provider = configured_provider()?;
+    let car_bytes = provider.fetch_repo_bytes(&did).await?;
+    match rkey {
+        Some(rkey) => record_markdown(&did, &car_bytes, collection, rkey),
+        None => collection_markdown(&did, &car_bytes, collection),
+    }
+}
+
+async fn resolve_did(resolver: &DidResolver, handle: &str) -> Result<String, AppError> {
+    resolver
+        .resolve_handle(handle)
+        .await?
+        .ok_or_else(|| AppError::DidResolveFailed(format!("Could not resolve handle: {}", handle)))
+}
+
+/// Split `at://{did or handle}/{collection}[/{rkey}]`
+fn parse_resource_uri(uri: &str) -> Result<(&str, &str, Option<&str>), AppError> {
+    let invalid = || {
+        AppError::InvalidInput(format!(
+            "Invalid resource URI: {}. Expected at://{{did}}/{{collection}}[/{{rkey}}]",
+            uri
+        ))
+    };
+    let path = uri.strip_prefix("at://").ok_or_else(invalid)?;
+    let parts: Vec<&str> = path.split('/').collect();
+    match parts.as_slice() {
+        [authority, collection] if !authority.is_empty() && !collection.is_empty() => {
+            Ok((authority, collection, None))
+        }
+        [authority, collection, rkey]
+            if !authority.is_empty() && !collection.is_empty() && !rkey.is_empty() =>
+        {
+            Ok((authority, collection, Some(rkey)))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Collection -> rkey -> record CID for every record in a repo CAR
+fn repo_index(car_bytes: &[u8]) -> Result<BTreeMap<String, BTreeMap<String, String>>, AppError> {
+    let mappings = extract_all_rkey_mappings(car_bytes).map_err(|e| {
+        AppError::RepoParseFailed(format!("Failed to extract MST mappings: {}", e))
+    })?;
+
+    let mut collections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for (cid, (collection, rkey)) in mappings {
+        collections.entry(collection).or_default().insert(rkey, cid);
+    }
+    Ok(collections)
+}
+
+/// Every collection of a repo, each followed by its records in rkey order
+pub fn repo_resources(did: &str, car_bytes: &[u8]) -> Result<Vec<Resource>, AppError> {
+    let collections = repo_index(car_bytes)?;
+    debug!("Listing {} collections of {}", collections.len(), did);
+
+    let mut resources = Vec::new();
+    for (collection, records) in collections {
+        resources.push(Resource {
+            uri: format!("at://{}/{}", did, collection),
+            name: format!("{} ({} records)", collection, records.len()),
+            mime_type: MARKDOWN,
+        });
+        resources.extend(records.into_keys().map(|rkey| Resource {
+            uri: format!("at://{}/{}/{}", did, collection, rkey),
+            name: format!("{}/{}", collection, rkey),
+            mime_type: MARKDOWN,
+        }));
+    }
+    Ok(resources)
+}
+
+/// Markdown listing the records of one collection
+pub fn collection_markdown(
+    did: &str,
+    car_bytes: &[u8],
+    collection: &str,
+) -> Result<String, AppError> {
+    let records = repo_index(car_bytes)?.remove(collection).ok_or_else(|| {
+        AppError::NotFound(format!("No {} records in the repo of {}", collection, did))
+    })?;
+
+    let mut markdown = format!("# {}\n\n{} records of {}\n\n", collection, records.len(), did);
+    for rkey in records.keys() {
+        markdown.push_str(&format!("- at://{}/{}/{}\n", did, collection, rkey));
+    }
+    Ok(markdown)
+}
+
+/// One record read from a repo CAR, decoded to its AT Protocol JSON form
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RepoRecord {
+    pub uri: String,
+    pub cid: String,
+    pub value: Value,
+}
+
+/// Find the record at `collection/rkey` in a repo CAR and decode its block
+pub fn read_record(
+    did: &str,
+    car_bytes: &[u8],
+    collection: &str,
+    rkey: &str,
+) -> Result<RepoRecord, AppError> {
+    let uri = format!("at://{}/{}/{}", did, collection, rkey);
+    let cid = repo_index(car_bytes)?
+        .get(collection)
+        .and_then(|records| records.get(rkey))
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Record not found: {}", uri)))?;
+
+    let index = CarIndex::build(car_bytes)
+        .map_err(|e| AppError::RepoParseFailed(format!("Failed to index CAR: {}", e)))?;
+    let block = cid
+        .parse::<Cid>()
+        .ok()
+        .and_then(|cid| index.get_block(&cid))
+        .ok_or_else(|| AppError::NotFound(format!("Record block missing from repo: {}", uri)))?;
+    let record = decode_cbor(block)
+        .map_err(|e| AppError::RepoParseFailed(format!("Failed to decode {}: {}", uri, e)))?;
+
+    Ok(RepoRecord {
+        uri,
+        cid,
+        value: cbor_to_json(&record),
+    })
+}
+
+/// Markdown for one record: post text when it has any, then the full record as JSON
+pub fn record_markdown(
+    did: &str,
+    car_bytes: &[u8],
+    collection: &str,
+    rkey: &str,
+) -> Result<String, AppError> {
+    let record = read_record(did, car_bytes, collection, rkey)?;
+
+    let mut markdown =
+        format!("# {}/{}\n\n{}\ncid: {}\n", collection, rkey, record.uri, record.cid);
+    if let Some(created_at) = record.value["createdAt"].as_str() {
+        markdown.push_str(&format!("created: {}\n", format_timestamp(created_at)));
+    }
+    if let Some(text) = record.value["text"].as_str() {
+        markdown.push_str(&format!("\n{}\n", blockquote_content(text)));
+    }
+    markdown.push_str(&record_json_block(&record)?);
+    Ok(markdown)
+}
+
+/// The record's value as a pretty-printed JSON code block
+pub fn record_json_block(record: &RepoRecord) -> Result<String, AppError> {
+    let json = serde_json::to_string_pretty(&record.value)
+        .map_err(|e| AppError::Internal(format!("Failed to render {}: {}", record.uri, e)))?;
+    Ok(format!("\n```json\n{}\n```\n", json))
+}
+
+/// AT Protocol JSON form of a DAG-CBOR value: links as `$link`, bytes as `$bytes`
+fn cbor_to_json(value: &CborValue) -> Value {
+    match value {
+        CborValue::Map(entries) => {
+            let mut map = Map::new();
+            for (key, value) in entries {
+                if let CborValue::Text(key) = key {
+                    map.insert(key.to_string(), cbor_to_json(value));
+                }
+            }
+            Value::Object(map)
+        }
+        CborValue::Array(items) => Value::Array(items.iter().map(cbor_to_json).collect()),
+        CborValue::Text(text) => json!(text),
+        CborValue::Bytes(bytes) => json!({ "$bytes": STANDARD_NO_PAD.encode(bytes) }),
+        CborValue::Integer(n) => json!(n),
+        CborValue::Bool(b) => json!(b),
+        CborValue::Null => Value::Null,
+        CborValue::Float(f) => json!(f),
+        CborValue::Link(bytes) => match Cid::from_bytes(bytes) {
+            Ok(cid) => json!({ "$link": cid.to_string() }),
+            Err(_) => Value::Null,
+        },
+        CborValue::Tag(_, inner) => cbor_to_json(inner),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::test_support::synthetic_repo_car;
+
+    #[test]
+    fn test_repo_resources_lists_collections_then_records() {
+        let car = synthetic_repo_car(Some("did:plc:alice"), &[("a1", "first"), ("b2", "second")]);
+        let resources = repo_resources("did:plc:alice", &car).unwrap();
+
+        let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec![
+                "at://did:plc:alice/app.bsky.feed.post",
+                "at://did:plc:alice/app.bsky.feed.post/a1",
+                "at://did:plc:alice/app.bsky.feed.post/b2",
+            ]
+        );
+        assert_eq!(resources[0].name, "app.bsky.feed.post (2 records)");
+        assert!(resources.iter().all(|r| r.mime_type == "text/markdown"));
+
+        let listing = collection_markdown("did:plc:alice", &car, "app.bsky.feed.post").unwrap();
+        assert!(listing.contains("2 records of did:plc:alice"));
+        assert!(listing.contains("- at://did:plc:alice/app.bsky.feed.post/b2\n"));
+        assert!(matches!(
+            collection_markdown("did:plc:alice", &car, "app.bsky.graph.follow"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_markdown_renders_post() {
+        let car = synthetic_repo_car(Some("did:plc:alice"), &[("a1", "hello\nworld")]);
+        let markdown = record_markdown("did:plc:alice", &car, "app.bsky.feed.post", "a1").unwrap();
+
+        assert!(markdown.starts_with("# app.bsky.feed.post/a1\n\n"));
+        assert!(markdown.contains("at://did:plc:alice/app.bsky.feed.post/a1\ncid: "));
+        assert!(markdown.contains("created: 2025-01-01T00:00:00Z\n"));
+        assert!(markdown.contains("\n> hello\n> world\n"));
+        assert!(markdown.contains("\"$type\": \"app.bsky.feed.post\""));
+
+        assert!(matches!(
+            record_markdown("did:plc:alice", &car, "app.bsky.feed.post", "zz"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_resource_uri_and_paging() {
+        assert_eq!(
+            parse_resource_uri("at://did:plc:a/app.bsky.feed.post/3k").unwrap(),
+            ("did:plc:a", "app.bsky.feed.post", Some("3k"))
+        );
+        assert_eq!(
+            parse_resource_uri("at://alice.bsky.social/app.bsky.feed.like").unwrap(),
+            ("alice.bsky.social", "app.bsky.feed.like", None)
+        );
+        for bad in ["https://bsky.app", "at://did:plc:a", "at://did:plc:a/c/r/x", "at:///c"] {
+            assert!(parse_resource_uri(bad).is_err(), "{}", bad);
+        }
+
+        let resources: Vec<Resource> = (0..RESOURCES_PAGE_SIZE + 5)
+            .map(|i| Resource {
+                uri: format!("at://did:plc:a/c/{}", i),
+                name: i.to_string(),
+                mime_type: MARKDOWN,
+            })
+            .collect();
+        let (page, next) = page_of(resources.clone(), 0);
+        assert_eq!(page.len(), RESOURCES_PAGE_SIZE);
+        assert_eq!(next.as_deref(), Some("100"));
+        let (page, next) = page_of(resources, 100);
+        assert_eq!(page.len(), 5);
+        assert_eq!(next, None);
+    }
+}
@@ -4,8 +4,9 @@
 //! to provide a complete search solution.
 
 use super::fuzzy::FuzzyMatcher;
-use super::parser::{ParsedQuery, QueryParser};
+use super::parser::{ParsedQuery, QueryFilter, QueryParser};
 use super::ranking::{MatchScore, ScoringWeights};
+use super::top_k::TopK;
 
 /// Search result with content and score
 #[derive(Debug, Clone)]
@@ -19,10 +20,65 @@ pub struct SearchResult<T> {
     pub matched_terms: Vec<String>,
 }
 
+/// How [`SearchEngine::search`] reads, filters and ranks the items it is given
+///
+/// Built from the text extractor with [`MatchOptions::new`]; by default every result
+/// is kept, every `has:` filter passes and ties keep input order.
+pub struct MatchOptions<'a, T> {
+    limit: usize,
+    extract_text: TextFn<'a, T>,
+    matches_filter: FilterFn<'a, T>,
+    adjust: AdjustFn<'a, T>,
+    tie_key: Box<dyn Fn(&T) -> String + 'a>,
+}
+
+type TextFn<'a, T> = Box<dyn Fn(&T) -> Vec<String> + 'a>;
+type FilterFn<'a, T> = Box<dyn Fn(&T, &QueryFilter) -> bool + 'a>;
+type AdjustFn<'a, T> = Box<dyn FnMut(&mut SearchResult<T>) + 'a>;
+
+impl<'a, T> MatchOptions<'a, T> {
+    /// Options searching the texts `extract_text` returns for each item
+    pub fn new(extract_text: impl Fn(&T) -> Vec<String> + 'a) -> Self {
+        Self {
+            limit: usize::MAX,
+            extract_text: Box::new(extract_text),
+            matches_filter: Box::new(|_, _| true),
+            adjust: Box::new(|_| {}),
+            tie_key: Box::new(|_| String::new()),
+        }
+    }
+
+    /// Keep only the best `limit` results
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Whether an item satisfies a single `has:` filter
+    pub fn with_filter(mut self, matches_filter: impl Fn(&T, &QueryFilter) -> bool + 'a) -> Self {
+        self.matches_filter = Box::new(matches_filter);
+        self
+    }
+
+    /// Runs on each match before ranking (e.g. to apply recency)
+    pub fn with_adjust(mut self, adjust: impl FnMut(&mut SearchResult<T>) + 'a) -> Self {
+        self.adjust = Box::new(adjust);
+        self
+    }
+
+    /// Score ties go to the smaller key, then input order
+    pub fn with_tie_key(mut self, tie_key: impl Fn(&T) -> String + 'a) -> Self {
+        self.tie_key = Box::new(tie_key);
+        self
+    }
+}
+
 /// Search engine that combines parsing, matching, and ranking
 pub struct SearchEngine {
     fuzzy_matcher: FuzzyMatcher,
     scoring_weights: ScoringWeights,
+    min_score: Option<f64>,
+    case_sensitive: bool,
 }
 
 impl Default for SearchEngine {
@@ -37,23 +93,86 @@ impl SearchEngine {
         Self {
             fuzzy_matcher: FuzzyMatcher::new(),
             scoring_weights: ScoringWeights::default(),
+            min_score: None,
+            case_sensitive: false,
         }
     }
 
     /// Create search engine with custom weights
-    #[allow(dead_code)]
     pub fn with_weights(weights: ScoringWeights) -> Self {
         Self {
             fuzzy_matcher: FuzzyMatcher::new(),
             scoring_weights: weights,
+            min_score: None,
+            case_sensitive: false,
         }
     }
 
-    /// Search items with a query string
+    /// Drop matches whose final score is below `min_score` (`None` keeps everything)
     ///
-    /// The extract_text function should return the searchable text for each item.
-    /// Returns results sorted by relevance (highest score first).
-    pub fn search<T, F>(
+    /// Operator-only queries produce neutral scores and are never cut.
+    pub fn with_min_score(mut self, min_score: Option<f64>) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Match query terms and exclusions with their original casing (e.g. `IT` vs `it`)
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self.fuzzy_matcher = if case_sensitive {
+            FuzzyMatcher::case_sensitive()
+        } else {
+            FuzzyMatcher::new()
+        };
+        self
+    }
+
+    /// Search a stream of items with a query string, keeping the best `options.limit` results
+    ///
+    /// Items are consumed one at a time, so memory stays O(limit) however long the
+    /// stream is. Scoring is purely per item, so no global first pass is needed.
+    /// Items failing a `has:` filter or containing a `-term` exclusion are dropped.
+    /// Results come best first; score ties go to the smaller tie key, then input order,
+    /// so results can be paged deterministically however items arrive.
+    /// A query made only of filters and exclusions keeps every remaining item.
+    pub fn search<T, I>(
+        &mut self,
+        query: &str,
+        items: I,
+        mut options: MatchOptions<'_, T>,
+    ) -> Vec<SearchResult<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let parsed = QueryParser::parse_with_case(query, self.case_sensitive);
+
+        let mut top = TopK::new(options.limit);
+        if !parsed.is_searchable() {
+            // Still drain the stream so callers observing it see every item
+            items.into_iter().for_each(drop);
+            return top.into_sorted_vec();
+        }
+
+        for item in items {
+            if let Some((score, matched_terms)) = self.score_item(&parsed, &item, &options) {
+                let key = (options.tie_key)(&item);
+                let mut result = SearchResult {
+                    item,
+                    score,
+                    matched_terms,
+                };
+                (options.adjust)(&mut result);
+                top.push(result, key);
+            }
+        }
+
+        top.into_sorted_vec()
+    }
+
+    /// Every match of `query` in `items`, best first: `search` without a limit, filters
+    /// or tie keys
+    #[cfg(test)]
+    pub fn search_all<'a, T, F>(
         &mut self,
         query: &str,
         items: &[T],
@@ -61,40 +180,46 @@ impl SearchEngine {
     ) -> Vec<SearchResult<T>>
     where
         T: Clone,
-        F: Fn(&T) -> Vec<String>,
+        F: Fn(&T) -> Vec<String> + 'a,
     {
-        // Parse the query
-        let parsed = QueryParser::parse(query);
+        self.search(query, items.iter().cloned(), MatchOptions::new(extract_text))
+    }
 
-        let mut results = Vec::new();
+    /// Score one item against a parsed query, or `None` if it is filtered out or does not match
+    ///
+    /// Items failing a `has:` filter or containing a `-term` exclusion are dropped.
+    /// Operator-only queries (filters/exclusions) keep every remaining item with a neutral score.
+    fn score_item<T>(
+        &mut self,
+        parsed: &ParsedQuery,
+        item: &T,
+        options: &MatchOptions<'_, T>,
+    ) -> Option<(MatchScore, Vec<String>)> {
+        if !parsed.filters.iter().all(|f| (options.matches_filter)(item, f)) {
+            return None;
+        }
 
-        // Search each item
-        for item in items {
-            let searchable_texts = extract_text(item);
+        let searchable_texts = (options.extract_text)(item);
 
-            if let Some(result) = self.match_item(item.clone(), &parsed, &searchable_texts) {
-                results.push(result);
-            }
+        // Drop anything containing an excluded term before ranking
+        if parsed.is_excluded(&searchable_texts) {
+            return None;
         }
 
-        // Sort by score (descending)
-        results.sort_by(|a, b| {
-            b.score
-                .final_score
-                .partial_cmp(&a.score.final_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        if parsed.is_empty() {
+            return Some((MatchScore::neutral(), Vec::new()));
+        }
 
-        results
+        self.match_item(parsed, &searchable_texts)
+            .filter(|(score, _)| self.min_score.is_none_or(|min| score.final_score >= min))
     }
 
-    /// Match a single item against the parsed query
-    fn match_item<T>(
+    /// Match an item's searchable texts against the parsed query
+    fn match_item(
         &mut self,
-        item: T,
         parsed: &ParsedQuery,
         searchable_texts: &[String],
-    ) -> Option<SearchResult<T>> {
+    ) -> Option<(MatchScore, Vec<String>)> {
         let mut best_score: Option<MatchScore> = None;
         let mut matched_terms = Vec::new();
 
@@ -158,11 +283,7 @@ impl SearchEngine {
             }
         }
 
-        best_score.map(|score| SearchResult {
-            item,
-            score,
-            matched_terms,
-        })
+        best_score.map(|score| (score, matched_terms))
     }
 
     /// Match text against searchable content
@@ -209,6 +330,7 @@ impl SearchEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{DateTime, Utc};
 
     #[derive(Debug, Clone, PartialEq)]
     struct TestPost {
@@ -231,7 +353,7 @@ mod tests {
             },
         ];
 
-        let results = engine.search("hello", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("hello", &posts, |p| vec![p.text.clone()]);
 
         assert_eq!(results.len(), 2);
         assert!(results[0].item.text.contains("Hello"));
@@ -253,13 +375,50 @@ mod tests {
             },
         ];
 
-        let results = engine.search(r#""hello world""#, &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all(r#""hello world""#, &posts, |p| vec![p.text.clone()]);
 
         // Only "Hello world" should match the exact phrase
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].item.text, "Hello world");
     }
 
+    #[test]
+    fn test_search_drops_excluded_terms() {
+        let mut engine = SearchEngine::new();
+
+        let posts = vec![
+            TestPost {
+                text: "Rust async runtime".to_string(),
+            },
+            TestPost {
+                text: "Rust for Crypto wallets".to_string(),
+            },
+            TestPost {
+                text: "Spam spam".to_string(),
+            },
+        ];
+
+        let results = engine.search_all("rust -crypto", &posts, |p| vec![p.text.clone()]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.text, "Rust async runtime");
+
+        // Exclusion-only query keeps every post that doesn't match
+        let results = engine.search_all("-spam", &posts, |p| vec![p.text.clone()]);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_search_empty_quoted_phrase_matches_nothing() {
+        let mut engine = SearchEngine::new();
+
+        let posts = vec![TestPost {
+            text: "Hello \"\" world".to_string(),
+        }];
+
+        let results = engine.search_all(r#""""#, &posts, |p| vec![p.text.clone()]);
+        assert!(results.is_empty());
+    }
+
     #[test]
     fn test_search_stop_words() {
         let mut engine = SearchEngine::new();
@@ -273,7 +432,7 @@ mod tests {
             },
         ];
 
-        let results = engine.search("cat mat", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("cat mat", &posts, |p| vec![p.text.clone()]);
 
         // Should find the cat post despite "on" and "the" being stop words
         assert_eq!(results.len(), 1);
@@ -296,7 +455,7 @@ mod tests {
             },
         ];
 
-        let results = engine.search("prog", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("prog", &posts, |p| vec![p.text.clone()]);
 
         // All should match with fuzzy matching
         assert_eq!(results.len(), 3);
@@ -310,7 +469,7 @@ mod tests {
             text: "Hello world".to_string(),
         }];
 
-        let results = engine.search("xyz", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("xyz", &posts, |p| vec![p.text.clone()]);
 
         assert_eq!(results.len(), 0);
     }
@@ -334,7 +493,7 @@ mod tests {
             }, // Middle of word
         ];
 
-        let results = engine.search("car", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("car", &posts, |p| vec![p.text.clone()]);
 
         assert_eq!(results.len(), 4);
         // Full word match should rank highest
@@ -355,7 +514,7 @@ mod tests {
         ];
 
         // Query that matches 'a' and 'b' - exact contiguous 'a b' should rank higher than 'a x b'
-        let results = engine.search("a b", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("a b", &posts, |p| vec![p.text.clone()]);
 
         assert_eq!(results.len(), 2);
         // 'a b' should rank highest due to exact proximity
@@ -371,7 +530,7 @@ mod tests {
         ];
 
         // Two quoted phrases, one of which does not exist
-        let results = engine.search(r#""hello world" "nonexistent""#, &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all(r#""hello world" "nonexistent""#, &posts, |p| vec![p.text.clone()]);
         assert_eq!(results.len(), 0, "If any quoted phrase is missing the item should not match");
     }
 
@@ -384,7 +543,7 @@ mod tests {
             TestPost { text: "world".to_string() },
         ];
 
-        let results = engine.search("hello world", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("hello world", &posts, |p| vec![p.text.clone()]);
 
         // Both posts should match via individual-word matches
         assert_eq!(results.len(), 2);
@@ -403,7 +562,7 @@ mod tests {
             TestPost { text: "appl".to_string() },  // fuzzy
         ];
 
-        let results = engine.search("apple", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("apple", &posts, |p| vec![p.text.clone()]);
 
     // Ensure the exact-match result exists and is marked as exact
     let exact = results.iter().find(|r| r.item.text == "apple").expect("exact match result present");
@@ -425,7 +584,7 @@ mod tests {
             MultiTextPost { title: "Another".to_string(), body: "No match".to_string() },
         ];
 
-        let results = engine.search("rustlang", &posts, |p: &MultiTextPost| vec![p.title.clone(), p.body.clone()]);
+        let results = engine.search_all("rustlang", &posts, |p: &MultiTextPost| vec![p.title.clone(), p.body.clone()]);
         assert_eq!(results.len(), 1);
     }
 
@@ -439,10 +598,198 @@ mod tests {
             TestPost { text: "world".to_string() },
         ];
 
-        let results = engine.search("hello world", &posts, |p| vec![p.text.clone()]);
+        let results = engine.search_all("hello world", &posts, |p| vec![p.text.clone()]);
 
         // Whole query match should be ranked first
         assert!(!results.is_empty());
         assert_eq!(results[0].item.text, "hello world");
     }
+
+    #[test]
+    fn test_recency_ranks_newer_of_identical_posts_first() {
+        #[derive(Clone)]
+        struct DatedPost {
+            text: String,
+            created_at: &'static str,
+        }
+
+        let weights = ScoringWeights {
+            recency_weight: 1.0,
+            recency_half_life_days: 7.0,
+            ..ScoringWeights::default()
+        };
+        let mut engine = SearchEngine::with_weights(weights.clone());
+        let posts = vec![
+            DatedPost { text: "rust release".to_string(), created_at: "2024-01-01T00:00:00Z" },
+            DatedPost { text: "rust release".to_string(), created_at: "2024-06-01T00:00:00Z" },
+            DatedPost { text: "rust release".to_string(), created_at: "not a date" },
+        ];
+        let now = DateTime::parse_from_rfc3339("2024-06-02T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let options = MatchOptions::new(|p: &DatedPost| vec![p.text.clone()]).with_adjust(|r| {
+            let created = DateTime::parse_from_rfc3339(r.item.created_at)
+                .ok()
+                .map(|d| d.with_timezone(&Utc));
+            r.score.apply_recency(created, now, &weights)
+        });
+        let results = engine.search("rust", posts, options);
+
+        assert_eq!(results[0].item.created_at, "2024-06-01T00:00:00Z");
+        // An unparseable timestamp gains nothing, so it ranks below even the oldest post
+        let unparsed = results.last().unwrap();
+        assert_eq!(unparsed.item.created_at, "not a date");
+        assert!(unparsed.score.final_score < results[1].score.final_score);
+    }
+
+    #[test]
+    fn test_search_limit_matches_truncated_full_search() {
+        let texts = [
+            "rust", "rusty nails", "trust me", "rust rust", "nothing", "crusty", "rust lang",
+            "rust",
+        ];
+        let posts: Vec<TestPost> = texts.iter().map(|t| TestPost { text: t.to_string() }).collect();
+        let mut engine = SearchEngine::new();
+        let full: Vec<String> = engine
+            .search_all("rust", &posts, |p| vec![p.text.clone()])
+            .into_iter()
+            .map(|r| r.item.text)
+            .collect();
+
+        for limit in [0, 1, 3, full.len(), full.len() + 5] {
+            let top: Vec<String> = engine
+                .search(
+                    "rust",
+                    posts.iter().cloned(),
+                    MatchOptions::new(|p: &TestPost| vec![p.text.clone()]).with_limit(limit),
+                )
+                .into_iter()
+                .map(|r| r.item.text)
+                .collect();
+            let expected: Vec<String> = full.iter().take(limit).cloned().collect();
+            assert_eq!(top, expected, "limit = {}", limit);
+        }
+    }
+
+    #[test]
+    fn test_search_applies_adjustment_before_ranking() {
+        let posts = vec![
+            TestPost { text: "rust".to_string() },
+            TestPost { text: "rust".to_string() },
+            TestPost { text: "other rust".to_string() },
+        ];
+        let mut engine = SearchEngine::new();
+        let mut seen = 0;
+        let options = MatchOptions::new(|p: &TestPost| vec![p.text.clone()])
+            .with_limit(1)
+            .with_adjust(|r| {
+                if r.item.text == "other rust" {
+                    r.score.final_score *= 1000.0;
+                }
+            });
+        let results = engine.search("rust", posts.into_iter().inspect(|_| seen += 1), options);
+        assert_eq!(seen, 3);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.text, "other rust");
+    }
+
+    #[test]
+    fn test_equal_scores_order_by_creation_then_uri() {
+        use crate::bluesky::records::PostRecord;
+
+        let post = |rkey: &str, created_at: &str| PostRecord {
+            uri: format!("at://did:plc:alice/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: "rust release".to_string(),
+            created_at: created_at.to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        let rkey = |p: &PostRecord| p.uri.rsplit('/').next().unwrap().to_string();
+        // Creation times compare as times, not as text
+        let a = post("3ka", "2024-02-01T00:00:00Z");
+        let b = post("3kb", "2024-02-01T01:00:00.000+02:00");
+        let c = post("3kc", "2024-02-01T00:00:00.000Z");
+
+        let mut engine = SearchEngine::new();
+        let expected = ["3kb", "3ka", "3kc"];
+        for posts in [
+            vec![a.clone(), b.clone(), c.clone()],
+            vec![c.clone(), a.clone(), b.clone()],
+            vec![b.clone(), c.clone(), a.clone()],
+        ] {
+            let options = MatchOptions::new(PostRecord::get_searchable_text)
+                .with_limit(3)
+                .with_tie_key(PostRecord::tie_key);
+            let top = engine.search("rust", posts.iter().cloned(), options);
+            assert_eq!(top[0].score.final_score, top[2].score.final_score);
+            let order: Vec<String> = top.iter().map(|r| rkey(&r.item)).collect();
+            assert_eq!(order, expected);
+        }
+    }
+
+    #[test]
+    fn test_min_score_drops_weak_matches() {
+        let posts = vec![
+            TestPost { text: "xenon".to_string() },
+            TestPost { text: "r u s t".to_string() },
+            TestPost { text: "rust".to_string() },
+        ];
+
+        let mut engine = SearchEngine::new().with_min_score(Some(100.0));
+        assert!(engine.search_all("xyz", &posts, |p| vec![p.text.clone()]).is_empty());
+
+        let results = engine.search_all("rust", &posts, |p| vec![p.text.clone()]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.text, "rust");
+
+        // Unset keeps the scattered fuzzy match
+        let mut engine = SearchEngine::new();
+        assert_eq!(engine.search_all("rust", &posts, |p| vec![p.text.clone()]).len(), 2);
+    }
+
+    #[test]
+    fn test_min_score_applies_before_top_k_limit() {
+        // Weak matches come first in input order but must not consume the limit
+        let posts = vec![
+            TestPost { text: "r u s t".to_string() },
+            TestPost { text: "r.u.s.t".to_string() },
+            TestPost { text: "rust".to_string() },
+            TestPost { text: "rust again".to_string() },
+        ];
+        let mut engine = SearchEngine::new().with_min_score(Some(100.0));
+        let options = MatchOptions::new(|p: &TestPost| vec![p.text.clone()]).with_limit(2);
+        let results = engine.search("rust", posts, options);
+        let texts: Vec<&str> = results.iter().map(|r| r.item.text.as_str()).collect();
+        assert_eq!(texts, vec!["rust", "rust again"]);
+    }
+
+    #[test]
+    fn test_min_score_keeps_operator_only_results() {
+        let posts = vec![TestPost { text: "anything".to_string() }];
+        let mut engine = SearchEngine::new().with_min_score(Some(100.0));
+        assert_eq!(engine.search_all("-spam", &posts, |p| vec![p.text.clone()]).len(), 1);
+    }
+
+    #[test]
+    fn test_case_sensitive_search() {
+        let posts = vec![
+            TestPost { text: "IT department".to_string() },
+            TestPost { text: "it happens".to_string() },
+        ];
+
+        let mut engine = SearchEngine::new();
+        assert_eq!(engine.search_all("IT", &posts, |p| vec![p.text.clone()]).len(), 2);
+
+        let mut engine = SearchEngine::new().with_case_sensitive(true);
+        let results = engine.search_all("IT", &posts, |p| vec![p.text.clone()]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.text, "IT department");
+
+        // Exclusions also respect case
+        let results = engine.search_all("-IT", &posts, |p| vec![p.text.clone()]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item.text, "it happens");
+    }
 }
@@ -4,7 +4,8 @@
 //! via the nucleo-matcher crate (used in Helix editor).
 
 use nucleo_matcher::{Config, Matcher};
-use unicode_normalization::UnicodeNormalization;
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Match result with score and position information
@@ -38,6 +39,7 @@ pub struct FuzzyMatcher {
     matcher: Matcher,
     #[allow(dead_code)]
     config: Config,
+    case_sensitive: bool,
 }
 
 impl Default for FuzzyMatcher {
@@ -49,17 +51,25 @@ impl Default for FuzzyMatcher {
 impl FuzzyMatcher {
     /// Create a new fuzzy matcher with default configuration
     pub fn new() -> Self {
-        let config = Config::DEFAULT;
-        let matcher = Matcher::new(config.clone());
+        Self::with_config(Config::DEFAULT)
+    }
 
-        Self { matcher, config }
+    /// Create a fuzzy matcher that only matches letters of the same case
+    pub fn case_sensitive() -> Self {
+        let mut config = Config::DEFAULT;
+        config.ignore_case = false;
+        Self::with_config(config)
     }
 
     /// Create a fuzzy matcher with custom configuration
-    #[allow(dead_code)]
     pub fn with_config(config: Config) -> Self {
         let matcher = Matcher::new(config.clone());
-        Self { matcher, config }
+        let case_sensitive = !config.ignore_case;
+        Self {
+            matcher,
+            config,
+            case_sensitive,
+        }
     }
 
     /// Perform fuzzy matching between needle and haystack
@@ -76,8 +86,8 @@ impl FuzzyMatcher {
 
         // Use nucleo-matcher for core fuzzy matching
         // Convert to Utf32String
-        let haystack_utf32 = nucleo_matcher::Utf32String::from(haystack_normalized.as_str());
-        let needle_utf32 = nucleo_matcher::Utf32String::from(needle_normalized.as_str());
+        let haystack_utf32 = nucleo_matcher::Utf32String::from(&*haystack_normalized);
+        let needle_utf32 = nucleo_matcher::Utf32String::from(&*needle_normalized);
 
         let score = self
             .matcher
@@ -113,12 +123,19 @@ impl FuzzyMatcher {
         }
     }
 
-    /// Check for exact substring match (case-insensitive)
+    /// Check for exact substring match (case-insensitive unless the matcher is case-sensitive)
     pub fn exact_match(&self, haystack: &str, needle: &str) -> bool {
         if needle.is_empty() {
             return false;
         }
 
+        // Compare in NFC so composed and decomposed accents match each other
+        let haystack = Self::normalize_for_matching(haystack);
+        let needle = Self::normalize_for_matching(needle);
+        if self.case_sensitive {
+            return haystack.contains(&*needle);
+        }
+
         let haystack_lower = haystack.to_lowercase();
         let needle_lower = needle.to_lowercase();
 
@@ -128,9 +145,12 @@ impl FuzzyMatcher {
     /// Normalize text for matching
     /// - Unicode NFC normalization
     /// - Strip non-alphanumeric for matching (but preserve for display)
-    fn normalize_for_matching(text: &str) -> String {
-        // Unicode normalization (NFC - canonical composition)
-        text.nfc().collect::<String>()
+    fn normalize_for_matching(text: &str) -> Cow<'_, str> {
+        // Unicode normalization (NFC - canonical composition); most text already is NFC
+        if is_nfc_quick(text.chars()) == IsNormalized::Yes {
+            return Cow::Borrowed(text);
+        }
+        Cow::Owned(text.nfc().collect::<String>())
     }
 
     /// Classify the type of match based on position in words
@@ -6,13 +6,17 @@ pub mod engine;
 pub mod fuzzy;
 pub mod parser;
 pub mod ranking;
+#[cfg(feature = "experimental-sentencepiece")]
+pub mod semantic;
+pub mod timestamp;
+pub mod top_k;
 
 #[allow(unused_imports)]
-pub use engine::{SearchEngine, SearchResult};
+pub use engine::{MatchOptions, SearchEngine, SearchResult};
 #[allow(unused_imports)]
 pub use fuzzy::FuzzyMatcher;
 #[allow(unused_imports)]
-pub use parser::{ParsedQuery, QueryParser};
+pub use parser::{FilterTarget, ParsedQuery, QueryFilter, QueryParser};
 #[allow(unused_imports)]
 pub use ranking::{MatchScore, ScoringWeights};
 
@@ -3,6 +3,7 @@
 //! Parses and tokenizes search queries, extracts quoted text,
 //! identifies special patterns, and filters stop words.
 
+use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Stop words that should be excluded from individual word searches
@@ -11,6 +12,69 @@ const STOP_WORDS: &[&str] = &[
     "its", "of", "on", "or", "that", "the", "to", "was", "will", "with", "i", "you",
 ];
 
+/// An item that structured query filters can be checked against
+pub trait FilterTarget {
+    /// Whether the item carries image embeds
+    fn has_image(&self) -> bool;
+    /// Whether the item carries a link card or link facet
+    fn has_link(&self) -> bool;
+    /// Whether the item quotes another record
+    fn has_quote(&self) -> bool;
+    /// Whether the item is tagged `tag` (lowercase, without `#`)
+    fn has_tag(&self, tag: &str) -> bool;
+}
+
+/// Structured filter extracted from a query: `has:` or a `#tag`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueryFilter {
+    /// `has:image` - post carries image embeds
+    Image,
+    /// `has:link` - post carries a link card or link facet
+    Link,
+    /// `has:quote` - post quotes another record
+    Quote,
+    /// `#tag` - post is tagged with the tag, by facet or in its text (lowercased, without `#`)
+    Tag(String),
+}
+
+impl QueryFilter {
+    /// Parse a single query token; unknown prefixes and values yield `None`
+    pub fn from_token(token: &str) -> Option<Self> {
+        let token = token.to_lowercase();
+        if let Some(tag) = token.strip_prefix('#') {
+            // A lone or doubled `#` is literal text
+            let literal = tag.is_empty() || tag.starts_with('#');
+            return (!literal).then(|| Self::Tag(tag.to_string()));
+        }
+        match token.as_str() {
+            "has:image" => Some(Self::Image),
+            "has:link" => Some(Self::Link),
+            "has:quote" => Some(Self::Quote),
+            _ => None,
+        }
+    }
+
+    /// Whether `item` satisfies this filter
+    pub fn matches<T: FilterTarget + ?Sized>(&self, item: &T) -> bool {
+        match self {
+            Self::Image => item.has_image(),
+            Self::Link => item.has_link(),
+            Self::Quote => item.has_quote(),
+            Self::Tag(tag) => item.has_tag(tag),
+        }
+    }
+
+    /// Query syntax for this filter, e.g. `has:image` or `#rust`
+    pub fn to_query(&self) -> String {
+        match self {
+            Self::Image => "has:image".to_string(),
+            Self::Link => "has:link".to_string(),
+            Self::Quote => "has:quote".to_string(),
+            Self::Tag(tag) => format!("#{}", tag),
+        }
+    }
+}
+
 /// Parsed and processed search query
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedQuery {
@@ -22,57 +86,243 @@ pub struct ParsedQuery {
     pub individual_words: Vec<String>,
     /// Exact match requirements from quoted text
     pub quoted_phrases: Vec<String>,
+    /// Query text outside quoted phrases (unbalanced quotes kept literally)
+    pub unquoted_text: String,
+    /// Structured filters (`has:image`, `#tag`, ...) removed from the free text
+    pub filters: Vec<QueryFilter>,
+    /// Free-text terms outside quotes that results should match
+    pub include: Vec<String>,
+    /// `-term` exclusions (lowercased unless case-sensitive); posts containing any are dropped
+    pub exclude: Vec<String>,
+    /// Whether terms keep their original casing when matched
+    pub case_sensitive: bool,
+}
+
+impl ParsedQuery {
+    /// True when the query contains no free text to match (e.g. `""` or whitespace)
+    pub fn is_empty(&self) -> bool {
+        self.quoted_phrases.is_empty() && self.unquoted_text.trim().is_empty()
+    }
+
+    /// True when the query can select anything: free text, `has:` filters or exclusions
+    pub fn is_searchable(&self) -> bool {
+        !self.is_empty() || !self.filters.is_empty() || !self.exclude.is_empty()
+    }
+
+    /// Terms to highlight in results: each quoted phrase as a single term,
+    /// followed by the included words outside quotes. Exclusions are never highlighted.
+    pub fn highlight_terms(&self) -> Vec<String> {
+        self.quoted_phrases
+            .iter()
+            .chain(self.include.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Whether any of the texts contains an excluded term (substring, case-insensitive
+    /// unless the query is case-sensitive)
+    pub fn is_excluded(&self, texts: &[String]) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        texts.iter().any(|text| {
+            if self.case_sensitive {
+                self.exclude.iter().any(|term| text.contains(term.as_str()))
+            } else {
+                let lower = text.to_lowercase();
+                self.exclude.iter().any(|term| lower.contains(term.as_str()))
+            }
+        })
+    }
+}
+
+/// Quoted parts of a query, split out by [`QueryParser::extract_quoted_phrases`]
+#[derive(Default)]
+struct QuotedParts {
+    /// Quoted phrases to match
+    phrases: Vec<String>,
+    /// `-"phrase"` exclusions, as written
+    excluded: Vec<String>,
+    /// Query text with the quoted parts removed
+    remaining: String,
+    /// Byte spans of the quoted phrases in the query, quotes included
+    spans: Vec<Range<usize>>,
+    /// Byte spans of the `-"phrase"` exclusions, `-` and quotes included
+    excluded_spans: Vec<Range<usize>>,
 }
 
 /// Query parser and preprocessor
 pub struct QueryParser;
 
 impl QueryParser {
-    /// Parse a search query into its components
+    /// Parse a search query into its components (case-insensitive)
     pub fn parse(query: &str) -> ParsedQuery {
+        Self::parse_with_case(query, false)
+    }
+
+    /// Parse a search query; when `case_sensitive`, words and exclusions keep their casing
+    pub fn parse_with_case(query: &str, case_sensitive: bool) -> ParsedQuery {
         let original = query.to_string();
 
         // Extract quoted phrases
-        let (quoted_phrases, query_without_quotes) = Self::extract_quoted_phrases(query);
+        let QuotedParts {
+            phrases: quoted_phrases,
+            excluded: excluded_phrases,
+            remaining: query_without_quotes,
+            spans: quoted_spans,
+            excluded_spans,
+        } = Self::extract_quoted_phrases(query);
+
+        // Pull recognized filters and exclusions out of the unquoted text
+        let (filters, mut exclude, query_without_quotes) =
+            Self::extract_operators(&query_without_quotes, case_sensitive);
+        for phrase in excluded_phrases {
+            let phrase = if case_sensitive {
+                phrase
+            } else {
+                phrase.to_lowercase()
+            };
+            if !exclude.contains(&phrase) {
+                exclude.push(phrase);
+            }
+        }
 
-        // The whole query is the original, but we'll use it for full-text matching
-        let whole_query = query.to_string();
+        // The whole query is the original (minus operators), used for full-text matching
+        let whole_query = if filters.is_empty() && exclude.is_empty() {
+            query.to_string()
+        } else {
+            Self::remove_operators(query, &quoted_spans, &excluded_spans)
+        };
 
         // Tokenize the query without quoted parts
-        let individual_words = Self::tokenize_and_filter(&query_without_quotes);
+        let individual_words = Self::tokenize_and_filter(&query_without_quotes, case_sensitive);
+        let include = query_without_quotes
+            .split_whitespace()
+            .map(|w| w.to_string())
+            .collect();
 
         ParsedQuery {
             original,
             whole_query,
             individual_words,
             quoted_phrases,
+            unquoted_text: query_without_quotes,
+            filters,
+            include,
+            exclude,
+            case_sensitive,
         }
     }
 
-    /// Extract quoted phrases (both single and double quotes)
-    /// Returns (quoted_phrases, query_with_quotes_removed)
-    fn extract_quoted_phrases(query: &str) -> (Vec<String>, String) {
-        let mut phrases = Vec::new();
-        let mut remaining = String::new();
-        let mut chars = query.chars().peekable();
+    /// Split recognized filter tokens and `-term` exclusions from the rest of the text.
+    /// Unknown `prefix:value` tokens, a lone `-` and `-` before a non-letter (`-5`) are
+    /// left in place as literal text.
+    fn extract_operators(text: &str, case_sensitive: bool) -> (Vec<QueryFilter>, Vec<String>, String) {
+        let mut filters = Vec::new();
+        let mut exclude = Vec::new();
+        let mut rest = Vec::new();
+
+        for token in text.split_whitespace() {
+            if let Some(filter) = QueryFilter::from_token(token) {
+                if !filters.contains(&filter) {
+                    filters.push(filter);
+                }
+            } else if let Some(term) = Self::excluded_word(token) {
+                let term = if case_sensitive {
+                    term.to_string()
+                } else {
+                    term.to_lowercase()
+                };
+                if !exclude.contains(&term) {
+                    exclude.push(term);
+                }
+            } else {
+                rest.push(token);
+            }
+        }
+
+        if filters.is_empty() && exclude.is_empty() {
+            (filters, exclude, text.to_string())
+        } else {
+            (filters, exclude, rest.join(" "))
+        }
+    }
 
-        while let Some(&ch) = chars.peek() {
+    /// The word excluded by a `-word` token; only a word starting with a letter counts,
+    /// so numbers like `-5` stay literal
+    fn excluded_word(token: &str) -> Option<&str> {
+        token
+            .strip_prefix('-')
+            .filter(|term| term.chars().next().is_some_and(char::is_alphabetic))
+    }
+
+    /// Cut the filter and exclusion tokens outside quoted phrases, and the `-"phrase"`
+    /// exclusions, out of `query`, keeping the rest of the text as written
+    fn remove_operators(
+        query: &str,
+        quoted_spans: &[Range<usize>],
+        excluded_spans: &[Range<usize>],
+    ) -> String {
+        let mut kept = String::new();
+        let mut copied_to = 0;
+        let mut tokens = query.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            let start = token.as_ptr() as usize - query.as_ptr() as usize;
+            let end = start + token.len();
+            let overlaps = |spans: &[Range<usize>]| spans.iter().any(|span| span.start < end && start < span.end);
+            let operator = QueryFilter::from_token(token).is_some() || Self::excluded_word(token).is_some();
+            if (operator && !overlaps(quoted_spans)) || overlaps(excluded_spans) {
+                kept.push_str(&query[copied_to..start]);
+                // Drop the whitespace after the token too, up to the next token
+                copied_to = tokens
+                    .peek()
+                    .map_or(query.len(), |next| next.as_ptr() as usize - query.as_ptr() as usize);
+            }
+        }
+        kept.push_str(&query[copied_to..]);
+        kept.trim().to_string()
+    }
+
+    /// Extract quoted phrases (both single and double quotes); a phrase opened by `-` at
+    /// the start of a token (`-"exact match"`) is an exclusion instead
+    fn extract_quoted_phrases(query: &str) -> QuotedParts {
+        let mut parts = QuotedParts::default();
+        let QuotedParts {
+            phrases,
+            excluded,
+            remaining,
+            spans,
+            excluded_spans,
+        } = &mut parts;
+        let mut chars = query.char_indices().peekable();
+
+        while let Some(&(start, ch)) = chars.peek() {
             if ch == '"' || ch == '\'' {
                 let quote_char = ch;
                 chars.next(); // consume opening quote
+                let negated = remaining.ends_with('-')
+                    && remaining[..remaining.len() - 1]
+                        .chars()
+                        .next_back()
+                        .is_none_or(char::is_whitespace);
 
                 let mut phrase = String::new();
                 let mut found_closing = false;
 
-                while let Some(&ch) = chars.peek() {
+                while let Some(&(at, ch)) = chars.peek() {
                     if ch == quote_char {
                         chars.next(); // consume closing quote
                         found_closing = true;
+                        if negated {
+                            excluded_spans.push(start - 1..at + ch.len_utf8());
+                        } else {
+                            spans.push(start..at + ch.len_utf8());
+                        }
                         break;
                     } else if ch == '\\' {
                         // Handle escaped quotes
                         chars.next();
-                        if let Some(&next_ch) = chars.peek() {
+                        if let Some(&(_, next_ch)) = chars.peek() {
                             phrase.push(next_ch);
                             chars.next();
                         }
@@ -82,7 +332,13 @@ impl QueryParser {
                     }
                 }
 
-                if found_closing && !phrase.is_empty() {
+                if found_closing && negated {
+                    // Drop the `-`; an empty `-""` excludes nothing
+                    remaining.pop();
+                    if !phrase.is_empty() {
+                        excluded.push(phrase);
+                    }
+                } else if found_closing && !phrase.is_empty() {
                     phrases.push(phrase);
                 } else if !found_closing {
                     // Unclosed quote - treat the quote literally
@@ -95,14 +351,21 @@ impl QueryParser {
             }
         }
 
-        (phrases, remaining)
+        parts
     }
 
     /// Tokenize text into words and filter stop words
-    fn tokenize_and_filter(text: &str) -> Vec<String> {
+    /// (stop words are recognized in any case; kept words are lowercased unless case-sensitive)
+    fn tokenize_and_filter(text: &str, case_sensitive: bool) -> Vec<String> {
         text.unicode_words()
-            .map(|w| w.to_lowercase())
-            .filter(|w| !Self::is_stop_word(w))
+            .filter(|w| !Self::is_stop_word(&w.to_lowercase()))
+            .map(|w| {
+                if case_sensitive {
+                    w.to_string()
+                } else {
+                    w.to_lowercase()
+                }
+            })
             .collect()
     }
 
@@ -186,4 +449,166 @@ mod tests {
         let parsed = QueryParser::parse("café résumé");
         assert_eq!(parsed.individual_words, vec!["café", "résumé"]);
     }
+
+    #[test]
+    fn test_quoted_phrase_rust_lang() {
+        let parsed = QueryParser::parse(r#""rust lang""#);
+        assert_eq!(parsed.quoted_phrases, vec!["rust lang"]);
+        assert!(parsed.individual_words.is_empty());
+        assert_eq!(parsed.highlight_terms(), vec!["rust lang"]);
+    }
+
+    #[test]
+    fn test_mixed_word_and_quoted_phrase() {
+        let parsed = QueryParser::parse(r#"foo "bar baz""#);
+        assert_eq!(parsed.quoted_phrases, vec!["bar baz"]);
+        assert_eq!(parsed.individual_words, vec!["foo"]);
+        assert_eq!(parsed.highlight_terms(), vec!["bar baz", "foo"]);
+    }
+
+    #[test]
+    fn test_empty_quotes_yield_empty_query() {
+        let parsed = QueryParser::parse(r#""""#);
+        assert!(parsed.quoted_phrases.is_empty());
+        assert!(parsed.individual_words.is_empty());
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn test_lone_quote_is_literal_in_highlight_terms() {
+        let parsed = QueryParser::parse(r#"say "hi"#);
+        assert!(parsed.quoted_phrases.is_empty());
+        assert_eq!(parsed.highlight_terms(), vec!["say", "\"hi"]);
+    }
+
+    #[test]
+    fn test_has_filters_extracted() {
+        let parsed = QueryParser::parse("has:image cats HAS:link");
+        assert_eq!(parsed.filters, vec![QueryFilter::Image, QueryFilter::Link]);
+        assert_eq!(parsed.individual_words, vec!["cats"]);
+        assert_eq!(parsed.whole_query, "cats");
+        assert_eq!(parsed.highlight_terms(), vec!["cats"]);
+    }
+
+    #[test]
+    fn test_hashtags_are_tag_filters() {
+        let parsed = QueryParser::parse("#Rust async");
+        assert_eq!(parsed.filters, vec![QueryFilter::Tag("rust".to_string())]);
+        assert_eq!(parsed.unquoted_text, "async");
+        assert_eq!(parsed.filters[0].to_query(), "#rust");
+
+        // A lone or doubled `#`, or one inside a word, is literal text
+        for query in ["#", "##rust", "C#"] {
+            let parsed = QueryParser::parse(query);
+            assert!(parsed.filters.is_empty(), "{}", query);
+            assert_eq!(parsed.unquoted_text, query);
+        }
+    }
+
+    #[test]
+    fn test_unknown_filter_prefix_is_literal() {
+        let parsed = QueryParser::parse("lang:en has:video");
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.whole_query, "lang:en has:video");
+        assert_eq!(parsed.highlight_terms(), vec!["lang:en", "has:video"]);
+    }
+
+    #[test]
+    fn test_filter_only_query_has_no_free_text() {
+        let parsed = QueryParser::parse("has:quote");
+        assert_eq!(parsed.filters, vec![QueryFilter::Quote]);
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn test_exclusion_terms_split_from_includes() {
+        let parsed = QueryParser::parse("rust -crypto -NFT");
+        assert_eq!(parsed.include, vec!["rust"]);
+        assert_eq!(parsed.exclude, vec!["crypto", "nft"]);
+        assert_eq!(parsed.individual_words, vec!["rust"]);
+        assert_eq!(parsed.whole_query, "rust");
+    }
+
+    #[test]
+    fn test_whole_query_keeps_text_around_operators() {
+        let parsed = QueryParser::parse("rust  lang -crypto");
+        assert_eq!(parsed.whole_query, "rust  lang");
+
+        let parsed = QueryParser::parse("-crypto   rust\tlang has:link");
+        assert_eq!(parsed.whole_query, "rust\tlang");
+
+        // Operators inside quoted phrases are text
+        let parsed = QueryParser::parse(r#""hello -world" -spam"#);
+        assert_eq!(parsed.exclude, vec!["spam"]);
+        assert_eq!(parsed.whole_query, r#""hello -world""#);
+    }
+
+    #[test]
+    fn test_exclusion_only_query() {
+        let parsed = QueryParser::parse("-spam");
+        assert!(parsed.include.is_empty());
+        assert_eq!(parsed.exclude, vec!["spam"]);
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn test_lone_minus_is_literal() {
+        let parsed = QueryParser::parse("a - b");
+        assert!(parsed.exclude.is_empty());
+        assert_eq!(parsed.include, vec!["a", "-", "b"]);
+    }
+
+    #[test]
+    fn test_minus_before_non_letter_is_literal() {
+        let parsed = QueryParser::parse("temperature -5 degrees -#tag");
+        assert!(parsed.exclude.is_empty());
+        assert_eq!(parsed.include, vec!["temperature", "-5", "degrees", "-#tag"]);
+        assert_eq!(parsed.whole_query, "temperature -5 degrees -#tag");
+    }
+
+    #[test]
+    fn test_quoted_exclusion() {
+        let parsed = QueryParser::parse(r#"rust -"Crypto Bros" "web dev" a-"b""#);
+        assert_eq!(parsed.exclude, vec!["crypto bros"]);
+        assert_eq!(parsed.quoted_phrases, vec!["web dev", "b"]);
+        assert_eq!(parsed.whole_query, r#"rust "web dev" a-"b""#);
+        assert!(parsed.is_excluded(&["no crypto bros here".to_string()]));
+        assert!(!parsed.highlight_terms().contains(&"crypto bros".to_string()));
+
+        // Only an exclusion is still searchable
+        let parsed = QueryParser::parse(r#"-"crypto bros""#);
+        assert!(parsed.is_empty());
+        assert!(parsed.is_searchable());
+    }
+
+    #[test]
+    fn test_excluded_terms_never_highlighted() {
+        let parsed = QueryParser::parse(r#""rust lang" -crypto async"#);
+        assert_eq!(parsed.highlight_terms(), vec!["rust lang", "async"]);
+    }
+
+    #[test]
+    fn test_is_excluded_case_insensitive_substring() {
+        let parsed = QueryParser::parse("rust -crypto");
+        assert!(parsed.is_excluded(&["All about CRYPTOcurrency".to_string()]));
+        assert!(!parsed.is_excluded(&["All about rust".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_with_case_keeps_casing() {
+        let parsed = QueryParser::parse_with_case("NASA Launch -SpaceX", true);
+        assert_eq!(parsed.individual_words, vec!["NASA", "Launch"]);
+        assert_eq!(parsed.exclude, vec!["SpaceX"]);
+        assert!(parsed.case_sensitive);
+
+        let parsed = QueryParser::parse("NASA Launch -SpaceX");
+        assert_eq!(parsed.individual_words, vec!["nasa", "launch"]);
+        assert_eq!(parsed.exclude, vec!["spacex"]);
+
+        // Stop words are recognized regardless of case
+        assert!(QueryParser::parse_with_case("The IT", true)
+            .individual_words
+            .iter()
+            .all(|w| w != "The"));
+    }
 }
@@ -1,3 +1,5 @@
+use crate::search::SearchEngine;
+use crate::tools::post_format::highlight_ranges;
 use proptest::prelude::*;
 
 // Local copy of the highlighting merge logic (keeps tests self-contained).
@@ -84,3 +86,56 @@ proptest! {
         prop_assert!(!md_double.contains(&expected_double));
     }
 }
+
+// Canonically equivalent spellings of accented letters: composed, decomposed, and for
+// stacked marks the decomposition with its combining marks out of canonical order
+const ACCENTED: &[&[&str]] = &[
+    &["\u{E9}", "e\u{301}"],
+    &["\u{C5}", "A\u{30A}"],
+    &["\u{F1}", "n\u{303}"],
+    &["\u{1EAD}", "a\u{323}\u{302}", "a\u{302}\u{323}"],
+    &["\u{1EDB}", "o\u{31B}\u{301}", "o\u{301}\u{31B}"],
+    // No precomposed form: the base letter keeps its mark in every spelling
+    &["q\u{301}"],
+];
+
+fn spell(letters: &[(usize, usize)]) -> String {
+    letters
+        .iter()
+        .map(|&(letter, form)| {
+            let forms = ACCENTED[letter % ACCENTED.len()];
+            forms[form % forms.len()]
+        })
+        .collect()
+}
+
+// Property test: a word matches and highlights whichever equivalent spelling the text and
+// the query use, with the bold span covering exactly the word in the original text
+proptest! {
+    #[test]
+    fn equivalent_spellings_match_and_highlight(
+        letters in proptest::collection::vec((0usize..6, 0usize..3), 1..5),
+        text_forms in proptest::collection::vec(0usize..3, 5),
+        prefix in "[a-z]{1,6}",
+    ) {
+        let query = spell(&letters);
+        let word = spell(
+            &letters
+                .iter()
+                .zip(&text_forms)
+                .map(|(&(letter, _), &form)| (letter, form))
+                .collect::<Vec<_>>(),
+        );
+        let text = format!("{} {} x", prefix, word);
+
+        let mut engine = SearchEngine::new();
+        let results = engine.search_all(&query, std::slice::from_ref(&text), |t| vec![t.clone()]);
+        prop_assert_eq!(results.len(), 1);
+
+        let start = prefix.len() + 1;
+        for case_sensitive in [false, true] {
+            let ranges = highlight_ranges(&text, std::slice::from_ref(&query), case_sensitive);
+            prop_assert_eq!(ranges, vec![(start, start + word.len())]);
+        }
+    }
+}
@@ -3,6 +3,30 @@
 //! Implements multi-signal scoring for search results with configurable weights.
 
 use super::fuzzy::{FuzzyMatch, MatchType};
+use chrono::{DateTime, Utc};
+use std::ops::RangeInclusive;
+
+/// Accepted range for `ScoringWeights::exact_match_bonus`
+pub const EXACT_BONUS_RANGE: RangeInclusive<f64> = 1.0..=100.0;
+/// Accepted range for `PositionMultipliers::word_start` (prefix matches)
+pub const PREFIX_BONUS_RANGE: RangeInclusive<f64> = 0.0..=2.0;
+/// Accepted range for `ScoringWeights::recency_weight`
+pub const RECENCY_WEIGHT_RANGE: RangeInclusive<f64> = 0.0..=10.0;
+/// Accepted range for the semantic search blend weight
+pub const SEMANTIC_WEIGHT_RANGE: RangeInclusive<f64> = 0.0..=1.0;
+/// Share of the semantic similarity in a semantic search's ranking
+pub const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.5;
+/// Fuzzy matches re-ranked per search, so close paraphrases further down can still surface
+pub const SEMANTIC_CANDIDATES: usize = 200;
+
+/// Clamp a user-supplied weight into `range`; non-finite values fall back to `default`
+pub fn clamp_weight(value: f64, range: &RangeInclusive<f64>, default: f64) -> f64 {
+    if value.is_finite() {
+        value.clamp(*range.start(), *range.end())
+    } else {
+        default
+    }
+}
 
 /// Scoring weights for different match signals
 #[derive(Debug, Clone)]
@@ -17,6 +41,10 @@ pub struct ScoringWeights {
     pub exact_match_bonus: f64,
     /// Bonus for exact Unicode match (vs normalized)
     pub unicode_exact_bonus: f64,
+    /// How strongly newer items are boosted (0 disables recency ranking)
+    pub recency_weight: f64,
+    /// Age in days at which the recency boost halves
+    pub recency_half_life_days: f64,
 }
 
 /// Position-based multipliers for different match types
@@ -42,6 +70,8 @@ impl Default for ScoringWeights {
             max_proximity_boost: 1.0,
             exact_match_bonus: 10.0,
             unicode_exact_bonus: 1.2,
+            recency_weight: 0.0,
+            recency_half_life_days: 30.0,
         }
     }
 }
@@ -133,6 +163,19 @@ impl MatchScore {
         }
     }
 
+    /// Neutral score for items matched by filters alone (no free-text terms)
+    pub fn neutral() -> Self {
+        Self {
+            base_score: 0.0,
+            position_weight: 0.0,
+            proximity_boost: 0.0,
+            is_exact_match: false,
+            is_exact_unicode: false,
+            final_score: 0.0,
+            match_type: MatchType::FullWord,
+        }
+    }
+
     /// Create a score for exact match (highest possible)
     pub fn exact_match(_haystack_len: usize, weights: &ScoringWeights) -> Self {
         let base_score = 1000.0; // High base score for exact match
@@ -150,6 +193,39 @@ impl MatchScore {
             match_type: MatchType::FullWord,
         }
     }
+
+    /// Apply the recency component for an item created at `created_at`.
+    ///
+    /// The multiplier is `1 + recency_weight * recency_factor(age)`, so newer items
+    /// gain more. A missing (unparseable) timestamp is neutral and leaves the score as is.
+    pub fn apply_recency(
+        &mut self,
+        created_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        weights: &ScoringWeights,
+    ) {
+        if weights.recency_weight <= 0.0 {
+            return;
+        }
+        let Some(created) = created_at else {
+            return;
+        };
+
+        let age_days = (now - created).num_seconds() as f64 / 86_400.0;
+        let multiplier =
+            1.0 + weights.recency_weight * recency_factor(age_days, weights.recency_half_life_days);
+        self.final_score *= multiplier;
+    }
+}
+
+/// Recency factor in (0, 1]: 1 for items created now, halving every
+/// `half_life_days`. Future timestamps count as brand new; a non-positive
+/// half-life disables decay.
+pub fn recency_factor(age_days: f64, half_life_days: f64) -> f64 {
+    if !(half_life_days.is_finite() && half_life_days > 0.0) {
+        return 1.0;
+    }
+    0.5f64.powf(age_days.max(0.0) / half_life_days)
 }
 
 /// Normalize scores to 0-1 range for comparison across different sources
@@ -329,8 +405,10 @@ mod tests {
     #[test]
     fn test_calculate_unicode_exact_bonus() {
         // Ensure that the unicode exact flag increases the final score
-        let mut weights = ScoringWeights::default();
-        weights.unicode_exact_bonus = 2.0;
+        let weights = ScoringWeights {
+            unicode_exact_bonus: 2.0,
+            ..ScoringWeights::default()
+        };
 
         let fuzzy = super::super::fuzzy::FuzzyMatch {
             score: 50,
@@ -369,4 +447,63 @@ mod tests {
         // Given same base score, end should rank higher than middle because multiplier is larger
         assert!(s_end.final_score > s_mid.final_score);
     }
+
+    #[test]
+    fn test_clamp_weight() {
+        assert_eq!(clamp_weight(500.0, &EXACT_BONUS_RANGE, 10.0), 100.0);
+        assert_eq!(clamp_weight(-1.0, &RECENCY_WEIGHT_RANGE, 0.0), 0.0);
+        assert_eq!(clamp_weight(f64::NAN, &PREFIX_BONUS_RANGE, 0.8), 0.8);
+        assert_eq!(clamp_weight(1.5, &PREFIX_BONUS_RANGE, 0.8), 1.5);
+    }
+
+    #[test]
+    fn test_recency_factor_halves_per_half_life() {
+        assert_eq!(recency_factor(0.0, 30.0), 1.0);
+        assert!((recency_factor(30.0, 30.0) - 0.5).abs() < 1e-9);
+        assert!((recency_factor(14.0, 7.0) - 0.25).abs() < 1e-9);
+        assert_eq!(recency_factor(-5.0, 30.0), 1.0);
+        assert_eq!(recency_factor(100.0, 0.0), 1.0);
+    }
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_recency_prefers_newer_of_equal_matches() {
+        let weights = ScoringWeights {
+            recency_weight: 1.0,
+            recency_half_life_days: 7.0,
+            ..ScoringWeights::default()
+        };
+        let now = ts("2024-06-30T00:00:00Z");
+
+        let mut older = MatchScore::exact_match(10, &weights);
+        let mut newer = MatchScore::exact_match(10, &weights);
+        older.apply_recency(Some(ts("2024-06-01T00:00:00Z")), now, &weights);
+        newer.apply_recency(Some(ts("2024-06-29T00:00:00Z")), now, &weights);
+
+        assert!(newer.final_score > older.final_score);
+    }
+
+    #[test]
+    fn test_recency_unparseable_timestamp_is_neutral() {
+        let weights = ScoringWeights {
+            recency_weight: 2.0,
+            ..ScoringWeights::default()
+        };
+        let mut score = MatchScore::exact_match(10, &weights);
+        let before = score.final_score;
+        score.apply_recency(None, ts("2024-06-30T00:00:00Z"), &weights);
+        assert_eq!(score.final_score, before);
+    }
+
+    #[test]
+    fn test_recency_disabled_by_default() {
+        let weights = ScoringWeights::default();
+        let mut score = MatchScore::exact_match(10, &weights);
+        let before = score.final_score;
+        score.apply_recency(Some(ts("2024-06-29T00:00:00Z")), ts("2024-06-30T00:00:00Z"), &weights);
+        assert_eq!(score.final_score, before);
+    }
 }
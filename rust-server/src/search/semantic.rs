@@ -0,0 +1,177 @@
+//! Semantic re-ranking (experimental)
+//!
+//! Blends fuzzy relevance with the cosine similarity between the averaged token embeddings
+//! of the query and of each result. Only built with the `experimental-sentencepiece` feature;
+//! the tokenizer and embeddings are read from the `models-dir` config directory.
+
+use super::engine::SearchResult;
+use crate::config::config;
+use crate::embeddings::{embed_text, load_embeddings, EmbeddingTable, DEFAULT_BITS};
+use crate::error::AppError;
+use crate::sentencepiece::tokenizer::SentencePieceProcessor;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Models are loaded once per process; a failed load is remembered and reported every time
+static RANKER: OnceLock<Result<Mutex<SemanticRanker>, String>> = OnceLock::new();
+
+/// Tokenizer and embedding table used to compare texts by meaning
+pub struct SemanticRanker {
+    tokenizer: SentencePieceProcessor,
+    table: EmbeddingTable,
+}
+
+impl SemanticRanker {
+    /// Load the Gemma tokenizer from `dir` and the embedding table quantized to `bits`
+    pub fn load(dir: &Path, bits: u32) -> Result<Self, AppError> {
+        let path = dir.join("tokenizer.model");
+        let tokenizer = SentencePieceProcessor::from_file(&path).map_err(|e| {
+            AppError::ConfigError(format!("Failed to load tokenizer {}: {}", path.display(), e))
+        })?;
+        let table = load_embeddings(dir, bits)
+            .map_err(|e| AppError::ConfigError(format!("Failed to load embeddings: {}", e)))?;
+        Ok(Self { tokenizer, table })
+    }
+
+    /// Unit-length embedding of `text` (all zeros when nothing could be tokenized)
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        embed_text(text, &self.tokenizer, &self.table)
+    }
+
+    /// Reorder fuzzy `results` by their blend with the similarity to `query`; see [`blend`]
+    pub fn rerank<T, F>(
+        &self,
+        results: &mut Vec<SearchResult<T>>,
+        query: &str,
+        weight: f64,
+        text: F,
+    ) where
+        F: Fn(&T) -> Vec<String>,
+    {
+        let query = self.embed(query);
+        blend(results, weight, |item| {
+            cosine_similarity(&query, &self.embed(&text(item).join("\n")))
+        });
+    }
+}
+
+/// Run `f` with the shared ranker, loading the configured models on first use
+pub fn with_ranker<R>(f: impl FnOnce(&SemanticRanker) -> R) -> Result<R, AppError> {
+    let ranker = RANKER.get_or_init(|| {
+        let config = config();
+        let dir = config
+            .models_dir
+            .clone()
+            .or_else(default_models_dir)
+            .ok_or_else(|| "No models-dir configured and no data directory".to_string())?;
+        let bits = config.embedding_bits.unwrap_or(DEFAULT_BITS);
+        SemanticRanker::load(&dir, bits)
+            .map(Mutex::new)
+            .map_err(|e| e.to_string())
+    });
+    match ranker {
+        Ok(ranker) => Ok(f(&ranker.lock().unwrap_or_else(|e| e.into_inner()))),
+        Err(e) => Err(AppError::ConfigError(e.clone())),
+    }
+}
+
+/// `{OS data dir}/autoreply/gemma-data`, searched when `models-dir` is not configured
+fn default_models_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("autoreply").join("gemma-data"))
+}
+
+/// Cosine of the angle between two vectors; 0 when either is all zeros
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(x), f64::from(y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Reorder `results` by `(1 - weight) * fuzzy + weight * similarity`.
+///
+/// Fuzzy scores are scaled by the best one and negative similarities count as 0, so both
+/// parts lie in 0..=1. Equal blends keep their fuzzy order.
+pub fn blend<T>(
+    results: &mut Vec<SearchResult<T>>,
+    weight: f64,
+    mut similarity: impl FnMut(&T) -> f64,
+) {
+    let best = results
+        .iter()
+        .map(|r| r.score.final_score)
+        .fold(0.0, f64::max);
+    let mut blended: Vec<(f64, SearchResult<T>)> = results
+        .drain(..)
+        .map(|result| {
+            let fuzzy = if best > 0.0 {
+                result.score.final_score / best
+            } else {
+                0.0
+            };
+            let semantic = similarity(&result.item).max(0.0);
+            ((1.0 - weight) * fuzzy + weight * semantic, result)
+        })
+        .collect();
+    blended.sort_by(|a, b| b.0.total_cmp(&a.0));
+    results.extend(blended.into_iter().map(|(_, result)| result));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::embeddings_file_name;
+    use crate::search::ranking::MatchScore;
+
+    fn result(text: &str, score: f64) -> SearchResult<String> {
+        let mut match_score = MatchScore::neutral();
+        match_score.final_score = score;
+        SearchResult {
+            item: text.to_string(),
+            score: match_score,
+            matched_terms: vec![],
+        }
+    }
+
+    #[test]
+    fn test_blend_weights_similarity_against_fuzzy() {
+        let similarity = |item: &String| if item == "b" { 1.0 } else { 0.0 };
+
+        let mut results = vec![result("a", 10.0), result("b", 8.0)];
+        blend(&mut results, 0.0, similarity);
+        assert_eq!(results[0].item, "a");
+
+        blend(&mut results, 0.5, similarity);
+        assert_eq!(results[0].item, "b");
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-9);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    #[ignore = "needs generated models in gemma-data"]
+    fn test_paraphrase_ranks_above_unrelated_post() {
+        // The models are downloaded and generated separately, not checked in
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../gemma-data");
+        let ranker = SemanticRanker::load(&dir, DEFAULT_BITS).expect("load semantic models");
+        let mut results = vec![
+            result("Quarterly earnings fell short of analyst estimates", 1.0),
+            result("My kitten naps on the sofa all afternoon", 1.0),
+        ];
+        ranker.rerank(&mut results, "cat sleeping on the couch", 0.5, |text| {
+            vec![text.clone()]
+        });
+        assert_eq!(results[0].item, "My kitten naps on the sofa all afternoon");
+    }
+}
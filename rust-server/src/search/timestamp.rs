@@ -0,0 +1,65 @@
+//! Timestamp normalization
+//!
+//! Record timestamps (`createdAt`) come from many clients and are not always
+//! strict RFC 3339. All date comparisons go through `parse_timestamp` so that
+//! filters, ordering and ranking agree on what a timestamp means.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+/// Parse a record timestamp into UTC.
+///
+/// Accepts RFC 3339 (`2024-01-15T10:00:00.123Z`, `+02:00` offsets), date-times
+/// without a timezone (treated as UTC) and bare dates (`2024-01-15`, midnight UTC).
+/// Returns `None` for anything else.
+pub fn parse_timestamp(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(naive.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rfc3339_variants() {
+        let z = parse_timestamp("2024-01-15T10:00:00.123Z").unwrap();
+        let offset = parse_timestamp("2024-01-15T12:00:00.123+02:00").unwrap();
+        assert_eq!(z, offset);
+    }
+
+    #[test]
+    fn test_parse_without_timezone_is_utc() {
+        let naive = parse_timestamp("2024-01-15T10:00:00").unwrap();
+        let utc = parse_timestamp("2024-01-15T10:00:00Z").unwrap();
+        assert_eq!(naive, utc);
+    }
+
+    #[test]
+    fn test_parse_bare_date_is_midnight() {
+        let date = parse_timestamp("2024-01-15").unwrap();
+        assert_eq!(date, parse_timestamp("2024-01-15T00:00:00Z").unwrap());
+    }
+
+    #[test]
+    fn test_parse_garbage() {
+        assert!(parse_timestamp("").is_none());
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+}
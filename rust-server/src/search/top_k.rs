@@ -0,0 +1,191 @@
+//! Bounded top-K result set
+//!
+//! Keeps the K best search results seen so far, so a stream of items can be
+//! ranked in O(K) memory. Ties go to the smaller tie key, then keep input order,
+//! matching a stable sort.
+
+use super::engine::SearchResult;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// A result tagged with its tie key and position in the input stream
+struct Ranked<T> {
+    seq: usize,
+    tie_key: String,
+    result: SearchResult<T>,
+}
+
+impl<T> Ranked<T> {
+    /// Higher score is better; on equal scores the smaller tie key, then the earlier item
+    fn rank_cmp(&self, other: &Self) -> Ordering {
+        self.result
+            .score
+            .final_score
+            .total_cmp(&other.result.score.final_score)
+            .then_with(|| other.tie_key.cmp(&self.tie_key))
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<T> PartialEq for Ranked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank_cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Ranked<T> {}
+
+impl<T> PartialOrd for Ranked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Ranked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank_cmp(other)
+    }
+}
+
+/// The best `limit` results pushed so far
+pub struct TopK<T> {
+    limit: usize,
+    next_seq: usize,
+    // Min-heap: the root is the worst result currently kept
+    heap: BinaryHeap<Reverse<Ranked<T>>>,
+}
+
+impl<T> TopK<T> {
+    /// Create an empty set that keeps at most `limit` results
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            next_seq: 0,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Offer a result; it is kept only if it ranks among the best `limit` so far.
+    /// Score ties go to the smaller `tie_key` before input order, so the ranking
+    /// does not depend on the order items arrive in
+    pub fn push(&mut self, result: SearchResult<T>, tie_key: String) {
+        let ranked = Ranked {
+            seq: self.next_seq,
+            tie_key,
+            result,
+        };
+        self.next_seq += 1;
+
+        if self.heap.len() < self.limit {
+            self.heap.push(Reverse(ranked));
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if ranked > worst.0 {
+                *worst = Reverse(ranked);
+            }
+        }
+    }
+
+    /// Number of results currently kept
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Whether no results are kept
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Kept results, best first
+    pub fn into_sorted_vec(self) -> Vec<SearchResult<T>> {
+        // Ascending order of Reverse<_> is descending rank
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ranked)| ranked.result)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::MatchScore;
+
+    fn result(id: u32, score: f64) -> SearchResult<u32> {
+        let mut match_score = MatchScore::neutral();
+        match_score.final_score = score;
+        SearchResult {
+            item: id,
+            score: match_score,
+            matched_terms: Vec::new(),
+        }
+    }
+
+    fn ids(top: TopK<u32>) -> Vec<u32> {
+        top.into_sorted_vec().into_iter().map(|r| r.item).collect()
+    }
+
+    #[test]
+    fn test_keeps_best_k() {
+        let mut top = TopK::new(3);
+        for (id, score) in [(1, 5.0), (2, 9.0), (3, 1.0), (4, 7.0), (5, 3.0), (6, 8.0)] {
+            top.push(result(id, score), String::new());
+        }
+        assert_eq!(top.len(), 3);
+        assert_eq!(ids(top), vec![2, 6, 4]);
+    }
+
+    #[test]
+    fn test_ties_keep_input_order() {
+        let mut top = TopK::new(3);
+        for id in 1..=5 {
+            top.push(result(id, 1.0), String::new());
+        }
+        assert_eq!(ids(top), vec![1, 2, 3]);
+
+        let mut top = TopK::new(2);
+        for (id, score) in [(1, 1.0), (2, 2.0), (3, 2.0), (4, 2.0)] {
+            top.push(result(id, score), String::new());
+        }
+        assert_eq!(ids(top), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_matches_full_stable_sort() {
+        let scores = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0, 5.0];
+        let mut expected: Vec<(u32, f64)> = scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i as u32, *s))
+            .collect();
+        expected.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        for k in 0..=scores.len() + 1 {
+            let mut top = TopK::new(k);
+            for (i, s) in scores.iter().enumerate() {
+                top.push(result(i as u32, *s), String::new());
+            }
+            let want: Vec<u32> = expected.iter().take(k).map(|(i, _)| *i).collect();
+            assert_eq!(ids(top), want, "k = {}", k);
+        }
+    }
+
+    #[test]
+    fn test_tie_key_orders_equal_scores() {
+        let mut top = TopK::new(3);
+        for (id, key) in [(1, "c"), (2, "a"), (3, "d"), (4, "b")] {
+            top.push(result(id, 1.0), key.to_string());
+        }
+        top.push(result(5, 2.0), "z".to_string());
+        assert_eq!(ids(top), vec![5, 2, 4]);
+    }
+
+    #[test]
+    fn test_zero_limit_keeps_nothing() {
+        let mut top = TopK::new(0);
+        top.push(result(1, 10.0), String::new());
+        assert!(top.is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+//! Batched repo writes via `com.atproto.repo.applyWrites`
+//!
+//! A batch is applied atomically: one invalid write fails all of them. Writes are therefore
+//! checked up front so the caller learns which write is at fault before anything is sent,
+//! and long lists are split into batches the PDS accepts.
+
+use crate::auth::Session;
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use tracing::debug;
+
+/// Most writes the reference PDS accepts in one applyWrites call
+pub const MAX_WRITES_PER_BATCH: usize = 200;
+
+/// One create or delete in a batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoWrite {
+    /// Create a record; without an rkey the PDS assigns one (and nothing else in the
+    /// batch can refer to it)
+    Create {
+        collection: String,
+        rkey: Option<String>,
+        value: Value,
+    },
+    Delete { collection: String, rkey: String },
+}
+
+impl RepoWrite {
+    fn action(&self) -> &'static str {
+        match self {
+            Self::Create { .. } => "create",
+            Self::Delete { .. } => "delete",
+        }
+    }
+
+    fn collection(&self) -> &str {
+        match self {
+            Self::Create { collection, .. }
+            | Self::Delete { collection, .. } => collection,
+        }
+    }
+
+    fn rkey(&self) -> Option<&str> {
+        match self {
+            Self::Create { rkey, .. } => rkey.as_deref(),
+            Self::Delete { rkey, .. } => Some(rkey),
+        }
+    }
+
+    fn value(&self) -> Option<&Value> {
+        match self {
+            Self::Create { value, .. } => Some(value),
+            Self::Delete { .. } => None,
+        }
+    }
+
+    /// `at://` URI of the written record, when its rkey is known up front
+    pub fn uri(&self, did: &str) -> Option<String> {
+        self.rkey()
+            .map(|rkey| format!("at://{}/{}/{}", did, self.collection(), rkey))
+    }
+
+    /// Short description used in error messages, e.g. `delete app.bsky.feed.post/3k`
+    fn describe(&self) -> String {
+        format!(
+            "{} {}/{}",
+            self.action(),
+            self.collection(),
+            self.rkey().unwrap_or("(new)")
+        )
+    }
+
+    /// The applyWrites union member for this write
+    fn to_json(&self) -> Value {
+        let mut write = serde_json::json!({
+            "$type": format!("com.atproto.repo.applyWrites#{}", self.action()),
+            "collection": self.collection(),
+        });
+        if let Some(rkey) = self.rkey() {
+            write["rkey"] = Value::from(rkey);
+        }
+        if let Some(value) = self.value() {
+            write["value"] = value.clone();
+        }
+        write
+    }
+}
+
+/// Outcome of one write, in input order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteResult {
+    /// URI of the record, when the PDS reported it or the rkey was given
+    pub uri: Option<String>,
+    /// CID of the created record (deletes have none)
+    pub cid: Option<String>,
+}
+
+/// Check every write before anything is sent, naming the first one the PDS would reject
+///
+/// Besides per-write checks (collection NSID, rkey syntax, record `$type`), this rejects
+/// two writes to the same record and strong refs to a record created later in the list.
+pub fn validate_writes(did: &str, writes: &[RepoWrite]) -> Result<(), AppError> {
+    if writes.is_empty() {
+        return Err(AppError::InvalidInput("No writes to apply".to_string()));
+    }
+
+    let created_at: HashMap<String, usize> = writes
+        .iter()
+        .enumerate()
+        .filter(|(_, write)| matches!(write, RepoWrite::Create { .. }))
+        .filter_map(|(i, write)| write.uri(did).map(|uri| (uri, i)))
+        .collect();
+
+    let mut targets = HashSet::new();
+    for (i, write) in writes.iter().enumerate() {
+        let fail = |reason: String| {
+            AppError::InvalidInput(format!("Write {} ({}): {}", i + 1, write.describe(), reason))
+        };
+
+        if !is_valid_nsid(write.collection()) {
+            return Err(fail("collection is not a valid NSID".to_string()));
+        }
+        if let Some(rkey) = write.rkey() {
+            if !is_valid_rkey(rkey) {
+                return Err(fail("invalid record key".to_string()));
+            }
+        }
+        if let Some(uri) = write.uri(did) {
+            if !targets.insert(uri) {
+                return Err(fail("the same record is written more than once".to_string()));
+            }
+        }
+        if let Some(value) = write.value() {
+            if value.get("$type").and_then(Value::as_str) != Some(write.collection()) {
+                return Err(fail(format!(
+                    "record $type must be {}",
+                    write.collection()
+                )));
+            }
+            for uri in strong_ref_uris(value) {
+                if let Some(&creator) = created_at.get(uri) {
+                    if creator > i {
+                        return Err(fail(format!(
+                            "refers to {} before write {} creates it",
+                            uri,
+                            creator + 1
+                        )));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Split writes into batches of at most `MAX_WRITES_PER_BATCH`
+///
+/// A write that refers to a record created earlier in the same batch starts a new batch:
+/// the referenced record's CID is only known once its batch has been applied.
+pub fn plan_batches(did: &str, writes: &[RepoWrite]) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut created_in_batch = HashSet::new();
+    for (i, write) in writes.iter().enumerate() {
+        let depends_on_batch = write
+            .value()
+            .is_some_and(|value| strong_ref_uris(value).any(|uri| created_in_batch.contains(uri)));
+        if i > start && (i - start == MAX_WRITES_PER_BATCH || depends_on_batch) {
+            batches.push(start..i);
+            start = i;
+            created_in_batch.clear();
+        }
+        if let (RepoWrite::Create { .. }, Some(uri)) = (write, write.uri(did)) {
+            created_in_batch.insert(uri);
+        }
+    }
+    if start < writes.len() {
+        batches.push(start..writes.len());
+    }
+    batches
+}
+
+/// Validate, batch and apply writes to the session's repo
+///
+/// Strong refs (`{uri, cid}`) to records created by an earlier batch get that record's CID
+/// filled in before their own batch is sent. If a batch fails, the error says which writes
+/// had already been applied.
+pub async fn apply_writes(
+    client: &reqwest::Client,
+    session: &Session,
+    mut writes: Vec<RepoWrite>,
+) -> Result<Vec<WriteResult>, AppError> {
+    validate_writes(&session.did, &writes)?;
+
+    let url = format!("{}/xrpc/com.atproto.repo.applyWrites", session.service);
+    let batches = plan_batches(&session.did, &writes);
+    let mut results = Vec::with_capacity(writes.len());
+    let mut created_cids: HashMap<String, String> = HashMap::new();
+
+    for (n, batch) in batches.iter().enumerate() {
+        for write in &mut writes[batch.clone()] {
+            if let RepoWrite::Create { value, .. } = write {
+                fill_strong_ref_cids(value, &created_cids);
+            }
+        }
+
+        let body = serde_json::json!({
+            "repo": session.did,
+            "validate": true,
+            "writes": writes[batch.clone()].iter().map(RepoWrite::to_json).collect::<Vec<_>>(),
+        });
+        debug!(
+            "applyWrites batch {} of {}: writes {}-{}",
+            n + 1,
+            batches.len(),
+            batch.start + 1,
+            batch.end
+        );
+
+        let failed = |reason: String| {
+            let applied = if batch.start == 0 {
+                "no writes were applied".to_string()
+            } else {
+                format!("writes 1-{} were already applied", batch.start)
+            };
+            AppError::NetworkError(format!(
+                "applyWrites batch {} of {} (writes {}-{}) failed: {}; {}",
+                n + 1,
+                batches.len(),
+                batch.start + 1,
+                batch.end,
+                reason,
+                applied
+            ))
+        };
+
+        let response = client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", session.access_jwt))
+            .json(&body)
+            .send_xrpc()
+            .await
+            .map_err(|e| failed(format!("request failed: {}", e)))?;
+
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(failed(format!("status {}: {}", status, error_text)));
+        }
+
+        let response: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::ParseError(format!("Failed to parse applyWrites response: {}", e)))?;
+
+        for result in batch_results(&session.did, &writes[batch.clone()], &response) {
+            if let (Some(uri), Some(cid)) = (&result.uri, &result.cid) {
+                created_cids.insert(uri.clone(), cid.clone());
+            }
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Pair a batch's writes with the `results` the PDS returned (older servers return none)
+fn batch_results(did: &str, writes: &[RepoWrite], response: &Value) -> Vec<WriteResult> {
+    let reported = response["results"].as_array();
+    writes
+        .iter()
+        .enumerate()
+        .map(|(i, write)| {
+            let result = reported.and_then(|results| results.get(i));
+            let field = |key: &str| {
+                result
+                    .and_then(|r| r[key].as_str())
+                    .map(str::to_string)
+            };
+            WriteResult {
+                uri: field("uri").or_else(|| write.uri(did)),
+                cid: field("cid"),
+            }
+        })
+        .collect()
+}
+
+/// URIs of every strong ref (`{uri, cid}` object) nested in a record
+fn strong_ref_uris(value: &Value) -> Box<dyn Iterator<Item = &str> + '_> {
+    match value {
+        Value::Object(map) => {
+            let own = match (map.get("uri"), map.get("cid")) {
+                (Some(Value::String(uri)), Some(_)) => Some(uri.as_str()),
+                _ => None,
+            };
+            Box::new(own.into_iter().chain(map.values().flat_map(strong_ref_uris)))
+        }
+        Value::Array(items) => Box::new(items.iter().flat_map(strong_ref_uris)),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+/// Set the `cid` of strong refs pointing at records whose CID is now known
+fn fill_strong_ref_cids(value: &mut Value, cids: &HashMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            let known = map
+                .get("uri")
+                .and_then(Value::as_str)
+                .filter(|_| map.contains_key("cid"))
+                .and_then(|uri| cids.get(uri));
+            if let Some(cid) = known {
+                map.insert("cid".to_string(), Value::from(cid.clone()));
+            }
+            map.values_mut().for_each(|v| fill_strong_ref_cids(v, cids));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| fill_strong_ref_cids(v, cids)),
+        _ => {}
+    }
+}
+
+/// NSID: at least three dot-separated segments of ASCII letters, digits and hyphens
+fn is_valid_nsid(nsid: &str) -> bool {
+    let segments: Vec<&str> = nsid.split('.').collect();
+    nsid.len() <= 317
+        && segments.len() >= 3
+        && segments.iter().all(|segment| {
+            !segment.is_empty()
+                && segment.len() <= 63
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Record key: 1-512 characters from `A-Za-z0-9._:~-`, and not `.` or `..`
+fn is_valid_rkey(rkey: &str) -> bool {
+    (1..=512).contains(&rkey.len())
+        && rkey != "."
+        && rkey != ".."
+        && rkey
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | ':' | '~' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DID: &str = "did:plc:me";
+
+    fn delete(rkey: &str) -> RepoWrite {
+        RepoWrite::Delete {
+            collection: "app.bsky.feed.post".to_string(),
+            rkey: rkey.to_string(),
+        }
+    }
+
+    /// A post with a fixed rkey, optionally replying to `parent`
+    fn create(rkey: &str, parent: Option<&str>) -> RepoWrite {
+        let mut value = json!({ "$type": "app.bsky.feed.post", "text": rkey });
+        if let Some(parent) = parent {
+            let strong_ref = json!({ "uri": parent, "cid": "" });
+            value["reply"] = json!({ "root": strong_ref, "parent": strong_ref });
+        }
+        RepoWrite::Create {
+            collection: "app.bsky.feed.post".to_string(),
+            rkey: Some(rkey.to_string()),
+            value,
+        }
+    }
+
+    fn post_uri(rkey: &str) -> String {
+        format!("at://{}/app.bsky.feed.post/{}", DID, rkey)
+    }
+
+    #[test]
+    fn test_write_json_shape() {
+        assert_eq!(
+            delete("3k").to_json(),
+            json!({
+                "$type": "com.atproto.repo.applyWrites#delete",
+                "collection": "app.bsky.feed.post",
+                "rkey": "3k"
+            })
+        );
+        let create = RepoWrite::Create {
+            collection: "app.bsky.feed.like".to_string(),
+            rkey: None,
+            value: json!({ "$type": "app.bsky.feed.like" }),
+        };
+        assert_eq!(
+            create.to_json(),
+            json!({
+                "$type": "com.atproto.repo.applyWrites#create",
+                "collection": "app.bsky.feed.like",
+                "value": { "$type": "app.bsky.feed.like" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_names_the_failing_write() {
+        let writes = vec![delete("a"), delete("b"), delete("bad/key")];
+        let err = validate_writes(DID, &writes).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Invalid input: Write 3 (delete app.bsky.feed.post/bad/key): invalid record key"
+        );
+
+        let mismatched = RepoWrite::Create {
+            collection: "app.bsky.feed.post".to_string(),
+            rkey: None,
+            value: json!({ "$type": "app.bsky.feed.like" }),
+        };
+        let err = validate_writes(DID, &[delete("a"), mismatched]).unwrap_err();
+        assert!(err.message().contains("Write 2 (create app.bsky.feed.post/(new)): record $type"));
+
+        let bad_nsid = RepoWrite::Delete {
+            collection: "posts".to_string(),
+            rkey: "a".to_string(),
+        };
+        assert!(validate_writes(DID, &[bad_nsid]).unwrap_err().message().contains("NSID"));
+        assert!(validate_writes(DID, &[]).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_targets() {
+        let err = validate_writes(DID, &[delete("a"), delete("b"), delete("a")]).unwrap_err();
+        assert!(err.message().contains(": Write 3 "), "{}", err.message());
+        assert!(err.message().contains("more than once"));
+    }
+
+    #[test]
+    fn test_validate_rejects_forward_references() {
+        let parent = post_uri("1");
+        let err = validate_writes(DID, &[create("2", Some(&parent)), create("1", None)]).unwrap_err();
+        assert!(err.message().contains("before write 2 creates it"), "{}", err.message());
+
+        assert!(validate_writes(DID, &[create("1", None), create("2", Some(&parent))]).is_ok());
+    }
+
+    #[test]
+    fn test_plan_batches_chunks_at_limit() {
+        let writes: Vec<RepoWrite> = (0..450).map(|i| delete(&i.to_string())).collect();
+        assert_eq!(plan_batches(DID, &writes), vec![0..200, 200..400, 400..450]);
+        assert_eq!(plan_batches(DID, &writes[..200]), vec![0..200]);
+    }
+
+    #[test]
+    fn test_plan_batches_splits_reply_chains() {
+        // Each reply needs its parent's CID, so it goes in a batch after its parent's
+        let writes = vec![
+            create("1", None),
+            create("2", Some(&post_uri("1"))),
+            create("3", Some(&post_uri("2"))),
+            delete("old"),
+            create("4", Some("at://did:plc:other/app.bsky.feed.post/x")),
+        ];
+        assert_eq!(plan_batches(DID, &writes), vec![0..1, 1..2, 2..5]);
+    }
+
+    #[test]
+    fn test_fill_strong_ref_cids() {
+        let RepoWrite::Create { mut value, .. } = create("2", Some(&post_uri("1"))) else {
+            unreachable!()
+        };
+        let cids = HashMap::from([(post_uri("1"), "bafyparent".to_string())]);
+        fill_strong_ref_cids(&mut value, &cids);
+        assert_eq!(value["reply"]["parent"]["cid"], "bafyparent");
+        assert_eq!(value["reply"]["root"]["cid"], "bafyparent");
+    }
+
+    #[test]
+    fn test_batch_results_fall_back_to_write_uri() {
+        let writes = vec![create("1", None), delete("2")];
+        let response = json!({
+            "results": [
+                { "$type": "com.atproto.repo.applyWrites#createResult", "uri": post_uri("1"), "cid": "bafy1" },
+                { "$type": "com.atproto.repo.applyWrites#deleteResult" }
+            ]
+        });
+        assert_eq!(
+            batch_results(DID, &writes, &response),
+            vec![
+                WriteResult { uri: Some(post_uri("1")), cid: Some("bafy1".to_string()) },
+                WriteResult { uri: Some(post_uri("2")), cid: None },
+            ]
+        );
+        assert_eq!(batch_results(DID, &writes, &json!({}))[0].cid, None);
+    }
+}
@@ -0,0 +1,270 @@
+//! Blob upload for post images
+//!
+//! Images are read from disk, checked against the limits of `app.bsky.embed.images`,
+//! uploaded with `com.atproto.repo.uploadBlob` and attached to a post as an images embed.
+
+use crate::bluesky::records::{BlobRef, Embed, ImageEmbed};
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Most images one post can carry
+pub const MAX_IMAGES_PER_POST: usize = 4;
+
+/// Largest image blob `app.bsky.embed.images` accepts, in bytes
+pub const MAX_IMAGE_BYTES: u64 = 1_000_000;
+
+/// An image file checked and ready to upload
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingImage {
+    pub path: PathBuf,
+    pub mime_type: &'static str,
+    pub alt: Option<String>,
+}
+
+/// MIME type of an image file, from its extension
+pub fn image_mime_type(path: &Path) -> Result<&'static str, AppError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => Ok("image/jpeg"),
+        Some("png") => Ok("image/png"),
+        Some("gif") => Ok("image/gif"),
+        Some("webp") => Ok("image/webp"),
+        _ => Err(AppError::InvalidInput(format!(
+            "Unsupported image type: {} (use .jpg, .jpeg, .png, .gif or .webp)",
+            path.display()
+        ))),
+    }
+}
+
+/// Check image count, alt text pairing, file types and sizes before anything is uploaded
+///
+/// `alts` pair with `images` by position; images past the end of `alts` have no alt text.
+pub fn prepare_images(images: &[PathBuf], alts: &[String]) -> Result<Vec<PendingImage>, AppError> {
+    if images.len() > MAX_IMAGES_PER_POST {
+        return Err(AppError::InvalidInput(format!(
+            "A post can have at most {} images, got {}",
+            MAX_IMAGES_PER_POST,
+            images.len()
+        )));
+    }
+    if alts.len() > images.len() {
+        return Err(AppError::InvalidInput(format!(
+            "{} alt texts given for {} images",
+            alts.len(),
+            images.len()
+        )));
+    }
+
+    images
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let mime_type = image_mime_type(path)?;
+            let size = std::fs::metadata(path)
+                .map_err(|e| {
+                    AppError::ConfigError(format!("Failed to read image {}: {}", path.display(), e))
+                })?
+                .len();
+            if size > MAX_IMAGE_BYTES {
+                return Err(AppError::InvalidInput(format!(
+                    "Image {} is {} bytes, over the {} byte limit",
+                    path.display(),
+                    size,
+                    MAX_IMAGE_BYTES
+                )));
+            }
+            Ok(PendingImage {
+                path: path.clone(),
+                mime_type,
+                alt: alts.get(i).cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Upload a blob to the session's PDS, returning the reference to put in a record
+pub async fn upload_blob(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    bytes: Vec<u8>,
+    mime_type: &str,
+) -> Result<BlobRef, AppError> {
+    let url = format!("{}/xrpc/com.atproto.repo.uploadBlob", session.service);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .header("Content-Type", mime_type)
+        .body(bytes)
+        .send_xrpc()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Blob upload request failed: {}", e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "Blob upload failed with status {}: {}",
+            status, error_text
+        )));
+    }
+
+    let mut result: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse upload response: {}", e)))?;
+
+    serde_json::from_value(result["blob"].take())
+        .map_err(|e| AppError::ParseError(format!("Invalid blob in upload response: {}", e)))
+}
+
+/// Read and upload each image, returning the images embed for the post
+pub async fn upload_images(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    images: Vec<PendingImage>,
+) -> Result<Embed, AppError> {
+    let mut uploaded = Vec::with_capacity(images.len());
+    for image in images {
+        let bytes = tokio::fs::read(&image.path).await.map_err(|e| {
+            AppError::ConfigError(format!("Failed to read image {}: {}", image.path.display(), e))
+        })?;
+        debug!("Uploading {} ({} bytes)", image.path.display(), bytes.len());
+        let blob = upload_blob(client, session, bytes, image.mime_type).await?;
+        uploaded.push((blob, image.alt));
+    }
+    Ok(images_embed(uploaded))
+}
+
+/// `app.bsky.embed.images` from uploaded blobs and their alt text
+///
+/// The lexicon requires an alt string on every image, so missing alt text is sent empty.
+pub fn images_embed(images: Vec<(BlobRef, Option<String>)>) -> Embed {
+    Embed::Images {
+        images: images
+            .into_iter()
+            .map(|(image, alt)| ImageEmbed {
+                alt: Some(alt.unwrap_or_default()),
+                image,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn blob(cid: &str, size: u64) -> BlobRef {
+        BlobRef {
+            type_: "blob".to_string(),
+            ref_: cid.to_string(),
+            mime_type: "image/jpeg".to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_image_mime_type_from_extension() {
+        assert_eq!(image_mime_type(Path::new("cat.JPG")).unwrap(), "image/jpeg");
+        assert_eq!(image_mime_type(Path::new("a/b.jpeg")).unwrap(), "image/jpeg");
+        assert_eq!(image_mime_type(Path::new("x.png")).unwrap(), "image/png");
+        assert_eq!(image_mime_type(Path::new("x.webp")).unwrap(), "image/webp");
+        assert!(matches!(
+            image_mime_type(Path::new("notes.txt")),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(image_mime_type(Path::new("no_extension")).is_err());
+    }
+
+    #[test]
+    fn test_images_embed_serializes_to_lexicon_shape() {
+        let embed = images_embed(vec![
+            (blob("bafkreione", 1234), Some("A cat".to_string())),
+            (blob("bafkreitwo", 99), None),
+        ]);
+
+        assert_eq!(
+            serde_json::to_value(&embed).unwrap(),
+            json!({
+                "$type": "app.bsky.embed.images",
+                "images": [
+                    {
+                        "alt": "A cat",
+                        "image": {
+                            "$type": "blob",
+                            "ref": { "$link": "bafkreione" },
+                            "mimeType": "image/jpeg",
+                            "size": 1234
+                        }
+                    },
+                    {
+                        "alt": "",
+                        "image": {
+                            "$type": "blob",
+                            "ref": { "$link": "bafkreitwo" },
+                            "mimeType": "image/jpeg",
+                            "size": 99
+                        }
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_upload_response_blob_parses() {
+        let response = json!({
+            "$type": "blob",
+            "ref": { "$link": "bafkreiuploaded" },
+            "mimeType": "image/png",
+            "size": 2048
+        });
+        let parsed: BlobRef = serde_json::from_value(response).unwrap();
+        assert_eq!(parsed.ref_, "bafkreiuploaded");
+        assert_eq!(parsed.mime_type, "image/png");
+        assert_eq!(parsed.size, 2048);
+    }
+
+    #[test]
+    fn test_prepare_images_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, len: usize| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, vec![0u8; len]).unwrap();
+            path
+        };
+        let small = write("small.png", 10);
+        let big = write("big.jpg", MAX_IMAGE_BYTES as usize + 1);
+
+        let prepared = prepare_images(std::slice::from_ref(&small), &["alt".to_string()]).unwrap();
+        assert_eq!(prepared[0].mime_type, "image/png");
+        assert_eq!(prepared[0].alt.as_deref(), Some("alt"));
+
+        let err = prepare_images(&[big], &[]).unwrap_err();
+        assert!(err.message().contains("over the 1000000 byte limit"), "{}", err.message());
+
+        let five = vec![small.clone(); MAX_IMAGES_PER_POST + 1];
+        assert!(prepare_images(&five, &[]).unwrap_err().message().contains("at most 4 images"));
+
+        let alts = vec!["a".to_string(), "b".to_string()];
+        assert!(prepare_images(&[small], &alts).is_err());
+
+        let missing = dir.path().join("missing.png");
+        assert!(matches!(prepare_images(&[missing], &[]), Err(AppError::ConfigError(_))));
+    }
+}
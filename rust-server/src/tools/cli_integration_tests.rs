@@ -5,7 +5,6 @@
 
 #[cfg(test)]
 mod cli_integration_tests {
-    use crate::bluesky::provider::{RepositoryProvider};
     use crate::bluesky::records::{PostRecord, ProfileRecord, Embed};
     use crate::cli::{SearchArgs, ProfileArgs};
 
@@ -53,7 +52,7 @@ mod cli_integration_tests {
 
         // Search should find the second post
         let mut engine = crate::search::SearchEngine::new();
-        let results = engine.search("post", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("post", &posts, |p| p.get_searchable_text());
         
         assert_eq!(results.len(), 1, "Should find 'post' in second post");
     }
@@ -65,6 +64,7 @@ mod cli_integration_tests {
             from: "did:plc:test123".to_string(),
             query: "rust programming".to_string(),
             limit: None,
+            ..Default::default()
         };
 
         assert_eq!(args.from, "did:plc:test123");
@@ -76,6 +76,7 @@ mod cli_integration_tests {
         // Test that ProfileArgs can be properly created and used
         let args = ProfileArgs {
             account: "did:plc:test456".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(args.account, "did:plc:test456");
@@ -89,7 +90,7 @@ mod cli_integration_tests {
         ];
 
         let mut engine = crate::search::SearchEngine::new();
-        let results = engine.search("nonexistent_query_xyz", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("nonexistent_query_xyz", &posts, |p| p.get_searchable_text());
         
         assert_eq!(results.len(), 0, "Should find no results for nonexistent query");
     }
@@ -104,7 +105,7 @@ mod cli_integration_tests {
         ];
 
         let mut engine = crate::search::SearchEngine::new();
-        let results = engine.search("fox", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("fox", &posts, |p| p.get_searchable_text());
         
         assert_eq!(results.len(), 3, "Should find all three posts mentioning 'fox'");
         // Results should be ranked; check that we have valid results
@@ -121,7 +122,7 @@ mod cli_integration_tests {
         ];
 
         let mut engine = crate::search::SearchEngine::new();
-        let results = engine.search("HELLO", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("HELLO", &posts, |p| p.get_searchable_text());
         
         assert_eq!(results.len(), 3, "Should find all variations of 'hello'");
     }
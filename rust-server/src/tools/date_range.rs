@@ -0,0 +1,239 @@
+//! Created-at date range filtering shared by search and feed
+//!
+//! Combines absolute bounds (`--since`, `--until`) with relative day counts
+//! (`--since-days`, `--until-days`). Every comparison goes through
+//! `search::timestamp::parse_timestamp`, so both forms treat timestamps alike.
+
+use crate::error::AppError;
+use crate::search::timestamp::parse_timestamp;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+/// Inclusive lower and upper bounds on post creation time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateRange {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    /// Build a range from tool arguments, resolving day counts against `now`.
+    ///
+    /// - `since_days = N` keeps posts from the start of the UTC day N days ago (0 = today)
+    /// - `until_days = N` keeps posts up to the end of the UTC day N days ago
+    /// - a date-only `until` (YYYY-MM-DD) keeps that whole UTC day
+    /// - giving both an absolute and a relative bound on the same side is an error
+    pub fn from_args(
+        since: Option<&str>,
+        until: Option<&str>,
+        since_days: Option<i64>,
+        until_days: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, AppError> {
+        if since.is_some() && since_days.is_some() {
+            return Err(AppError::InvalidInput(
+                "Use either since or sinceDays, not both".to_string(),
+            ));
+        }
+        if until.is_some() && until_days.is_some() {
+            return Err(AppError::InvalidInput(
+                "Use either until or untilDays, not both".to_string(),
+            ));
+        }
+
+        let since = match (since, since_days) {
+            (Some(s), _) => Some(parse_bound("since", s)?),
+            (None, Some(days)) => Some(start_of_day_ago("sinceDays", days, now)?),
+            (None, None) => None,
+        };
+        let until = match (until, until_days) {
+            (Some(s), _) if is_date_only(s) => Some(end_of_day(parse_bound("until", s)?)),
+            (Some(s), _) => Some(parse_bound("until", s)?),
+            (None, Some(days)) => Some(end_of_day(start_of_day_ago("untilDays", days, now)?)),
+            (None, None) => None,
+        };
+
+        if let (Some(s), Some(u)) = (since, until) {
+            if s > u {
+                return Err(AppError::InvalidInput(format!(
+                    "Empty date range: since {} is after until {}",
+                    s.to_rfc3339(),
+                    u.to_rfc3339()
+                )));
+            }
+        }
+
+        Ok(Self { since, until })
+    }
+
+    /// Whether any bound is set
+    pub fn is_active(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Check a record timestamp against the range.
+    /// Unparseable timestamps only pass when no bound is set.
+    pub fn contains(&self, timestamp: &str) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        match parse_timestamp(timestamp) {
+            Some(t) => {
+                self.since.is_none_or(|since| t >= since) && self.until.is_none_or(|until| t <= until)
+            }
+            None => false,
+        }
+    }
+}
+
+fn parse_bound(name: &str, value: &str) -> Result<DateTime<Utc>, AppError> {
+    parse_timestamp(value).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "Invalid {} date '{}': expected YYYY-MM-DD or an RFC 3339 timestamp",
+            name, value
+        ))
+    })
+}
+
+fn is_date_only(value: &str) -> bool {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").is_ok()
+}
+
+/// Last representable instant of the UTC day starting at `start_of_day`
+fn end_of_day(start_of_day: DateTime<Utc>) -> DateTime<Utc> {
+    start_of_day + Duration::days(1) - Duration::nanoseconds(1)
+}
+
+fn start_of_day_ago(name: &str, days: i64, now: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+    if days < 0 {
+        return Err(AppError::InvalidInput(format!(
+            "{} must not be negative (got {})",
+            name, days
+        )));
+    }
+    let day = now.date_naive() - Duration::days(days);
+    let start = day
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is out of range", name)))?;
+    Ok(start.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        parse_timestamp("2024-06-10T15:30:00Z").unwrap()
+    }
+
+    #[test]
+    fn test_since_days_zero_means_today() {
+        let range = DateRange::from_args(None, None, Some(0), None, now()).unwrap();
+        assert!(range.contains("2024-06-10T00:00:01Z"));
+        assert!(!range.contains("2024-06-09T23:59:59Z"));
+    }
+
+    #[test]
+    fn test_since_days_matches_absolute_since() {
+        let relative = DateRange::from_args(None, None, Some(7), None, now()).unwrap();
+        let absolute = DateRange::from_args(Some("2024-06-03"), None, None, None, now()).unwrap();
+        assert_eq!(relative, absolute);
+    }
+
+    #[test]
+    fn test_until_days_includes_whole_day() {
+        let range = DateRange::from_args(None, None, None, Some(1), now()).unwrap();
+        assert!(range.contains("2024-06-09T23:59:59.999Z"));
+        assert!(!range.contains("2024-06-10T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_negative_days_rejected() {
+        let err = DateRange::from_args(None, None, Some(-1), None, now()).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+        let err = DateRange::from_args(None, None, None, Some(-3), now()).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_absolute_and_relative_conflict() {
+        let err = DateRange::from_args(Some("2024-01-01"), None, Some(3), None, now()).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_timestamp_without_timezone_compares_as_utc() {
+        let range = DateRange::from_args(Some("2024-06-01T12:00:00Z"), None, None, None, now()).unwrap();
+        assert!(range.contains("2024-06-01T12:00:00"));
+        assert!(!range.contains("2024-06-01T11:59:59"));
+    }
+
+    #[test]
+    fn test_inactive_range_keeps_everything() {
+        let range = DateRange::default();
+        assert!(range.contains("not a date"));
+    }
+
+    #[test]
+    fn test_since_and_until_are_inclusive() {
+        let range = DateRange::from_args(
+            Some("2024-06-01T00:00:00Z"),
+            Some("2024-06-02T00:00:00Z"),
+            None,
+            None,
+            now(),
+        )
+        .unwrap();
+        assert!(range.contains("2024-06-01T00:00:00Z"));
+        assert!(range.contains("2024-06-02T00:00:00Z"));
+        assert!(!range.contains("2024-05-31T23:59:59.999Z"));
+        assert!(!range.contains("2024-06-02T00:00:00.001Z"));
+
+        // Offsets are compared as instants
+        assert!(range.contains("2024-06-02T01:00:00+01:00"));
+        assert!(!range.contains("2024-06-01T01:00:00+02:00"));
+        assert!(range.contains("2024-05-31T23:00:00-02:00"));
+    }
+
+    #[test]
+    fn test_date_only_until_keeps_whole_day() {
+        let range = DateRange::from_args(None, Some("2024-06-01"), None, None, now()).unwrap();
+        assert!(range.contains("2024-06-01T23:59:59.999Z"));
+        assert!(!range.contains("2024-06-02T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_unparseable_dates_excluded_when_active() {
+        for range in [
+            DateRange::from_args(Some("2024-01-01"), None, None, None, now()).unwrap(),
+            DateRange::from_args(None, Some("2024-01-01"), None, None, now()).unwrap(),
+        ] {
+            assert!(!range.contains("not a date"));
+            assert!(!range.contains(""));
+        }
+    }
+
+    #[test]
+    fn test_since_equal_to_until_is_accepted() {
+        let instant = "2024-06-01T12:00:00Z";
+        let range = DateRange::from_args(Some(instant), Some(instant), None, None, now()).unwrap();
+        assert!(range.contains(instant));
+        assert!(!range.contains("2024-06-01T12:00:00.001Z"));
+
+        let day = DateRange::from_args(Some("2024-06-01"), Some("2024-06-01"), None, None, now())
+            .unwrap();
+        assert!(day.contains("2024-06-01T00:00:00Z"));
+        assert!(day.contains("2024-06-01T18:00:00Z"));
+        assert!(!day.contains("2024-06-02T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_since_after_until_rejected() {
+        let err = DateRange::from_args(Some("2024-06-02"), Some("2024-06-01"), None, None, now())
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+        assert!(err.to_string().contains("Empty date range"), "{}", err);
+
+        let err = DateRange::from_args(Some("yesterday"), None, None, None, now()).unwrap_err();
+        assert!(err.to_string().contains("Invalid since date 'yesterday'"), "{}", err);
+    }
+}
@@ -0,0 +1,363 @@
+//! Facet detection and realignment for post text
+//!
+//! Facets address their target text by UTF-8 byte range, as AT Proto specifies.
+//! New posts get facets detected from their text (mentions, links, hashtags).
+//! When post text is edited those ranges drift; realignment re-anchors each facet
+//! on the substring it covered in the old text, for use when `post` edits a post.
+
+use crate::bluesky::did::{is_valid_handle, DidResolver};
+use crate::bluesky::records::{Facet, FacetFeature, FacetIndex};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// `@handle` at the start of the text or after whitespace or `(`
+static MENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(])(@[a-zA-Z0-9.-]+)").unwrap());
+
+/// `http(s)://` URL at the start of the text or after whitespace or `(`
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(])(https?://[^\s]+)").unwrap());
+
+/// `#tag` (or full-width `＃tag`) at the start of the text or after whitespace
+static HASHTAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\s)([#＃][^\s]+)").unwrap());
+
+/// Longest hashtag Bluesky indexes, in characters (without the `#`)
+const MAX_TAG_CHARS: usize = 64;
+
+/// What a detected facet points at, before mentions are resolved to DIDs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FacetTarget {
+    /// Handle without the `@`
+    Mention(String),
+    Link(String),
+    /// Tag without the `#`
+    Tag(String),
+}
+
+/// A facet found in post text, addressed by UTF-8 byte range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedFacet {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub target: FacetTarget,
+}
+
+/// Find mentions, links and hashtags in post text, in text order
+///
+/// Trailing punctuation is not part of a match (`see https://x.dev.` links `https://x.dev`),
+/// and a closing `)` is kept on a link only when the link opened one.
+pub fn detect_facets(text: &str) -> Vec<DetectedFacet> {
+    let mut detected = Vec::new();
+
+    for m in MENTION.captures_iter(text).filter_map(|c| c.get(1)) {
+        let handle = m.as_str()[1..].trim_end_matches(['.', '-']);
+        if is_valid_handle(handle) {
+            detected.push(DetectedFacet {
+                byte_start: m.start(),
+                byte_end: m.start() + 1 + handle.len(),
+                target: FacetTarget::Mention(handle.to_string()),
+            });
+        }
+    }
+
+    for m in LINK.captures_iter(text).filter_map(|c| c.get(1)) {
+        let uri = trim_link(m.as_str());
+        if url::Url::parse(uri).is_ok_and(|u| u.host_str().is_some()) {
+            detected.push(DetectedFacet {
+                byte_start: m.start(),
+                byte_end: m.start() + uri.len(),
+                target: FacetTarget::Link(uri.to_string()),
+            });
+        }
+    }
+
+    for m in HASHTAG.captures_iter(text).filter_map(|c| c.get(1)) {
+        let hash_len = m.as_str().chars().next().map_or(1, char::len_utf8);
+        let tag = m.as_str()[hash_len..].trim_end_matches(|c: char| c.is_ascii_punctuation());
+        let chars = tag.chars().count();
+        if chars == 0 || chars > MAX_TAG_CHARS || tag.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        detected.push(DetectedFacet {
+            byte_start: m.start(),
+            byte_end: m.start() + hash_len + tag.len(),
+            target: FacetTarget::Tag(tag.to_string()),
+        });
+    }
+
+    detected.sort_by_key(|f| f.byte_start);
+    detected
+}
+
+/// Strip trailing punctuation from a URL match, keeping a `)` that closes one in the URL
+fn trim_link(uri: &str) -> &str {
+    let mut uri = uri.trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'']);
+    while uri.ends_with(')') && uri.matches(')').count() > uri.matches('(').count() {
+        uri = uri[..uri.len() - 1].trim_end_matches(['.', ',', ';', ':', '!', '?', '"', '\'']);
+    }
+    uri
+}
+
+/// Turn detected facets into record facets; mentions of handles missing from `dids`
+/// (keyed by lowercase handle) are left as plain text
+pub fn build_facets(detected: &[DetectedFacet], dids: &HashMap<String, String>) -> Vec<Facet> {
+    detected
+        .iter()
+        .filter_map(|facet| {
+            let feature = match &facet.target {
+                FacetTarget::Mention(handle) => FacetFeature::Mention {
+                    did: dids.get(&handle.to_lowercase())?.clone(),
+                },
+                FacetTarget::Link(uri) => FacetFeature::Link { uri: uri.clone() },
+                FacetTarget::Tag(tag) => FacetFeature::Tag { tag: tag.clone() },
+            };
+            Some(Facet {
+                index: FacetIndex {
+                    byte_start: u32::try_from(facet.byte_start).ok()?,
+                    byte_end: u32::try_from(facet.byte_end).ok()?,
+                },
+                features: vec![feature],
+            })
+        })
+        .collect()
+}
+
+/// Detect facets in post text and resolve mentioned handles to DIDs
+pub async fn resolve_facets(text: &str, resolver: &DidResolver) -> Vec<Facet> {
+    let detected = detect_facets(text);
+
+    let mentions: Vec<String> = detected
+        .iter()
+        .filter_map(|facet| match &facet.target {
+            FacetTarget::Mention(handle) => Some(handle.to_lowercase()),
+            _ => None,
+        })
+        .collect();
+    let mentions: Vec<&str> = mentions.iter().map(String::as_str).collect();
+
+    // An unknown handle is not an error: the mention just stays plain text
+    let dids: HashMap<String, String> = resolver
+        .resolve_handles(&mentions)
+        .await
+        .into_iter()
+        .filter_map(|(handle, did)| did.map(|did| (handle, did)))
+        .collect();
+
+    build_facets(&detected, &dids)
+}
+
+/// Re-locate each facet's anchor text in `new_text` and update its byte offsets.
+///
+/// - A facet whose anchor occurs several times moves to the occurrence nearest its
+///   original byte offset (earlier wins a tie).
+/// - Facets are matched in original order and never share an occurrence, so two
+///   facets on identical text do not collapse onto one when one copy was removed.
+/// - Facets whose anchor no longer exists, or whose old range was invalid, are dropped.
+///
+/// The result is sorted by `byte_start`.
+pub fn realign_facets(old_text: &str, facets: &[Facet], new_text: &str) -> Vec<Facet> {
+    let mut ordered: Vec<&Facet> = facets.iter().collect();
+    ordered.sort_by_key(|f| (f.index.byte_start, f.index.byte_end));
+
+    let mut claimed: Vec<(usize, usize)> = Vec::new();
+    let mut realigned = Vec::new();
+
+    for facet in ordered {
+        let start = facet.index.byte_start as usize;
+        let end = facet.index.byte_end as usize;
+        let Some(anchor) = old_text.get(start..end).filter(|a| !a.is_empty()) else {
+            continue;
+        };
+
+        let nearest = occurrences(new_text, anchor)
+            .filter(|&pos| {
+                let range = (pos, pos + anchor.len());
+                !claimed.iter().any(|&c| overlaps(c, range))
+            })
+            .min_by_key(|&pos| pos.abs_diff(start));
+
+        let Some(pos) = nearest else {
+            continue;
+        };
+        let range = (pos, pos + anchor.len());
+        claimed.push(range);
+
+        let (Ok(byte_start), Ok(byte_end)) = (u32::try_from(range.0), u32::try_from(range.1)) else {
+            continue;
+        };
+        realigned.push(Facet {
+            index: FacetIndex {
+                byte_start,
+                byte_end,
+            },
+            features: facet.features.clone(),
+        });
+    }
+
+    realigned.sort_by_key(|f| f.index.byte_start);
+    realigned
+}
+
+/// Byte offsets of every (possibly overlapping) occurrence of `needle` in `haystack`
+fn occurrences<'a>(haystack: &'a str, needle: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let mut from = 0usize;
+    std::iter::from_fn(move || {
+        let pos = from + haystack.get(from..)?.find(needle)?;
+        // Resume one character later so overlapping matches are found too
+        from = pos + haystack[pos..].chars().next().map_or(1, char::len_utf8);
+        Some(pos)
+    })
+}
+
+fn overlaps(a: (usize, usize), b: (usize, usize)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(text: &str, anchor: &str, nth: usize, name: &str) -> Facet {
+        let start = text.match_indices(anchor).nth(nth).unwrap().0;
+        Facet {
+            index: FacetIndex {
+                byte_start: start as u32,
+                byte_end: (start + anchor.len()) as u32,
+            },
+            features: vec![FacetFeature::Tag {
+                tag: name.to_string(),
+            }],
+        }
+    }
+
+    fn spans<'a>(text: &'a str, facets: &[Facet]) -> Vec<(usize, &'a str)> {
+        facets
+            .iter()
+            .map(|f| {
+                let (s, e) = (f.index.byte_start as usize, f.index.byte_end as usize);
+                (s, &text[s..e])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shifts_offsets_after_multibyte_insert() {
+        let old = "Hi @alice.test see https://x.dev";
+        let new = "Hi 👋 café @alice.test — see https://x.dev";
+        let facets = vec![tag(old, "@alice.test", 0, "m"), tag(old, "https://x.dev", 0, "l")];
+
+        let realigned = realign_facets(old, &facets, new);
+        assert_eq!(
+            spans(new, &realigned),
+            vec![(14, "@alice.test"), (34, "https://x.dev")]
+        );
+        // Byte (not char) offsets: "Hi 👋 café " is 14 bytes but 10 chars
+        assert_eq!(new[..14].chars().count(), 10);
+    }
+
+    #[test]
+    fn test_drops_facets_whose_text_was_removed() {
+        let old = "Read #rust and #go";
+        let new = "Read #rust";
+        let facets = vec![tag(old, "#rust", 0, "rust"), tag(old, "#go", 0, "go")];
+
+        let realigned = realign_facets(old, &facets, new);
+        assert_eq!(spans(new, &realigned), vec![(5, "#rust")]);
+    }
+
+    #[test]
+    fn test_prefers_nearest_duplicate() {
+        let old = "#a x #a y #a";
+        // The facet sat at byte 5; candidates at 0, 3 and 8 are 5, 2 and 3 bytes away
+        let new = "#a #a x #a y";
+        let facets = vec![tag(old, "#a", 1, "a")];
+
+        let realigned = realign_facets(old, &facets, new);
+        assert_eq!(realigned.len(), 1);
+        assert_eq!(realigned[0].index.byte_start, 3);
+    }
+
+    #[test]
+    fn test_identical_anchors_do_not_collapse() {
+        let old = "#a #a #a";
+        let new = "#a #a";
+        let facets: Vec<Facet> = (0..3).map(|i| tag(old, "#a", i, "a")).collect();
+
+        let realigned = realign_facets(old, &facets, new);
+        assert_eq!(spans(new, &realigned), vec![(0, "#a"), (3, "#a")]);
+    }
+
+    #[test]
+    fn test_invalid_old_ranges_are_dropped() {
+        let old = "héllo";
+        let mut bad = tag(old, "héllo", 0, "x");
+        // Ends inside the two-byte 'é'
+        bad.index.byte_end = 2;
+        let mut out_of_range = tag(old, "héllo", 0, "y");
+        out_of_range.index.byte_end = 99;
+
+        assert!(realign_facets(old, &[bad, out_of_range], "héllo").is_empty());
+    }
+
+    fn detected_spans(text: &str) -> Vec<(usize, &str, FacetTarget)> {
+        detect_facets(text)
+            .into_iter()
+            .map(|f| (f.byte_start, &text[f.byte_start..f.byte_end], f.target))
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_mixed_facets_with_byte_offsets() {
+        let text = "Grüße 👋 @alice.bsky.social! Read https://example.com/a_(b) and #Rust.";
+        let spans = detected_spans(text);
+        assert_eq!(
+            spans,
+            vec![
+                (13, "@alice.bsky.social", FacetTarget::Mention("alice.bsky.social".to_string())),
+                (38, "https://example.com/a_(b)", FacetTarget::Link("https://example.com/a_(b)".to_string())),
+                (68, "#Rust", FacetTarget::Tag("Rust".to_string())),
+            ]
+        );
+        // Byte (not char) offsets: "Grüße 👋 " is 13 bytes but 8 chars
+        assert_eq!(text[..13].chars().count(), 8);
+    }
+
+    #[test]
+    fn test_detection_edges() {
+        // Trailing punctuation and unbalanced parentheses stay outside the facet
+        assert_eq!(
+            detected_spans("(see https://x.dev/path).")[0].1,
+            "https://x.dev/path"
+        );
+        // Email addresses, numeric tags, dotless handles and mid-word markers are ignored
+        assert!(detected_spans("mail bob@example.com about #1 or @localhost a#b").is_empty());
+        // Full-width hash is three bytes
+        assert_eq!(
+            detected_spans("＃日本語"),
+            vec![(0, "＃日本語", FacetTarget::Tag("日本語".to_string()))]
+        );
+        let long_tag = format!("#{}", "a".repeat(MAX_TAG_CHARS + 1));
+        assert!(detect_facets(&long_tag).is_empty());
+    }
+
+    #[test]
+    fn test_build_facets_resolves_mentions() {
+        let text = "hi @Alice.Test and @ghost.test #tag";
+        let dids = HashMap::from([("alice.test".to_string(), "did:plc:alice".to_string())]);
+
+        let facets = build_facets(&detect_facets(text), &dids);
+        assert_eq!(facets.len(), 2, "unresolved mention is dropped");
+        assert!(matches!(
+            &facets[0].features[0],
+            FacetFeature::Mention { did } if did == "did:plc:alice"
+        ));
+        assert_eq!((facets[0].index.byte_start, facets[0].index.byte_end), (3, 14));
+        assert!(matches!(&facets[1].features[0], FacetFeature::Tag { tag } if tag == "tag"));
+
+        let json = serde_json::to_value(&facets[0]).unwrap();
+        assert_eq!(json["index"]["byteStart"], 3);
+        assert_eq!(json["features"][0]["$type"], "app.bsky.richtext.facet#mention");
+    }
+}
@@ -2,63 +2,45 @@
 //!
 //! Implements the `feed` MCP tool for fetching BlueSky feeds
 
-use crate::cli::FeedArgs;
+use crate::cli::{FeedArgs, OutputFormat};
 use crate::error::AppError;
-use crate::http::client_with_timeout;
-use crate::mcp::{McpResponse, ToolResult};
-use crate::bluesky::records::Facet;
+use crate::tools::date_range::DateRange;
+use crate::http::{client_with_timeout, rate_limit_error, RetryPolicy, SendXrpc};
+use crate::mcp::{report_progress, McpResponse, Progress, ToolResult};
+use crate::bluesky::provider::{configured_provider, RepositoryProvider, GET_POSTS_MAX_URIS};
+use crate::tools::formatter::{formatter_for, FeedItem, OutputFormatter};
+use crate::tools::paging::{fetch_pages, page_limit, Page, PagedList};
+use crate::bluesky::feed_view::{
+    FeedPost, FeedPostSlot, FeedResponse, FeedViewPost, PostAuthor, PostRecord,
+};
+use crate::bluesky::records;
+use crate::bluesky::did::display_handle;
+use crate::tools::search::load_account_posts;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::time::timeout;
-use tracing::debug;
+use tracing::{debug, warn};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct PostAuthor {
-    did: String,
-    handle: String,
-    #[serde(rename = "displayName")]
-    display_name: Option<String>,
-}
+/// Maximum number of reposted originals fetched per feed request
+const MAX_REPOST_ORIGINAL_FETCHES: usize = GET_POSTS_MAX_URIS;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct PostRecord {
-    text: String,
-    #[serde(rename = "createdAt")]
-    created_at: String,
-    #[serde(default)]
-    facets: Vec<Facet>,
-}
+/// Maximum number of reply parents looked up per feed request (four getPosts calls)
+const MAX_REPLY_PARENT_FETCHES: usize = 4 * GET_POSTS_MAX_URIS;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct FeedPost {
-    uri: String,
-    cid: String,
-    author: PostAuthor,
-    record: PostRecord,
-    #[serde(rename = "indexedAt")]
-    indexed_at: String,
-    #[serde(rename = "likeCount")]
-    like_count: Option<i32>,
-    #[serde(rename = "replyCount")]
-    reply_count: Option<i32>,
-    #[serde(rename = "repostCount")]
-    repost_count: Option<i32>,
-    #[serde(rename = "quoteCount")]
-    quote_count: Option<i32>,
-}
+/// app.bsky.feed.getFeed and app.bsky.feed.getAuthorFeed return at most 100 posts per call
+const FEED_PAGE_MAX: usize = 100;
 
-#[derive(Deserialize)]
-struct FeedViewPost {
-    post: FeedPost,
-}
+/// Posts returned when no limit is given
+const DEFAULT_FEED_LIMIT: usize = 50;
 
-#[derive(Deserialize)]
-struct FeedResponse {
-    feed: Vec<FeedViewPost>,
-    cursor: Option<String>,
-}
+/// Most posts one feed call returns (five pages); larger limits are capped
+const MAX_FEED_LIMIT: usize = 500;
+
+const GET_FEED_URL: &str = "https://public.api.bsky.app/xrpc/app.bsky.feed.getFeed";
+const GET_AUTHOR_FEED_URL: &str = "https://public.api.bsky.app/xrpc/app.bsky.feed.getAuthorFeed";
 
 #[derive(Deserialize)]
 struct FeedGeneratorView {
@@ -73,7 +55,11 @@ struct PopularFeedsResponse {
 }
 
 /// Resolve a feed name/query to a full at:// URI by searching
-async fn resolve_feed_uri(client: &reqwest::Client, query: &str) -> Result<String, AppError> {
+async fn resolve_feed_uri(
+    client: &reqwest::Client,
+    retry: &RetryPolicy,
+    query: &str,
+) -> Result<String, AppError> {
     let search_url = format!(
         "https://public.api.bsky.app/xrpc/app.bsky.unspecced.getPopularFeedGenerators?query={}",
         urlencoding::encode(query)
@@ -83,10 +69,14 @@ async fn resolve_feed_uri(client: &reqwest::Client, query: &str) -> Result<Strin
 
     let response = client
         .get(&search_url)
-        .send()
+        .send_xrpc_with(retry)
         .await
         .map_err(|e| AppError::NetworkError(format!("Failed to search for feed: {}", e)))?;
 
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
     if !response.status().is_success() {
         return Err(AppError::NetworkError(format!(
             "Feed search API returned error {}",
@@ -116,8 +106,12 @@ async fn resolve_feed_uri(client: &reqwest::Client, query: &str) -> Result<Strin
 }
 
 /// Handle feed tool call
-pub async fn handle_feed(id: Option<Value>, args: Value) -> McpResponse {
-    match timeout(Duration::from_secs(120), handle_feed_impl(args)).await {
+pub async fn handle_feed(
+    id: Option<Value>,
+    args: Value,
+    progress: Option<&Progress>,
+) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_feed_impl(args, progress)).await {
         Ok(result) => match result {
             Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
             Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
@@ -126,162 +120,413 @@ pub async fn handle_feed(id: Option<Value>, args: Value) -> McpResponse {
     }
 }
 
-async fn handle_feed_impl(args: Value) -> Result<ToolResult, AppError> {
+async fn handle_feed_impl(
+    args: Value,
+    progress: Option<&Progress>,
+) -> Result<ToolResult, AppError> {
     let feed_args: FeedArgs = serde_json::from_value(args)
         .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
 
-    execute_feed(feed_args).await
+    let provider = configured_provider()?;
+    execute_feed_with_progress(feed_args, OutputFormat::Markdown, &provider, progress).await
+}
+
+/// Execute feed tool, rendering the posts as `format`. Feed requests are retried, and
+/// posts looked up and repos read, as `provider` is configured.
+pub async fn execute_feed(
+    feed_args: FeedArgs,
+    format: OutputFormat,
+    provider: &RepositoryProvider,
+) -> Result<ToolResult, AppError> {
+    execute_feed_with_progress(feed_args, format, provider, None).await
 }
 
-/// Execute feed tool
-pub async fn execute_feed(feed_args: FeedArgs) -> Result<ToolResult, AppError> {
+/// Execute feed tool, reporting each fetched batch
+pub async fn execute_feed_with_progress(
+    feed_args: FeedArgs,
+    format: OutputFormat,
+    provider: &RepositoryProvider,
+    progress: Option<&Progress>,
+) -> Result<ToolResult, AppError> {
     debug!("Feed request for feed: {:?}", feed_args.feed);
 
+    let date_range = DateRange::from_args(
+        feed_args.since.as_deref(),
+        feed_args.until.as_deref(),
+        feed_args.sinceDays,
+        feed_args.untilDays,
+        chrono::Utc::now(),
+    )?;
+
+    let requested_limit =
+        page_limit(feed_args.limit, DEFAULT_FEED_LIMIT, MAX_FEED_LIMIT, "posts")?;
     let client = client_with_timeout(Duration::from_secs(120));
+    let retry = provider.retry_policy();
+    let cursor = feed_args.continueAtCursor.clone();
 
-    // Resolve the feed URI
-    let feed_uri = match &feed_args.feed {
-        Some(feed_input) => {
-            // Check if it's already a valid at:// URI
-            if feed_input.starts_with("at://") && feed_input.contains("/app.bsky.feed.generator/") {
-                feed_input.clone()
-            } else {
-                // Not a full URI - search for feed by name
-                debug!("Feed '{}' is not a full URI, searching...", feed_input);
-                resolve_feed_uri(&client, feed_input).await?
-            }
+    let (mut all_posts, cursor) = match &feed_args.author {
+        Some(author) => {
+            fetch_author_feed(
+                &client,
+                provider,
+                GET_AUTHOR_FEED_URL,
+                author,
+                cursor,
+                requested_limit,
+                progress,
+            )
+            .await?
         }
         None => {
-            // Default to "What's Hot" feed
-            "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.generator/whats-hot".to_string()
+            let feed_uri = match &feed_args.feed {
+                Some(feed_input) => {
+                    // Check if it's already a valid at:// URI
+                    if feed_input.starts_with("at://")
+                        && feed_input.contains("/app.bsky.feed.generator/")
+                    {
+                        feed_input.clone()
+                    } else {
+                        // Not a full URI - search for feed by name
+                        debug!("Feed '{}' is not a full URI, searching...", feed_input);
+                        resolve_feed_uri(&client, retry, feed_input).await?
+                    }
+                }
+                None => {
+                    // Default to "What's Hot" feed
+                    "at://did:plc:z72i7hdynmk6r22z27h6tvur/app.bsky.feed.generator/whats-hot"
+                        .to_string()
+                }
+            };
+            debug!("Using feed URI: {}", feed_uri);
+
+            let endpoint = format!("{}?feed={}", GET_FEED_URL, urlencoding::encode(&feed_uri));
+            let subject = format!("Feed {}", feed_uri);
+            fetch_feed_pages(&client, retry, &endpoint, &subject, cursor, requested_limit, progress)
+                .await?
         }
     };
 
-    debug!("Using feed URI: {}", feed_uri);
+    debug!("Total posts fetched: {}", all_posts.len());
+
+    resolve_repost_originals(provider, &mut all_posts).await;
+    resolve_reply_parents(provider, &mut all_posts).await;
 
-    // Fetch in batches if needed
-    let requested_limit = feed_args.limit.unwrap_or(50);
-    let mut all_posts = Vec::new();
-    let mut cursor = feed_args.continueAtCursor.clone();
+    if date_range.is_active() {
+        all_posts.retain(|entry| entry.created_at().is_some_and(|t| date_range.contains(t)));
+        debug!("{} posts within the requested date range", all_posts.len());
+    }
 
-    while all_posts.len() < requested_limit {
-        let batch_size = std::cmp::min(requested_limit - all_posts.len(), 100); // API limit per request
+    // Feeds containing reposts are chronological (timeline/author style);
+    // order them by repost time. Ranked feed generators keep server order.
+    if all_posts.iter().any(|p| p.repost_reason().is_some()) {
+        sort_chronologically(&mut all_posts);
+    }
 
-        let mut url = format!(
-            "https://public.api.bsky.app/xrpc/app.bsky.feed.getFeed?feed={}&limit={}",
-            urlencoding::encode(&feed_uri),
-            batch_size
-        );
+    let text = format_feed_with(
+        formatter_for(format),
+        "Feed",
+        &all_posts,
+        cursor.as_deref(),
+        feed_args.relativeTime,
+    );
+    Ok(ToolResult::text(text).with_data(FeedOutput { posts: all_posts, cursor }))
+}
 
-        if let Some(ref c) = cursor {
-            url.push_str(&format!("&cursor={}", urlencoding::encode(c)));
-        }
+impl Page for FeedResponse {
+    type Item = FeedViewPost;
 
-        debug!("Fetching batch of {} posts from feed", batch_size);
-
-        let response = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::NetworkError(format!("Failed to fetch feed: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::NetworkError(format!(
-                "Feed API returned error {}: {}",
-                status, error_text
-            )));
-        }
+    fn into_parts(self) -> (Vec<FeedViewPost>, Option<String>) {
+        (self.feed, self.cursor)
+    }
 
-        let feed_response: FeedResponse = response
-            .json()
-            .await
-            .map_err(|e| AppError::ParseError(format!("Failed to parse feed response: {}", e)))?;
+    /// Feed cursors are indexing times, so a page can continue after any entry that has
+    /// one; after an unavailable post the server's cursor is kept
+    fn resume_after(last_kept: &FeedViewPost, cursor: Option<String>) -> Option<String> {
+        last_kept.cursor_time().map(str::to_string).or(cursor)
+    }
+}
 
-        let batch_count = feed_response.feed.len();
-        debug!("Received {} posts in this batch", batch_count);
+/// Page through a feed endpoint (a getFeed or getAuthorFeed URL with its query) until
+/// `requested_limit` posts or the end of the feed, returning the posts and the next cursor
+async fn fetch_feed_pages(
+    client: &reqwest::Client,
+    retry: &RetryPolicy,
+    endpoint: &str,
+    subject: &str,
+    cursor: Option<String>,
+    requested_limit: usize,
+    progress: Option<&Progress>,
+) -> Result<(Vec<FeedViewPost>, Option<String>), AppError> {
+    let list = PagedList {
+        url: endpoint,
+        subject,
+        noun: "posts",
+        page_max: FEED_PAGE_MAX,
+    };
+    fetch_pages::<FeedResponse>(client, retry, &list, cursor, requested_limit, progress).await
+}
 
-        if batch_count == 0 {
-            // No more posts available
-            break;
+/// An account's own posts and reposts from app.bsky.feed.getAuthorFeed at `feed_url`.
+/// When the API cannot be reached or fails on its side, the posts are read from the
+/// account's repo instead, the cached copy first; reposts, counts and embeds are then
+/// missing. Any other error, such as a rate limit or an unreadable page, is returned.
+async fn fetch_author_feed(
+    client: &reqwest::Client,
+    provider: &RepositoryProvider,
+    feed_url: &str,
+    author: &str,
+    cursor: Option<String>,
+    requested_limit: usize,
+    progress: Option<&Progress>,
+) -> Result<(Vec<FeedViewPost>, Option<String>), AppError> {
+    let actor = display_handle(author.trim());
+    let endpoint = format!("{}?actor={}", feed_url, urlencoding::encode(&actor));
+    let retry = provider.retry_policy();
+    let fetched = fetch_feed_pages(
+        client,
+        retry,
+        &endpoint,
+        &actor,
+        cursor.clone(),
+        requested_limit,
+        progress,
+    )
+    .await;
+
+    match fetched {
+        // Offline, timed out, or a 5xx from the AppView: `fetch_pages` reports a server
+        // error status as a network error
+        Err(e @ (AppError::NetworkError(_) | AppError::Timeout(_))) => {
+            warn!("Author feed API unavailable ({}); reading the repo of {} instead", e, actor);
+            let (did, posts) = load_account_posts(provider, &actor).await?;
+            report_progress(progress, || format!("Fetched repository for {}", actor));
+            Ok(repo_feed_page(posts, &did, &actor, cursor.as_deref(), requested_limit))
         }
+        other => other,
+    }
+}
 
-        all_posts.extend(feed_response.feed);
+/// The newest `limit` repo posts created before `cursor`, as feed entries. The cursor is
+/// a creation time, which is also the form getAuthorFeed cursors take.
+fn repo_feed_page(
+    mut posts: Vec<records::PostRecord>,
+    did: &str,
+    handle: &str,
+    cursor: Option<&str>,
+    limit: usize,
+) -> (Vec<FeedViewPost>, Option<String>) {
+    use crate::search::timestamp::parse_timestamp;
 
-        // Update cursor for next batch
-        cursor = feed_response.cursor;
+    let before = cursor.and_then(parse_timestamp);
+    posts.retain(|post| match (before, parse_timestamp(&post.created_at)) {
+        (Some(before), Some(created)) => created < before,
+        (Some(_), None) => false,
+        (None, _) => true,
+    });
+    posts.sort_by_key(|post| std::cmp::Reverse(parse_timestamp(&post.created_at)));
 
-        // If we got fewer posts than requested in this batch, we've reached the end
-        if batch_count < batch_size {
-            break;
-        }
+    let more = posts.len() > limit;
+    posts.truncate(limit);
+    let next_cursor = posts.last().filter(|_| more).map(|post| post.created_at.clone());
 
-        // If there's no cursor, we've reached the end
-        if cursor.is_none() {
-            break;
+    let entries = posts
+        .into_iter()
+        .map(|post| FeedViewPost {
+            post: FeedPostSlot::Post(Box::new(FeedPost {
+                uri: post.uri,
+                cid: post.cid,
+                author: PostAuthor {
+                    did: did.to_string(),
+                    handle: handle.to_string(),
+                    display_name: None,
+                },
+                record: PostRecord {
+                    text: post.text,
+                    created_at: post.created_at.clone(),
+                    facets: post.facets,
+                    reply: None,
+                },
+                indexed_at: post.created_at,
+                like_count: None,
+                reply_count: None,
+                repost_count: None,
+                quote_count: None,
+                embed: None,
+            })),
+            reason: None,
+            reply_parent: None,
+        })
+        .collect();
+    (entries, next_cursor)
+}
+
+/// Fetch originals for reposts the AppView returned without a hydrated post.
+/// Lookups are deduplicated, capped at `MAX_REPOST_ORIGINAL_FETCHES`, and a
+/// failed fetch leaves the entry as a placeholder rather than failing the feed.
+pub(crate) async fn resolve_repost_originals(
+    provider: &RepositoryProvider,
+    entries: &mut [FeedViewPost],
+) {
+    let mut wanted: Vec<String> = Vec::new();
+    for entry in entries.iter() {
+        if let (Some(_), FeedPostSlot::Unavailable { uri }) = (entry.repost_reason(), &entry.post) {
+            if !wanted.contains(uri) && wanted.len() < MAX_REPOST_ORIGINAL_FETCHES {
+                wanted.push(uri.clone());
+            }
         }
     }
 
-    debug!("Total posts fetched: {}", all_posts.len());
+    if wanted.is_empty() {
+        return;
+    }
 
-    debug!("Total posts fetched: {}", all_posts.len());
+    debug!("Fetching {} reposted originals", wanted.len());
+    let fetched = fetch_posts(provider, &wanted).await;
 
-    // Format as markdown per docs/16-mcp-schemas.md spec
-    let mut markdown = String::new();
-    markdown.push_str(&format!("# Feed · {} posts\n\n", all_posts.len()));
+    for entry in entries.iter_mut() {
+        if let FeedPostSlot::Unavailable { uri } = &entry.post {
+            if let Some(original) = fetched.get(uri) {
+                entry.post = FeedPostSlot::Post(Box::new(original.clone()));
+            }
+        }
+    }
+}
 
-    use crate::tools::post_format::*;
-    use std::collections::HashMap;
-    let mut seen_posts: HashMap<String, String> = HashMap::new();
+/// Look up the posts that replies in `entries` answer, so each reply can show its context.
+/// Parents already in the feed are reused; the rest are fetched in batches, capped at
+/// `MAX_REPLY_PARENT_FETCHES`. Parents that cannot be fetched are left unresolved.
+pub(crate) async fn resolve_reply_parents(
+    provider: &RepositoryProvider,
+    entries: &mut [FeedViewPost],
+) {
+    let mut known: HashMap<String, FeedPost> = entries
+        .iter()
+        .filter_map(|entry| match &entry.post {
+            FeedPostSlot::Post(post) => Some((post.uri.clone(), post.as_ref().clone())),
+            FeedPostSlot::Unavailable { .. } => None,
+        })
+        .collect();
 
-    for feed_post in &all_posts {
-        let post = &feed_post.post;
-        let rkey = extract_rkey(&post.uri);
-        let full_id = format!("{}/{}", post.author.handle, rkey);
-
-        // Author ID line
-        let author_id = compact_post_id(&post.author.handle, rkey, &seen_posts);
-        markdown.push_str(&format!("{}\n", author_id));
-        seen_posts.insert(full_id, post.uri.clone());
-
-        // Blockquote content with facets applied
-        let content = if !post.record.facets.is_empty() {
-            blockquote_content_with_facets(&post.record.text, &post.record.facets)
-        } else {
-            blockquote_content(&post.record.text)
+    let mut wanted: Vec<String> = Vec::new();
+    for entry in entries.iter() {
+        let FeedPostSlot::Post(post) = &entry.post else {
+            continue;
         };
-        markdown.push_str(&content);
-        markdown.push('\n');
-
-        // Stats and timestamp
-        let stats = format_stats(
-            post.like_count.unwrap_or(0),
-            post.repost_count.unwrap_or(0),
-            post.quote_count.unwrap_or(0),
-            post.reply_count.unwrap_or(0),
-        );
-        let timestamp = format_timestamp(&post.record.created_at);
+        if let Some(uri) = post.reply_parent_uri() {
+            let uri = uri.to_string();
+            if !known.contains_key(&uri)
+                && !wanted.contains(&uri)
+                && wanted.len() < MAX_REPLY_PARENT_FETCHES
+            {
+                wanted.push(uri);
+            }
+        }
+    }
+
+    if !wanted.is_empty() {
+        debug!("Fetching {} reply parents", wanted.len());
+        known.extend(fetch_posts(provider, &wanted).await);
+    }
+    attach_reply_parents(entries, &known);
+}
+
+/// Attach each reply's parent from `parents`, keyed by post URI
+fn attach_reply_parents(entries: &mut [FeedViewPost], parents: &HashMap<String, FeedPost>) {
+    for entry in entries.iter_mut() {
+        let FeedPostSlot::Post(post) = &entry.post else {
+            continue;
+        };
+        entry.reply_parent = post
+            .reply_parent_uri()
+            .and_then(|uri| parents.get(uri))
+            .map(|parent| Box::new(parent.clone()));
+    }
+}
 
-        if !stats.is_empty() {
-            markdown.push_str(&format!("{}  {}\n", stats, timestamp));
-        } else {
-            markdown.push_str(&format!("{}\n", timestamp));
+/// Fetch posts by URI through the provider's AppView, keyed by URI. A failed fetch is
+/// logged and leaves the map empty, so callers fall back to what the feed already had.
+async fn fetch_posts(provider: &RepositoryProvider, uris: &[String]) -> HashMap<String, FeedPost> {
+    match provider.get_posts::<FeedPost>(uris).await {
+        Ok(posts) => posts.into_iter().map(|p| (p.uri.clone(), p)).collect(),
+        Err(e) => {
+            debug!("Failed to fetch posts: {}", e);
+            HashMap::new()
         }
+    }
+}
 
-        markdown.push('\n');
+/// Sort newest first by repost time (or creation time for plain posts).
+/// Entries without a usable timestamp keep their relative order at the end.
+fn sort_chronologically(entries: &mut [FeedViewPost]) {
+    use crate::search::timestamp::parse_timestamp;
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.sort_time().and_then(parse_timestamp)));
+}
+
+/// Format fetched feed entries as markdown per docs/16-mcp-schemas.md spec
+#[cfg(test)]
+fn format_feed_markdown(
+    entries: &[FeedViewPost],
+    cursor: Option<&str>,
+    relative_time: bool,
+) -> String {
+    let markdown = formatter_for(OutputFormat::Markdown);
+    format_feed_with(markdown, "Feed", entries, cursor, relative_time)
+}
+
+/// Structured feed or timeline page, for `--format json`
+#[derive(Debug, Serialize)]
+pub(crate) struct FeedOutput {
+    pub(crate) posts: Vec<FeedViewPost>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+}
+
+/// Render feed entries under `title` (e.g. `Timeline`) through `formatter`, with relative
+/// or ISO times
+pub(crate) fn format_feed_with(
+    formatter: &dyn OutputFormatter,
+    title: &str,
+    entries: &[FeedViewPost],
+    cursor: Option<&str>,
+    relative_time: bool,
+) -> String {
+    use crate::tools::post_format::{compact_post_id, extract_rkey};
+
+    let mut out = formatter.results_heading(title, entries.len(), "posts");
+
+    let mut seen_posts: HashMap<String, String> = HashMap::new();
+
+    for entry in entries {
+        // Author ID, compacted when the same post was already shown
+        let author_id = match &entry.post {
+            FeedPostSlot::Post(post) => {
+                let rkey = extract_rkey(&post.uri);
+                let author_id = compact_post_id(&post.author.handle, rkey, &seen_posts);
+                seen_posts.insert(format!("{}/{}", post.author.handle, rkey), post.uri.clone());
+                author_id
+            }
+            FeedPostSlot::Unavailable { .. } => String::new(),
+        };
+        out.push_str(&formatter.feed_post(&FeedItem {
+            entry,
+            author_id: &author_id,
+            relative_time,
+        }));
     }
 
     if let Some(c) = cursor {
-        markdown.push_str(&format!("**Next cursor:** `{}`\n", c));
+        out.push_str(&formatter.next_page("Next cursor", c));
     }
 
-    Ok(ToolResult::text(markdown))
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::paging::pages_server;
+    use crate::bluesky::provider::CacheConfig;
 
     #[test]
     fn test_feed_args_deserialize() {
@@ -306,4 +551,488 @@ mod tests {
         assert_eq!(args.limit, None);
         assert_eq!(args.continueAtCursor, None);
     }
+
+    fn repost_entry(original_created: &str, reposted_at: &str, rkey: &str) -> serde_json::Value {
+        serde_json::json!({
+            "post": {
+                "uri": format!("at://did:plc:orig/app.bsky.feed.post/{}", rkey),
+                "cid": "cid",
+                "author": { "did": "did:plc:orig", "handle": "original.test" },
+                "record": { "text": "original text", "createdAt": original_created },
+                "indexedAt": original_created
+            },
+            "reason": {
+                "$type": "app.bsky.feed.defs#reasonRepost",
+                "by": { "did": "did:plc:rp", "handle": "reposter.test" },
+                "indexedAt": reposted_at
+            }
+        })
+    }
+
+    #[test]
+    fn test_repost_renders_reposter_and_original() {
+        let entry: FeedViewPost = serde_json::from_value(repost_entry(
+            "2024-01-01T00:00:00.000Z",
+            "2024-03-01T12:00:00.000Z",
+            "abc",
+        ))
+        .unwrap();
+        let md = format_feed_markdown(&[entry], None, false);
+        assert!(md.contains("♻️ reposted by @reposter.test  2024-03-01T12:00:00Z"));
+        assert!(md.contains("@original.test/abc"));
+        assert!(md.contains("> original text"));
+        assert!(md.contains("2024-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_relative_time_replaces_iso_timestamps() {
+        let entry: FeedViewPost = serde_json::from_value(repost_entry(
+            "2024-01-01T00:00:00.000Z",
+            "2024-03-01T12:00:00.000Z",
+            "abc",
+        ))
+        .unwrap();
+        let md = format_feed_markdown(&[entry], None, true);
+        assert!(!md.contains("2024-01-01T00:00:00Z"));
+        assert!(md.contains("♻️ reposted by @reposter.test  "));
+        assert!(md.contains(" ago"));
+    }
+
+    #[test]
+    fn test_plain_feed_has_no_markdown() {
+        let entry: FeedViewPost = serde_json::from_value(repost_entry(
+            "2024-01-01T00:00:00.000Z",
+            "2024-03-01T12:00:00.000Z",
+            "abc",
+        ))
+        .unwrap();
+        let text = format_feed_with(
+            formatter_for(OutputFormat::Plain),
+            "Feed",
+            &[entry],
+            Some("c2"),
+            false,
+        );
+        assert_eq!(
+            text,
+            "Feed · 1 posts\n\n\
+             ♻️ reposted by @reposter.test  2024-03-01T12:00:00Z\n\
+             @original.test: original text\n\
+             2024-01-01T00:00:00Z\n\n\
+             Next cursor: c2\n"
+        );
+    }
+
+    fn post_json(handle: &str, rkey: &str, text: &str, parent: Option<&str>) -> serde_json::Value {
+        let mut record = serde_json::json!({
+            "text": text,
+            "createdAt": "2024-03-01T12:00:00.000Z"
+        });
+        if let Some(parent) = parent {
+            let parent_ref = serde_json::json!({ "uri": parent, "cid": "bafyparent" });
+            record["reply"] = serde_json::json!({ "root": parent_ref, "parent": parent_ref });
+        }
+        serde_json::json!({
+            "uri": format!("at://did:plc:{}/app.bsky.feed.post/{}", handle, rkey),
+            "cid": "bafypost",
+            "author": {
+                "did": format!("did:plc:{}", handle),
+                "handle": format!("{}.test", handle)
+            },
+            "record": record,
+            "indexedAt": "2024-03-01T12:00:01.000Z"
+        })
+    }
+
+    #[test]
+    fn test_reply_renders_parent_context() {
+        let parent_uri = "at://did:plc:alice/app.bsky.feed.post/parent";
+        let mut entries: Vec<FeedViewPost> = vec![serde_json::from_value(serde_json::json!({
+            "post": post_json("bob", "reply", "Agreed!", Some(parent_uri))
+        }))
+        .unwrap()];
+        let long_text = format!("Has anyone\n tried   the new release? {}", "x".repeat(100));
+        let parent: FeedPost =
+            serde_json::from_value(post_json("alice", "parent", &long_text, None)).unwrap();
+        let parents = HashMap::from([(parent_uri.to_string(), parent)]);
+
+        attach_reply_parents(&mut entries, &parents);
+        let md = format_feed_markdown(&entries, None, false);
+        let context = md.lines().find(|line| line.starts_with("↳ replying to")).unwrap();
+        assert!(context.starts_with("↳ replying to @alice.test: Has anyone tried the new release? x"));
+        assert!(context.ends_with('…'));
+        assert_eq!(context.chars().count(), "↳ replying to @alice.test: ".chars().count() + 81);
+        // The context line sits above the reply's own author line
+        assert!(md.contains("…\n@bob.test/reply\n> Agreed!\n"));
+    }
+
+    #[test]
+    fn test_reply_with_unavailable_parent_shows_uri() {
+        let parent_uri = "at://did:plc:alice/app.bsky.feed.post/gone";
+        let mut entries: Vec<FeedViewPost> = vec![
+            serde_json::from_value(serde_json::json!({
+                "post": post_json("bob", "reply", "Agreed!", Some(parent_uri))
+            }))
+            .unwrap(),
+            serde_json::from_value(serde_json::json!({
+                "post": post_json("carol", "plain", "Not a reply", None)
+            }))
+            .unwrap(),
+        ];
+
+        attach_reply_parents(&mut entries, &HashMap::new());
+        let md = format_feed_markdown(&entries, None, false);
+        assert!(md.contains(&format!("↳ replying to {}\n@bob.test/reply\n", parent_uri)));
+        assert_eq!(md.matches("↳ replying to").count(), 1);
+    }
+
+    #[test]
+    fn test_repost_of_deleted_post_renders_placeholder() {
+        let entry: FeedViewPost = serde_json::from_value(serde_json::json!({
+            "post": { "uri": "at://did:plc:orig/app.bsky.feed.post/gone", "notFound": true },
+            "reason": {
+                "$type": "app.bsky.feed.defs#reasonRepost",
+                "by": { "did": "did:plc:rp", "handle": "reposter.test" },
+                "indexedAt": "2024-03-01T12:00:00.000Z"
+            }
+        }))
+        .unwrap();
+        assert!(matches!(entry.post, FeedPostSlot::Unavailable { .. }));
+        let md = format_feed_markdown(&[entry], None, false);
+        assert!(md.contains("♻️ reposted by @reposter.test"));
+        assert!(md.contains("Original post unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_repost_originals_fetched_from_provider_appview() {
+        use crate::bluesky::provider::CacheConfig;
+
+        let app = axum::Router::new().route(
+            "/xrpc/app.bsky.feed.getPosts",
+            axum::routing::get(|| async {
+                axum::Json(serde_json::json!({ "posts": [{
+                    "uri": "at://did:plc:orig/app.bsky.feed.post/hidden",
+                    "cid": "cid",
+                    "author": { "did": "did:plc:orig", "handle": "original.test" },
+                    "record": { "text": "found again", "createdAt": "2024-01-01T00:00:00Z" },
+                    "indexedAt": "2024-01-01T00:00:00Z"
+                }] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_appview(&base);
+
+        let mut entries: Vec<FeedViewPost> = vec![serde_json::from_value(serde_json::json!({
+            "post": { "uri": "at://did:plc:orig/app.bsky.feed.post/hidden", "blocked": true },
+            "reason": {
+                "$type": "app.bsky.feed.defs#reasonRepost",
+                "by": { "did": "did:plc:rp", "handle": "reposter.test" },
+                "indexedAt": "2024-03-01T12:00:00.000Z"
+            }
+        }))
+        .unwrap()];
+        resolve_repost_originals(&provider, &mut entries).await;
+        let md = format_feed_markdown(&entries, None, false);
+        assert!(md.contains("> found again"), "{}", md);
+    }
+
+    #[test]
+    fn test_reposts_sorted_by_repost_time() {
+        let older_repost: FeedViewPost = serde_json::from_value(repost_entry(
+            "2024-02-01T00:00:00Z",
+            "2024-02-02T00:00:00Z",
+            "newer_original",
+        ))
+        .unwrap();
+        let newer_repost: FeedViewPost = serde_json::from_value(repost_entry(
+            "2023-01-01T00:00:00Z",
+            "2024-05-01T00:00:00Z",
+            "older_original",
+        ))
+        .unwrap();
+        let mut entries = vec![older_repost, newer_repost];
+        sort_chronologically(&mut entries);
+        let md = format_feed_markdown(&entries, None, false);
+        let first = md.find("older_original").unwrap();
+        let second = md.find("newer_original").unwrap();
+        assert!(first < second, "Most recent repost should come first");
+    }
+
+    #[test]
+    fn test_date_range_uses_post_creation_not_repost_time() {
+        let entry: FeedViewPost = serde_json::from_value(repost_entry(
+            "2023-01-01T00:00:00Z",
+            "2024-05-01T00:00:00Z",
+            "old_post",
+        ))
+        .unwrap();
+        assert_eq!(entry.created_at(), Some("2023-01-01T00:00:00Z"));
+        assert_eq!(entry.sort_time(), Some("2024-05-01T00:00:00Z"));
+
+        let recent = DateRange::from_args(Some("2024-01-01"), None, None, None, chrono::Utc::now())
+            .unwrap();
+        assert!(!entry.created_at().is_some_and(|t| recent.contains(t)));
+    }
+
+    #[tokio::test]
+    async fn test_author_feed_pages_follow_cursor() {
+        let mut first = post_json("alice", "3kfirst", "Newest post", None);
+        first["likeCount"] = serde_json::json!(4);
+        first["replyCount"] = serde_json::json!(1);
+        let pages = HashMap::from([
+            (
+                ("app.bsky.feed.getAuthorFeed", None),
+                serde_json::json!({
+                    "feed": [
+                        { "post": first },
+                        { "post": post_json("alice", "3ksecond", "Second post", None) }
+                    ],
+                    "cursor": "2024-03-01T11:00:00.000Z"
+                }),
+            ),
+            (
+                ("app.bsky.feed.getAuthorFeed", Some("2024-03-01T11:00:00.000Z".to_string())),
+                serde_json::json!({
+                    "feed": [{ "post": post_json("alice", "3kthird", "Third post", None) }],
+                    "cursor": "2024-03-01T10:00:00.000Z"
+                }),
+            ),
+        ]);
+        let (url, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let endpoint = format!("{}/xrpc/app.bsky.feed.getAuthorFeed?actor=alice.test", url);
+
+        let retry = RetryPolicy::default();
+        let (entries, cursor) =
+            fetch_feed_pages(&client, &retry, &endpoint, "alice.test", None, 2, None)
+                .await
+                .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(cursor.as_deref(), Some("2024-03-01T11:00:00.000Z"));
+
+        let md = format_feed_markdown(&entries, cursor.as_deref(), false);
+        assert!(md.starts_with("# Feed · 2 posts\n\n@alice.test/3kfirst\n> Newest post\n"));
+        assert!(md.contains("👍 4  💬 1  2024-03-01T12:00:00Z\n"));
+        assert!(md.contains("@alice.test/3ksecond\n> Second post\n"));
+        assert!(md.ends_with("**Next cursor:** `2024-03-01T11:00:00.000Z`\n"));
+
+        // The printed cursor continues where the first call stopped
+        let (entries, cursor) =
+            fetch_feed_pages(&client, &retry, &endpoint, "alice.test", cursor, 2, None)
+                .await
+                .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(cursor.as_deref(), Some("2024-03-01T10:00:00.000Z"));
+        let md = format_feed_markdown(&entries, None, false);
+        assert!(md.contains("@alice.test/3kthird\n> Third post\n"));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].1["actor"], "alice.test");
+        assert_eq!(requests[0].1["limit"], "2");
+        assert!(!requests[0].1.contains_key("cursor"));
+        assert_eq!(requests[1].1["cursor"], "2024-03-01T11:00:00.000Z");
+    }
+
+    #[tokio::test]
+    async fn test_limit_bounds_fetch_and_output() {
+        // The server ignores the page size and sends five posts
+        let feed: Vec<serde_json::Value> = (1..=5)
+            .map(|i| {
+                let mut post = post_json("alice", &format!("3k{}", i), "A post", None);
+                post["indexedAt"] = serde_json::json!(format!("2024-03-01T1{}:00:00.000Z", 5 - i));
+                serde_json::json!({ "post": post })
+            })
+            .collect();
+        let pages = HashMap::from([(
+            ("app.bsky.feed.getAuthorFeed", None),
+            serde_json::json!({ "feed": feed, "cursor": "next" }),
+        )]);
+        let (url, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let endpoint = format!("{}/xrpc/app.bsky.feed.getAuthorFeed?actor=alice.test", url);
+
+        let retry = RetryPolicy::default();
+        let (entries, cursor) =
+            fetch_feed_pages(&client, &retry, &endpoint, "alice.test", None, 3, None)
+                .await
+                .unwrap();
+        assert_eq!(entries.len(), 3);
+        // The next page starts after the third post, not after the two dropped ones
+        assert_eq!(cursor.as_deref(), Some("2024-03-01T12:00:00.000Z"));
+        assert_eq!(requests.lock().unwrap()[0].1["limit"], "3");
+        let md = format_feed_markdown(&entries, None, false);
+        assert!(md.starts_with("# Feed · 3 posts\n"), "{}", md);
+    }
+
+    #[test]
+    fn test_resume_after_unavailable_post_keeps_server_cursor() {
+        let entry = |post| FeedViewPost {
+            post,
+            reason: None,
+            reply_parent: None,
+        };
+        let unavailable = entry(FeedPostSlot::Unavailable {
+            uri: "at://did:plc:alice/app.bsky.feed.post/3kgone".to_string(),
+        });
+        let cursor = Some("server-cursor".to_string());
+        assert_eq!(FeedResponse::resume_after(&unavailable, cursor.clone()), cursor);
+
+        let post: FeedPost =
+            serde_json::from_value(post_json("alice", "3k1", "A post", None)).unwrap();
+        let available = entry(FeedPostSlot::Post(Box::new(post)));
+        assert_eq!(
+            FeedResponse::resume_after(&available, cursor).as_deref(),
+            available.cursor_time()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_author_feed_server_error_falls_back_to_repo() {
+        let did = "did:plc:feedfallback";
+        let car = crate::car::test_support::synthetic_repo_car(
+            Some(did),
+            &[("3kaaa", "From the repo")],
+        );
+        let app = axum::Router::new()
+            .route(
+                "/xrpc/app.bsky.feed.getAuthorFeed",
+                axum::routing::get(|| async { axum::http::StatusCode::SERVICE_UNAVAILABLE }),
+            )
+            .route(
+                "/xrpc/com.atproto.sync.getRepo",
+                axum::routing::get(move || async move { car }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let provider = RepositoryProvider::new(CacheConfig {
+            no_cache: true,
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        });
+        provider.resolver().remember_pds(did, &url);
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let feed_url = format!("{}/xrpc/app.bsky.feed.getAuthorFeed", url);
+
+        let (entries, cursor) =
+            fetch_author_feed(&client, &provider, &feed_url, did, None, 10, None)
+                .await
+                .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(cursor, None);
+        assert!(format_feed_markdown(&entries, None, false).contains("> From the repo\n"));
+    }
+
+    #[tokio::test]
+    async fn test_author_feed_keeps_rate_limit_and_parse_errors() {
+        // Serve getAuthorFeed with a fixed reply; a repo fallback would instead fail to
+        // resolve the .invalid handle
+        async fn author_feed_url(status: u16, headers: &[(&str, &str)], body: &str) -> String {
+            let mut reply_headers = axum::http::HeaderMap::new();
+            for (name, value) in headers {
+                reply_headers.insert(
+                    axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                    value.parse().unwrap(),
+                );
+            }
+            let status = axum::http::StatusCode::from_u16(status).unwrap();
+            let body = body.to_string();
+            let app = axum::Router::new().route(
+                "/xrpc/app.bsky.feed.getAuthorFeed",
+                axum::routing::get(move || async move { (status, reply_headers, body) }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            format!("{}/xrpc/app.bsky.feed.getAuthorFeed", url)
+        }
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let provider = RepositoryProvider::default();
+        let fetch = |feed_url: String| {
+            let (client, provider) = (client.clone(), &provider);
+            async move {
+                fetch_author_feed(&client, provider, &feed_url, "alice.invalid", None, 10, None)
+                    .await
+                    .unwrap_err()
+            }
+        };
+
+        let limited = author_feed_url(429, &[("retry-after", "120")], "{}").await;
+        let err = fetch(limited).await;
+        assert!(matches!(err, AppError::RateLimited(_)), "{:?}", err);
+
+        let garbled = author_feed_url(200, &[], "not json").await;
+        let err = fetch(garbled).await;
+        assert!(matches!(err, AppError::ParseError(_)), "{:?}", err);
+
+        let body = r#"{"error":"InvalidRequest","message":"bad cursor"}"#;
+        let rejected = author_feed_url(400, &[], body).await;
+        let err = fetch(rejected).await;
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_is_rejected() {
+        let args = FeedArgs {
+            limit: Some(0),
+            ..Default::default()
+        };
+        let provider = RepositoryProvider::default();
+        let err = execute_feed(args, OutputFormat::Markdown, &provider).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+
+        // Negative limits never reach the tool: they fail to deserialize
+        let err = handle_feed_impl(serde_json::json!({ "limit": -1 }), None).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_repo_feed_page_matches_api_layout() {
+        let post = |rkey: &str, text: &str, created_at: &str| records::PostRecord {
+            uri: format!("at://did:plc:alice/app.bsky.feed.post/{}", rkey),
+            cid: format!("bafy{}", rkey),
+            text: text.to_string(),
+            created_at: created_at.to_string(),
+            embeds: None,
+            facets: Vec::new(),
+        };
+        let posts = vec![
+            post("3ka", "Oldest", "2024-01-01T00:00:00Z"),
+            post("3kc", "Newest", "2024-03-01T00:00:00Z"),
+            post("3kb", "Middle", "2024-02-01T00:00:00Z"),
+        ];
+
+        let (entries, cursor) =
+            repo_feed_page(posts.clone(), "did:plc:alice", "alice.test", None, 2);
+        assert_eq!(cursor.as_deref(), Some("2024-02-01T00:00:00Z"));
+        let md = format_feed_markdown(&entries, cursor.as_deref(), false);
+        assert_eq!(
+            md,
+            "# Feed · 2 posts\n\n\
+             @alice.test/3kc\n> Newest\n2024-03-01T00:00:00Z\n\n\
+             @alice.test/3kb\n> Middle\n2024-02-01T00:00:00Z\n\n\
+             **Next cursor:** `2024-02-01T00:00:00Z`\n"
+        );
+
+        // The cursor resumes with older posts; the last page has no cursor
+        let (entries, cursor) =
+            repo_feed_page(posts, "did:plc:alice", "alice.test", Some("2024-02-01T00:00:00Z"), 2);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(cursor, None);
+        assert!(format_feed_markdown(&entries, None, false).contains("> Oldest\n"));
+    }
 }
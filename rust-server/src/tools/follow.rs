@@ -0,0 +1,470 @@
+//! Follow tool implementation
+//!
+//! Implements the `follow(followAs, account, unfollow)` MCP tool
+
+use crate::auth::storage::CredentialStorage;
+use crate::auth::session::refresh_buffer;
+use crate::auth::SessionManager;
+use crate::bluesky::did::DidResolver;
+use crate::bluesky::uri::AtUri;
+use crate::cli::FollowArgs;
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use crate::mcp::{McpResponse, ToolResult};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Collection holding the caller's follow records
+const FOLLOW_COLLECTION: &str = "app.bsky.graph.follow";
+
+/// Graph records listed per page while scanning for an existing follow or block
+const GRAPH_PAGE_SIZE: usize = 100;
+
+/// Structured outcome of a follow or moderation call, for `--format json`
+#[derive(Debug, Serialize)]
+pub(crate) struct GraphActionOutput {
+    /// What happened, e.g. `followed` or `alreadyBlocked`
+    pub(crate) outcome: &'static str,
+    pub(crate) account: String,
+    pub(crate) did: String,
+    /// The follow or block record created, found or deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) rkey: Option<String>,
+}
+
+/// Handle follow tool call
+pub async fn handle_follow(id: Option<Value>, args: Value) -> McpResponse {
+    // Set total timeout to 120 seconds
+    match timeout(Duration::from_secs(120), handle_follow_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "Follow request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_follow_impl(args: Value) -> Result<ToolResult, AppError> {
+    // Parse arguments
+    let follow_args: FollowArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    // Execute using shared implementation
+    execute_follow(follow_args).await
+}
+
+/// Execute follow tool (shared implementation for MCP and CLI)
+pub async fn execute_follow(follow_args: FollowArgs) -> Result<ToolResult, AppError> {
+    debug!(
+        "{} request for account: {}, target: {}",
+        if follow_args.unfollow { "Unfollow" } else { "Follow" },
+        follow_args.followAs,
+        follow_args.account
+    );
+
+    if follow_args.followAs.trim().is_empty() {
+        return Err(AppError::InvalidInput("followAs cannot be empty".to_string()));
+    }
+    let subject_did = resolve_subject_did(&DidResolver::new(), &follow_args.account).await?;
+
+    // Get credentials for the account
+    let storage = CredentialStorage::new()?;
+
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &follow_args.followAs, refresh_buffer())
+        .await?;
+
+    debug!("Authenticated as {} (DID: {})", session.handle, session.did);
+
+    if subject_did == session.did {
+        return Err(AppError::InvalidInput(
+            "Cannot follow or unfollow your own account".to_string(),
+        ));
+    }
+
+    let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
+    let existing = find_graph_rkey(&client, &session, FOLLOW_COLLECTION, &subject_did).await?;
+
+    let (markdown, outcome, rkey) = if follow_args.unfollow {
+        let rkey = existing.ok_or_else(|| {
+            AppError::NotFound(format!("Not following {}", follow_args.account))
+        })?;
+        delete_graph_record(&client, &session, FOLLOW_COLLECTION, &rkey).await?;
+        let markdown = format!(
+            "# Unfollowed\n\n**Account:** {} ({})\n\n**Deleted follow record:** `{}`\n",
+            follow_args.account, subject_did, rkey
+        );
+        (markdown, "unfollowed", rkey)
+    } else if let Some(rkey) = existing {
+        // Following twice would only add a duplicate graph edge
+        let markdown = format!(
+            "# Already Following\n\n**Account:** {} ({})\n\n**Follow record:** `{}`\n",
+            follow_args.account, subject_did, rkey
+        );
+        (markdown, "alreadyFollowing", rkey)
+    } else {
+        let rkey = create_graph_record(&client, &session, FOLLOW_COLLECTION, &subject_did).await?;
+        let markdown = format!(
+            "# Followed\n\n**Account:** {} ({})\n\n**Follow record:** `{}`\n",
+            follow_args.account, subject_did, rkey
+        );
+        (markdown, "followed", rkey)
+    };
+
+    let data = GraphActionOutput {
+        outcome,
+        account: follow_args.account,
+        did: subject_did,
+        rkey: Some(rkey),
+    };
+    Ok(ToolResult::text(markdown).with_data(data))
+}
+
+/// Resolve the account to act on (handle, DID or profile URL) to its DID
+pub(crate) async fn resolve_subject_did(
+    resolver: &DidResolver,
+    account: &str,
+) -> Result<String, AppError> {
+    if account.trim().is_empty() {
+        return Err(AppError::InvalidInput("account cannot be empty".to_string()));
+    }
+    resolver
+        .resolve_handle(account)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Could not resolve account: {}", account)))
+}
+
+/// Build a graph record in `collection` (a follow or block) for `subject_did`
+pub(crate) fn graph_record(collection: &str, subject_did: &str, created_at: &str) -> Value {
+    serde_json::json!({
+        "$type": collection,
+        "subject": subject_did,
+        "createdAt": created_at,
+    })
+}
+
+/// Rkey of the record whose subject is `subject_did` in one listRecords page, if any
+fn subject_rkey_in_page(page: &Value, subject_did: &str) -> Option<String> {
+    page["records"]
+        .as_array()?
+        .iter()
+        .find(|record| record["value"]["subject"].as_str() == Some(subject_did))
+        .and_then(|record| record["uri"].as_str())
+        .and_then(|uri| AtUri::parse(uri).ok())
+        .map(|uri| uri.rkey.to_string())
+}
+
+/// Scan every page of the caller's `collection` records for one whose subject is `subject_did`
+pub(crate) async fn find_graph_rkey(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    collection: &str,
+    subject_did: &str,
+) -> Result<Option<String>, AppError> {
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut url = format!(
+            "{}/xrpc/com.atproto.repo.listRecords?repo={}&collection={}&limit={}",
+            session.service, session.did, collection, GRAPH_PAGE_SIZE
+        );
+        if let Some(cursor) = &cursor {
+            url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+        }
+
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", session.access_jwt))
+            .send_xrpc()
+            .await
+            .map_err(|e| {
+                AppError::NetworkError(format!("Failed to list {} records: {}", collection, e))
+            })?;
+
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::NetworkError(format!(
+                "Failed to list {} records: {}",
+                collection, status
+            )));
+        }
+
+        let page: Value = response.json().await.map_err(|e| {
+            AppError::ParseError(format!("Failed to parse {} records: {}", collection, e))
+        })?;
+
+        if let Some(rkey) = subject_rkey_in_page(&page, subject_did) {
+            return Ok(Some(rkey));
+        }
+        match page["cursor"].as_str() {
+            Some(next) if !next.is_empty() && Some(next) != cursor.as_deref() => {
+                cursor = Some(next.to_string());
+            }
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Create a `collection` record for `subject_did`, returning its rkey
+pub(crate) async fn create_graph_record(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    collection: &str,
+    subject_did: &str,
+) -> Result<String, AppError> {
+    let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.service);
+
+    let body = serde_json::json!({
+        "repo": session.did,
+        "collection": collection,
+        "record": graph_record(
+            collection,
+            subject_did,
+            &chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        ),
+    });
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .json(&body)
+        .send_xrpc()
+        .await
+        .map_err(|e| {
+            AppError::NetworkError(format!("Creating {} record failed: {}", collection, e))
+        })?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "Creating {} record failed with status {}: {}",
+            collection, status, error_text
+        )));
+    }
+
+    let result: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse response: {}", e)))?;
+
+    let uri = result["uri"]
+        .as_str()
+        .ok_or_else(|| AppError::ParseError("No URI in response".to_string()))?;
+    Ok(AtUri::parse(uri)?.rkey.to_string())
+}
+
+/// Delete the caller's `collection` record with `rkey`
+pub(crate) async fn delete_graph_record(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    collection: &str,
+    rkey: &str,
+) -> Result<(), AppError> {
+    let url = format!("{}/xrpc/com.atproto.repo.deleteRecord", session.service);
+
+    let body = serde_json::json!({
+        "repo": session.did,
+        "collection": collection,
+        "rkey": rkey
+    });
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .json(&body)
+        .send_xrpc()
+        .await
+        .map_err(|e| {
+            AppError::NetworkError(format!("Deleting {} record failed: {}", collection, e))
+        })?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "Deleting {} record failed with status {}: {}",
+            collection, status, error_text
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_follow_args_parsing() {
+        let args = json!({
+            "followAs": "me.bsky.social",
+            "account": "alice.bsky.social"
+        });
+
+        let parsed: FollowArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.followAs, "me.bsky.social");
+        assert_eq!(parsed.account, "alice.bsky.social");
+        assert!(!parsed.unfollow);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_subject_did_forms() {
+        let resolver = DidResolver::new();
+        for account in [
+            "did:plc:abc123",
+            "https://bsky.app/profile/did:plc:abc123",
+            "  did:plc:abc123  ",
+        ] {
+            assert_eq!(
+                resolve_subject_did(&resolver, account).await.unwrap(),
+                "did:plc:abc123",
+                "{}",
+                account
+            );
+        }
+
+        // Not a handle, so resolution fails without a lookup
+        assert!(matches!(
+            resolve_subject_did(&resolver, "not a handle").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            resolve_subject_did(&resolver, " ").await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_follow_record_shape() {
+        assert_eq!(
+            graph_record(FOLLOW_COLLECTION, "did:plc:alice", "2025-01-01T00:00:00.000Z"),
+            json!({
+                "$type": "app.bsky.graph.follow",
+                "subject": "did:plc:alice",
+                "createdAt": "2025-01-01T00:00:00.000Z"
+            })
+        );
+    }
+
+    #[test]
+    fn test_subject_rkey_in_page_detects_existing_follow() {
+        let page = json!({
+            "records": [
+                {
+                    "uri": "at://did:plc:me/app.bsky.graph.follow/3kbob",
+                    "value": { "subject": "did:plc:bob" }
+                },
+                {
+                    "uri": "at://did:plc:me/app.bsky.graph.follow/3kalice",
+                    "value": { "subject": "did:plc:alice" }
+                }
+            ],
+            "cursor": "3kalice"
+        });
+
+        assert_eq!(
+            subject_rkey_in_page(&page, "did:plc:alice"),
+            Some("3kalice".to_string())
+        );
+        assert_eq!(subject_rkey_in_page(&page, "did:plc:carol"), None);
+        assert_eq!(subject_rkey_in_page(&json!({}), "did:plc:alice"), None);
+
+        // A record whose URI has no rkey is not a match
+        let page = json!({
+            "records": [{
+                "uri": "at://did:plc:me/app.bsky.graph.follow/",
+                "value": { "subject": "did:plc:alice" }
+            }]
+        });
+        assert_eq!(subject_rkey_in_page(&page, "did:plc:alice"), None);
+    }
+
+    #[tokio::test]
+    async fn test_custom_service_receives_create_record() {
+        use crate::auth::{CredentialStorage, Credentials};
+        use axum::{extract::State, routing::post, Json, Router};
+        use std::sync::{Arc, Mutex};
+
+        // A self-hosted PDS recording the repo writes it receives
+        type Writes = Arc<Mutex<Vec<serde_json::Value>>>;
+        let writes: Writes = Arc::default();
+        let app = Router::new()
+            .route(
+                "/xrpc/com.atproto.server.createSession",
+                post(|| async {
+                    Json(json!({
+                        "accessJwt": "access",
+                        "refreshJwt": "refresh",
+                        "handle": "me.pds.example",
+                        "did": "did:plc:me"
+                    }))
+                }),
+            )
+            .route(
+                "/xrpc/com.atproto.repo.createRecord",
+                post(
+                    |State(writes): State<Writes>, Json(body): Json<serde_json::Value>| async move {
+                        writes.lock().unwrap().push(body);
+                        Json(json!({
+                            "uri": "at://did:plc:me/app.bsky.graph.follow/3kfollow",
+                            "cid": "bafyfollow"
+                        }))
+                    },
+                ),
+            )
+            .with_state(writes.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let service = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = CredentialStorage::with_file(dir.path().join("credentials.json"));
+        storage
+            .store_credentials(
+                "me.pds.example",
+                Credentials::with_service("me.pds.example", "app-password", &service),
+            )
+            .unwrap();
+
+        let session = SessionManager::new()
+            .unwrap()
+            .session_for(&storage, "me.pds.example", refresh_buffer())
+            .await
+            .unwrap();
+        assert_eq!(session.service, service);
+
+        let client = reqwest::Client::new();
+        let rkey = create_graph_record(&client, &session, FOLLOW_COLLECTION, "did:plc:alice")
+            .await
+            .unwrap();
+        assert_eq!(rkey, "3kfollow");
+
+        let writes = writes.lock().unwrap();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0]["repo"], "did:plc:me");
+        assert_eq!(writes[0]["collection"], "app.bsky.graph.follow");
+    }
+}
@@ -0,0 +1,786 @@
+//! Output formatters
+//!
+//! Tools describe what they show (a result set, its posts, a profile) through an
+//! `OutputFormatter` instead of building strings themselves, so another rendering can be
+//! swapped in without touching the tools. `MarkdownFormatter` renders the LLM-oriented
+//! markdown that MCP clients and the CLI show by default; `PlainFormatter` renders the
+//! same pieces as undecorated text for `--format plain`. A tool renders only the format
+//! it was asked for, through [`formatter_for`].
+
+use crate::bluesky::blob_url::{blob_host, did_from_at_uri, post_image_preset, BlobHost};
+use crate::bluesky::feed_view::{FeedPost, FeedPostSlot, FeedViewPost};
+use crate::bluesky::records::{
+    Embed, Facet, ImageEmbed, LabelerServiceRecord, PostRecord, ProfileCounts, ProfileRecord,
+    RepoStats,
+};
+use crate::cli::{OutputFormat, SearchArgs};
+use crate::tools::post_format::{
+    apply_bold, blockquote_content, blockquote_content_with_facets, extract_rkey,
+    format_embed_view, format_facet_breakdown, format_post_time, format_relative_time,
+    format_stats, highlight_ranges, truncate_with_ellipsis,
+};
+use serde::Serialize;
+
+/// Default cap on link card description length in search output
+pub const DEFAULT_EMBED_DESC_LEN: usize = 200;
+
+/// Characters of the parent post quoted in a reply's context line
+const REPLY_CONTEXT_CHARS: usize = 80;
+
+/// Rendering options for search results
+#[derive(Debug, Clone)]
+pub struct SearchFormatOptions {
+    /// Maximum characters of an external embed description (0 omits it)
+    pub embed_desc_len: usize,
+    /// Match and highlight terms with their original casing
+    pub case_sensitive: bool,
+    /// List each post's facets below its text, for debugging richtext
+    pub show_facets: bool,
+    /// Character budget for the rendered results; later posts are omitted whole
+    pub max_chars: Option<usize>,
+    /// Show post times relative to now instead of as stored
+    pub relative_time: bool,
+    /// Format the results are rendered in
+    pub output: OutputFormat,
+}
+
+impl Default for SearchFormatOptions {
+    fn default() -> Self {
+        Self {
+            embed_desc_len: DEFAULT_EMBED_DESC_LEN,
+            case_sensitive: false,
+            show_facets: false,
+            max_chars: None,
+            relative_time: false,
+            output: OutputFormat::Markdown,
+        }
+    }
+}
+
+impl SearchFormatOptions {
+    /// Build rendering options from search tool arguments, rendering markdown
+    pub fn from_args(args: &SearchArgs) -> Self {
+        Self {
+            embed_desc_len: args.embedDescLen.unwrap_or(DEFAULT_EMBED_DESC_LEN),
+            case_sensitive: args.caseSensitive,
+            show_facets: args.showFacets,
+            max_chars: args.maxChars,
+            relative_time: args.relativeTime,
+            output: OutputFormat::Markdown,
+        }
+    }
+}
+
+/// One post of a result set, with what was matched in it
+#[derive(Debug, Clone, Copy)]
+pub struct PostItem<'a> {
+    pub post: &'a PostRecord,
+    /// Handle (or DID) the post is attributed to
+    pub handle: &'a str,
+    /// Byte ranges of the post text to highlight
+    pub highlights: &'a [(usize, usize)],
+    /// Query terms, for highlighting inside alt text and quoted posts
+    pub terms: &'a [String],
+}
+
+/// One entry of a feed or timeline
+#[derive(Debug, Clone, Copy)]
+pub struct FeedItem<'a> {
+    pub entry: &'a FeedViewPost,
+    /// ID line for the post, compacted when the post was already shown
+    pub author_id: &'a str,
+    /// Show times relative to now instead of as timestamps
+    pub relative_time: bool,
+}
+
+/// One post of a thread, placed in the tree by the thread tool
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadItem<'a> {
+    pub post: &'a FeedPost,
+    /// ID line for the post, after the threading indicator for replies
+    pub id_line: &'a str,
+    /// List the post's facets below its text, for debugging richtext
+    pub show_facets: bool,
+}
+
+/// One notification
+#[derive(Debug, Clone, Copy)]
+pub struct NotificationItem<'a> {
+    /// Line naming who acted, on what, and when
+    pub header: &'a str,
+    /// Text of the post behind a reply, mention or quote
+    pub text: Option<&'a str>,
+    pub facets: &'a [Facet],
+}
+
+/// A list or feed generator found by a search
+#[derive(Debug, Clone, Copy)]
+pub struct CurationItem<'a> {
+    pub name: &'a str,
+    /// What it is, e.g. `curated list · 3 members` or `feed`
+    pub kind: &'a str,
+    pub uri: &'a str,
+    pub description: Option<&'a str>,
+}
+
+/// A profile with the details the profile tool gathered; also its `--format json` form
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileView {
+    pub handle: String,
+    pub did: String,
+    #[serde(flatten)]
+    pub profile: ProfileRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labeler: Option<LabelerServiceRecord>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<ProfileCounts>,
+    #[serde(rename = "repoStats", skip_serializing_if = "Option::is_none")]
+    pub repo_stats: Option<RepoStats>,
+}
+
+/// Renders tool output piece by piece; each call returns the text for that piece
+pub trait OutputFormatter {
+    /// Opening of a result set: `shown` items of kind `noun` under `title`
+    fn results_heading(&self, title: &str, shown: usize, noun: &str) -> String;
+
+    /// One post of a result set
+    fn post(&self, item: &PostItem, options: &SearchFormatOptions) -> String;
+
+    /// One entry of a feed
+    fn feed_post(&self, item: &FeedItem) -> String;
+
+    /// One post of a thread
+    fn thread_post(&self, item: &ThreadItem) -> String;
+
+    /// One notification
+    fn notification(&self, item: &NotificationItem) -> String;
+
+    /// One list or feed generator
+    fn curation(&self, item: &CurationItem) -> String;
+
+    /// Heading of a group of `count` results within a result set
+    fn section_heading(&self, title: &str, count: usize) -> String;
+
+    /// What stands in for a post that cannot be shown, e.g. `Post not found`
+    fn placeholder(&self, text: &str) -> String;
+
+    /// Closing note that `omitted` more results were left out
+    fn results_omitted(&self, omitted: usize) -> String;
+
+    /// Where the next page starts, e.g. `Next cursor` and its value
+    fn next_page(&self, label: &str, value: &str) -> String;
+
+    /// A whole profile
+    fn profile(&self, view: &ProfileView) -> String;
+}
+
+/// The formatter rendering `format`; JSON output keeps the markdown as its text content,
+/// which is what MCP clients see
+pub fn formatter_for(format: OutputFormat) -> &'static dyn OutputFormatter {
+    match format {
+        OutputFormat::Plain => &PlainFormatter,
+        OutputFormat::Markdown | OutputFormat::Json => &MarkdownFormatter,
+    }
+}
+
+/// The default formatter: markdown with blockquoted post text and bold matches
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownFormatter;
+
+impl OutputFormatter for MarkdownFormatter {
+    fn results_heading(&self, title: &str, shown: usize, noun: &str) -> String {
+        format!("# {} · {} {}\n\n", title, shown, noun)
+    }
+
+    /// The post's ID line, highlighted blockquote, time and embeds, ending with its `---`
+    /// separator
+    fn post(&self, item: &PostItem, options: &SearchFormatOptions) -> String {
+        let PostItem {
+            post,
+            handle,
+            highlights,
+            terms,
+        } = *item;
+        let mut md = String::new();
+        // Extract post id
+        let post_id = extract_rkey(&post.uri);
+        md.push_str(&format!("@{}/{}\n\n", handle, post_id));
+
+        // Quote highlighted text, then the quoted post nested inside: its text when it was
+        // looked up, otherwise its URI
+        md.push_str(&format!("> {}\n", apply_bold(&post.text, highlights)));
+        if let Some(record) = post.quote_embed() {
+            match &record.quoted {
+                Some(quoted) => {
+                    let text = apply_bold(
+                        &quoted.text,
+                        &highlight_ranges(&quoted.text, terms, options.case_sensitive),
+                    );
+                    md.push_str(&format!(
+                        ">\n> > Quoting @{}: {}\n",
+                        quoted.author,
+                        text.replace('\n', "\n> > ")
+                    ));
+                }
+                None => md.push_str(&format!(">\n> > Quoting {}\n", record.uri)),
+            }
+        }
+        md.push('\n');
+
+        if options.show_facets {
+            md.push_str(&format_facet_breakdown(&post.text, &post.facets));
+            md.push('\n');
+        }
+
+        if options.relative_time {
+            let time = format_relative_time(&post.created_at, chrono::Utc::now());
+            md.push_str(&format!("{}\n\n", time));
+        } else {
+            md.push_str(&format!("{}\n\n", post.created_at));
+        }
+
+        // Links and images from embeds, including the media beside a quote
+        for embed in post.embeds_flat() {
+            match embed {
+                Embed::External { external } => {
+                    md.push_str(&format!("- [{}]({})\n", external.title, external.uri));
+                    if options.embed_desc_len > 0 && !external.description.is_empty() {
+                        md.push_str(&format!(
+                            "  {}\n",
+                            truncate_with_ellipsis(&external.description, options.embed_desc_len)
+                        ));
+                    }
+                }
+                Embed::Images { images } => {
+                    for img in images {
+                        let alt = img.alt.as_deref().unwrap_or("Image");
+                        let alt_h = apply_bold(
+                            alt,
+                            &highlight_ranges(alt, terms, options.case_sensitive),
+                        );
+                        let url = image_embed_url(blob_host(), &post.uri, img);
+                        md.push_str(&format!("![{}]({})\n", alt_h, url));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        md.push_str("\n---\n\n");
+        md
+    }
+
+    /// Repost marker, reply context, ID line, blockquoted text, embeds and stats
+    fn feed_post(&self, item: &FeedItem) -> String {
+        let FeedItem {
+            entry,
+            author_id,
+            relative_time,
+        } = *item;
+        let mut markdown = String::new();
+
+        if let Some(marker) = repost_line(entry, relative_time) {
+            markdown.push_str(&marker);
+        }
+
+        let post = match &entry.post {
+            FeedPostSlot::Post(post) => post,
+            FeedPostSlot::Unavailable { uri } => {
+                markdown.push_str(&format!(
+                    "> *Original post unavailable (deleted or blocked): {}*\n\n",
+                    uri
+                ));
+                return markdown;
+            }
+        };
+
+        // Context line naming the post this one replies to
+        if let Some(parent_uri) = post.reply_parent_uri() {
+            markdown.push_str(&reply_context_line(parent_uri, entry.reply_parent.as_deref()));
+        }
+
+        // Author ID line
+        markdown.push_str(&format!("{}\n", author_id));
+
+        markdown.push_str(&quoted_text(&post.record.text, &post.record.facets));
+        markdown.push('\n');
+
+        if let Some(embed) = &post.embed {
+            let embed_md = format_embed_view(embed);
+            if !embed_md.is_empty() {
+                markdown.push_str(&embed_md);
+                markdown.push('\n');
+            }
+        }
+
+        markdown.push_str(&stats_line(post, relative_time));
+        markdown.push('\n');
+        markdown
+    }
+
+    /// ID line, blockquoted text (flush left at every depth), facets and stats
+    fn thread_post(&self, item: &ThreadItem) -> String {
+        let ThreadItem {
+            post,
+            id_line,
+            show_facets,
+        } = *item;
+        let mut markdown = format!("{}\n", id_line);
+        markdown.push_str(&quoted_text(&post.record.text, &post.record.facets));
+        markdown.push('\n');
+        if show_facets {
+            markdown.push_str(&format_facet_breakdown(&post.record.text, &post.record.facets));
+        }
+        markdown.push_str(&stats_line(post, false));
+        markdown.push('\n');
+        markdown
+    }
+
+    /// Header line, then the blockquoted text of a reply, mention or quote
+    fn notification(&self, item: &NotificationItem) -> String {
+        let mut markdown = item.header.to_string();
+        if let Some(text) = item.text {
+            markdown.push_str(&quoted_text(text, item.facets));
+            markdown.push('\n');
+        }
+        markdown.push('\n');
+        markdown
+    }
+
+    /// Bulleted bold name, kind and URI, then the description indented under it
+    fn curation(&self, item: &CurationItem) -> String {
+        let mut markdown = format!("- **{}** · {} · {}\n", item.name, item.kind, item.uri);
+        if let Some(description) = item.description.filter(|d| !d.is_empty()) {
+            markdown.push_str(&format!("  {}\n", description));
+        }
+        markdown
+    }
+
+    fn section_heading(&self, title: &str, count: usize) -> String {
+        format!("## {} · {}\n\n", title, count)
+    }
+
+    fn placeholder(&self, text: &str) -> String {
+        format!("*{}*", text)
+    }
+
+    fn results_omitted(&self, omitted: usize) -> String {
+        format!("…{} more results omitted\n\n", omitted)
+    }
+
+    fn next_page(&self, label: &str, value: &str) -> String {
+        format!("**{}:** `{}`\n", label, value)
+    }
+
+    fn profile(&self, view: &ProfileView) -> String {
+        view.profile.to_markdown_with_details(
+            &view.handle,
+            &view.did,
+            view.labeler.as_ref(),
+            view.counts.as_ref(),
+            view.repo_stats.as_ref(),
+        )
+    }
+}
+
+/// Text without markdown decorations: no bold, blockquotes or headings, and
+/// `@handle: text` lines for posts
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainFormatter;
+
+impl OutputFormatter for PlainFormatter {
+    fn results_heading(&self, title: &str, shown: usize, noun: &str) -> String {
+        format!("{} · {} {}\n\n", title, shown, noun)
+    }
+
+    /// `@handle: text` without highlighting, the quoted post, the time and any link
+    fn post(&self, item: &PostItem, options: &SearchFormatOptions) -> String {
+        let PostItem { post, handle, .. } = *item;
+        let mut text = format!("@{}: {}\n", handle, post.text);
+        if let Some(record) = post.quote_embed() {
+            match &record.quoted {
+                Some(quoted) => {
+                    text.push_str(&format!("  quoting @{}: {}\n", quoted.author, quoted.text))
+                }
+                None => text.push_str(&format!("  quoting {}\n", record.uri)),
+            }
+        }
+
+        if options.relative_time {
+            let time = format_relative_time(&post.created_at, chrono::Utc::now());
+            text.push_str(&format!("{}\n", time));
+        } else {
+            text.push_str(&format!("{}\n", post.created_at));
+        }
+
+        for embed in post.embeds_flat() {
+            if let Embed::External { external } = embed {
+                match external.title.trim() {
+                    "" => text.push_str(&format!("{}\n", external.uri)),
+                    title => text.push_str(&format!("{} {}\n", title, external.uri)),
+                }
+            }
+        }
+
+        text.push('\n');
+        text
+    }
+
+    /// Repost marker, reply context, `@handle: text` and stats
+    fn feed_post(&self, item: &FeedItem) -> String {
+        let FeedItem {
+            entry,
+            relative_time,
+            ..
+        } = *item;
+        let mut text = repost_line(entry, relative_time).unwrap_or_default();
+
+        let post = match &entry.post {
+            FeedPostSlot::Post(post) => post,
+            FeedPostSlot::Unavailable { uri } => {
+                text.push_str(&format!(
+                    "Original post unavailable (deleted or blocked): {}\n\n",
+                    uri
+                ));
+                return text;
+            }
+        };
+
+        if let Some(parent_uri) = post.reply_parent_uri() {
+            text.push_str(&reply_context_line(parent_uri, entry.reply_parent.as_deref()));
+        }
+        text.push_str(&format!("@{}: {}\n", post.author.handle, post.record.text));
+        text.push_str(&stats_line(post, relative_time));
+        text.push('\n');
+        text
+    }
+
+    /// ID line, text and stats
+    fn thread_post(&self, item: &ThreadItem) -> String {
+        let ThreadItem { post, id_line, .. } = *item;
+        format!("{}\n{}\n{}\n", id_line, post.record.text, stats_line(post, false))
+    }
+
+    /// Header line, then the text of a reply, mention or quote
+    fn notification(&self, item: &NotificationItem) -> String {
+        match item.text {
+            Some(text) => format!("{}{}\n\n", item.header, text),
+            None => format!("{}\n", item.header),
+        }
+    }
+
+    /// Name, kind and URI, then the description indented under it
+    fn curation(&self, item: &CurationItem) -> String {
+        let mut text = format!("{} · {} · {}\n", item.name, item.kind, item.uri);
+        if let Some(description) = item.description.filter(|d| !d.is_empty()) {
+            text.push_str(&format!("  {}\n", description));
+        }
+        text
+    }
+
+    fn section_heading(&self, title: &str, count: usize) -> String {
+        format!("{} · {}\n\n", title, count)
+    }
+
+    fn placeholder(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn results_omitted(&self, omitted: usize) -> String {
+        format!("…{} more results omitted\n\n", omitted)
+    }
+
+    fn next_page(&self, label: &str, value: &str) -> String {
+        format!("{}: {}\n", label, value)
+    }
+
+    fn profile(&self, view: &ProfileView) -> String {
+        let mut text = format!("@{} ({})\n\n", view.handle, view.did);
+        if let Some(labeler) = &view.labeler {
+            if labeler.label_values.is_empty() {
+                text.push_str("Labeler service\n");
+            } else {
+                text.push_str(&format!(
+                    "Labeler service · labels: {}\n",
+                    labeler.label_values.join(", ")
+                ));
+            }
+        }
+        if let Some(display_name) = &view.profile.display_name {
+            text.push_str(&format!("Display name: {}\n", display_name));
+        }
+        if let Some(description) = &view.profile.description {
+            text.push_str(&format!("Description: {}\n", description));
+        }
+        text.push_str(&format!("Created: {}\n", view.profile.created_at));
+        for (label, count) in view.counts.iter().flat_map(ProfileCounts::labeled) {
+            text.push_str(&format!("{}: {}\n", label, count));
+        }
+        if let Some(repo_stats) = &view.repo_stats {
+            text.push_str(&format!("Repo: {}\n", repo_stats.summary()));
+        }
+        text
+    }
+}
+
+/// URL of an image embedded in the post at `post_uri`, at the configured post image size.
+/// The mime subtype is the extension.
+fn image_embed_url(host: &BlobHost, post_uri: &str, img: &ImageEmbed) -> String {
+    let ext = img.image.mime_type.split('/').nth(1).unwrap_or("jpeg");
+    let did = did_from_at_uri(post_uri).unwrap_or_default();
+    host.image_url(did, &img.image.ref_, post_image_preset(), ext)
+}
+
+/// Post text blockquoted line by line, with its facets as markdown links
+fn quoted_text(text: &str, facets: &[Facet]) -> String {
+    if facets.is_empty() {
+        blockquote_content(text)
+    } else {
+        blockquote_content_with_facets(text, facets)
+    }
+}
+
+/// Repost marker attributing the reposter, with the repost time
+fn repost_line(entry: &FeedViewPost, relative_time: bool) -> Option<String> {
+    let reason = entry.repost_reason()?;
+    let reposter = reason
+        .by
+        .as_ref()
+        .map(|by| format!("@{}", by.handle))
+        .unwrap_or_else(|| "someone".to_string());
+    Some(match &reason.indexed_at {
+        Some(at) => format!(
+            "♻️ reposted by {}  {}\n",
+            reposter,
+            format_post_time(at, relative_time)
+        ),
+        None => format!("♻️ reposted by {}\n", reposter),
+    })
+}
+
+/// Stats and timestamp (always the original post's creation time)
+fn stats_line(post: &FeedPost, relative_time: bool) -> String {
+    let stats = format_stats(
+        post.like_count.unwrap_or(0),
+        post.repost_count.unwrap_or(0),
+        post.quote_count.unwrap_or(0),
+        post.reply_count.unwrap_or(0),
+    );
+    let timestamp = format_post_time(&post.record.created_at, relative_time);
+
+    if !stats.is_empty() {
+        format!("{}  {}\n", stats, timestamp)
+    } else {
+        format!("{}\n", timestamp)
+    }
+}
+
+/// "↳ replying to @handle: …" with the start of the parent's text, or its URI when unresolved
+fn reply_context_line(parent_uri: &str, parent: Option<&FeedPost>) -> String {
+    let Some(parent) = parent else {
+        return format!("↳ replying to {}\n", parent_uri);
+    };
+    let text = parent.record.text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        format!("↳ replying to @{}\n", parent.author.handle)
+    } else {
+        format!(
+            "↳ replying to @{}: {}\n",
+            parent.author.handle,
+            truncate_with_ellipsis(&text, REPLY_CONTEXT_CHARS)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bluesky::records::BlobRef;
+    use crate::tools::search::{format_search_page, format_search_page_with, AccountHandles};
+
+    /// Writes one line per call, naming what it was given
+    struct CallLog;
+
+    impl OutputFormatter for CallLog {
+        fn results_heading(&self, title: &str, shown: usize, noun: &str) -> String {
+            format!("heading {} {} {}\n", title, shown, noun)
+        }
+
+        fn post(&self, item: &PostItem, _options: &SearchFormatOptions) -> String {
+            format!("post @{} {:?} {:?}\n", item.handle, item.post.text, item.highlights)
+        }
+
+        fn feed_post(&self, item: &FeedItem) -> String {
+            format!("feed post {}\n", item.author_id)
+        }
+
+        fn thread_post(&self, item: &ThreadItem) -> String {
+            format!("thread post {}\n", item.id_line)
+        }
+
+        fn notification(&self, item: &NotificationItem) -> String {
+            format!("notification {}", item.header)
+        }
+
+        fn curation(&self, item: &CurationItem) -> String {
+            format!("curation {} {}\n", item.name, item.kind)
+        }
+
+        fn section_heading(&self, title: &str, count: usize) -> String {
+            format!("section {} {}\n", title, count)
+        }
+
+        fn placeholder(&self, text: &str) -> String {
+            format!("placeholder {}", text)
+        }
+
+        fn results_omitted(&self, omitted: usize) -> String {
+            format!("omitted {}\n", omitted)
+        }
+
+        fn next_page(&self, label: &str, value: &str) -> String {
+            format!("next {} {}\n", label, value)
+        }
+
+        fn profile(&self, view: &ProfileView) -> String {
+            format!("profile @{}\n", view.handle)
+        }
+    }
+
+    fn post(rkey: &str, text: &str) -> PostRecord {
+        PostRecord {
+            uri: format!("at://did:plc:alice/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: text.to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            embeds: None,
+            facets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_search_page_renders_through_formatter() {
+        let posts = [post("1", "rust tips"), post("2", "more Rust")];
+        let refs: Vec<&PostRecord> = posts.iter().collect();
+        let handles = AccountHandles::single("alice.test");
+
+        let (out, shown) = format_search_page_with(
+            &CallLog,
+            &refs,
+            &handles,
+            "rust",
+            &SearchFormatOptions::default(),
+        );
+        assert_eq!(shown, 2);
+        assert_eq!(
+            out,
+            "heading Search Results 2 posts\n\
+             post @alice.test \"rust tips\" [(0, 4)]\n\
+             post @alice.test \"more Rust\" [(5, 9)]\n"
+        );
+
+        // The budget counts the formatter's own output
+        let options = SearchFormatOptions {
+            max_chars: Some(60),
+            ..SearchFormatOptions::default()
+        };
+        let (out, shown) = format_search_page_with(&CallLog, &refs, &handles, "rust", &options);
+        assert_eq!(shown, 1);
+        assert!(out.ends_with("[(0, 4)]\nomitted 1\n"), "{}", out);
+
+        // The markdown formatter is what the tools use by default
+        let (markdown, _) = format_search_page(&refs, &handles, "rust", &options);
+        let (through, _) =
+            format_search_page_with(&MarkdownFormatter, &refs, &handles, "rust", &options);
+        assert_eq!(markdown, through);
+        assert!(markdown.starts_with("# Search Results · "));
+    }
+
+    #[test]
+    fn test_plain_search_result_has_no_markdown() {
+        let posts = [post("1", "I love rust")];
+        let refs: Vec<&PostRecord> = posts.iter().collect();
+        let handles = AccountHandles::single("alice.test");
+        let options = SearchFormatOptions::default();
+
+        let (markdown, _) = format_search_page(&refs, &handles, "rust", &options);
+        assert!(markdown.contains("> I love **rust**"), "{}", markdown);
+
+        let (plain, shown) =
+            format_search_page_with(&PlainFormatter, &refs, &handles, "rust", &options);
+        assert_eq!(shown, 1);
+        assert_eq!(
+            plain,
+            "Search Results · 1 posts\n\n@alice.test: I love rust\n2024-01-01T00:00:00Z\n\n"
+        );
+        assert!(!plain.contains("**") && !plain.contains('>') && !plain.contains('#'));
+    }
+
+    #[test]
+    fn test_plain_profile_and_render() {
+        use crate::cli::OutputFormat;
+        use crate::mcp::ToolResult;
+
+        let profile = ProfileRecord {
+            display_name: Some("Alice".to_string()),
+            description: Some("hello".to_string()),
+            avatar: None,
+            banner: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let counts = ProfileCounts {
+            followers_count: Some(3),
+            follows_count: None,
+            posts_count: Some(7),
+        };
+        let view = ProfileView {
+            handle: "alice.test".to_string(),
+            did: "did:plc:alice".to_string(),
+            profile,
+            labeler: None,
+            counts: Some(counts),
+            repo_stats: None,
+        };
+        let plain = PlainFormatter.profile(&view);
+        assert_eq!(
+            plain,
+            "@alice.test (did:plc:alice)\n\n\
+             Display name: Alice\n\
+             Description: hello\n\
+             Created: 2024-01-01T00:00:00Z\n\
+             Followers: 3\n\
+             Posts: 7\n"
+        );
+
+        let result = ToolResult::text(formatter_for(OutputFormat::Plain).profile(&view));
+        assert_eq!(result.render(OutputFormat::Plain), plain);
+        let markdown = formatter_for(OutputFormat::Json).profile(&view);
+        assert!(markdown.contains("**Display Name:** Alice"), "{}", markdown);
+    }
+
+    #[test]
+    fn test_image_embed_under_url_template() {
+        let img = ImageEmbed {
+            alt: Some("chart".to_string()),
+            image: BlobRef {
+                type_: "blob".to_string(),
+                mime_type: "image/webp".to_string(),
+                ref_: "bafkrei_chart".to_string(),
+                size: 2048,
+            },
+        };
+        let uri = "at://did:plc:test/app.bsky.feed.post/img";
+
+        let host = BlobHost::parse("https://img.example.com/{size}/{did}/{ref}.{ext}").unwrap();
+        assert_eq!(
+            image_embed_url(&host, uri, &img),
+            "https://img.example.com/feed_fullsize/did%3Aplc%3Atest/bafkrei_chart.webp"
+        );
+
+        // The default host keeps the bsky CDN layout
+        assert_eq!(
+            image_embed_url(&BlobHost::default(), uri, &img),
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkrei_chart@webp"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Graph tool implementation
+//!
+//! Implements the `graph(account, direction, limit, continueAtCursor)` MCP tool: an
+//! account's followers (`app.bsky.graph.getFollowers`) or the accounts it follows
+//! (`app.bsky.graph.getFollows`)
+
+use crate::bluesky::did::{parse_account_reference, DidResolver};
+use crate::bluesky::provider::DEFAULT_APPVIEW;
+use crate::cli::GraphArgs;
+use crate::error::{validate_account, AppError};
+use crate::http::{client_with_timeout, retry_policy};
+use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::paging::{fetch_pages, page_limit, Page, PagedList};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Accounts fetched when no limit is given
+const DEFAULT_GRAPH_LIMIT: usize = 50;
+
+/// Largest page `getFollowers` and `getFollows` return
+const MAX_GRAPH_PAGE: usize = 100;
+
+/// Most accounts one call returns (five pages); larger limits are capped
+const MAX_GRAPH_LIMIT: usize = 500;
+
+/// Which side of an account's follow graph to list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphDirection {
+    /// Accounts following it
+    #[default]
+    Followers,
+    /// Accounts it follows
+    Following,
+}
+
+impl GraphDirection {
+    /// Parse the `direction` argument; `None` means followers
+    pub fn parse(direction: Option<&str>) -> Result<Self, AppError> {
+        match direction.map(|d| d.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("followers") => Ok(Self::Followers),
+            Some("following") | Some("follows") => Ok(Self::Following),
+            Some(other) => Err(AppError::InvalidInput(format!(
+                "Unknown direction '{}': use 'followers' or 'following'",
+                other
+            ))),
+        }
+    }
+
+    fn method(self) -> &'static str {
+        match self {
+            Self::Followers => "app.bsky.graph.getFollowers",
+            Self::Following => "app.bsky.graph.getFollows",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Followers => "Followers of",
+            Self::Following => "Followed by",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct GraphActor {
+    did: String,
+    handle: String,
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+}
+
+/// A getFollowers page (`followers`) or a getFollows page (`follows`)
+#[derive(Deserialize, Debug)]
+struct GraphResponse {
+    #[serde(default, alias = "followers", alias = "follows")]
+    accounts: Vec<GraphActor>,
+    cursor: Option<String>,
+}
+
+impl Page for GraphResponse {
+    type Item = GraphActor;
+
+    fn into_parts(self) -> (Vec<GraphActor>, Option<String>) {
+        (self.accounts, self.cursor)
+    }
+}
+
+/// Structured followers or follows page, for `--format json`
+#[derive(Debug, Serialize)]
+struct GraphOutput {
+    handle: String,
+    accounts: Vec<GraphActor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+}
+
+/// Handle graph tool call
+pub async fn handle_graph(id: Option<Value>, args: Value) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_graph_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "Graph request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_graph_impl(args: Value) -> Result<ToolResult, AppError> {
+    let graph_args: GraphArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    execute_graph(graph_args).await
+}
+
+/// Execute graph tool (shared implementation for MCP and CLI)
+pub async fn execute_graph(graph_args: GraphArgs) -> Result<ToolResult, AppError> {
+    validate_account(&graph_args.account)?;
+    let direction = GraphDirection::parse(graph_args.direction.as_deref())?;
+    debug!("Graph request for account: {} ({:?})", graph_args.account, direction);
+
+    let requested_limit =
+        page_limit(graph_args.limit, DEFAULT_GRAPH_LIMIT, MAX_GRAPH_LIMIT, "accounts")?;
+
+    let did = DidResolver::new()
+        .resolve_handle(&graph_args.account)
+        .await?
+        .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+
+    let client = client_with_timeout(Duration::from_secs(120));
+    let (accounts, cursor) = fetch_graph(
+        &client,
+        DEFAULT_APPVIEW,
+        direction,
+        &did,
+        graph_args.continueAtCursor,
+        requested_limit,
+    )
+    .await?;
+
+    let handle = parse_account_reference(&graph_args.account);
+    Ok(graph_result(direction, handle, accounts, cursor))
+}
+
+/// Page through getFollowers or getFollows on `appview` for `actor` until
+/// `requested_limit` accounts or the last one, returning them and the next cursor
+async fn fetch_graph(
+    client: &reqwest::Client,
+    appview: &str,
+    direction: GraphDirection,
+    actor: &str,
+    cursor: Option<String>,
+    requested_limit: usize,
+) -> Result<(Vec<GraphActor>, Option<String>), AppError> {
+    let url = format!(
+        "{}/xrpc/{}?actor={}",
+        appview,
+        direction.method(),
+        urlencoding::encode(actor)
+    );
+    let list = PagedList {
+        url: &url,
+        subject: actor,
+        noun: "accounts",
+        page_max: MAX_GRAPH_PAGE,
+    };
+    fetch_pages::<GraphResponse>(client, retry_policy(), &list, cursor, requested_limit, None)
+        .await
+}
+
+/// Render the accounts as a markdown list, carrying the next cursor in `_meta`
+fn graph_result(
+    direction: GraphDirection,
+    handle: String,
+    accounts: Vec<GraphActor>,
+    cursor: Option<String>,
+) -> ToolResult {
+    let mut markdown =
+        format!("# {} @{} · {} accounts\n\n", direction.title(), handle, accounts.len());
+    if accounts.is_empty() {
+        markdown.push_str("No accounts.\n");
+    }
+
+    for account in &accounts {
+        match account.display_name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => markdown.push_str(&format!(
+                "- @{} ({}) · {}\n",
+                account.handle, name, account.did
+            )),
+            _ => markdown.push_str(&format!("- @{} · {}\n", account.handle, account.did)),
+        }
+    }
+
+    if let Some(c) = &cursor {
+        markdown.push_str(&format!("\n**Next cursor:** `{}`\n", c));
+    }
+
+    let meta = cursor.as_ref().map(|cursor| serde_json::json!({ "cursor": cursor }));
+    let result = ToolResult::text(markdown).with_data(GraphOutput { handle, accounts, cursor });
+    match meta {
+        Some(meta) => result.with_meta(meta),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::paging::pages_server;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    const ALICE: &str = "did:plc:alice";
+
+    fn actor_json(name: &str, display_name: Option<&str>) -> serde_json::Value {
+        let mut actor = json!({ "did": format!("did:plc:{}", name), "handle": name });
+        if let Some(display_name) = display_name {
+            actor["displayName"] = json!(display_name);
+        }
+        actor
+    }
+
+    #[test]
+    fn test_direction_parse() {
+        assert_eq!(GraphDirection::parse(None).unwrap(), GraphDirection::Followers);
+        assert_eq!(
+            GraphDirection::parse(Some("Following")).unwrap(),
+            GraphDirection::Following
+        );
+        assert_eq!(GraphDirection::parse(Some("follows")).unwrap(), GraphDirection::Following);
+        assert!(matches!(
+            GraphDirection::parse(Some("mutuals")),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_followers_render_and_follow_cursor() {
+        let pages = HashMap::from([
+            (
+                ("app.bsky.graph.getFollowers", None),
+                json!({
+                    "subject": actor_json("alice.test", None),
+                    "followers": [
+                        actor_json("bob.test", Some("Bob")),
+                        actor_json("carol.test", None)
+                    ],
+                    "cursor": "page2"
+                }),
+            ),
+            (
+                ("app.bsky.graph.getFollowers", Some("page2".to_string())),
+                json!({
+                    "subject": actor_json("alice.test", None),
+                    "followers": [actor_json("dave.test", Some("Dave"))]
+                }),
+            ),
+        ]);
+        let (appview, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let followers = GraphDirection::Followers;
+
+        let (accounts, cursor) =
+            fetch_graph(&client, &appview, followers, ALICE, None, 2).await.unwrap();
+        assert_eq!(cursor.as_deref(), Some("page2"));
+        let result =
+            graph_result(followers, "alice.test".to_string(), accounts, cursor.clone());
+        assert_eq!(
+            result.content[0].text,
+            "# Followers of @alice.test · 2 accounts\n\n\
+             - @bob.test (Bob) · did:plc:bob.test\n\
+             - @carol.test · did:plc:carol.test\n\n\
+             **Next cursor:** `page2`\n"
+        );
+        let value = serde_json::to_value(&result).unwrap();
+        assert_eq!(value["_meta"], json!({ "cursor": "page2" }));
+        let data = result.data_json().unwrap();
+        assert_eq!(data["handle"], "alice.test");
+        assert_eq!(data["accounts"][1]["did"], "did:plc:carol.test");
+
+        // The printed cursor is passed through to the next request
+        let (accounts, cursor) =
+            fetch_graph(&client, &appview, followers, ALICE, cursor, 2).await.unwrap();
+        assert!(cursor.is_none());
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].handle, "dave.test");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].1["actor"], ALICE);
+        assert_eq!(requests[0].1["limit"], "2");
+        assert!(!requests[0].1.contains_key("cursor"));
+        assert_eq!(requests[1].1["cursor"], "page2");
+    }
+
+    #[tokio::test]
+    async fn test_following_reads_follows() {
+        let pages = HashMap::from([(
+            ("app.bsky.graph.getFollows", None),
+            json!({
+                "subject": actor_json("alice.test", None),
+                "follows": [actor_json("erin.test", Some("Erin"))]
+            }),
+        )]);
+        let (appview, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let following = GraphDirection::Following;
+
+        let (accounts, cursor) =
+            fetch_graph(&client, &appview, following, ALICE, None, 50).await.unwrap();
+        let result = graph_result(following, "alice.test".to_string(), accounts, cursor);
+        assert_eq!(
+            result.content[0].text,
+            "# Followed by @alice.test · 1 accounts\n\n- @erin.test (Erin) · did:plc:erin.test\n"
+        );
+        assert_eq!(requests.lock().unwrap()[0].0, "app.bsky.graph.getFollows");
+    }
+}
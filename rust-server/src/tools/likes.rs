@@ -0,0 +1,255 @@
+//! Likes tool implementation
+//!
+//! Implements the `likes(postURI, limit, continueAtCursor)` MCP tool: the accounts that
+//! liked a post, from `app.bsky.feed.getLikes`
+
+use crate::bluesky::provider::DEFAULT_APPVIEW;
+use crate::cli::LikesArgs;
+use crate::error::AppError;
+use crate::http::{client_with_timeout, retry_policy};
+use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::paging::{fetch_pages, page_limit, Page, PagedList};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Likers fetched when no limit is given
+const DEFAULT_LIKES_LIMIT: usize = 50;
+
+/// Largest page `app.bsky.feed.getLikes` returns
+const MAX_LIKES_PAGE: usize = 100;
+
+/// Most likers one call returns (five pages); larger limits are capped
+const MAX_LIKES_LIMIT: usize = 500;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Liker {
+    did: String,
+    handle: String,
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Like {
+    actor: Liker,
+    #[serde(rename = "createdAt")]
+    created_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LikesResponse {
+    #[serde(default)]
+    likes: Vec<Like>,
+    cursor: Option<String>,
+}
+
+impl Page for LikesResponse {
+    type Item = Like;
+
+    fn into_parts(self) -> (Vec<Like>, Option<String>) {
+        (self.likes, self.cursor)
+    }
+}
+
+/// Structured likes page, for `--format json`
+#[derive(Debug, Serialize)]
+struct LikesOutput {
+    likes: Vec<Like>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+}
+
+/// Handle likes tool call
+pub async fn handle_likes(id: Option<Value>, args: Value) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_likes_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "Likes request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_likes_impl(args: Value) -> Result<ToolResult, AppError> {
+    let likes_args: LikesArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    execute_likes(likes_args).await
+}
+
+/// Execute likes tool (shared implementation for MCP and CLI)
+pub async fn execute_likes(likes_args: LikesArgs) -> Result<ToolResult, AppError> {
+    debug!("Likes request for post: {}", likes_args.postURI);
+
+    let requested_limit =
+        page_limit(likes_args.limit, DEFAULT_LIKES_LIMIT, MAX_LIKES_LIMIT, "accounts")?;
+
+    // getLikes takes the post's CID only to pin a version, so the URI alone is enough
+    let post_ref = crate::bluesky::uri::parse_post_uri(&likes_args.postURI).await?;
+    let post_uri = format!("at://{}/app.bsky.feed.post/{}", post_ref.did, post_ref.rkey);
+
+    let client = client_with_timeout(Duration::from_secs(120));
+    let (likes, cursor) = fetch_likes(
+        &client,
+        DEFAULT_APPVIEW,
+        &post_uri,
+        likes_args.continueAtCursor,
+        requested_limit,
+    )
+    .await?;
+
+    Ok(likes_result(likes, cursor))
+}
+
+/// Page through getLikes on `appview` until `requested_limit` likes or the last one,
+/// returning the likes and the next cursor
+async fn fetch_likes(
+    client: &reqwest::Client,
+    appview: &str,
+    post_uri: &str,
+    cursor: Option<String>,
+    requested_limit: usize,
+) -> Result<(Vec<Like>, Option<String>), AppError> {
+    let url = format!(
+        "{}/xrpc/app.bsky.feed.getLikes?uri={}",
+        appview,
+        urlencoding::encode(post_uri)
+    );
+    let list = PagedList {
+        url: &url,
+        subject: post_uri,
+        noun: "likes",
+        page_max: MAX_LIKES_PAGE,
+    };
+    fetch_pages::<LikesResponse>(client, retry_policy(), &list, cursor, requested_limit, None)
+        .await
+}
+
+/// Render the likers as a markdown list, carrying the next cursor in `_meta`
+fn likes_result(likes: Vec<Like>, cursor: Option<String>) -> ToolResult {
+    let mut markdown = format!("# Likes · {} accounts\n\n", likes.len());
+    if likes.is_empty() {
+        markdown.push_str("No likes on this post.\n");
+    }
+
+    for like in &likes {
+        let actor = &like.actor;
+        match actor.display_name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => {
+                markdown.push_str(&format!("- @{} ({})\n", actor.handle, name))
+            }
+            _ => markdown.push_str(&format!("- @{}\n", actor.handle)),
+        }
+    }
+
+    if let Some(c) = &cursor {
+        markdown.push_str(&format!("\n**Next cursor:** `{}`\n", c));
+    }
+
+    let meta = cursor.as_ref().map(|cursor| serde_json::json!({ "cursor": cursor }));
+    let result = ToolResult::text(markdown).with_data(LikesOutput { likes, cursor });
+    match meta {
+        Some(meta) => result.with_meta(meta),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::paging::pages_server;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    const POST: &str = "at://did:plc:alice/app.bsky.feed.post/3kpost";
+
+    fn like_json(handle: &str, display_name: Option<&str>) -> serde_json::Value {
+        let mut actor = json!({ "did": format!("did:plc:{}", handle), "handle": handle });
+        if let Some(name) = display_name {
+            actor["displayName"] = json!(name);
+        }
+        json!({ "actor": actor, "createdAt": "2025-01-01T00:00:00.000Z" })
+    }
+
+    #[test]
+    fn test_likes_args_parsing() {
+        let args = json!({ "postURI": POST, "limit": 10, "continueAtCursor": "abc" });
+        let parsed: LikesArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.postURI, POST);
+        assert_eq!(parsed.limit, Some(10));
+        assert_eq!(parsed.continueAtCursor.as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_likes_render_as_list_and_follow_cursor() {
+        let pages = HashMap::from([
+            (
+                ("app.bsky.feed.getLikes", None),
+                json!({
+                    "uri": POST,
+                    "likes": [
+                        like_json("bob.test", Some("Bob")),
+                        like_json("carol.test", None)
+                    ],
+                    "cursor": "page2"
+                }),
+            ),
+            (
+                ("app.bsky.feed.getLikes", Some("page2".to_string())),
+                json!({ "uri": POST, "likes": [like_json("dave.test", Some("  "))] }),
+            ),
+        ]);
+        let (appview, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let (likes, cursor) = fetch_likes(&client, &appview, POST, None, 2).await.unwrap();
+        assert_eq!(cursor.as_deref(), Some("page2"));
+        let result = likes_result(likes, cursor.clone());
+        assert_eq!(
+            result.content[0].text,
+            "# Likes · 2 accounts\n\n\
+             - @bob.test (Bob)\n\
+             - @carol.test\n\n\
+             **Next cursor:** `page2`\n"
+        );
+        let value = serde_json::to_value(&result).unwrap();
+        assert_eq!(value["_meta"], json!({ "cursor": "page2" }));
+        let data = result.data_json().unwrap();
+        assert_eq!(data["likes"][0]["actor"]["handle"], "bob.test");
+        assert_eq!(data["cursor"], "page2");
+
+        // The printed cursor continues where the first call stopped
+        let (likes, cursor) =
+            fetch_likes(&client, &appview, POST, cursor, 2).await.unwrap();
+        assert!(cursor.is_none());
+        let result = likes_result(likes, None);
+        assert_eq!(result.content[0].text, "# Likes · 1 accounts\n\n- @dave.test\n");
+        assert!(serde_json::to_value(&result).unwrap().get("_meta").is_none());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].1["uri"], POST);
+        assert_eq!(requests[0].1["limit"], "2");
+        assert!(!requests[0].1.contains_key("cursor"));
+        assert_eq!(requests[1].1["cursor"], "page2");
+    }
+
+    #[test]
+    fn test_no_likes() {
+        let result = likes_result(Vec::new(), None);
+        assert_eq!(result.content[0].text, "# Likes · 0 accounts\n\nNo likes on this post.\n");
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_is_rejected() {
+        let args = LikesArgs {
+            postURI: POST.to_string(),
+            limit: Some(0),
+            ..Default::default()
+        };
+        let err = execute_likes(args).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+}
@@ -0,0 +1,343 @@
+//! List tool implementation
+//!
+//! Implements the `list(account, list)` MCP tool: the lists (`app.bsky.graph.list`) an
+//! account curates, with their members, and the custom feeds (`app.bsky.feed.generator`)
+//! it publishes, read from its repo
+
+use crate::bluesky::did::{display_handle, DidResolver};
+use crate::bluesky::mst::CollectionBlock;
+use crate::bluesky::provider::configured_provider;
+use crate::bluesky::records::{FeedGeneratorRecord, ListItemRecord, ListRecord};
+use crate::cli::ListArgs;
+use crate::error::{validate_account, AppError};
+use crate::mcp::{McpResponse, ToolResult};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+const LIST_COLLECTION: &str = "app.bsky.graph.list";
+const LIST_ITEM_COLLECTION: &str = "app.bsky.graph.listitem";
+const FEED_GENERATOR_COLLECTION: &str = "app.bsky.feed.generator";
+
+/// The collections a repo's lists, their members and its feed generators are read from
+pub const CURATION_COLLECTIONS: [&str; 3] =
+    [LIST_COLLECTION, LIST_ITEM_COLLECTION, FEED_GENERATOR_COLLECTION];
+
+/// Members shown per list when every list is listed; one named list shows them all
+const PREVIEW_MEMBERS: usize = 10;
+
+/// Lists and feed generators declared in one repo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoCurations {
+    pub lists: Vec<ListRecord>,
+    pub feeds: Vec<FeedGeneratorRecord>,
+}
+
+impl RepoCurations {
+    /// Read the lists, their members and the feed generators of a repo CAR, ordered by rkey.
+    /// Records that do not decode are skipped.
+    pub fn from_car_bytes(did: &str, car_bytes: &[u8]) -> Result<Self, AppError> {
+        let blocks =
+            crate::bluesky::mst::extract_collection_blocks(car_bytes, &CURATION_COLLECTIONS)
+                .map_err(|e| {
+                    AppError::RepoParseFailed(format!("Failed to read lists from repo: {}", e))
+                })?;
+        Ok(Self::from_blocks(did, &blocks))
+    }
+
+    /// Build the curations from (collection, rkey, CBOR block) records of
+    /// `CURATION_COLLECTIONS`, as the MST walk yields them
+    pub fn from_blocks(did: &str, blocks: &[CollectionBlock]) -> Self {
+        let mut curations = Self::default();
+        let mut items = Vec::new();
+        for (collection, rkey, cbor_data) in blocks {
+            let uri = format!("at://{}/{}/{}", did, collection, rkey);
+            match collection.as_str() {
+                LIST_COLLECTION => curations.lists.extend(ListRecord::from_cbor(&uri, cbor_data)),
+                LIST_ITEM_COLLECTION => items.extend(ListItemRecord::from_cbor(cbor_data)),
+                FEED_GENERATOR_COLLECTION => {
+                    curations.feeds.extend(FeedGeneratorRecord::from_cbor(&uri, cbor_data))
+                }
+                _ => {}
+            }
+        }
+        for item in items {
+            if let Some(list) = curations.lists.iter_mut().find(|list| list.uri == item.list) {
+                list.members.push(item.subject);
+            }
+        }
+        curations
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty() && self.feeds.is_empty()
+    }
+
+    /// The list named by `reference`: its at:// URI, its rkey, or its name (any case)
+    pub fn find_list(&self, reference: &str) -> Option<&ListRecord> {
+        let reference = reference.trim();
+        self.lists.iter().find(|list| list.uri == reference).or_else(|| {
+            self.lists.iter().find(|list| {
+                list.uri.rsplit('/').next() == Some(reference)
+                    || list.name.to_lowercase() == reference.to_lowercase()
+            })
+        })
+    }
+}
+
+/// Handle list tool call
+pub async fn handle_list(id: Option<Value>, args: Value) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_list_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "List request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_list_impl(args: Value) -> Result<ToolResult, AppError> {
+    let list_args: ListArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    execute_list(list_args).await
+}
+
+/// Execute list tool (shared implementation for MCP and CLI)
+pub async fn execute_list(list_args: ListArgs) -> Result<ToolResult, AppError> {
+    validate_account(&list_args.account)?;
+    debug!("List request for account: {}", list_args.account);
+
+    let did = DidResolver::new()
+        .resolve_handle(&list_args.account)
+        .await?
+        .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+    let provider = configured_provider()?;
+    let car_bytes = provider.fetch_repo_bytes(&did).await?;
+    let curations = RepoCurations::from_car_bytes(&did, &car_bytes)?;
+
+    let handle = display_handle(&list_args.account);
+    if let Some(reference) = list_args.list.as_deref().filter(|r| !r.trim().is_empty()) {
+        let list = curations.find_list(reference).ok_or_else(|| {
+            AppError::NotFound(format!("@{} has no list named {}", handle, reference))
+        })?;
+        let markdown = format!("# @{} · list\n\n{}", handle, list.to_markdown(usize::MAX));
+        let data = ListOutput {
+            handle,
+            did,
+            lists: vec![list.clone()],
+            feeds: Vec::new(),
+        };
+        return Ok(ToolResult::text(markdown).with_data(data));
+    }
+
+    let markdown = format_curations_markdown(&handle, &curations);
+    let data = ListOutput {
+        handle,
+        did,
+        lists: curations.lists,
+        feeds: curations.feeds,
+    };
+    Ok(ToolResult::text(markdown).with_data(data))
+}
+
+/// Markdown overview of an account's lists (first members of each) and feeds
+fn format_curations_markdown(handle: &str, curations: &RepoCurations) -> String {
+    let count = |n: usize, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    let mut markdown = format!(
+        "# @{} · {} · {}\n\n",
+        handle,
+        count(curations.lists.len(), "list"),
+        count(curations.feeds.len(), "feed")
+    );
+    if curations.is_empty() {
+        markdown.push_str("No lists or custom feeds in this repository.\n");
+        return markdown;
+    }
+    for list in &curations.lists {
+        markdown.push_str(&list.to_markdown(PREVIEW_MEMBERS));
+    }
+    for feed in &curations.feeds {
+        markdown.push_str(&feed.to_markdown());
+    }
+    markdown
+}
+
+/// Structured list result, for `--format json`
+#[derive(Debug, Serialize)]
+struct ListOutput {
+    handle: String,
+    did: String,
+    lists: Vec<ListRecord>,
+    feeds: Vec<FeedGeneratorRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::test_support::{encode, synthetic_repo_records_car, Cbor};
+
+    fn bytes(value: Cbor) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(&value, &mut out);
+        out
+    }
+
+    fn list_cbor(name: &str, description: Option<&str>) -> Cbor {
+        let mut fields = vec![
+            ("$type", Cbor::Text("app.bsky.graph.list".to_string())),
+            ("name", Cbor::Text(name.to_string())),
+            ("purpose", Cbor::Text("app.bsky.graph.defs#curatelist".to_string())),
+            ("createdAt", Cbor::Text("2024-05-01T10:00:00.000Z".to_string())),
+        ];
+        if let Some(description) = description {
+            fields.push(("description", Cbor::Text(description.to_string())));
+        }
+        Cbor::Map(fields)
+    }
+
+    #[test]
+    fn test_list_record_parsing() {
+        let uri = "at://did:plc:alice/app.bsky.graph.list/3kl1";
+        let list = ListRecord::from_cbor(uri, &bytes(list_cbor("Rustaceans", Some("Rust folks"))))
+            .unwrap();
+        assert_eq!(list.uri, uri);
+        assert_eq!(list.name, "Rustaceans");
+        assert_eq!(list.purpose, "curatelist");
+        assert_eq!(list.purpose_label(), "curated list");
+        assert_eq!(list.description.as_deref(), Some("Rust folks"));
+        assert_eq!(list.created_at, "2024-05-01T10:00:00.000Z");
+
+        // Name is required by the lexicon
+        let unnamed = Cbor::Map(vec![("purpose", Cbor::Text("x#modlist".to_string()))]);
+        assert_eq!(ListRecord::from_cbor(uri, &bytes(unnamed)), None);
+        assert_eq!(ListRecord::from_cbor(uri, b"not cbor"), None);
+
+        let item = Cbor::Map(vec![
+            ("subject", Cbor::Text("did:plc:bob".to_string())),
+            ("list", Cbor::Text(uri.to_string())),
+        ]);
+        assert_eq!(
+            ListItemRecord::from_cbor(&bytes(item)),
+            Some(ListItemRecord {
+                subject: "did:plc:bob".to_string(),
+                list: uri.to_string()
+            })
+        );
+
+        let feed = Cbor::Map(vec![
+            ("did", Cbor::Text("did:web:feeds.example".to_string())),
+            ("displayName", Cbor::Text("Cat pics".to_string())),
+            ("createdAt", Cbor::Text("2024-05-02T00:00:00.000Z".to_string())),
+        ]);
+        let feed_uri = "at://did:plc:alice/app.bsky.feed.generator/cats";
+        let feed = FeedGeneratorRecord::from_cbor(feed_uri, &bytes(feed)).unwrap();
+        assert_eq!(feed.display_name, "Cat pics");
+        assert_eq!(feed.did, "did:web:feeds.example");
+        assert_eq!(feed.description, None);
+    }
+
+    #[test]
+    fn test_list_markdown() {
+        let mut list = ListRecord {
+            uri: "at://did:plc:alice/app.bsky.graph.list/3kl1".to_string(),
+            name: "Rustaceans".to_string(),
+            purpose: "curatelist".to_string(),
+            description: Some("Rust folks".to_string()),
+            created_at: "2024-05-01T10:00:00.000Z".to_string(),
+            members: vec![],
+        };
+        list.members = (1..=3).map(|i| format!("did:plc:member{}", i)).collect();
+
+        assert_eq!(
+            list.to_markdown(2),
+            "## Rustaceans\n\
+             curated list · 3 members · created 2024-05-01T10:00:00.000Z\n\
+             at://did:plc:alice/app.bsky.graph.list/3kl1\n\
+             \n\
+             Rust folks\n\
+             \n\
+             - did:plc:member1\n\
+             - did:plc:member2\n\
+             - … 1 more\n\
+             \n"
+        );
+        assert!(list.to_markdown(usize::MAX).contains("- did:plc:member3\n"));
+
+        let curations = RepoCurations {
+            lists: vec![list],
+            feeds: vec![FeedGeneratorRecord {
+                uri: "at://did:plc:alice/app.bsky.feed.generator/cats".to_string(),
+                display_name: "Cat pics".to_string(),
+                description: Some("Only cats".to_string()),
+                did: "did:web:feeds.example".to_string(),
+                created_at: "2024-05-02T00:00:00.000Z".to_string(),
+            }],
+        };
+        let md = format_curations_markdown("alice.test", &curations);
+        assert!(md.starts_with("# @alice.test · 1 list · 1 feed\n\n## Rustaceans\n"));
+        assert!(md.contains(
+            "## Cat pics\nfeed · served by did:web:feeds.example · created 2024-05-02T00:00:00.000Z\n"
+        ));
+        assert!(md.ends_with("\nOnly cats\n\n"));
+
+        let empty = format_curations_markdown("bob.test", &RepoCurations::default());
+        assert_eq!(
+            empty,
+            "# @bob.test · 0 lists · 0 feeds\n\nNo lists or custom feeds in this repository.\n"
+        );
+    }
+
+    #[test]
+    fn test_curations_from_repo_car() {
+        let car = synthetic_repo_records_car(
+            Some("did:plc:alice"),
+            vec![
+                ("app.bsky.feed.generator", "cats", feed_cbor()),
+                ("app.bsky.feed.post", "3kpost", post_cbor()),
+                ("app.bsky.graph.list", "3kl1", list_cbor("Rustaceans", None)),
+                ("app.bsky.graph.list", "3kl2", list_cbor("Mods", None)),
+                ("app.bsky.graph.listitem", "3ki1", item_cbor("did:plc:bob", "3kl1")),
+                ("app.bsky.graph.listitem", "3ki2", item_cbor("did:plc:carol", "3kl1")),
+                ("app.bsky.graph.listitem", "3ki3", item_cbor("did:plc:dave", "3kgone")),
+            ],
+        );
+
+        let curations = RepoCurations::from_car_bytes("did:plc:alice", &car).unwrap();
+        let names: Vec<&str> = curations.lists.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Rustaceans", "Mods"]);
+        assert_eq!(curations.lists[0].members, ["did:plc:bob", "did:plc:carol"]);
+        assert!(curations.lists[1].members.is_empty());
+        assert_eq!(curations.feeds.len(), 1);
+        assert_eq!(curations.feeds[0].uri, "at://did:plc:alice/app.bsky.feed.generator/cats");
+
+        assert_eq!(curations.find_list("rustaceans").unwrap().name, "Rustaceans");
+        assert_eq!(curations.find_list("3kl2").unwrap().name, "Mods");
+        let uri = "at://did:plc:alice/app.bsky.graph.list/3kl1";
+        assert_eq!(curations.find_list(uri).unwrap().name, "Rustaceans");
+        assert_eq!(curations.find_list("nope"), None);
+    }
+
+    fn feed_cbor() -> Cbor {
+        Cbor::Map(vec![
+            ("did", Cbor::Text("did:web:feeds.example".to_string())),
+            ("displayName", Cbor::Text("Cat pics".to_string())),
+        ])
+    }
+
+    fn post_cbor() -> Cbor {
+        Cbor::Map(vec![("text", Cbor::Text("hello".to_string()))])
+    }
+
+    fn item_cbor(subject: &str, list_rkey: &str) -> Cbor {
+        Cbor::Map(vec![
+            ("subject", Cbor::Text(subject.to_string())),
+            (
+                "list",
+                Cbor::Text(format!("at://did:plc:alice/app.bsky.graph.list/{}", list_rkey)),
+            ),
+        ])
+    }
+}
@@ -19,7 +19,8 @@ async fn handle_login_impl(args: Value, context: &ServerContext) -> Result<ToolR
 
     // Check if we need elicitation for missing fields
     let needs_handle = command.handle.is_none();
-    let needs_password = command.password.is_none();
+    // The device flow is OAuth, so it never needs an app password
+    let needs_password = command.password.is_none() && !command.device;
 
     // If client supports elicitation and we're missing fields, use it
     if context.supports_elicitation() {
@@ -59,52 +60,10 @@ async fn handle_login_impl(args: Value, context: &ServerContext) -> Result<ToolR
         }
 
         // Elicit password if missing and not using OAuth
-        if needs_password && command.handle.is_some() {
-            let handle = command.handle.as_ref().unwrap();
-            let schema = json!({
-                "type": "object",
-                "properties": {
-                    "password": {
-                        "type": "string",
-                        "description": "BlueSky app password (create at https://bsky.app/settings/app-passwords)"
-                    }
-                },
-                "required": ["password"]
-            });
-
-            let message = format!(
-                "Please provide a BlueSky app password for @{} (NOT your main password).\n\n\
-                Create an app password at: https://bsky.app/settings/app-passwords\n\n\
-                Alternatively, cancel and use OAuth authentication instead.",
-                handle
-            );
-
-            match context.request_elicitation(message, schema).await {
-                Ok(response) => match response.action.as_str() {
-                    "accept" => {
-                        if let Some(content) = response.content {
-                            if let Some(password) = content.get("password").and_then(|v| v.as_str())
-                            {
-                                command.password = Some(password.to_string());
-                            }
-                        }
-                    }
-                    "cancel" => {
-                        return Ok(ToolResult::text(format!(
-                                "Login cancelled. To use OAuth, call login with handle={} and omit the password parameter.",
-                                handle
-                            )));
-                    }
-                    _ => {
-                        return Ok(ToolResult::text("Login declined"));
-                    }
-                },
-                Err(e) => {
-                    tracing::warn!("Password elicitation failed: {}", e);
-                    return Ok(create_password_elicitation_unavailable_error(
-                        context, handle,
-                    ));
-                }
+        if let Some(handle) = command.handle.clone().filter(|_| needs_password) {
+            match elicit_password(context, &handle, None).await {
+                Ok(password) => command.password = password,
+                Err(result) => return Ok(result),
             }
         }
     } else {
@@ -119,21 +78,88 @@ async fn handle_login_impl(args: Value, context: &ServerContext) -> Result<ToolR
     }
 
     let request = LoginRequest {
-        payload: command,
+        payload: command.clone(),
         // Disable interactive prompts when client does not support elicitation
         interactive: context.supports_elicitation(),
     };
 
-    let outcome = manager.execute(request).await?;
-
-    if let Some(_elicitation) = outcome.elicitation {
-        // Elicitation should have been handled via MCP requests above; return message only.
-        return Ok(ToolResult::text(outcome.message));
+    // A device flow keeps polling in the background after its instructions are returned
+    let mut outcome = manager.execute(request).await?;
+
+    // The server rejected the app password: ask again, a few times at most
+    for _ in 1..MAX_PASSWORD_ATTEMPTS {
+        let rejected = outcome.elicitation.as_ref().is_some_and(|e| e.field == "password");
+        let Some(handle) = command.handle.clone().filter(|_| rejected) else {
+            break;
+        };
+        match elicit_password(context, &handle, Some(&outcome.message)).await {
+            Ok(password) => command.password = password,
+            Err(result) => return Ok(result),
+        }
+        outcome = manager
+            .execute(LoginRequest {
+                payload: command.clone(),
+                interactive: true,
+            })
+            .await?;
     }
 
+    // Any elicitation left was not answered; return its message only
     Ok(ToolResult::text(outcome.message))
 }
 
+/// App password prompts offered before giving up on a rejected password
+const MAX_PASSWORD_ATTEMPTS: usize = 3;
+
+/// Ask the client for an app password for `handle`, leading with `retry` after a rejection.
+/// `Err` carries the tool result to return instead (cancelled, declined or unsupported).
+async fn elicit_password(
+    context: &ServerContext,
+    handle: &str,
+    retry: Option<&str>,
+) -> Result<Option<String>, ToolResult> {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "password": {
+                "type": "string",
+                "description": "BlueSky app password (create at https://bsky.app/settings/app-passwords)"
+            }
+        },
+        "required": ["password"]
+    });
+
+    let mut message = format!(
+        "Please provide a BlueSky app password for @{} (NOT your main password).\n\n\
+        Create an app password at: https://bsky.app/settings/app-passwords\n\n\
+        Alternatively, cancel and use OAuth authentication instead.",
+        handle
+    );
+    if let Some(retry) = retry {
+        message = format!("{}\n\n{}", retry, message);
+    }
+
+    match context.request_elicitation(message, schema).await {
+        Ok(response) => match response.action.as_str() {
+            "accept" => Ok(response
+                .content
+                .as_ref()
+                .and_then(|content| content.get("password"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string)),
+            "cancel" => Err(ToolResult::text(format!(
+                "Login cancelled. To use OAuth, call login with handle={} and omit the password parameter.",
+                handle
+            ))),
+            _ => Err(ToolResult::text("Login declined")),
+        },
+        Err(e) => {
+            tracing::warn!("Password elicitation failed: {}", e);
+            Err(create_password_elicitation_unavailable_error(context, handle))
+        }
+    }
+}
+
 /// Create error message when elicitation is unavailable
 pub(crate) fn create_elicitation_unavailable_error(
     context: &ServerContext,
@@ -1,16 +1,32 @@
 //! MCP tools implementation
 
+pub mod apply_writes;
+pub mod blob;
+pub mod date_range;
+pub mod facets;
 pub mod feed;
+pub mod follow;
+pub mod formatter;
+pub mod graph;
+pub mod likes;
+pub mod list;
 pub mod login;
+pub mod moderation;
+pub mod notifications;
+pub mod paging;
 pub mod post;
 pub mod post_format;
 pub mod profile;
 pub mod react;
+pub mod record;
 pub mod search;
+pub mod search_global;
 pub mod thread;
+pub mod timeline;
 pub mod util;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod cli_integration_tests;
 
 #[cfg(test)]
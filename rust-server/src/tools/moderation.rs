@@ -0,0 +1,394 @@
+//! Moderation tool implementation
+//!
+//! Implements the `moderation(moderateAs, account, action)` MCP tool: muting accounts
+//! (`app.bsky.graph.muteActor`, private to the caller) and blocking them (public
+//! `app.bsky.graph.block` records in the caller's repo)
+
+use crate::auth::session::refresh_buffer;
+use crate::auth::storage::CredentialStorage;
+use crate::auth::SessionManager;
+use crate::bluesky::did::DidResolver;
+use crate::cli::ModerationArgs;
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::follow::{
+    create_graph_record, delete_graph_record, find_graph_rkey, resolve_subject_did,
+    GraphActionOutput,
+};
+use anyhow::Result;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Collection holding the caller's block records
+const BLOCK_COLLECTION: &str = "app.bsky.graph.block";
+
+/// What to do to the target account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    Mute,
+    Unmute,
+    Block,
+    Unblock,
+}
+
+impl ModerationAction {
+    /// Parse the `action` argument
+    pub fn parse(action: &str) -> Result<Self, AppError> {
+        match action.trim().to_ascii_lowercase().as_str() {
+            "mute" => Ok(Self::Mute),
+            "unmute" => Ok(Self::Unmute),
+            "block" => Ok(Self::Block),
+            "unblock" => Ok(Self::Unblock),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown action '{}': use mute, unmute, block or unblock",
+                other
+            ))),
+        }
+    }
+}
+
+/// Handle moderation tool call
+pub async fn handle_moderation(id: Option<Value>, args: Value) -> McpResponse {
+    // Set total timeout to 120 seconds
+    match timeout(Duration::from_secs(120), handle_moderation_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => {
+            McpResponse::error(id, "timeout", "Moderation request exceeded 120 second timeout")
+        }
+    }
+}
+
+async fn handle_moderation_impl(args: Value) -> Result<ToolResult, AppError> {
+    let moderation_args: ModerationArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    execute_moderation(moderation_args).await
+}
+
+/// Execute moderation tool (shared implementation for MCP and CLI)
+pub async fn execute_moderation(moderation_args: ModerationArgs) -> Result<ToolResult, AppError> {
+    let action = ModerationAction::parse(&moderation_args.action)?;
+    let subject_did = resolve_subject_did(&DidResolver::new(), &moderation_args.account).await?;
+
+    // Get credentials for the requested, default or only stored account
+    let storage = CredentialStorage::new()?;
+    let account = storage.resolve_account(moderation_args.moderateAs.as_deref())?;
+    debug!(
+        "{:?} request for account: {}, target: {}",
+        action, account, moderation_args.account
+    );
+
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &account, refresh_buffer())
+        .await?;
+
+    debug!("Authenticated as {} (DID: {})", session.handle, session.did);
+
+    if subject_did == session.did {
+        return Err(AppError::InvalidInput(
+            "Cannot mute or block your own account".to_string(),
+        ));
+    }
+
+    let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
+    moderate(&client, &session, action, &moderation_args.account, &subject_did).await
+}
+
+/// Apply `action` to `subject_did` as the session's account, returning the confirmation
+async fn moderate(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    action: ModerationAction,
+    account: &str,
+    subject_did: &str,
+) -> Result<ToolResult, AppError> {
+    let target = format!("**Account:** {} ({})", account, subject_did);
+    let (markdown, outcome, rkey) = match action {
+        ModerationAction::Mute => {
+            set_muted(client, session, subject_did, true).await?;
+            let markdown = format!(
+                "# Muted\n\n{}\n\nTheir posts are hidden from you; they are not told.\n",
+                target
+            );
+            (markdown, "muted", None)
+        }
+        ModerationAction::Unmute => {
+            set_muted(client, session, subject_did, false).await?;
+            (format!("# Unmuted\n\n{}\n", target), "unmuted", None)
+        }
+        ModerationAction::Block => {
+            let existing = find_graph_rkey(client, session, BLOCK_COLLECTION, subject_did).await?;
+            match existing {
+                // Blocking twice would only add a duplicate record
+                Some(rkey) => {
+                    let markdown = format!(
+                        "# Already Blocked\n\n{}\n\n**Block record:** `{}`\n",
+                        target, rkey
+                    );
+                    (markdown, "alreadyBlocked", Some(rkey))
+                }
+                None => {
+                    let rkey =
+                        create_graph_record(client, session, BLOCK_COLLECTION, subject_did).await?;
+                    let markdown =
+                        format!("# Blocked\n\n{}\n\n**Block record:** `{}`\n", target, rkey);
+                    (markdown, "blocked", Some(rkey))
+                }
+            }
+        }
+        ModerationAction::Unblock => {
+            let rkey = find_graph_rkey(client, session, BLOCK_COLLECTION, subject_did)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Not blocking {}", account)))?;
+            delete_graph_record(client, session, BLOCK_COLLECTION, &rkey).await?;
+            let markdown =
+                format!("# Unblocked\n\n{}\n\n**Deleted block record:** `{}`\n", target, rkey);
+            (markdown, "unblocked", Some(rkey))
+        }
+    };
+    let data = GraphActionOutput {
+        outcome,
+        account: account.to_string(),
+        did: subject_did.to_string(),
+        rkey,
+    };
+    Ok(ToolResult::text(markdown).with_data(data))
+}
+
+/// Mute or unmute `subject_did` through the caller's PDS
+async fn set_muted(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    subject_did: &str,
+    muted: bool,
+) -> Result<(), AppError> {
+    let (method, verb) = if muted {
+        ("app.bsky.graph.muteActor", "Mute")
+    } else {
+        ("app.bsky.graph.unmuteActor", "Unmute")
+    };
+    let url = format!("{}/xrpc/{}", session.service, method);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .json(&serde_json::json!({ "actor": subject_did }))
+        .send_xrpc()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("{} request failed: {}", verb, e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "{} failed with status {}: {}",
+            verb, status, error_text
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Query, State};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Requests a fake PDS received: the XRPC method and its query or body
+    type Calls = Arc<Mutex<Vec<(&'static str, Value)>>>;
+
+    /// A PDS holding two pages of block records, the second blocking did:plc:alice
+    async fn pds() -> (crate::auth::Session, Calls) {
+        let calls: Calls = Arc::default();
+        let app = Router::new()
+            .route(
+                "/xrpc/com.atproto.repo.listRecords",
+                get(
+                    |State(calls): State<Calls>,
+                     Query(query): Query<HashMap<String, String>>| async move {
+                        calls.lock().unwrap().push(("listRecords", json!(query)));
+                        Json(match query.get("cursor").map(String::as_str) {
+                            None => json!({
+                                "records": [{
+                                    "uri": "at://did:plc:me/app.bsky.graph.block/3kbob",
+                                    "value": { "subject": "did:plc:bob" }
+                                }],
+                                "cursor": "3kbob"
+                            }),
+                            _ => json!({
+                                "records": [{
+                                    "uri": "at://did:plc:me/app.bsky.graph.block/3kalice",
+                                    "value": { "subject": "did:plc:alice" }
+                                }]
+                            }),
+                        })
+                    },
+                ),
+            )
+            .route(
+                "/xrpc/com.atproto.repo.deleteRecord",
+                post(|State(calls): State<Calls>, Json(body): Json<Value>| async move {
+                    calls.lock().unwrap().push(("deleteRecord", body));
+                    Json(json!({}))
+                }),
+            )
+            .route(
+                "/xrpc/app.bsky.graph.muteActor",
+                post(|State(calls): State<Calls>, Json(body): Json<Value>| async move {
+                    calls.lock().unwrap().push(("muteActor", body));
+                    Json(json!({}))
+                }),
+            )
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let service = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let session = crate::auth::Session {
+            access_jwt: "access".to_string(),
+            refresh_jwt: "refresh".to_string(),
+            handle: "me.test".to_string(),
+            did: "did:plc:me".to_string(),
+            service,
+            expires_at: None,
+            oauth: None,
+        };
+        (session, calls)
+    }
+
+    #[test]
+    fn test_moderation_args_parsing() {
+        let args = json!({
+            "moderateAs": "me.bsky.social",
+            "account": "alice.bsky.social",
+            "action": "block"
+        });
+
+        let parsed: ModerationArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.moderateAs.as_deref(), Some("me.bsky.social"));
+        assert_eq!(parsed.account, "alice.bsky.social");
+        assert_eq!(ModerationAction::parse(&parsed.action).unwrap(), ModerationAction::Block);
+        assert_eq!(ModerationAction::parse(" Unmute ").unwrap(), ModerationAction::Unmute);
+        assert!(matches!(ModerationAction::parse("report"), Err(AppError::InvalidInput(_))));
+
+        let parsed: ModerationArgs = serde_json::from_value(json!({
+            "account": "alice.bsky.social",
+            "action": "mute"
+        }))
+        .unwrap();
+        assert_eq!(parsed.moderateAs, None);
+    }
+
+    #[test]
+    fn test_block_record_shape() {
+        assert_eq!(
+            crate::tools::follow::graph_record(
+                BLOCK_COLLECTION,
+                "did:plc:alice",
+                "2025-01-01T00:00:00.000Z"
+            ),
+            json!({
+                "$type": "app.bsky.graph.block",
+                "subject": "did:plc:alice",
+                "createdAt": "2025-01-01T00:00:00.000Z"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unblock_finds_record_then_deletes_it() {
+        let (session, calls) = pds().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let result = moderate(
+            &client,
+            &session,
+            ModerationAction::Unblock,
+            "alice.test",
+            "did:plc:alice",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.content[0].text,
+            "# Unblocked\n\n**Account:** alice.test (did:plc:alice)\n\n\
+             **Deleted block record:** `3kalice`\n"
+        );
+        assert_eq!(
+            result.data_json().unwrap(),
+            json!({
+                "outcome": "unblocked",
+                "account": "alice.test",
+                "did": "did:plc:alice",
+                "rkey": "3kalice"
+            })
+        );
+
+        let calls = calls.lock().unwrap();
+        let methods: Vec<&str> = calls.iter().map(|(method, _)| *method).collect();
+        assert_eq!(methods, ["listRecords", "listRecords", "deleteRecord"]);
+        assert_eq!(calls[0].1["collection"], "app.bsky.graph.block");
+        assert_eq!(calls[1].1["cursor"], "3kbob");
+        assert_eq!(
+            calls[2].1,
+            json!({ "repo": "did:plc:me", "collection": "app.bsky.graph.block", "rkey": "3kalice" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unblock_without_block_deletes_nothing() {
+        let (session, calls) = pds().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let err =
+            moderate(&client, &session, ModerationAction::Unblock, "carol.test", "did:plc:carol")
+                .await
+                .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{:?}", err);
+        assert!(calls.lock().unwrap().iter().all(|(method, _)| *method == "listRecords"));
+
+        // An existing block is reported instead of duplicated
+        let result =
+            moderate(&client, &session, ModerationAction::Block, "bob.test", "did:plc:bob")
+                .await
+                .unwrap();
+        let markdown = &result.content[0].text;
+        assert!(markdown.starts_with("# Already Blocked\n"), "{}", markdown);
+        assert!(markdown.contains("`3kbob`"), "{}", markdown);
+    }
+
+    #[tokio::test]
+    async fn test_mute_posts_actor() {
+        let (session, calls) = pds().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let result =
+            moderate(&client, &session, ModerationAction::Mute, "bob.test", "did:plc:bob")
+                .await
+                .unwrap();
+        let markdown = &result.content[0].text;
+        assert!(markdown.starts_with("# Muted\n\n**Account:** bob.test (did:plc:bob)\n"));
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [("muteActor", json!({ "actor": "did:plc:bob" }))]
+        );
+    }
+}
@@ -0,0 +1,494 @@
+//! Notifications tool implementation
+//!
+//! Implements the `notifications(viewAs, limit, unreadOnly)` MCP tool
+
+use crate::auth::storage::CredentialStorage;
+use crate::auth::session::refresh_buffer;
+use crate::auth::SessionManager;
+use crate::bluesky::records::Facet;
+use crate::cli::{NotificationsArgs, OutputFormat};
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::formatter::{formatter_for, NotificationItem, OutputFormatter};
+use crate::tools::paging::page_limit;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Notifications fetched when no limit is given
+const DEFAULT_NOTIFICATION_LIMIT: usize = 50;
+
+/// Largest page `app.bsky.notification.listNotifications` returns
+const MAX_NOTIFICATION_LIMIT: usize = 100;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct NotificationAuthor {
+    handle: String,
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+}
+
+/// The record behind a notification; only posts (replies, mentions, quotes) carry text
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+struct NotificationRecord {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    facets: Vec<Facet>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Notification {
+    uri: String,
+    author: NotificationAuthor,
+    reason: String,
+    #[serde(rename = "reasonSubject")]
+    reason_subject: Option<String>,
+    #[serde(default)]
+    record: NotificationRecord,
+    #[serde(rename = "isRead", default)]
+    is_read: bool,
+    #[serde(rename = "indexedAt")]
+    indexed_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct NotificationsResponse {
+    notifications: Vec<Notification>,
+    cursor: Option<String>,
+}
+
+/// Structured notifications page, for `--format json`
+#[derive(Debug, Serialize)]
+struct NotificationsOutput {
+    notifications: Vec<Notification>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<String>,
+}
+
+/// Notification reasons in display order, with their section headings
+const NOTIFICATION_GROUPS: &[(&str, &str)] = &[
+    ("reply", "Replies"),
+    ("mention", "Mentions"),
+    ("quote", "Quotes"),
+    ("repost", "Reposts"),
+    ("like", "Likes"),
+    ("follow", "Follows"),
+];
+
+/// Handle notifications tool call
+pub async fn handle_notifications(id: Option<Value>, args: Value) -> McpResponse {
+    // Set total timeout to 120 seconds
+    match timeout(Duration::from_secs(120), handle_notifications_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(
+            id,
+            "timeout",
+            "Notifications request exceeded 120 second timeout",
+        ),
+    }
+}
+
+async fn handle_notifications_impl(args: Value) -> Result<ToolResult, AppError> {
+    // Parse arguments
+    let notifications_args: NotificationsArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    // Execute using shared implementation
+    execute_notifications(notifications_args, OutputFormat::Markdown).await
+}
+
+/// Execute notifications tool (shared implementation for MCP and CLI), rendering as `format`
+pub async fn execute_notifications(
+    notifications_args: NotificationsArgs,
+    format: OutputFormat,
+) -> Result<ToolResult, AppError> {
+    debug!(
+        "Notifications request for account: {}, unread only: {}",
+        notifications_args.viewAs, notifications_args.unreadOnly
+    );
+
+    if notifications_args.viewAs.trim().is_empty() {
+        return Err(AppError::InvalidInput("viewAs cannot be empty".to_string()));
+    }
+    let limit = page_limit(
+        notifications_args.limit,
+        DEFAULT_NOTIFICATION_LIMIT,
+        MAX_NOTIFICATION_LIMIT,
+        "notifications",
+    )?;
+
+    // Get credentials for the account
+    let storage = CredentialStorage::new()?;
+
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &notifications_args.viewAs, refresh_buffer())
+        .await?;
+
+    debug!("Authenticated as {} (DID: {})", session.handle, session.did);
+
+    let mut url = format!(
+        "{}/xrpc/app.bsky.notification.listNotifications?limit={}",
+        session.service, limit
+    );
+    if let Some(cursor) = &notifications_args.continueAtCursor {
+        url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+    }
+
+    let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .send_xrpc()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Failed to fetch notifications: {}", e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "Notifications request failed with status {}: {}",
+            status, error_text
+        )));
+    }
+
+    let page: NotificationsResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse notifications: {}", e)))?;
+
+    let notifications: Vec<Notification> = if notifications_args.unreadOnly {
+        page.notifications
+            .into_iter()
+            .filter(|n| !n.is_read)
+            .collect()
+    } else {
+        page.notifications
+    };
+
+    let text = format_notifications(
+        formatter_for(format),
+        &notifications,
+        page.cursor.as_deref(),
+    );
+    let data = NotificationsOutput { notifications, cursor: page.cursor };
+    Ok(ToolResult::text(text).with_data(data))
+}
+
+/// Render notifications grouped by reason, newest first within each group
+fn format_notifications(
+    formatter: &dyn OutputFormatter,
+    notifications: &[Notification],
+    cursor: Option<&str>,
+) -> String {
+    let unread = notifications.iter().filter(|n| !n.is_read).count();
+    let mut text = formatter.results_heading(
+        "Notifications",
+        notifications.len(),
+        &format!("({} unread)", unread),
+    );
+
+    if notifications.is_empty() {
+        text.push_str("No notifications.\n\n");
+    }
+
+    let known: Vec<&str> = NOTIFICATION_GROUPS
+        .iter()
+        .map(|(reason, _)| *reason)
+        .collect();
+    let other: Vec<&Notification> = notifications
+        .iter()
+        .filter(|n| !known.contains(&n.reason.as_str()))
+        .collect();
+
+    for (reason, heading) in NOTIFICATION_GROUPS {
+        let group: Vec<&Notification> = notifications
+            .iter()
+            .filter(|n| n.reason == *reason)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        text.push_str(&formatter.section_heading(heading, group.len()));
+        for notification in group {
+            let header = notification_header(notification);
+            // Replies, mentions and quotes are posts; show what they say
+            let post_text = matches!(*reason, "reply" | "mention" | "quote")
+                .then_some(notification.record.text.as_str());
+            text.push_str(&formatter.notification(&NotificationItem {
+                header: &header,
+                text: post_text,
+                facets: &notification.record.facets,
+            }));
+        }
+    }
+
+    if !other.is_empty() {
+        text.push_str(&formatter.section_heading("Other", other.len()));
+        for notification in other {
+            text.push_str(&formatter.notification(&NotificationItem {
+                header: &notification_header(notification),
+                text: None,
+                facets: &[],
+            }));
+        }
+    }
+
+    if let Some(c) = cursor {
+        text.push_str(&formatter.next_page("Next cursor", c));
+    }
+
+    text
+}
+
+/// One line naming who acted, on what, and when
+fn notification_header(notification: &Notification) -> String {
+    use crate::tools::post_format::*;
+
+    let author = &notification.author;
+    let who = match author
+        .display_name
+        .as_deref()
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) => format!("@{} ({})", author.handle, name),
+        None => format!("@{}", author.handle),
+    };
+    let unread_marker = if notification.is_read { "" } else { "🔵 " };
+    let timestamp = format_timestamp(&notification.indexed_at);
+
+    let action = match notification.reason.as_str() {
+        // The notification URI is the post itself
+        "reply" | "mention" | "quote" => {
+            format!("@{}/{}", author.handle, extract_rkey(&notification.uri))
+        }
+        "like" => format!("liked {}", subject_label(notification)),
+        "repost" => format!("reposted {}", subject_label(notification)),
+        "follow" => "followed you".to_string(),
+        other => other.to_string(),
+    };
+
+    match notification.reason.as_str() {
+        "reply" | "mention" | "quote" => {
+            format!("{}{}  {}  {}\n", unread_marker, action, who, timestamp)
+        }
+        _ => format!("{}{} {}  {}\n", unread_marker, who, action, timestamp),
+    }
+}
+
+/// The post a like or repost points at, as `…/rkey`
+fn subject_label(notification: &Notification) -> String {
+    match &notification.reason_subject {
+        Some(subject) => format!(
+            "your post {}",
+            crate::tools::post_format::extract_rkey(subject)
+        ),
+        None => "your post".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::formatter::{MarkdownFormatter, PlainFormatter};
+    use serde_json::json;
+
+    fn sample_response() -> NotificationsResponse {
+        serde_json::from_value(json!({
+            "notifications": [
+                {
+                    "uri": "at://did:plc:bob/app.bsky.feed.like/3klike1",
+                    "cid": "bafylike1",
+                    "author": { "did": "did:plc:bob", "handle": "bob.bsky.social" },
+                    "reason": "like",
+                    "reasonSubject": "at://did:plc:me/app.bsky.feed.post/3kmine",
+                    "record": { "$type": "app.bsky.feed.like", "createdAt": "2025-01-02T10:00:00.000Z" },
+                    "isRead": false,
+                    "indexedAt": "2025-01-02T10:00:01.123Z"
+                },
+                {
+                    "uri": "at://did:plc:alice/app.bsky.feed.post/3kreply",
+                    "cid": "bafyreply",
+                    "author": { "did": "did:plc:alice", "handle": "alice.bsky.social", "displayName": "Alice" },
+                    "reason": "reply",
+                    "reasonSubject": "at://did:plc:me/app.bsky.feed.post/3kmine",
+                    "record": { "$type": "app.bsky.feed.post", "text": "Great point\nfully agree", "createdAt": "2025-01-02T09:00:00.000Z" },
+                    "isRead": true,
+                    "indexedAt": "2025-01-02T09:00:00Z"
+                },
+                {
+                    "uri": "at://did:plc:carol/app.bsky.graph.follow/3kfollow",
+                    "cid": "bafyfollow",
+                    "author": { "did": "did:plc:carol", "handle": "carol.bsky.social", "displayName": "" },
+                    "reason": "follow",
+                    "record": { "$type": "app.bsky.graph.follow", "subject": "did:plc:me" },
+                    "isRead": true,
+                    "indexedAt": "2025-01-01T08:00:00Z"
+                },
+                {
+                    "uri": "at://did:plc:dave/app.bsky.feed.repost/3krepost",
+                    "cid": "bafyrepost",
+                    "author": { "did": "did:plc:dave", "handle": "dave.bsky.social" },
+                    "reason": "repost",
+                    "reasonSubject": "at://did:plc:me/app.bsky.feed.post/3kmine",
+                    "record": {},
+                    "isRead": false,
+                    "indexedAt": "2025-01-01T07:00:00Z"
+                },
+                {
+                    "uri": "at://did:plc:erin/app.bsky.feed.post/3kmention",
+                    "cid": "bafymention",
+                    "author": { "did": "did:plc:erin", "handle": "erin.bsky.social" },
+                    "reason": "mention",
+                    "record": { "text": "hey @me.bsky.social", "createdAt": "2025-01-01T06:00:00Z" },
+                    "isRead": false,
+                    "indexedAt": "2025-01-01T06:00:00Z"
+                },
+                {
+                    "uri": "at://did:plc:bob/app.bsky.feed.like/3klike2",
+                    "cid": "bafylike2",
+                    "author": { "did": "did:plc:frank", "handle": "frank.bsky.social" },
+                    "reason": "like",
+                    "reasonSubject": "at://did:plc:me/app.bsky.feed.post/3kother",
+                    "record": {},
+                    "isRead": true,
+                    "indexedAt": "2025-01-01T05:00:00Z"
+                }
+            ],
+            "cursor": "2025-01-01T05:00:00Z"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_notifications_args_parsing() {
+        let args = json!({ "viewAs": "me.bsky.social", "limit": 20, "unreadOnly": true });
+
+        let parsed: NotificationsArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.viewAs, "me.bsky.social");
+        assert_eq!(parsed.limit, Some(20));
+        assert!(parsed.unreadOnly);
+
+        let parsed: NotificationsArgs =
+            serde_json::from_value(json!({ "viewAs": "me.bsky.social" })).unwrap();
+        assert!(!parsed.unreadOnly);
+        assert_eq!(parsed.limit, None);
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_is_rejected() {
+        let args: NotificationsArgs =
+            serde_json::from_value(json!({ "viewAs": "me.bsky.social", "limit": 0 })).unwrap();
+        let err = execute_notifications(args, OutputFormat::Markdown).await.unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn test_format_notifications_groups_by_reason() {
+        let response = sample_response();
+        let markdown = format_notifications(
+            &MarkdownFormatter,
+            &response.notifications,
+            response.cursor.as_deref(),
+        );
+
+        assert!(
+            markdown.starts_with("# Notifications · 6 (3 unread)\n\n"),
+            "{}",
+            markdown
+        );
+
+        // Groups appear in a fixed order regardless of payload order
+        let order: Vec<usize> = [
+            "## Replies · 1",
+            "## Mentions · 1",
+            "## Reposts · 1",
+            "## Likes · 2",
+            "## Follows · 1",
+        ]
+        .iter()
+        .map(|heading| {
+            markdown
+                .find(heading)
+                .unwrap_or_else(|| panic!("missing {}", heading))
+        })
+        .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]), "{}", markdown);
+        assert!(!markdown.contains("## Quotes"));
+
+        // Reply text is blockquoted line by line
+        assert!(markdown.contains(
+            "@alice.bsky.social/3kreply  @alice.bsky.social (Alice)  2025-01-02T09:00:00Z\n> Great point\n> fully agree\n"
+        ), "{}", markdown);
+        assert!(markdown.contains("🔵 @erin.bsky.social/3kmention  @erin.bsky.social  2025-01-01T06:00:00Z\n> hey @me.bsky.social\n"));
+
+        // Likes and reposts name the subject post, unread ones are marked
+        assert!(
+            markdown.contains("🔵 @bob.bsky.social liked your post 3kmine  2025-01-02T10:00:01Z\n")
+        );
+        assert!(
+            markdown.contains("@frank.bsky.social liked your post 3kother  2025-01-01T05:00:00Z\n")
+        );
+        assert!(markdown
+            .contains("🔵 @dave.bsky.social reposted your post 3kmine  2025-01-01T07:00:00Z\n"));
+        assert!(markdown.contains("@carol.bsky.social followed you  2025-01-01T08:00:00Z\n"));
+
+        assert!(markdown.ends_with("**Next cursor:** `2025-01-01T05:00:00Z`\n"));
+    }
+
+    #[test]
+    fn test_format_notifications_plain() {
+        let response = sample_response();
+        let text = format_notifications(
+            &PlainFormatter,
+            &response.notifications,
+            response.cursor.as_deref(),
+        );
+
+        assert!(text.starts_with("Notifications · 6 (3 unread)\n\nReplies · 1\n\n"), "{}", text);
+        assert!(text.contains(
+            "@alice.bsky.social/3kreply  @alice.bsky.social (Alice)  2025-01-02T09:00:00Z\n\
+             Great point\nfully agree\n\n"
+        ));
+        assert!(text.contains("🔵 @bob.bsky.social liked your post 3kmine  2025-01-02T10:00:01Z\n"));
+        assert!(text.ends_with("Next cursor: 2025-01-01T05:00:00Z\n"));
+        assert!(!text.contains("> ") && !text.contains("**") && !text.contains('#'));
+    }
+
+    #[test]
+    fn test_format_notifications_unknown_reason_and_empty() {
+        let notifications: Vec<Notification> = serde_json::from_value(json!([{
+            "uri": "at://did:plc:x/app.bsky.feed.post/3kx",
+            "author": { "handle": "x.bsky.social" },
+            "reason": "starterpack-joined",
+            "isRead": true,
+            "indexedAt": "2025-01-01T00:00:00Z"
+        }]))
+        .unwrap();
+        let markdown = format_notifications(&MarkdownFormatter, &notifications, None);
+        assert!(markdown
+            .contains("## Other · 1\n\n@x.bsky.social starterpack-joined  2025-01-01T00:00:00Z\n"));
+        assert!(!markdown.contains("Next cursor"));
+
+        let empty = format_notifications(&MarkdownFormatter, &[], None);
+        assert_eq!(
+            empty,
+            "# Notifications · 0 (0 unread)\n\nNo notifications.\n\n"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Cursor paging for AppView list endpoints
+//!
+//! getLikes, getFollowers, getFollows, getFeed and getAuthorFeed return one page per call
+//! with a cursor for the next. The tools listing them read up to a limit across pages
+//! through [`fetch_pages`], and bound that limit with [`page_limit`].
+
+use crate::error::AppError;
+use crate::http::{rate_limit_error, RetryPolicy, SendXrpc, XrpcError};
+use crate::mcp::{report_progress, Progress};
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+/// One page of a cursor-paged list endpoint
+pub(crate) trait Page: DeserializeOwned {
+    type Item;
+
+    /// The page's items and the cursor for the next page
+    fn into_parts(self) -> (Vec<Self::Item>, Option<String>);
+
+    /// Where the next page starts when the items after `last_kept` were dropped because
+    /// the server sent more than was asked for. Opaque cursors cannot say, so by default
+    /// the page's own `cursor` is kept.
+    fn resume_after(_last_kept: &Self::Item, cursor: Option<String>) -> Option<String> {
+        cursor
+    }
+}
+
+/// A cursor-paged list endpoint
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PagedList<'a> {
+    /// Endpoint URL with its query; `limit` and `cursor` are appended
+    pub(crate) url: &'a str,
+    /// Account or post the list belongs to, named when the AppView cannot show it
+    pub(crate) subject: &'a str,
+    /// What the list holds, for errors and progress, e.g. `likes`
+    pub(crate) noun: &'a str,
+    /// Largest page the endpoint returns
+    pub(crate) page_max: usize,
+}
+
+/// The number of items to fetch: `default` when none is given, capped at `max`.
+/// Zero is rejected.
+pub(crate) fn page_limit(
+    limit: Option<usize>,
+    default: usize,
+    max: usize,
+    noun: &str,
+) -> Result<usize, AppError> {
+    match limit {
+        None => Ok(default),
+        Some(0) => Err(AppError::InvalidInput(format!(
+            "limit must be a positive number of {}",
+            noun
+        ))),
+        Some(limit) => Ok(limit.min(max)),
+    }
+}
+
+/// Page through `list` from `cursor` until `limit` items or the last one, returning the
+/// items and the next cursor. A rate limit is waited out under `retry`, and each page
+/// fetched is reported to `progress`.
+pub(crate) async fn fetch_pages<P: Page>(
+    client: &reqwest::Client,
+    retry: &RetryPolicy,
+    list: &PagedList<'_>,
+    mut cursor: Option<String>,
+    limit: usize,
+    progress: Option<&Progress>,
+) -> Result<(Vec<P::Item>, Option<String>), AppError> {
+    let mut items: Vec<P::Item> = Vec::new();
+
+    while items.len() < limit {
+        let batch_size = std::cmp::min(limit - items.len(), list.page_max);
+        let mut url = format!("{}&limit={}", list.url, batch_size);
+        if let Some(ref c) = cursor {
+            url.push_str(&format!("&cursor={}", urlencoding::encode(c)));
+        }
+
+        debug!("Fetching batch of {} {}", batch_size, list.noun);
+
+        let response = client
+            .get(&url)
+            .send_xrpc_with(retry)
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to fetch {}: {}", list.noun, e)))?;
+
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            let unavailable =
+                XrpcError::parse(&error_text).and_then(|x| x.unavailable_error(list.subject));
+            if let Some(e) = unavailable {
+                return Err(e);
+            }
+            let message = format!(
+                "AppView returned error {} listing {}: {}",
+                status, list.noun, error_text
+            );
+            // A server failure is the AppView's; any other status refuses the request itself
+            if status.is_server_error() {
+                return Err(AppError::NetworkError(message));
+            }
+            return Err(AppError::InvalidInput(message));
+        }
+
+        let page: P = response.json().await.map_err(|e| {
+            AppError::ParseError(format!("Failed to parse {} response: {}", list.noun, e))
+        })?;
+        let (batch, next) = page.into_parts();
+        let batch_count = batch.len();
+        debug!("Received {} {} in this batch", batch_count, list.noun);
+
+        items.extend(batch);
+        cursor = next;
+
+        // A server may send more than the page size asked for: keep only the limit, and
+        // continue after the last item kept where the cursor allows
+        let overfull = items.len() > limit;
+        if overfull {
+            items.truncate(limit);
+            if let Some(last) = items.last() {
+                cursor = P::resume_after(last, cursor);
+            }
+        }
+        report_progress(progress, || {
+            format!("Fetched {} of up to {} {}", items.len(), limit, list.noun)
+        });
+
+        // An overfull, short or empty page, or a missing cursor, means the last item
+        if overfull || batch_count < batch_size || cursor.is_none() {
+            break;
+        }
+    }
+
+    Ok((items, cursor))
+}
+
+/// Requests a [`pages_server`] received: each one's XRPC method and query
+#[cfg(test)]
+pub(crate) type SeenRequests =
+    std::sync::Arc<std::sync::Mutex<Vec<(String, std::collections::HashMap<String, String>)>>>;
+
+/// Serve XRPC list pages keyed by method and cursor, recording each request. Returns
+/// the server's base URL.
+#[cfg(test)]
+pub(crate) async fn pages_server(
+    pages: std::collections::HashMap<(&'static str, Option<String>), serde_json::Value>,
+) -> (String, SeenRequests) {
+    use axum::extract::{Path, Query};
+    use std::collections::HashMap;
+
+    let requests = SeenRequests::default();
+    let seen = requests.clone();
+    let app = axum::Router::new().route(
+        "/xrpc/:method",
+        axum::routing::get(
+            move |Path(method): Path<String>, Query(query): Query<HashMap<String, String>>| {
+                let cursor = query.get("cursor").cloned();
+                let page = pages
+                    .iter()
+                    .find(|((m, c), _)| *m == method && *c == cursor)
+                    .map(|(_, page)| page.clone())
+                    .unwrap();
+                seen.lock().unwrap().push((method, query));
+                async move { axum::Json(page) }
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize)]
+    struct NumbersPage {
+        numbers: Vec<u32>,
+        cursor: Option<String>,
+    }
+
+    impl Page for NumbersPage {
+        type Item = u32;
+
+        fn into_parts(self) -> (Vec<u32>, Option<String>) {
+            (self.numbers, self.cursor)
+        }
+
+        fn resume_after(last_kept: &u32, _cursor: Option<String>) -> Option<String> {
+            Some(format!("after{}", last_kept))
+        }
+    }
+
+    fn numbers_list(url: &str) -> PagedList<'_> {
+        PagedList {
+            url,
+            subject: "did:plc:alice",
+            noun: "numbers",
+            page_max: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_cursor_up_to_limit() {
+        let pages = HashMap::from([
+            (("test.numbers", None), json!({ "numbers": [1, 2], "cursor": "p2" })),
+            (
+                ("test.numbers", Some("p2".to_string())),
+                json!({ "numbers": [3, 4], "cursor": "p3" }),
+            ),
+            (("test.numbers", Some("p3".to_string())), json!({ "numbers": [5] })),
+        ]);
+        let (base, requests) = pages_server(pages).await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let url = format!("{}/xrpc/test.numbers?actor=alice", base);
+
+        let retry = RetryPolicy::default();
+        let (numbers, cursor) =
+            fetch_pages::<NumbersPage>(&client, &retry, &numbers_list(&url), None, 3, None)
+                .await
+                .unwrap();
+        // The second page is asked for one item, but the server sends two
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(cursor.as_deref(), Some("after3"));
+
+        let (numbers, cursor) = fetch_pages::<NumbersPage>(
+            &client,
+            &retry,
+            &numbers_list(&url),
+            Some("p3".to_string()),
+            10,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(numbers, vec![5]);
+        assert!(cursor.is_none());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].1["actor"], "alice");
+        assert_eq!(requests[0].1["limit"], "2");
+        assert!(!requests[0].1.contains_key("cursor"));
+        assert_eq!(requests[1].1["limit"], "1");
+        assert_eq!(requests[1].1["cursor"], "p2");
+        assert_eq!(requests[2].1["cursor"], "p3");
+    }
+
+    #[test]
+    fn test_page_limit_defaults_caps_and_rejects_zero() {
+        assert_eq!(page_limit(None, 50, 500, "posts").unwrap(), 50);
+        assert_eq!(page_limit(Some(3), 50, 500, "posts").unwrap(), 3);
+        assert_eq!(page_limit(Some(100_000), 50, 500, "posts").unwrap(), 500);
+        match page_limit(Some(0), 50, 500, "accounts") {
+            Err(AppError::InvalidInput(message)) => {
+                assert_eq!(message, "limit must be a positive number of accounts")
+            }
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+}
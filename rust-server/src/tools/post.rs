@@ -1,13 +1,21 @@
 //! Post tool implementation
 //!
-//! Implements the `post(postAs, text, replyTo)` MCP tool
+//! Implements the `post(postAs, text, replyTo, root, quote, edit, images, alt)` MCP tool
 
 use crate::auth::storage::CredentialStorage;
+use crate::auth::session::refresh_buffer;
 use crate::auth::SessionManager;
+use crate::bluesky::records::{Embed, Facet, RecordEmbed};
+use crate::bluesky::uri::AtUri;
 use crate::cli::PostArgs;
 use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
 use crate::mcp::{McpResponse, ToolResult};
+use crate::bluesky::did::DidResolver;
+use crate::tools::blob::{prepare_images, upload_images, PendingImage};
+use crate::tools::facets::{realign_facets, resolve_facets};
 use anyhow::Result;
+use serde::Serialize;
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
 use tracing::debug;
@@ -36,50 +44,91 @@ async fn handle_post_impl(args: Value) -> Result<ToolResult, AppError> {
 /// Execute post tool (shared implementation for MCP and CLI)
 pub async fn execute_post(post_args: PostArgs) -> Result<ToolResult, AppError> {
     debug!(
-        "Post request for account: {}, text: '{}'",
-        post_args.postAs, post_args.text
+        "Post request for account: {}, {} chars",
+        post_args.postAs.as_deref().unwrap_or("(default)"),
+        post_args.text.chars().count()
     );
 
-    // Get credentials for the account
+    let mut record = serde_json::json!({
+        "$type": "app.bsky.feed.post",
+        "text": post_args.text,
+        "createdAt": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    });
+
+    // Pre-flight validation, before authenticating or touching the network
+    validate_reply_args(&post_args)?;
+    validate_edit_args(&post_args)?;
+    let images = prepare_images(&post_args.images, &post_args.alt)?;
+    PostValidationRules::from_args(&post_args)?.validate(&with_image_alts(&record, &images))?;
+
+    // Get credentials for the requested, default or only stored account
     let storage = CredentialStorage::new()?;
+    let account = storage.resolve_account(post_args.postAs.as_deref())?;
 
-    // Try to get stored session first (for OAuth accounts)
-    let session = if let Some(stored_session) = storage.get_session(&post_args.postAs)? {
-        debug!("Using stored session for {}", post_args.postAs);
-        stored_session
-    } else {
-        // Fallback to creating new session with credentials (for app password accounts)
-        debug!(
-            "No stored session, creating new session for {}",
-            post_args.postAs
-        );
-        let credentials = storage.get_credentials(&post_args.postAs)?;
-        let session_manager = SessionManager::new()?;
-        session_manager.login(&credentials).await?
-    };
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &account, refresh_buffer())
+        .await?;
 
     debug!("Authenticated as {} (DID: {})", session.handle, session.did);
 
+    // Make mentions, links and hashtags in the text clickable
+    let facets = resolve_facets(&post_args.text, &DidResolver::new()).await;
+
+    if let Some(edit) = &post_args.edit {
+        let (post_uri, cid) = edit_post(&session, edit, &post_args.text, facets).await?;
+        debug!("Post edited successfully: {}", post_uri);
+
+        let markdown = format!(
+            "# Post Edited\n\n**Post URI:** {}\n\n**Text:** {}\n",
+            post_uri, post_args.text
+        );
+        let data = PostOutput {
+            uri: post_uri,
+            cid,
+            text: post_args.text,
+            reply_to: None,
+            quote: None,
+        };
+        return Ok(ToolResult::text(markdown).with_data(data));
+    }
+
+    if !facets.is_empty() {
+        record["facets"] = serde_json::to_value(facets)?;
+    }
+
     // Parse reply-to if provided
     let reply_ref = if let Some(reply_to) = &post_args.replyTo {
-        Some(parse_and_fetch_reply(&session, reply_to).await?)
+        Some(fetch_reply_ref(&session, reply_to, post_args.root.as_deref()).await?)
+    } else {
+        None
+    };
+
+    // Resolve the quoted post to a strong reference if provided
+    let quote_ref = if let Some(quote) = &post_args.quote {
+        Some(fetch_strong_ref(&session, quote, "Quoted post").await?.0)
     } else {
         None
     };
 
-    // Create the post
     let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
-    let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.service);
 
-    let mut record = serde_json::json!({
-        "$type": "app.bsky.feed.post",
-        "text": post_args.text,
-        "createdAt": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
-    });
+    // Upload images if any were attached
+    let images_embed = if images.is_empty() {
+        None
+    } else {
+        Some(upload_images(&client, &session, images).await?)
+    };
+
+    // Create the post
+    let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.service);
 
     // Add reply information if present
     if let Some(reply) = reply_ref {
-        record["reply"] = reply;
+        record["reply"] = serde_json::to_value(reply)?;
+    }
+    if let Some(embed) = post_embed(quote_ref, images_embed) {
+        record["embed"] = serde_json::to_value(embed)?;
     }
 
     let body = serde_json::json!({
@@ -94,10 +143,14 @@ pub async fn execute_post(post_args: PostArgs) -> Result<ToolResult, AppError> {
         .post(&url)
         .header("Authorization", format!("Bearer {}", session.access_jwt))
         .json(&body)
-        .send()
+        .send_xrpc()
         .await
         .map_err(|e| AppError::NetworkError(format!("Post creation request failed: {}", e)))?;
 
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
     let status = response.status();
     if !status.is_success() {
         let error_text = response
@@ -122,12 +175,10 @@ pub async fn execute_post(post_args: PostArgs) -> Result<ToolResult, AppError> {
     debug!("Post created successfully: {}", post_uri);
 
     // Format result as markdown
-    let markdown = if post_args.replyTo.is_some() {
+    let mut markdown = if let Some(reply_to) = &post_args.replyTo {
         format!(
             "# Reply Posted\n\n**Post URI:** {}\n\n**Text:** {}\n\n**Reply To:** {}\n",
-            post_uri,
-            post_args.text,
-            post_args.replyTo.as_ref().unwrap()
+            post_uri, post_args.text, reply_to
         )
     } else {
         format!(
@@ -135,33 +186,392 @@ pub async fn execute_post(post_args: PostArgs) -> Result<ToolResult, AppError> {
             post_uri, post_args.text
         )
     };
+    if let Some(quote) = &post_args.quote {
+        markdown.push_str(&format!("\n**Quoting:** {}\n", quote));
+    }
+
+    let data = PostOutput {
+        uri: post_uri.to_string(),
+        cid: result["cid"].as_str().map(str::to_string),
+        text: post_args.text,
+        reply_to: post_args.replyTo,
+        quote: post_args.quote,
+    };
+    Ok(ToolResult::text(markdown).with_data(data))
+}
 
-    Ok(ToolResult::text(markdown))
+/// Structured result of a post call, for `--format json`
+#[derive(Debug, Serialize)]
+struct PostOutput {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cid: Option<String>,
+    text: String,
+    #[serde(rename = "replyTo", skip_serializing_if = "Option::is_none")]
+    reply_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quote: Option<String>,
 }
 
-/// Parse a post URI/URL and fetch the post details to create a reply reference
-async fn parse_and_fetch_reply(
+/// Lint rules checked against a post record before it is sent.
+///
+/// Every rule is off by default, so an unconfigured post goes through unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostValidationRules {
+    /// Reject images without alt text (only relevant when images are attached)
+    pub require_alt: bool,
+    /// Maximum number of hashtags allowed in the text
+    pub max_hashtags: Option<usize>,
+    /// Banned words or phrases, lowercased
+    pub ban_words: Vec<String>,
+}
+
+impl PostValidationRules {
+    /// Build rules from post arguments, reading the ban-words file if one is given
+    pub fn from_args(args: &PostArgs) -> Result<Self, AppError> {
+        let ban_words = match &args.banWords {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path).map_err(|e| {
+                    AppError::ConfigError(format!("Failed to read ban words file {}: {}", path, e))
+                })?;
+                parse_ban_words(&contents)
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            require_alt: args.requireAlt,
+            max_hashtags: args.maxHashtags,
+            ban_words,
+        })
+    }
+
+    /// Check an `app.bsky.feed.post` record against the rules.
+    ///
+    /// Error messages name the rule and the offending word or count, never the post text.
+    pub fn validate(&self, record: &Value) -> Result<(), AppError> {
+        let text = record["text"].as_str().unwrap_or("");
+
+        if self.require_alt {
+            let missing = post_images(record)
+                .iter()
+                .filter(|image| image["alt"].as_str().is_none_or(|alt| alt.trim().is_empty()))
+                .count();
+            if missing > 0 {
+                return Err(AppError::InvalidInput(format!(
+                    "Post rejected: {} attached image(s) missing alt text",
+                    missing
+                )));
+            }
+        }
+
+        if let Some(max) = self.max_hashtags {
+            let count = count_hashtags(text);
+            if count > max {
+                return Err(AppError::InvalidInput(format!(
+                    "Post rejected: {} hashtags exceeds the limit of {}",
+                    count, max
+                )));
+            }
+        }
+
+        if let Some(word) = find_banned_word(text, &self.ban_words) {
+            return Err(AppError::InvalidInput(format!(
+                "Post rejected: contains banned word '{}'",
+                word
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a ban-words file: one word or phrase per line, blank lines and `#` comments ignored
+fn parse_ban_words(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Images attached to a post record, directly or alongside a quoted record
+fn post_images(record: &Value) -> Vec<&Value> {
+    let embed = &record["embed"];
+    let images = match embed["$type"].as_str() {
+        Some("app.bsky.embed.images") => &embed["images"],
+        Some("app.bsky.embed.recordWithMedia") => &embed["media"]["images"],
+        _ => return Vec::new(),
+    };
+    images
+        .as_array()
+        .map(|images| images.iter().collect())
+        .unwrap_or_default()
+}
+
+/// Count hashtags the way Bluesky detects them: `#` followed by a tag that is not purely numeric
+fn count_hashtags(text: &str) -> usize {
+    text.split_whitespace()
+        .filter_map(|token| token.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+        .filter(|tag| !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()))
+        .count()
+}
+
+/// Find the first banned word or phrase in the text (case-insensitive, whole words only)
+fn find_banned_word<'a>(text: &str, ban_words: &'a [String]) -> Option<&'a str> {
+    if ban_words.is_empty() {
+        return None;
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let normalized = format!(" {} ", words.join(" "));
+
+    ban_words
+        .iter()
+        .find(|banned| {
+            let phrase: Vec<&str> = banned
+                .split(|c: char| !c.is_alphanumeric() && c != '\'')
+                .filter(|w| !w.is_empty())
+                .collect();
+            !phrase.is_empty() && normalized.contains(&format!(" {} ", phrase.join(" ")))
+        })
+        .map(String::as_str)
+}
+
+/// The post's embed: a quote, images, or both as `app.bsky.embed.recordWithMedia`
+fn post_embed(quoted: Option<RecordEmbed>, images: Option<Embed>) -> Option<Embed> {
+    match (quoted, images) {
+        (Some(record), Some(media)) => Some(Embed::RecordWithMedia {
+            record,
+            media: Box::new(media),
+        }),
+        (Some(record), None) => Some(Embed::Record { record }),
+        (None, images) => images,
+    }
+}
+
+/// The record as it will look once images are attached, carrying only their alt text,
+/// so validation rules can run before anything is uploaded
+fn with_image_alts(record: &Value, images: &[PendingImage]) -> Value {
+    let mut preview = record.clone();
+    if !images.is_empty() {
+        preview["embed"] = serde_json::json!({
+            "$type": "app.bsky.embed.images",
+            "images": images
+                .iter()
+                .map(|image| serde_json::json!({ "alt": image.alt }))
+                .collect::<Vec<_>>(),
+        });
+    }
+    preview
+}
+
+/// `app.bsky.feed.post#replyRef`: strong refs to the thread root and the post replied to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplyRef {
+    pub root: RecordEmbed,
+    pub parent: RecordEmbed,
+}
+
+impl ReplyRef {
+    /// Reply to `parent`, using the root it records (`parent_reply`, the parent's own
+    /// `reply` field) or the parent itself when it starts the thread
+    fn from_parent(parent: RecordEmbed, parent_reply: Option<&Value>) -> Self {
+        let recorded_root = parent_reply.and_then(|reply| {
+            let root = reply.get("root")?;
+            Some(RecordEmbed {
+                uri: root["uri"].as_str()?.to_string(),
+                cid: root["cid"].as_str()?.to_string(),
+                quoted: None,
+            })
+        });
+        Self {
+            root: recorded_root.unwrap_or_else(|| parent.clone()),
+            parent,
+        }
+    }
+}
+
+/// Reject a thread root that is given without replyTo or names the same post as replyTo
+fn validate_reply_args(args: &PostArgs) -> Result<(), AppError> {
+    match (&args.replyTo, &args.root) {
+        (None, Some(_)) => Err(AppError::InvalidInput(
+            "root can only be given together with replyTo".to_string(),
+        )),
+        (Some(parent), Some(root)) if parent.trim() == root.trim() => Err(
+            AppError::InvalidInput(
+                "root and replyTo name the same post; omit root when replying to the thread root"
+                    .to_string(),
+            ),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Reject edits that ask to change more than the text of the post
+fn validate_edit_args(args: &PostArgs) -> Result<(), AppError> {
+    if args.edit.is_some()
+        && (args.replyTo.is_some() || args.quote.is_some() || !args.images.is_empty())
+    {
+        return Err(AppError::InvalidInput(
+            "edit only replaces the text of a post; replyTo, quote and images cannot be given with it"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The record of an edited post: `old_record` with `new_text`, its facets moved to follow
+/// the text they were on, and `detected` facets added where no old facet remains
+fn edited_record(old_record: &Value, new_text: &str, detected: Vec<Facet>) -> Value {
+    let old_text = old_record["text"].as_str().unwrap_or_default();
+    let old_facets: Vec<Facet> =
+        serde_json::from_value(old_record["facets"].clone()).unwrap_or_default();
+
+    let mut facets = realign_facets(old_text, &old_facets, new_text);
+    for facet in detected {
+        let taken = facets.iter().any(|kept| {
+            kept.index.byte_start < facet.index.byte_end
+                && facet.index.byte_start < kept.index.byte_end
+        });
+        if !taken {
+            facets.push(facet);
+        }
+    }
+    facets.sort_by_key(|f| f.index.byte_start);
+
+    let mut record = old_record.clone();
+    record["text"] = Value::from(new_text);
+    match record.as_object_mut() {
+        Some(fields) if facets.is_empty() => {
+            fields.remove("facets");
+        }
+        _ => record["facets"] = serde_json::to_value(facets).unwrap_or_default(),
+    }
+    record
+}
+
+/// Replace the text of one of the session's own posts, returning its URI and new CID
+///
+/// The write is guarded by the CID of the fetched record, so a change made in between
+/// fails instead of being overwritten.
+async fn edit_post(
+    session: &crate::auth::Session,
+    reference: &str,
+    text: &str,
+    detected: Vec<Facet>,
+) -> Result<(String, Option<String>), AppError> {
+    let (post, old_record) = fetch_strong_ref(session, reference, "Edited post").await?;
+    let uri = AtUri::parse(&post.uri)?;
+    if uri.did != session.did {
+        return Err(AppError::InvalidInput(format!(
+            "Cannot edit post that doesn't belong to you: {}",
+            reference
+        )));
+    }
+
+    let body = serde_json::json!({
+        "repo": session.did,
+        "collection": uri.collection,
+        "rkey": uri.rkey,
+        "record": edited_record(&old_record, text, detected),
+        "swapRecord": post.cid,
+    });
+
+    debug!("Editing post {} (rkey {})", post.uri, uri.rkey);
+
+    let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
+    let response = client
+        .post(format!("{}/xrpc/com.atproto.repo.putRecord", session.service))
+        .header("Authorization", format!("Bearer {}", session.access_jwt))
+        .json(&body)
+        .send_xrpc()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Post edit request failed: {}", e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AppError::NetworkError(format!(
+            "Post edit failed with status {}: {}",
+            status, error_text
+        )));
+    }
+
+    let result: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse response: {}", e)))?;
+
+    Ok((post.uri, result["cid"].as_str().map(str::to_string)))
+}
+
+/// Resolve the post being replied to, and the thread root, into a reply reference
+async fn fetch_reply_ref(
     session: &crate::auth::Session,
     reply_to: &str,
-) -> Result<serde_json::Value, AppError> {
-    // Parse the URI using the shared utility
-    let post_ref = crate::bluesky::uri::parse_post_uri(reply_to).await?;
+    root: Option<&str>,
+) -> Result<ReplyRef, AppError> {
+    let (parent, parent_value) = fetch_strong_ref(session, reply_to, "Reply-to post").await?;
+
+    let Some(root) = root else {
+        return Ok(ReplyRef::from_parent(parent, parent_value.get("reply")));
+    };
+
+    let (root, _) = fetch_strong_ref(session, root, "Thread root post").await?;
+    // Different spellings (URL vs at:// URI) may still name the same post
+    if root.uri == parent.uri {
+        return Err(AppError::InvalidInput(
+            "root and replyTo name the same post; omit root when replying to the thread root"
+                .to_string(),
+        ));
+    }
+    Ok(ReplyRef { root, parent })
+}
+
+/// Parse a post URI/URL and fetch the post, returning its strong reference and record value
+///
+/// `noun` names the post in errors, e.g. "Quoted post not found: ...".
+async fn fetch_strong_ref(
+    session: &crate::auth::Session,
+    reference: &str,
+    noun: &str,
+) -> Result<(RecordEmbed, Value), AppError> {
+    let post_ref = crate::bluesky::uri::parse_post_uri(reference).await?;
 
-    // Fetch the post to get its CID
     let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
     let url = format!(
         "{}/xrpc/com.atproto.repo.getRecord?repo={}&collection=app.bsky.feed.post&rkey={}",
         session.service, post_ref.did, post_ref.rkey
     );
 
-    debug!("Fetching reply-to post from: {}", url);
+    debug!("Fetching {} from: {}", noun.to_lowercase(), url);
 
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .send()
+        .send_xrpc()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to fetch reply-to post: {}", e)))?;
+        .map_err(|e| {
+            AppError::NetworkError(format!("Failed to fetch {}: {}", noun.to_lowercase(), e))
+        })?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
 
     let status = response.status();
     if !status.is_success() {
@@ -169,48 +579,37 @@ async fn parse_and_fetch_reply(
             .text()
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
+        // getRecord answers a missing record with 400 RecordNotFound
+        if status == reqwest::StatusCode::NOT_FOUND || error_text.contains("RecordNotFound") {
+            return Err(AppError::NotFound(format!("{} not found: {}", noun, reference)));
+        }
         return Err(AppError::NetworkError(format!(
-            "Failed to fetch reply-to post with status {}: {}",
-            status, error_text
+            "Failed to fetch {} with status {}: {}",
+            noun.to_lowercase(),
+            status,
+            error_text
         )));
     }
 
-    let post_data: serde_json::Value = response
+    let mut post_data: serde_json::Value = response
         .json()
         .await
         .map_err(|e| AppError::ParseError(format!("Failed to parse post data: {}", e)))?;
 
-    let post_uri = post_data["uri"]
-        .as_str()
-        .ok_or_else(|| AppError::ParseError("No URI in post data".to_string()))?;
-    let post_cid = post_data["cid"]
-        .as_str()
-        .ok_or_else(|| AppError::ParseError("No CID in post data".to_string()))?;
-
-    // Check if the post itself is a reply to build the proper reply chain
-    let root_ref = if let Some(reply) = post_data["value"].get("reply") {
-        if let Some(root) = reply.get("root") {
-            root.clone()
-        } else {
-            serde_json::json!({
-                "uri": post_uri,
-                "cid": post_cid
-            })
-        }
-    } else {
-        serde_json::json!({
-            "uri": post_uri,
-            "cid": post_cid
-        })
-    };
-
-    Ok(serde_json::json!({
-        "root": root_ref,
-        "parent": {
-            "uri": post_uri,
-            "cid": post_cid
-        }
-    }))
+    match (post_data["uri"].as_str(), post_data["cid"].as_str()) {
+        (Some(uri), Some(cid)) => Ok((
+            RecordEmbed {
+                uri: uri.to_string(),
+                cid: cid.to_string(),
+                quoted: None,
+            },
+            post_data["value"].take(),
+        )),
+        _ => Err(AppError::NotFound(format!(
+            "{} could not be resolved: {}",
+            noun, reference
+        ))),
+    }
 }
 
 #[cfg(test)]
@@ -226,7 +625,7 @@ mod tests {
         });
 
         let parsed: PostArgs = serde_json::from_value(args).unwrap();
-        assert_eq!(parsed.postAs, "test.bsky.social");
+        assert_eq!(parsed.postAs.as_deref(), Some("test.bsky.social"));
         assert_eq!(parsed.text, "Hello, world!");
         assert!(parsed.replyTo.is_none());
     }
@@ -240,11 +639,334 @@ mod tests {
         });
 
         let parsed: PostArgs = serde_json::from_value(args).unwrap();
-        assert_eq!(parsed.postAs, "test.bsky.social");
+        assert_eq!(parsed.postAs.as_deref(), Some("test.bsky.social"));
         assert_eq!(parsed.text, "Reply text");
         assert_eq!(
             parsed.replyTo,
             Some("at://did:plc:abc/app.bsky.feed.post/123".to_string())
         );
     }
+
+    #[tokio::test]
+    async fn test_post_args_with_quote() {
+        let args = json!({
+            "postAs": "test.bsky.social",
+            "text": "Look at this",
+            "quote": "at://did:plc:abc/app.bsky.feed.post/123"
+        });
+
+        let parsed: PostArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(
+            parsed.quote,
+            Some("at://did:plc:abc/app.bsky.feed.post/123".to_string())
+        );
+        assert!(parsed.replyTo.is_none());
+    }
+
+    #[test]
+    fn test_quote_embed_is_strong_ref() {
+        let mut record = post("Look at this");
+        let quoted = RecordEmbed {
+            uri: "at://did:plc:abc/app.bsky.feed.post/123".to_string(),
+            cid: "bafyreiquoted".to_string(),
+            quoted: None,
+        };
+        record["embed"] = serde_json::to_value(post_embed(Some(quoted), None)).unwrap();
+
+        assert_eq!(
+            record["embed"],
+            json!({
+                "$type": "app.bsky.embed.record",
+                "record": {
+                    "uri": "at://did:plc:abc/app.bsky.feed.post/123",
+                    "cid": "bafyreiquoted"
+                }
+            })
+        );
+        assert_eq!(record["text"], "Look at this");
+    }
+
+    #[test]
+    fn test_post_embed_combines_quote_and_images() {
+        use crate::bluesky::records::BlobRef;
+        use crate::tools::blob::images_embed;
+
+        let images = images_embed(vec![(
+            BlobRef {
+                type_: "blob".to_string(),
+                ref_: "bafkreiimg".to_string(),
+                mime_type: "image/png".to_string(),
+                size: 10,
+            },
+            Some("chart".to_string()),
+        )]);
+        let embed = post_embed(Some(strong_ref("q")), Some(images.clone())).unwrap();
+        let json = serde_json::to_value(embed).unwrap();
+        assert_eq!(json["$type"], "app.bsky.embed.recordWithMedia");
+        assert_eq!(json["record"]["uri"], "at://did:plc:abc/app.bsky.feed.post/q");
+        assert_eq!(json["media"]["$type"], "app.bsky.embed.images");
+        assert_eq!(json["media"]["images"][0]["image"]["ref"]["$link"], "bafkreiimg");
+
+        let json = serde_json::to_value(post_embed(None, Some(images)).unwrap()).unwrap();
+        assert_eq!(json["$type"], "app.bsky.embed.images");
+        assert!(post_embed(None, None).is_none());
+    }
+
+    #[test]
+    fn test_require_alt_checks_pending_images() {
+        let rules = PostValidationRules {
+            require_alt: true,
+            ..rules()
+        };
+        let image = |alt: Option<&str>| PendingImage {
+            path: "cat.png".into(),
+            mime_type: "image/png",
+            alt: alt.map(str::to_string),
+        };
+
+        let record = post("pics");
+        assert!(rules.validate(&with_image_alts(&record, &[image(Some("a cat"))])).is_ok());
+        assert!(rules
+            .validate(&with_image_alts(&record, &[image(Some("a cat")), image(None)]))
+            .is_err());
+    }
+
+    fn strong_ref(rkey: &str) -> RecordEmbed {
+        RecordEmbed {
+            uri: format!("at://did:plc:abc/app.bsky.feed.post/{}", rkey),
+            cid: format!("bafy{}", rkey),
+            quoted: None,
+        }
+    }
+
+    #[test]
+    fn test_reply_ref_shape() {
+        let reply = ReplyRef {
+            root: strong_ref("root"),
+            parent: strong_ref("parent"),
+        };
+        assert_eq!(
+            serde_json::to_value(reply).unwrap(),
+            json!({
+                "root": { "uri": "at://did:plc:abc/app.bsky.feed.post/root", "cid": "bafyroot" },
+                "parent": { "uri": "at://did:plc:abc/app.bsky.feed.post/parent", "cid": "bafyparent" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reply_ref_root_from_parent() {
+        // Replying to a top-level post: it is both parent and root
+        let reply = ReplyRef::from_parent(strong_ref("top"), None);
+        assert_eq!(reply.root, strong_ref("top"));
+        assert_eq!(reply.parent, strong_ref("top"));
+
+        // Replying to a reply: keep the root it records
+        let parent_reply = json!({
+            "root": { "uri": "at://did:plc:abc/app.bsky.feed.post/root", "cid": "bafyroot" },
+            "parent": { "uri": "at://did:plc:abc/app.bsky.feed.post/mid", "cid": "bafymid" }
+        });
+        let reply = ReplyRef::from_parent(strong_ref("leaf"), Some(&parent_reply));
+        assert_eq!(reply.root, strong_ref("root"));
+        assert_eq!(reply.parent, strong_ref("leaf"));
+    }
+
+    #[test]
+    fn test_validate_reply_args() {
+        let args = |reply_to: Option<&str>, root: Option<&str>| PostArgs {
+            postAs: Some("test.bsky.social".to_string()),
+            text: "hi".to_string(),
+            replyTo: reply_to.map(str::to_string),
+            root: root.map(str::to_string),
+            ..Default::default()
+        };
+        let parent = "at://did:plc:abc/app.bsky.feed.post/parent";
+        let root = "at://did:plc:abc/app.bsky.feed.post/root";
+
+        assert!(validate_reply_args(&args(None, None)).is_ok());
+        assert!(validate_reply_args(&args(Some(parent), None)).is_ok());
+        assert!(validate_reply_args(&args(Some(parent), Some(root))).is_ok());
+        assert!(matches!(
+            validate_reply_args(&args(Some(parent), Some(parent))),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            validate_reply_args(&args(None, Some(root))),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_edit_args() {
+        let edit = PostArgs {
+            text: "fixed".to_string(),
+            edit: Some("at://did:plc:abc/app.bsky.feed.post/1".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_edit_args(&edit).is_ok());
+
+        let with_quote = PostArgs {
+            quote: Some("at://did:plc:abc/app.bsky.feed.post/2".to_string()),
+            ..edit.clone()
+        };
+        assert!(matches!(
+            validate_edit_args(&with_quote),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_edited_record_moves_facets_and_keeps_fields() {
+        let old = json!({
+            "$type": "app.bsky.feed.post",
+            "text": "See #rust and #go",
+            "createdAt": "2024-01-01T00:00:00.000Z",
+            "reply": { "root": { "uri": "at://r", "cid": "c" } },
+            "facets": [
+                { "index": { "byteStart": 4, "byteEnd": 9 },
+                  "features": [{ "$type": "app.bsky.richtext.facet#tag", "tag": "rust" }] },
+                { "index": { "byteStart": 14, "byteEnd": 17 },
+                  "features": [{ "$type": "app.bsky.richtext.facet#tag", "tag": "go" }] }
+            ]
+        });
+        let detected = crate::tools::facets::build_facets(
+            &crate::tools::facets::detect_facets("Now see #rust, #zig"),
+            &Default::default(),
+        );
+
+        let record = edited_record(&old, "Now see #rust, #zig", detected);
+        assert_eq!(record["text"], "Now see #rust, #zig");
+        assert_eq!(record["createdAt"], old["createdAt"]);
+        assert_eq!(record["reply"], old["reply"]);
+
+        let spans: Vec<(u64, u64)> = record["facets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| {
+                (
+                    f["index"]["byteStart"].as_u64().unwrap(),
+                    f["index"]["byteEnd"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        // #rust moved from the old facet, #go dropped, #zig newly detected
+        assert_eq!(spans, vec![(8, 13), (15, 19)]);
+    }
+
+    #[test]
+    fn test_edited_record_without_facets_drops_field() {
+        let old = json!({
+            "$type": "app.bsky.feed.post",
+            "text": "#gone",
+            "facets": [
+                { "index": { "byteStart": 0, "byteEnd": 5 },
+                  "features": [{ "$type": "app.bsky.richtext.facet#tag", "tag": "gone" }] }
+            ]
+        });
+        let record = edited_record(&old, "plain text", Vec::new());
+        assert!(record.get("facets").is_none());
+    }
+
+    fn rules() -> PostValidationRules {
+        PostValidationRules::default()
+    }
+
+    fn post(text: &str) -> Value {
+        json!({ "$type": "app.bsky.feed.post", "text": text })
+    }
+
+    #[test]
+    fn test_validation_off_by_default() {
+        let record = json!({
+            "$type": "app.bsky.feed.post",
+            "text": "#a #b #c #d #e with whatever words",
+            "embed": { "$type": "app.bsky.embed.images", "images": [{ "alt": "" }] }
+        });
+        assert!(rules().validate(&record).is_ok());
+
+        let args = PostArgs {
+            postAs: Some("test.bsky.social".to_string()),
+            text: "hello".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(PostValidationRules::from_args(&args).unwrap(), rules());
+    }
+
+    #[test]
+    fn test_require_alt_only_applies_to_images() {
+        let rules = PostValidationRules {
+            require_alt: true,
+            ..rules()
+        };
+        assert!(rules.validate(&post("no images here")).is_ok());
+
+        let missing = json!({
+            "text": "pic",
+            "embed": { "$type": "app.bsky.embed.images", "images": [{ "alt": "a cat" }, { "alt": "  " }] }
+        });
+        let err = rules.validate(&missing).unwrap_err();
+        assert!(err.message().contains("1 attached image(s) missing alt text"));
+
+        let with_media = json!({
+            "text": "quote",
+            "embed": {
+                "$type": "app.bsky.embed.recordWithMedia",
+                "media": { "$type": "app.bsky.embed.images", "images": [{}] }
+            }
+        });
+        assert!(rules.validate(&with_media).is_err());
+
+        let described = json!({
+            "text": "pic",
+            "embed": { "$type": "app.bsky.embed.images", "images": [{ "alt": "a cat" }] }
+        });
+        assert!(rules.validate(&described).is_ok());
+    }
+
+    #[test]
+    fn test_max_hashtags() {
+        let rules = PostValidationRules {
+            max_hashtags: Some(2),
+            ..rules()
+        };
+        assert!(rules.validate(&post("#rust #atproto ok")).is_ok());
+        // Numeric tags and bare '#' are not hashtags
+        assert!(rules.validate(&post("#rust #atproto #1 # issue")).is_ok());
+
+        let err = rules.validate(&post("#rust, #atproto! #bluesky")).unwrap_err();
+        assert!(err.message().contains("3 hashtags exceeds the limit of 2"));
+    }
+
+    #[test]
+    fn test_ban_words_case_insensitive_and_reports_word() {
+        let rules = PostValidationRules {
+            ban_words: parse_ban_words("# comment\n\nSpam\nbuy now\n"),
+            ..rules()
+        };
+        assert_eq!(rules.ban_words, vec!["spam", "buy now"]);
+
+        let err = rules
+            .validate(&post("Totally not SPAM, trust me with this long secret text"))
+            .unwrap_err();
+        let message = err.message();
+        assert!(message.contains("'spam'"));
+        assert!(!message.contains("secret"));
+
+        assert!(rules.validate(&post("Buy   NOW!")).is_err());
+        // Whole words only
+        assert!(rules.validate(&post("spammer buying nowhere")).is_ok());
+    }
+
+    #[test]
+    fn test_ban_words_file_missing_is_config_error() {
+        let args = PostArgs {
+            postAs: Some("test.bsky.social".to_string()),
+            text: "hello".to_string(),
+            banWords: Some("/nonexistent/ban-words.txt".to_string()),
+            ..Default::default()
+        };
+        let err = PostValidationRules::from_args(&args).unwrap_err();
+        assert!(matches!(err, AppError::ConfigError(_)));
+    }
 }
@@ -5,10 +5,14 @@
 //! - Compact post IDs (@handle/rkey or @h/…last4)
 //! - Emoji stats (👍 likes  ♻️ reshares  💬 replies)
 //! - Threading indicators (└─ with indentation)
-//! - ISO timestamps without milliseconds
+//! - ISO timestamps without milliseconds, or relative ("3h ago") on request
 
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use crate::bluesky::records::{Embed, Facet, FacetFeature, ImageEmbed};
+use crate::bluesky::records::{Facet, FacetFeature};
+use crate::bluesky::uri::{AtUri, AtUriError};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Apply facets to text, converting mentions/links/tags to Markdown format
 /// Facets use byte indices, so we need to handle UTF-8 properly
@@ -67,47 +71,66 @@ pub fn apply_facets_to_text(text: &str, facets: &[Facet]) -> String {
     result
 }
 
-/// Format a single embed into a Markdown string.
-/// `did` is required to construct full image URLs.
-pub fn format_embed(embed: &Embed, did: &str) -> String {
-    match embed {
-        Embed::Images { images } => images
-            .iter()
-            .map(|img| {
-                let alt = img.alt.as_deref().unwrap_or("");
-                // URL format: https://cdn.bsky.app/img/feed_fullsize/plain/{did}/{cid}@jpeg
-                let url = format!(
-                    "https://cdn.bsky.app/img/feed_fullsize/plain/{}@jpeg",
-                    img.image.ref_
-                );
-                format!("![{}]({})", alt, url)
+/// Format a hydrated AppView embed (`app.bsky.embed.*#view`) into Markdown.
+/// Image URLs are already resolved by the AppView.
+/// Returns an empty string for unknown or empty embeds.
+pub fn format_embed_view(embed: &serde_json::Value) -> String {
+    let embed_type = embed.get("$type").and_then(|t| t.as_str()).unwrap_or("");
+    match embed_type {
+        "app.bsky.embed.images#view" => embed
+            .get("images")
+            .and_then(|i| i.as_array())
+            .map(|images| {
+                images
+                    .iter()
+                    .filter_map(|img| {
+                        let url = img.get("fullsize").and_then(|u| u.as_str())?;
+                        let alt = img.get("alt").and_then(|a| a.as_str()).unwrap_or("");
+                        Some(format!("![{}]({})", alt, url))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
             })
-            .collect::<Vec<_>>()
-            .join("\n"),
-        Embed::External { external } => {
-            let mut parts = vec![format!("[{}]({})", external.title, external.uri)];
-            if !external.description.is_empty() {
-                parts.push(blockquote_content(&external.description));
+            .unwrap_or_default(),
+        "app.bsky.embed.external#view" => {
+            let external = &embed["external"];
+            match external.get("uri").and_then(|u| u.as_str()) {
+                Some(uri) => {
+                    let title = external
+                        .get("title")
+                        .and_then(|t| t.as_str())
+                        .filter(|t| !t.is_empty())
+                        .unwrap_or(uri);
+                    format!("[{}]({})", title, uri)
+                }
+                None => String::new(),
             }
-            if let Some(thumb) = &external.thumb {
-                let url = format!(
-                    "https://cdn.bsky.app/img/feed_thumbnail/plain/{}@jpeg",
-                    thumb.ref_
-                );
-                parts.push(format!("![thumb]({})", url));
-            }
-            parts.join("\n")
         }
-        Embed::Record { record } => {
-            // For now, just show the record URI. A full implementation would
-            // require fetching and rendering the quoted post.
-            blockquote_content(&format!("Quoted post: {}", record.uri))
+        "app.bsky.embed.record#view" => {
+            let record = &embed["record"];
+            let handle = record["author"]["handle"].as_str();
+            let text = record["value"]["text"].as_str();
+            match (handle, text) {
+                (Some(handle), Some(text)) => {
+                    blockquote_content(&format!("Quoting @{}: {}", handle, text))
+                }
+                _ => match record.get("uri").and_then(|u| u.as_str()) {
+                    Some(uri) => blockquote_content(&format!("Quoted post: {}", uri)),
+                    None => String::new(),
+                },
+            }
         }
-        Embed::RecordWithMedia { record, media } => {
-            let record_md = format_embed(&Embed::Record { record: record.clone() }, did);
-            let media_md = format_embed(media, did);
-            format!("{}\n{}", record_md, media_md)
+        "app.bsky.embed.recordWithMedia#view" => {
+            let parts: Vec<String> = [
+                format_embed_view(&embed["record"]),
+                format_embed_view(&embed["media"]),
+            ]
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect();
+            parts.join("\n")
         }
+        _ => String::new(),
     }
 }
 
@@ -137,6 +160,49 @@ fn format_facet_feature(text: &str, features: &[FacetFeature]) -> String {
     }
 }
 
+/// List each facet's byte range, the exact text it covers and its features, for
+/// debugging richtext. Facets appear in stored order; ranges that fall outside the
+/// text or split a UTF-8 character are flagged instead of sliced.
+pub fn format_facet_breakdown(text: &str, facets: &[Facet]) -> String {
+    if facets.is_empty() {
+        return "Facets: none\n".to_string();
+    }
+
+    let mut out = String::from("Facets:\n");
+    for facet in facets {
+        let start = facet.index.byte_start as usize;
+        let end = facet.index.byte_end as usize;
+        let covered = if start > end {
+            "(invalid: start after end)".to_string()
+        } else if end > text.len() {
+            format!("(invalid: past end of {}-byte text)", text.len())
+        } else {
+            match text.get(start..end) {
+                Some(slice) => format!("{:?}", slice),
+                None => "(invalid: splits a UTF-8 character)".to_string(),
+            }
+        };
+
+        let features = if facet.features.is_empty() {
+            "no features".to_string()
+        } else {
+            facet
+                .features
+                .iter()
+                .map(|feature| match feature {
+                    FacetFeature::Mention { did } => format!("mention {}", did),
+                    FacetFeature::Link { uri } => format!("link {}", uri),
+                    FacetFeature::Tag { tag } => format!("tag #{}", tag),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        out.push_str(&format!("- {}..{} {} → {}\n", start, end, covered, features));
+    }
+    out
+}
+
 /// Compact a post ID for display
 /// - First mention: @handle/rkey
 /// - Subsequent mentions in thread: @firstletter/…last4
@@ -184,6 +250,15 @@ pub fn blockquote_content_with_facets(text: &str, facets: &[Facet]) -> String {
     blockquote_content(&formatted_text)
 }
 
+/// Truncate text to at most `max_chars` characters, appending `…` when cut.
+/// Cuts on char boundaries so multi-byte UTF-8 is never split.
+pub fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => format!("{}…", text[..byte_idx].trim_end()),
+        None => text.to_string(),
+    }
+}
+
 /// Format stats with emojis
 /// ♻️ combines reposts + quotes
 /// Only shows non-zero stats
@@ -228,13 +303,48 @@ pub fn format_timestamp(timestamp: &str) -> String {
     }
 }
 
+/// Format a timestamp relative to `now`: "just now", "5m ago", "3h ago", "2d ago",
+/// "4mo ago", "1y ago" (or "in 5m" for future times). Unparseable input is returned as is.
+pub fn format_relative_time(timestamp: &str, now: DateTime<Utc>) -> String {
+    let Some(time) = crate::search::timestamp::parse_timestamp(timestamp) else {
+        return timestamp.to_string();
+    };
+
+    let seconds = (now - time).num_seconds();
+    let span = seconds.unsigned_abs();
+    let amount = match span {
+        0..60 => return "just now".to_string(),
+        60..3_600 => format!("{}m", span / 60),
+        3_600..86_400 => format!("{}h", span / 3_600),
+        86_400..2_592_000 => format!("{}d", span / 86_400),
+        2_592_000..31_536_000 => format!("{}mo", span / 2_592_000),
+        _ => format!("{}y", span / 31_536_000),
+    };
+    if seconds < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// Post time for display: ISO without milliseconds, or relative to now
+pub fn format_post_time(timestamp: &str, relative: bool) -> String {
+    if relative {
+        format_relative_time(timestamp, Utc::now())
+    } else {
+        format_timestamp(timestamp)
+    }
+}
+
 /// Extract rkey from at:// URI
 /// at://did:plc:abc123/app.bsky.feed.post/3m4jnj3efp22t -> 3m4jnj3efp22t
+/// A bare rkey (no `/`) is returned as is; anything else malformed is `unknown`.
 pub fn extract_rkey(uri: &str) -> &str {
-    if uri.is_empty() {
-        return "unknown";
+    match AtUri::parse(uri) {
+        Ok(parsed) => parsed.rkey,
+        Err(AtUriError::NotAtUri(_)) if !uri.is_empty() && !uri.contains('/') => uri,
+        Err(_) => "unknown",
     }
-    uri.split('/').next_back().unwrap_or("unknown")
 }
 
 /// Build threading indicator with indentation
@@ -252,12 +362,139 @@ pub fn threading_indicator(depth: usize, reply_to_compact: &str, author_id: &str
     }
 }
 
+/// Find all occurrences of the terms (a quoted phrase is one term), case-insensitively
+/// unless `case_sensitive`, and merge overlapping or adjacent ranges. Text and terms are
+/// compared in Unicode NFC, so a composed and a decomposed accent match each other. Byte
+/// offsets refer to the original `text`, even where normalization or lowercasing changes
+/// its byte length, and a range never splits a base character from its combining marks.
+pub fn highlight_ranges(text: &str, terms: &[String], case_sensitive: bool) -> Vec<(usize, usize)> {
+    if terms.iter().all(|t| t.is_empty()) {
+        return Vec::new();
+    }
+    let fold = |s: &str| -> String {
+        if case_sensitive {
+            s.nfc().collect()
+        } else {
+            s.to_lowercase().nfc().collect()
+        }
+    };
+
+    // Fold each grapheme cluster, remembering which original cluster each folded byte came
+    // from; canonical composition never reaches across a cluster boundary
+    let mut folded = String::with_capacity(text.len());
+    let mut origin: Vec<(usize, usize)> = Vec::with_capacity(text.len());
+    for (start, cluster) in text.grapheme_indices(true) {
+        let end = start + cluster.len();
+        let cluster_f = fold(cluster);
+        origin.extend(std::iter::repeat_n((start, end), cluster_f.len()));
+        folded.push_str(&cluster_f);
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in terms {
+        if term.is_empty() {
+            continue;
+        }
+        let term_f = fold(term);
+        let mut idx = 0usize;
+        while let Some(pos) = folded[idx..].find(&term_f) {
+            let abs = idx + pos;
+            let last = abs + term_f.len() - 1;
+            ranges.push((origin[abs].0, origin[last].1));
+            idx = abs + term_f.len();
+        }
+    }
+
+    if ranges.is_empty() {
+        return ranges;
+    }
+
+    // Sort and merge ranges (merge adjacent and overlapping ranges)
+    ranges.sort_by_key(|r| r.0);
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (s, e) in ranges {
+        if let Some(last) = merged.last_mut() {
+            if s <= last.1 {
+                // Overlap or adjacent: extend the last range
+                if e > last.1 {
+                    last.1 = e;
+                }
+            } else if s == last.1 + 1 && text.as_bytes()[last.1] == b'\n' {
+                // Allow merging across a single newline (soft line break) so that
+                // emphasis can span across lines inside a paragraph. Do NOT merge
+                // across spaces or multiple newlines (paragraph breaks).
+                if e > last.1 {
+                    last.1 = e;
+                }
+            } else {
+                merged.push((s, e));
+            }
+        } else {
+            merged.push((s, e));
+        }
+    }
+
+    merged
+}
+
+/// Wrap each range of `text` in `**bold**`
+pub fn apply_bold(text: &str, ranges: &[(usize, usize)]) -> String {
+    let mut res = String::with_capacity(text.len() + ranges.len() * 4);
+    let mut last_idx = 0usize;
+    for &(s, e) in ranges {
+        if last_idx < s {
+            res.push_str(&text[last_idx..s]);
+        }
+        res.push_str("**");
+        res.push_str(&text[s..e]);
+        res.push_str("**");
+        last_idx = e;
+    }
+    if last_idx < text.len() {
+        res.push_str(&text[last_idx..]);
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bluesky::records::{
-        BlobRef, Embed, ExternalEmbed, Facet, FacetFeature, FacetIndex, ImageEmbed, RecordEmbed,
-    };
+    use crate::bluesky::records::{Facet, FacetFeature, FacetIndex};
+
+    fn facet(start: u32, end: u32, feature: FacetFeature) -> Facet {
+        Facet {
+            index: FacetIndex {
+                byte_start: start,
+                byte_end: end,
+            },
+            features: vec![feature],
+        }
+    }
+
+    #[test]
+    fn test_facet_breakdown_multibyte_substrings() {
+        let text = "héllo @élan.test #café";
+        let facets = vec![
+            facet(7, 18, FacetFeature::Mention { did: "did:plc:elan".to_string() }),
+            facet(19, 25, FacetFeature::Tag { tag: "café".to_string() }),
+        ];
+        assert_eq!(
+            format_facet_breakdown(text, &facets),
+            "Facets:\n- 7..18 \"@élan.test\" → mention did:plc:elan\n- 19..25 \"#café\" → tag #café\n"
+        );
+    }
+
+    #[test]
+    fn test_facet_breakdown_flags_invalid_ranges() {
+        let text = "héllo";
+        let link = || FacetFeature::Link { uri: "https://x.dev".to_string() };
+        let facets = vec![facet(0, 2, link()), facet(3, 99, link()), facet(4, 1, link())];
+        let out = format_facet_breakdown(text, &facets);
+        assert!(out.contains("- 0..2 (invalid: splits a UTF-8 character) → link https://x.dev\n"));
+        assert!(out.contains("- 3..99 (invalid: past end of 6-byte text)"));
+        assert!(out.contains("- 4..1 (invalid: start after end)"));
+        assert_eq!(format_facet_breakdown(text, &[]), "Facets: none\n");
+    }
 
     #[test]
     fn test_apply_facets_mention() {
@@ -486,6 +723,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_relative_time_deltas() {
+        let now = crate::search::timestamp::parse_timestamp("2024-06-15T12:00:00Z").unwrap();
+        let cases = [
+            ("2024-06-15T11:59:30Z", "just now"),
+            ("2024-06-15T11:54:59.123Z", "5m ago"),
+            ("2024-06-15T09:00:00Z", "3h ago"),
+            ("2024-06-15T14:00:00+02:00", "just now"),
+            ("2024-06-13T11:00:00Z", "2d ago"),
+            ("2024-02-01T12:00:00Z", "4mo ago"),
+            ("2022-06-01T00:00:00Z", "2y ago"),
+            ("2024-06-15T12:05:00Z", "in 5m"),
+            ("2024-06-15", "12h ago"),
+        ];
+        for (timestamp, expected) in cases {
+            assert_eq!(format_relative_time(timestamp, now), expected, "{}", timestamp);
+        }
+    }
+
+    #[test]
+    fn test_format_relative_time_unparseable_is_raw() {
+        let now = Utc::now();
+        assert_eq!(format_relative_time("sometime last week", now), "sometime last week");
+        assert_eq!(format_relative_time("", now), "");
+        assert_eq!(format_post_time("not a date", true), "not a date");
+        assert_eq!(format_post_time("2024-10-06T10:15:33.5Z", false), "2024-10-06T10:15:33Z");
+    }
+
     #[test]
     fn test_extract_rkey() {
         assert_eq!(
@@ -494,6 +759,9 @@ mod tests {
         );
         assert_eq!(extract_rkey("3m4jnj3efp22t"), "3m4jnj3efp22t");
         assert_eq!(extract_rkey(""), "unknown");
+        assert_eq!(extract_rkey("at://did:plc:abc/app.bsky.feed.post/3kabc/"), "3kabc");
+        assert_eq!(extract_rkey("at://did:plc:abc/app.bsky.feed.post/"), "unknown");
+        assert_eq!(extract_rkey("https://bsky.app/profile/a/post/3kabc"), "unknown");
     }
 
     #[test]
@@ -639,228 +907,40 @@ mod tests {
     }
 
     #[test]
-    fn test_format_embed_single_image() {
-        let embed = Embed::Images {
-            images: vec![ImageEmbed {
-                alt: Some("A beautiful sunset".to_string()),
-                image: BlobRef {
-                    type_: "blob".to_string(),
-                    ref_: "did:plc:test/bafkreihd...".to_string(),
-                    mime_type: "image/jpeg".to_string(),
-                    size: 12345,
-                },
-            }],
-        };
-        let result = format_embed(&embed, "did:plc:test");
-        assert_eq!(
-            result,
-            "![A beautiful sunset](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkreihd...@jpeg)"
-        );
-    }
-
-    #[test]
-    fn test_format_embed_multiple_images() {
-        let embed = Embed::Images {
-            images: vec![
-                ImageEmbed {
-                    alt: Some("Image 1".to_string()),
-                    image: BlobRef {
-                        type_: "blob".to_string(),
-                        ref_: "did:plc:test/bafkrei_img1...".to_string(),
-                        mime_type: "image/jpeg".to_string(),
-                        size: 100,
-                    },
-                },
-                ImageEmbed {
-                    alt: Some("Image 2".to_string()),
-                    image: BlobRef {
-                        type_: "blob".to_string(),
-                        ref_: "did:plc:test/bafkrei_img2...".to_string(),
-                        mime_type: "image/jpeg".to_string(),
-                        size: 200,
-                    },
-                },
-            ],
-        };
-        let result = format_embed(&embed, "did:plc:test");
-        assert_eq!(
-            result,
-            "![Image 1](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkrei_img1...@jpeg)\n![Image 2](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkrei_img2...@jpeg)"
-        );
-    }
-
-    #[test]
-    fn test_format_embed_external() {
-        let embed = Embed::External {
-            external: ExternalEmbed {
-                uri: "https://example.com".to_string(),
-                title: "Example Title".to_string(),
-                description: "This is a description.".to_string(),
-                thumb: Some(BlobRef {
-                    type_: "blob".to_string(),
-                    ref_: "did:plc:test/bafkrei_thumb...".to_string(),
-                    mime_type: "image/jpeg".to_string(),
-                    size: 50,
-                }),
-            },
-        };
-        let result = format_embed(&embed, "did:plc:test");
-        let expected = "[Example Title](https://example.com)\n> This is a description.\n![thumb](https://cdn.bsky.app/img/feed_thumbnail/plain/did:plc:test/bafkrei_thumb...@jpeg)";
-        assert_eq!(result, expected);
-    }
-
-    #[test]
-    fn test_format_embed_record() {
-        let embed = Embed::Record {
-            record: RecordEmbed {
-                uri: "at://did:plc:test/app.bsky.feed.post/3kxyz".to_string(),
-                cid: "bafy...".to_string(),
+    fn test_format_embed_view_images_and_quote() {
+        let embed = serde_json::json!({
+            "$type": "app.bsky.embed.recordWithMedia#view",
+            "record": {
+                "$type": "app.bsky.embed.record#view",
+                "record": {
+                    "uri": "at://did:plc:q/app.bsky.feed.post/1",
+                    "author": { "handle": "bob.test" },
+                    "value": { "text": "original words" }
+                }
             },
-        };
-        let result = format_embed(&embed, "did:plc:test");
+            "media": {
+                "$type": "app.bsky.embed.images#view",
+                "images": [{ "alt": "a cat", "fullsize": "https://cdn.example/cat.jpg" }]
+            }
+        });
+        let result = format_embed_view(&embed);
         assert_eq!(
             result,
-            "> Quoted post: at://did:plc:test/app.bsky.feed.post/3kxyz"
+            "> Quoting @bob.test: original words\n![a cat](https://cdn.example/cat.jpg)"
         );
     }
 
     #[test]
-    fn test_format_embed_record_with_media() {
-        let embed = Embed::RecordWithMedia {
-            record: RecordEmbed {
-                uri: "at://did:plc:quote/app.bsky.feed.post/3kabc".to_string(),
-                cid: "bafy_quote".to_string(),
-            },
-            media: Box::new(Embed::Images {
-                images: vec![ImageEmbed {
-                    alt: Some("A cat".to_string()),
-                    image: BlobRef {
-                        type_: "blob".to_string(),
-                        ref_: "did:plc:test/bafkrei_cat...".to_string(),
-                        mime_type: "image/jpeg".to_string(),
-                        size: 999,
-                    },
-                }],
-            }),
-        };
-        let result = format_embed(&embed, "did:plc:test");
-        let expected = "> Quoted post: at://did:plc:quote/app.bsky.feed.post/3kabc\n![A cat](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkrei_cat...@jpeg)";
-        assert_eq!(result, expected);
-    }
-
-    #[test]
-    fn test_format_post_with_text_and_embed() {
-        // A post with both text and an image embed
-        let text = "Check out this cool picture!";
-        let facets = vec![];
-        let embed = Embed::Images {
-            images: vec![ImageEmbed {
-                alt: Some("A cool picture".to_string()),
-                image: BlobRef {
-                    type_: "blob".to_string(),
-                    ref_: "did:plc:test/bafy_cool...".to_string(),
-                    mime_type: "image/jpeg".to_string(),
-                    size: 123,
-                },
-            }],
-        };
-
-        let text_md = blockquote_content_with_facets(text, &facets);
-        let embed_md = format_embed(&embed, "did:plc:test");
-
-        let final_md = format!("{}\n\n{}", text_md, embed_md);
-
-        let expected_text = "> Check out this cool picture!";
-        let expected_embed = "![A cool picture](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafy_cool...@jpeg)";
-        assert!(final_md.contains(expected_text));
-        assert!(final_md.contains(expected_embed));
-    }
-
-    #[test]
-    fn test_format_post_with_facets_and_embed() {
-        // A post with rich text (facets) and an external embed
-        let text = "More info at example.com";
-        let facets = vec![Facet {
-            index: FacetIndex {
-                byte_start: 13,
-                byte_end: 24,
-            },
-            features: vec![FacetFeature::Link {
-                uri: "https://example.com".to_string(),
-            }],
-        }];
-        let embed = Embed::External {
-            external: ExternalEmbed {
-                uri: "https://anotherexample.com".to_string(),
-                title: "Another Example".to_string(),
-                description: "Description here.".to_string(),
-                thumb: None,
-            },
-        };
-
-        let text_md = blockquote_content_with_facets(text, &facets);
-        let embed_md = format_embed(&embed, "did:plc:test");
-
-        let final_md = format!("{}\n\n{}", text_md, embed_md);
-
-        let expected_text = "> More info at [example.com](https://example.com)";
-        let expected_embed = "[Another Example](https://anotherexample.com)\n> Description here.";
-        assert!(final_md.contains(expected_text));
-        assert!(final_md.contains(expected_embed));
-    }
-
-    #[test]
-    fn test_format_post_with_embed_and_empty_text() {
-        // A post with an embed but no text content.
-        let text = "";
-        let facets = vec![];
-        let embed = Embed::Images {
-            images: vec![ImageEmbed {
-                alt: Some("An image on its own".to_string()),
-                image: BlobRef {
-                    type_: "blob".to_string(),
-                    ref_: "did:plc:test/bafy_solo...".to_string(),
-                    mime_type: "image/jpeg".to_string(),
-                    size: 456,
-                },
-            }],
-        };
-
-        let text_md = blockquote_content_with_facets(text, &facets);
-        let embed_md = format_embed(&embed, "did:plc:test");
-
-        // When text is empty, blockquote_content returns "> \n". We might not want that.
-        // Let's define the final output as just the embed.
-        let final_md = if text.is_empty() {
-            embed_md
-        } else {
-            format!("{}\n\n{}", text_md, embed_md)
-        };
-
-        let expected_embed = "![An image on its own](https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafy_solo...@jpeg)";
-        assert_eq!(final_md, expected_embed);
-        assert!(!final_md.contains(">"));
+    fn test_format_embed_view_unknown_is_empty() {
+        let embed = serde_json::json!({ "$type": "app.bsky.embed.video#view" });
+        assert_eq!(format_embed_view(&embed), "");
     }
 
     #[test]
-    fn test_format_embed_record_with_external_media() {
-        // Test a more complex combination: a quote post that also has an external link card.
-        let embed = Embed::RecordWithMedia {
-            record: RecordEmbed {
-                uri: "at://did:plc:quote/app.bsky.feed.post/3kdef".to_string(),
-                cid: "bafy_quote_ext".to_string(),
-            },
-            media: Box::new(Embed::External {
-                external: ExternalEmbed {
-                    uri: "https://dev.blueskyweb.xyz/".to_string(),
-                    title: "Bluesky Dev".to_string(),
-                    description: "Dev docs".to_string(),
-                    thumb: None,
-                },
-            }),
-        };
-        let result = format_embed(&embed, "did:plc:test");
-        let expected = "> Quoted post: at://did:plc:quote/app.bsky.feed.post/3kdef\n[Bluesky Dev](https://dev.blueskyweb.xyz/)\n> Dev docs";
-        assert_eq!(result, expected);
+    fn test_truncate_with_ellipsis_respects_utf8() {
+        assert_eq!(truncate_with_ellipsis("héllo wörld", 4), "héll…");
+        assert_eq!(truncate_with_ellipsis("😊😊😊", 2), "😊😊…");
+        assert_eq!(truncate_with_ellipsis("short", 10), "short");
+        assert_eq!(truncate_with_ellipsis("exact", 5), "exact");
     }
 }
@@ -2,16 +2,19 @@
 //!
 //! Implements the `profile(account)` MCP tool
 
-use crate::bluesky::did::DidResolver;
-use crate::bluesky::provider::RepositoryProvider;
-use crate::car::cbor::{decode_cbor, get_text_field, CborValue};
-use crate::cli::ProfileArgs;
+use crate::bluesky::mst::blob_ref_cid;
+use crate::bluesky::provider::{configured_provider, RepositoryProvider};
+use crate::bluesky::records::{LabelerServiceRecord, ProfileCounts, ProfileRecord, RepoStats};
+use crate::car::cbor::{decode_cbor, get_array_field, get_map_field, get_text_field, CborValue};
+use crate::cli::{OutputFormat, ProfileArgs};
 use crate::error::{validate_account, AppError};
+use crate::http::{client_with_timeout, rate_limit_error, RetryPolicy, SendXrpc, XrpcError};
 use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::formatter::{formatter_for, ProfileView};
 use anyhow::Result;
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Helper function to extract integer field from CBOR map
 fn get_cbor_int_field(map: &[(CborValue, CborValue)], key: &str) -> Option<i64> {
@@ -42,9 +45,11 @@ fn get_cbor_blob_field(
                     let type_ = get_text_field(blob_map, "$type")
                         .unwrap_or_default()
                         .to_string();
-                    let ref_ = get_text_field(blob_map, "ref")
-                        .unwrap_or_default()
-                        .to_string();
+                    let ref_ = blob_map
+                        .iter()
+                        .find(|(k, _)| k == &CborValue::Text("ref"))
+                        .and_then(|(_, v)| blob_ref_cid(v))
+                        .unwrap_or_default();
                     let mime_type = get_text_field(blob_map, "mimeType")
                         .unwrap_or_default()
                         .to_string();
@@ -63,6 +68,89 @@ fn get_cbor_blob_field(
     None
 }
 
+/// Decode an app.bsky.actor.profile record
+fn parse_profile_record(cbor_data: &[u8]) -> Option<ProfileRecord> {
+    let CborValue::Map(profile_map) = decode_cbor(cbor_data).ok()? else {
+        return None;
+    };
+
+    // Use helper function to avoid string allocations
+    let display_name = get_text_field(&profile_map, "displayName").map(|s| s.to_string());
+    let description = get_text_field(&profile_map, "description").map(|s| s.to_string());
+    let avatar = get_cbor_blob_field(&profile_map, "avatar");
+    let banner = get_cbor_blob_field(&profile_map, "banner");
+    let created_at = get_text_field(&profile_map, "createdAt")
+        .unwrap_or("unknown")
+        .to_string();
+
+    Some(ProfileRecord {
+        display_name,
+        description,
+        avatar,
+        banner,
+        created_at,
+    })
+}
+
+/// Decode an app.bsky.labeler.service record.
+/// A record without `policies` still counts as a labeler with no declared values.
+fn parse_labeler_record(cbor_data: &[u8]) -> Option<LabelerServiceRecord> {
+    let CborValue::Map(labeler_map) = decode_cbor(cbor_data).ok()? else {
+        return None;
+    };
+
+    let label_values = get_map_field(&labeler_map, "policies")
+        .and_then(|policies| get_array_field(policies, "labelValues"))
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| match v {
+                    CborValue::Text(t) => Some(t.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(LabelerServiceRecord { label_values })
+}
+
+/// Fetch follower, following and post counts from the app.bsky.actor.getProfile view
+async fn fetch_profile_counts(did: &str, retry: &RetryPolicy) -> Result<ProfileCounts, AppError> {
+    let client = client_with_timeout(Duration::from_secs(30));
+    let url = format!(
+        "https://public.api.bsky.app/xrpc/app.bsky.actor.getProfile?actor={}",
+        urlencoding::encode(did)
+    );
+    debug!("Fetching profile counts from: {}", url);
+
+    let response = client
+        .get(&url)
+        .send_xrpc_with(retry)
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Failed to fetch profile view: {}", e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        if let Some(e) = XrpcError::parse(&error_text).and_then(|x| x.unavailable_error(did)) {
+            return Err(e);
+        }
+        return Err(AppError::NetworkError(format!(
+            "Profile API returned error {}: {}",
+            status, error_text
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse profile view: {}", e)))
+}
+
 /// Handle profile tool call
 pub async fn handle_profile(id: Option<Value>, args: Value) -> McpResponse {
     // Set total timeout to 120 seconds as specified
@@ -81,19 +169,24 @@ async fn handle_profile_impl(args: Value) -> Result<ToolResult, AppError> {
         .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
 
     // Execute using shared implementation
-    execute_profile(profile_args).await
+    let provider = configured_provider()?;
+    execute_profile(profile_args, OutputFormat::Markdown, &provider).await
 }
 
-/// Execute profile tool (shared implementation for MCP and CLI)
-pub async fn execute_profile(profile_args: ProfileArgs) -> Result<ToolResult, AppError> {
+/// Execute profile tool (shared implementation for MCP and CLI), rendering as `format`.
+/// The account is resolved, and its repo fetched and cached, as `provider` is configured.
+pub async fn execute_profile(
+    profile_args: ProfileArgs,
+    format: OutputFormat,
+    provider: &RepositoryProvider,
+) -> Result<ToolResult, AppError> {
     // Validate account parameter
     validate_account(&profile_args.account)?;
 
     debug!("Profile request for account: {}", profile_args.account);
 
     // Resolve handle to DID
-    let resolver = DidResolver::new();
-    let did = resolver.resolve_handle(&profile_args.account).await?;
+    let did = provider.resolver().resolve_handle(&profile_args.account).await?;
 
     // Determine the handle for display
     let display_handle = if profile_args.account.starts_with("did:plc:") {
@@ -109,51 +202,55 @@ pub async fn execute_profile(profile_args: ProfileArgs) -> Result<ToolResult, Ap
 
     debug!("Resolved {} to DID: {:?}", profile_args.account, did);
 
-    // Use true streaming to process CAR blocks one by one (like Go version)
-    let provider = RepositoryProvider::new()?;
-
     debug!("Starting streaming CAR block processing for {:?}", did);
-    use crate::bluesky::records::ProfileRecord;
+
+    let repo_did = did
+        .as_deref()
+        .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+
+    // Counts come from the view API alongside the repo download; the profile renders
+    // without them if that call fails
+    let fetch_counts = async {
+        if profile_args.noNetworkStats {
+            return None;
+        }
+        fetch_profile_counts(repo_did, provider.retry_policy())
+            .await
+            .map_err(|e| warn!("Profile counts unavailable for {}: {}", repo_did, e))
+            .ok()
+    };
 
     // Use the new iterator-based streaming approach
-    let mut records = provider
-        .records(
-            did.as_ref()
-                .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?,
-        )
-        .await?;
-    let profile = records.find_map(|record_result| {
-        let (record_type, cbor_data, _cid_str) = record_result.ok()?;
+    let (records, counts) = tokio::join!(provider.records(repo_did), fetch_counts);
+    let records = records?;
+    // Tally repo statistics in the same pass, from the CAR already loaded for the profile
+    let mut repo_stats = profile_args
+        .withRepoStats
+        .then(|| RepoStats::new(records.byte_len()));
+    let mut profile = None;
+    let mut labeler = None;
+    for record_result in records {
+        let Ok((record_type, cbor_data, _cid_str)) = record_result else {
+            continue;
+        };
         debug!("Processing record of type: {}", record_type);
 
-        // Check if this is a profile record
-        if record_type == "app.bsky.actor.profile" {
-            debug!("Found profile record!");
-
-            // Decode CBOR data to ProfileRecord
-            if let Ok(CborValue::Map(profile_map)) = decode_cbor(&cbor_data) {
-                // Use helper function to avoid string allocations
-                let display_name =
-                    get_text_field(&profile_map, "displayName").map(|s| s.to_string());
-                let description =
-                    get_text_field(&profile_map, "description").map(|s| s.to_string());
-                let avatar = get_cbor_blob_field(&profile_map, "avatar");
-                let banner = get_cbor_blob_field(&profile_map, "banner");
-                let created_at = get_text_field(&profile_map, "createdAt")
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                return Some(ProfileRecord {
-                    display_name,
-                    description,
-                    avatar,
-                    banner,
-                    created_at,
-                });
+        if let Some(stats) = repo_stats.as_mut() {
+            stats.tally(&record_type);
+        }
+
+        match record_type.as_str() {
+            "app.bsky.actor.profile" if profile.is_none() => {
+                debug!("Found profile record!");
+                profile = parse_profile_record(&cbor_data);
             }
+            "app.bsky.labeler.service" if labeler.is_none() => {
+                debug!("Found labeler service record");
+                labeler = parse_labeler_record(&cbor_data);
+            }
+            _ => {}
         }
-        None
-    });
+    }
 
     let profile = match profile {
         Some(profile_data) => profile_data,
@@ -167,16 +264,21 @@ pub async fn execute_profile(profile_args: ProfileArgs) -> Result<ToolResult, Ap
 
     debug!("Found profile record");
 
-    // Convert to markdown
-    let markdown = profile.to_markdown(
-        &display_handle,
-        did.as_ref()
-            .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?,
-    );
+    let did = did.ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+
+    let view = ProfileView {
+        handle: display_handle,
+        did,
+        profile,
+        labeler,
+        counts,
+        repo_stats,
+    };
+    let text = formatter_for(format).profile(&view);
 
     debug!("Profile request completed for: {}", profile_args.account);
 
-    Ok(ToolResult::text(markdown))
+    Ok(ToolResult::text(text).with_data(view))
 }
 
 #[cfg(test)]
@@ -208,4 +310,227 @@ mod tests {
         let result = validate_account("did:plc:abc123xyz789012345678901");
         assert!(result.is_ok());
     }
+
+    fn cbor_text(s: &str) -> Vec<u8> {
+        let mut out = if s.len() < 24 {
+            vec![0x60 + s.len() as u8]
+        } else {
+            vec![0x78, s.len() as u8]
+        };
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    #[test]
+    fn test_parse_profile_record_avatar_link_ref() {
+        use crate::car::test_support::{encode, Cbor};
+
+        let mut cid = vec![1, 0x55, 0x12, 32];
+        cid.extend([7; 32]);
+        let mut data = Vec::new();
+        encode(
+            &Cbor::Map(vec![
+                ("$type", Cbor::Text("app.bsky.actor.profile".to_string())),
+                ("displayName", Cbor::Text("Alice".to_string())),
+                (
+                    "avatar",
+                    Cbor::Map(vec![
+                        ("$type", Cbor::Text("blob".to_string())),
+                        ("ref", Cbor::Link(cid)),
+                        ("mimeType", Cbor::Text("image/jpeg".to_string())),
+                        ("size", Cbor::Int(2048)),
+                    ]),
+                ),
+            ]),
+            &mut data,
+        );
+
+        let profile = parse_profile_record(&data).expect("profile record");
+        let avatar = profile.avatar.expect("avatar");
+        assert_eq!(avatar.ref_, "bafkreiaha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4");
+        assert_eq!(avatar.size, 2048);
+    }
+
+    #[test]
+    fn test_parse_labeler_record_with_label_values() {
+        let mut data = vec![0xa2];
+        data.extend(cbor_text("$type"));
+        data.extend(cbor_text("app.bsky.labeler.service"));
+        data.extend(cbor_text("policies"));
+        data.push(0xa1);
+        data.extend(cbor_text("labelValues"));
+        data.push(0x82);
+        data.extend(cbor_text("spam"));
+        data.extend(cbor_text("nsfw"));
+
+        let labeler = parse_labeler_record(&data).expect("labeler record");
+        assert_eq!(labeler.label_values, vec!["spam", "nsfw"]);
+
+        let profile = ProfileRecord {
+            display_name: Some("Mod".to_string()),
+            description: None,
+            avatar: None,
+            banner: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let md =
+            profile.to_markdown_with_details("mod.test", "did:plc:mod", Some(&labeler), None, None);
+        assert!(md.contains("🏷 Labeler service** · labels: spam, nsfw"));
+        assert!(!profile.to_markdown("mod.test", "did:plc:mod").contains("Labeler"));
+    }
+
+    #[test]
+    fn test_parse_labeler_record_without_policies() {
+        let mut data = vec![0xa1];
+        data.extend(cbor_text("$type"));
+        data.extend(cbor_text("app.bsky.labeler.service"));
+
+        let labeler = parse_labeler_record(&data).expect("labeler without policies");
+        assert!(labeler.label_values.is_empty());
+        assert_eq!(labeler.to_markdown(), "**🏷 Labeler service**\n\n");
+    }
+
+    #[test]
+    fn test_repo_stats_line_in_profile() {
+        let mut stats = RepoStats::new(3 * 1024 * 1024 / 2);
+        for record_type in ["app.bsky.feed.post", "app.bsky.feed.like", "app.bsky.feed.post"] {
+            stats.tally(record_type);
+        }
+        assert_eq!(stats.total_records, 3);
+        assert_eq!(stats.post_count, 2);
+        assert_eq!(stats.to_markdown(), "- Repo: 3 records · 2 posts · 1.5 MB\n");
+        assert!(RepoStats::new(512).to_markdown().ends_with("· 512 B\n"));
+
+        let profile = ProfileRecord {
+            display_name: None,
+            description: None,
+            avatar: None,
+            banner: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let md =
+            profile.to_markdown_with_details("big.test", "did:plc:big", None, None, Some(&stats));
+        assert!(md.find("- Created:").unwrap() < md.find("- Repo: 3 records").unwrap());
+        assert!(!profile.to_markdown("big.test", "did:plc:big").contains("- Repo:"));
+    }
+
+    #[test]
+    fn test_profile_json_output() {
+        let profile = ProfileRecord {
+            display_name: Some("Alice".to_string()),
+            description: Some("hello".to_string()),
+            avatar: None,
+            banner: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let mut stats = RepoStats::new(2048);
+        stats.tally("app.bsky.feed.post");
+        let data = ProfileView {
+            handle: "alice.test".to_string(),
+            did: "did:plc:alice".to_string(),
+            profile,
+            labeler: None,
+            counts: None,
+            repo_stats: Some(stats),
+        };
+        let result = ToolResult::text("# @alice.test").with_data(data);
+
+        let json: Value = serde_json::from_str(&result.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["handle"], "alice.test");
+        assert_eq!(json["did"], "did:plc:alice");
+        assert_eq!(json["displayName"], "Alice");
+        assert_eq!(json["createdAt"], "2024-01-01T00:00:00Z");
+        assert_eq!(json["repoStats"]["postCount"], 1);
+        assert!(json.get("labeler").is_none());
+        assert_eq!(result.render(OutputFormat::Markdown), "# @alice.test");
+
+        // Tools without structured data still produce valid JSON
+        let plain: Value =
+            serde_json::from_str(&ToolResult::text("done").render(OutputFormat::Json)).unwrap();
+        assert_eq!(plain["markdown"], "done");
+    }
+
+    #[test]
+    fn test_profile_counts_from_view() {
+        let view = r#"{
+            "did": "did:plc:alice",
+            "handle": "alice.test",
+            "followersCount": 1234,
+            "followsCount": 567,
+            "postsCount": 8901,
+            "indexedAt": "2024-01-01T00:00:00Z"
+        }"#;
+        let counts: ProfileCounts = serde_json::from_str(view).unwrap();
+        assert_eq!(
+            counts.to_markdown(),
+            "- Followers: 1234\n- Following: 567\n- Posts: 8901\n"
+        );
+
+        let profile = ProfileRecord {
+            display_name: None,
+            description: None,
+            avatar: None,
+            banner: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        let with_counts = profile.to_markdown_with_details(
+            "alice.test",
+            "did:plc:alice",
+            None,
+            Some(&counts),
+            None,
+        );
+        assert!(with_counts.contains(
+            "**Stats:**\n- Created: 2024-01-01T00:00:00Z\n- Followers: 1234\n- Following: 567\n"
+        ));
+
+        // Repo-derived data only, as with --no-network-stats
+        let offline = profile.to_markdown("alice.test", "did:plc:alice");
+        assert!(offline.contains("**Stats:**\n- Created: 2024-01-01T00:00:00Z\n\n"));
+        assert!(!offline.contains("Followers"));
+
+        // Counts the view leaves out are skipped
+        let partial: ProfileCounts = serde_json::from_str(r#"{"followersCount": 3}"#).unwrap();
+        assert_eq!(partial.to_markdown(), "- Followers: 3\n");
+
+        let data = ProfileView {
+            handle: "alice.test".to_string(),
+            did: "did:plc:alice".to_string(),
+            profile,
+            labeler: None,
+            counts: Some(counts),
+            repo_stats: None,
+        };
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["followersCount"], 1234);
+        assert_eq!(json["postsCount"], 8901);
+
+        // Missing counts are left out of JSON rather than written as null
+        let json = serde_json::to_value(ProfileView {
+            counts: Some(partial),
+            ..data
+        })
+        .unwrap();
+        assert_eq!(json["followersCount"], 3);
+        assert!(json.get("followsCount").is_none());
+        assert!(json.get("postsCount").is_none());
+    }
+
+    #[test]
+    fn test_network_stats_default_on() {
+        let parsed: ProfileArgs = serde_json::from_value(json!({ "account": "a.test" })).unwrap();
+        assert!(!parsed.noNetworkStats);
+        let parsed: ProfileArgs =
+            serde_json::from_value(json!({ "account": "a.test", "noNetworkStats": true })).unwrap();
+        assert!(parsed.noNetworkStats);
+    }
+
+    #[test]
+    fn test_repo_stats_opt_in() {
+        let parsed: ProfileArgs = serde_json::from_value(json!({ "account": "a.test" })).unwrap();
+        assert!(!parsed.withRepoStats);
+        let parsed: ProfileArgs =
+            serde_json::from_value(json!({ "account": "a.test", "withRepoStats": true })).unwrap();
+        assert!(parsed.withRepoStats);
+    }
 }
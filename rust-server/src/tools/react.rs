@@ -1,14 +1,19 @@
 //! React tool implementation
 //!
-//! Implements the `react(reactAs, like, unlike, repost, delete)` MCP tool
+//! Implements the `react(reactAs, like, unlike, repost, unrepost, delete)` MCP tool
 //! Supports batching multiple operations in a single call
 
 use crate::auth::storage::CredentialStorage;
+use crate::auth::session::refresh_buffer;
 use crate::auth::SessionManager;
+use crate::bluesky::uri::AtUri;
 use crate::cli::ReactArgs;
 use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
 use crate::mcp::{McpResponse, ToolResult};
+use crate::tools::apply_writes::{apply_writes, RepoWrite};
 use anyhow::Result;
+use serde::Serialize;
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
 use tracing::debug;
@@ -37,31 +42,25 @@ async fn handle_react_impl(args: Value) -> Result<ToolResult, AppError> {
 /// Execute react tool (shared implementation for MCP and CLI)
 pub async fn execute_react(react_args: ReactArgs) -> Result<ToolResult, AppError> {
     debug!(
-        "React request for account: {}, like: {}, unlike: {}, repost: {}, delete: {}",
-        react_args.reactAs,
+        "React request for account: {}, like: {}, unlike: {}, repost: {}, unrepost: {}, delete: {}",
+        react_args.reactAs.as_deref().unwrap_or("(default)"),
         react_args.like.len(),
         react_args.unlike.len(),
         react_args.repost.len(),
+        react_args.unrepost.len(),
         react_args.delete.len()
     );
 
-    // Get credentials for the account
+    validate_react_args(&react_args)?;
+
+    // Get credentials for the requested, default or only stored account
     let storage = CredentialStorage::new()?;
+    let account = storage.resolve_account(react_args.reactAs.as_deref())?;
 
-    // Try to get stored session first (for OAuth accounts)
-    let session = if let Some(stored_session) = storage.get_session(&react_args.reactAs)? {
-        debug!("Using stored session for {}", react_args.reactAs);
-        stored_session
-    } else {
-        // Fallback to creating new session with credentials (for app password accounts)
-        debug!(
-            "No valid session for {}, using app password flow",
-            react_args.reactAs
-        );
-        let credentials = storage.get_credentials(&react_args.reactAs)?;
-        let session_manager = SessionManager::new()?;
-        session_manager.login(&credentials).await?
-    };
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &account, refresh_buffer())
+        .await?;
 
     debug!("Authenticated as {} (DID: {})", session.handle, session.did);
 
@@ -95,13 +94,40 @@ pub async fn execute_react(react_args: ReactArgs) -> Result<ToolResult, AppError
         }
     }
 
-    // Process deletes
-    for post_uri in &react_args.delete {
-        match process_delete(&client, &session, post_uri).await {
+    // Process unreposts
+    for post_uri in &react_args.unrepost {
+        match process_unrepost(&client, &session, post_uri).await {
             Ok(msg) => results.push(msg),
+            Err(e) => errors.push(format!("Unrepost failed for {}: {}", post_uri, e)),
+        }
+    }
+
+    // Check every delete first, then remove them together in one applyWrites batch
+    let mut deletes: Vec<(&String, RepoWrite)> = Vec::new();
+    for post_uri in &react_args.delete {
+        match delete_write(&client, &session, post_uri).await {
+            Ok(write) if deletes.iter().any(|(_, queued)| *queued == write) => {
+                debug!("Skipping repeated delete of {}", post_uri);
+            }
+            Ok(write) => deletes.push((post_uri, write)),
             Err(e) => errors.push(format!("Delete failed for {}: {}", post_uri, e)),
         }
     }
+    if !deletes.is_empty() {
+        let writes = deletes.iter().map(|(_, write)| write.clone()).collect();
+        match apply_writes(&client, &session, writes).await {
+            Ok(_) => results.extend(
+                deletes
+                    .iter()
+                    .map(|(post_uri, _)| format!("Deleted post: {}", post_uri)),
+            ),
+            Err(e) => errors.extend(
+                deletes
+                    .iter()
+                    .map(|(post_uri, _)| format!("Delete failed for {}: {}", post_uri, e)),
+            ),
+        }
+    }
 
     // Format results as markdown
     let mut markdown = String::from("# React Operations Results\n\n");
@@ -134,7 +160,59 @@ pub async fn execute_react(react_args: ReactArgs) -> Result<ToolResult, AppError
         errors.len()
     );
 
-    Ok(ToolResult::text(markdown))
+    let data = ReactOutput { succeeded: results, failed: errors };
+    Ok(ToolResult::text(markdown).with_data(data))
+}
+
+/// Structured result of a react call, for `--format json`
+#[derive(Debug, Serialize)]
+struct ReactOutput {
+    succeeded: Vec<String>,
+    failed: Vec<String>,
+}
+
+/// Reject calls that name an empty account, request no operation, or both add and remove
+/// the same reaction on one post
+fn validate_react_args(args: &ReactArgs) -> Result<(), AppError> {
+    if args.reactAs.as_deref().is_some_and(|account| account.trim().is_empty()) {
+        return Err(AppError::InvalidInput("reactAs cannot be empty".to_string()));
+    }
+
+    let operations = [&args.like, &args.unlike, &args.repost, &args.unrepost, &args.delete];
+    if operations.iter().all(|posts| posts.is_empty()) {
+        return Err(AppError::InvalidInput(
+            "No operations given: provide like, unlike, repost, unrepost or delete".to_string(),
+        ));
+    }
+    if operations.iter().any(|posts| posts.iter().any(|p| p.trim().is_empty())) {
+        return Err(AppError::InvalidInput("Post reference cannot be empty".to_string()));
+    }
+
+    for (add, remove, verb) in [
+        (&args.like, &args.unlike, "like and unlike"),
+        (&args.repost, &args.unrepost, "repost and unrepost"),
+    ] {
+        if let Some(post) = add.iter().find(|p| remove.contains(p)) {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot both {} the same post: {}",
+                verb, post
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a like or repost record pointing at a post by strong ref (URI + CID)
+fn subject_record(record_type: &str, uri: &str, cid: &str, created_at: &str) -> Value {
+    serde_json::json!({
+        "$type": record_type,
+        "subject": {
+            "uri": uri,
+            "cid": cid
+        },
+        "createdAt": created_at,
+    })
 }
 
 /// Process a like operation
@@ -143,44 +221,40 @@ async fn process_like(
     session: &crate::auth::Session,
     post_uri: &str,
 ) -> Result<String, AppError> {
-    let (did, _rkey, uri, cid) = fetch_post_info(client, session, post_uri).await?;
-
-    let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.service);
-
-    let body = serde_json::json!({
-        "repo": session.did,
-        "collection": "app.bsky.feed.like",
-        "record": {
-            "$type": "app.bsky.feed.like",
-            "subject": {
-                "uri": uri,
-                "cid": cid
-            },
-            "createdAt": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
-        }
-    });
-
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::NetworkError(format!("Like request failed: {}", e)))?;
+    let (did, rkey) =
+        create_subject_record(client, session, post_uri, "app.bsky.feed.like").await?;
+    Ok(format!("Liked post: {} ({}), like rkey {}", post_uri, did, rkey))
+}
 
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(AppError::NetworkError(format!(
-            "Like failed with status {}: {}",
-            status, error_text
-        )));
-    }
+/// Create the caller's `collection` record (like or repost) pointing at the post by strong
+/// ref. Returns the post author's DID and the rkey of the new record.
+async fn create_subject_record(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    post_uri: &str,
+    collection: &str,
+) -> Result<(String, String), AppError> {
+    let (did, _rkey, uri, cid) = fetch_post_info(client, session, post_uri).await?;
 
-    Ok(format!("Liked post: {} ({})", post_uri, did))
+    let write = RepoWrite::Create {
+        collection: collection.to_string(),
+        rkey: None,
+        value: subject_record(
+            collection,
+            &uri,
+            &cid,
+            &chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        ),
+    };
+    let rkey = apply_writes(client, session, vec![write])
+        .await?
+        .pop()
+        .and_then(|created| created.uri)
+        .as_deref()
+        .and_then(record_rkey)
+        .ok_or_else(|| AppError::ParseError(format!("No record URI for new {}", collection)))?;
+
+    Ok((did, rkey))
 }
 
 /// Process an unlike operation
@@ -189,71 +263,105 @@ async fn process_unlike(
     session: &crate::auth::Session,
     post_uri: &str,
 ) -> Result<String, AppError> {
-    // First, we need to find the like record for this post
+    let (did, rkey) =
+        delete_subject_record(client, session, post_uri, "app.bsky.feed.like", "like").await?;
+    Ok(format!("Unliked post: {} ({}), deleted like rkey {}", post_uri, did, rkey))
+}
+
+/// Process an unrepost operation
+async fn process_unrepost(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    post_uri: &str,
+) -> Result<String, AppError> {
+    let (did, rkey) =
+        delete_subject_record(client, session, post_uri, "app.bsky.feed.repost", "repost").await?;
+    Ok(format!("Unreposted post: {} ({}), deleted repost rkey {}", post_uri, did, rkey))
+}
+
+/// Find the caller's `collection` record (like or repost) whose subject is the post and
+/// delete it. Returns the post author's DID and the rkey of the deleted record.
+async fn delete_subject_record(
+    client: &reqwest::Client,
+    session: &crate::auth::Session,
+    post_uri: &str,
+    collection: &str,
+    noun: &str,
+) -> Result<(String, String), AppError> {
     let (did, _rkey, uri, _cid) = fetch_post_info(client, session, post_uri).await?;
 
-    // List likes to find the one for this post
-    // Note: This lists up to 100 likes. For users with more likes, this is a known limitation.
-    // A future improvement would be to implement pagination if the like is not found in the first page.
-    let list_url = format!(
-        "{}/xrpc/com.atproto.repo.listRecords?repo={}&collection=app.bsky.feed.like&limit=100",
-        session.service, session.did
-    );
+    // Records are listed newest first, so recent reactions are found on the first page
+    let mut cursor: Option<String> = None;
+    let record_rkey = loop {
+        let mut list_url = format!(
+            "{}/xrpc/com.atproto.repo.listRecords?repo={}&collection={}&limit=100",
+            session.service, session.did, collection
+        );
+        if let Some(cursor) = &cursor {
+            list_url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+        }
 
-    let response = client
-        .get(&list_url)
-        .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .send()
-        .await
-        .map_err(|e| AppError::NetworkError(format!("Failed to list likes: {}", e)))?;
+        let response = client
+            .get(&list_url)
+            .header("Authorization", format!("Bearer {}", session.access_jwt))
+            .send_xrpc()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to list {}s: {}", noun, e)))?;
 
-    let status = response.status();
-    if !status.is_success() {
-        return Err(AppError::NetworkError(format!(
-            "Failed to list likes: {}",
-            status
-        )));
-    }
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
 
-    let list_result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| AppError::ParseError(format!("Failed to parse likes list: {}", e)))?;
-
-    // Find the like record for this post
-    let like_rkey = list_result["records"]
-        .as_array()
-        .and_then(|records| {
-            records.iter().find_map(|record| {
-                let subject_uri = record["value"]["subject"]["uri"].as_str()?;
-                if subject_uri == uri {
-                    record["uri"].as_str().and_then(|u| {
-                        // Extract rkey from at://{did}/app.bsky.feed.like/{rkey}
-                        u.split('/').next_back().map(|s| s.to_string())
-                    })
-                } else {
-                    None
-                }
-            })
-        })
-        .ok_or_else(|| AppError::NotFound(format!("No like found for post: {}", post_uri)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::NetworkError(format!(
+                "Failed to list {}s: {}",
+                noun, status
+            )));
+        }
 
-    // Delete the like record
+        let list_result: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::ParseError(format!("Failed to parse {}s list: {}", noun, e)))?;
+
+        if let Some(rkey) = find_subject_record_rkey(&list_result, &uri) {
+            break rkey;
+        }
+        let has_records = list_result["records"].as_array().is_some_and(|r| !r.is_empty());
+        match list_result["cursor"].as_str() {
+            Some(next) if has_records && cursor.as_deref() != Some(next) => {
+                cursor = Some(next.to_string());
+            }
+            _ => {
+                return Err(AppError::NotFound(format!(
+                    "No {} found for post: {}",
+                    noun, post_uri
+                )))
+            }
+        }
+    };
+
+    // Delete the record
     let delete_url = format!("{}/xrpc/com.atproto.repo.deleteRecord", session.service);
 
     let delete_body = serde_json::json!({
         "repo": session.did,
-        "collection": "app.bsky.feed.like",
-        "rkey": like_rkey
+        "collection": collection,
+        "rkey": record_rkey
     });
 
     let response = client
         .post(&delete_url)
         .header("Authorization", format!("Bearer {}", session.access_jwt))
         .json(&delete_body)
-        .send()
+        .send_xrpc()
         .await
-        .map_err(|e| AppError::NetworkError(format!("Unlike request failed: {}", e)))?;
+        .map_err(|e| AppError::NetworkError(format!("Un{} request failed: {}", noun, e)))?;
+
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
 
     let status = response.status();
     if !status.is_success() {
@@ -262,12 +370,27 @@ async fn process_unlike(
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
         return Err(AppError::NetworkError(format!(
-            "Unlike failed with status {}: {}",
-            status, error_text
+            "Un{} failed with status {}: {}",
+            noun, status, error_text
         )));
     }
 
-    Ok(format!("Unliked post: {} ({})", post_uri, did))
+    Ok((did, record_rkey))
+}
+
+/// Rkey of the record in a `listRecords` response whose subject is `subject_uri`
+fn find_subject_record_rkey(list_result: &Value, subject_uri: &str) -> Option<String> {
+    list_result["records"].as_array()?.iter().find_map(|record| {
+        if record["value"]["subject"]["uri"].as_str()? != subject_uri {
+            return None;
+        }
+        record["uri"].as_str().and_then(record_rkey)
+    })
+}
+
+/// Rkey from an `at://{did}/{collection}/{rkey}` record URI
+fn record_rkey(uri: &str) -> Option<String> {
+    AtUri::parse(uri).ok().map(|uri| uri.rkey.to_string())
 }
 
 /// Process a repost operation
@@ -276,52 +399,17 @@ async fn process_repost(
     session: &crate::auth::Session,
     post_uri: &str,
 ) -> Result<String, AppError> {
-    let (did, _rkey, uri, cid) = fetch_post_info(client, session, post_uri).await?;
-
-    let url = format!("{}/xrpc/com.atproto.repo.createRecord", session.service);
-
-    let body = serde_json::json!({
-        "repo": session.did,
-        "collection": "app.bsky.feed.repost",
-        "record": {
-            "$type": "app.bsky.feed.repost",
-            "subject": {
-                "uri": uri,
-                "cid": cid
-            },
-            "createdAt": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
-        }
-    });
-
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::NetworkError(format!("Repost request failed: {}", e)))?;
-
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(AppError::NetworkError(format!(
-            "Repost failed with status {}: {}",
-            status, error_text
-        )));
-    }
-
-    Ok(format!("Reposted post: {} ({})", post_uri, did))
+    let (did, rkey) =
+        create_subject_record(client, session, post_uri, "app.bsky.feed.repost").await?;
+    Ok(format!("Reposted post: {} ({}), repost rkey {}", post_uri, did, rkey))
 }
 
-/// Process a delete operation
-async fn process_delete(
+/// Check that a post exists and belongs to the authenticated user, and build its delete
+async fn delete_write(
     client: &reqwest::Client,
     session: &crate::auth::Session,
     post_uri: &str,
-) -> Result<String, AppError> {
+) -> Result<RepoWrite, AppError> {
     let (did, rkey, _uri, _cid) = fetch_post_info(client, session, post_uri).await?;
 
     // Verify the post belongs to the authenticated user
@@ -332,35 +420,10 @@ async fn process_delete(
         )));
     }
 
-    let url = format!("{}/xrpc/com.atproto.repo.deleteRecord", session.service);
-
-    let body = serde_json::json!({
-        "repo": session.did,
-        "collection": "app.bsky.feed.post",
-        "rkey": rkey
-    });
-
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| AppError::NetworkError(format!("Delete request failed: {}", e)))?;
-
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(AppError::NetworkError(format!(
-            "Delete failed with status {}: {}",
-            status, error_text
-        )));
-    }
-
-    Ok(format!("Deleted post: {}", post_uri))
+    Ok(RepoWrite::Delete {
+        collection: "app.bsky.feed.post".to_string(),
+        rkey,
+    })
 }
 
 /// Fetch post information (DID, rkey, URI, CID) from a URI/URL
@@ -381,10 +444,14 @@ async fn fetch_post_info(
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", session.access_jwt))
-        .send()
+        .send_xrpc()
         .await
         .map_err(|e| AppError::NetworkError(format!("Failed to fetch post: {}", e)))?;
 
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
     let status = response.status();
     if !status.is_success() {
         let error_text = response
@@ -430,7 +497,7 @@ mod tests {
         });
 
         let parsed: ReactArgs = serde_json::from_value(args).unwrap();
-        assert_eq!(parsed.reactAs, "test.bsky.social");
+        assert_eq!(parsed.reactAs.as_deref(), Some("test.bsky.social"));
         assert_eq!(parsed.like.len(), 1);
         assert_eq!(parsed.unlike.len(), 0);
         assert_eq!(parsed.repost.len(), 0);
@@ -448,10 +515,238 @@ mod tests {
         });
 
         let parsed: ReactArgs = serde_json::from_value(args).unwrap();
-        assert_eq!(parsed.reactAs, "test.bsky.social");
+        assert_eq!(parsed.reactAs.as_deref(), Some("test.bsky.social"));
         assert_eq!(parsed.like.len(), 1);
         assert_eq!(parsed.unlike.len(), 1);
         assert_eq!(parsed.repost.len(), 1);
         assert_eq!(parsed.delete.len(), 1);
+        assert!(parsed.unrepost.is_empty());
+    }
+
+    fn args(repost: &[&str], unrepost: &[&str]) -> ReactArgs {
+        let posts = |p: &[&str]| p.iter().map(|s| s.to_string()).collect();
+        ReactArgs {
+            reactAs: Some("test.bsky.social".to_string()),
+            like: vec![],
+            unlike: vec![],
+            repost: posts(repost),
+            unrepost: posts(unrepost),
+            delete: vec![],
+        }
+    }
+
+    #[test]
+    fn test_validate_react_args() {
+        let a = "at://did:plc:abc/app.bsky.feed.post/1";
+        let b = "at://did:plc:abc/app.bsky.feed.post/2";
+        assert!(validate_react_args(&args(&[a], &[b])).is_ok());
+        assert!(validate_react_args(&args(&[], &[b])).is_ok());
+
+        for bad in [args(&[], &[]), args(&[a], &[a]), args(&[" "], &[])] {
+            assert!(matches!(validate_react_args(&bad), Err(AppError::InvalidInput(_))));
+        }
+
+        let mut no_account = args(&[a], &[]);
+        no_account.reactAs = Some(String::new());
+        assert!(matches!(validate_react_args(&no_account), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_subject_record_is_strong_ref() {
+        let record = subject_record(
+            "app.bsky.feed.repost",
+            "at://did:plc:abc/app.bsky.feed.post/3k",
+            "bafyreicid",
+            "2025-01-01T00:00:00.000Z",
+        );
+        assert_eq!(
+            record,
+            json!({
+                "$type": "app.bsky.feed.repost",
+                "subject": {
+                    "uri": "at://did:plc:abc/app.bsky.feed.post/3k",
+                    "cid": "bafyreicid"
+                },
+                "createdAt": "2025-01-01T00:00:00.000Z"
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_subject_record_rkey() {
+        let list = json!({
+            "records": [
+                {
+                    "uri": "at://did:plc:me/app.bsky.feed.repost/3aaa",
+                    "value": { "subject": { "uri": "at://did:plc:x/app.bsky.feed.post/1" } }
+                },
+                {
+                    "uri": "at://did:plc:me/app.bsky.feed.repost/3bbb",
+                    "value": { "subject": { "uri": "at://did:plc:x/app.bsky.feed.post/2" } }
+                }
+            ]
+        });
+        assert_eq!(
+            find_subject_record_rkey(&list, "at://did:plc:x/app.bsky.feed.post/2").as_deref(),
+            Some("3bbb")
+        );
+        assert_eq!(find_subject_record_rkey(&list, "at://did:plc:x/app.bsky.feed.post/9"), None);
+        assert_eq!(find_subject_record_rkey(&json!({}), "anything"), None);
+    }
+
+    /// PDS stand-in serving one post, two pages of likes, and the record writes
+    async fn pds_server() -> (String, std::sync::Arc<std::sync::Mutex<Vec<(String, Value)>>>) {
+        use axum::extract::Query;
+        use axum::routing::{get, post};
+        use std::collections::HashMap;
+
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let (on_list, on_create, on_delete) =
+            (requests.clone(), requests.clone(), requests.clone());
+        let app = axum::Router::new()
+            .route(
+                "/xrpc/com.atproto.repo.getRecord",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    axum::Json(json!({
+                        "uri": format!("at://did:plc:author/app.bsky.feed.post/{}", query["rkey"]),
+                        "cid": "bafypost",
+                        "value": { "text": "hello" }
+                    }))
+                }),
+            )
+            .route(
+                "/xrpc/com.atproto.repo.listRecords",
+                get(move |Query(query): Query<HashMap<String, String>>| {
+                    let page = match query.get("cursor").map(String::as_str) {
+                        None => json!({
+                            "records": [{
+                                "uri": "at://did:plc:me/app.bsky.feed.like/3kother",
+                                "value": { "subject": {
+                                    "uri": "at://did:plc:author/app.bsky.feed.post/3kelse"
+                                } }
+                            }],
+                            "cursor": "page2"
+                        }),
+                        _ => json!({
+                            "records": [{
+                                "uri": "at://did:plc:me/app.bsky.feed.like/3klike",
+                                "value": { "subject": {
+                                    "uri": "at://did:plc:author/app.bsky.feed.post/3kpost"
+                                } }
+                            }]
+                        }),
+                    };
+                    on_list.lock().unwrap().push(("list".to_string(), json!(query)));
+                    async move { axum::Json(page) }
+                }),
+            )
+            .route(
+                "/xrpc/com.atproto.repo.applyWrites",
+                post(move |axum::Json(body): axum::Json<Value>| {
+                    on_create.lock().unwrap().push(("create".to_string(), body));
+                    async {
+                        axum::Json(json!({
+                            "results": [{
+                                "$type": "com.atproto.repo.applyWrites#createResult",
+                                "uri": "at://did:plc:me/app.bsky.feed.like/3knew",
+                                "cid": "bafynew"
+                            }]
+                        }))
+                    }
+                }),
+            )
+            .route(
+                "/xrpc/com.atproto.repo.deleteRecord",
+                post(move |axum::Json(body): axum::Json<Value>| {
+                    on_delete.lock().unwrap().push(("delete".to_string(), body));
+                    async { axum::Json(json!({})) }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, requests)
+    }
+
+    fn session(service: &str) -> crate::auth::Session {
+        crate::auth::Session {
+            access_jwt: "access".to_string(),
+            refresh_jwt: "refresh".to_string(),
+            handle: "me.test".to_string(),
+            did: "did:plc:me".to_string(),
+            service: service.to_string(),
+            expires_at: None,
+            oauth: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_like_creates_strong_ref_and_reports_rkey() {
+        let (url, requests) = pds_server().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let post = "at://did:plc:author/app.bsky.feed.post/3kpost";
+
+        let message = process_like(&client, &session(&url), post).await.unwrap();
+        assert_eq!(
+            message,
+            format!("Liked post: {} (did:plc:author), like rkey 3knew", post)
+        );
+
+        let requests = requests.lock().unwrap();
+        let (kind, body) = &requests[0];
+        assert_eq!(kind, "create");
+        assert_eq!(body["repo"], "did:plc:me");
+        let write = &body["writes"][0];
+        assert_eq!(write["$type"], "com.atproto.repo.applyWrites#create");
+        assert_eq!(write["collection"], "app.bsky.feed.like");
+        assert!(write.get("rkey").is_none());
+        assert_eq!(write["value"]["$type"], "app.bsky.feed.like");
+        assert_eq!(write["value"]["subject"], json!({ "uri": post, "cid": "bafypost" }));
+        assert!(write["value"]["createdAt"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[tokio::test]
+    async fn test_unlike_finds_like_on_later_page_then_deletes_it() {
+        let (url, log) = pds_server().await;
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let post = "at://did:plc:author/app.bsky.feed.post/3kpost";
+
+        let message = process_unlike(&client, &session(&url), post).await.unwrap();
+        assert_eq!(
+            message,
+            format!("Unliked post: {} (did:plc:author), deleted like rkey 3klike", post)
+        );
+
+        {
+            let requests = log.lock().unwrap();
+            let kinds: Vec<&str> = requests.iter().map(|(kind, _)| kind.as_str()).collect();
+            assert_eq!(kinds, ["list", "list", "delete"]);
+            assert_eq!(requests[0].1["collection"], "app.bsky.feed.like");
+            assert!(requests[0].1.get("cursor").is_none());
+            assert_eq!(requests[1].1["cursor"], "page2");
+            assert_eq!(
+                requests[2].1,
+                json!({
+                    "repo": "did:plc:me",
+                    "collection": "app.bsky.feed.like",
+                    "rkey": "3klike"
+                })
+            );
+        }
+
+        // A post the caller never liked is reported after the last page, and nothing is deleted
+        let never_liked = "at://did:plc:author/app.bsky.feed.post/3kmissing";
+        let result = process_unlike(&client, &session(&url), never_liked).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        let requests = log.lock().unwrap();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests.iter().filter(|(kind, _)| kind == "delete").count(), 1);
+    }
+
+    #[test]
+    fn test_record_rkey() {
+        let uri = "at://did:plc:me/app.bsky.feed.like/3kabc";
+        assert_eq!(record_rkey(uri).as_deref(), Some("3kabc"));
+        assert_eq!(record_rkey("at://did:plc:me/app.bsky.feed.like/"), None);
     }
 }
@@ -0,0 +1,173 @@
+//! Record tool implementation
+//!
+//! Implements the `record(uri)` MCP tool: one record of any collection, read from its
+//! owner's repository and shown as its decoded JSON (also the `--format json` output)
+
+use crate::bluesky::did::DidResolver;
+use crate::bluesky::provider::configured_provider;
+use crate::bluesky::uri::AtUri;
+use crate::cli::RecordArgs;
+use crate::error::AppError;
+use crate::mcp::{McpResponse, ToolResult};
+use crate::resources::{read_record, record_json_block};
+use anyhow::Result;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Handle record tool call
+pub async fn handle_record(id: Option<Value>, args: Value) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_record_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "Record request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_record_impl(args: Value) -> Result<ToolResult, AppError> {
+    let record_args: RecordArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    execute_record(record_args).await
+}
+
+/// Execute record tool (shared implementation for MCP and CLI)
+pub async fn execute_record(record_args: RecordArgs) -> Result<ToolResult, AppError> {
+    debug!("Record request for: {}", record_args.uri);
+
+    // The authority may be a handle, resolved like any other account
+    let uri = AtUri::parse(record_args.uri.trim())?;
+    let did = DidResolver::new()
+        .resolve_handle(uri.did)
+        .await?
+        .ok_or_else(|| AppError::DidResolveFailed(format!("Could not resolve: {}", uri.did)))?;
+
+    let provider = configured_provider()?;
+    let car_bytes = provider.fetch_repo_bytes(&did).await?;
+    record_result(&did, &car_bytes, uri.collection, uri.rkey)
+}
+
+/// The record as a JSON block under its URI and CID; the structured data is the record
+fn record_result(
+    did: &str,
+    car_bytes: &[u8],
+    collection: &str,
+    rkey: &str,
+) -> Result<ToolResult, AppError> {
+    let record = read_record(did, car_bytes, collection, rkey)?;
+    let markdown = format!(
+        "# {}/{}\n\n{}\ncid: {}\n{}",
+        collection,
+        rkey,
+        record.uri,
+        record.cid,
+        record_json_block(&record)?
+    );
+    Ok(ToolResult::text(markdown).with_data(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::test_support::{synthetic_repo_records_car, test_cid, Cbor};
+    use serde_json::json;
+
+    const ALICE: &str = "did:plc:alice";
+
+    fn repo() -> Vec<u8> {
+        let like = Cbor::Map(vec![
+            ("$type", Cbor::Text("app.bsky.feed.like".to_string())),
+            (
+                "subject",
+                Cbor::Map(vec![
+                    ("cid", Cbor::Text("bafypost".to_string())),
+                    ("uri", Cbor::Text("at://did:plc:bob/app.bsky.feed.post/3kp".to_string())),
+                ]),
+            ),
+            ("createdAt", Cbor::Text("2025-01-01T00:00:00Z".to_string())),
+        ]);
+        let profile = Cbor::Map(vec![
+            ("$type", Cbor::Text("app.bsky.actor.profile".to_string())),
+            ("displayName", Cbor::Text("Alice".to_string())),
+            (
+                "avatar",
+                Cbor::Map(vec![
+                    ("$type", Cbor::Text("blob".to_string())),
+                    ("ref", Cbor::Link(test_cid(7))),
+                    ("size", Cbor::Int(1024)),
+                ]),
+            ),
+        ]);
+        synthetic_repo_records_car(
+            Some(ALICE),
+            vec![
+                ("app.bsky.actor.profile", "self", profile),
+                ("app.bsky.feed.like", "3klike", like),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_record_args_parsing() {
+        let args = json!({ "uri": "at://did:plc:alice/app.bsky.feed.like/3klike" });
+        let parsed: RecordArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.uri, "at://did:plc:alice/app.bsky.feed.like/3klike");
+    }
+
+    #[test]
+    fn test_record_round_trips_to_json() {
+        let car = repo();
+
+        let result = record_result(ALICE, &car, "app.bsky.feed.like", "3klike").unwrap();
+        let data = result.data_json().unwrap();
+        assert_eq!(data["uri"], "at://did:plc:alice/app.bsky.feed.like/3klike");
+        assert_eq!(
+            data["value"],
+            json!({
+                "$type": "app.bsky.feed.like",
+                "subject": {
+                    "cid": "bafypost",
+                    "uri": "at://did:plc:bob/app.bsky.feed.post/3kp"
+                },
+                "createdAt": "2025-01-01T00:00:00Z"
+            })
+        );
+        let text = &result.content[0].text;
+        assert!(text.starts_with(
+            "# app.bsky.feed.like/3klike\n\nat://did:plc:alice/app.bsky.feed.like/3klike\ncid: "
+        ));
+        assert!(text.contains("\n```json\n{\n  \"$type\": \"app.bsky.feed.like\",\n"), "{}", text);
+
+        // Links and integers keep their AT Protocol JSON form
+        let profile = record_result(ALICE, &car, "app.bsky.actor.profile", "self").unwrap();
+        let avatar = &profile.data_json().unwrap()["value"]["avatar"];
+        assert_eq!(avatar["size"], 1024);
+        assert!(avatar["ref"]["$link"].as_str().unwrap().starts_with("bafy"), "{}", avatar);
+    }
+
+    #[test]
+    fn test_missing_record_is_not_found() {
+        let car = repo();
+        for (collection, rkey) in [("app.bsky.feed.like", "3kother"), ("app.bsky.feed.post", "x")] {
+            assert!(matches!(
+                record_result(ALICE, &car, collection, rkey),
+                Err(AppError::NotFound(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collection_uri_is_rejected() {
+        for uri in [
+            "at://did:plc:alice/app.bsky.feed.like",
+            "at://did:plc:alice/app.bsky.feed.like/",
+            "did:plc:alice/app.bsky.feed.like/3klike",
+        ] {
+            let args = RecordArgs { uri: uri.to_string() };
+            let err = execute_record(args).await.unwrap_err();
+            assert!(matches!(err, AppError::InvalidInput(_)), "{}: {:?}", uri, err);
+        }
+    }
+}
@@ -2,21 +2,42 @@
 //!
 //! Implements the `search(from, query)` MCP tool
 
-use crate::bluesky::did::DidResolver;
-use crate::bluesky::provider::RepositoryProvider;
+use crate::bluesky::blob_url::did_from_at_uri;
+use crate::bluesky::did::display_handle;
+use crate::bluesky::firehose::replay_capture;
+use crate::bluesky::mst::blob_ref_cid;
+use crate::bluesky::provider::{configured_provider, RepositoryProvider, GET_POSTS_MAX_URIS};
 use crate::bluesky::records::{Facet, FacetFeature, FacetIndex, PostRecord};
+use crate::bluesky::records::{FeedGeneratorRecord, ListRecord};
 use crate::bluesky::records::{Embed, ImageEmbed, ExternalEmbed, RecordEmbed, BlobRef};
+use crate::car::{CarError, CarRecords};
 use crate::car::cbor::{decode_cbor, get_array_field, get_int_field, get_map_field, get_text_field, CborValue};
-use crate::cli::SearchArgs;
+use crate::cli::{OutputFormat, SearchArgs};
 use crate::error::{normalize_text, validate_account, validate_query, AppError};
-use crate::mcp::{McpResponse, ToolResult};
-use crate::search::SearchEngine;
+use crate::mcp::{report_progress, McpResponse, Progress, ToolResult};
+use crate::tools::date_range::DateRange;
+use crate::tools::formatter::{
+    formatter_for, CurationItem, OutputFormatter, PostItem, SearchFormatOptions,
+};
+use crate::tools::list::{RepoCurations, CURATION_COLLECTIONS};
+use crate::tools::search_global::search_global;
+use crate::tools::post_format::highlight_ranges;
+use crate::search::ranking::{
+    clamp_weight, DEFAULT_SEMANTIC_WEIGHT, EXACT_BONUS_RANGE, PREFIX_BONUS_RANGE,
+    RECENCY_WEIGHT_RANGE, SEMANTIC_CANDIDATES, SEMANTIC_WEIGHT_RANGE,
+};
+use crate::search::timestamp::parse_timestamp;
+use crate::search::{MatchOptions, QueryParser, ScoringWeights, SearchEngine, SearchResult};
 use anyhow::Result;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use serde::Serialize;
 use serde_json::Value;
 use tokio::time::{timeout, Duration};
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Extract facets from CBOR map (Vec of tuples)
 fn extract_facets(post_map: &[(CborValue, CborValue)]) -> Vec<Facet> {
@@ -152,7 +173,11 @@ fn parse_external_embed(external_map: &[(CborValue, CborValue)]) -> Option<Exter
 fn parse_record_embed(record_map: &[(CborValue, CborValue)]) -> Option<RecordEmbed> {
     let uri = get_text_field(record_map, "uri")?.to_string();
     let cid = get_text_field(record_map, "cid")?.to_string();
-    Some(RecordEmbed { uri, cid })
+    Some(RecordEmbed {
+        uri,
+        cid,
+        quoted: None,
+    })
 }
 
 /// Parse a BlobRef from a CBOR map
@@ -160,17 +185,11 @@ fn parse_blob_ref(blob_map: &[(CborValue, CborValue)]) -> Option<BlobRef> {
     let type_ = get_text_field(blob_map, "$type")?.to_string();
     let mime_type = get_text_field(blob_map, "mimeType")?.to_string();
     let size = get_int_field(blob_map, "size")? as u64;
-    // The 'ref' can be a map with a '$link' key
-    let ref_val = blob_map
+    // The 'ref' is a CID link in repo records, or a map with a '$link' key or a string
+    let ref_ = blob_map
         .iter()
         .find(|(k, _)| k == &CborValue::Text("ref"))
-        .map(|(_, v)| v);
-
-    let ref_ = match ref_val {
-        Some(CborValue::Map(ref_map)) => get_text_field(ref_map, "$link").map(|s| s.to_string()),
-        Some(CborValue::Text(s)) => Some(s.to_string()),
-        _ => None,
-    }?;
+        .and_then(|(_, v)| blob_ref_cid(v))?;
 
     Some(BlobRef {
         type_,
@@ -180,132 +199,364 @@ fn parse_blob_ref(blob_map: &[(CborValue, CborValue)]) -> Option<BlobRef> {
     })
 }
 
-/// Format search results into markdown for display (used by tests and CLI)
-pub fn format_search_results(posts: &[&PostRecord], handle: &str, query: &str) -> String {
-    // Highlighter that splits query into words, finds all matches (case-insensitive),
-    // merges adjacent/overlapping match ranges and wraps each merged range in **bold**.
-    fn highlight(text: &str, query: &str) -> String {
-        if query.is_empty() {
-            return text.to_string();
-        }
+/// Order in which search results are listed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchOrder {
+    /// Best match first
+    #[default]
+    Relevance,
+    /// Most recently created first
+    Newest,
+    /// Least recently created first
+    Oldest,
+}
 
-        let lower = text.to_lowercase();
-        let mut ranges: Vec<(usize, usize)> = Vec::new();
+impl SearchOrder {
+    /// Parse the `order` argument; `None` means relevance
+    pub fn parse(value: Option<&str>) -> Result<Self, AppError> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("relevance") => Ok(Self::Relevance),
+            Some("newest") => Ok(Self::Newest),
+            Some("oldest") => Ok(Self::Oldest),
+            Some(other) => Err(AppError::InvalidInput(format!(
+                "Invalid order '{}': expected relevance, newest or oldest",
+                other
+            ))),
+        }
+    }
 
-        for term in query.split_whitespace() {
-            if term.is_empty() {
-                continue;
-            }
-            let term_l = term.to_lowercase();
-            let mut idx = 0usize;
-            while let Some(pos) = lower[idx..].find(&term_l) {
-                let abs = idx + pos;
-                // term.len() is byte length which aligns with positions returned by find on the
-                // lowercased string (both are byte indices), so this is safe for UTF-8 slices
-                // as long as we use the same string for slicing the original `text`.
-                ranges.push((abs, abs + term.len()));
-                idx = abs + term.len();
+    /// Reorder already-limited results by creation date.
+    ///
+    /// Posts whose `created_at` cannot be parsed keep their relative order at the end.
+    pub fn sort_posts(self, posts: &mut [&PostRecord]) {
+        let newest_first = match self {
+            Self::Relevance => return,
+            Self::Newest => true,
+            Self::Oldest => false,
+        };
+        posts.sort_by(|a, b| {
+            match (parse_timestamp(&a.created_at), parse_timestamp(&b.created_at)) {
+                (Some(a), Some(b)) if newest_first => b.cmp(&a),
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
             }
-        }
+        });
+    }
+}
 
-        if ranges.is_empty() {
-            return text.to_string();
+/// How repo search ranks its matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Fuzzy text relevance
+    #[default]
+    Fuzzy,
+    /// Fuzzy matches re-ranked by embedding similarity to the query
+    Semantic,
+}
+
+impl SearchMode {
+    /// Parse the `mode` argument; `None` means fuzzy
+    pub fn parse(value: Option<&str>) -> Result<Self, AppError> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("fuzzy") => Ok(Self::Fuzzy),
+            Some("semantic") => Ok(Self::Semantic),
+            Some(other) => Err(AppError::InvalidInput(format!(
+                "Invalid mode '{}': expected fuzzy or semantic",
+                other
+            ))),
         }
+    }
+}
 
-        // Sort and merge ranges (merge adjacent and overlapping ranges)
-        ranges.sort_by_key(|r| r.0);
-        let mut merged: Vec<(usize, usize)> = Vec::new();
-        for (s, e) in ranges {
-            if let Some(last) = merged.last_mut() {
-                if s <= last.1 {
-                    // Overlap or adjacent: extend the last range
-                    if e > last.1 {
-                        last.1 = e;
-                    }
-                } else {
-                    // Allow merging across a single newline (soft line break) so that
-                    // emphasis can span across lines inside a paragraph. Do NOT merge
-                    // across spaces or multiple newlines (paragraph breaks).
-                    if s == last.1 + 1 && text.as_bytes()[last.1] == b'\n' {
-                        // extend across the single newline
-                        if e > last.1 {
-                            last.1 = e;
-                        }
-                    } else {
-                        merged.push((s, e));
-                    }
-                }
-            } else {
-                merged.push((s, e));
-            }
+/// Where a search looks for posts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Repos downloaded or read from disk, ranked locally
+    #[default]
+    Repo,
+    /// The whole network, through app.bsky.feed.searchPosts
+    Global,
+}
+
+impl SearchScope {
+    /// Parse the `scope` argument; `None` means repo
+    pub fn parse(value: Option<&str>) -> Result<Self, AppError> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("repo") => Ok(Self::Repo),
+            Some("global") => Ok(Self::Global),
+            Some(other) => Err(AppError::InvalidInput(format!(
+                "Invalid scope '{}': expected repo or global",
+                other
+            ))),
         }
+    }
+}
 
-        // Build highlighted string from merged ranges
-        let mut res = String::new();
-        let mut last_idx = 0usize;
-        for (s, e) in merged {
-            if last_idx < s {
-                res.push_str(&text[last_idx..s]);
-            }
-            res.push_str("**");
-            res.push_str(&text[s..e]);
-            res.push_str("**");
-            last_idx = e;
+/// Everything besides account and query that shapes a search run
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Maximum number of results (defaults to 50)
+    pub limit: Option<usize>,
+    /// Number of ranked results to skip, for paging
+    pub offset: usize,
+    /// Result ordering, applied after the limit
+    pub order: SearchOrder,
+    /// Markdown rendering options
+    pub format: SearchFormatOptions,
+    /// Relevance weights, already clamped into their documented ranges
+    pub weights: ScoringWeights,
+    /// Matches scoring below this are dropped before the limit applies
+    pub min_score: Option<f64>,
+    /// Share of embedding similarity when re-ranking semantically; `None` ranks fuzzily
+    pub semantic_weight: Option<f64>,
+}
+
+impl SearchOptions {
+    /// Build search options from tool arguments, clamping weight overrides
+    pub fn from_args(args: &SearchArgs) -> Result<Self, AppError> {
+        let mut weights = ScoringWeights::default();
+        if let Some(v) = args.exactBonus {
+            weights.exact_match_bonus =
+                clamp_weight(v, &EXACT_BONUS_RANGE, weights.exact_match_bonus);
+        }
+        if let Some(v) = args.prefixBonus {
+            weights.position_multipliers.word_start =
+                clamp_weight(v, &PREFIX_BONUS_RANGE, weights.position_multipliers.word_start);
         }
-        if last_idx < text.len() {
-            res.push_str(&text[last_idx..]);
+        if let Some(v) = args.recencyWeight {
+            weights.recency_weight = clamp_weight(v, &RECENCY_WEIGHT_RANGE, weights.recency_weight);
         }
 
-        res
+        let min_score = match args.minScore {
+            Some(v) if !v.is_finite() || v < 0.0 => {
+                return Err(AppError::InvalidInput(format!(
+                    "minScore must be a non-negative number, got {}",
+                    v
+                )));
+            }
+            v => v.map(f64::from),
+        };
+
+        let semantic_weight = match SearchMode::parse(args.mode.as_deref())? {
+            SearchMode::Fuzzy => None,
+            SearchMode::Semantic if cfg!(feature = "experimental-sentencepiece") => {
+                Some(args.semanticWeight.map_or(DEFAULT_SEMANTIC_WEIGHT, |v| {
+                    clamp_weight(v, &SEMANTIC_WEIGHT_RANGE, DEFAULT_SEMANTIC_WEIGHT)
+                }))
+            }
+            SearchMode::Semantic => {
+                warn!("Semantic search needs the experimental-sentencepiece feature; using fuzzy");
+                None
+            }
+        };
+
+        Ok(Self {
+            limit: args.limit,
+            offset: args.offset.unwrap_or(0),
+            min_score,
+            semantic_weight,
+            order: SearchOrder::parse(args.order.as_deref())?,
+            format: SearchFormatOptions::from_args(args),
+            weights,
+        })
     }
+}
 
-    let mut md = String::new();
-    md.push_str(&format!("# Search Results · {} posts\n\n", posts.len()));
+/// Most accounts one search call may cover, bounding repo downloads
+pub const MAX_SEARCH_ACCOUNTS: usize = 10;
 
-    for post in posts {
-        // Extract post id
-        let post_id = post.uri.split('/').last().unwrap_or(&post.uri);
-        md.push_str(&format!("@{}/{}\n\n", handle, post_id));
+/// Posts parsed between progress notifications while streaming repo records
+const PROGRESS_INTERVAL_POSTS: usize = 1000;
 
-        // Quote highlighted text
-        md.push_str(&format!("> {}\n\n", highlight(&post.text, query)));
+/// Post blocks read from a CAR before they are decoded together across threads
+const DECODE_BATCH_POSTS: usize = 2048;
 
-        md.push_str(&format!("{}\n\n", post.created_at));
+/// Batches smaller than this are decoded on the calling thread
+const PARALLEL_DECODE_MIN_POSTS: usize = 256;
 
-        // Links from external embeds and facets
-        if let Some(embeds) = &post.embeds {
-            for embed in embeds {
-                match embed {
-                    Embed::External { external } => {
-                        md.push_str(&format!("- [{}]({})\n", external.title, external.uri));
-                    }
-                    Embed::Images { images } => {
-                        for img in images {
-                            let alt = img.alt.as_deref().unwrap_or("Image");
-                            let alt_h = highlight(alt, query);
-                            // Build CDN URL from BlobRef. Use mime subtype as extension
-                            let ext = img.image.mime_type.split('/').nth(1).unwrap_or("jpeg");
-                            let url = format!(
-                                "https://cdn.bsky.app/img/feed_fullsize/plain/{}@{}",
-                                img.image.ref_, ext
-                            );
-                            md.push_str(&format!("![{}]({})\n", alt_h, url));
-                        }
-                    }
-                    _ => {}
-                }
-            }
+/// Most quoted posts looked up per search (two getPosts calls)
+const MAX_QUOTE_LOOKUPS: usize = 2 * GET_POSTS_MAX_URIS;
+
+/// Display handle of each searched account, used to prefix results with `@handle/`
+#[derive(Debug, Clone, Default)]
+pub struct AccountHandles {
+    /// Handle shown for posts whose repo DID is not registered
+    fallback: Option<String>,
+    by_did: HashMap<String, String>,
+}
+
+impl AccountHandles {
+    /// Show `handle` for every result, as for a single-account search
+    pub fn single(handle: &str) -> Self {
+        Self {
+            fallback: Some(handle.to_string()),
+            by_did: HashMap::new(),
         }
+    }
+
+    /// Show `handle` for results from the repo of `did`
+    pub fn insert(&mut self, did: &str, handle: &str) {
+        self.by_did.insert(did.to_string(), handle.to_string());
+    }
+
+    /// Handle for the repo a post URI belongs to, falling back to the DID itself
+    pub fn handle_for<'a>(&'a self, uri: &'a str) -> &'a str {
+        let did = did_from_at_uri(uri);
+        did.and_then(|did| self.by_did.get(did))
+            .or(self.fallback.as_ref())
+            .map(String::as_str)
+            .or(did)
+            .unwrap_or("")
+    }
+}
+
+impl From<&str> for AccountHandles {
+    fn from(handle: &str) -> Self {
+        Self::single(handle)
+    }
+}
+
+/// Split the `from` argument into distinct accounts (comma-separated), validating each
+pub fn parse_accounts(from: &str) -> Result<Vec<String>, AppError> {
+    let mut accounts: Vec<String> = Vec::new();
+    for account in from.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+        validate_account(account)?;
+        if !accounts.iter().any(|a| a == account) {
+            accounts.push(account.to_string());
+        }
+    }
+    if accounts.is_empty() {
+        validate_account("")?;
+    }
+    if accounts.len() > MAX_SEARCH_ACCOUNTS {
+        return Err(AppError::InvalidInput(format!(
+            "Too many accounts: {} (at most {} per search)",
+            accounts.len(),
+            MAX_SEARCH_ACCOUNTS
+        )));
+    }
+    Ok(accounts)
+}
+
+/// Format search results into markdown with default rendering options
+pub fn format_search_results(posts: &[&PostRecord], handle: &str, query: &str) -> String {
+    format_search_page(posts, &handle.into(), query, &SearchFormatOptions::default()).0
+}
+
+/// Highlighted byte ranges of one post's text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostMatch {
+    /// `at://` URI of the post
+    pub uri: String,
+    /// Byte ranges into the post text, sorted, merged and on char boundaries
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// Compute the ranges `format_search_results` bolds in each post's text, one entry per
+/// post in input order, for callers that render highlights themselves instead of parsing
+/// markdown.
+pub fn search_matches(posts: &[&PostRecord], query: &str) -> Vec<PostMatch> {
+    search_matches_with_case(posts, query, false)
+}
 
-        md.push_str("\n---\n\n");
+/// Like `search_matches`, optionally matching terms with their original casing
+pub fn search_matches_with_case(
+    posts: &[&PostRecord],
+    query: &str,
+    case_sensitive: bool,
+) -> Vec<PostMatch> {
+    let terms = QueryParser::parse_with_case(query, case_sensitive).highlight_terms();
+    posts
+        .iter()
+        .map(|post| PostMatch {
+            uri: post.uri.clone(),
+            ranges: highlight_ranges(&post.text, &terms, case_sensitive),
+        })
+        .collect()
+}
+
+/// Format search results into markdown, without the count of posts shown
+#[cfg(test)]
+pub fn format_search_results_with_options(
+    posts: &[&PostRecord],
+    handles: &AccountHandles,
+    query: &str,
+    options: &SearchFormatOptions,
+) -> String {
+    format_search_page(posts, handles, query, options).0
+}
+
+/// Format search results as `options.output` for display (used by MCP and CLI), returning
+/// the text and how many posts it shows. Past `options.max_chars`, posts are dropped whole
+/// from the end so a blockquote is never cut; the first post is always kept.
+pub fn format_search_page(
+    posts: &[&PostRecord],
+    handles: &AccountHandles,
+    query: &str,
+    options: &SearchFormatOptions,
+) -> (String, usize) {
+    format_search_page_with(formatter_for(options.output), posts, handles, query, options)
+}
+
+/// `format_search_page` rendering through `formatter`; the character budget applies to
+/// whatever the formatter produces
+pub fn format_search_page_with(
+    formatter: &dyn OutputFormatter,
+    posts: &[&PostRecord],
+    handles: &AccountHandles,
+    query: &str,
+    options: &SearchFormatOptions,
+) -> (String, usize) {
+    let terms = QueryParser::parse_with_case(query, options.case_sensitive).highlight_terms();
+    let matches = search_matches_with_case(posts, query, options.case_sensitive);
+
+    let blocks: Vec<String> = posts
+        .iter()
+        .zip(&matches)
+        .map(|(post, post_match)| {
+            let item = PostItem {
+                post,
+                handle: handles.handle_for(&post.uri),
+                highlights: &post_match.ranges,
+                terms: &terms,
+            };
+            formatter.post(&item, options)
+        })
+        .collect();
+
+    let budget = options.max_chars.unwrap_or(usize::MAX);
+    let heading = |shown: usize| formatter.results_heading("Search Results", shown, "posts");
+    let mut used = 0;
+    let mut shown = 0;
+    for block in &blocks {
+        let size = block.chars().count();
+        let total = heading(shown + 1).chars().count() + used + size;
+        if shown > 0 && total > budget {
+            break;
+        }
+        used += size;
+        shown += 1;
     }
 
-    md
+    let mut md = heading(shown);
+    for block in &blocks[..shown] {
+        md.push_str(block);
+    }
+    if shown < blocks.len() {
+        md.push_str(&formatter.results_omitted(blocks.len() - shown));
+    }
+    (md, shown)
 }
 
 /// Handle search tool call (MCP)
-pub async fn handle_search(id: Option<Value>, args: Value) -> McpResponse {
-    match timeout(Duration::from_secs(120), handle_search_impl(args)).await {
+pub async fn handle_search(
+    id: Option<Value>,
+    args: Value,
+    progress: Option<&Progress>,
+) -> McpResponse {
+    match timeout(Duration::from_secs(120), handle_search_impl(args, progress)).await {
         Ok(result) => match result {
             Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
             Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
@@ -314,20 +565,60 @@ pub async fn handle_search(id: Option<Value>, args: Value) -> McpResponse {
     }
 }
 
-async fn handle_search_impl(args: Value) -> Result<ToolResult, AppError> {
+async fn handle_search_impl(
+    args: Value,
+    progress: Option<&Progress>,
+) -> Result<ToolResult, AppError> {
     let search_args: SearchArgs = serde_json::from_value(args)
         .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
 
-    execute_search(search_args).await
+    let provider = configured_provider()?;
+    execute_search_with_progress(search_args, OutputFormat::Markdown, &provider, progress).await
+}
+
+/// Shared implementation for search (used by MCP and CLI), rendering results as `format`.
+/// Accounts are resolved, and their repos fetched and cached, as `provider` is configured;
+/// CAR files and replays are read without it.
+pub async fn execute_search(
+    search_args: SearchArgs,
+    format: OutputFormat,
+    provider: &RepositoryProvider,
+) -> Result<ToolResult, AppError> {
+    execute_search_with_progress(search_args, format, provider, None).await
 }
 
-/// Shared implementation for search (used by MCP and CLI)
-pub async fn execute_search(search_args: SearchArgs) -> Result<ToolResult, AppError> {
+/// Search, reporting each loaded repo and every `PROGRESS_INTERVAL_POSTS` parsed posts
+pub async fn execute_search_with_progress(
+    search_args: SearchArgs,
+    format: OutputFormat,
+    provider: &RepositoryProvider,
+    progress: Option<&Progress>,
+) -> Result<ToolResult, AppError> {
     // Validate inputs
-    validate_account(&search_args.from)?;
+    let sources = [
+        !search_args.from.trim().is_empty(),
+        !search_args.car.is_empty(),
+        search_args.replay.is_some(),
+    ];
+    if sources.iter().filter(|given| **given).count() > 1 {
+        return Err(AppError::InvalidInput(
+            "Use only one of from, car or replay".to_string(),
+        ));
+    }
+    let scope = SearchScope::parse(search_args.scope.as_deref())?;
+    let accounts = if scope == SearchScope::Global {
+        Vec::new()
+    } else if search_args.car.is_empty() && search_args.replay.is_none() {
+        parse_accounts(&search_args.from)?
+    } else {
+        Vec::new()
+    };
     validate_query(&search_args.query)?;
 
-    debug!("Search request for account: {}, query: '{}'", search_args.from, search_args.query);
+    debug!(
+        "Search request for account(s): {}, CAR file(s): {:?}, replay: {:?}, query: '{}'",
+        search_args.from, search_args.car, search_args.replay, search_args.query
+    );
 
     // Normalize query as specified
     let normalized_query = normalize_text(&search_args.query);
@@ -337,67 +628,603 @@ pub async fn execute_search(search_args: SearchArgs) -> Result<ToolResult, AppEr
         ));
     }
 
-    // Resolve handle to DID
-    let resolver = DidResolver::new();
-    let did = resolver.resolve_handle(&search_args.from).await?;
+    let date_range = DateRange::from_args(
+        search_args.since.as_deref(),
+        search_args.until.as_deref(),
+        search_args.sinceDays,
+        search_args.untilDays,
+        chrono::Utc::now(),
+    )?;
+    let mut options = SearchOptions::from_args(&search_args)?;
+    options.format.output = format;
+
+    if scope == SearchScope::Global {
+        let retry = provider.retry_policy();
+        return search_global(&search_args, &date_range, &options, retry, progress).await;
+    }
+    if let Some(path) = &search_args.replay {
+        return search_replay(path, &search_args.query, &date_range, &options).await;
+    }
 
-    // Determine display handle for markdown
-    let display_handle = if search_args.from.starts_with("did:plc:") {
-        // If input was a DID, use it verbatim
-        search_args.from.clone()
+    // Load every repo; one account or file failing does not abort the others
+    let mut handles = AccountHandles::default();
+    let mut repos = Vec::new();
+    let mut failures: Vec<(String, AppError)> = Vec::new();
+    if search_args.car.is_empty() {
+        if accounts.len() > 1 {
+            // Resolve all handles up front in one concurrent pass; the loads below hit the cache
+            let refs: Vec<&str> = accounts.iter().map(String::as_str).collect();
+            provider.resolver().resolve_handles(&refs).await;
+        }
+        for account in &accounts {
+            match load_account_repo(provider, account).await {
+                Ok(repo) => {
+                    report_progress(progress, || format!("Fetched repository for {}", account));
+                    add_repo(&mut repos, &mut handles, repo, &display_handle(account))
+                }
+                Err(e) => {
+                    debug!("Skipping account {}: {}", account, e.message());
+                    failures.push((account.clone(), e));
+                }
+            }
+        }
     } else {
-        search_args
-            .from
-            .strip_prefix('@')
-            .unwrap_or(&search_args.from)
-            .to_string()
-    };
+        for path in &search_args.car {
+            match load_car_repo(path).await {
+                Ok(repo) => {
+                    report_progress(progress, || format!("Read repository {}", path));
+                    // Offline there is no handle to show, so attribute posts by DID
+                    let did = repo.did.clone();
+                    add_repo(&mut repos, &mut handles, repo, &did);
+                }
+                Err(e) => {
+                    debug!("Skipping CAR file {}: {}", path, e.message());
+                    failures.push((path.clone(), e));
+                }
+            }
+        }
+    }
+    if repos.is_empty() {
+        // Every source failed: surface the first error as a single-source search would
+        return Err(failures.remove(0).1);
+    }
+    if accounts.len() == 1 {
+        handles = AccountHandles::single(&display_handle(&accounts[0]));
+    }
 
-    let did_str = did
-        .as_ref()
-        .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+    let curations: Vec<RepoCurations> = repos
+        .iter_mut()
+        .map(|repo| std::mem::take(&mut repo.curations))
+        .collect();
+
+    let quote_provider = search_args.quotes.then_some(provider);
+
+    // Decoding and ranking are CPU-bound, so they run on a blocking thread. If this future
+    // is dropped (cancelled or timed out) the guard raises `cancelled`, and the thread
+    // stops at the next batch of posts.
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+    let ranking = {
+        let query = search_args.query.clone();
+        let options = options.clone();
+        let progress = progress.cloned();
+        tokio::task::spawn_blocking(move || {
+            // Stream records, decoding each CBOR entry into a PostRecord on demand
+            let mut parsed = 0usize;
+            let skipped = Cell::new(0usize);
+            let posts = repos
+                .into_iter()
+                .flat_map(|repo| repo.into_posts(&skipped, &cancelled))
+                .inspect(|_| {
+                    parsed += 1;
+                    if parsed.is_multiple_of(PROGRESS_INTERVAL_POSTS) {
+                        report_progress(progress.as_ref(), || format!("Parsed {} posts", parsed));
+                    }
+                })
+                .filter(|post| date_range.contains(&post.created_at));
 
-    // Fetch CAR and extract CID->rkey mapping to reconstruct post rkeys
-    let provider = RepositoryProvider::new()?;
-    let car_path = provider.fetch_repo_car(did_str).await?;
-    let car_bytes = tokio::fs::read(&car_path)
+            // Use fuzzy search engine, keeping only the top results in memory
+            let page = rank_post_stream(posts, &query, &options);
+            (page, parsed, skipped.get())
+        })
+    };
+    let (page, parsed, skipped) = ranking
         .await
-        .map_err(|e| AppError::CacheError(format!("Failed to read CAR file: {}", e)))?;
+        .map_err(|e| AppError::Internal(format!("Search stopped: {}", e)))?;
+
+    // Look up the posts quoted on the page shown
+    let result = match page {
+        Ok(mut page) => {
+            if let Some(provider) = quote_provider {
+                hydrate_quotes(provider, &mut page.posts).await;
+            }
+            Ok(page.render(&handles, &search_args.query, &options))
+        }
+        Err(e) => Err(e),
+    };
+    report_progress(progress, || format!("Searched {} posts", parsed));
+    // Matching lists and feeds are listed once, with the first page of posts
+    let result = if options.offset == 0 {
+        report_curation_matches(result, &curations, &search_args.query, options.format.output)
+    } else {
+        result
+    };
+    let result = report_skipped_records(result, skipped);
+    report_failed_accounts(result, &failures)
+}
+
+/// Raises its flag when dropped, telling blocking work that the future waiting on it is gone
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Attach each quoted post's author and text to the quote embeds of `posts`, one page of
+/// results, looking up at most `MAX_QUOTE_LOOKUPS` quoted posts, those of the newest
+/// quoting posts first. A failed lookup is logged and leaves the posts as they were.
+async fn hydrate_quotes(provider: &RepositoryProvider, posts: &mut [PostRecord]) {
+    let mut quoting: Vec<&PostRecord> = posts.iter().filter(|post| post.has_quote()).collect();
+    quoting.sort_by_key(|post| std::cmp::Reverse(parse_timestamp(&post.created_at)));
+    let mut uris: Vec<String> = Vec::new();
+    for record in quoting.into_iter().filter_map(PostRecord::quote_embed) {
+        if uris.len() == MAX_QUOTE_LOOKUPS {
+            break;
+        }
+        if record.uri.starts_with("at://did:") && !uris.contains(&record.uri) {
+            uris.push(record.uri.clone());
+        }
+    }
+    if uris.is_empty() {
+        return;
+    }
+
+    let quoted = match provider.fetch_quoted_posts(&uris).await {
+        Ok(quoted) => quoted,
+        Err(e) => {
+            debug!("Failed to look up quoted posts: {}", e.message());
+            return;
+        }
+    };
+    for record in posts.iter_mut().filter_map(PostRecord::quote_embed_mut) {
+        if record.quoted.is_none() {
+            record.quoted = quoted.get(&record.uri).cloned();
+        }
+    }
+}
+
+/// Add the lists and feeds whose name or description holds every query term after the
+/// posts, rendered as `format`, or as the whole result when no post matched
+fn report_curation_matches(
+    result: Result<ToolResult, AppError>,
+    curations: &[RepoCurations],
+    query: &str,
+    format: OutputFormat,
+) -> Result<ToolResult, AppError> {
+    let terms = QueryParser::parse_with_case(query, false).highlight_terms();
+    let matches = |name: &str, description: Option<&str>| {
+        let text = normalize_text(&format!("{} {}", name, description.unwrap_or_default()))
+            .to_lowercase();
+        !terms.is_empty() && terms.iter().all(|term| text.contains(&term.to_lowercase()))
+    };
+    let lists: Vec<&ListRecord> = curations
+        .iter()
+        .flat_map(|c| &c.lists)
+        .filter(|list| matches(&list.name, list.description.as_deref()))
+        .collect();
+    let feeds: Vec<&FeedGeneratorRecord> = curations
+        .iter()
+        .flat_map(|c| &c.feeds)
+        .filter(|feed| matches(&feed.display_name, feed.description.as_deref()))
+        .collect();
+    if lists.is_empty() && feeds.is_empty() {
+        return result;
+    }
+
+    let formatter = formatter_for(format);
+    let mut section = formatter.section_heading("Lists and feeds", lists.len() + feeds.len());
+    for list in &lists {
+        let kind = format!("{} · {} members", list.purpose_label(), list.members.len());
+        section.push_str(&formatter.curation(&CurationItem {
+            name: &list.name,
+            kind: &kind,
+            uri: &list.uri,
+            description: list.description.as_deref(),
+        }));
+    }
+    for feed in &feeds {
+        section.push_str(&formatter.curation(&CurationItem {
+            name: &feed.display_name,
+            kind: "feed",
+            uri: &feed.uri,
+            description: feed.description.as_deref(),
+        }));
+    }
+
+    let mut tool_result = match result {
+        Ok(tool_result) => tool_result,
+        Err(AppError::NotFound(_)) => {
+            let data = SearchOutput { query: query.to_string(), ..SearchOutput::default() };
+            ToolResult::text(formatter.results_heading("Search Results", 0, "posts"))
+                .with_data(data)
+        }
+        Err(e) => return Err(e),
+    };
+    if let Some(item) = tool_result.content.first_mut() {
+        if !item.text.ends_with("\n\n") {
+            item.text.push('\n');
+        }
+        item.text.push_str(&section);
+    }
+    if let Some(data) = tool_result.data_mut::<SearchOutput>() {
+        data.lists = lists.into_iter().cloned().collect();
+        data.feeds = feeds.into_iter().cloned().collect();
+    }
+    Ok(tool_result)
+}
+
+/// A searched account's repo, ready to stream posts from
+struct AccountRepo {
+    did: String,
+    cid_to_rkey: HashMap<String, String>,
+    records: CarRecords,
+    /// Lists and feed generators, searched by name and description alongside the posts
+    curations: RepoCurations,
+}
 
-    debug!("Extracting CID->rkey mappings from MST for collection app.bsky.feed.post");
-    let cid_to_rkey = crate::bluesky::mst::extract_cid_to_rkey_mapping(&car_bytes, "app.bsky.feed.post")
+impl AccountRepo {
+    /// Map post CIDs to rkeys and prepare to stream the records of a repo CAR
+    fn from_car_bytes(did: String, car_bytes: Vec<u8>) -> Result<Self, AppError> {
+        debug!("Extracting CID->rkey mappings from MST for collection app.bsky.feed.post");
+        // Lists and feeds are read in the same walk as the post mappings
+        let (cid_to_rkey, curation_blocks) = crate::bluesky::mst::extract_mapping_and_blocks(
+            &car_bytes,
+            "app.bsky.feed.post",
+            &CURATION_COLLECTIONS,
+        )
         .map_err(|e| {
             AppError::RepoParseFailed(format!("Failed to extract MST mappings: {:?}", e))
         })?;
 
-    debug!("Extracted {} CID->rkey mappings", cid_to_rkey.len());
+        debug!("Extracted {} CID->rkey mappings for {}", cid_to_rkey.len(), did);
+        let curations = RepoCurations::from_blocks(&did, &curation_blocks);
+
+        let records = CarRecords::from_bytes(car_bytes)
+            .map_err(|e| {
+                AppError::RepoParseFailed(format!("Failed to create CAR iterator: {}", e))
+            })?
+            .with_undecodable(true);
+        Ok(Self {
+            did,
+            cid_to_rkey,
+            records,
+            curations,
+        })
+    }
 
-    // Stream records and collect posts with rkeys
-    let records = provider.records(did_str).await?;
+    /// Stream the repo's posts. Post blocks are read in batches of `DECODE_BATCH_POSTS`
+    /// and each batch is decoded in parallel, yielding posts in CAR order.
+    /// Records that fail to decode are left out and counted in `skipped`; the stream ends
+    /// early, before the next batch, once `cancelled` is raised.
+    fn into_posts<'a>(
+        self,
+        skipped: &'a Cell<usize>,
+        cancelled: &'a AtomicBool,
+    ) -> impl Iterator<Item = PostRecord> + 'a {
+        let AccountRepo {
+            did,
+            cid_to_rkey,
+            records,
+            ..
+        } = self;
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut blocks = records
+            .filter_map(|record_result| match record_result {
+                Ok(record) => Some(record),
+                Err(CarError::UndecodableBlock { cid, reason }) => {
+                    debug!("Skipping unparseable record {}: {}", cid, reason);
+                    skipped.set(skipped.get() + 1);
+                    None
+                }
+                Err(_) => None,
+            })
+            .filter(|(record_type, _, _)| record_type == "app.bsky.feed.post")
+            .map(|(_, cbor_data, cid_str)| (cbor_data, cid_str));
+        std::iter::from_fn(move || {
+            if cancelled.load(Ordering::Relaxed) {
+                debug!("Search of {} cancelled", did);
+                return None;
+            }
+            let batch: Vec<_> = blocks.by_ref().take(DECODE_BATCH_POSTS).collect();
+            if batch.is_empty() {
+                return None;
+            }
+            let (posts, failed) = decode_post_blocks(&did, &batch, &cid_to_rkey, workers);
+            skipped.set(skipped.get() + failed);
+            Some(posts)
+        })
+        .flatten()
+    }
+}
 
-    // Iterate records, decode each CBOR entry and build PostRecord directly
-    let mut posts: Vec<PostRecord> = Vec::new();
-    for record_result in records {
-        let (record_type, cbor_data, cid_str) = match record_result {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
+/// Decode one post block into a PostRecord; `Err` if the block is not a CBOR map.
+/// `Ok(None)` is a well-formed record that is not a usable post.
+fn decode_post_block(
+    did: &str,
+    cbor_data: &[u8],
+    cid_str: &str,
+    cid_to_rkey: &HashMap<String, String>,
+) -> Result<Option<PostRecord>, String> {
+    match decode_cbor(cbor_data) {
+        Ok(CborValue::Map(post_map)) => {
+            Ok(collect_post_from_map(did, post_map.as_slice(), cid_str, cid_to_rkey))
+        }
+        Ok(_) => Err("not a map".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Decode `(cbor, cid)` post blocks on up to `workers` threads, keeping their order.
+/// Also returns how many blocks failed to decode.
+fn decode_post_blocks(
+    did: &str,
+    blocks: &[(Vec<u8>, String)],
+    cid_to_rkey: &HashMap<String, String>,
+    workers: usize,
+) -> (Vec<PostRecord>, usize) {
+    let decode_all = |blocks: &[(Vec<u8>, String)]| -> (Vec<PostRecord>, usize) {
+        let mut failed = 0;
+        let posts = blocks
+            .iter()
+            .filter_map(|(cbor_data, cid_str)| {
+                match decode_post_block(did, cbor_data, cid_str, cid_to_rkey) {
+                    Ok(post) => post,
+                    Err(reason) => {
+                        debug!("Skipping unparseable post {} in {}: {}", cid_str, did, reason);
+                        failed += 1;
+                        None
+                    }
+                }
+            })
+            .collect();
+        (posts, failed)
+    };
+    if workers <= 1 || blocks.len() < PARALLEL_DECODE_MIN_POSTS {
+        return decode_all(blocks);
+    }
+
+    let chunk_len = blocks.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = blocks
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || decode_all(chunk)))
+            .collect();
+        let mut decoded = (Vec::with_capacity(blocks.len()), 0);
+        for handle in handles {
+            let (posts, failed) =
+                handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+            decoded.0.extend(posts);
+            decoded.1 += failed;
+        }
+        decoded
+    })
+}
+
+/// Every post in an account's repo with the account's DID, for tools that list posts
+/// rather than search them. What is cached is used first, however old: a DID already
+/// resolved for a handle and the repo CAR, so an unreachable network is asked for neither.
+pub(crate) async fn load_account_posts(
+    provider: &RepositoryProvider,
+    account: &str,
+) -> Result<(String, Vec<PostRecord>), AppError> {
+    let resolver = provider.resolver();
+    let did = match resolver.cached_did(account) {
+        Some(did) => did,
+        None => resolver
+            .resolve_handle(account)
+            .await?
+            .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?,
+    };
+    let car_bytes = match provider.cached_repo_bytes(&did).await {
+        Some(car_bytes) => car_bytes,
+        None => provider.fetch_repo_bytes(&did).await?,
+    };
+
+    let repo = AccountRepo::from_car_bytes(did, car_bytes)?;
+    let did = repo.did.clone();
+    let posts = repo.into_posts(&Cell::new(0), &AtomicBool::new(false)).collect();
+    Ok((did, posts))
+}
+
+/// Add a loaded repo unless the same DID was already loaded (e.g. a handle and its DID,
+/// or the same CAR given twice), so its posts are not ranked twice
+fn add_repo(
+    repos: &mut Vec<AccountRepo>,
+    handles: &mut AccountHandles,
+    repo: AccountRepo,
+    handle: &str,
+) {
+    if repos.iter().any(|r| r.did == repo.did) {
+        debug!("Repo {} already loaded, skipping duplicate", repo.did);
+        return;
+    }
+    handles.insert(&repo.did, handle);
+    repos.push(repo);
+}
+
+/// Resolve an account and fetch its repo CAR
+async fn load_account_repo(
+    provider: &RepositoryProvider,
+    account: &str,
+) -> Result<AccountRepo, AppError> {
+    let did = provider
+        .resolver()
+        .resolve_handle(account)
+        .await?
+        .ok_or_else(|| AppError::DidResolveFailed("DID resolution failed".to_string()))?;
+
+    let car_bytes = provider.fetch_repo_bytes(&did).await?;
+
+    AccountRepo::from_car_bytes(did, car_bytes)
+}
+
+/// Read a downloaded repo CAR, attributing it to the DID in its own commit
+async fn load_car_repo(path: &str) -> Result<AccountRepo, AppError> {
+    let car_bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| AppError::ConfigError(format!("Failed to read CAR file {}: {}", path, e)))?;
 
-        if record_type != "app.bsky.feed.post" {
-            continue;
+    let did = crate::bluesky::mst::extract_commit_did(&car_bytes).map_err(|e| {
+        AppError::RepoParseFailed(format!("Failed to read repo commit from {}: {}", path, e))
+    })?;
+
+    AccountRepo::from_car_bytes(did, car_bytes)
+}
+
+/// Search the posts left in place by replaying a firehose capture file
+async fn search_replay(
+    path: &str,
+    query: &str,
+    date_range: &DateRange,
+    options: &SearchOptions,
+) -> Result<ToolResult, AppError> {
+    let capture = tokio::fs::read(path).await.map_err(|e| {
+        AppError::ConfigError(format!("Failed to read capture file {}: {}", path, e))
+    })?;
+    let outcome = replay_capture(&capture);
+    debug!(
+        "Replayed {} frames from {} ({} skipped, {} duplicate ops), {} records remain",
+        outcome.frames,
+        path,
+        outcome.skipped_frames,
+        outcome.duplicates,
+        outcome.records.len()
+    );
+
+    let skipped = Cell::new(0usize);
+    let posts = outcome
+        .records
+        .iter()
+        .filter(|record| record.collection == "app.bsky.feed.post")
+        .filter_map(|record| {
+            let cid_to_rkey = HashMap::from([(record.cid.clone(), record.rkey.clone())]);
+            match decode_post_block(&record.did, &record.bytes, &record.cid, &cid_to_rkey) {
+                Ok(post) => post,
+                Err(reason) => {
+                    debug!("Skipping unparseable post {}: {}", record.cid, reason);
+                    skipped.set(skipped.get() + 1);
+                    None
+                }
+            }
+        })
+        .filter(|post| date_range.contains(&post.created_at));
+
+    // Posts are attributed by DID, as for CAR files
+    let result = run_search_on_post_stream(posts, &AccountHandles::default(), query, options);
+    let result = report_skipped_records(result, skipped.get());
+    let Some(stop) = &outcome.stopped_at else {
+        return result;
+    };
+    let note = format!(
+        "Replay stopped at byte {} of {}: {}",
+        stop.offset,
+        capture.len(),
+        stop.reason
+    );
+    match result {
+        Ok(mut tool_result) => {
+            if let Some(item) = tool_result.content.first_mut() {
+                insert_after_heading(&mut item.text, &format!("{}\n\n", note));
+            }
+            Ok(tool_result)
         }
+        Err(AppError::NotFound(message)) => {
+            Err(AppError::NotFound(format!("{}; {}", message, note)))
+        }
+        Err(e) => Err(e),
+    }
+}
 
-        if let Ok(CborValue::Map(post_map)) = decode_cbor(&cbor_data) {
-            if let Some(post) = collect_post_from_map(did_str, post_map.as_slice(), &cid_str, &cid_to_rkey) {
-                posts.push(post);
+/// Note accounts or CAR files that could not be searched below the results heading, or in the
+/// not-found message when nothing matched in the accounts that were searched
+fn report_failed_accounts(
+    result: Result<ToolResult, AppError>,
+    failures: &[(String, AppError)],
+) -> Result<ToolResult, AppError> {
+    if failures.is_empty() {
+        return result;
+    }
+    let summary = failures
+        .iter()
+        .map(|(account, e)| format!("{} ({})", account, e.message()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match result {
+        Ok(mut tool_result) => {
+            if let Some(item) = tool_result.content.first_mut() {
+                insert_after_heading(&mut item.text, &format!("Not searched: {}\n\n", summary));
             }
+            if let Some(data) = tool_result.data_mut::<SearchOutput>() {
+                data.not_searched = failures
+                    .iter()
+                    .map(|(account, e)| NotSearched {
+                        account: account.clone(),
+                        error: e.message(),
+                    })
+                    .collect();
+            }
+            Ok(tool_result)
         }
+        Err(AppError::NotFound(message)) => Err(AppError::NotFound(format!(
+            "{}; not searched: {}",
+            message, summary
+        ))),
+        Err(e) => Err(e),
     }
+}
 
-    debug!("Extracted {} post records with rkeys", posts.len());
+/// Close the results with how many records were left out because they failed to parse,
+/// or add it to the not-found message
+fn report_skipped_records(
+    result: Result<ToolResult, AppError>,
+    skipped: usize,
+) -> Result<ToolResult, AppError> {
+    if skipped == 0 {
+        return result;
+    }
+    let note = format!(
+        "{} {} skipped due to parse errors",
+        skipped,
+        if skipped == 1 { "record" } else { "records" }
+    );
+
+    match result {
+        Ok(mut tool_result) => {
+            if let Some(item) = tool_result.content.first_mut() {
+                if !item.text.ends_with("\n\n") {
+                    item.text.push('\n');
+                }
+                item.text.push_str(&format!("{}\n", note));
+            }
+            if let Some(data) = tool_result.data_mut::<SearchOutput>() {
+                data.skipped_records = Some(skipped);
+            }
+            Ok(tool_result)
+        }
+        Err(AppError::NotFound(message)) => {
+            Err(AppError::NotFound(format!("{}; {}", message, note)))
+        }
+        Err(e) => Err(e),
+    }
+}
 
-    // Use fuzzy search engine
-    run_search_on_posts(&posts, &display_handle, &search_args.query, search_args.limit).await
+/// Insert a line right below the `# Search Results` heading
+fn insert_after_heading(markdown: &mut String, text: &str) {
+    let insert_at = markdown.find("\n\n").map(|i| i + 2).unwrap_or(0);
+    markdown.insert_str(insert_at, text);
 }
 
 /// Construct PostRecord vector from decoded CBOR maps
@@ -416,10 +1243,14 @@ pub(crate) fn collect_post_from_map(
     let facets = extract_facets(post_map);
     let embeds = extract_embeds(post_map);
 
-    let collection_rkey = cid_to_rkey.get(cid_str)?.clone();
+    // MST mappings hold `collection/rkey` keys; firehose records give the bare rkey
+    let collection_rkey = cid_to_rkey.get(cid_str)?;
+    let rkey = collection_rkey
+        .strip_prefix("app.bsky.feed.post/")
+        .unwrap_or(collection_rkey);
 
     Some(PostRecord {
-        uri: format!("at://{}/app.bsky.feed.post/{}", did_str, collection_rkey),
+        uri: format!("at://{}/app.bsky.feed.post/{}", did_str, rkey),
         cid: cid_str.to_string(),
         text,
         created_at,
@@ -428,50 +1259,254 @@ pub(crate) fn collect_post_from_map(
     })
 }
 
-// Compatibility wrapper used by some tests: build posts from a decoded-records shape
-pub(crate) fn collect_posts_from_maps(
-    did_str: &str,
-    decoded: &[(String, Vec<(CborValue, CborValue)>, String)],
-    cid_to_rkey: &HashMap<String, String>,
-) -> Vec<PostRecord> {
-    let mut posts: Vec<PostRecord> = Vec::new();
-    for (_record_type, post_map, cid_str) in decoded.iter() {
-        if let Some(post) = collect_post_from_map(did_str, post_map.as_slice(), cid_str, cid_to_rkey) {
-            posts.push(post);
-        }
-    }
-    posts
-}
-
 /// Run search + formatting on an existing set of posts.
 /// Extracted into a helper to allow tests to call the search/format pipeline directly.
+#[cfg(test)]
 pub(crate) async fn run_search_on_posts(
     posts: &[PostRecord],
-    display_handle: &str,
+    handles: &AccountHandles,
     query: &str,
-    limit_opt: Option<usize>,
+    options: &SearchOptions,
 ) -> Result<ToolResult, AppError> {
-    let mut search_engine = SearchEngine::new();
-    let search_results = search_engine.search(query, posts, |post| post.get_searchable_text());
+    run_search_on_post_stream(posts.iter().cloned(), handles, query, options)
+}
+
+/// Blend meaning into the fuzzy order of `results`; a failed model load keeps the fuzzy order
+#[cfg(feature = "experimental-sentencepiece")]
+fn rerank_semantically(results: &mut Vec<SearchResult<PostRecord>>, query: &str, weight: f64) {
+    let text = QueryParser::parse(query).whole_query;
+    let reranked = crate::search::semantic::with_ranker(|ranker| {
+        ranker.rerank(results, &text, weight, |post| post.get_searchable_text())
+    });
+    if let Err(e) = reranked {
+        warn!("Semantic ranking unavailable, keeping fuzzy order: {}", e);
+    }
+}
+
+/// Builds without the models never request semantic ranking; see [`SearchOptions`]
+#[cfg(not(feature = "experimental-sentencepiece"))]
+fn rerank_semantically(_: &mut [SearchResult<PostRecord>], _: &str, _: f64) {}
+
+/// Run search + formatting over a stream of posts.
+///
+/// Only the best `limit` matches are held at once, so memory is O(limit + one record)
+/// rather than O(all posts). Filter elimination counts are tallied as posts stream by.
+pub(crate) fn run_search_on_post_stream<I>(
+    posts: I,
+    handles: &AccountHandles,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<ToolResult, AppError>
+where
+    I: IntoIterator<Item = PostRecord>,
+{
+    rank_post_stream(posts, query, options).map(|page| page.render(handles, query, options))
+}
 
-    let limit = limit_opt.unwrap_or(50usize);
-    let matching_posts: Vec<&PostRecord> = search_results.iter().take(limit).map(|r| &r.item).collect();
+/// One page of ranked matches, ready to render
+struct RankedPage {
+    /// The page's posts in rank order
+    posts: Vec<PostRecord>,
+    total_matches: usize,
+    /// How many posts each filter eliminated, shown below the heading
+    filter_summary: Option<String>,
+}
+
+impl RankedPage {
+    /// Render the page in the requested order
+    fn render(self, handles: &AccountHandles, query: &str, options: &SearchOptions) -> ToolResult {
+        let mut matching_posts: Vec<&PostRecord> = self.posts.iter().collect();
+        options.order.sort_posts(&mut matching_posts);
+        results_page(
+            matching_posts,
+            handles,
+            query,
+            options,
+            self.total_matches,
+            self.filter_summary,
+        )
+    }
+}
 
-    if matching_posts.is_empty() {
+/// Rank a stream of posts down to the page `options` asks for
+fn rank_post_stream<I>(
+    posts: I,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<RankedPage, AppError>
+where
+    I: IntoIterator<Item = PostRecord>,
+{
+    let filters = QueryParser::parse(query).filters;
+    let mut eliminated = vec![0usize; filters.len()];
+    let mut scanned = 0usize;
+    let posts = posts.into_iter().inspect(|post| {
+        scanned += 1;
+        for (count, filter) in eliminated.iter_mut().zip(&filters) {
+            if !filter.matches(post) {
+                *count += 1;
+            }
+        }
+    });
+
+    let now = chrono::Utc::now();
+    let weights = options.weights.clone();
+    let limit = options.limit.unwrap_or(50usize);
+    let mut total_matches = 0usize;
+    let mut search_engine = SearchEngine::with_weights(options.weights.clone())
+        .with_min_score(options.min_score)
+        .with_case_sensitive(options.format.case_sensitive);
+    // Rank through the end of the requested page; creation time and URI break score ties so
+    // pages are stable.
+    // Semantic re-ranking draws from a wider pool of fuzzy matches.
+    let page_end = options.offset.saturating_add(limit);
+    let candidates = match options.semantic_weight {
+        Some(_) => page_end.max(SEMANTIC_CANDIDATES),
+        None => page_end,
+    };
+    let match_options = MatchOptions::new(PostRecord::get_searchable_text)
+        .with_limit(candidates)
+        .with_filter(|post, filter| filter.matches(post))
+        .with_adjust(|result| {
+            total_matches += 1;
+            result
+                .score
+                .apply_recency(parse_timestamp(&result.item.created_at), now, &weights)
+        })
+        .with_tie_key(PostRecord::tie_key);
+    let mut search_results = search_engine.search(query, posts, match_options);
+    debug!(
+        "Scanned {} posts, {} matched, kept {} results",
+        scanned,
+        total_matches,
+        search_results.len()
+    );
+
+    if let Some(weight) = options.semantic_weight {
+        rerank_semantically(&mut search_results, query, weight);
+        search_results.truncate(page_end);
+    }
+
+    let posts: Vec<PostRecord> = search_results
+        .into_iter()
+        .skip(options.offset)
+        .map(|r| r.item)
+        .collect();
+
+    if posts.is_empty() {
+        if options.offset > 0 && total_matches > 0 {
+            return Err(AppError::NotFound(format!(
+                "No results at offset {}: query '{}' has {} matches",
+                options.offset, query, total_matches
+            )));
+        }
         return Err(AppError::NotFound(format!(
             "No posts found matching query '{}' for results",
             query
         )));
     }
 
-    let markdown = format_search_results(&matching_posts, display_handle, query);
-    Ok(ToolResult::text(markdown))
+    // Report how many posts each filter eliminated, right below the heading
+    let filter_summary = (!filters.is_empty()).then(|| {
+        filters
+            .iter()
+            .zip(&eliminated)
+            .map(|(filter, count)| format!("`{}` excluded {} posts", filter.to_query(), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    Ok(RankedPage {
+        posts,
+        total_matches,
+        filter_summary: filter_summary.map(|summary| format!("Filters: {}\n\n", summary)),
+    })
+}
+
+/// Render one page of ranked results with the next offset when more matches remain,
+/// and `note` (if any) right below the heading
+pub(crate) fn results_page(
+    mut matching_posts: Vec<&PostRecord>,
+    handles: &AccountHandles,
+    query: &str,
+    options: &SearchOptions,
+    total_matches: usize,
+    note: Option<String>,
+) -> ToolResult {
+    let (mut text, shown) = format_search_page(&matching_posts, handles, query, &options.format);
+    // Posts left out for the character budget are where the next page starts
+    matching_posts.truncate(shown);
+    let end = options.offset + shown;
+    let next_offset = (end < total_matches).then_some(end);
+
+    if let Some(note) = &note {
+        insert_after_heading(&mut text, note);
+    }
+    if let Some(next) = next_offset {
+        let formatter = formatter_for(options.format.output);
+        text.push_str(&formatter.next_page("Next offset", &next.to_string()));
+    }
+
+    let data = SearchOutput {
+        query: query.to_string(),
+        total_matches,
+        next_offset,
+        posts: matching_posts
+            .iter()
+            .map(|post| SearchHit {
+                handle: handles.handle_for(&post.uri).to_string(),
+                did: did_from_at_uri(&post.uri).unwrap_or_default().to_string(),
+                post: (*post).clone(),
+            })
+            .collect(),
+        ..SearchOutput::default()
+    };
+    let result = ToolResult::text(text).with_data(data);
+    match next_offset {
+        Some(next) => result.with_meta(serde_json::json!({ "nextOffset": next })),
+        None => result,
+    }
+}
+
+/// Structured search results, for `--format json`
+#[derive(Debug, Default, Serialize)]
+struct SearchOutput {
+    query: String,
+    #[serde(rename = "totalMatches")]
+    total_matches: usize,
+    #[serde(rename = "nextOffset", skip_serializing_if = "Option::is_none")]
+    next_offset: Option<usize>,
+    posts: Vec<SearchHit>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lists: Vec<ListRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    feeds: Vec<FeedGeneratorRecord>,
+    #[serde(rename = "notSearched", skip_serializing_if = "Vec::is_empty")]
+    not_searched: Vec<NotSearched>,
+    #[serde(rename = "skippedRecords", skip_serializing_if = "Option::is_none")]
+    skipped_records: Option<usize>,
+}
+
+/// One matching post with the account it was found in
+#[derive(Debug, Serialize)]
+struct SearchHit {
+    handle: String,
+    did: String,
+    #[serde(flatten)]
+    post: PostRecord,
+}
+
+/// An account left out of the search, with why
+#[derive(Debug, Serialize)]
+struct NotSearched {
+    account: String,
+    error: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bluesky::records::PostRecord;
+    use crate::bluesky::records::{PostRecord, QuotedPost};
+    use crate::car::test_support::{firehose_commit, synthetic_repo_car};
     use serde_json::json;
 
     #[tokio::test]
@@ -518,7 +1553,7 @@ mod tests {
         ];
 
         let mut engine = SearchEngine::new();
-        let results = engine.search("hello", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("hello", &posts, |p| p.get_searchable_text());
 
         assert_eq!(results.len(), 2);
         assert!(results.iter().any(|r| r.item.text.contains("Hello world")));
@@ -526,11 +1561,11 @@ mod tests {
             .iter()
             .any(|r| r.item.text.contains("Hello everyone")));
 
-        let results = engine.search("programming", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("programming", &posts, |p| p.get_searchable_text());
         assert_eq!(results.len(), 1);
         assert!(results[0].item.text.contains("programming"));
 
-        let results = engine.search("nonexistent", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("nonexistent", &posts, |p| p.get_searchable_text());
         assert_eq!(results.len(), 0);
     }
 
@@ -691,7 +1726,7 @@ mod tests {
 
         // 1. Test that search finds the post based on embed content
         let mut engine = SearchEngine::new();
-        let search_results = engine.search("fuzzy cat", &vec![post.clone()], |p| {
+        let search_results = engine.search_all("fuzzy cat", std::slice::from_ref(&post), |p| {
             p.get_searchable_text()
         });
 
@@ -751,11 +1786,132 @@ mod tests {
         assert!(!markdown.is_empty(), "Should produce output even for empty results");
     }
 
+    fn post_with_link_card(description: &str) -> PostRecord {
+        PostRecord {
+            uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
+            cid: "cid1".to_string(),
+            text: "Look at this".to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            embeds: Some(vec![Embed::External {
+                external: ExternalEmbed {
+                    uri: "https://example.com/a".to_string(),
+                    title: "Example Title".to_string(),
+                    description: description.to_string(),
+                    thumb: None,
+                },
+            }]),
+            facets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_format_search_results_truncates_embed_description() {
+        let post = post_with_link_card("Ünïcödé description that goes on and on");
+        let options = SearchFormatOptions { embed_desc_len: 7, ..Default::default() };
+        let md = format_search_results_with_options(&[&post], &"host".into(), "look", &options);
+        assert!(md.contains("- [Example Title](https://example.com/a)"), "got:\n{}", md);
+        assert!(md.contains("  Ünïcödé…\n"), "got:\n{}", md);
+        assert!(!md.contains("goes on"), "got:\n{}", md);
+    }
+
+    #[test]
+    fn test_format_search_results_zero_embed_desc_len_omits_description() {
+        let post = post_with_link_card("Hidden description");
+        let options = SearchFormatOptions { embed_desc_len: 0, ..Default::default() };
+        let md = format_search_results_with_options(&[&post], &"host".into(), "look", &options);
+        assert!(md.contains("- [Example Title](https://example.com/a)"), "got:\n{}", md);
+        assert!(!md.contains("Hidden description"), "got:\n{}", md);
+    }
+
+    fn numbered_posts(count: usize) -> Vec<PostRecord> {
+        (0..count)
+            .map(|i| PostRecord {
+                uri: format!("at://did:plc:test/app.bsky.feed.post/{}", i),
+                cid: format!("cid{}", i),
+                text: format!("rust post number {}\nwith a second line", i),
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                embeds: None,
+                facets: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_format_search_page_respects_char_budget() {
+        let posts = numbered_posts(10);
+        let refs: Vec<&PostRecord> = posts.iter().collect();
+        let handles: AccountHandles = "host".into();
+
+        let (full, shown) =
+            format_search_page(&refs, &handles, "rust", &SearchFormatOptions::default());
+        assert_eq!(shown, 10);
+        assert!(!full.contains("omitted"));
+
+        // Room for roughly three posts
+        let budget = full.chars().count() * 3 / 10;
+        let options = SearchFormatOptions { max_chars: Some(budget), ..Default::default() };
+        let (md, shown) = format_search_page(&refs, &handles, "rust", &options);
+        let footer = format!("…{} more results omitted\n\n", 10 - shown);
+        assert!(shown > 0 && shown < 10);
+        assert!(md.starts_with(&format!("# Search Results · {} posts\n\n", shown)));
+        assert!(md.ends_with(&format!("---\n\n{}", footer)), "got:\n{}", md);
+        assert!(md.chars().count() - footer.chars().count() <= budget);
+        // Whole posts only: every shown post keeps both quoted lines and its separator
+        assert_eq!(md.matches("\n---\n").count(), shown);
+        assert_eq!(md.matches("with a second line").count(), shown);
+        assert!(md.contains(&format!("@host/{}\n", shown - 1)));
+        assert!(!md.contains(&format!("@host/{}\n", shown)));
+    }
+
+    #[test]
+    fn test_format_search_page_keeps_first_post_over_budget() {
+        let posts = numbered_posts(3);
+        let refs: Vec<&PostRecord> = posts.iter().collect();
+        let options = SearchFormatOptions { max_chars: Some(10), ..Default::default() };
+        let (md, shown) = format_search_page(&refs, &"host".into(), "rust", &options);
+        assert_eq!(shown, 1);
+        assert!(md.contains("> **rust** post number 0"));
+        assert!(md.ends_with("…2 more results omitted\n\n"));
+    }
+
+    #[test]
+    fn test_plain_results_page_continues_after_shown_posts() {
+        let posts = numbered_posts(10);
+        let refs: Vec<&PostRecord> = posts.iter().collect();
+        let mut options = SearchOptions::default();
+        options.format.output = OutputFormat::Plain;
+        options.format.max_chars = Some(200);
+
+        let result = results_page(refs, &"host".into(), "rust", &options, 10, None);
+        let text = result.render(OutputFormat::Plain);
+        let shown = text.matches("rust post number").count();
+        assert!(shown > 0 && shown < 10, "{}", text);
+        assert!(!text.contains("**"), "{}", text);
+        assert!(text.ends_with(&format!("Next offset: {}\n", shown)), "{}", text);
+    }
+
+    #[test]
+    fn test_format_search_results_bolds_quoted_phrase_as_one_span() {
+        let post = PostRecord {
+            uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
+            cid: "cid1".to_string(),
+            text: "I love Rust Lang today".to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        let markdown = format_search_results(&[&post], "host", r#"foo "rust lang""#);
+        assert!(
+            markdown.contains("I love **Rust Lang** today"),
+            "Quoted phrase should be one bold span; got:\n{}",
+            markdown
+        );
+    }
+
     #[test]
     fn test_format_search_results_multiple_posts() {
         // Test formatting with multiple search results
-        let posts = vec![
-            PostRecord {
+        let posts = [PostRecord {
                 uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
                 cid: "cid1".to_string(),
                 text: "First post about Rust".to_string(),
@@ -770,8 +1926,7 @@ mod tests {
                 created_at: "2024-01-15T11:00:00Z".to_string(),
                 embeds: None,
                 facets: vec![],
-            },
-        ];
+            }];
 
         let post_refs: Vec<&PostRecord> = posts.iter().collect();
         let markdown = format_search_results(&post_refs, "test.bsky.social", "Rust");
@@ -799,7 +1954,7 @@ mod tests {
             embeds: None,
             facets: vec![],
         };
-        let markdown = format_search_results(&vec![&post], "host", "a b");
+        let markdown = format_search_results(&[&post], "host", "a b");
         assert!(markdown.contains("**ab**"), "Adjacent single-letter matches should merge into **ab**; got:\n{}", markdown);
     }
 
@@ -815,7 +1970,7 @@ mod tests {
             facets: vec![],
         };
         // terms "gram" and "ming" abut in the word
-        let markdown = format_search_results(&vec![&post], "host", "gram ming");
+        let markdown = format_search_results(&[&post], "host", "gram ming");
         // expect the merged bold span covering the joined substring
         assert!(markdown.contains("pro**gramming**"), "Within-word adjacent matches should merge; got:\n{}", markdown);
     }
@@ -832,7 +1987,7 @@ mod tests {
             embeds: None,
             facets: vec![],
         };
-        let markdown = format_search_results(&vec![&post], "host", "a b");
+        let markdown = format_search_results(&[&post], "host", "a b");
         // Should contain two separate bold spans with a space between them
         assert!(markdown.contains("**a** **b**"), "Matches across a space should not be merged; got:\n{}", markdown);
     }
@@ -849,7 +2004,7 @@ mod tests {
             embeds: None,
             facets: vec![],
         };
-        let markdown = format_search_results(&vec![&post], "host", "c d");
+        let markdown = format_search_results(&[&post], "host", "c d");
         // Expect a single bold span containing the newline between the matched characters
         assert!(markdown.contains("**c\nd**"), "Matches across a single newline should merge into one bold span; got:\n{}", markdown);
     }
@@ -865,11 +2020,103 @@ mod tests {
             embeds: None,
             facets: vec![],
         };
-        let markdown = format_search_results(&vec![&post], "host", "c d");
+        let markdown = format_search_results(&[&post], "host", "c d");
         // Should not merge across paragraph break; expect separate bold spans
         assert!(markdown.contains("**c**") && markdown.contains("**d**") && !markdown.contains("**c\n\nd**"), "Matches across paragraph should not merge; got:\n{}", markdown);
     }
 
+    fn match_post(uri: &str, text: &str) -> PostRecord {
+        PostRecord {
+            uri: uri.to_string(),
+            cid: format!("cid_{}", uri),
+            text: text.to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            embeds: None,
+            facets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_search_matches_multibyte_offsets() {
+        let post = match_post("at://did:plc:test/app.bsky.feed.post/mb1", "Café ☕ café CAFÉ");
+        let matches = search_matches(&[&post], "café");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].uri, post.uri);
+        assert_eq!(matches[0].ranges, vec![(0, 5), (10, 15), (16, 21)]);
+        for &(s, e) in &matches[0].ranges {
+            assert_eq!(post.text[s..e].to_lowercase(), "café");
+        }
+    }
+
+    #[test]
+    fn test_search_matches_offsets_when_lowercase_changes_length() {
+        // 'İ' is 2 bytes but lowercases to 3 ("i" + combining dot)
+        let post = match_post("at://did:plc:test/app.bsky.feed.post/mb2", "İstanbul trip");
+        let ranges = &search_matches(&[&post], "stanbul")[0].ranges;
+        assert_eq!(ranges, &vec![(2, 9)]);
+        assert_eq!(&post.text[2..9], "stanbul");
+
+        let markdown = format_search_results(&[&post], "host", "trip");
+        assert!(markdown.contains("İstanbul **trip**"), "got:\n{}", markdown);
+    }
+
+    #[test]
+    fn test_search_matches_merges_adjacent_ranges() {
+        let post = match_post("at://did:plc:test/app.bsky.feed.post/adj3", "foobar baz foo bar");
+        let ranges = &search_matches(&[&post], "foo bar")[0].ranges;
+        // "foo"+"bar" abut inside "foobar"; the later pair is split by a space
+        assert_eq!(ranges, &vec![(0, 6), (11, 14), (15, 18)]);
+    }
+
+    #[tokio::test]
+    async fn test_case_sensitive_mode_on_same_post() {
+        let posts = vec![
+            match_post("at://did:plc:test/app.bsky.feed.post/acronym", "IT says it works"),
+            match_post("at://did:plc:test/app.bsky.feed.post/lower", "it works fine"),
+        ];
+        let run = |case_sensitive: bool| {
+            let args = SearchArgs {
+                caseSensitive: case_sensitive,
+                ..Default::default()
+            };
+            let options = SearchOptions::from_args(&args).unwrap();
+            let posts = posts.clone();
+            async move {
+                run_search_on_posts(&posts, &"host".into(), "IT", &options)
+                    .await
+                    .unwrap()
+                    .content[0]
+                    .text
+                    .clone()
+            }
+        };
+
+        let insensitive = run(false).await;
+        assert!(insensitive.contains("> **IT** says **it** works"), "got:\n{}", insensitive);
+        assert!(insensitive.contains("/lower"));
+
+        let sensitive = run(true).await;
+        assert!(sensitive.contains("> **IT** says it works"), "got:\n{}", sensitive);
+        assert!(!sensitive.contains("/lower"));
+
+        let post = &posts[0];
+        assert_eq!(search_matches_with_case(&[post], "IT", false)[0].ranges, vec![(0, 2), (8, 10)]);
+        assert_eq!(search_matches_with_case(&[post], "IT", true)[0].ranges, vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_search_matches_agree_with_markdown() {
+        let post = match_post("at://did:plc:test/app.bsky.feed.post/agree", "Rust and rustaceans love \"rust lang\"");
+        let query = "rust \"rust lang\"";
+        let ranges = search_matches(&[&post], query)[0].ranges.clone();
+        let markdown = format_search_results(&[&post], "host", query);
+        for (s, e) in ranges {
+            let bolded = format!("**{}**", &post.text[s..e]);
+            assert!(markdown.contains(&bolded), "missing {} in:\n{}", bolded, markdown);
+        }
+        assert!(search_matches(&[&post], "")[0].ranges.is_empty());
+    }
+
     #[test]
     fn test_extract_facets_with_link() {
         // Test extracting facets with link features
@@ -1021,7 +2268,7 @@ mod tests {
             embeds: None,
             facets: vec![],
         };
-        let markdown = format_search_results(&vec![&post], "host", "😊");
+        let markdown = format_search_results(&[&post], "host", "😊");
         assert!(markdown.contains("**😊**"), "Emoji should be highlighted correctly; got:\n{}", markdown);
     }
 
@@ -1087,7 +2334,7 @@ mod tests {
         ];
 
         let mut engine = SearchEngine::new();
-        let results = engine.search("rust", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("rust", &posts, |p| p.get_searchable_text());
 
         assert_eq!(results.len(), 3, "Should find all three posts");
         // Results should be ranked by relevance
@@ -1113,7 +2360,7 @@ mod tests {
 
         let mut engine = SearchEngine::new();
         // Query with numbers and letters should work
-        let results = engine.search("web3", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("web3", &posts, |p| p.get_searchable_text());
         assert_eq!(results.len(), 1, "Should find post with 'web3'");
     }
 
@@ -1132,7 +2379,7 @@ mod tests {
         ];
 
         let mut engine = SearchEngine::new();
-        let results = engine.search("programming", &posts, |p| p.get_searchable_text());
+        let results = engine.search_all("programming", &posts, |p| p.get_searchable_text());
         assert_eq!(results.len(), 1, "Should find post even with emoji present");
     }
 
@@ -1187,6 +2434,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_blob_ref_link_ref() {
+        use crate::car::cbor::CborValue;
+
+        // Repo records hold 'ref' as a DAG-CBOR link of a raw-codec CID
+        let mut cid = vec![1, 0x55, 0x12, 32];
+        cid.extend([7; 32]);
+        let blob_map = vec![
+            (CborValue::Text("$type"), CborValue::Text("blob")),
+            (CborValue::Text("mimeType"), CborValue::Text("image/png")),
+            (CborValue::Text("size"), CborValue::Integer(1234)),
+            (CborValue::Text("ref"), CborValue::Link(&cid)),
+        ];
+
+        let br = parse_blob_ref(&blob_map).expect("Should parse blob ref with link ref");
+        let expected = "bafkreiaha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4dqobyha4";
+        assert_eq!(br.ref_, expected);
+
+        // A malformed link drops the blob rather than building a bad URL
+        let mut bad = blob_map.clone();
+        bad[3].1 = CborValue::Link(&[0x01]);
+        assert!(parse_blob_ref(&bad).is_none());
+
+        // The CDN URL is built from the canonical CID
+        let post = PostRecord {
+            uri: "at://did:plc:test/app.bsky.feed.post/img".to_string(),
+            cid: "cid_img".to_string(),
+            text: "Diagram".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            embeds: Some(vec![Embed::Images {
+                images: vec![ImageEmbed {
+                    alt: Some("chart".to_string()),
+                    image: br,
+                }],
+            }]),
+            facets: vec![],
+        };
+        let md = format_search_results(&[&post], "test.bsky.social", "diagram");
+        let url = format!(
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/{}@png",
+            expected
+        );
+        assert!(md.contains(&url), "{}", md);
+    }
+
     #[test]
     fn test_parse_blob_ref_map_ref() {
         use crate::car::cbor::CborValue;
@@ -1347,7 +2639,7 @@ mod tests {
             facets: vec![],
         };
 
-        let markdown = format_search_results(&vec![&post], "host", "alpha beta");
+        let markdown = format_search_results(&[&post], "host", "alpha beta");
 
         // Expect two separate bold spans with the comma between them
         assert!(markdown.contains("**alpha**,**beta**") || markdown.contains("**alpha**, **beta**"), "Punctuation separator should produce separate bold spans; got:\n{}", markdown);
@@ -1450,7 +2742,7 @@ mod tests {
             facets: vec![],
         };
 
-        let md = format_search_results(&vec![&post], "host", "Alt");
+        let md = format_search_results(&[&post], "host", "Alt");
         assert!(md.contains("@jpeg"), "Should use @jpeg fallback when mime subtype missing; got:\n{}", md);
     }
 
@@ -1465,7 +2757,7 @@ mod tests {
             facets: vec![],
         };
 
-        let md = format_search_results(&vec![&post], "host", "");
+        let md = format_search_results(&[&post], "host", "");
         // The quoted text should equal the original text (no ** markers)
         assert!(md.contains("> No highlight here"), "Empty query should preserve original text; got:\n{}", md);
     }
@@ -1482,9 +2774,8 @@ mod tests {
             facets: vec![],
         };
 
-        let md_trail = format_search_results(&vec![&post_trailing], "me", "Trailing");
-        // Because split('/').last() yields an empty string, header should contain "@me/"
-        assert!(md_trail.contains("@me/"), "Trailing slash should produce empty id segment in header; got:\n{}", md_trail);
+        let md_trail = format_search_results(&[&post_trailing], "me", "Trailing");
+        assert!(md_trail.starts_with("# Search Results · 1 posts\n\n@me/99\n"), "Trailing slash should not hide the rkey; got:\n{}", md_trail);
 
         // No slash (plain id)
         let post_plain = PostRecord {
@@ -1496,8 +2787,16 @@ mod tests {
             facets: vec![],
         };
 
-        let md_plain = format_search_results(&vec![&post_plain], "me", "Plain");
+        let md_plain = format_search_results(&[&post_plain], "me", "Plain");
         assert!(md_plain.contains("@me/justid"), "Plain uri should be used as id; got:\n{}", md_plain);
+
+        // An at:// URI without an rkey has no post id to show
+        let post_missing = PostRecord {
+            uri: "at://did:plc:test/app.bsky.feed.post".to_string(),
+            ..post_plain.clone()
+        };
+        let md_missing = format_search_results(&[&post_missing], "me", "Plain");
+        assert!(md_missing.contains("@me/unknown\n"), "got:\n{}", md_missing);
     }
 
     #[test]
@@ -1520,7 +2819,7 @@ mod tests {
             facets: vec![],
         };
 
-        let md = format_search_results(&vec![&post], "host", "altterm");
+        let md = format_search_results(&[&post], "host", "altterm");
         // Default alt text should be present in markdown
         assert!(md.contains("![Image]"), "Should render default alt when alt missing; got:\n{}", md);
     }
@@ -1574,14 +2873,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_execute_search_empty_query_normalizes_to_error() {
+        let provider = RepositoryProvider::default();
         // Query with only whitespace should normalize to empty and produce InvalidInput
         let args = crate::cli::SearchArgs {
             from: "test.bsky.social".to_string(),
             query: "   \n\t  ".to_string(),
             limit: None,
+            ..Default::default()
         };
 
-        let res = execute_search(args).await;
+        let res = execute_search(args, OutputFormat::Markdown, &provider).await;
         assert!(res.is_err(), "Expected error when query normalizes to empty");
         match res {
             Err(AppError::InvalidInput(msg)) => assert!(msg.contains("Query is empty after normalization")),
@@ -1591,14 +2892,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_execute_search_invalid_account_error() {
+        let provider = RepositoryProvider::default();
         // Invalid account string should be rejected by validate_account before resolution
         let args = crate::cli::SearchArgs {
             from: "".to_string(),
             query: "hello".to_string(),
             limit: None,
+            ..Default::default()
         };
 
-        let res = execute_search(args).await;
+        let res = execute_search(args, OutputFormat::Markdown, &provider).await;
         assert!(res.is_err(), "Expected error for invalid account");
         match res {
             Err(AppError::InvalidInput(_)) => {}
@@ -1607,7 +2910,7 @@ mod tests {
     }
 
     #[test]
-    fn test_collect_posts_from_maps_builds_post() {
+    fn test_collect_post_from_map_builds_post() {
         use crate::car::cbor::CborValue;
         use std::collections::HashMap;
 
@@ -1618,16 +2921,685 @@ mod tests {
             (CborValue::Text("createdAt"), CborValue::Text("2025-11-08T00:00:00Z")),
         ];
 
-        let decoded = vec![("app.bsky.feed.post".to_string(), post_map, "cid1".to_string())];
-
         let mut mapping = HashMap::new();
         mapping.insert("cid1".to_string(), "rkey1".to_string());
 
-        let posts = collect_posts_from_maps("did:plc:test", &decoded, &mapping);
+        let post = collect_post_from_map("did:plc:test", &post_map, "cid1", &mapping).unwrap();
+
+        assert!(post.uri.contains("rkey1"));
+        assert_eq!(post.text, "Hello from map");
+    }
+
+    #[test]
+    fn test_format_search_results_show_facets() {
+        let post = PostRecord {
+            uri: "at://did:plc:test/app.bsky.feed.post/1".to_string(),
+            cid: "cid1".to_string(),
+            text: "Read #rust".to_string(),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            embeds: None,
+            facets: vec![Facet {
+                index: FacetIndex {
+                    byte_start: 5,
+                    byte_end: 10,
+                },
+                features: vec![FacetFeature::Tag {
+                    tag: "rust".to_string(),
+                }],
+            }],
+        };
+
+        let lean = format_search_results(&[&post], "host", "rust");
+        assert!(!lean.contains("Facets:"));
+
+        let options = SearchFormatOptions {
+            show_facets: true,
+            ..Default::default()
+        };
+        let md = format_search_results_with_options(&[&post], &"host".into(), "rust", &options);
+        assert!(md.contains("> Read #**rust**\n\nFacets:\n- 5..10 \"#rust\" → tag #rust\n"), "{}", md);
+    }
+
+    #[tokio::test]
+    async fn test_hydrated_quote_renders_nested() {
+        use crate::bluesky::provider::CacheConfig;
+        use axum::extract::RawQuery;
+        use std::sync::{Arc, Mutex};
+
+        // getPosts stand-in that knows every post except rkey q1, recording each batch size
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let seen = batches.clone();
+        let app = axum::Router::new().route(
+            "/xrpc/app.bsky.feed.getPosts",
+            axum::routing::get(move |RawQuery(query): RawQuery| {
+                let uris: Vec<String> = url::form_urlencoded::parse(query.unwrap().as_bytes())
+                    .filter(|(key, _)| key == "uris")
+                    .map(|(_, uri)| uri.into_owned())
+                    .collect();
+                seen.lock().unwrap().push(uris.len());
+                let posts: Vec<Value> = uris
+                    .iter()
+                    .filter(|uri| !uri.ends_with("/q1"))
+                    .map(|uri| {
+                        json!({
+                            "uri": uri,
+                            "cid": "bafyquoted",
+                            "author": { "did": "did:plc:bob", "handle": "bob.test" },
+                            "record": { "text": "Ferris the crab\nsays hi" }
+                        })
+                    })
+                    .collect();
+                async move { axum::Json(json!({ "posts": posts })) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let temp = tempfile::tempdir().unwrap();
+        let provider = RepositoryProvider::new(CacheConfig {
+            dir: temp.path().to_path_buf(),
+            ..CacheConfig::default()
+        })
+        .unwrap()
+        .with_appview(&base);
+
+        let quoting = |i: usize, quoted_rkey: &str| {
+            let uri = format!("at://did:plc:alice/app.bsky.feed.post/{}", i);
+            let mut post = match_post(&uri, "look");
+            post.created_at = format!("2024-01-01T00:00:{:02}Z", i);
+            post.embeds = Some(vec![Embed::Record {
+                record: RecordEmbed {
+                    uri: format!("at://did:plc:bob/app.bsky.feed.post/{}", quoted_rkey),
+                    cid: "bafyquoted".to_string(),
+                    quoted: None,
+                },
+            }]);
+            post
+        };
+        // The newest post quotes q0; the oldest quotes more posts than are looked up
+        let mut posts: Vec<PostRecord> = (0..MAX_QUOTE_LOOKUPS + 2)
+            .map(|i| quoting(i, &format!("x{}", i)))
+            .collect();
+        posts.push(quoting(58, "q1"));
+        posts.push(quoting(59, "q0"));
+        assert!(posts[0].get_searchable_text().iter().all(|text| !text.contains("Ferris")));
+
+        hydrate_quotes(&provider, &mut posts).await;
+        assert_eq!(*batches.lock().unwrap(), [GET_POSTS_MAX_URIS, GET_POSTS_MAX_URIS]);
+        // Only the newest quoting posts were looked up, and unknown posts stay bare
+        assert!(posts[0].quote_embed().unwrap().quoted.is_none());
+        assert!(posts[MAX_QUOTE_LOOKUPS + 2].quote_embed().unwrap().quoted.is_none());
+
+        let quoting_post = &posts[MAX_QUOTE_LOOKUPS + 3];
+        assert!(quoting_post
+            .get_searchable_text()
+            .contains(&"Ferris the crab\nsays hi".to_string()));
+        let md = format_search_results(&[quoting_post], "alice.test", "ferris");
+        assert!(
+            md.contains("> look\n>\n> > Quoting @bob.test: **Ferris** the crab\n> > says hi\n\n"),
+            "{}",
+            md
+        );
+
+        // A search looks up only the quotes of the page shown: the two newest posts
+        let options = SearchOptions {
+            limit: Some(2),
+            ..SearchOptions::default()
+        };
+        let mut page = rank_post_stream(posts, "look", &options).unwrap();
+        hydrate_quotes(&provider, &mut page.posts).await;
+        assert_eq!(batches.lock().unwrap().last(), Some(&2));
+    }
+
+    #[test]
+    fn test_quote_and_record_with_media_embeds_render() {
+        let quote = RecordEmbed {
+            uri: "at://did:plc:bob/app.bsky.feed.post/3kq".to_string(),
+            cid: "bafyquoted".to_string(),
+            quoted: None,
+        };
+        let image = ImageEmbed {
+            alt: Some("a crab".to_string()),
+            image: BlobRef {
+                type_: "blob".to_string(),
+                ref_: "bafkrei_crab".to_string(),
+                mime_type: "image/png".to_string(),
+                size: 100,
+            },
+        };
+
+        // A quote that was not looked up still names the quoted post
+        let mut post = match_post("at://did:plc:test/app.bsky.feed.post/q", "look at this");
+        post.embeds = Some(vec![Embed::Record {
+            record: quote.clone(),
+        }]);
+        let md = format_search_results(&[&post], "test.bsky.social", "look");
+        let quoting = format!("> **look** at this\n>\n> > Quoting {}\n\n", quote.uri);
+        assert!(md.contains(&quoting), "{}", md);
+
+        // Quote and media both render for recordWithMedia
+        post.embeds = Some(vec![Embed::RecordWithMedia {
+            record: RecordEmbed {
+                quoted: Some(QuotedPost {
+                    uri: quote.uri.clone(),
+                    author: "bob.test".to_string(),
+                    text: "Ferris".to_string(),
+                }),
+                ..quote
+            },
+            media: Box::new(Embed::Images {
+                images: vec![image],
+            }),
+        }]);
+        let md = format_search_results(&[&post], "test.bsky.social", "crab");
+        assert!(md.contains("> look at this\n>\n> > Quoting @bob.test: Ferris\n\n"), "{}", md);
+        let image_url =
+            "https://cdn.bsky.app/img/feed_fullsize/plain/did:plc:test/bafkrei_crab@png";
+        assert!(md.contains(&format!("![a **crab**]({})\n", image_url)), "{}", md);
+
+        let handles = AccountHandles::single("test.bsky.social");
+        let item = PostItem {
+            post: &post,
+            handle: handles.handle_for(&post.uri),
+            highlights: &[],
+            terms: &[],
+        };
+        let plain =
+            formatter_for(OutputFormat::Plain).post(&item, &SearchFormatOptions::default());
+        assert!(
+            plain.starts_with("@test.bsky.social: look at this\n  quoting @bob.test: Ferris\n"),
+            "{}",
+            plain
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_search_on_posts_pages_without_gaps() {
+        // Equal scores, shuffled input: only the URI tie-break makes the order stable
+        let posts: Vec<PostRecord> = [3, 1, 5, 2, 4]
+            .iter()
+            .map(|i| PostRecord {
+                uri: format!("at://did:plc:test/app.bsky.feed.post/{}", i),
+                cid: format!("cid{}", i),
+                text: "rust".to_string(),
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                embeds: None,
+                facets: vec![],
+            })
+            .collect();
+
+        let mut seen = Vec::new();
+        let mut offset = 0;
+        let mut pages = 0;
+        loop {
+            let options = SearchOptions {
+                limit: Some(2),
+                offset,
+                ..Default::default()
+            };
+            let result = run_search_on_posts(&posts, &"host".into(), "rust", &options)
+                .await
+                .unwrap();
+            let item = &result.content[0];
+            seen.extend(
+                item.text
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("@host/"))
+                    .map(str::to_string),
+            );
+            pages += 1;
+            match result.meta.as_ref().and_then(|m| m["nextOffset"].as_u64()) {
+                Some(next) => {
+                    assert!(item.text.contains(&format!("**Next offset:** `{}`", next)));
+                    offset = next as usize;
+                }
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen, vec!["1", "2", "3", "4", "5"]);
+
+        let past_end = SearchOptions {
+            offset: 5,
+            ..Default::default()
+        };
+        assert!(matches!(
+            run_search_on_posts(&posts, &"host".into(), "rust", &past_end).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_search_on_posts_tags_each_account() {
+        let post = |did: &str, rkey: &str, text: &str| PostRecord {
+            uri: format!("at://{}/app.bsky.feed.post/{}", did, rkey),
+            cid: format!("cid-{}", rkey),
+            text: text.to_string(),
+            created_at: "2025-11-08T00:00:00Z".to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        let posts = vec![
+            post("did:plc:alice", "a1", "Rust tips from alice"),
+            post("did:plc:bob", "b1", "Rust news from bob"),
+            post("did:plc:alice", "a2", "Nothing relevant here"),
+        ];
+
+        let mut handles = AccountHandles::default();
+        handles.insert("did:plc:alice", "alice.test");
+        handles.insert("did:plc:bob", "bob.test");
+
+        let md = run_search_on_posts(&posts, &handles, "rust", &SearchOptions::default())
+            .await
+            .unwrap()
+            .content[0]
+            .text
+            .clone();
+        assert!(md.contains("# Search Results · 2 posts"));
+        assert!(md.contains("@alice.test/a1\n"));
+        assert!(md.contains("@bob.test/b1\n"));
+        assert!(!md.contains("@alice.test/b1") && !md.contains("@bob.test/a1"));
+    }
+
+    #[test]
+    fn test_parse_accounts() {
+        assert_eq!(
+            parse_accounts("alice.bsky.social, @bob.bsky.social,alice.bsky.social").unwrap(),
+            vec!["alice.bsky.social", "@bob.bsky.social"]
+        );
+        assert!(matches!(parse_accounts(" , "), Err(AppError::InvalidInput(_))));
+        assert!(matches!(
+            parse_accounts("alice.bsky.social,not a handle"),
+            Err(AppError::InvalidInput(_))
+        ));
+        let many = (0..=MAX_SEARCH_ACCOUNTS)
+            .map(|i| format!("user{}.bsky.social", i))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert!(matches!(parse_accounts(&many), Err(AppError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_merges_local_cars() {
+        let provider = RepositoryProvider::default();
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, bytes: Vec<u8>| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let alice = write(
+            "alice.car",
+            synthetic_repo_car(Some("did:plc:alice"), &[("a1", "rust tips"), ("a2", "gardening")]),
+        );
+        let bob = write(
+            "bob.car",
+            synthetic_repo_car(Some("did:plc:bob"), &[("b1", "more rust tips"), ("b2", "rust rust")]),
+        );
+        let corrupt = write("corrupt.car", b"not a car file".to_vec());
+        let no_did = write("no-did.car", synthetic_repo_car(None, &[("c1", "rust")]));
+
+        let search = |limit: Option<usize>| {
+            execute_search(SearchArgs {
+                query: "rust".to_string(),
+                car: vec![alice.clone(), corrupt.clone(), bob.clone(), no_did.clone(), alice.clone()],
+                limit,
+                ..Default::default()
+            }, OutputFormat::Markdown, &provider)
+        };
+
+        // Attributed by each commit's DID; the duplicate CAR adds nothing
+        let md = search(None).await.unwrap().content[0].text.clone();
+        assert!(md.starts_with("# Search Results · 3 posts\n\nNot searched: "), "{}", md);
+        for tag in ["@did:plc:alice/a1\n", "@did:plc:bob/b1\n", "@did:plc:bob/b2\n"] {
+            assert!(md.contains(tag), "missing {} in {}", tag, md);
+        }
+        assert!(!md.contains("/a2\n") && !md.contains("/c1\n"));
+        assert!(md.contains(&corrupt) && md.contains(&no_did));
+
+        // The limit applies once across all repos
+        let md = search(Some(2)).await.unwrap().content[0].text.clone();
+        assert!(md.starts_with("# Search Results · 2 posts\n\n"));
+        assert_eq!(md.matches("\n@did:plc:").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_finds_lists_and_feeds() {
+        let provider = RepositoryProvider::default();
+        use crate::car::test_support::{synthetic_repo_records_car, Cbor};
+
+        let text = |value: &str| Cbor::Text(value.to_string());
+        let car = synthetic_repo_records_car(
+            Some("did:plc:alice"),
+            vec![
+                (
+                    "app.bsky.feed.generator",
+                    "rustfeed",
+                    Cbor::Map(vec![
+                        ("did", text("did:web:feeds.example")),
+                        ("displayName", text("Rust news")),
+                    ]),
+                ),
+                (
+                    "app.bsky.feed.post",
+                    "a1",
+                    Cbor::Map(vec![
+                        ("$type", text("app.bsky.feed.post")),
+                        ("text", text("rust tips")),
+                        ("createdAt", text("2025-01-01T00:00:00Z")),
+                    ]),
+                ),
+                (
+                    "app.bsky.graph.list",
+                    "3kl1",
+                    Cbor::Map(vec![
+                        ("name", text("Rustaceans")),
+                        ("purpose", text("app.bsky.graph.defs#curatelist")),
+                        ("description", text("People who write Rust")),
+                    ]),
+                ),
+                (
+                    "app.bsky.graph.list",
+                    "3kl2",
+                    Cbor::Map(vec![("name", text("Gardeners")), ("purpose", text("x#modlist"))]),
+                ),
+            ],
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alice.car");
+        std::fs::write(&path, car).unwrap();
+        let search_as = |query: &str, format: OutputFormat| {
+            execute_search(SearchArgs {
+                query: query.to_string(),
+                car: vec![path.to_string_lossy().into_owned()],
+                ..Default::default()
+            }, format, &provider)
+        };
+        let search = |query: &str| search_as(query, OutputFormat::Markdown);
+
+        let result = search("rust").await.unwrap();
+        let md = &result.content[0].text;
+        assert!(md.starts_with("# Search Results · 1 posts\n\n@did:plc:alice/a1\n"), "{}", md);
+        assert!(md.ends_with(
+            "## Lists and feeds · 2\n\n\
+             - **Rustaceans** · curated list · 0 members · at://did:plc:alice/app.bsky.graph.list/3kl1\n\
+             \x20 People who write Rust\n\
+             - **Rust news** · feed · at://did:plc:alice/app.bsky.feed.generator/rustfeed\n"
+        ));
+        assert!(!md.contains("Gardeners"));
+        let data = result.data_json().unwrap();
+        assert_eq!(data["lists"][0]["name"], "Rustaceans");
+        assert_eq!(data["feeds"][0]["displayName"], "Rust news");
+
+        // A list match alone is a result, even with no matching post
+        let md = search("gardeners").await.unwrap().content[0].text.clone();
+        assert!(md.starts_with("# Search Results · 0 posts\n\n## Lists and feeds · 1\n"));
+        assert!(matches!(search("nothing").await, Err(AppError::NotFound(_))));
+
+        // Plain output lists them without markdown
+        let text = search_as("rust", OutputFormat::Plain).await.unwrap().content[0].text.clone();
+        assert!(text.ends_with(
+            "Lists and feeds · 2\n\n\
+             Rustaceans · curated list · 0 members · at://did:plc:alice/app.bsky.graph.list/3kl1\n\
+             \x20 People who write Rust\n\
+             Rust news · feed · at://did:plc:alice/app.bsky.feed.generator/rustfeed\n"
+        ), "{}", text);
+        assert!(!text.contains("**") && !text.contains("## "), "{}", text);
+        let result = search_as("gardeners", OutputFormat::Plain).await.unwrap();
+        let text = &result.content[0].text;
+        assert!(text.starts_with("Search Results · 0 posts\n\nLists and feeds · 1\n"), "{}", text);
+
+        // And JSON output carries them as records
+        let result = search_as("gardeners", OutputFormat::Json).await.unwrap();
+        let json: Value = serde_json::from_str(&result.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["lists"][0]["name"], "Gardeners");
+        assert_eq!(json["lists"][0]["purpose"], "modlist");
+        assert_eq!(json["posts"].as_array().map(Vec::len), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_reports_progress() {
+        let provider = RepositoryProvider::default();
+        use crate::mcp::RpcSender;
+        use std::sync::Arc;
+        use tokio::io::AsyncReadExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alice.car");
+        let car = synthetic_repo_car(Some("did:plc:alice"), &[("a1", "rust"), ("a2", "tips")]);
+        std::fs::write(&path, car).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let (writer, mut reader) = tokio::io::duplex(64 * 1024);
+        let progress = Progress::start(Arc::new(RpcSender::new(writer)), json!("search-1"));
+        let args = SearchArgs {
+            query: "rust".to_string(),
+            car: vec![path.clone()],
+            ..Default::default()
+        };
+        execute_search_with_progress(args, OutputFormat::Markdown, &provider, Some(&progress))
+            .await
+            .unwrap();
+        // Dropping the reporter lets its writer task flush and close the stream
+        drop(progress);
+
+        let mut output = String::new();
+        reader.read_to_string(&mut output).await.unwrap();
+        let sent: Vec<Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert!(sent.iter().all(|n| n["method"] == "notifications/progress"));
+        assert!(sent.iter().all(|n| n["params"]["progressToken"] == "search-1"));
+        let steps: Vec<(u64, &str)> = sent
+            .iter()
+            .map(|n| {
+                let params = &n["params"];
+                (params["progress"].as_u64().unwrap(), params["message"].as_str().unwrap())
+            })
+            .collect();
+        let read = format!("Read repository {}", path);
+        assert_eq!(steps, vec![(1, read.as_str()), (2, "Searched 2 posts")]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancelled_search_stops_ranking() {
+        use crate::mcp::RpcSender;
+        use tokio::io::AsyncBufReadExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.car");
+        let total = 16 * DECODE_BATCH_POSTS;
+        let rkeys: Vec<String> = (0..total).map(|i| format!("3k{:06}", i)).collect();
+        let posts: Vec<(&str, &str)> = rkeys.iter().map(|r| (r.as_str(), "rust tips")).collect();
+        std::fs::write(&path, synthetic_repo_car(Some("did:plc:big"), &posts)).unwrap();
+
+        let (writer, reader) = tokio::io::duplex(64 * 1024);
+        let progress = Progress::start(Arc::new(RpcSender::new(writer)), json!("big"));
+        let args = SearchArgs {
+            query: "rust".to_string(),
+            car: vec![path.to_string_lossy().into_owned()],
+            ..Default::default()
+        };
+        let search = tokio::spawn({
+            let progress = progress.clone();
+            async move {
+                let provider = RepositoryProvider::default();
+                execute_search_with_progress(args, OutputFormat::Markdown, &provider, Some(&progress))
+                    .await
+            }
+        });
+
+        // Cancel as soon as ranking is under way, as the MCP loop does by dropping the future
+        let mut lines = tokio::io::BufReader::new(reader).lines();
+        let mut messages = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let message: Value = serde_json::from_str(&line).unwrap();
+            let text = message["params"]["message"].as_str().unwrap().to_string();
+            messages.push(text.clone());
+            if text.starts_with("Parsed ") {
+                search.abort();
+                break;
+            }
+        }
+        assert!(search.await.unwrap_err().is_cancelled());
+        drop(progress);
+
+        // The blocking thread stops at its next batch and drops its reporter, closing the stream
+        let drained = tokio::time::timeout(Duration::from_secs(30), async {
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let message: Value = serde_json::from_str(&line).unwrap();
+                messages.push(message["params"]["message"].as_str().unwrap().to_string());
+            }
+        })
+        .await;
+        assert!(drained.is_ok(), "Ranking kept running after cancellation");
+        let parsed = messages
+            .iter()
+            .filter_map(|m| m.strip_prefix("Parsed ")?.strip_suffix(" posts")?.parse().ok())
+            .max()
+            .unwrap_or(0usize);
+        assert!(parsed < total, "Parsed all {} posts despite the cancel", parsed);
+        assert!(!messages.iter().any(|m| m.starts_with("Searched")), "{:?}", messages);
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_json_output() {
+        let provider = RepositoryProvider::default();
+        use crate::cli::OutputFormat;
+
+        let dir = tempfile::tempdir().unwrap();
+        let alice = dir.path().join("alice.car");
+        let car = synthetic_repo_car(Some("did:plc:alice"), &[("a1", "rust tips"), ("a2", "rust")]);
+        std::fs::write(&alice, car).unwrap();
+        let missing = dir.path().join("missing.car").to_string_lossy().into_owned();
+
+        let result = execute_search(SearchArgs {
+            query: "rust".to_string(),
+            car: vec![alice.to_string_lossy().into_owned(), missing.clone()],
+            limit: Some(1),
+            ..Default::default()
+        }, OutputFormat::Markdown, &provider)
+        .await
+        .unwrap();
 
+        let json: Value = serde_json::from_str(&result.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["query"], "rust");
+        assert_eq!(json["totalMatches"], 2);
+        assert_eq!(json["nextOffset"], 1);
+        let posts = json["posts"].as_array().unwrap();
         assert_eq!(posts.len(), 1);
-        assert!(posts[0].uri.contains("rkey1"));
-        assert_eq!(posts[0].text, "Hello from map");
+        assert_eq!(posts[0]["did"], "did:plc:alice");
+        assert!(posts[0]["uri"].as_str().unwrap().starts_with("at://did:plc:alice/"));
+        assert!(posts[0]["text"].as_str().unwrap().contains("rust"));
+        assert_eq!(posts[0]["createdAt"], "2025-01-01T00:00:00Z");
+        assert!(posts[0]["facets"].is_array());
+        assert_eq!(json["notSearched"][0]["account"], missing.as_str());
+
+        // Markdown stays the default rendering
+        assert!(result.render(OutputFormat::Markdown).starts_with("# Search Results"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_char_budget_moves_next_offset() {
+        let provider = RepositoryProvider::default();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alice.car");
+        let posts = [("a1", "rust one"), ("a2", "rust two"), ("a3", "rust three")];
+        std::fs::write(&path, synthetic_repo_car(Some("did:plc:alice"), &posts)).unwrap();
+
+        let md = execute_search(SearchArgs {
+            query: "rust".to_string(),
+            car: vec![path.to_string_lossy().into_owned()],
+            maxChars: Some(1),
+            ..Default::default()
+        }, OutputFormat::Markdown, &provider)
+        .await
+        .unwrap()
+        .content[0]
+            .text
+            .clone();
+        assert!(md.starts_with("# Search Results · 1 posts\n\n"), "{}", md);
+        assert!(md.contains("…2 more results omitted\n\n**Next offset:** `1`\n"), "{}", md);
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_rejects_from_with_car() {
+        let provider = RepositoryProvider::default();
+        let args = SearchArgs {
+            from: "alice.bsky.social".to_string(),
+            query: "rust".to_string(),
+            car: vec!["repo.car".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            execute_search(args, OutputFormat::Markdown, &provider).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_replays_capture() {
+        let provider = RepositoryProvider::default();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("capture.bin");
+        let tail = firehose_commit("did:plc:a", 4, vec![("create", "4", 13, Some("rust lost"))]);
+        let capture = [
+            firehose_commit("did:plc:a", 2, vec![("delete", "1", 0, None)]),
+            firehose_commit("did:plc:a", 1, vec![("create", "1", 10, Some("rust deleted"))]),
+            firehose_commit("did:plc:b", 3, vec![("create", "3", 12, Some("rust kept"))]),
+            tail[..tail.len() - 5].to_vec(),
+        ]
+        .concat();
+        std::fs::write(&path, &capture).unwrap();
+
+        let md = execute_search(SearchArgs {
+            query: "rust".to_string(),
+            replay: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        }, OutputFormat::Markdown, &provider)
+        .await
+        .unwrap()
+        .content[0]
+            .text
+            .clone();
+        assert!(
+            md.starts_with("# Search Results · 1 posts\n\nReplay stopped at byte "),
+            "{}",
+            md
+        );
+        assert!(md.contains("@did:plc:b/3\n"));
+        assert!(!md.contains("/1\n") && !md.contains("/4\n"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_rejects_car_with_replay() {
+        let provider = RepositoryProvider::default();
+        let args = SearchArgs {
+            query: "rust".to_string(),
+            car: vec!["repo.car".to_string()],
+            replay: Some("capture.bin".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            execute_search(args, OutputFormat::Markdown, &provider).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_report_failed_accounts() {
+        let failures = vec![(
+            "gone.bsky.social".to_string(),
+            AppError::DidResolveFailed("no such handle".to_string()),
+        )];
+
+        let ok = ToolResult::text("# Search Results · 1 posts\n\n@a/1\n\n".to_string());
+        let md = report_failed_accounts(Ok(ok), &failures).unwrap().content[0].text.clone();
+        assert!(md.starts_with("# Search Results · 1 posts\n\nNot searched: gone.bsky.social ("));
+
+        let not_found = report_failed_accounts(Err(AppError::NotFound("none".to_string())), &failures);
+        match not_found {
+            Err(AppError::NotFound(msg)) => assert!(msg.contains("not searched: gone.bsky.social")),
+            other => panic!("expected NotFound, got {:?}", other.map(|_| ())),
+        }
     }
 
     #[tokio::test]
@@ -1642,7 +3614,11 @@ mod tests {
             facets: vec![],
         };
 
-        let res = run_search_on_posts(&[post.clone()], "test.handle", "keyword", Some(10)).await;
+        let options = SearchOptions {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let res = run_search_on_posts(std::slice::from_ref(&post), &"test.handle".into(), "keyword", &options).await;
         assert!(res.is_ok(), "Search should return results");
         if let Ok(tool) = res {
             // Inspect the returned ToolResult content text
@@ -1658,7 +3634,7 @@ mod tests {
         let id = serde_json::json!("testid");
         let args = serde_json::json!({}); // missing required fields -> serde error
 
-        let resp = handle_search(Some(id.into()), args).await;
+        let resp = handle_search(Some(id), args, None).await;
         // Should be an error response (error field present)
         assert!(resp.error.is_some(), "Expected McpResponse to carry an error");
     }
@@ -1735,6 +3711,277 @@ mod tests {
         assert!(res.is_none(), "Should return None when CID->rkey mapping missing");
     }
 
+    fn post_with_image_alt(rkey: &str, text: &str, alt: &str) -> PostRecord {
+        PostRecord {
+            uri: format!("at://did:plc:test/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: text.to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            embeds: Some(vec![Embed::Images {
+                images: vec![ImageEmbed {
+                    alt: Some(alt.to_string()),
+                    image: BlobRef {
+                        type_: "blob".to_string(),
+                        ref_: format!("bafkrei{}", rkey),
+                        mime_type: "image/jpeg".to_string(),
+                        size: 100,
+                    },
+                }],
+            }]),
+            facets: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_has_image_filter_matches_only_image_posts_with_alt() {
+        let posts = vec![
+            post_with_image_alt("img_cats", "My afternoon", "two sleepy cats"),
+            post_with_image_alt("img_dogs", "My morning", "a happy dog"),
+            PostRecord {
+                uri: "at://did:plc:test/app.bsky.feed.post/text_cats".to_string(),
+                cid: "cid_text".to_string(),
+                text: "I love cats".to_string(),
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                embeds: None,
+                facets: vec![],
+            },
+        ];
+
+        let mut engine = SearchEngine::new();
+        let options = MatchOptions::new(PostRecord::get_searchable_text)
+            .with_filter(|p, f| f.matches(p))
+            .with_tie_key(PostRecord::tie_key);
+        let results = engine.search("has:image cats", posts.iter().cloned(), options);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].item.uri.ends_with("/img_cats"));
+
+        let res = run_search_on_posts(&posts, &"host".into(), "has:image cats", &SearchOptions::default())
+            .await
+            .unwrap();
+        let text = &res.content[0].text;
+        assert!(text.contains("Filters: `has:image` excluded 1 posts"), "got:\n{}", text);
+        assert!(text.contains("@host/img_cats"), "got:\n{}", text);
+        assert!(!text.contains("@host/text_cats"), "got:\n{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_hashtag_query_matches_tag_facets() {
+        let post = |rkey: &str, text: &str, tag: Option<&str>| PostRecord {
+            uri: format!("at://did:plc:test/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: text.to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            embeds: None,
+            facets: tag
+                .map(|tag| Facet {
+                    index: FacetIndex {
+                        byte_start: 0,
+                        byte_end: tag.len() as u32 + 1,
+                    },
+                    features: vec![FacetFeature::Tag {
+                        tag: tag.to_string(),
+                    }],
+                })
+                .into_iter()
+                .collect(),
+        };
+        let posts = vec![
+            post("tagged", "#Rust is great", Some("Rust")),
+            post("untagged", "older post about #RUST, written without facets", None),
+            post("plain", "rust without a tag", None),
+            post("longer", "#rustacean and C#rust are other words", None),
+            post("web3", "building on web3, (web3.0) today", None),
+        ];
+        let options = SearchOptions::default();
+
+        let res = run_search_on_posts(&posts, &"host".into(), "#rust", &options).await.unwrap();
+        let text = &res.content[0].text;
+        assert!(text.contains("@host/tagged"), "got:\n{}", text);
+        assert!(text.contains("@host/untagged"), "got:\n{}", text);
+        assert!(!text.contains("@host/plain"), "got:\n{}", text);
+        assert!(!text.contains("@host/longer"), "got:\n{}", text);
+        assert!(text.contains("Filters: `#rust` excluded 3 posts"), "got:\n{}", text);
+
+        // Digits next to punctuation stay part of the word
+        let res = run_search_on_posts(&posts, &"host".into(), "web3.0", &options).await.unwrap();
+        let text = &res.content[0].text;
+        assert!(text.contains("@host/web3"), "got:\n{}", text);
+        assert!(text.contains("(**web3.0**) today"), "got:\n{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_recency_weight_reorders_equal_posts() {
+        let make = |rkey: &str, created_at: &str| PostRecord {
+            uri: format!("at://did:plc:test/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: "identical rust post".to_string(),
+            created_at: created_at.to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        let days_ago = |days: i64| (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        // Equal scores tie-break by URI, so without recency "a-older" lists first
+        let posts = vec![make("a-older", &days_ago(60)), make("b-newer", &days_ago(1))];
+
+        let default_md = run_search_on_posts(&posts, &"host".into(), "rust", &SearchOptions::default())
+            .await
+            .unwrap()
+            .content[0]
+            .text
+            .clone();
+        assert!(default_md.find("/a-older").unwrap() < default_md.find("/b-newer").unwrap());
+
+        let args = SearchArgs {
+            from: "host".to_string(),
+            query: "rust".to_string(),
+            recencyWeight: Some(5.0),
+            ..Default::default()
+        };
+        let recency_md = run_search_on_posts(&posts, &"host".into(), "rust", &SearchOptions::from_args(&args).unwrap())
+            .await
+            .unwrap()
+            .content[0]
+            .text
+            .clone();
+        assert!(recency_md.find("/b-newer").unwrap() < recency_md.find("/a-older").unwrap());
+    }
+
+    #[test]
+    fn test_search_options_clamp_weights() {
+        let args = SearchArgs {
+            exactBonus: Some(1000.0),
+            prefixBonus: Some(-2.0),
+            recencyWeight: Some(f64::INFINITY),
+            ..Default::default()
+        };
+        let options = SearchOptions::from_args(&args).unwrap();
+        assert_eq!(options.weights.exact_match_bonus, 100.0);
+        assert_eq!(options.weights.position_multipliers.word_start, 0.0);
+        assert_eq!(options.weights.recency_weight, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_order_sorts_limited_results_by_date() {
+        let make = |rkey: &str, text: &str, created_at: &str| PostRecord {
+            uri: format!("at://did:plc:test/app.bsky.feed.post/{}", rkey),
+            cid: format!("cid_{}", rkey),
+            text: text.to_string(),
+            created_at: created_at.to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        // Relevance puts exact "rust" ahead of the fuzzy matches; dates disagree with that order
+        let posts = vec![
+            make("mid", "rust", "2024-02-01T00:00:00Z"),
+            make("new", "ruts in the road", "2024-03-01T00:00:00"),
+            make("old", "r u s t", "2024-01-01T00:00:00.000+02:00"),
+        ];
+        let run = |order: &str| {
+            let args = SearchArgs {
+                order: Some(order.to_string()),
+                ..Default::default()
+            };
+            let options = SearchOptions::from_args(&args).unwrap();
+            let posts = posts.clone();
+            async move {
+                let md = run_search_on_posts(&posts, &"host".into(), "rust", &options)
+                    .await
+                    .unwrap()
+                    .content[0]
+                    .text
+                    .clone();
+                let mut positions: Vec<(usize, &str)> = ["/old", "/mid", "/new"]
+                    .iter()
+                    .map(|k| (md.find(k).unwrap(), *k))
+                    .collect();
+                positions.sort();
+                positions.into_iter().map(|(_, k)| k).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(run("newest").await, vec!["/new", "/mid", "/old"]);
+        assert_eq!(run("oldest").await, vec!["/old", "/mid", "/new"]);
+        assert_eq!(run("relevance").await[0], "/mid");
+    }
+
+    #[test]
+    fn test_mode_option() {
+        let semantic = |weight: Option<f64>| SearchArgs {
+            mode: Some("Semantic".to_string()),
+            semanticWeight: weight,
+            ..Default::default()
+        };
+        let weight = |args: &SearchArgs| SearchOptions::from_args(args).unwrap().semantic_weight;
+
+        assert_eq!(weight(&SearchArgs::default()), None);
+        if cfg!(feature = "experimental-sentencepiece") {
+            assert_eq!(weight(&semantic(None)), Some(DEFAULT_SEMANTIC_WEIGHT));
+            assert_eq!(weight(&semantic(Some(3.0))), Some(1.0));
+        } else {
+            // Without the embeddings, semantic mode ranks like fuzzy
+            assert_eq!(weight(&semantic(Some(0.8))), None);
+        }
+
+        let args = SearchArgs {
+            mode: Some("vector".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            SearchOptions::from_args(&args),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_min_score_option() {
+        let args = SearchArgs {
+            minScore: Some(50.0),
+            ..Default::default()
+        };
+        assert_eq!(SearchOptions::from_args(&args).unwrap().min_score, Some(50.0));
+        assert_eq!(SearchOptions::from_args(&SearchArgs::default()).unwrap().min_score, None);
+
+        for bad in [-1.0, f32::NAN] {
+            let args = SearchArgs {
+                minScore: Some(bad),
+                ..Default::default()
+            };
+            assert!(matches!(
+                SearchOptions::from_args(&args),
+                Err(AppError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_order_parse() {
+        assert_eq!(SearchOrder::parse(None).unwrap(), SearchOrder::Relevance);
+        assert_eq!(SearchOrder::parse(Some("Newest")).unwrap(), SearchOrder::Newest);
+        assert_eq!(SearchOrder::parse(Some("oldest")).unwrap(), SearchOrder::Oldest);
+        assert!(matches!(
+            SearchOrder::parse(Some("popular")),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_order_puts_unparseable_dates_last() {
+        let make = |rkey: &str, created_at: &str| PostRecord {
+            uri: rkey.to_string(),
+            cid: String::new(),
+            text: String::new(),
+            created_at: created_at.to_string(),
+            embeds: None,
+            facets: vec![],
+        };
+        let bad = make("bad", "yesterday");
+        let old = make("old", "2024-01-01");
+        let new = make("new", "2024-05-01T12:00:00Z");
+        let mut posts = vec![&bad, &old, &new];
+        SearchOrder::Oldest.sort_posts(&mut posts);
+        assert_eq!(posts.iter().map(|p| p.uri.as_str()).collect::<Vec<_>>(), ["old", "new", "bad"]);
+    }
+
     #[tokio::test]
     async fn test_run_search_on_posts_no_matches_returns_not_found() {
         let post = PostRecord {
@@ -1746,7 +3993,7 @@ mod tests {
             facets: vec![],
         };
 
-        let res = run_search_on_posts(&[post], "handle", "something", None).await;
+        let res = run_search_on_posts(&[post], &"handle".into(), "something", &SearchOptions::default()).await;
         assert!(res.is_err(), "Expected NotFound for no matching posts");
         match res {
             Err(AppError::NotFound(_)) => {}
@@ -1790,7 +4037,7 @@ mod tests {
             facets: vec![],
         };
 
-        let md = format_search_results(&vec![&post], "host", "PNG");
+        let md = format_search_results(&[&post], "host", "PNG");
         assert!(md.contains("@png"), "Should use @png when mime subtype is 'png'; got:\n{}", md);
     }
 
@@ -1829,8 +4076,13 @@ mod tests {
         let res = collect_post_from_map("did:plc:alice", post_map.as_slice(), "cid_ok", &mapping);
         assert!(res.is_some(), "Should build PostRecord when mapping present");
         let p = res.unwrap();
-        assert!(p.uri.contains("rkey_ok"));
+        assert_eq!(p.uri, "at://did:plc:alice/app.bsky.feed.post/rkey_ok");
         assert_eq!(p.text, "Collected post");
+
+        // MST keys carry the collection, which the URI names only once
+        mapping.insert("cid_ok".to_string(), "app.bsky.feed.post/rkey_ok".to_string());
+        let res = collect_post_from_map("did:plc:alice", post_map.as_slice(), "cid_ok", &mapping);
+        assert_eq!(res.unwrap().uri, "at://did:plc:alice/app.bsky.feed.post/rkey_ok");
     }
 
     #[test]
@@ -1849,4 +4101,82 @@ mod tests {
             panic!("parse_record_embed should succeed for valid record map");
         }
     }
+
+    #[test]
+    fn test_parallel_decode_matches_sequential() {
+        let texts: Vec<(String, String)> = (0..600)
+            .map(|i| (format!("3k{:05}", i), format!("post number {} about rust", i)))
+            .collect();
+        let posts: Vec<(&str, &str)> =
+            texts.iter().map(|(rkey, text)| (rkey.as_str(), text.as_str())).collect();
+        let car = synthetic_repo_car(Some("did:plc:alice"), &posts);
+        let repo =
+            || AccountRepo::from_car_bytes("did:plc:alice".to_string(), car.clone()).unwrap();
+
+        let AccountRepo {
+            did,
+            cid_to_rkey,
+            records,
+            ..
+        } = repo();
+        let blocks: Vec<(Vec<u8>, String)> = records
+            .filter_map(Result::ok)
+            .filter(|(record_type, _, _)| record_type == "app.bsky.feed.post")
+            .map(|(_, cbor_data, cid_str)| (cbor_data, cid_str))
+            .collect();
+        assert!(blocks.len() >= PARALLEL_DECODE_MIN_POSTS);
+
+        let as_json = |posts: Vec<PostRecord>| serde_json::to_value(posts).unwrap();
+        let (sequential, failed) = decode_post_blocks(&did, &blocks, &cid_to_rkey, 1);
+        assert_eq!((sequential.len(), failed), (600, 0));
+        let sequential = as_json(sequential);
+        for workers in [2, 3, 8] {
+            let (parallel, _) = decode_post_blocks(&did, &blocks, &cid_to_rkey, workers);
+            assert_eq!(as_json(parallel), sequential, "{} workers", workers);
+        }
+        let skipped = Cell::new(0);
+        assert_eq!(as_json(repo().into_posts(&skipped, &AtomicBool::new(false)).collect()), sequential);
+        assert_eq!(skipped.get(), 0);
+    }
+
+    /// `car` with an extra block under `cid` holding raw (not necessarily valid) bytes
+    fn with_raw_block(mut car: Vec<u8>, cid: Vec<u8>, bytes: &[u8]) -> Vec<u8> {
+        crate::car::test_support::varint(&mut car, cid.len() + bytes.len());
+        car.extend(cid);
+        car.extend(bytes);
+        car
+    }
+
+    #[tokio::test]
+    async fn test_search_counts_unparseable_records() {
+        let provider = RepositoryProvider::default();
+        use crate::car::test_support::test_cid;
+
+        let car = synthetic_repo_car(
+            Some("did:plc:alice"),
+            &[("a1", "rust tips"), ("a2", "gardening"), ("a3", "rust again")],
+        );
+        // A map cut off after its first key, and a byte that starts no CBOR item
+        let car = with_raw_block(car, test_cid(200), &[0xA2, 0x65, b'$', b't', b'y', b'p', b'e']);
+        let car = with_raw_block(car, test_cid(201), &[0xFF]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alice.car");
+        std::fs::write(&path, car).unwrap();
+        let search = |query: &str| {
+            execute_search(SearchArgs {
+                query: query.to_string(),
+                car: vec![path.to_string_lossy().into_owned()],
+                ..Default::default()
+            }, OutputFormat::Markdown, &provider)
+        };
+
+        let result = search("rust").await.unwrap();
+        let text = &result.content[0].text;
+        assert!(text.contains("alice/a1") && text.contains("alice/a3"), "{}", text);
+        assert!(text.ends_with("\n\n2 records skipped due to parse errors\n"), "{}", text);
+        assert_eq!(result.data_json().unwrap()["skippedRecords"], 2);
+
+        let err = search("kayaking").await.unwrap_err();
+        assert!(err.message().ends_with("; 2 records skipped due to parse errors"), "{}", err);
+    }
 }
@@ -0,0 +1,358 @@
+//! Network-wide post search
+//!
+//! Backs `search` with `scope: "global"`: the query goes to `app.bsky.feed.searchPosts` on
+//! the public AppView, which applies its own operators (`from:`, `#tag`, `"phrase"`,
+//! `lang:` and so on) and ranking, and the hydrated posts are rendered in the same
+//! markdown as a repo search.
+
+use crate::bluesky::did::display_handle;
+use crate::bluesky::records::{Embed, Facet, PostRecord};
+use crate::cli::SearchArgs;
+use crate::error::AppError;
+use crate::http::{client_with_timeout, rate_limit_error, RetryPolicy, SendXrpc, XrpcError};
+use crate::mcp::{report_progress, Progress, ToolResult};
+use crate::tools::date_range::DateRange;
+use crate::tools::search::{
+    parse_accounts, results_page, AccountHandles, SearchOptions, SearchOrder,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::debug;
+
+const SEARCH_POSTS_URL: &str = "https://public.api.bsky.app/xrpc/app.bsky.feed.searchPosts";
+
+/// Largest page `app.bsky.feed.searchPosts` returns
+const SEARCH_POSTS_MAX: usize = 100;
+
+#[derive(Deserialize, Debug)]
+struct SearchPostsResponse {
+    posts: Vec<PostView>,
+    #[serde(default)]
+    cursor: Option<String>,
+    /// Estimated matches across all pages, when the AppView reports it
+    #[serde(rename = "hitsTotal", default)]
+    hits_total: Option<usize>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PostView {
+    uri: String,
+    cid: String,
+    author: PostViewAuthor,
+    record: PostViewRecord,
+}
+
+#[derive(Deserialize, Debug)]
+struct PostViewAuthor {
+    did: String,
+    handle: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct PostViewRecord {
+    #[serde(default)]
+    text: String,
+    #[serde(rename = "createdAt", default)]
+    created_at: String,
+    #[serde(default)]
+    facets: Vec<Facet>,
+    #[serde(default)]
+    embed: Option<Value>,
+}
+
+impl PostView {
+    /// The post as a repo search would have decoded it; embeds the record types do not
+    /// model are left out
+    fn into_post_record(self) -> PostRecord {
+        let embed = self
+            .record
+            .embed
+            .and_then(|embed| serde_json::from_value::<Embed>(embed).ok());
+        PostRecord {
+            uri: self.uri,
+            cid: self.cid,
+            text: self.record.text,
+            created_at: self.record.created_at,
+            embeds: embed.map(|embed| vec![embed]),
+            facets: self.record.facets,
+        }
+    }
+}
+
+/// Search posts across the network for `search` with `scope: "global"`, waiting out a
+/// rate limit under `retry`
+pub(crate) async fn search_global(
+    args: &SearchArgs,
+    date_range: &DateRange,
+    options: &SearchOptions,
+    retry: &RetryPolicy,
+    progress: Option<&Progress>,
+) -> Result<ToolResult, AppError> {
+    if !args.car.is_empty() || args.replay.is_some() {
+        return Err(AppError::InvalidInput(
+            "car and replay search local files; they cannot be used with scope global"
+                .to_string(),
+        ));
+    }
+    let author = if args.from.trim().is_empty() {
+        None
+    } else {
+        let accounts = parse_accounts(&args.from)?;
+        if accounts.len() > 1 {
+            return Err(AppError::InvalidInput(
+                "scope global takes at most one from account; use from: operators in the query \
+                 for more"
+                    .to_string(),
+            ));
+        }
+        Some(display_handle(&accounts[0]))
+    };
+
+    let url =
+        search_posts_url(SEARCH_POSTS_URL, &args.query, author.as_deref(), options, date_range);
+    debug!("Searching posts across the network: {}", url);
+
+    let client = client_with_timeout(Duration::from_secs(120));
+    let response = client
+        .get(&url)
+        .send_xrpc_with(retry)
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Failed to search posts: {}", e)))?;
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        let subject = author.as_deref().unwrap_or("The searched account");
+        let unavailable =
+            XrpcError::parse(&error_text).and_then(|x| x.unavailable_error(subject));
+        if let Some(e) = unavailable {
+            return Err(e);
+        }
+        return Err(AppError::NetworkError(format!(
+            "Search API returned error {}: {}",
+            status, error_text
+        )));
+    }
+
+    let found: SearchPostsResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::ParseError(format!("Failed to parse search response: {}", e)))?;
+    report_progress(progress, || {
+        format!("Fetched {} posts from network search", found.posts.len())
+    });
+
+    global_results(found, &args.query, date_range, options)
+}
+
+/// The searchPosts request for a query. The AppView's cursor is a result offset, so
+/// `offset` pages the same way it does for a repo search.
+fn search_posts_url(
+    base: &str,
+    query: &str,
+    author: Option<&str>,
+    options: &SearchOptions,
+    date_range: &DateRange,
+) -> String {
+    let limit = options.limit.unwrap_or(50).clamp(1, SEARCH_POSTS_MAX);
+    let sort = if options.order == SearchOrder::Newest { "latest" } else { "top" };
+    let mut url = format!(
+        "{}?q={}&limit={}&sort={}",
+        base,
+        urlencoding::encode(query),
+        limit,
+        sort
+    );
+    if let Some(author) = author {
+        url.push_str(&format!("&author={}", urlencoding::encode(author)));
+    }
+    if let Some(since) = date_range.since {
+        url.push_str(&format!("&since={}", urlencoding::encode(&since.to_rfc3339())));
+    }
+    if let Some(until) = date_range.until {
+        url.push_str(&format!("&until={}", urlencoding::encode(&until.to_rfc3339())));
+    }
+    if options.offset > 0 {
+        url.push_str(&format!("&cursor={}", options.offset));
+    }
+    url
+}
+
+/// Render a searchPosts page in the repo search layout, attributing posts to their
+/// authors' handles
+fn global_results(
+    found: SearchPostsResponse,
+    query: &str,
+    date_range: &DateRange,
+    options: &SearchOptions,
+) -> Result<ToolResult, AppError> {
+    let mut handles = AccountHandles::default();
+    let posts: Vec<PostRecord> = found
+        .posts
+        .into_iter()
+        .inspect(|view| handles.insert(&view.author.did, &view.author.handle))
+        .map(PostView::into_post_record)
+        .filter(|post| date_range.contains(&post.created_at))
+        .collect();
+    if posts.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "No posts found across the network matching query '{}'",
+            query
+        )));
+    }
+
+    let mut matching_posts: Vec<&PostRecord> = posts.iter().collect();
+    options.order.sort_posts(&mut matching_posts);
+
+    // Without a reported total, a cursor means at least one more match
+    let fetched = options.offset + posts.len();
+    let total_matches = found
+        .hits_total
+        .unwrap_or(fetched + usize::from(found.cursor.is_some()))
+        .max(fetched);
+    Ok(results_page(matching_posts, &handles, query, options, total_matches, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bluesky::provider::RepositoryProvider;
+    use crate::cli::OutputFormat;
+    use crate::tools::search::{execute_search, SearchScope};
+    use serde_json::json;
+
+    fn post_view(handle: &str, rkey: &str, text: &str, created_at: &str) -> Value {
+        json!({
+            "uri": format!("at://did:plc:{}/app.bsky.feed.post/{}", handle, rkey),
+            "cid": format!("bafy{}", rkey),
+            "author": {
+                "did": format!("did:plc:{}", handle),
+                "handle": format!("{}.test", handle)
+            },
+            "record": {
+                "$type": "app.bsky.feed.post",
+                "text": text,
+                "createdAt": created_at,
+                "embed": {
+                    "$type": "app.bsky.embed.external",
+                    "external": {
+                        "uri": "https://rust-lang.org",
+                        "title": "Rust",
+                        "description": ""
+                    }
+                }
+            },
+            "likeCount": 3,
+            "indexedAt": created_at
+        })
+    }
+
+    #[test]
+    fn test_scope_parsing() {
+        assert_eq!(SearchScope::parse(None).unwrap(), SearchScope::Repo);
+        assert_eq!(SearchScope::parse(Some("repo")).unwrap(), SearchScope::Repo);
+        assert_eq!(SearchScope::parse(Some(" Global ")).unwrap(), SearchScope::Global);
+        assert!(matches!(
+            SearchScope::parse(Some("everywhere")),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_global_scope_rejects_local_sources_and_several_accounts() {
+        let args = |extra: Value| {
+            let mut args = json!({ "query": "rust", "scope": "global" });
+            args.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            serde_json::from_value::<SearchArgs>(args).unwrap()
+        };
+        let provider = RepositoryProvider::default();
+        let search =
+            |extra: Value| execute_search(args(extra), OutputFormat::Markdown, &provider);
+
+        let result = search(json!({ "car": ["repo.car"] })).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("scope global")));
+        let result = search(json!({ "from": "alice.test,bob.test" })).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("at most one")));
+        let result = search(json!({ "scope": "planet" })).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(m)) if m.contains("Invalid scope")));
+    }
+
+    #[test]
+    fn test_search_posts_url() {
+        let args: SearchArgs = serde_json::from_value(json!({
+            "query": "from:alice.test \"exact phrase\" #rust",
+            "scope": "global",
+            "limit": 500,
+            "offset": 25,
+            "order": "newest",
+            "since": "2024-01-01"
+        }))
+        .unwrap();
+        let options = SearchOptions::from_args(&args).unwrap();
+        let range = DateRange::from_args(Some("2024-01-01"), None, None, None, chrono::Utc::now())
+            .unwrap();
+
+        let base = "https://appview.test/search";
+        let url = search_posts_url(base, &args.query, Some("bob.test"), &options, &range);
+        assert_eq!(
+            url,
+            "https://appview.test/search?q=from%3Aalice.test%20%22exact%20phrase%22%20%23rust\
+             &limit=100&sort=latest&author=bob.test&since=2024-01-01T00%3A00%3A00%2B00%3A00\
+             &cursor=25"
+        );
+
+        let defaults = SearchOptions::default();
+        let url = search_posts_url(base, "rust", None, &defaults, &DateRange::default());
+        assert_eq!(url, "https://appview.test/search?q=rust&limit=50&sort=top");
+    }
+
+    #[test]
+    fn test_global_results_mapping() {
+        let found: SearchPostsResponse = serde_json::from_value(json!({
+            "posts": [
+                post_view("alice", "3kone", "Learning rust this week", "2024-03-02T10:00:00.000Z"),
+                post_view("bob", "3ktwo", "rust and coffee", "2024-03-01T09:00:00.000Z")
+            ],
+            "cursor": "2",
+            "hitsTotal": 40
+        }))
+        .unwrap();
+
+        let result = global_results(found, "rust", &DateRange::default(), &SearchOptions::default())
+            .unwrap();
+        let markdown = result.render(OutputFormat::Markdown);
+        assert!(markdown.starts_with("# Search Results · 2 posts\n\n"));
+        assert!(markdown.contains("@alice.test/3kone\n\n> Learning **rust** this week\n"));
+        assert!(markdown.contains("@bob.test/3ktwo\n\n> **rust** and coffee\n"));
+        assert!(markdown.contains("- [Rust](https://rust-lang.org)\n"));
+        assert!(markdown.ends_with("**Next offset:** `2`\n"));
+
+        let data: Value = serde_json::from_str(&result.render(OutputFormat::Json)).unwrap();
+        assert_eq!(data["totalMatches"], 40);
+        assert_eq!(data["posts"][1]["handle"], "bob.test");
+        assert_eq!(data["posts"][1]["did"], "did:plc:bob");
+
+        // Nothing after the date filter is a not-found, as for a repo search
+        let found: SearchPostsResponse = serde_json::from_value(json!({
+            "posts": [post_view("alice", "3kone", "old rust", "2020-01-01T00:00:00.000Z")]
+        }))
+        .unwrap();
+        let range = DateRange::from_args(Some("2024-01-01"), None, None, None, chrono::Utc::now())
+            .unwrap();
+        let result = global_results(found, "rust", &range, &SearchOptions::default());
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        // A cursor without hitsTotal still offers the next page
+        let found: SearchPostsResponse = serde_json::from_value(json!({
+            "posts": [post_view("alice", "3kone", "rust", "2024-03-02T10:00:00.000Z")],
+            "cursor": "1"
+        }))
+        .unwrap();
+        let result = global_results(found, "rust", &DateRange::default(), &SearchOptions::default())
+            .unwrap();
+        assert!(result.render(OutputFormat::Markdown).ends_with("**Next offset:** `1`\n"));
+    }
+}
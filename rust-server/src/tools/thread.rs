@@ -2,11 +2,12 @@
 //!
 //! Implements the `thread` MCP tool for fetching BlueSky threads
 
-use crate::cli::ThreadArgs;
+use crate::bluesky::feed_view::FeedPost;
+use crate::cli::{OutputFormat, ThreadArgs};
 use crate::error::AppError;
-use crate::http::client_with_timeout;
+use crate::http::{client_with_timeout, rate_limit_error, SendXrpc};
 use crate::mcp::{McpResponse, ToolResult};
-use crate::bluesky::records::{Facet};
+use crate::tools::formatter::{formatter_for, OutputFormatter, ThreadItem};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -15,69 +16,42 @@ use std::time::Duration;
 use tokio::time::timeout;
 use tracing::debug;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct PostAuthor {
-    did: String,
-    handle: String,
-    #[serde(rename = "displayName")]
-    display_name: Option<String>,
-}
+/// Reply levels shown when `maxDepth` is not given (the API's own default)
+pub const DEFAULT_MAX_DEPTH: usize = 6;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct PostRecord {
-    text: String,
-    #[serde(rename = "createdAt")]
-    created_at: String,
-    #[serde(default)]
-    facets: Vec<Facet>,
-}
+/// Replies shown under each post when `maxReplies` is not given
+pub const DEFAULT_MAX_REPLIES: usize = 20;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct ThreadPost {
-    uri: String,
-    cid: String,
-    author: PostAuthor,
-    record: PostRecord,
-    #[serde(rename = "indexedAt")]
-    indexed_at: Option<String>,
-    #[serde(rename = "likeCount")]
-    like_count: Option<i32>,
-    #[serde(rename = "replyCount")]
-    reply_count: Option<i32>,
-    #[serde(rename = "repostCount")]
-    repost_count: Option<i32>,
-    #[serde(rename = "quoteCount")]
-    quote_count: Option<i32>,
-}
+/// Deepest `depth` the getPostThread endpoint accepts
+const API_MAX_DEPTH: usize = 1000;
 
 /// Represents a node in the thread tree (post, not found, or blocked)
 #[allow(clippy::large_enum_variant)]
 #[allow(clippy::enum_variant_names)]
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "$type")]
 enum ThreadNode {
     #[serde(rename = "app.bsky.feed.defs#threadViewPost")]
     ThreadViewPost {
-        post: ThreadPost,
+        post: FeedPost,
         #[serde(default)]
         replies: Vec<ThreadNode>,
     },
     #[serde(rename = "app.bsky.feed.defs#notFoundPost")]
     NotFoundPost {
-        #[allow(dead_code)]
         uri: String,
         #[serde(rename = "notFound")]
-        #[allow(dead_code)]
         not_found: bool,
     },
     #[serde(rename = "app.bsky.feed.defs#blockedPost")]
     BlockedPost {
-        #[allow(dead_code)]
         uri: String,
         #[serde(rename = "blocked")]
-        #[allow(dead_code)]
         blocked: bool,
     },
+    /// Node kinds this client does not know yet
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Deserialize)]
@@ -100,11 +74,14 @@ async fn handle_thread_impl(args: Value) -> Result<ToolResult, AppError> {
     let thread_args: ThreadArgs = serde_json::from_value(args)
         .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
 
-    execute_thread(thread_args).await
+    execute_thread(thread_args, OutputFormat::Markdown).await
 }
 
-/// Execute thread tool
-pub async fn execute_thread(thread_args: ThreadArgs) -> Result<ToolResult, AppError> {
+/// Execute thread tool, rendering the thread as `format`
+pub async fn execute_thread(
+    thread_args: ThreadArgs,
+    format: OutputFormat,
+) -> Result<ToolResult, AppError> {
     debug!("Thread request for post: {}", thread_args.postURI);
 
     let client = client_with_timeout(Duration::from_secs(120));
@@ -112,20 +89,27 @@ pub async fn execute_thread(thread_args: ThreadArgs) -> Result<ToolResult, AppEr
     // Parse the post URI - it could be a URL or an at:// URI
     let post_uri = parse_post_uri(&client, &thread_args.postURI).await?;
 
-    // Build the URL for the getPostThread endpoint
+    let options = ThreadFormatOptions::from_args(&thread_args);
+
+    // One level past the cap, so posts at the cap can say how many replies were left out
     let url = format!(
-        "https://public.api.bsky.app/xrpc/app.bsky.feed.getPostThread?uri={}",
-        urlencoding::encode(&post_uri)
+        "https://public.api.bsky.app/xrpc/app.bsky.feed.getPostThread?uri={}&depth={}",
+        urlencoding::encode(&post_uri),
+        (options.max_depth + 1).min(API_MAX_DEPTH)
     );
 
     debug!("Fetching thread from: {}", url);
 
     let response = client
         .get(&url)
-        .send()
+        .send_xrpc()
         .await
         .map_err(|e| AppError::NetworkError(format!("Failed to fetch thread: {}", e)))?;
 
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
@@ -140,33 +124,62 @@ pub async fn execute_thread(thread_args: ThreadArgs) -> Result<ToolResult, AppEr
         .await
         .map_err(|e| AppError::ParseError(format!("Failed to parse thread response: {}", e)))?;
 
-    // Format thread as markdown
-    let markdown = format_thread(&thread_response.thread);
+    let text = format_thread(formatter_for(format), &thread_response.thread, &options);
 
     debug!("Thread formatted successfully");
 
-    Ok(ToolResult::text(markdown))
+    Ok(ToolResult::text(text).with_data(thread_response.thread))
 }
 
-/// Format a thread as markdown per docs/16-mcp-schemas.md spec
-fn format_thread(node: &ThreadNode) -> String {
-    #[allow(unused_imports)]
-    use crate::tools::post_format::*;
-    use std::collections::HashMap;
+/// Options controlling thread formatting
+#[derive(Debug, Clone)]
+struct ThreadFormatOptions {
+    /// List each post's facets below its text
+    show_facets: bool,
+    /// Deepest reply level rendered; 0 renders only the root
+    max_depth: usize,
+    /// Replies rendered under each post
+    max_replies: usize,
+}
 
-    let mut markdown = String::new();
+impl Default for ThreadFormatOptions {
+    fn default() -> Self {
+        Self {
+            show_facets: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_replies: DEFAULT_MAX_REPLIES,
+        }
+    }
+}
 
-    // Count total posts
-    let total_posts = count_posts(node);
-    markdown.push_str(&format!("# Thread · {} posts\n\n", total_posts));
+impl ThreadFormatOptions {
+    fn from_args(args: &ThreadArgs) -> Self {
+        Self {
+            show_facets: args.showFacets,
+            max_depth: args.maxDepth.unwrap_or(DEFAULT_MAX_DEPTH),
+            max_replies: args.maxReplies.unwrap_or(DEFAULT_MAX_REPLIES),
+        }
+    }
+}
+
+/// Render a thread per docs/16-mcp-schemas.md spec
+///
+/// Replies nest under their parent with the threading indicator indented by depth. Replies
+/// past `max_depth`, or past `max_replies` under one post, are summed up in a single line.
+fn format_thread(
+    formatter: &dyn OutputFormatter,
+    node: &ThreadNode,
+    options: &ThreadFormatOptions,
+) -> String {
+    let mut text = formatter.results_heading("Thread", count_posts(node), "posts");
 
     // Track seen posts for ID compaction
     let mut seen_posts: HashMap<String, String> = HashMap::new();
 
     // Format thread recursively with proper threading indicators
-    format_thread_recursive(node, &mut markdown, &mut seen_posts, 0, None);
+    format_thread_recursive(formatter, node, &mut text, &mut seen_posts, 0, None, options);
 
-    markdown
+    text
 }
 
 /// Count total posts in thread
@@ -181,70 +194,73 @@ fn count_posts(node: &ThreadNode) -> usize {
 
 /// Recursively format thread with proper indentation and threading indicators
 fn format_thread_recursive(
+    formatter: &dyn OutputFormatter,
     node: &ThreadNode,
-    markdown: &mut String,
+    text: &mut String,
     seen_posts: &mut HashMap<String, String>,
     depth: usize,
-    parent_post: Option<&ThreadPost>,
+    parent_post: Option<&FeedPost>,
+    options: &ThreadFormatOptions,
 ) {
     use crate::tools::post_format::*;
 
-    if let ThreadNode::ThreadViewPost { post, replies } = node {
-        let rkey = extract_rkey(&post.uri);
-        let full_id = format!("{}/{}", post.author.handle, rkey);
-
-        // Build the first line with threading indicator (INDENTED)
-        let author_id = compact_post_id(&post.author.handle, rkey, seen_posts);
-
-        if depth == 0 {
-            // Root post - just the author ID, no indent
-            markdown.push_str(&format!("{}\n", author_id));
-        } else if let Some(parent) = parent_post {
-            // Reply - show threading indicator with indentation
+    // Threading indicator pointing at the parent, ahead of a reply's ID line
+    let indicated = |id: &str| match parent_post {
+        Some(parent) => {
             let parent_rkey = extract_rkey(&parent.uri);
             let parent_compact = ultra_compact_id(&parent.author.handle, parent_rkey);
-            let indicator = threading_indicator(depth, &parent_compact, &author_id);
-            markdown.push_str(&format!("{}\n", indicator));
+            threading_indicator(depth, &parent_compact, id)
         }
+        None => id.to_string(),
+    };
+
+    let placeholder = match node {
+        ThreadNode::ThreadViewPost { .. } => None,
+        ThreadNode::NotFoundPost { .. } => Some("Post not found"),
+        ThreadNode::BlockedPost { .. } => Some("Blocked post"),
+        ThreadNode::Unknown => Some("Post unavailable"),
+    };
+    if let Some(placeholder) = placeholder {
+        text.push_str(&format!("{}\n\n", indicated(&formatter.placeholder(placeholder))));
+        return;
+    }
+    let ThreadNode::ThreadViewPost { post, replies } = node else {
+        return;
+    };
+
+    let rkey = extract_rkey(&post.uri);
+    let author_id = compact_post_id(&post.author.handle, rkey, seen_posts);
+    // Mark this post as seen for future compaction
+    seen_posts.insert(format!("{}/{}", post.author.handle, rkey), post.uri.clone());
+
+    text.push_str(&formatter.thread_post(&ThreadItem {
+        post,
+        id_line: &indicated(&author_id),
+        show_facets: options.show_facets,
+    }));
+
+    // Process replies recursively, within the depth and breadth caps
+    let shown = if depth < options.max_depth {
+        replies.len().min(options.max_replies)
+    } else {
+        0
+    };
+    for reply in &replies[..shown] {
+        format_thread_recursive(formatter, reply, text, seen_posts, depth + 1, Some(post), options);
+    }
 
-        // Mark this post as seen for future compaction
-        seen_posts.insert(full_id, post.uri.clone());
-
-        // Blockquote the content (ALWAYS FLUSH-LEFT, NO INDENTATION)
-        // Apply facets if available
-        let content = if !post.record.facets.is_empty() {
-            blockquote_content_with_facets(&post.record.text, &post.record.facets)
-        } else {
-            blockquote_content(&post.record.text)
-        };
-        markdown.push_str(&content);
-        markdown.push('\n');
-
-        // Stats and timestamp on same line (FLUSH-LEFT)
-        let stats = format_stats(
-            post.like_count.unwrap_or(0),
-            post.repost_count.unwrap_or(0),
-            post.quote_count.unwrap_or(0),
-            post.reply_count.unwrap_or(0),
-        );
-        let timestamp = format_timestamp(&post.record.created_at);
-
-        if !stats.is_empty() {
-            markdown.push_str(&format!("{}  {}\n", stats, timestamp));
-        } else {
-            markdown.push_str(&format!("{}\n", timestamp));
-        }
-
-        // Blank line before next post
-        markdown.push('\n');
-
-        // Process replies recursively
-        for reply in replies {
-            format_thread_recursive(reply, markdown, seen_posts, depth + 1, Some(post));
-        }
+    let omitted: usize = replies[shown..].iter().map(count_posts).sum();
+    if omitted > 0 {
+        text.push_str(&omitted_replies_line(depth + 1, omitted));
     }
 }
 
+/// Summary line for replies left out at `depth`, indented like a reply there
+fn omitted_replies_line(depth: usize, count: usize) -> String {
+    let noun = if count == 1 { "reply" } else { "replies" };
+    format!("{}└─ … {} more {} not shown\n\n", "  ".repeat(depth - 1), count, noun)
+}
+
 /// Parse a post URI from either a BlueSky URL or an at:// URI
 async fn parse_post_uri(client: &reqwest::Client, uri: &str) -> Result<String, AppError> {
     let trimmed = uri.trim();
@@ -317,10 +333,14 @@ async fn resolve_handle(client: &reqwest::Client, handle: &str) -> Result<String
 
     let response = client
         .get(&url)
-        .send()
+        .send_xrpc()
         .await
         .map_err(|e| AppError::NetworkError(format!("Failed to resolve handle: {}", e)))?;
 
+    if let Some(e) = rate_limit_error(&response) {
+        return Err(e);
+    }
+
     if !response.status().is_success() {
         return Err(AppError::InvalidInput(format!(
             "Failed to resolve handle '{}': {}",
@@ -346,6 +366,9 @@ async fn resolve_handle(client: &reqwest::Client, handle: &str) -> Result<String
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bluesky::feed_view::{PostAuthor, PostRecord};
+    use crate::bluesky::records::Facet;
+    use crate::tools::formatter::{MarkdownFormatter, PlainFormatter};
 
     #[test]
     fn test_thread_args_deserialize() {
@@ -413,6 +436,36 @@ mod tests {
         assert_eq!(count_posts(&thread), 4);
     }
 
+    #[test]
+    fn test_format_thread_show_facets() {
+        use crate::bluesky::records::{FacetFeature, FacetIndex};
+
+        let mut post = create_mock_post("alice", "3kq8a3f1", "Ping @bob.test");
+        post.record.facets = vec![Facet {
+            index: FacetIndex {
+                byte_start: 5,
+                byte_end: 14,
+            },
+            features: vec![FacetFeature::Mention {
+                did: "did:plc:bob".to_string(),
+            }],
+        }];
+        let thread = ThreadNode::ThreadViewPost {
+            post,
+            replies: vec![],
+        };
+
+        let plain = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
+        assert!(!plain.contains("Facets:"));
+
+        let options = ThreadFormatOptions {
+            show_facets: true,
+            ..Default::default()
+        };
+        let debug = format_thread(&MarkdownFormatter, &thread, &options);
+        assert!(debug.contains("Facets:\n- 5..14 \"@bob.test\" → mention did:plc:bob\n"));
+    }
+
     #[test]
     fn test_format_thread_single_post() {
         let thread = ThreadNode::ThreadViewPost {
@@ -420,7 +473,7 @@ mod tests {
             replies: vec![],
         };
 
-        let markdown = format_thread(&thread);
+        let markdown = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
 
         assert!(markdown.contains("# Thread · 1 posts"));
         assert!(markdown.contains("@utopia-defer.red/3m4jnj3efp22t"));
@@ -448,7 +501,7 @@ mod tests {
             ],
         };
 
-        let markdown = format_thread(&thread);
+        let markdown = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
 
         assert!(markdown.contains("# Thread · 3 posts"));
 
@@ -478,7 +531,7 @@ mod tests {
             }],
         };
 
-        let markdown = format_thread(&thread);
+        let markdown = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
 
         // Check indentation levels - ONLY the threading indicator is indented, NOT the content
         assert!(markdown.contains("@alice/3kq8a3f1")); // Root, no indent
@@ -491,6 +544,116 @@ mod tests {
         assert!(markdown.contains("\n> Reply depth 2\n"));
     }
 
+    /// A getPostThread response node for a post with the given replies
+    fn thread_json(handle: &str, rkey: &str, replies: Vec<Value>) -> Value {
+        serde_json::json!({
+            "$type": "app.bsky.feed.defs#threadViewPost",
+            "post": serde_json::to_value(create_mock_post(handle, rkey, &format!("by {}", handle)))
+                .unwrap(),
+            "replies": replies
+        })
+    }
+
+    #[test]
+    fn test_format_thread_indents_each_depth_with_placeholders() {
+        let response = serde_json::json!({
+            "thread": thread_json("alice", "3kq8a001", vec![
+                thread_json("bob", "3kq8b001", vec![
+                    thread_json("carol", "3kq8c001", vec![
+                        thread_json("dave", "3kq8d001", vec![]),
+                    ]),
+                    serde_json::json!({
+                        "$type": "app.bsky.feed.defs#blockedPost",
+                        "uri": "at://did:plc:x/app.bsky.feed.post/3kq8x001",
+                        "blocked": true,
+                        "author": { "did": "did:plc:x" }
+                    }),
+                ]),
+                serde_json::json!({
+                    "$type": "app.bsky.feed.defs#notFoundPost",
+                    "uri": "at://did:plc:y/app.bsky.feed.post/3kq8y001",
+                    "notFound": true
+                }),
+                serde_json::json!({ "$type": "app.bsky.feed.defs#somethingNew" }),
+            ])
+        });
+        let response: ThreadResponse = serde_json::from_value(response).unwrap();
+        let options = ThreadFormatOptions::default();
+        let markdown = format_thread(&MarkdownFormatter, &response.thread, &options);
+
+        assert!(markdown.contains("# Thread · 4 posts"));
+        assert!(markdown.contains("\n└─@a/…a001 → @bob/3kq8b001\n> by bob\n"));
+        assert!(markdown.contains("\n  └─@b/…b001 → @carol/3kq8c001\n> by carol\n"));
+        assert!(markdown.contains("\n    └─@c/…c001 → @dave/3kq8d001\n> by dave\n"));
+        assert!(markdown.contains("\n  └─@b/…b001 → *Blocked post*\n\n"));
+        assert!(markdown.contains("\n└─@a/…a001 → *Post not found*\n\n"));
+        assert!(markdown.contains("\n└─@a/…a001 → *Post unavailable*\n\n"));
+        // Siblings keep their order under the parent
+        let blocked = markdown.find("*Blocked post*").unwrap();
+        assert!(blocked < markdown.find("*Post not found*").unwrap());
+    }
+
+    #[test]
+    fn test_format_thread_plain() {
+        let response = serde_json::json!({
+            "thread": thread_json("alice", "3kq8a001", vec![
+                thread_json("bob", "3kq8b001", vec![]),
+                serde_json::json!({
+                    "$type": "app.bsky.feed.defs#notFoundPost",
+                    "uri": "at://did:plc:y/app.bsky.feed.post/3kq8y001",
+                    "notFound": true
+                }),
+            ])
+        });
+        let response: ThreadResponse = serde_json::from_value(response).unwrap();
+        let options = ThreadFormatOptions::default();
+        let text = format_thread(&PlainFormatter, &response.thread, &options);
+
+        assert!(text.starts_with("Thread · 2 posts\n\n@alice/3kq8a001\nby alice\n"), "{}", text);
+        assert!(text.contains("👍 33  ♻️ 1  💬 1  2024-10-06T10:15:33Z\n"));
+        assert!(text.contains("\n└─@a/…a001 → @bob/3kq8b001\nby bob\n"));
+        assert!(text.contains("\n└─@a/…a001 → Post not found\n\n"));
+        assert!(!text.contains("> ") && !text.contains('*') && !text.contains('#'));
+    }
+
+    #[test]
+    fn test_format_thread_caps_depth_and_breadth() {
+        let response = serde_json::json!({
+            "thread": thread_json("alice", "3kq8a001", vec![
+                thread_json("bob", "3kq8b001", vec![
+                    thread_json("carol", "3kq8c001", vec![]),
+                    thread_json("dave", "3kq8d001", vec![]),
+                ]),
+                thread_json("erin", "3kq8e001", vec![]),
+                thread_json("frank", "3kq8f001", vec![]),
+                thread_json("grace", "3kq8g001", vec![]),
+            ])
+        });
+        let response: ThreadResponse = serde_json::from_value(response).unwrap();
+
+        let options = ThreadFormatOptions {
+            max_depth: 1,
+            max_replies: 2,
+            ..Default::default()
+        };
+        let markdown = format_thread(&MarkdownFormatter, &response.thread, &options);
+        assert!(markdown.contains("@bob/3kq8b001"));
+        assert!(markdown.contains("@erin/3kq8e001"));
+        assert!(!markdown.contains("@carol"));
+        assert!(!markdown.contains("@frank"));
+        assert!(markdown.contains("\n  └─ … 2 more replies not shown\n"));
+        assert!(markdown.contains("\n└─ … 2 more replies not shown\n"));
+
+        let options = ThreadFormatOptions {
+            max_depth: 0,
+            ..Default::default()
+        };
+        let markdown = format_thread(&MarkdownFormatter, &response.thread, &options);
+        assert!(markdown.contains("> by alice\n"));
+        assert!(!markdown.contains("@bob"));
+        assert!(markdown.contains("\n└─ … 6 more replies not shown\n"));
+    }
+
     #[test]
     fn test_format_thread_multiline_content() {
         let thread = ThreadNode::ThreadViewPost {
@@ -498,7 +661,7 @@ mod tests {
             replies: vec![],
         };
 
-        let markdown = format_thread(&thread);
+        let markdown = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
 
         // Each line should be block-quoted
         assert!(markdown.contains("> Line 1\n> Line 2\n> Line 3"));
@@ -515,15 +678,15 @@ mod tests {
             replies: vec![],
         };
 
-        let markdown = format_thread(&thread);
+        let markdown = format_thread(&MarkdownFormatter, &thread, &ThreadFormatOptions::default());
 
         // Markdown syntax should be inside blockquotes
         assert!(markdown.contains("> # This looks like a header\n> ## But it's quoted!"));
     }
 
     // Helper to create mock posts
-    fn create_mock_post(handle: &str, rkey: &str, text: &str) -> ThreadPost {
-        ThreadPost {
+    fn create_mock_post(handle: &str, rkey: &str, text: &str) -> FeedPost {
+        FeedPost {
             uri: format!("at://did:plc:test{}/app.bsky.feed.post/{}", handle, rkey),
             cid: format!("cid{}", rkey),
             author: PostAuthor {
@@ -535,16 +698,18 @@ mod tests {
                 text: text.to_string(),
                 created_at: "2024-10-06T10:15:33.123Z".to_string(),
                 facets: vec![],
+                reply: None,
             },
-            indexed_at: Some("2024-10-06T10:15:34Z".to_string()),
+            indexed_at: "2024-10-06T10:15:34Z".to_string(),
             like_count: Some(33),
             reply_count: Some(1),
             repost_count: Some(0),
             quote_count: Some(1),
+            embed: None,
         }
     }
 
-    fn create_mock_post_multiline(handle: &str, rkey: &str, text: &str) -> ThreadPost {
+    fn create_mock_post_multiline(handle: &str, rkey: &str, text: &str) -> FeedPost {
         create_mock_post(handle, rkey, text)
     }
 }
@@ -0,0 +1,238 @@
+//! Timeline tool implementation
+//!
+//! Implements the `timeline(viewAs, limit, continueAtCursor)` MCP tool for the
+//! authenticated account's home feed
+
+use crate::auth::storage::CredentialStorage;
+use crate::bluesky::provider::configured_provider;
+use crate::auth::session::refresh_buffer;
+use crate::auth::SessionManager;
+use crate::cli::{OutputFormat, TimelineArgs};
+use crate::error::AppError;
+use crate::http::{rate_limit_error, SendXrpc};
+use crate::mcp::{McpResponse, ToolResult};
+use crate::bluesky::feed_view::{FeedResponse, FeedViewPost};
+use crate::tools::feed::{
+    format_feed_with, resolve_reply_parents, resolve_repost_originals, FeedOutput,
+};
+use crate::tools::formatter::{formatter_for, OutputFormatter};
+use anyhow::Result;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::debug;
+
+/// Posts fetched when no limit is given
+const DEFAULT_TIMELINE_LIMIT: usize = 50;
+
+/// Largest page `app.bsky.feed.getTimeline` returns
+const MAX_TIMELINE_PAGE: usize = 100;
+
+/// Handle timeline tool call
+pub async fn handle_timeline(id: Option<Value>, args: Value) -> McpResponse {
+    // Set total timeout to 120 seconds
+    match timeout(Duration::from_secs(120), handle_timeline_impl(args)).await {
+        Ok(result) => match result {
+            Ok(content) => McpResponse::success(id, serde_json::to_value(content).unwrap()),
+            Err(e) => McpResponse::error(id, e.error_code(), &e.message()),
+        },
+        Err(_) => McpResponse::error(id, "timeout", "Timeline request exceeded 120 second timeout"),
+    }
+}
+
+async fn handle_timeline_impl(args: Value) -> Result<ToolResult, AppError> {
+    // Parse arguments
+    let timeline_args: TimelineArgs = serde_json::from_value(args)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+    // Execute using shared implementation
+    execute_timeline(timeline_args, OutputFormat::Markdown).await
+}
+
+/// Execute timeline tool (shared implementation for MCP and CLI), rendering as `format`
+pub async fn execute_timeline(
+    timeline_args: TimelineArgs,
+    format: OutputFormat,
+) -> Result<ToolResult, AppError> {
+    debug!("Timeline request for account: {}", timeline_args.viewAs);
+
+    if timeline_args.viewAs.trim().is_empty() {
+        return Err(AppError::InvalidInput("viewAs cannot be empty".to_string()));
+    }
+
+    // Get credentials for the account
+    let storage = CredentialStorage::new()?;
+
+    // Stored session, refreshed ahead of expiry, or a new app password session
+    let session = SessionManager::new()?
+        .session_for(&storage, &timeline_args.viewAs, refresh_buffer())
+        .await?;
+
+    debug!("Authenticated as {} (DID: {})", session.handle, session.did);
+
+    let client = crate::http::client_with_timeout(std::time::Duration::from_secs(120));
+
+    // Fetch in batches if needed
+    let requested_limit = timeline_args.limit.unwrap_or(DEFAULT_TIMELINE_LIMIT);
+    let mut all_posts: Vec<FeedViewPost> = Vec::new();
+    let mut cursor = timeline_args.continueAtCursor.clone();
+
+    while all_posts.len() < requested_limit {
+        let batch_size = std::cmp::min(requested_limit - all_posts.len(), MAX_TIMELINE_PAGE);
+        let url = timeline_url(&session.service, batch_size, cursor.as_deref());
+
+        debug!("Fetching batch of {} posts from timeline", batch_size);
+
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", session.access_jwt))
+            .send_xrpc()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to fetch timeline: {}", e)))?;
+
+        if let Some(e) = rate_limit_error(&response) {
+            return Err(e);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::NetworkError(format!(
+                "Timeline API returned error {}: {}",
+                status, error_text
+            )));
+        }
+
+        let page: FeedResponse = response.json().await.map_err(|e| {
+            AppError::ParseError(format!("Failed to parse timeline response: {}", e))
+        })?;
+
+        let batch_count = page.feed.len();
+        debug!("Received {} posts in this batch", batch_count);
+
+        all_posts.extend(page.feed);
+        cursor = page.cursor;
+
+        // A short or empty page, or a missing cursor, means the end of the timeline
+        if batch_count < batch_size || cursor.is_none() {
+            break;
+        }
+    }
+
+    debug!("Total timeline posts fetched: {}", all_posts.len());
+
+    let provider = configured_provider()?;
+    resolve_repost_originals(&provider, &mut all_posts).await;
+    resolve_reply_parents(&provider, &mut all_posts).await;
+
+    Ok(timeline_result(formatter_for(format), all_posts, cursor))
+}
+
+/// `getTimeline` URL for one page, continuing from `cursor` when given
+fn timeline_url(service: &str, limit: usize, cursor: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/xrpc/app.bsky.feed.getTimeline?limit={}",
+        service, limit
+    );
+    if let Some(cursor) = cursor {
+        url.push_str(&format!("&cursor={}", urlencoding::encode(cursor)));
+    }
+    url
+}
+
+/// Render the timeline through `formatter`, carrying the next cursor in `_meta`
+fn timeline_result(
+    formatter: &dyn OutputFormatter,
+    entries: Vec<FeedViewPost>,
+    cursor: Option<String>,
+) -> ToolResult {
+    let mut text = format_feed_with(formatter, "Timeline", &entries, cursor.as_deref(), false);
+    if entries.is_empty() {
+        text.push_str("No posts in your timeline.\n");
+    }
+
+    let meta = cursor.as_ref().map(|cursor| serde_json::json!({ "cursor": cursor }));
+    let result = ToolResult::text(text).with_data(FeedOutput { posts: entries, cursor });
+    match meta {
+        Some(meta) => result.with_meta(meta),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_timeline_args_parsing() {
+        let args = json!({
+            "viewAs": "me.bsky.social",
+            "limit": 25,
+            "continueAtCursor": "1700000000000::bafy"
+        });
+
+        let parsed: TimelineArgs = serde_json::from_value(args).unwrap();
+        assert_eq!(parsed.viewAs, "me.bsky.social");
+        assert_eq!(parsed.limit, Some(25));
+        assert_eq!(parsed.continueAtCursor.as_deref(), Some("1700000000000::bafy"));
+    }
+
+    #[test]
+    fn test_timeline_url_passes_cursor_through() {
+        assert_eq!(
+            timeline_url("https://pds.example", 50, None),
+            "https://pds.example/xrpc/app.bsky.feed.getTimeline?limit=50"
+        );
+        assert_eq!(
+            timeline_url("https://pds.example", 10, Some("1700000000000::bafy+x")),
+            "https://pds.example/xrpc/app.bsky.feed.getTimeline?limit=10&cursor=1700000000000%3A%3Abafy%2Bx"
+        );
+    }
+
+    #[test]
+    fn test_timeline_result_carries_next_cursor_in_meta() {
+        let page: FeedResponse = serde_json::from_value(json!({
+            "feed": [{
+                "post": {
+                    "uri": "at://did:plc:alice/app.bsky.feed.post/3kpost",
+                    "cid": "bafypost",
+                    "author": { "did": "did:plc:alice", "handle": "alice.bsky.social" },
+                    "record": { "text": "Hello timeline", "createdAt": "2025-01-01T00:00:00.000Z" },
+                    "indexedAt": "2025-01-01T00:00:01.000Z"
+                }
+            }],
+            "cursor": "next-page"
+        }))
+        .unwrap();
+
+        let markdown = formatter_for(OutputFormat::Markdown);
+        let result = timeline_result(markdown, page.feed, page.cursor);
+        let value = serde_json::to_value(&result).unwrap();
+        assert_eq!(value["_meta"], json!({ "cursor": "next-page" }));
+
+        let text = &result.content[0].text;
+        assert!(text.starts_with("# Timeline · 1 posts\n\n"), "{}", text);
+        assert!(text.contains("@alice.bsky.social/3kpost\n> Hello timeline\n"), "{}", text);
+        assert!(text.ends_with("**Next cursor:** `next-page`\n"), "{}", text);
+
+        let data = result.data_json().unwrap();
+        assert_eq!(data["cursor"], "next-page");
+        assert_eq!(data["posts"][0]["post"]["record"]["text"], "Hello timeline");
+        assert_eq!(data["posts"][0]["post"]["author"]["handle"], "alice.bsky.social");
+    }
+
+    #[test]
+    fn test_timeline_result_empty_feed() {
+        let page: FeedResponse = serde_json::from_value(json!({ "feed": [] })).unwrap();
+        assert!(page.cursor.is_none());
+
+        let markdown = formatter_for(OutputFormat::Markdown);
+        let result = timeline_result(markdown, page.feed, page.cursor);
+        let value = serde_json::to_value(&result).unwrap();
+        assert!(value.get("_meta").is_none());
+        assert_eq!(
+            result.content[0].text,
+            "# Timeline · 0 posts\n\nNo posts in your timeline.\n"
+        );
+    }
+}
@@ -8,13 +8,14 @@ mod tools_argument_parsing_tests {
     fn test_post_args_basic_creation() {
         // Test basic PostArgs creation
         let args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: "Hello world".to_string(),
             replyTo: None,
+            ..Default::default()
         };
 
         assert_eq!(args.text, "Hello world");
-        assert_eq!(args.postAs, "test.bsky.social");
+        assert_eq!(args.postAs.as_deref(), Some("test.bsky.social"));
         assert!(args.replyTo.is_none());
     }
 
@@ -22,9 +23,10 @@ mod tools_argument_parsing_tests {
     fn test_post_args_with_reply() {
         // Test PostArgs with reply-to reference
         let args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: "Great post!".to_string(),
             replyTo: Some("at://did:plc:test/app.bsky.feed.post/123".to_string()),
+            ..Default::default()
         };
 
         assert_eq!(args.text, "Great post!");
@@ -39,14 +41,15 @@ mod tools_argument_parsing_tests {
     fn test_react_args_like_action() {
         // Test ReactArgs for like action
         let args = ReactArgs {
-            reactAs: "test.bsky.social".to_string(),
+            reactAs: Some("test.bsky.social".to_string()),
             like: vec!["at://did:plc:test/app.bsky.feed.post/456".to_string()],
             unlike: vec![],
             repost: vec![],
+            unrepost: vec![],
             delete: vec![],
         };
 
-        assert_eq!(args.reactAs, "test.bsky.social");
+        assert_eq!(args.reactAs.as_deref(), Some("test.bsky.social"));
         assert_eq!(args.like.len(), 1);
     }
 
@@ -54,10 +57,11 @@ mod tools_argument_parsing_tests {
     fn test_react_args_repost_action() {
         // Test ReactArgs for repost action
         let args = ReactArgs {
-            reactAs: "test.bsky.social".to_string(),
+            reactAs: Some("test.bsky.social".to_string()),
             like: vec![],
             unlike: vec![],
             repost: vec!["at://did:plc:test/app.bsky.feed.post/789".to_string()],
+            unrepost: vec![],
             delete: vec![],
         };
 
@@ -68,10 +72,11 @@ mod tools_argument_parsing_tests {
     fn test_react_args_multiple_operations() {
         // Test ReactArgs with multiple operations
         let args = ReactArgs {
-            reactAs: "test.bsky.social".to_string(),
+            reactAs: Some("test.bsky.social".to_string()),
             like: vec!["at://did:plc:test/app.bsky.feed.post/1".to_string()],
             unlike: vec!["at://did:plc:test/app.bsky.feed.post/2".to_string()],
             repost: vec![],
+            unrepost: vec![],
             delete: vec![],
         };
 
@@ -87,6 +92,7 @@ mod tools_argument_parsing_tests {
             limit: Some(50),
             viewAs: None,
             continueAtCursor: None,
+            ..Default::default()
         };
 
         assert_eq!(args.feed, Some("at://did:plc:test/app.bsky.feed.generator/following".to_string()));
@@ -102,6 +108,7 @@ mod tools_argument_parsing_tests {
             limit: Some(25),
             viewAs: None,
             continueAtCursor: Some("page_2_token_xyz".to_string()),
+            ..Default::default()
         };
 
         assert_eq!(args.continueAtCursor, Some("page_2_token_xyz".to_string()));
@@ -112,9 +119,10 @@ mod tools_argument_parsing_tests {
         // Test that post URI references are properly structured
         let reply_uri = "at://did:plc:test/app.bsky.feed.post/abc123";
         let args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: "Reply text".to_string(),
             replyTo: Some(reply_uri.to_string()),
+            ..Default::default()
         };
 
         // URI should follow AT protocol format
@@ -127,10 +135,11 @@ mod tools_argument_parsing_tests {
         // Test that react URI references are properly structured
         let post_uri = "at://did:plc:test/app.bsky.feed.post/def456";
         let args = ReactArgs {
-            reactAs: "test.bsky.social".to_string(),
+            reactAs: Some("test.bsky.social".to_string()),
             like: vec![post_uri.to_string()],
             unlike: vec![],
             repost: vec![],
+            unrepost: vec![],
             delete: vec![],
         };
 
@@ -148,6 +157,7 @@ mod tools_argument_parsing_tests {
             limit: None,
             viewAs: None,
             continueAtCursor: None,
+            ..Default::default()
         };
 
         // Feed URI should reference a generator
@@ -162,23 +172,26 @@ mod tools_argument_parsing_tests {
         let long_text = "a".repeat(300); // Simulate long post
 
         let short_args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: short_text.to_string(),
             replyTo: None,
+            ..Default::default()
         };
         assert_eq!(short_args.text.len(), short_text.len());
 
         let medium_args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: medium_text.to_string(),
             replyTo: None,
+            ..Default::default()
         };
         assert_eq!(medium_args.text.len(), medium_text.len());
 
         let long_args = PostArgs {
-            postAs: "test.bsky.social".to_string(),
+            postAs: Some("test.bsky.social".to_string()),
             text: long_text.to_string(),
             replyTo: None,
+            ..Default::default()
         };
         assert_eq!(long_args.text.len(), 300);
     }
@@ -194,6 +207,7 @@ mod tools_argument_parsing_tests {
                 limit: Some(limit),
                 viewAs: None,
                 continueAtCursor: None,
+                ..Default::default()
             };
             assert_eq!(args.limit, Some(limit));
         }
@@ -203,10 +217,11 @@ mod tools_argument_parsing_tests {
     fn test_react_args_all_operations_empty() {
         // Test ReactArgs with all operations as empty (should be invalid state, but structure allows it)
         let args = ReactArgs {
-            reactAs: "test.bsky.social".to_string(),
+            reactAs: Some("test.bsky.social".to_string()),
             like: vec![],
             unlike: vec![],
             repost: vec![],
+            unrepost: vec![],
             delete: vec![],
         };
 
@@ -233,7 +248,7 @@ mod tools_formatting_tests {
     fn test_apply_facets_empty_facets() {
         // Test applying empty facets list
         let text = "Hello world".to_string();
-        let result = apply_facets_to_text(&text, &vec![]);
+        let result = apply_facets_to_text(&text, &[]);
 
         assert_eq!(result, "Hello world");
     }
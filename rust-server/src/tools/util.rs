@@ -1,25 +1,16 @@
 //! Utility functions for tools
 
+use crate::bluesky::uri::AtUri;
+
 /// Convert AT URI to BlueSky web URL
 /// at://did:plc:abc/app.bsky.feed.post/xyz -> https://bsky.app/profile/handle/post/xyz
 /// Falls back to using DID in the URL if handle is empty
 #[allow(dead_code)]
 pub fn at_uri_to_bsky_url(at_uri: &str, handle: &str) -> String {
     // Parse AT URI: at://{did}/{collection}/{rkey}
-    if !at_uri.starts_with("at://") {
-        return at_uri.to_string();
-    }
-
-    let parts: Vec<&str> = at_uri.trim_start_matches("at://").split('/').collect();
-    if parts.len() < 3 {
+    let Ok(AtUri { did, rkey, .. }) = AtUri::parse(at_uri) else {
         return at_uri.to_string();
-    }
-
-    // parts[0] = DID
-    // parts[1] = collection (e.g., app.bsky.feed.post)
-    // parts[2] = rkey
-    let did = parts[0];
-    let rkey = parts[2];
+    };
 
     // Use handle if available, otherwise use DID as fallback
     let profile = if handle.is_empty() { did } else { handle };